@@ -0,0 +1,295 @@
+//! Local IPC between the GUI and pulito-agent.
+//!
+//! Newline-delimited JSON over a unix socket in $XDG_RUNTIME_DIR. Each request line
+//! gets one response line, except `subscribe`, which turns the connection into an
+//! event stream until the client disconnects.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+use tokio::time::{timeout, Duration};
+
+use crate::metrics;
+use crate::watchdog::{self, BackendHealth};
+
+const SOCKET_NAME: &str = "pulito-agent.sock";
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcRequest {
+    Status,
+    SampleNow,
+    Subscribe,
+    Ping,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AgentLiveState {
+    pub pid: u32,
+    pub started_at: i64,
+    pub last_sample_at: Option<i64>,
+    pub samples_taken: u32,
+    pub cache_events_seen: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AgentEvent {
+    DiskSample { used_bytes: u64, total_bytes: u64, timestamp: i64 },
+    CacheGrowth { path: String, size: u64, source: String, timestamp: i64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcResponse {
+    Status(AgentLiveState),
+    Sampled { timestamp: i64 },
+    Event(AgentEvent),
+    Pong(BackendHealth),
+    Error { message: String },
+}
+
+lazy_static::lazy_static! {
+    static ref EVENTS: broadcast::Sender<AgentEvent> = broadcast::channel(EVENT_CHANNEL_CAPACITY).0;
+    static ref LIVE_STATE: Mutex<AgentLiveState> = Mutex::new(AgentLiveState::default());
+}
+
+/// Set while the GUI is forwarding agent events, so subscribing twice is a no-op
+static GUI_SUBSCRIBED: AtomicBool = AtomicBool::new(false);
+
+pub fn socket_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join(SOCKET_NAME),
+        None => std::env::temp_dir().join(format!("pulito-agent-{}.sock", unsafe { libc::getuid() })),
+    }
+}
+
+/// Record an event in the live state and forward it to subscribers.
+/// Cheap no-op bookkeeping when called from the GUI process (nobody is subscribed).
+pub fn publish(event: AgentEvent) {
+    if let Ok(mut state) = LIVE_STATE.lock() {
+        match &event {
+            AgentEvent::DiskSample { timestamp, .. } => {
+                state.last_sample_at = Some(*timestamp);
+                state.samples_taken += 1;
+            }
+            AgentEvent::CacheGrowth { .. } => state.cache_events_seen += 1,
+        }
+    }
+    // Err only means there are no subscribers right now
+    let _ = EVENTS.send(event);
+}
+
+fn live_state() -> AgentLiveState {
+    LIVE_STATE.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+async fn write_message<T: Serialize>(stream: &mut (impl AsyncWriteExt + Unpin), message: &T) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(std::io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).await
+}
+
+async fn handle_connection(app_handle: tauri::AppHandle, stream: UnixStream) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let request: IpcRequest = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                write_message(&mut writer, &IpcResponse::Error { message: format!("Invalid request: {}", e) }).await?;
+                continue;
+            }
+        };
+
+        match request {
+            IpcRequest::Status => {
+                write_message(&mut writer, &IpcResponse::Status(live_state())).await?;
+            }
+            IpcRequest::SampleNow => {
+                let response = match metrics::timed("record_disk_usage", crate::commands::record_disk_usage(&app_handle)).await {
+                    Ok(()) => IpcResponse::Sampled { timestamp: chrono::Utc::now().timestamp() },
+                    Err(message) => IpcResponse::Error { message },
+                };
+                write_message(&mut writer, &response).await?;
+            }
+            IpcRequest::Ping => {
+                write_message(&mut writer, &IpcResponse::Pong(watchdog::status())).await?;
+            }
+            IpcRequest::Subscribe => {
+                let mut events = EVENTS.subscribe();
+                loop {
+                    match events.recv().await {
+                        Ok(event) => write_message(&mut writer, &IpcResponse::Event(event)).await?,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::debug!("IPC subscriber lagged, skipped {} events", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Agent side: listen on the socket and serve GUI requests
+pub async fn serve(app_handle: tauri::AppHandle) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = socket_path();
+    // A previous agent that crashed leaves its socket file behind
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to remove stale socket {}: {}", path.display(), e))?;
+    }
+
+    let listener = UnixListener::bind(&path).map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict socket permissions: {}", e))?;
+
+    if let Ok(mut state) = LIVE_STATE.lock() {
+        state.pid = std::process::id();
+        state.started_at = chrono::Utc::now().timestamp();
+    }
+
+    tracing::info!("Agent IPC listening on {}", path.display());
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let handle = app_handle.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(handle, stream).await {
+                        tracing::debug!("IPC connection closed: {}", e);
+                    }
+                });
+            }
+            Err(e) => tracing::warn!("Failed to accept IPC connection: {}", e),
+        }
+    }
+}
+
+/// GUI side: send one request and read its response
+async fn request(request: &IpcRequest) -> Result<IpcResponse, String> {
+    let exchange = async {
+        let stream = UnixStream::connect(socket_path())
+            .await
+            .map_err(|e| format!("pulito-agent is not reachable: {}", e))?;
+        let (reader, mut writer) = stream.into_split();
+
+        write_message(&mut writer, request).await.map_err(|e| format!("Failed to send request: {}", e))?;
+
+        let response = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await
+            .map_err(|e| format!("Failed to read response: {}", e))?
+            .ok_or("Agent closed the connection")?;
+        serde_json::from_str(&response).map_err(|e| format!("Invalid response from agent: {}", e))
+    };
+
+    timeout(REQUEST_TIMEOUT, exchange)
+        .await
+        .map_err(|_| "Timed out waiting for pulito-agent".to_string())?
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_agent_live_state() -> Result<AgentLiveState, String> {
+    match request(&IpcRequest::Status).await? {
+        IpcResponse::Status(state) => Ok(state),
+        IpcResponse::Error { message } => Err(message),
+        other => Err(format!("Unexpected response from agent: {:?}", other)),
+    }
+}
+
+/// Ask the agent to take a disk sample right away
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn trigger_agent_sample() -> Result<i64, String> {
+    match request(&IpcRequest::SampleNow).await? {
+        IpcResponse::Sampled { timestamp } => Ok(timestamp),
+        IpcResponse::Error { message } => Err(message),
+        other => Err(format!("Unexpected response from agent: {:?}", other)),
+    }
+}
+
+/// Whether the agent answers, and whether its watchdog sees its database or a scan stuck
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn ping_agent() -> Result<BackendHealth, String> {
+    match request(&IpcRequest::Ping).await? {
+        IpcResponse::Pong(health) => Ok(health),
+        IpcResponse::Error { message } => Err(message),
+        other => Err(format!("Unexpected response from agent: {:?}", other)),
+    }
+}
+
+/// Forward the agent's event stream to the frontend as "agent-event" events
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn subscribe_agent_events(app_handle: tauri::AppHandle) -> Result<(), String> {
+    if GUI_SUBSCRIBED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let stream = match UnixStream::connect(socket_path()).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            GUI_SUBSCRIBED.store(false, Ordering::SeqCst);
+            return Err(format!("pulito-agent is not reachable: {}", e));
+        }
+    };
+
+    tokio::spawn(async move {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        if write_message(&mut writer, &IpcRequest::Subscribe).await.is_ok() {
+            while let Ok(Some(line)) = lines.next_line().await {
+                match serde_json::from_str::<IpcResponse>(&line) {
+                    Ok(IpcResponse::Event(event)) => {
+                        if let Err(e) = crate::detached::emit(&app_handle, "agent-event", &event) {
+                            tracing::warn!("Failed to emit agent event: {}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::debug!("Ignoring malformed agent message: {}", e),
+                }
+            }
+        }
+
+        tracing::info!("Agent event stream ended");
+        GUI_SUBSCRIBED.store(false, Ordering::SeqCst);
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_wire_format() {
+        assert_eq!(serde_json::to_string(&IpcRequest::SampleNow).unwrap(), r#"{"type":"sample_now"}"#);
+        assert!(matches!(serde_json::from_str::<IpcRequest>(r#"{"type":"status"}"#).unwrap(), IpcRequest::Status));
+        assert!(matches!(serde_json::from_str::<IpcRequest>(r#"{"type":"ping"}"#).unwrap(), IpcRequest::Ping));
+
+        let event = IpcResponse::Event(AgentEvent::DiskSample { used_bytes: 1, total_bytes: 2, timestamp: 3 });
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"type":"event","kind":"disk_sample","used_bytes":1,"total_bytes":2,"timestamp":3}"#);
+        assert!(matches!(serde_json::from_str::<IpcResponse>(&json).unwrap(), IpcResponse::Event(_)));
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::db::DbAccess;
+use crate::read_only;
+
+pub mod ipc;
+
+/// Command-line flag that starts Pulito headless as the monitoring agent
+pub const AGENT_FLAG: &str = "--agent";
+pub const SERVICE_NAME: &str = "pulito-agent.service";
+
+/// How often the agent refreshes its heartbeat in monitoring_state
+const HEARTBEAT_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AgentStatus {
+    pub installed: bool,
+    pub enabled: bool,
+    pub active: bool,
+    pub unit_path: String,
+    pub last_heartbeat: Option<i64>,
+}
+
+pub fn is_agent_mode() -> bool {
+    std::env::args().any(|arg| arg == AGENT_FLAG)
+}
+
+fn unit_path() -> Result<PathBuf, String> {
+    let config_dir = dirs::config_dir().ok_or("Cannot determine config directory")?;
+    Ok(config_dir.join("systemd/user").join(SERVICE_NAME))
+}
+
+/// Generate the systemd user unit running this executable in agent mode.
+/// It is bound to the graphical session because the agent still initializes the
+/// Tauri runtime (without creating any window).
+pub fn unit_file_contents(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Pulito background monitoring agent\n\
+         PartOf=graphical-session.target\n\
+         After=graphical-session.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         ExecStart=\"{}\" {}\n\
+         Restart=on-failure\n\
+         RestartSec=30\n\
+         Nice=10\n\
+         IOSchedulingClass=idle\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n",
+        exe.display(),
+        AGENT_FLAG
+    )
+}
+
+fn systemctl_user(args: &[&str]) -> Result<std::process::Output, String> {
+    Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute systemctl: {}", e))
+}
+
+fn systemctl_user_checked(args: &[&str]) -> Result<(), String> {
+    let output = systemctl_user(args)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Whether the agent service is currently running. The GUI uses this to avoid
+/// sampling and watching twice while the agent already does it.
+pub fn is_agent_active() -> bool {
+    systemctl_user(&["is-active", SERVICE_NAME])
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "active")
+        .unwrap_or(false)
+}
+
+/// Restart the running agent so it reloads settings from the database
+pub fn restart_agent() -> Result<(), String> {
+    systemctl_user_checked(&["restart", SERVICE_NAME])
+}
+
+/// Entry point for `--agent`: start disk sampling, cache watching and the scheduler,
+/// then keep a heartbeat in the shared database for the GUI to read
+pub async fn run(app_handle: tauri::AppHandle) {
+    tracing::info!("Starting Pulito agent (headless)");
+
+    let ipc_handle = app_handle.clone();
+    tokio::spawn(async move {
+        if let Err(e) = ipc::serve(ipc_handle).await {
+            tracing::error!("Agent IPC server failed: {}", e);
+        }
+    });
+
+    if let Err(e) = crate::commands::start_diskpulse_monitoring(app_handle.clone()).await {
+        tracing::error!("Agent failed to start DiskPulse monitoring: {}", e);
+    }
+    if let Err(e) = crate::commands::resume_scheduler(app_handle.clone()).await {
+        tracing::error!("Agent failed to start scheduler: {}", e);
+    }
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let now = chrono::Utc::now().timestamp();
+        if let Err(e) = app_handle.db(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO monitoring_state (key, value, updated_at) VALUES ('agent_heartbeat', ?1, ?1)",
+                [now],
+            )?;
+            Ok(())
+        }) {
+            tracing::warn!("Failed to write agent heartbeat: {}", e);
+        }
+    }
+}
+
+fn read_status(app_handle: &tauri::AppHandle) -> Result<AgentStatus, String> {
+    let path = unit_path()?;
+    let enabled = systemctl_user(&["is-enabled", SERVICE_NAME])
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "enabled")
+        .unwrap_or(false);
+
+    let last_heartbeat = app_handle.db(|conn| {
+        use rusqlite::OptionalExtension;
+        conn.query_row(
+            "SELECT updated_at FROM monitoring_state WHERE key = 'agent_heartbeat'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ).optional()
+    }).unwrap_or(None);
+
+    Ok(AgentStatus {
+        installed: path.exists(),
+        enabled,
+        active: is_agent_active(),
+        unit_path: path.to_string_lossy().to_string(),
+        last_heartbeat,
+    })
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_agent_status(app_handle: tauri::AppHandle) -> Result<AgentStatus, String> {
+    read_status(&app_handle)
+}
+
+/// Write the unit file, reload systemd and enable + start the agent
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn install_agent(app_handle: tauri::AppHandle) -> Result<AgentStatus, String> {
+    read_only::check("Installing the background agent")?;
+    let exe = std::env::current_exe().map_err(|e| format!("Cannot determine executable path: {}", e))?;
+    // AppImages run from a temporary mount; the stable path is in $APPIMAGE
+    let exe = std::env::var_os("APPIMAGE").map(PathBuf::from).unwrap_or(exe);
+
+    let path = unit_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, unit_file_contents(&exe))
+        .map_err(|e| format!("Failed to write unit file: {}", e))?;
+    tracing::info!("Installed agent unit at {}", path.display());
+
+    // The GUI's own monitors would duplicate the agent's samples
+    crate::commands::stop_diskpulse_monitoring(app_handle.clone()).await?;
+
+    systemctl_user_checked(&["daemon-reload"])?;
+    systemctl_user_checked(&["enable", "--now", SERVICE_NAME])?;
+
+    read_status(&app_handle)
+}
+
+/// Stop, disable and remove the agent unit
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn uninstall_agent(app_handle: tauri::AppHandle) -> Result<AgentStatus, String> {
+    read_only::check("Removing the background agent")?;
+    let path = unit_path()?;
+
+    if path.exists() {
+        systemctl_user_checked(&["disable", "--now", SERVICE_NAME])?;
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to remove unit file: {}", e))?;
+        systemctl_user_checked(&["daemon-reload"])?;
+        tracing::info!("Removed agent unit {}", path.display());
+    }
+
+    read_status(&app_handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_file_contents() {
+        let unit = unit_file_contents(Path::new("/opt/Pulito/pulito"));
+        assert!(unit.contains("ExecStart=\"/opt/Pulito/pulito\" --agent\n"));
+        assert!(unit.contains("WantedBy=graphical-session.target"));
+        assert!(unit.contains("[Service]"));
+    }
+}
@@ -0,0 +1,276 @@
+//! Read-only analyses of a user-chosen directory.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::time::{timeout, Duration};
+use walkdir::WalkDir;
+
+use crate::commands;
+
+const ANALYSIS_TIMEOUT: Duration = Duration::from_secs(120);
+
+const DAY_SECS: u64 = 24 * 3600;
+
+/// Last-modified age buckets, youngest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum AgeBucket {
+    UpTo30Days,
+    UpTo90Days,
+    UpTo1Year,
+    OverYear,
+}
+
+impl AgeBucket {
+    pub const ALL: [AgeBucket; 4] = [AgeBucket::UpTo30Days, AgeBucket::UpTo90Days, AgeBucket::UpTo1Year, AgeBucket::OverYear];
+
+    pub fn for_age_days(days: u64) -> AgeBucket {
+        match days {
+            0..=29 => AgeBucket::UpTo30Days,
+            30..=89 => AgeBucket::UpTo90Days,
+            90..=364 => AgeBucket::UpTo1Year,
+            _ => AgeBucket::OverYear,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AgeBucket::UpTo30Days => "0-30 days",
+            AgeBucket::UpTo90Days => "30-90 days",
+            AgeBucket::UpTo1Year => "90-365 days",
+            AgeBucket::OverYear => "Over 1 year",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AgeCell {
+    pub bucket: AgeBucket,
+    pub label: String,
+    pub file_count: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AgeHeatmapRow {
+    pub path: String,
+    pub name: String,
+    pub is_loose_files: bool, // files directly in the root rather than a subdirectory
+    pub file_count: u64,
+    pub total_size: u64,
+    pub cells: Vec<AgeCell>, // one per AgeBucket, in AgeBucket::ALL order
+    pub stale_ratio: f64,    // share of bytes not modified for over a year
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AgeHeatmap {
+    pub root: String,
+    pub rows: Vec<AgeHeatmapRow>, // largest first
+    pub totals: Vec<AgeCell>,
+    pub total_size: u64,
+    pub file_count: u64,
+    pub unreadable_entries: u64,
+    pub generated_at: i64,
+}
+
+#[derive(Default)]
+struct Counts {
+    files: [u64; 4],
+    bytes: [u64; 4],
+}
+
+impl Counts {
+    fn add(&mut self, bucket: AgeBucket, size: u64) {
+        self.files[bucket.index()] += 1;
+        self.bytes[bucket.index()] += size;
+    }
+
+    fn cells(&self) -> Vec<AgeCell> {
+        AgeBucket::ALL
+            .iter()
+            .map(|b| AgeCell {
+                bucket: *b,
+                label: b.label().to_string(),
+                file_count: self.files[b.index()],
+                size: self.bytes[b.index()],
+            })
+            .collect()
+    }
+
+    fn total_size(&self) -> u64 {
+        self.bytes.iter().sum()
+    }
+
+    fn file_count(&self) -> u64 {
+        self.files.iter().sum()
+    }
+}
+
+/// Bucket every file below `root` by last-modified age, grouped by the
+/// top-level entry of `root` it lives in. Symlinks are not followed, nor
+/// other filesystems mounted below `root` with `one_file_system`.
+pub fn build_age_heatmap(root: &Path, now: SystemTime, one_file_system: bool) -> AgeHeatmap {
+    let mut groups: BTreeMap<PathBuf, Counts> = BTreeMap::new();
+    let mut loose = Counts::default();
+    let mut totals = Counts::default();
+    let mut unreadable_entries = 0;
+
+    for entry in WalkDir::new(root).min_depth(1).follow_links(false).same_file_system(one_file_system) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => {
+                unreadable_entries += 1;
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            unreadable_entries += 1;
+            continue;
+        };
+
+        let age_days = metadata
+            .modified()
+            .ok()
+            .and_then(|m| now.duration_since(m).ok())
+            .map(|d| d.as_secs() / DAY_SECS)
+            .unwrap_or(0); // future mtimes count as fresh
+        let bucket = AgeBucket::for_age_days(age_days);
+        let size = metadata.len();
+        totals.add(bucket, size);
+
+        if entry.depth() == 1 {
+            loose.add(bucket, size);
+        } else if let Some(top) = entry.path().strip_prefix(root).ok().and_then(|rel| rel.components().next()) {
+            groups.entry(root.join(top)).or_default().add(bucket, size);
+        }
+    }
+
+    let row = |path: &Path, name: String, is_loose_files: bool, counts: &Counts| {
+        let total_size = counts.total_size();
+        AgeHeatmapRow {
+            path: path.to_string_lossy().to_string(),
+            name,
+            is_loose_files,
+            file_count: counts.file_count(),
+            total_size,
+            cells: counts.cells(),
+            stale_ratio: if total_size > 0 {
+                counts.bytes[AgeBucket::OverYear.index()] as f64 / total_size as f64
+            } else {
+                0.0
+            },
+        }
+    };
+
+    let mut rows: Vec<AgeHeatmapRow> = groups
+        .iter()
+        .map(|(path, counts)| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            row(path, name, false, counts)
+        })
+        .collect();
+    if loose.file_count() > 0 {
+        rows.push(row(root, "(files in this folder)".to_string(), true, &loose));
+    }
+    rows.sort_by(|a, b| b.total_size.cmp(&a.total_size));
+
+    AgeHeatmap {
+        root: root.to_string_lossy().to_string(),
+        rows,
+        totals: totals.cells(),
+        total_size: totals.total_size(),
+        file_count: totals.file_count(),
+        unreadable_entries,
+        generated_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+fn resolve_dir(path: &str) -> Result<PathBuf, String> {
+    let path = if path == "~" {
+        dirs::home_dir().ok_or("Cannot determine home directory")?
+    } else {
+        PathBuf::from(path)
+    };
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Cannot access {}: {}", path.display(), e))?;
+    if !canonical.is_dir() {
+        return Err(format!("Not a directory: {}", canonical.display()));
+    }
+    Ok(canonical)
+}
+
+/// Age heatmap for the subdirectories of `root_path`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_age_heatmap(app_handle: tauri::AppHandle, root_path: String) -> Result<AgeHeatmap, String> {
+    let root = resolve_dir(&root_path)?;
+    let one_file_system = commands::load_settings(&app_handle).scan.one_file_system;
+    let task = tokio::task::spawn_blocking(move || build_age_heatmap(&root, SystemTime::now(), one_file_system));
+
+    match timeout(ANALYSIS_TIMEOUT, task).await {
+        Ok(Ok(heatmap)) => Ok(heatmap),
+        Ok(Err(e)) => Err(format!("Analysis task failed: {}", e)),
+        Err(_) => Err(format!("Age analysis timed out after {} seconds", ANALYSIS_TIMEOUT.as_secs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+
+    fn write_aged(path: &Path, size: usize, age_days: u64, now: SystemTime) {
+        fs::write(path, vec![0u8; size]).unwrap();
+        let mtime = now - std::time::Duration::from_secs(age_days * DAY_SECS);
+        File::options().write(true).open(path).unwrap().set_modified(mtime).unwrap();
+    }
+
+    #[test]
+    fn test_bucket_boundaries() {
+        assert_eq!(AgeBucket::for_age_days(0), AgeBucket::UpTo30Days);
+        assert_eq!(AgeBucket::for_age_days(30), AgeBucket::UpTo90Days);
+        assert_eq!(AgeBucket::for_age_days(90), AgeBucket::UpTo1Year);
+        assert_eq!(AgeBucket::for_age_days(365), AgeBucket::OverYear);
+    }
+
+    #[test]
+    fn test_heatmap_groups_by_top_level_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = SystemTime::now();
+        fs::create_dir_all(dir.path().join("old/nested")).unwrap();
+        fs::create_dir(dir.path().join("fresh")).unwrap();
+        write_aged(&dir.path().join("old/nested/a.bin"), 300, 800, now);
+        write_aged(&dir.path().join("old/b.bin"), 100, 10, now);
+        write_aged(&dir.path().join("fresh/c.bin"), 50, 1, now);
+        write_aged(&dir.path().join("loose.txt"), 10, 45, now);
+
+        let heatmap = build_age_heatmap(dir.path(), now, true);
+        assert_eq!(heatmap.file_count, 4);
+        assert_eq!(heatmap.total_size, 460);
+        assert_eq!(heatmap.rows.len(), 3);
+
+        let old = &heatmap.rows[0];
+        assert_eq!(old.name, "old");
+        assert_eq!(old.cells[AgeBucket::OverYear.index()].size, 300);
+        assert_eq!(old.cells[AgeBucket::UpTo30Days.index()].file_count, 1);
+        assert!((old.stale_ratio - 0.75).abs() < 1e-9);
+
+        let loose = heatmap.rows.iter().find(|r| r.is_loose_files).unwrap();
+        assert_eq!(loose.cells[AgeBucket::UpTo90Days.index()].size, 10);
+    }
+}
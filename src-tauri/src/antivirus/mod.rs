@@ -0,0 +1,106 @@
+//! Optional ClamAV pass over files before they are cleaned.
+//!
+//! Used for old downloads when `scan.antivirus_scan_downloads` is enabled, so
+//! users learn whether the junk they're removing was also malicious. Detection
+//! never blocks the cleanup; results are surfaced separately.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::process::{Command, Stdio};
+
+use crate::snapshots::tool_available;
+
+/// Paths passed to one clamscan invocation
+const CLAMSCAN_BATCH: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct MalwareDetection {
+    pub path: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AntivirusReport {
+    pub available: bool, // clamscan is installed
+    pub scanned: usize,
+    pub detections: Vec<MalwareDetection>,
+    pub errors: Vec<String>,
+}
+
+/// Parse `clamscan --infected --no-summary` output: `<path>: <signature> FOUND`
+pub fn parse_clamscan_output(output: &str) -> Vec<MalwareDetection> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.strip_suffix(" FOUND")?;
+            let (path, signature) = rest.rsplit_once(": ")?;
+            Some(MalwareDetection { path: path.to_string(), signature: signature.to_string() })
+        })
+        .collect()
+}
+
+/// Scan `paths` with clamscan. Blocking; call from a blocking task.
+pub fn scan_paths(paths: &[String]) -> AntivirusReport {
+    let mut report = AntivirusReport { available: tool_available("clamscan"), scanned: 0, detections: Vec::new(), errors: Vec::new() };
+    if !report.available {
+        return report;
+    }
+
+    for batch in paths.chunks(CLAMSCAN_BATCH) {
+        let output = Command::new("clamscan")
+            .args(["--infected", "--no-summary", "--stdout", "--"])
+            .args(batch)
+            .stdin(Stdio::null())
+            .output();
+
+        match output {
+            // 0: clean, 1: virus found, 2: some files could not be scanned
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                report.detections.extend(parse_clamscan_output(&stdout));
+                if output.status.code() == Some(2) {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    report.errors.extend(stderr.lines().filter(|l| !l.trim().is_empty()).map(|l| l.to_string()));
+                }
+                report.scanned += batch.len();
+            }
+            Err(e) => {
+                report.errors.push(format!("Failed to run clamscan: {}", e));
+                break;
+            }
+        }
+    }
+
+    if !report.detections.is_empty() {
+        tracing::warn!("clamscan reported {} detection(s) among files to clean", report.detections.len());
+    }
+    report
+}
+
+/// Run ClamAV over files before cleaning them
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn scan_with_clamav(paths: Vec<String>) -> Result<AntivirusReport, String> {
+    tokio::task::spawn_blocking(move || scan_paths(&paths))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clamscan_output() {
+        let output = "/home/u/Downloads/eicar.com: Win.Test.EICAR_HDB-1 FOUND\n\
+                      /home/u/Downloads/odd: name.zip: Unix.Trojan.Mirai-1 FOUND\n\
+                      /home/u/Downloads/ok.pdf: OK\n";
+        let detections = parse_clamscan_output(output);
+        assert_eq!(detections.len(), 2);
+        assert_eq!(detections[0].path, "/home/u/Downloads/eicar.com");
+        assert_eq!(detections[0].signature, "Win.Test.EICAR_HDB-1");
+        assert_eq!(detections[1].path, "/home/u/Downloads/odd: name.zip");
+    }
+}
@@ -0,0 +1,301 @@
+//! Offloading files to another location (an external drive, a NAS mount) and the
+//! manifest that remembers where each file went. Every offloaded file is recorded
+//! with its name, BLAKE3 hash, original path and new location in
+//! `archive_manifest`, so `find_archived` can answer "where is that file?" long
+//! after the move.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::commands::CleanResult;
+use crate::path_validation::{self, SecurityContext};
+use crate::db::DbAccess;
+use crate::operations::{guard, OperationPlan};
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const MAX_SEARCH_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ArchivedFile {
+    pub id: i64,
+    pub name: String,
+    pub hash: String, // BLAKE3 of the contents, to find the file again if it was renamed since
+    pub size: u64,
+    pub original_path: String,
+    pub location: String, // where the file was moved to
+    pub archive: String, // destination the file was offloaded into
+    pub archived_at: i64,
+}
+
+pub fn record(conn: &Connection, file: &ArchivedFile) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO archive_manifest (name, hash, size, original_path, location, archive, archived_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![file.name, file.hash, file.size as i64, file.original_path, file.location, file.archive, file.archived_at],
+    )?;
+    Ok(())
+}
+
+/// Manifest entries whose name, original path or new location contains `query`
+/// (case-insensitive), or whose hash starts with it; most recent first
+pub fn search(conn: &Connection, query: &str, limit: usize) -> rusqlite::Result<Vec<ArchivedFile>> {
+    let escaped = query.trim().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    let mut stmt = conn.prepare(
+        "SELECT id, name, hash, size, original_path, location, archive, archived_at
+         FROM archive_manifest
+         WHERE name LIKE ?1 ESCAPE '\\' OR original_path LIKE ?1 ESCAPE '\\' OR location LIKE ?1 ESCAPE '\\'
+            OR hash LIKE ?2 ESCAPE '\\'
+         ORDER BY archived_at DESC, id DESC
+         LIMIT ?3",
+    )?;
+    let rows = stmt
+        .query_map(params![format!("%{}%", escaped), format!("{}%", escaped.to_lowercase()), limit as i64], |row| {
+            Ok(ArchivedFile {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                hash: row.get(2)?,
+                size: row.get::<_, i64>(3)?.max(0) as u64,
+                original_path: row.get(4)?,
+                location: row.get(5)?,
+                archive: row.get(6)?,
+                archived_at: row.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Rename, or copy and remove when the destination is on another filesystem
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::File::open(to)?.sync_all()?;
+    if fs::metadata(to)?.len() != fs::metadata(from)?.len() {
+        let _ = fs::remove_file(to);
+        return Err(std::io::Error::other(format!("Copy of {} is incomplete", from.display())));
+    }
+    fs::remove_file(from)
+}
+
+/// Where `source` goes inside `destination`: its path relative to home (or to /
+/// outside home), so offloads from different folders don't collide
+pub fn target_path(source: &Path, destination: &Path, home: &Path) -> PathBuf {
+    let relative = source.strip_prefix(home).or_else(|_| source.strip_prefix("/")).unwrap_or(source);
+    destination.join(relative)
+}
+
+/// Move a file or directory tree into `destination`, returning a manifest entry
+/// per file moved and the number of files that couldn't be. Files already present
+/// at the target are left in place.
+pub fn offload(source: &Path, destination: &Path, home: &Path) -> (Vec<ArchivedFile>, usize) {
+    let now = chrono::Utc::now().timestamp();
+    let archive = destination.to_string_lossy().to_string();
+    let mut moved = Vec::new();
+    let mut failed = 0;
+
+    let files: Vec<PathBuf> = WalkDir::new(source)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+    for file in files {
+        let target = target_path(&file, destination, home);
+        if target.exists() {
+            tracing::warn!("Not offloading {}: {} already exists", file.display(), target.display());
+            failed += 1;
+            continue;
+        }
+        let size = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+        let result = hash_file(&file).and_then(|hash| {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            move_file(&file, &target).map(|_| hash)
+        });
+        let hash = match result {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::error!("Failed to offload {}: {}", file.display(), e);
+                failed += 1;
+                continue;
+            }
+        };
+        moved.push(ArchivedFile {
+            id: 0,
+            name: file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            hash,
+            size,
+            original_path: file.to_string_lossy().to_string(),
+            location: target.to_string_lossy().to_string(),
+            archive: archive.clone(),
+            archived_at: now,
+        });
+    }
+
+    // Directories emptied by the move; anything skipped keeps its folder
+    if source.is_dir() {
+        let mut folders: Vec<PathBuf> = WalkDir::new(source)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_dir())
+            .map(|e| e.into_path())
+            .collect();
+        // Deepest first, so parents are empty by the time they're removed
+        folders.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+        for folder in folders {
+            let _ = fs::remove_dir(folder);
+        }
+    }
+    (moved, failed)
+}
+
+/// Why `destination` can't receive offloaded files, if it can't
+pub fn check_destination(destination: &Path, sources: &[String]) -> Result<(), String> {
+    if !destination.is_absolute() || !destination.is_dir() {
+        return Err(format!("Offload destination must be an existing folder: {}", destination.display()));
+    }
+    if let Some(source) = sources.iter().find(|s| destination.starts_with(s)) {
+        return Err(format!("Cannot offload {} into itself", source));
+    }
+    Ok(())
+}
+
+/// Move files to another location, recording each in the archive manifest
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn offload_files(
+    app_handle: tauri::AppHandle,
+    item_paths: Vec<String>,
+    destination: String,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    let plan = OperationPlan::OffloadFiles { item_paths: item_paths.clone(), destination: destination.clone() };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let destination = PathBuf::from(destination);
+    check_destination(&destination, &item_paths)?;
+
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+    for path_str in item_paths {
+        if let Err(e) = path_validation::validate_path_comprehensive(&path_str, SecurityContext::Deletion) {
+            tracing::warn!("Path validation failed for {}: {}", path_str, e);
+            failed += 1;
+            continue;
+        }
+        let (source, destination, home) = (PathBuf::from(&path_str), destination.clone(), home.clone());
+        let (files, failed_files) = tokio::task::spawn_blocking(move || offload(&source, &destination, &home))
+            .await
+            .map_err(|e| format!("Offload task failed: {}", e))?;
+
+        // Recorded even when part of a folder failed, so nothing moved is lost track of
+        if let Err(e) = app_handle.db(|conn| files.iter().try_for_each(|f| record(conn, f))) {
+            tracing::error!("Failed to record offloaded files in the manifest: {}", e);
+        }
+        total_size += files.iter().map(|f| f.size).sum::<u64>();
+        if failed_files == 0 {
+            cleaned += 1;
+            tracing::info!("Offloaded {} ({} files)", path_str, files.len());
+        } else {
+            failed += 1;
+            tracing::warn!("Offloaded {} only partially: {} files moved, {} failed", path_str, files.len(), failed_files);
+        }
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+/// Search the archive manifest for where an offloaded file went
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn find_archived(app_handle: tauri::AppHandle, query: String, limit: Option<usize>) -> Result<Vec<ArchivedFile>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+    app_handle
+        .db(|conn| search(conn, &query, limit))
+        .map_err(|e| format!("Failed to search archive manifest: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE archive_manifest (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, hash TEXT NOT NULL,
+             size INTEGER NOT NULL, original_path TEXT NOT NULL, location TEXT NOT NULL, archive TEXT NOT NULL,
+             archived_at INTEGER NOT NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_offload_and_search() {
+        let home = tempfile::TempDir::new().unwrap();
+        let drive = tempfile::TempDir::new().unwrap();
+        let project = home.path().join("Videos/2023 Trip");
+        fs::create_dir_all(project.join("raw")).unwrap();
+        fs::write(project.join("raw/clip_001.mp4"), b"clip one").unwrap();
+        fs::write(project.join("edit.kdenlive"), b"project").unwrap();
+
+        let (files, failed) = offload(&project, drive.path(), home.path());
+        assert_eq!((files.len(), failed), (2, 0));
+        assert!(!project.exists());
+        let moved = drive.path().join("Videos/2023 Trip/raw/clip_001.mp4");
+        assert_eq!(fs::read(&moved).unwrap(), b"clip one");
+
+        let conn = manifest_db();
+        for file in &files {
+            record(&conn, file).unwrap();
+        }
+        let found = search(&conn, "CLIP_001", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].location, moved.to_string_lossy());
+        assert_eq!(found[0].original_path, project.join("raw/clip_001.mp4").to_string_lossy());
+
+        let by_hash = search(&conn, &found[0].hash[..12], 10).unwrap();
+        assert_eq!(by_hash[0].name, "clip_001.mp4");
+        // LIKE wildcards in the query are taken literally
+        assert!(search(&conn, "%", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_destination_checks() {
+        let home = Path::new("/home/u");
+        assert_eq!(target_path(Path::new("/home/u/a/b.txt"), Path::new("/media/usb"), home), PathBuf::from("/media/usb/a/b.txt"));
+        assert_eq!(target_path(Path::new("/srv/data/x"), Path::new("/media/usb"), home), PathBuf::from("/media/usb/srv/data/x"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("inside")).unwrap();
+        let source = dir.path().to_string_lossy().to_string();
+        assert!(check_destination(&dir.path().join("inside"), &[source]).is_err());
+        assert!(check_destination(Path::new("relative"), &[]).is_err());
+        assert!(check_destination(dir.path(), &["/elsewhere".to_string()]).is_ok());
+    }
+}
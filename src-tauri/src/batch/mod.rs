@@ -0,0 +1,500 @@
+//! Scriptable batch runs: a JSON operations file lists scans and cleanups to
+//! run in order, so the same cleanup can be repeated across machines. A dry run
+//! only describes the cleanups and returns a confirmation token for the file as
+//! it is; running it for real takes that token, so nothing is deleted without
+//! the user reviewing the dry run first. Each cleanup still goes through the
+//! guard like any other, so read-only mode, safe mode and the audit log apply;
+//! the confirmed file counts once against the rate limit. Scans run again on
+//! the real run, but `clean_scan_results` only cleans items the dry run listed
+//! for that step, and only those the scans still find.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "dry_run": true,
+//!   "steps": [
+//!     { "action": "scan", "options": { "include_caches": true, "include_packages": false,
+//!                                      "include_large_files": false, "include_logs": true } },
+//!     { "action": "clean_scan_results", "categories": ["Cache"], "max_risk_level": 1 },
+//!     { "action": "operation", "plan": { "kind": "vacuum_journal", "max_size_mb": 200, "max_age_days": 30 } }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::archive;
+use crate::cache_redirect;
+use crate::commands;
+use crate::coredumps;
+use crate::docker;
+use crate::flatpak;
+use crate::journal;
+use crate::metrics;
+use crate::operations::{self, guard, OperationDescription, OperationPlan};
+use crate::packages::kernels;
+use crate::recovery;
+use crate::scanner::{glob, HashMode, ScanItem, ScanOptions};
+use crate::snaps;
+use crate::snapshots::existing;
+use crate::timestamps;
+use crate::xdg_trash;
+
+pub const BATCH_FILE_VERSION: u32 = 1;
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// An operations file, see the module docs for an example
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchFile {
+    pub version: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Describe every cleanup instead of running it; scans still run
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Keep going after a failed step instead of stopping there
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub steps: Vec<BatchStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BatchStep {
+    /// System scan; its items are what `clean_scan_results` picks from
+    Scan { options: ScanOptions },
+    /// Large files and old downloads, also offered to `clean_scan_results`
+    StorageRecovery {
+        #[serde(default)]
+        hash_mode: Option<HashMode>,
+        #[serde(default)]
+        include_globs: Option<Vec<String>>,
+        #[serde(default)]
+        exclude_globs: Option<Vec<String>>,
+    },
+    /// Clean what the scans so far found, in the given categories (all when empty)
+    /// and up to the given risk level
+    CleanScanResults {
+        #[serde(default)]
+        categories: Vec<String>,
+        #[serde(default)]
+        max_risk_level: u8,
+        #[serde(default = "default_use_trash")]
+        use_trash: bool,
+    },
+    /// Any destructive command, as passed to `describe_operation`
+    Operation { plan: OperationPlan },
+}
+
+fn default_use_trash() -> bool {
+    true
+}
+
+impl BatchStep {
+    fn action(&self) -> String {
+        match self {
+            BatchStep::Scan { .. } => "scan".to_string(),
+            BatchStep::StorageRecovery { .. } => "storage_recovery".to_string(),
+            BatchStep::CleanScanResults { .. } => "clean_scan_results".to_string(),
+            BatchStep::Operation { plan } => plan.name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BatchStepReport {
+    pub index: usize,
+    pub action: String, // "scan", "storage_recovery", "clean_scan_results" or the command name
+    pub ok: bool,
+    pub dry_run: bool, // described only
+    pub item_count: usize,
+    pub total_size: u64, // found by a scan, freed by a cleanup, or that a dry run would free
+    pub message: String,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BatchReport {
+    pub path: String,
+    pub name: Option<String>,
+    pub dry_run: bool,
+    /// UTC unix seconds; reports saved by older releases have RFC 3339 strings here
+    #[serde(deserialize_with = "crate::timestamps::deserialize")]
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub steps: Vec<BatchStepReport>,
+    pub total_found: u64,
+    pub total_freed: u64,
+    pub total_would_free: u64,
+    pub failed_steps: usize,
+    /// A step failed and `continue_on_error` wasn't set, so later steps didn't run
+    pub stopped_early: bool,
+    /// From a dry run: pass to `run_batch` to run the same file for real
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<String>,
+}
+
+/// Parse and check an operations file
+pub fn parse_batch_file(contents: &str) -> Result<BatchFile, String> {
+    let file: BatchFile = serde_json::from_str(contents).map_err(|e| format!("Invalid operations file: {}", e))?;
+    if file.version != BATCH_FILE_VERSION {
+        return Err(format!(
+            "Unsupported operations file version {}; expected {}",
+            file.version, BATCH_FILE_VERSION
+        ));
+    }
+    if file.steps.is_empty() {
+        return Err("The operations file has no steps".to_string());
+    }
+    Ok(file)
+}
+
+/// What a batch confirmation token is bound to: the file and its exact contents
+fn batch_fingerprint(path: &Path, contents: &str) -> String {
+    format!("batch:{}:{}", path.display(), blake3::hash(contents.as_bytes()).to_hex())
+}
+
+/// The parsed file and its fingerprint
+fn read_batch_file(path: &Path) -> Result<(BatchFile, String), String> {
+    if matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml")) {
+        return Err("YAML operations files aren't supported; write the same structure as JSON".to_string());
+    }
+    let metadata = std::fs::metadata(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    if metadata.len() > MAX_FILE_SIZE {
+        return Err(format!("{} is too large for an operations file", path.display()));
+    }
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    Ok((parse_batch_file(&contents)?, batch_fingerprint(path, &contents)))
+}
+
+/// Scan items picked by a `clean_scan_results` step
+fn select_items<'a>(found: &'a [ScanItem], categories: &[String], max_risk_level: u8) -> Vec<&'a ScanItem> {
+    found
+        .iter()
+        .filter(|item| item.risk_level <= max_risk_level)
+        .filter(|item| categories.is_empty() || categories.iter().any(|c| c.eq_ignore_ascii_case(&item.category)))
+        .collect()
+}
+
+/// Drop the paths the dry run didn't list from `item_paths`; `reviewed` is
+/// sorted. Returns how many were dropped.
+fn keep_reviewed(item_paths: &mut Vec<String>, reviewed: &[String]) -> usize {
+    let before = item_paths.len();
+    item_paths.retain(|path| reviewed.binary_search(path).is_ok());
+    before - item_paths.len()
+}
+
+/// Run a described plan with its confirmation token; returns items cleaned and bytes freed
+async fn execute(app_handle: &tauri::AppHandle, plan: OperationPlan, token: String) -> Result<(usize, u64), String> {
+    let handle = app_handle.clone();
+    let result = match plan {
+        OperationPlan::CleanItems { item_paths, use_trash, retention_days } => {
+            // Item ids only pair up with the paths, the paths are what gets cleaned
+            commands::clean_items(handle, item_paths.clone(), item_paths, use_trash, retention_days, token, None).await?
+        }
+        OperationPlan::ClearCache => commands::clear_cache(handle, token).await?,
+        OperationPlan::CleanPackages => commands::clean_packages(handle, token).await?,
+        OperationPlan::ClearLogs => commands::clear_logs(handle, token).await?,
+        OperationPlan::ClearCacheItem { item_name } => commands::clear_cache_item(handle, item_name, token).await?,
+        OperationPlan::CleanupOldFiles { days_cutoff, include_libraries } => {
+            commands::cleanup_old_files(handle, days_cutoff, Some(include_libraries), token).await?
+        }
+        OperationPlan::EmptyTrash => {
+            let removed = commands::empty_trash(handle, token).await?;
+            return Ok((removed, 0));
+        }
+        OperationPlan::DeleteFromTrash { id } => {
+            commands::delete_from_trash(handle, id, token).await?;
+            return Ok((1, 0));
+        }
+        OperationPlan::QuickCleanSafe => {
+            let result = commands::quick_clean_safe(handle, token).await?;
+            return Ok((result.cleaned as usize, result.total_size));
+        }
+        OperationPlan::CleanDockerArtifacts { artifacts } => docker::clean_docker_artifacts(handle, artifacts, token).await?,
+        OperationPlan::UninstallFlatpakRefs { refs } => flatpak::uninstall_flatpak_refs(handle, refs, token).await?,
+        OperationPlan::OffloadFiles { item_paths, destination } => {
+            archive::offload_files(handle, item_paths, destination, token).await?
+        }
+        OperationPlan::RemoveDisabledSnaps => snaps::remove_disabled_snaps(handle, token).await?,
+        OperationPlan::VacuumJournal { max_size_mb, max_age_days } => {
+            journal::vacuum_journal(handle, max_size_mb, max_age_days, token).await?
+        }
+        OperationPlan::RemoveCoredumps { older_than_days } => {
+            coredumps::remove_coredumps(handle, older_than_days, token).await?
+        }
+        OperationPlan::RemoveOldKernels { releases } => kernels::remove_old_kernels(handle, releases, token).await?,
+        OperationPlan::TerminateHolder { pid } => {
+            recovery::terminate_holder(handle, pid, token).await?;
+            return Ok((1, 0));
+        }
+        OperationPlan::DeleteFilesystemSnapshots { ids } => {
+            existing::delete_filesystem_snapshots(handle, ids, token).await?
+        }
+        OperationPlan::EmptySystemTrash { trash_dirs, entries, older_than_days } => {
+            xdg_trash::empty_system_trash(handle, trash_dirs, entries, older_than_days, token).await?
+        }
+        OperationPlan::RedirectCache { name } => {
+            cache_redirect::enable_cache_redirect(handle, name, token).await?;
+            return Ok((1, 0));
+        }
+        OperationPlan::DisableSafeMode => return Err("Safe mode can only be relaxed in Settings".to_string()),
+    };
+    if result.failed > 0 {
+        tracing::warn!("Batch cleanup left {} item(s) in place", result.failed);
+    }
+    Ok((result.cleaned, result.total_size))
+}
+
+/// Describe `plan`, then run it under `grant`; without one this is a dry run
+async fn run_plan(
+    app_handle: &tauri::AppHandle,
+    plan: OperationPlan,
+    grant: Option<&guard::BatchGrant>,
+    report: &mut BatchStepReport,
+) -> Result<(), String> {
+    let description: OperationDescription = operations::describe_operation(app_handle.clone(), plan.clone()).await?;
+    report.warnings.extend(description.warnings.iter().cloned());
+    let Some(grant) = grant else {
+        report.item_count = description.item_count;
+        report.total_size = description.total_size;
+        report.message = format!("Would run: {}", description.title);
+        return Ok(());
+    };
+
+    let token = grant.step_token(&plan);
+    let (cleaned, freed) = metrics::timed(plan.name(), execute(app_handle, plan, token)).await?;
+    report.item_count = cleaned;
+    report.total_size = freed;
+    report.message = description.title;
+    Ok(())
+}
+
+/// Run step `index`; a dry run fills `listed` with the item paths a cleanup would clean
+async fn run_step(
+    app_handle: &tauri::AppHandle,
+    index: usize,
+    step: BatchStep,
+    grant: Option<&guard::BatchGrant>,
+    found: &mut Vec<ScanItem>,
+    listed: &mut Vec<String>,
+    report: &mut BatchStepReport,
+) -> Result<(), String> {
+    match step {
+        BatchStep::Scan { options } => {
+            let results = commands::start_scan(app_handle.clone(), options).await?;
+            report.item_count = results.items.len();
+            report.total_size = results.total_size;
+            report.message = format!("Found {} items", results.items.len());
+            found.extend(results.items);
+            Ok(())
+        }
+        BatchStep::StorageRecovery { hash_mode, include_globs, exclude_globs } => {
+            let results =
+                commands::scan_storage_recovery(app_handle.clone(), hash_mode, include_globs, exclude_globs, None).await?;
+            let items: Vec<ScanItem> = results.large_files.into_iter().chain(results.old_downloads).collect();
+            report.item_count = items.len();
+            report.total_size = results.total_recoverable_size;
+            report.message = format!("Found {} large files and old downloads", items.len());
+            found.extend(items);
+            Ok(())
+        }
+        BatchStep::CleanScanResults { categories, max_risk_level, use_trash } => {
+            let mut item_paths: Vec<String> = select_items(found, &categories, max_risk_level)
+                .into_iter()
+                .map(|item| item.path.clone())
+                .collect();
+            item_paths.sort();
+            item_paths.dedup();
+            match grant {
+                Some(grant) => {
+                    let dropped = keep_reviewed(&mut item_paths, grant.reviewed_items(index));
+                    if dropped > 0 {
+                        report.warnings.push(format!("{} items not in the dry run were left alone", dropped));
+                    }
+                }
+                None => listed.clone_from(&item_paths),
+            }
+            if item_paths.is_empty() {
+                report.message = "Nothing to clean".to_string();
+                return Ok(());
+            }
+            let retention_days = commands::load_settings(app_handle).trash.retention_days;
+            let plan = OperationPlan::CleanItems { item_paths: item_paths.clone(), use_trash, retention_days };
+            run_plan(app_handle, plan, grant, report).await?;
+            if grant.is_some() {
+                found.retain(|item| item_paths.binary_search(&item.path).is_err());
+            }
+            Ok(())
+        }
+        BatchStep::Operation { plan } => run_plan(app_handle, plan, grant, report).await,
+    }
+}
+
+/// Run the scans and cleanups an operations file lists, in order, and report on
+/// each. `dry_run` overrides the file's own setting; a real run needs the
+/// `confirmation_token` of a dry run of the unchanged file.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn run_batch(
+    app_handle: tauri::AppHandle,
+    path: String,
+    dry_run: Option<bool>,
+    confirmation_token: Option<String>,
+) -> Result<BatchReport, String> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let file_path = glob::expand_home(path.trim(), &home);
+    let (batch, fingerprint) = tokio::task::spawn_blocking({
+        let file_path = file_path.clone();
+        move || read_batch_file(&file_path)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+
+    let dry_run = dry_run.unwrap_or(batch.dry_run);
+    let grant = if dry_run {
+        None
+    } else {
+        let token = confirmation_token.ok_or("Review a dry run of this operations file first, then run it with its confirmation token")?;
+        Some(guard::authorize_batch(&app_handle, &fingerprint, &token)?)
+    };
+    let started = Instant::now();
+    let mut report = BatchReport {
+        path: file_path.to_string_lossy().to_string(),
+        name: batch.name.clone(),
+        dry_run,
+        started_at: timestamps::now(),
+        duration_ms: 0,
+        steps: Vec::new(),
+        total_found: 0,
+        total_freed: 0,
+        total_would_free: 0,
+        failed_steps: 0,
+        stopped_early: false,
+        confirmation_token: None,
+    };
+    tracing::info!("Running operations file {} ({} steps, dry run: {})", report.path, batch.steps.len(), dry_run);
+
+    let mut found = Vec::new();
+    let mut reviewed_items = Vec::new();
+    let step_count = batch.steps.len();
+    for (index, step) in batch.steps.into_iter().enumerate() {
+        let is_scan = matches!(step, BatchStep::Scan { .. } | BatchStep::StorageRecovery { .. });
+        let mut step_report = BatchStepReport {
+            index,
+            action: step.action(),
+            ok: true,
+            dry_run: dry_run && !is_scan,
+            item_count: 0,
+            total_size: 0,
+            message: String::new(),
+            warnings: Vec::new(),
+        };
+
+        let mut listed = Vec::new();
+        let result = run_step(&app_handle, index, step, grant.as_ref(), &mut found, &mut listed, &mut step_report).await;
+        // A step that failed in the dry run was never shown, so it clears nothing
+        if result.is_err() {
+            listed.clear();
+        }
+        reviewed_items.push(listed);
+        if let Err(e) = result {
+            tracing::warn!("Batch step {} ({}) failed: {}", index, step_report.action, e);
+            step_report.ok = false;
+            step_report.message = e;
+            report.failed_steps += 1;
+        } else if is_scan {
+            report.total_found += step_report.total_size;
+        } else if dry_run {
+            report.total_would_free += step_report.total_size;
+        } else {
+            report.total_freed += step_report.total_size;
+        }
+
+        let failed = !step_report.ok;
+        report.steps.push(step_report);
+        if failed && !batch.continue_on_error && index + 1 < step_count {
+            report.stopped_early = true;
+            break;
+        }
+    }
+
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    if dry_run {
+        report.confirmation_token = Some(guard::issue_batch_token(&fingerprint, reviewed_items));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::builtin::new_item;
+
+    fn item(path: &str, category: &str, risk_level: u8) -> ScanItem {
+        new_item(path.to_string(), Path::new(path), 10, "file", category, risk_level, "")
+    }
+
+    #[test]
+    fn test_parse_batch_file() {
+        let batch = parse_batch_file(
+            r#"{
+                "version": 1,
+                "dry_run": true,
+                "steps": [
+                    { "action": "scan", "options": { "include_caches": true, "include_packages": false,
+                                                     "include_large_files": false, "include_logs": false } },
+                    { "action": "clean_scan_results", "categories": ["Cache"] },
+                    { "action": "operation", "plan": { "kind": "vacuum_journal", "max_size_mb": 200, "max_age_days": 30 } }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert!(batch.dry_run);
+        assert!(!batch.continue_on_error);
+        assert_eq!(batch.steps.len(), 3);
+        assert!(matches!(&batch.steps[1], BatchStep::CleanScanResults { use_trash: true, max_risk_level: 0, .. }));
+        assert_eq!(batch.steps[2].action(), "vacuum_journal");
+
+        assert!(parse_batch_file(r#"{ "version": 2, "steps": [{ "action": "operation", "plan": { "kind": "clear_cache" } }] }"#).is_err());
+        assert!(parse_batch_file(r#"{ "version": 1, "steps": [] }"#).is_err());
+        assert!(parse_batch_file(r#"{ "version": 1, "dryrun": true, "steps": [{ "action": "scan" }] }"#).is_err());
+    }
+
+    #[test]
+    fn test_select_items() {
+        let found = vec![item("/a", "Cache", 0), item("/b", "Logs", 0), item("/c", "Cache", 2)];
+        let picked: Vec<&str> = select_items(&found, &["cache".to_string()], 1).iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(picked, vec!["/a"]);
+        assert_eq!(select_items(&found, &[], 2).len(), 3);
+    }
+
+    #[test]
+    fn test_keep_reviewed() {
+        let reviewed = vec!["/a".to_string(), "/c".to_string()];
+        let mut item_paths = vec!["/a".to_string(), "/b".to_string(), "/c".to_string()];
+        assert_eq!(keep_reviewed(&mut item_paths, &reviewed), 1);
+        assert_eq!(item_paths, ["/a", "/c"]);
+        assert_eq!(keep_reviewed(&mut item_paths, &[]), 2);
+        assert!(item_paths.is_empty());
+    }
+
+    #[test]
+    fn test_batch_fingerprint_binds_contents() {
+        let path = Path::new("/home/user/ops.json");
+        let contents = r#"{ "version": 1, "steps": [{ "action": "operation", "plan": { "kind": "clear_cache" } }] }"#;
+        assert_eq!(batch_fingerprint(path, contents), batch_fingerprint(path, contents));
+        assert_ne!(batch_fingerprint(path, contents), batch_fingerprint(path, &contents.replace("clear_cache", "empty_trash")));
+        assert_ne!(batch_fingerprint(path, contents), batch_fingerprint(Path::new("/tmp/ops.json"), contents));
+    }
+}
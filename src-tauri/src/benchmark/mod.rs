@@ -0,0 +1,433 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::Emitter;
+use tokio::time::{timeout, Duration};
+
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::db::DbAccess;
+
+/// O_DIRECT requires buffers and offsets aligned to the logical block size
+const ALIGNMENT: usize = 4096;
+const SEQ_BLOCK_SIZE: usize = 1024 * 1024; // 1MB
+const RANDOM_BLOCK_SIZE: usize = 4096; // 4KB
+const RANDOM_OPS: usize = 2048;
+const DEFAULT_FILE_SIZE_MB: u64 = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct DiskBenchmarkResult {
+    pub target_path: String,
+    pub file_size_bytes: u64,
+    pub seq_read_mbps: f64,
+    pub seq_write_mbps: f64,
+    pub random_read_iops: f64,
+    pub random_write_iops: f64,
+    pub direct_io: bool, // false when the filesystem rejected O_DIRECT (e.g. tmpfs)
+    pub duration_ms: u64,
+    pub timestamp: i64,
+}
+
+/// Progress event structure for benchmark updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkProgress {
+    pub phase: String, // "seq_write", "seq_read", "random_write", "random_read", "complete"
+    pub progress: u8,  // 0-100
+    pub message: String,
+}
+
+/// Heap buffer whose usable slice starts on an ALIGNMENT boundary
+struct AlignedBuffer {
+    storage: Vec<u8>,
+    offset: usize,
+    len: usize,
+}
+
+impl AlignedBuffer {
+    fn new(len: usize) -> Self {
+        let storage = vec![0u8; len + ALIGNMENT];
+        let offset = storage.as_ptr().align_offset(ALIGNMENT);
+        Self { storage, offset, len }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.storage[self.offset..self.offset + self.len]
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.storage[self.offset..self.offset + self.len]
+    }
+}
+
+/// Removes the benchmark file however the benchmark exits
+struct TempFileGuard(PathBuf);
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove benchmark file {}: {}", self.0.display(), e);
+            }
+        }
+    }
+}
+
+/// Small xorshift generator - good enough for offsets and incompressible data
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15);
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+fn open_benchmark_file(path: &Path, write: bool, direct: bool) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    if write {
+        options.write(true).create(true);
+    }
+
+    #[cfg(target_os = "linux")]
+    if direct {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_DIRECT);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = direct;
+
+    options.open(path)
+}
+
+/// Ask the kernel to drop cached pages so buffered reads hit the device
+fn drop_page_cache(file: &File) {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::io::AsRawFd;
+        unsafe {
+            libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = file;
+}
+
+fn available_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Progress through a loop of `total` steps at step `i`, reported every tenth
+fn progress_step(i: usize, total: usize) -> Option<u8> {
+    (i % (total / 10).max(1) == 0).then(|| ((i * 100) / total) as u8)
+}
+
+fn rate(amount: f64, secs: f64) -> f64 {
+    if secs > 0.0 {
+        amount / secs
+    } else {
+        0.0
+    }
+}
+
+/// Phase durations in seconds
+struct PhaseTimes {
+    seq_write: f64,
+    seq_read: f64,
+    random_write: f64,
+    random_read: f64,
+}
+
+fn benchmark_result(target_dir: &Path, file_size_bytes: u64, times: &PhaseTimes, direct_io: bool, duration_ms: u64) -> DiskBenchmarkResult {
+    let megabytes = file_size_bytes as f64 / (1024.0 * 1024.0);
+    DiskBenchmarkResult {
+        target_path: target_dir.to_string_lossy().to_string(),
+        file_size_bytes,
+        seq_read_mbps: rate(megabytes, times.seq_read),
+        seq_write_mbps: rate(megabytes, times.seq_write),
+        random_read_iops: rate(RANDOM_OPS as f64, times.random_read),
+        random_write_iops: rate(RANDOM_OPS as f64, times.random_write),
+        direct_io,
+        duration_ms,
+        timestamp: chrono::Utc::now().timestamp(),
+    }
+}
+
+fn run_benchmark_blocking(
+    target_dir: &Path,
+    file_size_bytes: u64,
+    emit: &dyn Fn(&str, u8, &str),
+) -> Result<DiskBenchmarkResult, String> {
+    let start = Instant::now();
+    let file_path = target_dir.join(format!(".pulito-benchmark-{}.tmp", uuid::Uuid::new_v4()));
+    let _guard = TempFileGuard(file_path.clone());
+
+    let block_count = (file_size_bytes as usize / SEQ_BLOCK_SIZE).max(1);
+    let mut rng = XorShift::new();
+    let mut buffer = AlignedBuffer::new(SEQ_BLOCK_SIZE);
+    for chunk in buffer.as_mut_slice().chunks_mut(8) {
+        let bytes = rng.next().to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+
+    // Prefer O_DIRECT so results reflect the device, not the page cache
+    let (mut file, direct_io) = match open_benchmark_file(&file_path, true, true) {
+        Ok(file) => (file, true),
+        Err(e) => {
+            tracing::debug!("O_DIRECT unavailable for {}: {}, using buffered I/O", target_dir.display(), e);
+            let file = open_benchmark_file(&file_path, true, false)
+                .map_err(|e| format!("Failed to create benchmark file: {}", e))?;
+            (file, false)
+        }
+    };
+
+    // Sequential write
+    emit("seq_write", 0, "Measuring sequential write speed...");
+    let phase_start = Instant::now();
+    for i in 0..block_count {
+        file.write_all(buffer.as_slice())
+            .map_err(|e| format!("Sequential write failed: {}", e))?;
+        if let Some(progress) = progress_step(i, block_count) {
+            emit("seq_write", progress, "Measuring sequential write speed...");
+        }
+    }
+    file.sync_all().map_err(|e| format!("Failed to flush benchmark file: {}", e))?;
+    let seq_write_secs = phase_start.elapsed().as_secs_f64();
+    drop_page_cache(&file);
+    drop(file);
+
+    // Sequential read
+    emit("seq_read", 0, "Measuring sequential read speed...");
+    let mut file = open_benchmark_file(&file_path, false, direct_io)
+        .map_err(|e| format!("Failed to reopen benchmark file: {}", e))?;
+    let phase_start = Instant::now();
+    for i in 0..block_count {
+        file.read_exact(buffer.as_mut_slice())
+            .map_err(|e| format!("Sequential read failed: {}", e))?;
+        if let Some(progress) = progress_step(i, block_count) {
+            emit("seq_read", progress, "Measuring sequential read speed...");
+        }
+    }
+    let seq_read_secs = phase_start.elapsed().as_secs_f64();
+    drop(file);
+
+    // Random 4K writes at aligned offsets
+    emit("random_write", 0, "Measuring random write IOPS...");
+    let total_small_blocks = (block_count * SEQ_BLOCK_SIZE / RANDOM_BLOCK_SIZE) as u64;
+    let mut small = AlignedBuffer::new(RANDOM_BLOCK_SIZE);
+    small.as_mut_slice().copy_from_slice(&buffer.as_slice()[..RANDOM_BLOCK_SIZE]);
+    let mut file = open_benchmark_file(&file_path, true, direct_io)
+        .map_err(|e| format!("Failed to reopen benchmark file: {}", e))?;
+    let phase_start = Instant::now();
+    for i in 0..RANDOM_OPS {
+        let offset = (rng.next() % total_small_blocks) * RANDOM_BLOCK_SIZE as u64;
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.write_all(small.as_slice()))
+            .map_err(|e| format!("Random write failed: {}", e))?;
+        if let Some(progress) = progress_step(i, RANDOM_OPS) {
+            emit("random_write", progress, "Measuring random write IOPS...");
+        }
+    }
+    file.sync_all().map_err(|e| format!("Failed to flush benchmark file: {}", e))?;
+    let random_write_secs = phase_start.elapsed().as_secs_f64();
+    drop_page_cache(&file);
+    drop(file);
+
+    // Random 4K reads
+    emit("random_read", 0, "Measuring random read IOPS...");
+    let mut file = open_benchmark_file(&file_path, false, direct_io)
+        .map_err(|e| format!("Failed to reopen benchmark file: {}", e))?;
+    let phase_start = Instant::now();
+    for i in 0..RANDOM_OPS {
+        let offset = (rng.next() % total_small_blocks) * RANDOM_BLOCK_SIZE as u64;
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(small.as_mut_slice()))
+            .map_err(|e| format!("Random read failed: {}", e))?;
+        if let Some(progress) = progress_step(i, RANDOM_OPS) {
+            emit("random_read", progress, "Measuring random read IOPS...");
+        }
+    }
+    let random_read_secs = phase_start.elapsed().as_secs_f64();
+
+    let times = PhaseTimes {
+        seq_write: seq_write_secs,
+        seq_read: seq_read_secs,
+        random_write: random_write_secs,
+        random_read: random_read_secs,
+    };
+    Ok(benchmark_result(target_dir, (block_count * SEQ_BLOCK_SIZE) as u64, &times, direct_io, start.elapsed().as_millis() as u64))
+}
+
+/// Benchmark sequential and random read/write speed of the disk backing `target_dir`
+/// (home directory by default). The temp file is always removed afterwards and the
+/// result is stored in disk_benchmarks so degradation can be tracked over time.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn run_disk_benchmark(
+    app_handle: tauri::AppHandle,
+    target_dir: Option<String>,
+    file_size_mb: Option<u64>,
+) -> Result<DiskBenchmarkResult, String> {
+    let benchmark_timeout = Duration::from_secs(300);
+
+    let target_dir = match target_dir {
+        Some(dir) => {
+            validate_path_comprehensive(&dir, SecurityContext::ReadWrite)
+                .map_err(|e| format!("Security validation failed: {}", e))?;
+            PathBuf::from(dir)
+        }
+        None => dirs::home_dir().ok_or("Cannot determine home directory")?,
+    };
+    if !target_dir.is_dir() {
+        return Err(format!("Not a directory: {}", target_dir.display()));
+    }
+
+    let file_size_bytes = file_size_mb.unwrap_or(DEFAULT_FILE_SIZE_MB).clamp(16, 2048) * 1024 * 1024;
+    if let Some(available) = available_space(&target_dir) {
+        // Leave generous headroom - never fill the disk for a benchmark
+        if available < file_size_bytes * 4 {
+            return Err(format!(
+                "Not enough free space for a {} benchmark on {}",
+                crate::scanner::format_bytes(file_size_bytes),
+                target_dir.display()
+            ));
+        }
+    }
+
+    tracing::info!("Starting disk benchmark on {} ({} bytes)", target_dir.display(), file_size_bytes);
+
+    let progress_handle = app_handle.clone();
+    let result = timeout(
+        benchmark_timeout,
+        tokio::task::spawn_blocking(move || {
+            let emit = |phase: &str, progress: u8, message: &str| {
+                let event = BenchmarkProgress {
+                    phase: phase.to_string(),
+                    progress,
+                    message: message.to_string(),
+                };
+                if let Err(e) = progress_handle.emit("benchmark-progress", &event) {
+                    tracing::warn!("Failed to emit benchmark progress event: {}", e);
+                }
+            };
+            run_benchmark_blocking(&target_dir, file_size_bytes, &emit)
+        }),
+    )
+    .await
+    .map_err(|_| format!("Disk benchmark timed out after {} seconds", benchmark_timeout.as_secs()))?
+    .map_err(|e| format!("Disk benchmark task failed: {}", e))??;
+
+    let _ = app_handle.emit("benchmark-progress", &BenchmarkProgress {
+        phase: "complete".to_string(),
+        progress: 100,
+        message: "Benchmark complete".to_string(),
+    });
+
+    if let Err(e) = app_handle.db(|conn| {
+        conn.execute(
+            "INSERT INTO disk_benchmarks (timestamp, target_path, file_size_bytes, seq_read_mbps, seq_write_mbps, random_read_iops, random_write_iops, direct_io, duration_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                result.timestamp,
+                result.target_path,
+                result.file_size_bytes as i64,
+                result.seq_read_mbps,
+                result.seq_write_mbps,
+                result.random_read_iops,
+                result.random_write_iops,
+                result.direct_io,
+                result.duration_ms as i64,
+            ],
+        )?;
+        Ok(())
+    }) {
+        tracing::warn!("Failed to store benchmark result: {}", e);
+    }
+
+    tracing::info!(
+        "Disk benchmark complete: read {:.1} MB/s, write {:.1} MB/s, {:.0}/{:.0} random read/write IOPS",
+        result.seq_read_mbps, result.seq_write_mbps, result.random_read_iops, result.random_write_iops
+    );
+    Ok(result)
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_benchmark_history(app_handle: tauri::AppHandle, limit: usize) -> Result<Vec<DiskBenchmarkResult>, String> {
+    app_handle.db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT target_path, file_size_bytes, seq_read_mbps, seq_write_mbps, random_read_iops, random_write_iops, direct_io, duration_ms, timestamp
+             FROM disk_benchmarks ORDER BY timestamp DESC LIMIT ?"
+        )?;
+        let rows = stmt.query_map([limit as i64], |row| {
+            Ok(DiskBenchmarkResult {
+                target_path: row.get(0)?,
+                file_size_bytes: row.get::<_, i64>(1)? as u64,
+                seq_read_mbps: row.get(2)?,
+                seq_write_mbps: row.get(3)?,
+                random_read_iops: row.get(4)?,
+                random_write_iops: row.get(5)?,
+                direct_io: row.get(6)?,
+                duration_ms: row.get::<_, i64>(7)? as u64,
+                timestamp: row.get(8)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    }).map_err(|e| format!("Failed to get benchmark history: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_benchmark_result() {
+        let times = PhaseTimes { seq_write: 2.0, seq_read: 0.5, random_write: 0.25, random_read: 0.0 };
+        let result = benchmark_result(Path::new("/mnt/data"), 256 * 1024 * 1024, &times, true, 4000);
+        assert_eq!(result.seq_write_mbps, 128.0);
+        assert_eq!(result.seq_read_mbps, 512.0);
+        assert_eq!(result.random_write_iops, RANDOM_OPS as f64 * 4.0);
+        assert_eq!(result.random_read_iops, 0.0); // too fast to time
+        assert_eq!(result.target_path, "/mnt/data");
+    }
+
+    #[test]
+    fn test_progress_in_every_phase() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let events = RefCell::new(Vec::new());
+        let emit = |phase: &str, progress: u8, _: &str| events.borrow_mut().push((phase.to_string(), progress));
+        let result = run_benchmark_blocking(dir.path(), 16 * 1024 * 1024, &emit).unwrap();
+        assert_eq!(result.file_size_bytes, 16 * 1024 * 1024);
+
+        let events = events.into_inner();
+        for phase in ["seq_write", "seq_read", "random_write", "random_read"] {
+            let progress: Vec<u8> = events.iter().filter(|(p, _)| p == phase).map(|(_, progress)| *progress).collect();
+            assert!(progress.len() > 2, "{}: {:?}", phase, progress);
+            assert!(progress.windows(2).all(|w| w[0] <= w[1]) && progress.last() >= Some(&90), "{}: {:?}", phase, progress);
+        }
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0); // benchmark file removed
+    }
+}
@@ -0,0 +1,327 @@
+//! Before/after boot comparison for startup program toggles.
+//!
+//! Every toggle in the startup manager is logged; recorded boots on either side
+//! of it are averaged so users can see whether the change paid off.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+
+use super::UnitTiming;
+use crate::db::DbAccess;
+
+/// Boots averaged on each side of a change
+const WINDOW: usize = 5;
+/// Units listed per change, largest difference first
+const UNIT_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BootWindow {
+    pub boots: usize,
+    pub avg_total_ms: Option<u64>,
+    pub avg_userspace_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct UnitImpact {
+    pub unit: String,
+    pub before_ms: Option<u64>, // None when the unit did not start in that window
+    pub after_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct StartupImpact {
+    pub change_id: i64,
+    pub program_id: String,
+    pub program_name: String,
+    pub unit: Option<String>, // systemd user unit for systemd_user entries
+    pub enabled: bool,
+    pub changed_at: i64,
+    pub before: BootWindow,
+    pub after: BootWindow, // empty until the next recorded boot
+    pub units: Vec<UnitImpact>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StartupChange {
+    pub id: i64,
+    pub program_id: String,
+    pub program_name: String,
+    pub unit: Option<String>,
+    pub enabled: bool,
+    pub changed_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredBoot {
+    pub booted_at: i64,
+    pub total_ms: u64,
+    pub userspace_ms: Option<u64>,
+    pub units: Vec<UnitTiming>, // system and user blame combined
+}
+
+pub fn record_change(
+    conn: &Connection,
+    program_id: &str,
+    program_name: &str,
+    unit: Option<&str>,
+    enabled: bool,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO startup_changes (program_id, program_name, unit, enabled, changed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![program_id, program_name, unit, enabled, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Oldest first
+pub fn load_changes(conn: &Connection) -> rusqlite::Result<Vec<StartupChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, program_id, program_name, unit, enabled, changed_at FROM startup_changes ORDER BY changed_at, id",
+    )?;
+    let changes = stmt
+        .query_map([], |row| {
+            Ok(StartupChange {
+                id: row.get(0)?,
+                program_id: row.get(1)?,
+                program_name: row.get(2)?,
+                unit: row.get(3)?,
+                enabled: row.get(4)?,
+                changed_at: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(changes)
+}
+
+/// Oldest first
+pub fn load_boots(conn: &Connection) -> rusqlite::Result<Vec<StoredBoot>> {
+    let mut stmt = conn.prepare(
+        "SELECT booted_at, total_ms, userspace_ms, blame, user_blame FROM boot_analyses ORDER BY booted_at",
+    )?;
+    let boots = stmt
+        .query_map([], |row| {
+            let blame: String = row.get(3)?;
+            let user_blame: String = row.get(4)?;
+            let mut units: Vec<UnitTiming> = serde_json::from_str(&blame).unwrap_or_default();
+            units.extend(serde_json::from_str::<Vec<UnitTiming>>(&user_blame).unwrap_or_default());
+            Ok(StoredBoot {
+                booted_at: row.get(0)?,
+                total_ms: row.get::<_, i64>(1)? as u64,
+                userspace_ms: row.get::<_, Option<i64>>(2)?.map(|v| v as u64),
+                units,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(boots)
+}
+
+fn average(values: impl Iterator<Item = u64>) -> Option<u64> {
+    let (sum, count) = values.fold((0u64, 0u64), |(s, c), v| (s + v, c + 1));
+    (count > 0).then(|| sum / count)
+}
+
+fn summarize(boots: &[&StoredBoot]) -> BootWindow {
+    BootWindow {
+        boots: boots.len(),
+        avg_total_ms: average(boots.iter().map(|b| b.total_ms)),
+        avg_userspace_ms: average(boots.iter().filter_map(|b| b.userspace_ms)),
+    }
+}
+
+/// Mean time per unit over the boots it appeared in
+fn unit_averages(boots: &[&StoredBoot]) -> HashMap<String, u64> {
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for boot in boots {
+        for timing in &boot.units {
+            let entry = totals.entry(timing.unit.clone()).or_default();
+            entry.0 += timing.time_ms;
+            entry.1 += 1;
+        }
+    }
+    totals.into_iter().map(|(unit, (sum, count))| (unit, sum / count)).collect()
+}
+
+fn compare_units(before: &[&StoredBoot], after: &[&StoredBoot], unit: Option<&str>) -> Vec<UnitImpact> {
+    if before.is_empty() || after.is_empty() {
+        return Vec::new();
+    }
+    let before_avg = unit_averages(before);
+    let after_avg = unit_averages(after);
+
+    let mut units: Vec<UnitImpact> = before_avg
+        .keys()
+        .chain(after_avg.keys().filter(|u| !before_avg.contains_key(*u)))
+        .map(|u| UnitImpact {
+            unit: u.clone(),
+            before_ms: before_avg.get(u).copied(),
+            after_ms: after_avg.get(u).copied(),
+        })
+        .collect();
+    let delta = |u: &UnitImpact| u.before_ms.unwrap_or(0).abs_diff(u.after_ms.unwrap_or(0));
+    // The toggled unit always comes first, the rest by size of the difference
+    units.sort_by(|a, b| {
+        (Some(b.unit.as_str()) == unit)
+            .cmp(&(Some(a.unit.as_str()) == unit))
+            .then_with(|| delta(b).cmp(&delta(a)))
+            .then_with(|| a.unit.cmp(&b.unit))
+    });
+    units.truncate(UNIT_LIMIT);
+    units
+}
+
+/// Pair each change with the boots around it, newest change first.
+///
+/// Toggles made in the same session share their "after" boots; the window
+/// closes at the first change made after a reboot so later edits are not
+/// credited to earlier ones.
+pub fn compute_impacts(changes: &[StartupChange], boots: &[StoredBoot]) -> Vec<StartupImpact> {
+    let mut impacts: Vec<StartupImpact> = changes
+        .iter()
+        .map(|change| {
+            let before: Vec<&StoredBoot> = {
+                let earlier: Vec<&StoredBoot> = boots.iter().filter(|b| b.booted_at < change.changed_at).collect();
+                earlier[earlier.len().saturating_sub(WINDOW)..].to_vec()
+            };
+
+            let after: Vec<&StoredBoot> = match boots.iter().find(|b| b.booted_at > change.changed_at) {
+                Some(first) => {
+                    let boundary = changes
+                        .iter()
+                        .map(|c| c.changed_at)
+                        .filter(|&t| t > first.booted_at)
+                        .min()
+                        .unwrap_or(i64::MAX);
+                    boots
+                        .iter()
+                        .filter(|b| b.booted_at > change.changed_at && b.booted_at < boundary)
+                        .take(WINDOW)
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+
+            StartupImpact {
+                change_id: change.id,
+                program_id: change.program_id.clone(),
+                program_name: change.program_name.clone(),
+                unit: change.unit.clone(),
+                enabled: change.enabled,
+                changed_at: change.changed_at,
+                before: summarize(&before),
+                after: summarize(&after),
+                units: compare_units(&before, &after, change.unit.as_deref()),
+            }
+        })
+        .collect();
+    impacts.reverse();
+    impacts
+}
+
+/// Log a startup toggle; failures are only logged so the toggle itself still succeeds
+pub fn log_change(app_handle: &tauri::AppHandle, program_id: &str, program_name: &str, unit: Option<&str>, enabled: bool) {
+    if let Err(e) = app_handle.db(|conn| record_change(conn, program_id, program_name, unit, enabled)) {
+        tracing::warn!("Failed to record startup change: {}", e);
+    }
+}
+
+/// Before/after boot times for recent startup program toggles, newest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_startup_impact(app_handle: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<StartupImpact>, String> {
+    let (changes, boots) = app_handle
+        .db(|conn| Ok((load_changes(conn)?, load_boots(conn)?)))
+        .map_err(|e| format!("Failed to load startup history: {}", e))?;
+    let mut impacts = compute_impacts(&changes, &boots);
+    impacts.truncate(limit.unwrap_or(20));
+    Ok(impacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boot(booted_at: i64, total_ms: u64, units: &[(&str, u64)]) -> StoredBoot {
+        StoredBoot {
+            booted_at,
+            total_ms,
+            userspace_ms: Some(total_ms / 2),
+            units: units
+                .iter()
+                .map(|(unit, time_ms)| UnitTiming { unit: unit.to_string(), time_ms: *time_ms, unit_type: "service".to_string() })
+                .collect(),
+        }
+    }
+
+    fn change(id: i64, changed_at: i64, unit: Option<&str>) -> StartupChange {
+        StartupChange {
+            id,
+            program_id: format!("p{}", id),
+            program_name: format!("Program {}", id),
+            unit: unit.map(String::from),
+            enabled: false,
+            changed_at,
+        }
+    }
+
+    #[test]
+    fn test_before_and_after_windows() {
+        let boots = vec![
+            boot(100, 30_000, &[("docker.service", 5000), ("dbus.service", 100)]),
+            boot(200, 32_000, &[("docker.service", 6000), ("dbus.service", 100)]),
+            boot(400, 20_000, &[("dbus.service", 120)]),
+            boot(500, 22_000, &[("dbus.service", 80)]),
+        ];
+        let impacts = compute_impacts(&[change(1, 300, Some("docker.service"))], &boots);
+        assert_eq!(impacts.len(), 1);
+        let impact = &impacts[0];
+        assert_eq!(impact.before, BootWindow { boots: 2, avg_total_ms: Some(31_000), avg_userspace_ms: Some(15_500) });
+        assert_eq!(impact.after.boots, 2);
+        assert_eq!(impact.after.avg_total_ms, Some(21_000));
+        assert_eq!(impact.units[0], UnitImpact { unit: "docker.service".to_string(), before_ms: Some(5500), after_ms: None });
+        assert_eq!(impact.units[1].unit, "dbus.service");
+    }
+
+    #[test]
+    fn test_pending_change_has_no_after_window() {
+        let boots = vec![boot(100, 30_000, &[])];
+        let impacts = compute_impacts(&[change(1, 150, None)], &boots);
+        assert_eq!(impacts[0].before.boots, 1);
+        assert_eq!(impacts[0].after, BootWindow::default());
+        assert!(impacts[0].units.is_empty());
+    }
+
+    #[test]
+    fn test_later_changes_close_the_window() {
+        let boots = vec![boot(100, 30_000, &[]), boot(300, 25_000, &[]), boot(500, 20_000, &[])];
+        // Changes 1 and 2 in the same session, change 3 after the reboot at 300
+        let changes = [change(1, 150, None), change(2, 160, None), change(3, 350, None)];
+        let impacts = compute_impacts(&changes, &boots);
+        assert_eq!(impacts[0].change_id, 3);
+        assert_eq!(impacts[0].after.avg_total_ms, Some(20_000));
+        assert_eq!(impacts[1].after.boots, 1);
+        assert_eq!(impacts[2].after.boots, 1);
+        assert_eq!(impacts[2].after.avg_total_ms, Some(25_000));
+    }
+
+    #[test]
+    fn test_record_and_load_changes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE startup_changes (id INTEGER PRIMARY KEY AUTOINCREMENT, program_id TEXT NOT NULL,
+             program_name TEXT NOT NULL, unit TEXT, enabled INTEGER NOT NULL, changed_at INTEGER NOT NULL);",
+        )
+        .unwrap();
+        record_change(&conn, "systemd_user_docker", "docker.service", Some("docker.service"), false).unwrap();
+        record_change(&conn, "xdg_slack", "Slack", None, true).unwrap();
+        let changes = load_changes(&conn).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].unit.as_deref(), Some("docker.service"));
+        assert!(!changes[0].enabled);
+        assert!(changes[1].enabled);
+    }
+}
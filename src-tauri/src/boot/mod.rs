@@ -0,0 +1,360 @@
+//! Boot time analysis from `systemd-analyze` (time, blame, critical-chain).
+//!
+//! Each boot is stored once, keyed by the kernel boot id, so users can see over
+//! time whether disabling startup items actually made booting faster.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::process::{Command, Stdio};
+
+use crate::db::DbAccess;
+
+pub mod impact;
+
+/// Slowest units kept per boot
+const BLAME_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BootTimes {
+    pub firmware_ms: Option<u64>,
+    pub loader_ms: Option<u64>,
+    pub kernel_ms: Option<u64>,
+    pub initrd_ms: Option<u64>,
+    pub userspace_ms: Option<u64>,
+    pub total_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct UnitTiming {
+    pub unit: String,
+    pub time_ms: u64,
+    pub unit_type: String, // unit suffix: "service", "mount", "device", ...
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CriticalChainEntry {
+    pub unit: String,
+    pub depth: usize,
+    pub activated_at_ms: Option<u64>, // "@" time after boot
+    pub duration_ms: Option<u64>,     // "+" time the unit took to start
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BootAnalysis {
+    pub boot_id: String,
+    pub booted_at: i64,
+    pub times: BootTimes,
+    pub blame: Vec<UnitTiming>,
+    pub user_blame: Vec<UnitTiming>, // systemd --user units (startup manager services)
+    pub critical_chain: Vec<CriticalChainEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BootHistoryEntry {
+    pub boot_id: String,
+    pub booted_at: i64,
+    pub total_ms: u64,
+    pub userspace_ms: Option<u64>,
+}
+
+/// Parse a systemd timespan such as "1min 2.345s", "812ms" or "1h 3min"
+pub fn parse_duration_ms(text: &str) -> Option<u64> {
+    let mut total = 0.0;
+    let mut any = false;
+    for part in text.split_whitespace() {
+        let split = part.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (number, unit) = part.split_at(split);
+        let value: f64 = number.parse().ok()?;
+        let factor = match unit {
+            "us" | "µs" => 0.001,
+            "ms" => 1.0,
+            "s" => 1000.0,
+            "min" => 60_000.0,
+            "h" => 3_600_000.0,
+            _ => return None,
+        };
+        total += value * factor;
+        any = true;
+    }
+    any.then(|| total.round() as u64)
+}
+
+/// `systemd-analyze time`: "Startup finished in 3.1s (firmware) + ... = 25.9s"
+pub fn parse_time(output: &str) -> Option<BootTimes> {
+    let line = output.lines().find(|l| l.starts_with("Startup finished in "))?;
+    let rest = line.trim_start_matches("Startup finished in ");
+    let (phases, total) = rest.rsplit_once(" = ")?;
+
+    let mut times = BootTimes { total_ms: parse_duration_ms(total.trim())?, ..Default::default() };
+    for phase in phases.split(" + ") {
+        let (duration, name) = phase.trim().rsplit_once(" (")?;
+        let ms = parse_duration_ms(duration);
+        match name.trim_end_matches(')') {
+            "firmware" => times.firmware_ms = ms,
+            "loader" => times.loader_ms = ms,
+            "kernel" => times.kernel_ms = ms,
+            "initrd" => times.initrd_ms = ms,
+            "userspace" => times.userspace_ms = ms,
+            _ => {}
+        }
+    }
+    Some(times)
+}
+
+/// `systemd-analyze blame`: "<timespan> <unit>" per line, slowest first
+pub fn parse_blame(output: &str) -> Vec<UnitTiming> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (duration, unit) = line.rsplit_once(' ')?;
+            let unit_type = unit.rsplit_once('.').map(|(_, t)| t.to_string())?;
+            Some(UnitTiming { unit: unit.to_string(), time_ms: parse_duration_ms(duration.trim())?, unit_type })
+        })
+        .collect()
+}
+
+/// `systemd-analyze critical-chain`: tree lines like "  └─docker.service @5.2s +4.8s"
+pub fn parse_critical_chain(output: &str) -> Vec<CriticalChainEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let start = line.find(|c: char| c.is_ascii_alphanumeric())?;
+            let prefix = &line[..start];
+            let (unit, timing) = line[start..].split_once(' ').unwrap_or((&line[start..], ""));
+            // Header lines ("The time when unit became active...") are not unit names
+            if !unit.contains('.') {
+                return None;
+            }
+            // Timespans contain spaces: "@1min 7.093s +23.034s"
+            let (activated, took) = timing.split_once('+').unwrap_or((timing, ""));
+            Some(CriticalChainEntry {
+                unit: unit.to_string(),
+                depth: prefix.chars().count() / 2,
+                activated_at_ms: activated.trim().strip_prefix('@').and_then(parse_duration_ms),
+                duration_ms: parse_duration_ms(took.trim()),
+            })
+        })
+        .collect()
+}
+
+fn systemd_analyze(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("systemd-analyze")
+        .args(args)
+        .arg("--no-pager")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run systemd-analyze: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn current_boot_id() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/random/boot_id").ok().map(|s| s.trim().to_string())
+}
+
+fn booted_at() -> i64 {
+    let uptime = std::fs::read_to_string("/proc/uptime")
+        .ok()
+        .and_then(|s| s.split_whitespace().next().and_then(|u| u.parse::<f64>().ok()))
+        .unwrap_or(0.0);
+    chrono::Utc::now().timestamp() - uptime as i64
+}
+
+/// Blocking; call from a blocking task
+pub fn analyze_current_boot() -> Result<BootAnalysis, String> {
+    let boot_id = current_boot_id().ok_or("Cannot read the current boot id")?;
+    let time_output = systemd_analyze(&["time"]).map_err(|e| format!("Boot analysis unavailable: {}", e))?;
+    let times = parse_time(&time_output).ok_or("Unrecognized systemd-analyze time output")?;
+
+    let mut blame = parse_blame(&systemd_analyze(&["blame"]).unwrap_or_default());
+    blame.truncate(BLAME_LIMIT);
+    let mut user_blame = parse_blame(&systemd_analyze(&["--user", "blame"]).unwrap_or_default());
+    user_blame.truncate(BLAME_LIMIT);
+    let critical_chain = parse_critical_chain(&systemd_analyze(&["critical-chain"]).unwrap_or_default());
+
+    Ok(BootAnalysis { boot_id, booted_at: booted_at(), times, blame, user_blame, critical_chain })
+}
+
+pub fn store_analysis(conn: &Connection, analysis: &BootAnalysis) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO boot_analyses
+         (boot_id, booted_at, recorded_at, firmware_ms, loader_ms, kernel_ms, initrd_ms, userspace_ms, total_ms,
+          blame, user_blame, critical_chain)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            analysis.boot_id,
+            analysis.booted_at,
+            chrono::Utc::now().timestamp(),
+            analysis.times.firmware_ms.map(|v| v as i64),
+            analysis.times.loader_ms.map(|v| v as i64),
+            analysis.times.kernel_ms.map(|v| v as i64),
+            analysis.times.initrd_ms.map(|v| v as i64),
+            analysis.times.userspace_ms.map(|v| v as i64),
+            analysis.times.total_ms as i64,
+            serde_json::to_string(&analysis.blame).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&analysis.user_blame).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&analysis.critical_chain).unwrap_or_else(|_| "[]".to_string()),
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn is_recorded(conn: &Connection, boot_id: &str) -> rusqlite::Result<bool> {
+    conn.query_row("SELECT 1 FROM boot_analyses WHERE boot_id = ?1", [boot_id], |_| Ok(()))
+        .optional()
+        .map(|r| r.is_some())
+}
+
+pub fn query_history(conn: &Connection, limit: usize) -> rusqlite::Result<Vec<BootHistoryEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT boot_id, booted_at, total_ms, userspace_ms FROM boot_analyses ORDER BY booted_at DESC LIMIT ?1",
+    )?;
+    let entries = stmt
+        .query_map([limit as i64], |row| {
+            Ok(BootHistoryEntry {
+                boot_id: row.get(0)?,
+                booted_at: row.get(1)?,
+                total_ms: row.get::<_, i64>(2)? as u64,
+                userspace_ms: row.get::<_, Option<i64>>(3)?.map(|v| v as u64),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+async fn analyze(app_handle: &tauri::AppHandle) -> Result<BootAnalysis, String> {
+    let analysis = tokio::task::spawn_blocking(analyze_current_boot)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    app_handle
+        .db(|conn| store_analysis(conn, &analysis))
+        .map_err(|e| format!("Failed to store boot analysis: {}", e))?;
+    Ok(analysis)
+}
+
+/// Record the current boot once; called at app startup so history builds up without a visit to the view
+pub async fn record_current_boot(app_handle: &tauri::AppHandle) {
+    let Some(boot_id) = current_boot_id() else { return };
+    if app_handle.db(|conn| is_recorded(conn, &boot_id)).unwrap_or(false) {
+        return;
+    }
+    // Fails while boot is still in progress ("Bootup is not yet finished"); retried next launch
+    if let Err(e) = analyze(app_handle).await {
+        tracing::debug!("Boot analysis not recorded: {}", e);
+    }
+}
+
+/// Timing of the current boot from systemd-analyze (time, blame, critical-chain)
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_boot_analysis(app_handle: tauri::AppHandle) -> Result<BootAnalysis, String> {
+    analyze(&app_handle).await
+}
+
+/// Recorded boots, newest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_boot_history(app_handle: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<BootHistoryEntry>, String> {
+    app_handle
+        .db(|conn| query_history(conn, limit.unwrap_or(30)))
+        .map_err(|e| format!("Failed to load boot history: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration_ms("812ms"), Some(812));
+        assert_eq!(parse_duration_ms("2.345s"), Some(2345));
+        assert_eq!(parse_duration_ms("1min 2.5s"), Some(62_500));
+        assert_eq!(parse_duration_ms("1h 3min"), Some(3_780_000));
+        assert_eq!(parse_duration_ms("fast"), None);
+        assert_eq!(parse_duration_ms(""), None);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let output = "Startup finished in 7.012s (firmware) + 3.100s (loader) + 2.500s (kernel) + 3.2s (initrd) + 1min 10.1s (userspace) = 1min 25.912s\n\
+                      graphical.target reached after 1min 9.8s in userspace.\n";
+        let times = parse_time(output).unwrap();
+        assert_eq!(times.firmware_ms, Some(7012));
+        assert_eq!(times.initrd_ms, Some(3200));
+        assert_eq!(times.userspace_ms, Some(70_100));
+        assert_eq!(times.total_ms, 85_912);
+
+        // No firmware/loader phases on VMs and BIOS systems
+        let times = parse_time("Startup finished in 1.2s (kernel) + 4.5s (userspace) = 5.7s\n").unwrap();
+        assert_eq!(times.firmware_ms, None);
+        assert_eq!(times.total_ms, 5700);
+        assert!(parse_time("Bootup is not yet finished.").is_none());
+    }
+
+    #[test]
+    fn test_parse_blame() {
+        let output = "     1min 2.100s NetworkManager-wait-online.service\n   812ms dev-nvme0n1p2.device\n  45ms home.mount\n";
+        let blame = parse_blame(output);
+        assert_eq!(blame.len(), 3);
+        assert_eq!(blame[0].unit, "NetworkManager-wait-online.service");
+        assert_eq!(blame[0].time_ms, 62_100);
+        assert_eq!(blame[1].unit_type, "device");
+        assert_eq!(blame[2].unit_type, "mount");
+    }
+
+    #[test]
+    fn test_parse_critical_chain() {
+        let output = "The time when unit became active or started is printed after the \"@\" character.\n\
+                      The time the unit took to start is printed after the \"+\" character.\n\
+                      \n\
+                      graphical.target @10.1s\n\
+                      └─multi-user.target @1min 10.1s\n  \
+                        └─docker.service @1min 5.2s +4.8s\n";
+        let chain = parse_critical_chain(output);
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[0].depth, 0);
+        assert_eq!(chain[2].unit, "docker.service");
+        assert_eq!(chain[2].depth, 2);
+        assert_eq!(chain[2].activated_at_ms, Some(65_200));
+        assert_eq!(chain[2].duration_ms, Some(4800));
+        assert_eq!(chain[1].activated_at_ms, Some(70_100));
+        assert_eq!(chain[1].duration_ms, None);
+    }
+
+    #[test]
+    fn test_store_and_query_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE boot_analyses (boot_id TEXT PRIMARY KEY, booted_at INTEGER NOT NULL, recorded_at INTEGER NOT NULL,
+             firmware_ms INTEGER, loader_ms INTEGER, kernel_ms INTEGER, initrd_ms INTEGER, userspace_ms INTEGER,
+             total_ms INTEGER NOT NULL, blame TEXT NOT NULL, user_blame TEXT NOT NULL, critical_chain TEXT NOT NULL);",
+        )
+        .unwrap();
+        for (id, at, total) in [("a", 100, 30_000), ("b", 200, 20_000)] {
+            let analysis = BootAnalysis {
+                boot_id: id.to_string(),
+                booted_at: at,
+                times: BootTimes { total_ms: total, ..Default::default() },
+                blame: Vec::new(),
+                user_blame: Vec::new(),
+                critical_chain: Vec::new(),
+            };
+            store_analysis(&conn, &analysis).unwrap();
+        }
+        assert!(is_recorded(&conn, "a").unwrap());
+        assert!(!is_recorded(&conn, "c").unwrap());
+        let history = query_history(&conn, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].boot_id, "b");
+        assert_eq!(history[0].total_ms, 20_000);
+    }
+}
@@ -0,0 +1,387 @@
+//! Browser storage per profile. Firefox, Chrome and Chromium keep a separate
+//! cache, site storage and history for every profile; listing them per profile
+//! and per kind lets one profile be cleaned without touching another, and the
+//! cache be cleared without signing out of every site.
+
+use std::path::{Path, PathBuf};
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::{format_bytes, ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Browser";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Gecko,
+    Chromium,
+}
+
+/// A browser's profile folder and the folder its caches go to, relative to home
+struct BrowserDef {
+    name: &'static str,
+    engine: Engine,
+    data_dir: &'static str,
+    cache_dir: &'static str,
+}
+
+const BROWSERS: &[BrowserDef] = &[
+    BrowserDef { name: "Firefox", engine: Engine::Gecko, data_dir: ".mozilla/firefox", cache_dir: ".cache/mozilla/firefox" },
+    BrowserDef {
+        name: "Firefox (snap)",
+        engine: Engine::Gecko,
+        data_dir: "snap/firefox/common/.mozilla/firefox",
+        cache_dir: "snap/firefox/common/.cache/mozilla/firefox",
+    },
+    BrowserDef { name: "Google Chrome", engine: Engine::Chromium, data_dir: ".config/google-chrome", cache_dir: ".cache/google-chrome" },
+    BrowserDef { name: "Chromium", engine: Engine::Chromium, data_dir: ".config/chromium", cache_dir: ".cache/chromium" },
+];
+
+/// What a group of profile files holds, from harmless to lose to personal data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    Cache,
+    ServiceWorkers,
+    IndexedDb,
+    History,
+}
+
+impl StorageKind {
+    pub const ALL: [StorageKind; 4] = [StorageKind::Cache, StorageKind::ServiceWorkers, StorageKind::IndexedDb, StorageKind::History];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StorageKind::Cache => "Cache",
+            StorageKind::ServiceWorkers => "Service workers",
+            StorageKind::IndexedDb => "IndexedDB",
+            StorageKind::History => "History",
+        }
+    }
+
+    fn risk_level(self) -> u8 {
+        match self {
+            StorageKind::Cache => 0,
+            StorageKind::ServiceWorkers => 1,
+            StorageKind::IndexedDb => 2,
+            StorageKind::History => 3,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            StorageKind::Cache => "Downloaded pages, scripts and images, fetched again as needed",
+            StorageKind::ServiceWorkers => "Offline copies of web apps; sites reinstall them on the next visit",
+            StorageKind::IndexedDb => "Site databases: offline mail, documents and app state are lost",
+            StorageKind::History => "Visited pages and their icons; cannot be recovered",
+        }
+    }
+}
+
+/// A browser profile and where its storage of each kind is
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserProfile {
+    pub browser: &'static str,
+    pub name: String,
+    pub path: PathBuf,
+    pub cache_path: PathBuf,
+    pub running: bool,
+    engine: Engine,
+}
+
+impl BrowserProfile {
+    /// The files and folders holding storage of `kind`, as far as they exist
+    pub fn storage(&self, kind: StorageKind) -> Vec<PathBuf> {
+        let (profile, cache) = (&self.path, &self.cache_path);
+        let paths: Vec<PathBuf> = match (self.engine, kind) {
+            (Engine::Gecko, StorageKind::Cache) => vec![cache.join("cache2"), cache.join("startupCache"), cache.join("thumbnails")],
+            (Engine::Gecko, StorageKind::ServiceWorkers) => site_storage(profile, "cache"),
+            (Engine::Gecko, StorageKind::IndexedDb) => site_storage(profile, "idb"),
+            (Engine::Gecko, StorageKind::History) => ["places.sqlite", "places.sqlite-wal", "favicons.sqlite", "favicons.sqlite-wal"]
+                .iter()
+                .map(|f| profile.join(f))
+                .collect(),
+            (Engine::Chromium, StorageKind::Cache) => vec![cache.join("Cache"), cache.join("Code Cache"), profile.join("GPUCache")],
+            (Engine::Chromium, StorageKind::ServiceWorkers) => vec![profile.join("Service Worker")],
+            (Engine::Chromium, StorageKind::IndexedDb) => vec![profile.join("IndexedDB")],
+            (Engine::Chromium, StorageKind::History) => ["History", "History-journal", "Visited Links", "Top Sites", "Favicons"]
+                .iter()
+                .map(|f| profile.join(f))
+                .collect(),
+        };
+        paths.into_iter().filter(|p| p.symlink_metadata().is_ok()).collect()
+    }
+}
+
+/// Per-site folders of one kind in a Firefox profile, `storage/default/<origin>/<kind>`
+fn site_storage(profile: &Path, kind: &str) -> Vec<PathBuf> {
+    let Ok(origins) = std::fs::read_dir(profile.join("storage/default")) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = origins.filter_map(|e| e.ok()).map(|e| e.path().join(kind)).filter(|p| p.is_dir()).collect();
+    paths.sort();
+    paths
+}
+
+/// Profiles listed in a Firefox `profiles.ini` as (name, path, relative)
+fn parse_profiles_ini(ini: &str) -> Vec<(String, String, bool)> {
+    let mut profiles = Vec::new();
+    let mut current: Option<(Option<String>, Option<String>, bool)> = None;
+    let mut flush = |current: &mut Option<(Option<String>, Option<String>, bool)>| {
+        if let Some((name, Some(path), relative)) = current.take() {
+            profiles.push((name.unwrap_or_else(|| path.clone()), path, relative));
+        }
+    };
+    for line in ini.lines().map(str::trim) {
+        if line.starts_with('[') {
+            flush(&mut current);
+            current = line.starts_with("[Profile").then_some((None, None, true));
+        } else if let (Some(section), Some((key, value))) = (current.as_mut(), line.split_once('=')) {
+            match key.trim() {
+                "Name" => section.0 = Some(value.trim().to_string()),
+                "Path" => section.1 = Some(value.trim().to_string()),
+                "IsRelative" => section.2 = value.trim() != "0",
+                _ => {}
+            }
+        }
+    }
+    flush(&mut current);
+    profiles
+}
+
+/// Display names of Chromium profiles from `Local State`, by profile folder
+fn chromium_profile_name(local_state: &serde_json::Value, dir: &str) -> Option<String> {
+    local_state.pointer(&format!("/profile/info_cache/{}/name", dir.replace('~', "~0").replace('/', "~1")))?.as_str().map(str::to_string)
+}
+
+fn gecko_profiles(def: &BrowserDef, home: &Path) -> Vec<BrowserProfile> {
+    let data_dir = home.join(def.data_dir);
+    let Ok(ini) = std::fs::read_to_string(data_dir.join("profiles.ini")) else {
+        return Vec::new();
+    };
+    parse_profiles_ini(&ini)
+        .into_iter()
+        .map(|(name, path, relative)| {
+            let path = if relative { data_dir.join(&path) } else { PathBuf::from(&path) };
+            // The cache folder mirrors the profile's path below the data folder
+            let cache_path = match path.strip_prefix(&data_dir) {
+                Ok(rel) => home.join(def.cache_dir).join(rel),
+                Err(_) => path.clone(),
+            };
+            // Firefox holds `lock`, a symlink to its host and pid, while the profile is open
+            let running = path.join("lock").symlink_metadata().is_ok();
+            BrowserProfile { browser: def.name, name, path, cache_path, running, engine: Engine::Gecko }
+        })
+        .filter(|profile| profile.path.is_dir())
+        .collect()
+}
+
+fn chromium_profiles(def: &BrowserDef, home: &Path) -> Vec<BrowserProfile> {
+    let data_dir = home.join(def.data_dir);
+    let Ok(entries) = std::fs::read_dir(&data_dir) else {
+        return Vec::new();
+    };
+    let local_state: serde_json::Value = std::fs::read_to_string(data_dir.join("Local State"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    // Chrome holds `SingletonLock` in the data folder while any profile is open
+    let running = data_dir.join("SingletonLock").symlink_metadata().is_ok();
+
+    let mut profiles: Vec<BrowserProfile> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.join("Preferences").is_file())
+        .map(|path| {
+            let dir = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            BrowserProfile {
+                browser: def.name,
+                name: chromium_profile_name(&local_state, &dir).unwrap_or_else(|| dir.clone()),
+                cache_path: home.join(def.cache_dir).join(&dir),
+                path,
+                running,
+                engine: Engine::Chromium,
+            }
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.path.cmp(&b.path));
+    profiles
+}
+
+/// Profiles of every supported browser installed for the user in `home`
+pub fn find_profiles(home: &Path) -> Vec<BrowserProfile> {
+    BROWSERS
+        .iter()
+        .flat_map(|def| match def.engine {
+            Engine::Gecko => gecko_profiles(def, home),
+            Engine::Chromium => chromium_profiles(def, home),
+        })
+        .collect()
+}
+
+fn path_size(path: &Path, ctx: &ScanContext) -> u64 {
+    match path.symlink_metadata() {
+        Ok(meta) if meta.is_file() => meta.len(),
+        Ok(meta) if meta.is_dir() => ctx.dir_size(path),
+        _ => 0,
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+pub struct BrowserProfileScanner;
+
+impl Scanner for BrowserProfileScanner {
+    fn id(&self) -> &'static str {
+        "browser_profiles"
+    }
+
+    fn name(&self) -> &'static str {
+        "Browser profiles"
+    }
+
+    fn description(&self) -> &'static str {
+        "Cache, site storage and history of each Firefox, Chrome and Chromium profile"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_caches && dirs::home_dir().is_some_and(|home| !find_profiles(&home).is_empty())
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+
+        let profiles = find_profiles(&home);
+        let mut items = Vec::new();
+        for (index, profile) in profiles.iter().enumerate() {
+            ctx.check_cancelled()?;
+            if !ctx.enters(&profile.path) {
+                continue;
+            }
+            ctx.progress((index * 100 / profiles.len()) as u8, &format!("Measuring {} profile {}...", profile.browser, profile.name));
+
+            let mut kinds = Vec::new();
+            for kind in StorageKind::ALL {
+                let children: Vec<ScanItem> = profile
+                    .storage(kind)
+                    .into_iter()
+                    .filter(|path| ctx.allows(path))
+                    .map(|path| {
+                        let size = path_size(&path, ctx);
+                        let name = match path.strip_prefix(&profile.path) {
+                            Ok(rel) => rel.to_string_lossy().to_string(),
+                            Err(_) => file_name(&path),
+                        };
+                        (path, name, size)
+                    })
+                    .filter(|(_, _, size)| *size > 0)
+                    .map(|(path, name, size)| new_item(
+                        name,
+                        &path,
+                        size,
+                        if path.is_dir() { "directory" } else { "file" },
+                        CATEGORY,
+                        kind.risk_level(),
+                        kind.description(),
+                    ))
+                    .collect();
+                let size: u64 = children.iter().map(|c| c.size).sum();
+                if size == 0 {
+                    continue;
+                }
+                let mut description = kind.description().to_string();
+                // Removing anything but the cache from under a running browser corrupts its databases
+                let mut risk_level = kind.risk_level();
+                if profile.running && kind != StorageKind::Cache {
+                    risk_level = (risk_level + 1).min(3);
+                    description = format!("{}. Close {} first", description, profile.browser);
+                }
+                // Stands for the paths listed as children
+                let mut item = new_item(
+                    kind.label().to_string(),
+                    &profile.path,
+                    size,
+                    "browser_storage",
+                    CATEGORY,
+                    risk_level,
+                    &description,
+                );
+                item.children = Some(children);
+                kinds.push(item);
+            }
+            if kinds.is_empty() {
+                continue;
+            }
+
+            let summary: Vec<String> = kinds.iter().map(|k| format!("{} {}", k.name.to_lowercase(), format_bytes(k.size))).collect();
+            // The profile itself is never removed, only the storage listed as children
+            let mut item = new_item(
+                format!("{} ({})", profile.browser, profile.name),
+                &profile.path,
+                kinds.iter().map(|k| k.size).sum(),
+                "browser_profile",
+                CATEGORY,
+                kinds.iter().map(|k| k.risk_level).min().unwrap_or(0),
+                &format!("{}{}", summary.join(", "), if profile.running { "; browser is open" } else { "" }),
+            );
+            item.children = Some(kinds);
+            items.push(item);
+        }
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_profiles_ini() {
+        let ini = "[Install4F96D1932A9F858E]\nDefault=abc.default-release\n\n\
+                   [Profile1]\nName=work\nIsRelative=1\nPath=xyz.work\n\n\
+                   [Profile0]\nName=default-release\nIsRelative=0\nPath=/data/ff/abc.default-release\nDefault=1\n\n\
+                   [General]\nStartWithLastProfile=1\n";
+        assert_eq!(
+            parse_profiles_ini(ini),
+            vec![
+                ("work".to_string(), "xyz.work".to_string(), true),
+                ("default-release".to_string(), "/data/ff/abc.default-release".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_profiles_and_storage() {
+        let home = tempfile::TempDir::new().unwrap();
+        let ff = home.path().join(".mozilla/firefox");
+        fs::create_dir_all(ff.join("abc.default/storage/default/https+++mail.example/idb")).unwrap();
+        fs::create_dir_all(ff.join("abc.default/storage/default/https+++app.example/cache")).unwrap();
+        fs::write(ff.join("abc.default/places.sqlite"), "history").unwrap();
+        fs::write(ff.join("profiles.ini"), "[Profile0]\nName=default\nIsRelative=1\nPath=abc.default\n").unwrap();
+        fs::create_dir_all(home.path().join(".cache/mozilla/firefox/abc.default/cache2")).unwrap();
+
+        let chrome = home.path().join(".config/google-chrome");
+        for profile in ["Default", "Profile 1"] {
+            fs::create_dir_all(chrome.join(profile).join("IndexedDB")).unwrap();
+            fs::write(chrome.join(profile).join("Preferences"), "{}").unwrap();
+        }
+        fs::create_dir_all(chrome.join("Crashpad")).unwrap();
+        fs::write(chrome.join("Local State"), r#"{"profile":{"info_cache":{"Profile 1":{"name":"Work"}}}}"#).unwrap();
+
+        let profiles = find_profiles(home.path());
+        let names: Vec<(&str, &str)> = profiles.iter().map(|p| (p.browser, p.name.as_str())).collect();
+        assert_eq!(names, vec![("Firefox", "default"), ("Google Chrome", "Default"), ("Google Chrome", "Work")]);
+
+        let firefox = &profiles[0];
+        assert_eq!(firefox.cache_path, home.path().join(".cache/mozilla/firefox/abc.default"));
+        assert_eq!(firefox.storage(StorageKind::Cache), vec![firefox.cache_path.join("cache2")]);
+        assert_eq!(firefox.storage(StorageKind::IndexedDb), vec![ff.join("abc.default/storage/default/https+++mail.example/idb")]);
+        assert_eq!(firefox.storage(StorageKind::ServiceWorkers), vec![ff.join("abc.default/storage/default/https+++app.example/cache")]);
+        assert_eq!(firefox.storage(StorageKind::History), vec![ff.join("abc.default/places.sqlite")]);
+        assert_eq!(profiles[2].storage(StorageKind::IndexedDb), vec![chrome.join("Profile 1/IndexedDB")]);
+        assert!(profiles[2].storage(StorageKind::History).is_empty());
+    }
+}
@@ -32,7 +32,8 @@ pub struct CacheManager {
 pub struct ScanCacheEntry {
     pub total_size: u64,
     pub total_items: usize,
-    pub timestamp: String,
+    #[serde(deserialize_with = "crate::timestamps::deserialize")]
+    pub timestamp: i64, // UTC unix seconds
 }
 
 impl CacheManager {
@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::db::DbAccess;
+use crate::operations::{guard, OperationPlan};
+use crate::read_only;
+use crate::trash;
+
+/// Caches under ~/.cache that regrow right after being cleared and are safe to keep
+/// on volatile storage (they are rebuilt on demand), with the processes (as named in
+/// /proc/<pid>/comm) of the app writing to each
+const REDIRECTABLE_CACHES: &[(&str, &str, &[&str])] = &[
+    ("thumbnails", "Thumbnails", &[]),
+    ("mozilla", "Firefox", &["firefox", "firefox-bin"]),
+    ("google-chrome", "Google Chrome", &["chrome"]),
+    ("chromium", "Chromium", &["chromium", "chromium-browse"]),
+    ("BraveSoftware", "Brave", &["brave"]),
+    ("microsoft-edge", "Microsoft Edge", &["msedge"]),
+    ("vivaldi", "Vivaldi", &["vivaldi-bin"]),
+    ("opera", "Opera", &["opera"]),
+];
+
+/// Subdirectory of $XDG_RUNTIME_DIR holding redirected caches
+const VOLATILE_DIR_NAME: &str = "pulito-cache";
+
+/// Read by `systemd-tmpfiles --user` at login, relative to the config directory
+const TMPFILES_CONFIG: &str = "user-tmpfiles.d/pulito.conf";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CacheRedirectSuggestion {
+    pub name: String, // directory name under ~/.cache, used as the identifier
+    pub label: String,
+    pub path: String,
+    pub size: u64,
+    pub weekly_growth: u64, // bytes added in the last 7 days, from cache_events
+    pub redirected: bool,
+    pub target: Option<String>,
+}
+
+/// One /proc/mounts line
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountEntry {
+    pub source: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    pub options: String,
+}
+
+/// Every /proc/mounts line, in order
+pub fn parse_mounts(mounts: &str) -> Vec<MountEntry> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (source, mount_point, fs_type) = (fields.next()?, fields.next()?, fields.next()?);
+            Some(MountEntry {
+                source: source.to_string(),
+                // /proc/mounts escapes spaces as \040
+                mount_point: mount_point.replace("\\040", " "),
+                fs_type: fs_type.to_string(),
+                options: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Find the mount containing `path`, given /proc/mounts content
+pub fn mount_entry(mounts: &str, path: &Path) -> Option<MountEntry> {
+    let mut best: Option<MountEntry> = None;
+    for entry in parse_mounts(mounts) {
+        let longer = best.as_ref().map(|m| entry.mount_point.len() > m.mount_point.len()).unwrap_or(true);
+        if path.starts_with(&entry.mount_point) && longer {
+            best = Some(entry);
+        }
+    }
+    best
+}
+
+/// Find the filesystem type of the mount containing `path`, given /proc/mounts content
+pub fn mount_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    mount_entry(mounts, path).map(|m| m.fs_type)
+}
+
+/// The volatile base directory ($XDG_RUNTIME_DIR/pulito-cache), only if it is backed by tmpfs
+fn volatile_base_dir() -> Result<PathBuf, String> {
+    let runtime_dir = dirs::runtime_dir().ok_or("XDG_RUNTIME_DIR is not set")?;
+    let mounts = std::fs::read_to_string("/proc/mounts")
+        .map_err(|e| format!("Failed to read /proc/mounts: {}", e))?;
+
+    match mount_fs_type(&mounts, &runtime_dir).as_deref() {
+        Some("tmpfs") => Ok(runtime_dir.join(VOLATILE_DIR_NAME)),
+        other => Err(format!(
+            "{} is not on tmpfs ({}), refusing to redirect caches there",
+            runtime_dir.display(),
+            other.unwrap_or("unknown")
+        )),
+    }
+}
+
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::create_dir_all(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o700))
+}
+
+/// Path, label and writing processes of the redirectable cache `name`
+pub fn lookup_cache(name: &str) -> Result<(PathBuf, &'static str, &'static [&'static str]), String> {
+    let (dir, label, owners) = REDIRECTABLE_CACHES
+        .iter()
+        .find(|(dir, _, _)| *dir == name)
+        .ok_or_else(|| format!("Unknown cache: {}", name))?;
+    let cache_dir = dirs::cache_dir().ok_or("Cannot determine cache directory")?;
+    Ok((cache_dir.join(dir), label, owners))
+}
+
+fn running_processes() -> HashSet<String> {
+    std::fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_string())
+        .collect()
+}
+
+fn first_running<'a>(owners: &[&'a str], running: &HashSet<String>) -> Option<&'a str> {
+    owners.iter().copied().find(|owner| running.contains(*owner))
+}
+
+/// Refuse while the app writing to a cache runs: it keeps files open in the
+/// directory that is removed and recreates it under the symlink
+pub fn check_owner_closed(label: &str, owners: &[&str]) -> Result<(), String> {
+    match first_running(owners, &running_processes()) {
+        Some(process) => Err(format!("{} is running ({}); close it before moving its cache", label, process)),
+        None => Ok(()),
+    }
+}
+
+fn load_redirections(app_handle: &tauri::AppHandle) -> Result<Vec<(String, String)>, String> {
+    app_handle.db(|conn| {
+        let mut stmt = conn.prepare("SELECT path, target FROM cache_redirections")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    }).map_err(|e| format!("Failed to load cache redirections: {}", e))
+}
+
+/// tmpfiles.d lines creating `targets`, written relative to the runtime dir (%t)
+fn tmpfiles_config(targets: &[String], runtime_dir: &Path) -> String {
+    let mut config = String::from("# Written by Pulito: folders redirected caches point to, recreated at login\n");
+    config.push_str(&format!("d %t/{} 0700 - - -\n", VOLATILE_DIR_NAME));
+    for target in targets {
+        let line_path = match Path::new(target).strip_prefix(runtime_dir) {
+            Ok(relative) => format!("%t/{}", relative.display()),
+            Err(_) => target.clone(),
+        };
+        config.push_str(&format!("d {} 0700 - - -\n", line_path));
+    }
+    config
+}
+
+/// Keep the user tmpfiles.d entry in line with the recorded redirections, so their
+/// targets exist from login on and not only once Pulito has started
+fn sync_login_targets(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let config_path = dirs::config_dir().ok_or("Cannot determine config directory")?.join(TMPFILES_CONFIG);
+    let targets: Vec<String> = load_redirections(app_handle)?.into_iter().map(|(_, target)| target).collect();
+    if targets.is_empty() {
+        return match std::fs::remove_file(&config_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove {}: {}", config_path.display(), e)),
+            _ => Ok(()),
+        };
+    }
+
+    let runtime_dir = dirs::runtime_dir().ok_or("XDG_RUNTIME_DIR is not set")?;
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&config_path, tmpfiles_config(&targets, &runtime_dir))
+        .map_err(|e| format!("Failed to write {}: {}", config_path.display(), e))
+}
+
+/// Recreate tmpfs targets for redirected caches. The runtime dir is wiped on logout/reboot,
+/// which would otherwise leave dangling symlinks in ~/.cache; the tmpfiles.d entry covers
+/// that from login on, this covers sessions started before it existed. Called during app setup.
+pub fn restore_redirect_targets(app_handle: &tauri::AppHandle) {
+    let redirections = match load_redirections(app_handle) {
+        Ok(redirections) => redirections,
+        Err(e) => {
+            tracing::warn!("{}", e);
+            return;
+        }
+    };
+
+    for (path, target) in redirections {
+        let target = PathBuf::from(target);
+        if !target.exists() {
+            if let Err(e) = create_private_dir(&target) {
+                tracing::warn!("Failed to recreate cache redirect target {} for {}: {}", target.display(), path, e);
+            } else {
+                tracing::debug!("Recreated cache redirect target {}", target.display());
+            }
+        }
+    }
+    if let Err(e) = sync_login_targets(app_handle) {
+        tracing::warn!("{}", e);
+    }
+}
+
+/// Suggest caches worth moving to tmpfs, with their current size and weekly regrowth
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_cache_redirect_suggestions(app_handle: tauri::AppHandle) -> Result<Vec<CacheRedirectSuggestion>, String> {
+    let redirections = load_redirections(&app_handle)?;
+    let ignored = crate::ignores::load_active(&app_handle);
+    let week_ago = chrono::Utc::now().timestamp() - 7 * 24 * 3600;
+    let mut suggestions = Vec::new();
+
+    for (name, _, _) in REDIRECTABLE_CACHES {
+        let (path, label, _) = lookup_cache(name)?;
+        let path_str = path.to_string_lossy().to_string();
+        let target = redirections.iter().find(|(p, _)| *p == path_str).map(|(_, t)| t.clone());
+
+        if target.is_none() && !path.is_dir() {
+            continue;
+        }
+        // Caches already moved are still listed so they can be undone
+        if target.is_none() && ignored.ignores(&path_str, "Cache") {
+            continue;
+        }
+
+        let size_path = path.clone();
+        let size = tokio::task::spawn_blocking(move || trash::get_dir_size(&size_path))
+            .await
+            .unwrap_or(0);
+
+        let like_pattern = format!("{}%", path_str);
+        let weekly_growth = app_handle.db(|conn| {
+            conn.query_row(
+                "SELECT COALESCE(SUM(size_change), 0) FROM cache_events WHERE path LIKE ?1 AND timestamp >= ?2 AND size_change > 0",
+                rusqlite::params![like_pattern, week_ago],
+                |row| row.get::<_, i64>(0),
+            )
+        }).unwrap_or(0).max(0) as u64;
+
+        suggestions.push(CacheRedirectSuggestion {
+            name: name.to_string(),
+            label: label.to_string(),
+            path: path_str,
+            size,
+            weekly_growth,
+            redirected: target.is_some(),
+            target,
+        });
+    }
+
+    // Fastest regrowing caches first - they benefit most from tmpfs
+    suggestions.sort_by(|a, b| b.weekly_growth.cmp(&a.weekly_growth).then(b.size.cmp(&a.size)));
+    Ok(suggestions)
+}
+
+/// Replace a cache directory with a symlink into tmpfs. Existing cache contents are
+/// discarded (they are regenerated); the redirection is recorded so it can be reverted.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn enable_cache_redirect(
+    app_handle: tauri::AppHandle,
+    name: String,
+    confirmation_token: String,
+) -> Result<CacheRedirectSuggestion, String> {
+    let (path, label, owners) = lookup_cache(&name)?;
+    check_owner_closed(label, owners)?;
+    guard::authorize(&app_handle, &OperationPlan::RedirectCache { name: name.clone() }, &confirmation_token)?;
+    let target = volatile_base_dir()?.join(&name);
+
+    let metadata = std::fs::symlink_metadata(&path)
+        .map_err(|e| format!("Cannot access {}: {}", path.display(), e))?;
+    if metadata.file_type().is_symlink() {
+        return Err(format!("{} is already a symlink", path.display()));
+    }
+    if !metadata.is_dir() {
+        return Err(format!("{} is not a directory", path.display()));
+    }
+
+    tracing::info!("Redirecting {} to {}", path.display(), target.display());
+
+    let original_size = trash::get_dir_size(&path);
+    create_private_dir(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    std::fs::remove_dir_all(&path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    if let Err(e) = std::os::unix::fs::symlink(&target, &path) {
+        // Leave a usable (empty) cache directory behind rather than nothing
+        let _ = std::fs::create_dir_all(&path);
+        return Err(format!("Failed to create symlink {}: {}", path.display(), e));
+    }
+
+    let path_str = path.to_string_lossy().to_string();
+    let target_str = target.to_string_lossy().to_string();
+    app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO cache_redirections (path, target, original_size, created_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![path_str, target_str, original_size as i64, chrono::Utc::now().timestamp()],
+        )
+    }).map_err(|e| format!("Failed to record cache redirection: {}", e))?;
+    if let Err(e) = sync_login_targets(&app_handle) {
+        tracing::warn!("{}", e);
+    }
+
+    Ok(CacheRedirectSuggestion {
+        name,
+        label: label.to_string(),
+        path: path_str,
+        size: 0,
+        weekly_growth: 0,
+        redirected: true,
+        target: Some(target_str),
+    })
+}
+
+/// Undo a redirection: remove the symlink, restore an empty on-disk cache directory
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn disable_cache_redirect(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
+    read_only::check("Restoring redirected caches")?;
+    let (path, _, _) = lookup_cache(&name)?;
+    let path_str = path.to_string_lossy().to_string();
+
+    let target = app_handle.db(|conn| {
+        conn.query_row(
+            "SELECT target FROM cache_redirections WHERE path = ?1",
+            [&path_str],
+            |row| row.get::<_, String>(0),
+        )
+    }).map_err(|_| format!("{} is not redirected", path.display()))?;
+
+    tracing::info!("Reverting cache redirection for {}", path.display());
+
+    // Only remove the symlink if it still points where we put it
+    match std::fs::read_link(&path) {
+        Ok(link) if link == Path::new(&target) => {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to remove symlink {}: {}", path.display(), e))?;
+        }
+        Ok(link) => {
+            return Err(format!("{} now points to {}, not touching it", path.display(), link.display()));
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(_) => {
+            return Err(format!("{} is no longer a symlink, not touching it", path.display()));
+        }
+    }
+
+    std::fs::create_dir_all(&path).map_err(|e| format!("Failed to recreate {}: {}", path.display(), e))?;
+    if let Err(e) = std::fs::remove_dir_all(&target) {
+        tracing::debug!("Could not remove volatile cache {}: {}", target, e);
+    }
+
+    app_handle.db(|conn| {
+        conn.execute("DELETE FROM cache_redirections WHERE path = ?1", [&path_str])
+    }).map_err(|e| format!("Failed to update cache redirections: {}", e))?;
+    if let Err(e) = sync_login_targets(&app_handle) {
+        tracing::warn!("{}", e);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_fs_type_longest_prefix() {
+        let mounts = "/dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+                      tmpfs /run tmpfs rw,nosuid 0 0\n\
+                      tmpfs /run/user/1000 tmpfs rw,nosuid,nodev 0 0\n\
+                      /dev/sdb1 /mnt/my\\040disk ext4 rw 0 0\n";
+
+        assert_eq!(mount_fs_type(mounts, Path::new("/run/user/1000/pulito-cache")).as_deref(), Some("tmpfs"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/home/user/.cache")).as_deref(), Some("ext4"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/mnt/my disk/x")).as_deref(), Some("ext4"));
+    }
+
+    #[test]
+    fn test_lookup_cache_rejects_unknown_names() {
+        assert!(lookup_cache("../../Documents").is_err());
+        assert!(lookup_cache("not-a-cache").is_err());
+    }
+
+    #[test]
+    fn test_first_running_owner() {
+        let running: HashSet<String> = ["bash", "firefox-bin"].iter().map(|p| p.to_string()).collect();
+        assert_eq!(first_running(&["firefox", "firefox-bin"], &running), Some("firefox-bin"));
+        assert_eq!(first_running(&["chrome"], &running), None);
+        assert_eq!(first_running(&[], &running), None);
+    }
+
+    #[test]
+    fn test_tmpfiles_config() {
+        let targets = ["/run/user/1000/pulito-cache/mozilla".to_string(), "/elsewhere/thumbnails".to_string()];
+        let config = tmpfiles_config(&targets, Path::new("/run/user/1000"));
+        let lines: Vec<&str> = config.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            ["d %t/pulito-cache 0700 - - -", "d %t/pulito-cache/mozilla 0700 - - -", "d /elsewhere/thumbnails 0700 - - -"]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Coalescing of identical in-flight commands. The dashboard can fire the same
+//! expensive command several times while it re-renders; [`InFlight::run`] lets
+//! the first call compute and hands its result to every call that arrives for the
+//! same key before it finishes.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+type Outcome = Arc<dyn Any + Send + Sync>;
+
+/// Requests being computed, by key; kept in managed state
+#[derive(Default)]
+pub struct InFlight {
+    pending: Mutex<HashMap<String, broadcast::Sender<Outcome>>>,
+}
+
+/// Clears the leader's entry even when its future is dropped halfway, so waiting
+/// callers compute for themselves instead of waiting forever
+struct Leader<'a> {
+    inflight: &'a InFlight,
+    key: &'a str,
+}
+
+impl Leader<'_> {
+    fn take(&self) -> Option<broadcast::Sender<Outcome>> {
+        self.inflight.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(self.key)
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+impl InFlight {
+    /// Run `compute` for `key`, or wait for the run already in progress and share its result
+    pub async fn run<T, F, Fut>(&self, key: &str, compute: F) -> Result<T, String>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let waiting = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            match pending.get(key) {
+                Some(sender) => Some(sender.subscribe()),
+                None => {
+                    pending.insert(key.to_string(), broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+
+        if let Some(mut receiver) = waiting {
+            if let Ok(outcome) = receiver.recv().await {
+                if let Some(result) = outcome.downcast_ref::<Result<T, String>>() {
+                    tracing::debug!("Shared the result of an in-flight {}", key);
+                    return result.clone();
+                }
+            }
+            // The first caller went away before finishing
+            return compute().await;
+        }
+
+        let leader = Leader { inflight: self, key };
+        let result = compute().await;
+        if let Some(sender) = leader.take() {
+            let _ = sender.send(Arc::new(result.clone()));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_run() {
+        let inflight = InFlight::default();
+        let runs = AtomicUsize::new(0);
+        let counter = &runs;
+        let compute = move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, String>(42u64)
+        };
+
+        let (a, b) = tokio::join!(inflight.run("stats", compute), inflight.run("stats", compute));
+        assert_eq!((a, b), (Ok(42), Ok(42)));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // Finished requests aren't cached
+        assert_eq!(inflight.run("stats", compute).await, Ok(42));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert!(inflight.pending.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,772 @@
+//! Cleaning: scan results, caches, packages and logs, the one-click safe
+//! cleanup and its preview.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use tokio::time::{timeout, Duration};
+
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::privileged::{self, PrivilegedOp, PrivilegedOutput};
+use crate::read_only;
+use crate::recovery;
+use crate::safe_mode;
+use crate::scanner::format_bytes;
+use crate::trash::{self, TrashMetadata};
+
+use super::diskpulse::get_cache_items;
+use super::scan::{scan_filesystem_health, scan_storage_recovery};
+use super::trash::enforce_trash_size_limit;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CleanResult {
+    pub cleaned: usize,
+    pub failed: usize,
+    pub total_size: u64,
+    /// Processes still holding deleted files open; their space is freed when they close them
+    #[serde(default)]
+    pub pending_release: Vec<recovery::PendingRelease>,
+}
+
+/// Clean selected items from scan results
+/// Moves items to trash with configurable retention or permanently deletes if use_trash=false
+///
+/// Confirmation metadata: `describe_operation` with `{ kind: "clean_items", ... }`
+///
+/// Parameters:
+/// - item_ids: Array of item IDs from scan results
+/// - item_paths: Array of absolute paths to clean
+/// - use_trash: Whether to use trash system (recommended: true)
+/// - retention_days: Days to retain items in trash (default: 3)
+/// - confirmation_token: Token from `describe_operation` for the same paths
+/// - acknowledgements: In safe mode, an `acknowledge_risky_item` token for each item of risk level 2 or higher
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clean_items(
+    app_handle: tauri::AppHandle,
+    item_ids: Vec<String>,
+    item_paths: Vec<String>,
+    use_trash: bool,
+    retention_days: i64,
+    confirmation_token: String,
+    acknowledgements: Option<Vec<String>>,
+) -> Result<CleanResult, String> {
+    safe_mode::check_acknowledgements(&item_paths, &acknowledgements.unwrap_or_default())?;
+    let plan = OperationPlan::CleanItems { item_paths: item_paths.clone(), use_trash, retention_days };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    // Set timeout for cleanup operations (5 minutes should be plenty)
+    let cleanup_timeout = Duration::from_secs(300);
+
+    match timeout(cleanup_timeout, clean_items_inner(item_ids, item_paths, use_trash, retention_days)).await {
+        Ok(result) => {
+            if let Ok(clean_result) = &result {
+                record_cleanup(&app_handle, "Cleanup", clean_result.total_size);
+            }
+            if use_trash {
+                enforce_trash_size_limit(&app_handle).await;
+            }
+            result
+        }
+        Err(_) => {
+            tracing::error!("Cleanup operation timed out after {} seconds", cleanup_timeout.as_secs());
+            Err("Cleanup operation timed out. Some items may have been partially processed.".to_string())
+        }
+    }
+}
+
+async fn clean_items_inner(
+    item_ids: Vec<String>,
+    item_paths: Vec<String>,
+    use_trash: bool,
+    retention_days: i64,
+) -> Result<CleanResult, String> {
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+    let mut deleted: Vec<std::path::PathBuf> = Vec::new();
+
+    for (_id, path) in item_ids.iter().zip(item_paths.iter()) {
+        // Validate path before any operations with comprehensive security
+        if let Err(validation_error) = validate_path_comprehensive(path, SecurityContext::Deletion) {
+            tracing::warn!("Path validation failed for {}: {}", path, validation_error);
+            failed += 1;
+            continue;
+        }
+
+        let result = if use_trash {
+            trash::move_to_trash(
+                path,
+                retention_days,
+                Some(TrashMetadata {
+                    category: "Cleanup".to_string(),
+                    risk_level: 0,
+                    reason: "User selected for cleanup".to_string(),
+                }),
+            )
+        } else {
+            let path_buf = std::path::PathBuf::from(path);
+            if path_buf.is_dir() {
+                std::fs::remove_dir_all(&path_buf)
+                    .map(|_| trash::TrashItem {
+                        id: String::new(),
+                        original_path: path.clone(),
+                        trash_path: String::new(),
+                        deleted_at: crate::timestamps::now(),
+                        expires_at: 0,
+                        size: 0,
+                        item_type: "directory".to_string(),
+                        metadata: None,
+                        pinned: false,
+                        payload: None,
+                    })
+                    .map_err(|e| e.to_string())
+            } else {
+                std::fs::remove_file(&path_buf)
+                    .map(|_| trash::TrashItem {
+                        id: String::new(),
+                        original_path: path.clone(),
+                        trash_path: String::new(),
+                        deleted_at: crate::timestamps::now(),
+                        expires_at: 0,
+                        size: 0,
+                        item_type: "file".to_string(),
+                        metadata: None,
+                        pinned: false,
+                        payload: None,
+                    })
+                    .map_err(|e| e.to_string())
+            }
+        };
+
+        match result {
+            Ok(item) => {
+                cleaned += 1;
+                total_size += item.size;
+                if !use_trash {
+                    deleted.push(std::path::PathBuf::from(path));
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to clean {}: {}", path, e);
+                failed += 1;
+            }
+        }
+    }
+
+    // Trashed files keep their space anyway; deleted ones only free it once no process holds them
+    let pending_release = if deleted.is_empty() {
+        Vec::new()
+    } else {
+        tokio::task::spawn_blocking(move || recovery::pending_release(&deleted)).await.unwrap_or_default()
+    };
+    for holder in &pending_release {
+        tracing::info!("{} bytes pending release by {} ({})", holder.bytes, holder.process_name, holder.pid);
+    }
+
+    Ok(CleanResult { cleaned, failed, total_size, pending_release })
+}
+
+/// Record freed bytes for monthly digests; failures are logged, never surfaced
+pub(super) fn record_cleanup(app_handle: &tauri::AppHandle, category: &str, bytes_freed: u64) {
+    if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, category, bytes_freed)) {
+        tracing::warn!("Failed to record cleanup history: {}", e);
+    }
+}
+
+/// Safe cache subdirectories to clean (user-specific, not system-critical)
+pub(crate) const SAFE_CACHE_DIRS: &[&str] = &[
+    "thumbnails",
+    "mozilla",
+    "google-chrome",
+    "chromium",
+    "code",
+    "npm",
+    "pip",
+    "yarn",
+    "cargo",
+    "rustup",
+];
+
+/// Entries of `cache_dir` that `clear_cache` removes
+pub(crate) fn cache_cleanup_targets(cache_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            SAFE_CACHE_DIRS.iter().any(|&safe| dir_name.contains(safe))
+        })
+        .collect()
+}
+
+/// User-owned log directories that `clear_logs` removes
+pub(crate) fn log_cleanup_targets(home: &Path) -> Vec<PathBuf> {
+    vec![
+        home.join(".local/share/logs"),
+        home.join(".cache/logs"),
+    ]
+}
+
+/// Clear user cache directories (~/.cache)
+/// Only operates on safe cache locations within user's home directory
+///
+/// Confirmation metadata: `describe_operation` with `{ kind: "clear_cache" }`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clear_cache(app_handle: tauri::AppHandle, confirmation_token: String) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::ClearCache, &confirmation_token)?;
+    clear_cache_inner(app_handle).await
+}
+
+pub(super) async fn clear_cache_inner(app_handle: tauri::AppHandle) -> Result<CleanResult, String> {
+    tracing::info!("Clearing user cache directories");
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let cache_dir = home.join(".cache");
+
+    if !cache_dir.exists() {
+        return Ok(CleanResult { cleaned: 0, failed: 0, total_size: 0, pending_release: Vec::new() });
+    }
+
+    for entry_path in cache_cleanup_targets(&cache_dir) {
+        let path_str = entry_path.to_string_lossy().to_string();
+
+        // Validate path before deletion with cache cleanup context
+        if let Err(validation_error) = validate_path_comprehensive(&path_str, SecurityContext::CacheCleanup) {
+            tracing::warn!("Path validation failed for {}: {}", path_str, validation_error);
+            failed += 1;
+            continue;
+        }
+
+        // Get size before deletion
+        let size = if entry_path.is_dir() {
+            trash::get_dir_size(&entry_path)
+        } else {
+            entry_path.metadata().map(|m| m.len()).unwrap_or(0)
+        };
+
+        // Move to trash with 3-day retention
+        match trash::move_to_trash(
+            &path_str,
+            3,
+            Some(TrashMetadata {
+                category: "Cache".to_string(),
+                risk_level: 0,
+                reason: "User requested cache cleanup".to_string(),
+            }),
+        ) {
+            Ok(_) => {
+                cleaned += 1;
+                total_size += size;
+                tracing::info!("Cleaned cache: {} ({} bytes)", path_str, size);
+            }
+            Err(e) => {
+                tracing::error!("Failed to clean cache {}: {}", path_str, e);
+                failed += 1;
+            }
+        }
+    }
+
+    tracing::info!("Cache cleanup complete: {} cleaned, {} failed, {} bytes", cleaned, failed, total_size);
+    record_cleanup(&app_handle, "Cache", total_size);
+    Ok(CleanResult { cleaned, failed, total_size, pending_release: Vec::new() })
+}
+
+/// Clean package manager caches and remove orphaned packages
+/// Uses package manager commands safely
+///
+/// Confirmation metadata: `describe_operation` with `{ kind: "clean_packages" }`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clean_packages(app_handle: tauri::AppHandle, confirmation_token: String) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::CleanPackages, &confirmation_token)?;
+    clean_packages_inner(app_handle).await
+}
+
+pub(super) async fn clean_packages_inner(app_handle: tauri::AppHandle) -> Result<CleanResult, String> {
+    tracing::info!("Cleaning package manager caches and orphaned packages");
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+
+    // Clean APT cache
+    let apt_clean_result = run_apt(&app_handle, PrivilegedOp::AptClean, &["clean"]).await;
+
+    match apt_clean_result {
+        Ok(output) => {
+            if output.success {
+                cleaned += 1;
+                tracing::info!("APT cache cleaned successfully");
+            } else {
+                failed += 1;
+                tracing::warn!("APT cache clean failed: {}", output.stderr);
+            }
+        }
+        Err(e) => {
+            failed += 1;
+            tracing::warn!("Failed to run apt clean: {}", e);
+        }
+    }
+
+    // Clean APT autoremove (orphaned packages)
+    let apt_autoremove_result = run_apt(&app_handle, PrivilegedOp::AptAutoremove, &["autoremove", "-y"]).await;
+
+    match apt_autoremove_result {
+        Ok(output) => {
+            if output.success {
+                cleaned += 1;
+                // Estimate size from output (rough estimate)
+                let stdout = &output.stdout;
+                if stdout.contains("MB") || stdout.contains("KB") {
+                    // Try to extract size from output
+                    // This is a rough estimate - actual size would need more parsing
+                    total_size += 50 * 1024 * 1024; // Estimate 50MB per autoremove
+                }
+                tracing::info!("APT autoremove completed successfully");
+            } else {
+                failed += 1;
+                tracing::warn!("APT autoremove failed: {}", output.stderr);
+            }
+        }
+        Err(e) => {
+            failed += 1;
+            tracing::warn!("Failed to run apt autoremove: {}", e);
+        }
+    }
+
+    // Clean pip cache (if exists)
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let pip_cache = home.join(".cache/pip");
+    if pip_cache.exists() {
+        let path_str = pip_cache.to_string_lossy().to_string();
+        if let Ok(()) = validate_path_comprehensive(&path_str, SecurityContext::PackageManagement) {
+            let size = trash::get_dir_size(&pip_cache);
+            match trash::move_to_trash(
+                &path_str,
+                3,
+                Some(TrashMetadata {
+                    category: "Package Cache".to_string(),
+                    risk_level: 0,
+                    reason: "User requested package cache cleanup".to_string(),
+                }),
+            ) {
+                Ok(_) => {
+                    cleaned += 1;
+                    total_size += size;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to clean pip cache: {}", e);
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    tracing::info!("Package cleanup complete: {} operations, {} failed, {} bytes", cleaned, failed, total_size);
+    record_cleanup(&app_handle, "Packages", total_size);
+    Ok(CleanResult { cleaned, failed, total_size, pending_release: Vec::new() })
+}
+
+/// Run an apt subcommand through the privileged session when one is active,
+/// otherwise directly with the app's own permissions
+async fn run_apt(app_handle: &tauri::AppHandle, op: PrivilegedOp, args: &'static [&'static str]) -> Result<PrivilegedOutput, String> {
+    let app_handle = app_handle.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Some(output) = privileged::run_in_session(&app_handle, op)? {
+            return Ok(output);
+        }
+        std::process::Command::new("apt")
+            .args(args)
+            .output()
+            .map(PrivilegedOutput::from)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Clear old system logs
+/// Only operates on user-accessible log locations, not system logs
+///
+/// Confirmation metadata: `describe_operation` with `{ kind: "clear_logs" }`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clear_logs(app_handle: tauri::AppHandle, confirmation_token: String) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::ClearLogs, &confirmation_token)?;
+    clear_logs_inner(app_handle).await
+}
+
+async fn clear_logs_inner(app_handle: tauri::AppHandle) -> Result<CleanResult, String> {
+    tracing::info!("Clearing old user logs");
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+
+    // Only clean logs in user's home directory (safe locations)
+    for log_dir in log_cleanup_targets(&home) {
+        if !log_dir.exists() {
+            continue;
+        }
+
+        let path_str = log_dir.to_string_lossy().to_string();
+
+        // Validate path before deletion with log cleanup context
+        if let Err(validation_error) = validate_path_comprehensive(&path_str, SecurityContext::LogCleanup) {
+            tracing::warn!("Path validation failed for {}: {}", path_str, validation_error);
+            failed += 1;
+            continue;
+        }
+
+        // Get size before deletion
+        let size = trash::get_dir_size(&log_dir);
+
+        // Move to trash with 7-day retention (logs might be needed for debugging)
+        match trash::move_to_trash(
+            &path_str,
+            7,
+            Some(TrashMetadata {
+                category: "Logs".to_string(),
+                risk_level: 1,
+                reason: "User requested log cleanup".to_string(),
+            }),
+        ) {
+            Ok(_) => {
+                cleaned += 1;
+                total_size += size;
+                tracing::info!("Cleaned logs: {} ({} bytes)", path_str, size);
+            }
+            Err(e) => {
+                tracing::error!("Failed to clean logs {}: {}", path_str, e);
+                failed += 1;
+            }
+        }
+    }
+
+    tracing::info!("Log cleanup complete: {} cleaned, {} failed, {} bytes", cleaned, failed, total_size);
+    record_cleanup(&app_handle, "Logs", total_size);
+    Ok(CleanResult { cleaned, failed, total_size, pending_release: Vec::new() })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct QuickCleanResult {
+    pub cleaned: u32,
+    pub failed: u32,
+    pub total_size: u64,
+    pub categories: Vec<String>,
+    pub duration_ms: u64,
+}
+
+#[tauri::command]
+pub async fn quick_clean_safe(app_handle: tauri::AppHandle, confirmation_token: String) -> Result<QuickCleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::QuickCleanSafe, &confirmation_token)?;
+    quick_clean_safe_inner(app_handle).await
+}
+
+/// Quick clean without the confirmation check; scheduled and shortcut runs were
+/// confirmed when the user configured the schedule or shortcut.
+pub(crate) async fn quick_clean_safe_inner(app_handle: tauri::AppHandle) -> Result<QuickCleanResult, String> {
+    // Also stops scheduled and on-startup cleanups
+    read_only::check("Cleaning")?;
+    let timeout_duration = Duration::from_secs(120); // 2 minutes max
+    let start_time = std::time::Instant::now();
+
+    timeout(timeout_duration, async {
+        let mut cleaned = 0;
+        let mut failed = 0;
+        let mut total_size: u64 = 0;
+        let mut categories = Vec::new();
+
+        // 1. Clear cache (risk 0 - always safe)
+        match clear_cache_inner(app_handle.clone()).await {
+            Ok(result) => {
+                cleaned += result.cleaned as u32;
+                failed += result.failed as u32;
+                total_size += result.total_size;
+                if result.cleaned > 0 {
+                    categories.push("Cache".to_string());
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Cache cleanup failed in quick clean: {}", e);
+                failed += 1;
+            }
+        }
+
+        // 2. Clear logs (risk 0 - always safe)
+        match clear_logs_inner(app_handle.clone()).await {
+            Ok(result) => {
+                cleaned += result.cleaned as u32;
+                failed += result.failed as u32;
+                total_size += result.total_size;
+                if result.cleaned > 0 {
+                    categories.push("Logs".to_string());
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Log cleanup failed in quick clean: {}", e);
+                failed += 1;
+            }
+        }
+
+        // 3. Clean filesystem health safe items (risk 0-1 only)
+        match scan_filesystem_health(app_handle.clone(), None).await {
+            Ok(health_results) => {
+                if health_results.total_items > 0 {
+                    // Only clean items with risk_level 0-1
+                    let safe_items: Vec<_> = health_results.empty_directories
+                        .iter()
+                        .chain(health_results.broken_symlinks.iter())
+                        .chain(health_results.orphaned_temp_files.iter())
+                        .filter(|item| item.risk_level <= 1)
+                        .collect();
+
+                    if !safe_items.is_empty() {
+                        let item_ids: Vec<String> = safe_items.iter().map(|i| i.id.clone()).collect();
+                        let item_paths: Vec<String> = safe_items.iter().map(|i| i.path.clone()).collect();
+
+                        match clean_items_inner(
+                            item_ids,
+                            item_paths,
+                            false, // Direct deletion for safe items
+                            3,
+                        ).await {
+                            Ok(result) => {
+                                cleaned += result.cleaned as u32;
+                                failed += result.failed as u32;
+                                total_size += result.total_size;
+                                record_cleanup(&app_handle, "Filesystem Health", result.total_size);
+                                if result.cleaned > 0 {
+                                    categories.push("Filesystem Health".to_string());
+                                }
+                            }
+                            Err(e) => {
+                                tracing::warn!("Filesystem health cleanup failed: {}", e);
+                                failed += safe_items.len() as u32;
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Filesystem health scan failed in quick clean: {}", e);
+            }
+        }
+
+        let duration_ms = start_time.elapsed().as_millis() as u64;
+
+        Ok(QuickCleanResult {
+            cleaned,
+            failed,
+            total_size,
+            pending_release: Vec::new(),
+            categories,
+            duration_ms,
+        })
+    })
+    .await
+    .map_err(|_| "Quick clean operation timed out".to_string())?
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CleanupPreview {
+    pub cache_items: Vec<PreviewItem>,
+    pub log_items: Vec<PreviewItem>,
+    pub filesystem_items: Vec<PreviewItem>,
+    pub storage_items: Vec<PreviewItem>,
+    pub total_size: u64,
+    pub total_items: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct PreviewItem {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub category: String,
+    pub risk_level: u8,
+    pub description: String,
+}
+
+#[tauri::command]
+#[allow(dead_code)]
+pub async fn get_cleanup_preview(app_handle: tauri::AppHandle) -> Result<CleanupPreview, String> {
+    let timeout_duration = Duration::from_secs(180); // 3 minutes for comprehensive scan
+
+    timeout(timeout_duration, async {
+        let mut cache_items = Vec::new();
+        let mut log_items = Vec::new();
+        let mut filesystem_items = Vec::new();
+        let mut storage_items = Vec::new();
+
+        // 1. Get cache items (scan only, no cleanup)
+        match get_cache_items(app_handle.clone()).await {
+            Ok(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    cache_items.push(PreviewItem {
+                        id: format!("cache_{}", idx),
+                        name: item.name.clone(),
+                        path: item.category.clone(),
+                        size: item.size,
+                        category: "cache".to_string(),
+                        risk_level: 0,
+                        description: format!("Cache item: {}", item.name),
+                    });
+                }
+            }
+            Err(_) => {}
+        }
+
+        // 2. Get log items (simplified - scan log directories)
+        let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+        let log_dirs = vec![
+            home.join(".local/share/logs"),
+            home.join(".cache/logs"),
+        ];
+
+        for log_dir in log_dirs {
+            if log_dir.exists() {
+                let size = trash::get_dir_size(&log_dir);
+                if size > 0 {
+                    log_items.push(PreviewItem {
+                        id: format!("log_{}", log_items.len()),
+                        name: log_dir.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("Logs")
+                            .to_string(),
+                        path: log_dir.to_string_lossy().to_string(),
+                        size,
+                        category: "logs".to_string(),
+                        risk_level: 0,
+                        description: "Log directory".to_string(),
+                    });
+                }
+            }
+        }
+
+        // 3. Get filesystem health items
+        match scan_filesystem_health(app_handle.clone(), None).await {
+            Ok(results) => {
+                for item in results.empty_directories {
+                    filesystem_items.push(PreviewItem {
+                        id: item.id,
+                        name: item.name,
+                        path: item.path,
+                        size: item.size,
+                        category: "empty_directory".to_string(),
+                        risk_level: item.risk_level,
+                        description: "Empty directory".to_string(),
+                    });
+                }
+                for item in results.broken_symlinks {
+                    filesystem_items.push(PreviewItem {
+                        id: item.id,
+                        name: item.name,
+                        path: item.path,
+                        size: item.size,
+                        category: "broken_symlink".to_string(),
+                        risk_level: item.risk_level,
+                        description: "Broken symbolic link".to_string(),
+                    });
+                }
+                for item in results.orphaned_temp_files {
+                    filesystem_items.push(PreviewItem {
+                        id: item.id,
+                        name: item.name,
+                        path: item.path,
+                        size: item.size,
+                        category: "orphaned_temp".to_string(),
+                        risk_level: item.risk_level,
+                        description: "Orphaned temp file".to_string(),
+                    });
+                }
+            }
+            Err(_) => {}
+        }
+
+        // 4. Get storage recovery items (duplicates, large files)
+        match scan_storage_recovery(app_handle.clone(), None, None, None, None).await {
+            Ok(results) => {
+                // Add duplicate groups
+                for group in results.duplicates {
+                    for (idx, file) in group.files.iter().enumerate() {
+                        // Skip the suggested keeper
+                        if !group.preselected.contains(&file.id) {
+                            continue;
+                        }
+                        storage_items.push(PreviewItem {
+                            id: format!("dup_{}_{}", group.id, idx),
+                            name: file.name.clone(),
+                            path: file.path.clone(),
+                            size: file.size,
+                            category: "duplicate".to_string(),
+                            risk_level: 1,
+                            description: format!("Duplicate file ({} copies)", group.group_size),
+                        });
+                    }
+                }
+
+                // Add large files
+                for file in results.large_files {
+                    storage_items.push(PreviewItem {
+                        id: file.id.clone(),
+                        name: file.name.clone(),
+                        path: file.path.clone(),
+                        size: file.size,
+                        category: "large_file".to_string(),
+                        risk_level: 2,
+                        description: format!("Large file: {}", format_bytes(file.size)),
+                    });
+                }
+
+                // Add old downloads
+                for file in results.old_downloads {
+                    storage_items.push(PreviewItem {
+                        id: file.id.clone(),
+                        name: file.name.clone(),
+                        path: file.path.clone(),
+                        size: file.size,
+                        category: "old_download".to_string(),
+                        risk_level: 1,
+                        description: "Old download file".to_string(),
+                    });
+                }
+            }
+            Err(_) => {}
+        }
+
+        let total_size = cache_items.iter().map(|i| i.size).sum::<u64>()
+            + log_items.iter().map(|i| i.size).sum::<u64>()
+            + filesystem_items.iter().map(|i| i.size).sum::<u64>()
+            + storage_items.iter().map(|i| i.size).sum::<u64>();
+
+        let total_items = cache_items.len() + log_items.len() + filesystem_items.len() + storage_items.len();
+
+        Ok(CleanupPreview {
+            cache_items,
+            log_items,
+            filesystem_items,
+            storage_items,
+            total_size,
+            total_items,
+        })
+    })
+    .await
+    .map_err(|_| "Preview scan timed out".to_string())?
+}
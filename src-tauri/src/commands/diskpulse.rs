@@ -0,0 +1,878 @@
+//! DiskPulse: background disk usage sampling and cache watching, and the
+//! cache, old file and analytics data its views show.
+
+use notify::Watcher;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use sysinfo::Disks;
+use tauri::Manager;
+use tokio::time::{timeout, Duration};
+
+use crate::agent;
+use crate::coalesce::InFlight;
+use crate::db::DbAccess;
+use crate::disk_health;
+use crate::electron;
+use crate::flatpak;
+use crate::jobs::{self, Job};
+use crate::libraries::{self, LibraryDetector, ManagedLibrary};
+use crate::maintenance;
+use crate::monitoring::MonitoringHandle;
+use crate::notifications::{self, NotificationKind};
+use crate::operations::{guard, OperationPlan};
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::power;
+use crate::scanner::{self, format_bytes};
+use crate::trash::{self, TrashMetadata};
+
+use super::cleanup::{clean_packages_inner, clear_cache_inner, record_cleanup, CleanResult};
+use super::health::get_system_stats;
+
+/// DiskPulse disk usage sampling interval
+const DISK_SAMPLE_INTERVAL_SECS: i64 = 4 * 3600;
+/// Longest single sleep between wall-clock checks
+const DISK_SAMPLE_MAX_SLEEP_SECS: u64 = 10 * 60;
+/// Free space on / below this percentage raises a low disk alert
+const LOW_DISK_PERCENT: u64 = 10;
+
+/// Minimum gap between repeated low disk / trash expiry alerts
+pub(super) const ALERT_REPEAT_SECS: i64 = 24 * 3600;
+
+// Cache analytics structures
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CacheAnalytics {
+    pub total_cache_size: u64,
+    pub cache_breakdown: Vec<CacheContributor>,
+    pub growth_trend: Vec<CacheGrowthPoint>,
+    pub recommended_limits: std::collections::HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CacheContributor {
+    pub source: String,
+    pub size: u64,
+    pub growth_rate: f32, // MB per day
+    pub last_activity: i64,
+    pub recommended_limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CacheGrowthPoint {
+    pub timestamp: i64,
+    pub total_size: u64,
+    pub sources: std::collections::HashMap<String, u64>,
+}
+
+// DiskPulse data structures
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CacheEvent {
+    pub id: i64,
+    pub path: String,
+    pub size_change: i64,
+    pub event_type: String,
+    pub source: Option<String>,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct DiskPulseHealth {
+    pub disk_usage_percent: f32,
+    pub projected_days_until_full: Option<f32>,
+    pub status_color: String, // "green", "yellow", "red"
+    pub status_message: String,
+    pub disk_errors: Vec<disk_health::DiskErrorEvent>, // kernel log excerpts from the last 24h
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct OldFilesSummary {
+    pub total_files: usize, // outside application libraries
+    pub total_size: u64,
+    pub cutoff_days: u32,
+    /// Application-managed libraries holding old files; only cleaned when opted in
+    #[serde(default)]
+    pub libraries: Vec<ManagedLibrary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CacheItem {
+    pub name: String,
+    pub size: u64,
+    pub category: String,
+    pub can_clear: bool,
+    /// What the app keeps besides this cache, for Flatpak apps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_size: Option<u64>,
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn start_diskpulse_monitoring(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let monitoring = MonitoringHandle::of(&app_handle);
+
+    if monitoring.diskpulse_running().await {
+        return Ok(()); // Already running
+    }
+
+    if !agent::is_agent_mode() && agent::is_agent_active() {
+        tracing::info!("pulito-agent is running, leaving DiskPulse monitoring to it");
+        return Ok(());
+    }
+
+    let started = monitoring.start_diskpulse(|| spawn_diskpulse(app_handle.clone())).await?;
+    if !started {
+        return Ok(()); // Started by a concurrent call
+    }
+
+    // Update monitoring state in database
+    app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO monitoring_state (key, value, updated_at) VALUES ('diskpulse_running', 'true', ?)",
+            [chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }).map_err(|e| format!("Failed to update monitoring state: {}", e))?;
+
+    tracing::info!("DiskPulse monitoring started successfully");
+    Ok(())
+}
+
+/// The disk sampling task and cache watcher DiskPulse runs
+async fn spawn_diskpulse(
+    app_handle: tauri::AppHandle,
+) -> Result<(tokio::task::JoinHandle<()>, notify::RecommendedWatcher), String> {
+    tracing::info!("Starting DiskPulse background monitoring");
+
+    // Start disk usage monitoring (every 4 hours of wall-clock time, held back after resume)
+    power::spawn_listener();
+    let disk_app_handle = app_handle.clone();
+    let disk_task = tokio::spawn(async move {
+        let mut power_events = power::subscribe();
+        let mut suspend_clock = power::SuspendClock::new();
+        let mut last_sample = None;
+
+        loop {
+            let now = chrono::Utc::now().timestamp();
+            let delay = power::next_sample_delay(now, last_sample, power::last_resume(), DISK_SAMPLE_INTERVAL_SECS);
+            if delay == 0 {
+                if let Err(e) = record_disk_usage(&disk_app_handle).await {
+                    tracing::error!("Failed to record disk usage: {}", e);
+                }
+                maintenance::run_if_due(&disk_app_handle).await;
+                last_sample = Some(now);
+                continue;
+            }
+
+            // tokio's clock stops during suspend, so sleep in short steps and re-check the wall clock
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(delay.min(DISK_SAMPLE_MAX_SLEEP_SECS))) => {}
+                _ = power_events.recv() => {}
+            }
+            suspend_clock.check();
+        }
+    });
+
+    // Start cache directory watching
+    let cache_watcher = match setup_cache_watcher(app_handle).await {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            disk_task.abort();
+            return Err(e);
+        }
+    };
+
+    Ok((disk_task, cache_watcher))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn stop_diskpulse_monitoring(app_handle: tauri::AppHandle) -> Result<(), String> {
+    // Aborts the disk sampling task and drops the cache watcher
+    if !MonitoringHandle::of(&app_handle).stop_diskpulse().await {
+        return Ok(()); // Not running
+    }
+    tracing::info!("Stopped DiskPulse background monitoring");
+
+    // Update monitoring state in database
+    app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO monitoring_state (key, value, updated_at) VALUES ('diskpulse_running', 'false', ?)",
+            [chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }).map_err(|e| format!("Failed to update monitoring state: {}", e))?;
+
+    tracing::info!("DiskPulse monitoring stopped successfully");
+    Ok(())
+}
+
+pub(crate) async fn record_disk_usage(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let disks = Disks::new_with_refreshed_list();
+
+    for disk in disks.list() {
+        if disk.mount_point().to_string_lossy() == "/" {
+            let used = disk.total_space() - disk.available_space();
+            let timestamp = chrono::Utc::now().timestamp();
+
+            app_handle.db(|conn| {
+                conn.execute(
+                    "INSERT INTO disk_history (timestamp, used_bytes, total_bytes, available_bytes, after_resume) VALUES (?, ?, ?, ?, ?)",
+                    [
+                        timestamp,
+                        used as i64,
+                        disk.total_space() as i64,
+                        disk.available_space() as i64,
+                        power::is_after_resume(timestamp) as i64,
+                    ],
+                )?;
+                Ok(())
+            }).map_err(|e| format!("Failed to record disk usage: {}", e))?;
+
+            agent::ipc::publish(agent::ipc::AgentEvent::DiskSample {
+                used_bytes: used,
+                total_bytes: disk.total_space(),
+                timestamp,
+            });
+
+            if disk.available_space() * 100 < disk.total_space() * LOW_DISK_PERCENT {
+                let repeated = app_handle
+                    .db(|conn| notifications::alerted_since(conn, NotificationKind::LowDisk, timestamp - ALERT_REPEAT_SECS))
+                    .unwrap_or(false);
+                if !repeated {
+                    notifications::alert(
+                        app_handle,
+                        NotificationKind::LowDisk,
+                        "Low disk space",
+                        &format!("Only {} free on /", format_bytes(disk.available_space())),
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+
+    // Fold completed months into digests so raw samples can be pruned
+    jobs::submit(app_handle, Job::RollUpDigests);
+
+    Ok(())
+}
+
+async fn setup_cache_watcher(app_handle: tauri::AppHandle) -> Result<notify::RecommendedWatcher, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let mut watcher = notify::recommended_watcher(move |res| {
+        if let Err(e) = tx.send(res) {
+            tracing::error!("Failed to send watch event: {}", e);
+        }
+    }).map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    // Watch common cache directories
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let cache_dirs = vec![
+        home.join(".cache"),
+        home.join(".local/share/cache"),
+    ];
+
+    for cache_dir in cache_dirs {
+        if cache_dir.exists() {
+            if let Err(e) = watcher.watch(&cache_dir, notify::RecursiveMode::Recursive) {
+                tracing::warn!("Failed to watch cache directory {:?}: {}", cache_dir, e);
+            }
+        }
+    }
+
+    // Handle cache events in background task
+    tokio::spawn(async move {
+        while let Ok(event) = rx.recv() {
+            if let Err(e) = handle_cache_event(&app_handle, event).await {
+                tracing::error!("Failed to handle cache event: {}", e);
+            }
+        }
+    });
+
+    Ok(watcher)
+}
+
+async fn handle_cache_event(app_handle: &tauri::AppHandle, event: notify::Result<notify::Event>) -> Result<(), String> {
+    let event = event.map_err(|e| format!("Watch event error: {}", e))?;
+
+    // Only process write/create events that might indicate cache growth
+    if matches!(event.kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_)) {
+        for path in &event.paths {
+            if let Ok(metadata) = std::fs::metadata(path) {
+                let size = metadata.len() as i64;
+                let path_str = path.to_string_lossy().to_string();
+                let timestamp = chrono::Utc::now().timestamp();
+
+                // Determine source from path
+                let source = if path_str.contains("chromium") || path_str.contains("chrome") {
+                    Some("chrome".to_string())
+                } else if path_str.contains("firefox") {
+                    Some("firefox".to_string())
+                } else if path_str.contains("pip") {
+                    Some("pip".to_string())
+                } else if path_str.contains("npm") {
+                    Some("npm".to_string())
+                } else {
+                    None
+                };
+
+                if let Some(source) = source {
+                    app_handle.db(|conn| {
+                        conn.execute(
+                            "INSERT INTO cache_events (path, size_change, event_type, source, timestamp) VALUES (?, ?, 'growth', ?, ?)",
+                            [&path_str, &size.to_string(), &source, &timestamp.to_string()],
+                        )?;
+                        Ok(())
+                    }).map_err(|e| format!("Failed to record cache event: {}", e))?;
+
+                    agent::ipc::publish(agent::ipc::AgentEvent::CacheGrowth {
+                        path: path_str,
+                        size: size as u64,
+                        source,
+                        timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// DiskPulse UI data commands
+/// Traffic-light status for a disk usage percentage (shared with remote hosts)
+pub(crate) fn usage_status(usage_percent: f32) -> (&'static str, String) {
+    if usage_percent < 70.0 {
+        ("green", "You're good. No action needed.".to_string())
+    } else if usage_percent < 85.0 {
+        ("yellow", "Getting full, maybe check in.".to_string())
+    } else {
+        ("red", "Running low, take action.".to_string())
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_diskpulse_health(app_handle: tauri::AppHandle) -> Result<DiskPulseHealth, String> {
+    let stats = get_system_stats(app_handle.clone()).await?;
+
+    // Calculate disk usage percentage
+    let usage_percent = if stats.total_disk_space > 0 {
+        (stats.used_disk_space as f32 / stats.total_disk_space as f32) * 100.0
+    } else {
+        0.0
+    };
+
+    // Kernel-reported disk errors outrank any usage-based status
+    let disk_errors = timeout(
+        Duration::from_secs(5),
+        tokio::task::spawn_blocking(|| disk_health::recent_disk_errors(24)),
+    )
+    .await
+    .ok()
+    .and_then(|r| r.ok())
+    .unwrap_or_default();
+
+    // Determine status color
+    let (status_color, status_message) = if !disk_errors.is_empty() {
+        ("red", format!("Disk errors detected ({} in the last 24h). Back up important data.", disk_errors.len()))
+    } else {
+        usage_status(usage_percent)
+    };
+
+    // Calculate projected days until full using historical data if available
+    let projected_days = if stats.total_disk_space > 0 && stats.used_disk_space > 0 {
+        // Try to get historical data from disk_history table
+        let historical_data = app_handle.db(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT used_bytes, timestamp FROM disk_history ORDER BY timestamp DESC LIMIT 30"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })?;
+
+            let mut data_points: Vec<(i64, i64)> = Vec::new();
+            for row_result in rows {
+                data_points.push(row_result?);
+            }
+            Ok::<Vec<(i64, i64)>, rusqlite::Error>(data_points)
+        }).unwrap_or_default();
+
+        if historical_data.len() >= 2 {
+            // Calculate daily growth rate from historical data
+            let oldest = historical_data.last().unwrap();
+            let newest = historical_data.first().unwrap();
+            let days_diff = (newest.1 - oldest.1) as f32 / (24.0 * 3600.0);
+
+            if days_diff > 0.0 {
+                let bytes_growth = (newest.0 - oldest.0) as f32;
+                let daily_usage_rate = bytes_growth / days_diff;
+
+                if daily_usage_rate > 0.0 {
+                    let remaining_space = stats.total_disk_space.saturating_sub(stats.used_disk_space);
+                    Some((remaining_space as f32 / daily_usage_rate).ceil())
+                } else {
+                    // Disk is shrinking or stable, can't project
+                    None
+                }
+            } else {
+                None
+            }
+        } else {
+            // Not enough historical data, use simplified calculation with current usage
+            // Estimate based on cleanable space and assume moderate growth
+            let remaining_space = stats.total_disk_space.saturating_sub(stats.used_disk_space);
+            // Use a conservative estimate: assume 1% growth per month
+            let monthly_growth = stats.total_disk_space as f32 * 0.01;
+            let daily_growth = monthly_growth / 30.0;
+
+            if daily_growth > 0.0 {
+                Some((remaining_space as f32 / daily_growth).ceil())
+            } else {
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(DiskPulseHealth {
+        disk_usage_percent: usage_percent,
+        projected_days_until_full: projected_days,
+        status_color: status_color.to_string(),
+        status_message,
+        disk_errors,
+    })
+}
+
+/// Old files from the access log as (path, size), with those inside
+/// application-managed libraries grouped per library instead
+pub(crate) fn old_file_targets(app_handle: &tauri::AppHandle, days_cutoff: u32) -> Result<(Vec<(String, u64)>, Vec<ManagedLibrary>), String> {
+    let cutoff_timestamp = chrono::Utc::now().timestamp() - (days_cutoff as i64 * 24 * 3600);
+
+    let old_files = app_handle.db(|conn| {
+        let mut stmt = conn.prepare("SELECT path, size FROM file_access WHERE last_access < ?")?;
+        let rows = stmt.query_map([cutoff_timestamp], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))?;
+        rows.collect::<Result<Vec<_>, _>>()
+    }).map_err(|e| format!("Failed to get old files: {}", e))?;
+
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    Ok(libraries::partition(old_files, &mut LibraryDetector::new(&home)))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_old_files_summary(app_handle: tauri::AppHandle, days_cutoff: u32) -> Result<OldFilesSummary, String> {
+    let (loose, libraries) = tokio::task::spawn_blocking(move || old_file_targets(&app_handle, days_cutoff))
+        .await
+        .map_err(|e| format!("Failed to get old files summary: {}", e))??;
+
+    Ok(OldFilesSummary {
+        total_files: loose.len(),
+        total_size: loose.iter().map(|(_, size)| size).sum(),
+        cutoff_days: days_cutoff,
+        libraries,
+    })
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_recent_cache_events(app_handle: tauri::AppHandle, limit: usize) -> Result<Vec<CacheEvent>, String> {
+    let events = app_handle.db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, path, size_change, event_type, source, timestamp FROM cache_events
+             ORDER BY timestamp DESC LIMIT ?"
+        )?;
+        let mut rows = stmt.query([limit as i64])?;
+
+        let mut events = Vec::new();
+        while let Some(row) = rows.next()? {
+            events.push(CacheEvent {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                size_change: row.get(2)?,
+                event_type: row.get(3)?,
+                source: row.get(4)?,
+                timestamp: row.get(5)?,
+            });
+        }
+        Ok(events)
+    }).map_err(|e| format!("Failed to get cache events: {}", e))?;
+
+    Ok(events)
+}
+
+/// Concurrent calls share one computation, see `coalesce::InFlight`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_cache_items(app_handle: tauri::AppHandle) -> Result<Vec<CacheItem>, String> {
+    let inflight = app_handle.state::<InFlight>();
+    inflight.run("get_cache_items", get_cache_items_inner).await
+}
+
+async fn get_cache_items_inner() -> Result<Vec<CacheItem>, String> {
+    let mut items = Vec::new();
+
+    // Get real cache sizes from system
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+
+    // Chrome/Chromium cache
+    let chrome_cache = home.join(".cache/google-chrome");
+    let chrome_size = if chrome_cache.exists() {
+        scanner::sizing::measure("chrome_cache", chrome_cache, Duration::from_secs(30)).await.unwrap_or(0)
+    } else {
+        0
+    };
+
+    if chrome_size > 0 {
+        items.push(CacheItem {
+            name: "Chrome temporary files".to_string(),
+            size: chrome_size,
+            category: "browser".to_string(),
+            can_clear: true,
+            data_size: None,
+        });
+    }
+
+    // Firefox cache
+    let firefox_cache = home.join(".cache/mozilla/firefox");
+    let firefox_size = if firefox_cache.exists() {
+        scanner::sizing::measure("firefox_cache", firefox_cache, Duration::from_secs(30)).await.unwrap_or(0)
+    } else {
+        0
+    };
+
+    if firefox_size > 0 {
+        items.push(CacheItem {
+            name: "Firefox cache".to_string(),
+            size: firefox_size,
+            category: "browser".to_string(),
+            can_clear: true,
+            data_size: None,
+        });
+    }
+
+    // PIP cache
+    let pip_cache = home.join(".cache/pip");
+    let pip_size = if pip_cache.exists() {
+        scanner::sizing::measure("pip_cache", pip_cache, Duration::from_secs(30)).await.unwrap_or(0)
+    } else {
+        0
+    };
+
+    if pip_size > 0 {
+        items.push(CacheItem {
+            name: "Python packages cache".to_string(),
+            size: pip_size,
+            category: "development".to_string(),
+            can_clear: true,
+            data_size: None,
+        });
+    }
+
+    // Electron apps keep their caches in ~/.config, outside the folders above
+    let electron_home = home.clone();
+    let electron_caches = tokio::task::spawn_blocking(move || {
+        electron::find_electron_caches(&electron_home)
+            .into_iter()
+            .map(|cache| {
+                let size: u64 = cache.cache_dirs.iter().map(|d| trash::get_dir_size(d)).sum();
+                (cache, size)
+            })
+            .collect::<Vec<_>>()
+    });
+    match timeout(Duration::from_secs(30), electron_caches).await {
+        Ok(Ok(caches)) => {
+            for (cache, size) in caches.into_iter().filter(|(_, size)| *size > 0) {
+                items.push(CacheItem {
+                    name: cache.item_name(),
+                    size,
+                    category: "electron".to_string(),
+                    // Cleared from under a running app, its open windows can break
+                    can_clear: !cache.is_running(),
+                    data_size: None,
+                });
+            }
+        }
+        Ok(Err(e)) => tracing::warn!("Electron cache lookup failed: {}", e),
+        Err(_) => tracing::warn!("Electron cache lookup timed out"),
+    }
+
+    // Flatpak apps are sandboxed into ~/.var/app/<id>; their data is listed but stays
+    let flatpak_home = home.clone();
+    let flatpak_apps = tokio::task::spawn_blocking(move || {
+        let running = flatpak::appdata::running_now();
+        flatpak::appdata::find_apps(&flatpak_home)
+            .into_iter()
+            .map(|app| {
+                let is_running = running.contains(&app.app_id);
+                (app.item_name(), app.usage(), is_running)
+            })
+            .collect::<Vec<_>>()
+    });
+    match timeout(Duration::from_secs(30), flatpak_apps).await {
+        Ok(Ok(apps)) => {
+            for (name, usage, is_running) in apps.into_iter().filter(|(_, usage, _)| usage.cache > 0) {
+                items.push(CacheItem {
+                    name,
+                    size: usage.cache,
+                    category: "flatpak".to_string(),
+                    can_clear: !is_running,
+                    data_size: Some(usage.data),
+                });
+            }
+        }
+        Ok(Err(e)) => tracing::warn!("Flatpak app lookup failed: {}", e),
+        Err(_) => tracing::warn!("Flatpak app lookup timed out"),
+    }
+
+    Ok(items)
+}
+
+/// Refuse to clear the cache of a Flatpak or Electron app that is running: it
+/// may have started since the cache list was shown, and would write to the
+/// cache while it's being cleared
+fn check_app_closed(home: &std::path::Path, item_name: &str) -> Result<(), String> {
+    let running = if let Some(app) = flatpak::appdata::find_by_item_name(home, item_name) {
+        flatpak::appdata::running_now().contains(&app.app_id).then_some(app.app_id)
+    } else {
+        electron::find_by_item_name(home, item_name).filter(|cache| cache.is_running()).map(|cache| cache.app)
+    };
+    match running {
+        Some(app) => Err(format!("{} is running; close it before clearing its cache", app)),
+        None => Ok(()),
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clear_cache_item(app_handle: tauri::AppHandle, item_name: String, confirmation_token: String) -> Result<CleanResult, String> {
+    if let Some(home) = dirs::home_dir() {
+        check_app_closed(&home, &item_name)?;
+    }
+    let plan = OperationPlan::ClearCacheItem { item_name: item_name.clone() };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    match item_name.as_str() {
+        "Chrome temporary files" => clear_cache_inner(app_handle).await,
+        "Firefox cache" => clear_cache_inner(app_handle).await, // Would need Firefox-specific logic
+        "Python packages cache" => clean_packages_inner(app_handle).await,
+        _ => {
+            let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+            if let Some(app) = flatpak::appdata::find_by_item_name(&home, &item_name) {
+                return tokio::task::spawn_blocking(move || flatpak::appdata::clear(&app_handle, &app))
+                    .await
+                    .map_err(|e| format!("Cache cleanup task failed: {}", e));
+            }
+            let cache = electron::find_by_item_name(&home, &item_name).ok_or_else(|| format!("Unknown cache item: {}", item_name))?;
+            tokio::task::spawn_blocking(move || electron::clear(&app_handle, &cache))
+                .await
+                .map_err(|e| format!("Cache cleanup task failed: {}", e))
+        }
+    }
+}
+
+/// Trash old files. Files inside application-managed libraries are skipped; a
+/// library listed in `include_libraries` is trashed as a whole instead.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn cleanup_old_files(
+    app_handle: tauri::AppHandle,
+    days_cutoff: u32,
+    include_libraries: Option<Vec<String>>,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    let include_libraries = include_libraries.unwrap_or_default();
+    let plan = OperationPlan::CleanupOldFiles { days_cutoff, include_libraries: include_libraries.clone() };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    let (old_files, libraries) = old_file_targets(&app_handle, days_cutoff)?;
+    let old_files = old_files
+        .into_iter()
+        .map(|(path, _)| (path, 1, format!("File not accessed in {} days", days_cutoff)));
+    let opted_in = libraries.into_iter().filter(|l| include_libraries.contains(&l.root)).map(|l| {
+        let reason = format!("{} library with files not accessed in {} days", l.app, days_cutoff);
+        (l.root, 2, reason)
+    });
+
+    // Calculate actual file sizes and clean the files
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+
+    for (path_str, risk_level, reason) in old_files.chain(opted_in) {
+        let path = std::path::PathBuf::from(&path_str);
+
+        // Validate path before any operations
+        if let Err(validation_error) = validate_path_comprehensive(&path_str, SecurityContext::Deletion) {
+            tracing::warn!("Path validation failed for {}: {}", path_str, validation_error);
+            failed += 1;
+            continue;
+        }
+
+        // Calculate file size before deletion
+        let file_size = if path.exists() {
+            if path.is_dir() {
+                trash::get_dir_size(&path)
+            } else {
+                path.metadata().map(|m| m.len()).unwrap_or(0)
+            }
+        } else {
+            // File no longer exists, skip it
+            failed += 1;
+            continue;
+        };
+
+        // Move to trash (30 day retention for old files)
+        match trash::move_to_trash(
+            &path_str,
+            30,
+            Some(TrashMetadata {
+                category: "Old Files".to_string(),
+                risk_level,
+                reason,
+            }),
+        ) {
+            Ok(_) => {
+                cleaned += 1;
+                total_size += file_size;
+                tracing::info!("Cleaned old file: {} ({} bytes)", path_str, file_size);
+            }
+            Err(e) => {
+                tracing::error!("Failed to clean old file {}: {}", path_str, e);
+                failed += 1;
+            }
+        }
+    }
+
+    record_cleanup(&app_handle, "Old Files", total_size);
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+// Cache Optimization Suite commands
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_cache_analytics(app_handle: tauri::AppHandle) -> Result<CacheAnalytics, String> {
+    // Set a timeout for cache analytics (30 seconds - database operations)
+    let analytics_timeout = Duration::from_secs(30);
+
+    match timeout(analytics_timeout, get_cache_analytics_inner(app_handle)).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Cache analytics timed out after {} seconds", analytics_timeout.as_secs());
+            Err("Cache analytics timed out. Please try again.".to_string())
+        }
+    }
+}
+
+async fn get_cache_analytics_inner(app_handle: tauri::AppHandle) -> Result<CacheAnalytics, String> {
+    let cache_events = app_handle.db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT source, size_change, timestamp FROM cache_events
+             WHERE timestamp > ? ORDER BY timestamp DESC"
+        )?;
+        let cutoff = chrono::Utc::now().timestamp() - (30 * 24 * 3600); // Last 30 days
+
+        let rows = stmt.query_map([cutoff], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut events = Vec::new();
+        for event in rows.flatten() {
+            events.push(event);
+        }
+        Ok(events)
+    }).map_err(|e| format!("Failed to get cache events: {}", e))?;
+
+    // Analyze current cache sizes
+    let current_cache_items = get_cache_items(app_handle.clone()).await?;
+    let mut total_cache_size = 0u64;
+    let mut contributors = Vec::new();
+
+    for item in current_cache_items {
+        total_cache_size += item.size;
+
+        // Calculate growth rate from events (simplified)
+        let source_events: Vec<_> = cache_events.iter()
+            .filter(|(source, _, _)| *source == item.category)
+            .collect();
+
+        let growth_rate = if source_events.len() > 1 {
+            let total_growth: i64 = source_events.iter().map(|(_, size, _)| *size).sum();
+            let days_span = 30.0; // Assume 30 days of data
+            (total_growth as f32 / (1024.0 * 1024.0)) / days_span // MB per day
+        } else {
+            0.0
+        };
+
+        let last_activity = source_events.first()
+            .map(|(_, _, ts)| *ts)
+            .unwrap_or(chrono::Utc::now().timestamp());
+
+        let category = item.category.clone();
+        contributors.push(CacheContributor {
+            source: category.clone(),
+            size: item.size,
+            growth_rate,
+            last_activity,
+            recommended_limit: get_recommended_cache_limit(&category),
+        });
+    }
+
+    // Generate growth trend (simplified - last 7 days)
+    let mut growth_trend = Vec::new();
+    for day_offset in (0..7).rev() {
+        let timestamp = chrono::Utc::now().timestamp() - (day_offset * 24 * 3600);
+        let day_size: u64 = cache_events.iter()
+            .filter(|(_, _, ts)| *ts >= timestamp && *ts < timestamp + 24 * 3600)
+            .map(|(_, size, _)| *size as u64)
+            .sum();
+
+        growth_trend.push(CacheGrowthPoint {
+            timestamp,
+            total_size: day_size,
+            sources: std::collections::HashMap::new(), // Could be populated with per-source data
+        });
+    }
+
+    // Recommended limits
+    let mut recommended_limits = std::collections::HashMap::new();
+    for contributor in &contributors {
+        if let Some(limit) = contributor.recommended_limit {
+            recommended_limits.insert(contributor.source.clone(), limit);
+        }
+    }
+
+    Ok(CacheAnalytics {
+        total_cache_size,
+        cache_breakdown: contributors,
+        growth_trend,
+        recommended_limits,
+    })
+}
+
+fn get_recommended_cache_limit(cache_type: &str) -> Option<u64> {
+    match cache_type {
+        "browser" => Some(1024 * 1024 * 1024), // 1GB for browsers
+        "development" => Some(2 * 1024 * 1024 * 1024), // 2GB for dev tools
+        "system" => Some(512 * 1024 * 1024), // 512MB for system caches
+        _ => None,
+    }
+}
@@ -0,0 +1,868 @@
+//! System statistics and the live health view: CPU, memory, disks, network,
+//! temperatures, battery and processes.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::Mutex;
+use std::time::Instant;
+use sysinfo::{Components, Networks, System};
+use tauri::Manager;
+use tokio::time::{timeout, Duration};
+
+use crate::coalesce::InFlight;
+use crate::db::DbAccess;
+use crate::mounts::{self, MountStats};
+use crate::packages;
+use crate::scanner::{self, reclaimable::{Reclaimable, ReclaimableSource}, FilesystemHealthResults, StorageRecoveryResults};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SystemStats {
+    pub total_disk_space: u64,
+    pub used_disk_space: u64,
+    pub cleanable_space: u64,
+    pub last_scan: Option<i64>, // unix seconds
+    pub packages_installed: usize,
+    pub orphan_packages: usize,
+    pub cache_size: u64,
+    pub filesystem_health_savings: Option<u64>, // Real savings from last filesystem health scan
+    pub storage_recovery_savings: Option<u64>, // Real savings from last storage recovery scan
+    pub orphan_packages_size: Option<u64>, // Real size of orphaned packages
+    /// Every mounted volume, root first
+    #[serde(default)]
+    pub mounts: Vec<MountStats>,
+    /// What each source adds to `cleanable_space`, overlaps left out
+    #[serde(default)]
+    pub reclaimable: Vec<ReclaimableSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SystemHealthData {
+    // CPU
+    pub cpu_usage: f32,
+    pub cpu_cores: usize,
+    pub cpu_frequency: f32,
+    pub core_usages: Vec<f32>,
+
+    // Memory
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub available_memory: u64,
+
+    // GPU (enhanced)
+    pub gpu_info: Option<GpuInfo>,
+
+    // Network (enhanced)
+    pub network_up: u64,
+    pub network_down: u64,
+    pub network_interfaces: Vec<NetworkInterfaceInfo>,
+    pub active_connections: Vec<NetworkConnection>,
+
+    // Temperatures (enhanced)
+    pub temperatures: Temperatures,
+
+    // Disk I/O (enhanced)
+    pub disk_read_bytes: u64,
+    pub disk_write_bytes: u64,
+    pub disk_read_ops: u64,
+    pub disk_write_ops: u64,
+
+    // Battery (new for laptops)
+    pub battery_info: Option<BatteryInfo>,
+
+    // Processes (top resource consumers)
+    pub top_processes: Vec<ProcessInfo>,
+
+    // System load averages
+    pub load_average: Option<LoadAverage>,
+
+    // Swap usage
+    pub swap_total: u64,
+    pub swap_used: u64,
+
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct GpuInfo {
+    pub name: String,
+    pub usage: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub temperature: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct Temperatures {
+    pub cpu: f32,              // CPU temperature from thermal zones
+    pub cpu_sensors: f32,      // CPU temperature from lm-sensors
+    pub system: f32,           // System temperature (highest thermal zone)
+    pub gpu: Option<f32>,      // GPU temperature
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct NetworkInterfaceInfo {
+    pub name: String,
+    pub received: u64,
+    pub transmitted: u64,
+    pub packets_received: u64,
+    pub packets_transmitted: u64,
+    pub errors_received: u64,
+    pub errors_transmitted: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct NetworkConnection {
+    pub local_address: String,
+    pub remote_address: String,
+    pub local_port: u16,
+    pub remote_port: u16,
+    pub state: String,
+    pub process_name: Option<String>,
+    pub process_pid: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BatteryInfo {
+    pub percentage: f32,
+    pub is_charging: bool,
+    pub time_to_full: Option<u64>, // seconds
+    pub time_to_empty: Option<u64>, // seconds
+    pub power_consumption: Option<f32>, // watts
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+    pub status: String,
+    pub user_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct LoadAverage {
+    pub one_minute: f64,
+    pub five_minutes: f64,
+    pub fifteen_minutes: f64,
+}
+
+/// Concurrent calls share one computation, see `coalesce::InFlight`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_system_stats(app_handle: tauri::AppHandle) -> Result<SystemStats, String> {
+    let inflight = app_handle.state::<InFlight>();
+    inflight.run("get_system_stats", || get_system_stats_inner(app_handle.clone())).await
+}
+
+async fn get_system_stats_inner(app_handle: tauri::AppHandle) -> Result<SystemStats, String> {
+    let mounts = mounts::list_mounts();
+
+    // Log disk information for debugging
+    tracing::info!("Mounted volumes:");
+    for mount in &mounts {
+        tracing::info!("  Mount: {} ({}), Total: {} GB, Used: {} GB, Available: {} GB",
+            mount.mount_point,
+            mount.file_system,
+            mount.total_space / (1024*1024*1024),
+            mount.used_space / (1024*1024*1024),
+            mount.available_space / (1024*1024*1024)
+        );
+    }
+
+    // Headline totals stay on the root filesystem; other volumes are in `mounts`
+    let (total_space, used_space) = mounts
+        .iter()
+        .find(|m| m.mount_point == "/")
+        .map_or((0, 0), |m| (m.total_space, m.used_space));
+
+    // Get package stats - this is a synchronous operation, but we'll wrap it in a timeout
+    // by running it in a blocking task
+    let pkg_stats = match timeout(Duration::from_secs(30), tokio::task::spawn_blocking(|| {
+        packages::get_package_stats()
+    })).await {
+        Ok(Ok(stats)) => stats,
+        Ok(Err(_)) | Err(_) => {
+            tracing::warn!("Package stats query timed out or failed, using defaults");
+            packages::PackageStats {
+                total_packages: 0,
+                orphan_packages: 0,
+                orphan_size: 0,
+            }
+        }
+    };
+
+    // Get cache size - run in blocking task with timeout
+    let home = dirs::home_dir().unwrap_or_default();
+    let cache_path = home.join(".cache");
+    // A refresh that arrives while this is still walking takes over
+    let cache_size = if cache_path.exists() {
+        scanner::sizing::measure("cache_size", cache_path, Duration::from_secs(30)).await.unwrap_or(0)
+    } else {
+        0
+    };
+
+    // Get last scan results from database
+    let (filesystem_health, storage_recovery) = app_handle.db(|conn| {
+            let mut stmt = conn.prepare("SELECT total_size, scan_data FROM last_scan_results WHERE scan_type = ?1 ORDER BY timestamp DESC LIMIT 1")?;
+            let mut last = |scan_type: &str| -> Option<(u64, Option<String>)> {
+                stmt.query_row([scan_type], |row| {
+                    let size: i64 = row.get(0)?;
+                    Ok((size as u64, row.get(1)?))
+                }).ok()
+            };
+            Ok((last("filesystem_health"), last("storage_recovery")))
+        })
+        .unwrap_or((None, None));
+    let filesystem_health_savings = filesystem_health.as_ref().map(|(size, _)| *size);
+    let storage_recovery_savings = storage_recovery.as_ref().map(|(size, _)| *size);
+
+    // The sources overlap (a large file in ~/.cache is also cache), so each path is counted once
+    let mut space = Reclaimable::new();
+    space.add("Cache", &cache_path, cache_size);
+    if let Some(results) = filesystem_health.and_then(|(_, data)| serde_json::from_str::<FilesystemHealthResults>(&data?).ok()) {
+        space.add_filesystem_health(&results);
+    }
+    if let Some(results) = storage_recovery.and_then(|(_, data)| serde_json::from_str::<StorageRecoveryResults>(&data?).ok()) {
+        space.add_storage_recovery(&results);
+    }
+    space.add_unpathed("Orphaned packages", pkg_stats.orphan_size);
+    let cleanable_space = space.total();
+    let reclaimable = space.breakdown();
+
+    let last_scan = app_handle.db(|conn| {
+            let mut stmt = conn.prepare("SELECT timestamp FROM scan_history ORDER BY id DESC LIMIT 1")?;
+            let timestamp: Result<i64, _> = stmt.query_row([], |row| row.get(0));
+            Ok(timestamp.ok())
+        })
+        .unwrap_or(None);
+
+    Ok(SystemStats {
+        total_disk_space: total_space,
+        used_disk_space: used_space,
+        cleanable_space,
+        last_scan,
+        packages_installed: pkg_stats.total_packages,
+        orphan_packages: pkg_stats.orphan_packages,
+        cache_size,
+        filesystem_health_savings,
+        storage_recovery_savings,
+        orphan_packages_size: if pkg_stats.orphan_size > 0 { Some(pkg_stats.orphan_size) } else { None },
+        mounts,
+        reclaimable,
+    })
+}
+
+/// Get disk I/O statistics on Linux
+#[cfg(target_os = "linux")]
+fn get_disk_io_stats_linux() -> (u64, u64, u64, u64) {
+    use std::fs;
+
+    let mut total_read_bytes = 0u64;
+    let mut total_write_bytes = 0u64;
+    let mut total_read_ops = 0u64;
+    let mut total_write_ops = 0u64;
+
+    if let Ok(content) = fs::read_to_string("/proc/diskstats") {
+        for line in content.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() >= 14 {
+                // Skip partitions (major number > 259 are partitions, not devices)
+                if let Ok(major) = parts[0].parse::<u32>() {
+                    if major < 259 { // Physical devices only
+                        if let (Ok(read_ops), Ok(read_bytes), Ok(write_ops), Ok(write_bytes)) = (
+                            parts[3].parse::<u64>(),
+                            parts[5].parse::<u64>(),
+                            parts[7].parse::<u64>(),
+                            parts[9].parse::<u64>()
+                        ) {
+                            total_read_ops += read_ops;
+                            total_read_bytes += read_bytes * 512; // Convert sectors to bytes
+                            total_write_ops += write_ops;
+                            total_write_bytes += write_bytes * 512;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    (total_read_bytes, total_write_bytes, total_read_ops, total_write_ops)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_disk_io_stats_linux() -> (u64, u64, u64, u64) {
+    (0, 0, 0, 0)
+}
+
+/// Get network connections on Linux
+#[cfg(target_os = "linux")]
+fn get_network_connections() -> Vec<NetworkConnection> {
+    use std::fs;
+    let mut connections = Vec::new();
+
+    // Read TCP connections
+    if let Ok(content) = fs::read_to_string("/proc/net/tcp") {
+        for line in content.lines().skip(1) { // Skip header
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() >= 10 {
+                if let (Ok(local_addr), Ok(remote_addr), Ok(state)) = (
+                    u32::from_str_radix(&parts[1][6..], 16), // Remove "00000000:" prefix
+                    u32::from_str_radix(&parts[2][6..], 16),
+                    u8::from_str_radix(parts[3], 16)
+                ) {
+                    let local_port = (local_addr & 0xFFFF) as u16;
+                    let remote_port = (remote_addr & 0xFFFF) as u16;
+                    let local_ip = format!("{}.{}.{}.{}",
+                        (local_addr >> 24) & 0xFF,
+                        (local_addr >> 16) & 0xFF,
+                        (local_addr >> 8) & 0xFF,
+                        local_addr & 0xFF
+                    );
+                    let remote_ip = format!("{}.{}.{}.{}",
+                        (remote_addr >> 24) & 0xFF,
+                        (remote_addr >> 16) & 0xFF,
+                        (remote_addr >> 8) & 0xFF,
+                        remote_addr & 0xFF
+                    );
+
+                    let state_str = match state {
+                        0x01 => "ESTABLISHED",
+                        0x02 => "SYN_SENT",
+                        0x03 => "SYN_RECV",
+                        0x04 => "FIN_WAIT1",
+                        0x05 => "FIN_WAIT2",
+                        0x06 => "TIME_WAIT",
+                        0x07 => "CLOSE",
+                        0x08 => "CLOSE_WAIT",
+                        0x09 => "LAST_ACK",
+                        0x0A => "LISTEN",
+                        0x0B => "CLOSING",
+                        _ => "UNKNOWN"
+                    };
+
+                    connections.push(NetworkConnection {
+                        local_address: local_ip,
+                        remote_address: remote_ip,
+                        local_port,
+                        remote_port,
+                        state: state_str.to_string(),
+                        process_name: None, // Would need additional processing
+                        process_pid: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // Limit to first 50 connections to avoid overwhelming the UI
+    connections.truncate(50);
+    connections
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_network_connections() -> Vec<NetworkConnection> {
+    Vec::new()
+}
+
+/// Fallback GPU detection using system components
+/// Get battery information safely without external dependencies
+/// This provides basic battery monitoring using system files directly
+fn get_battery_info_safely() -> Option<BatteryInfo> {
+    // Try to read battery info from /sys/class/power_supply on Linux
+    #[cfg(target_os = "linux")]
+    {
+        use std::fs;
+
+        // Look for battery directories
+        if let Ok(entries) = fs::read_dir("/sys/class/power_supply") {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if let Some(dir_name) = path.file_name() {
+                    let dir_str = dir_name.to_string_lossy();
+                    if dir_str.starts_with("BAT") {
+                        // Found a battery, try to read its information
+                        let capacity_path = path.join("capacity");
+                        let status_path = path.join("status");
+
+                        let percentage = fs::read_to_string(&capacity_path)
+                            .ok()
+                            .and_then(|s| s.trim().parse::<f32>().ok());
+
+                        let status = fs::read_to_string(&status_path)
+                            .ok()
+                            .map(|s| s.trim().to_string());
+
+                        if let Some(percentage) = percentage {
+                            return Some(BatteryInfo {
+                                percentage,
+                                is_charging: status.as_ref().is_some_and(|s| s == "Charging"),
+                                time_to_full: None, // Would need more complex calculation
+                                time_to_empty: None, // Would need more complex calculation
+                                power_consumption: None, // Would need additional files
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // For other platforms or if reading fails, return None
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        None
+    }
+}
+
+// State tracking for network speed calculation (per-second rates)
+struct NetworkState {
+    last_transmitted: u64,
+    last_received: u64,
+    last_update: Instant,
+}
+
+static NETWORK_STATE: Mutex<Option<NetworkState>> = Mutex::new(None);
+
+// State tracking for disk I/O calculation (per-second rates)
+struct DiskIOState {
+    last_read_bytes: u64,
+    last_write_bytes: u64,
+    last_read_ops: u64,
+    last_write_ops: u64,
+    last_update: Instant,
+}
+
+static DISK_IO_STATE: Mutex<Option<DiskIOState>> = Mutex::new(None);
+
+fn get_gpu_info_from_components(components: &sysinfo::Components) -> Option<GpuInfo> {
+    components.iter()
+        .find(|c| c.label().to_lowercase().contains("gpu") ||
+                 c.label().to_lowercase().contains("graphics"))
+        .and_then(|gpu_comp| {
+            // Only return if we have temperature data (meaningful information)
+            // Don't return placeholder zeros for usage/memory
+            if let Some(temp) = gpu_comp.temperature() {
+                Some(GpuInfo {
+                    name: gpu_comp.label().to_string(),
+                    usage: 0.0, // Not available from components - will be handled by frontend
+                    memory_used: 0,
+                    memory_total: 0,
+                    temperature: Some(temp),
+                })
+            } else {
+                None // No meaningful GPU data available
+            }
+        })
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_system_health() -> Result<SystemHealthData, String> {
+    // Set timeout for system health monitoring (30 seconds)
+    let health_timeout = Duration::from_secs(30);
+
+    match timeout(health_timeout, async {
+        let mut sys = System::new();
+
+    // Refresh system information
+    sys.refresh_cpu_usage();
+    sys.refresh_memory();
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+    // CPU data
+    let cpu_usage = sys.global_cpu_usage();
+    let cpu_cores = sys.cpus().len();
+    let cpu_frequency = sys.cpus().first().map(|cpu| cpu.frequency() as f32).unwrap_or(0.0);
+    let core_usages: Vec<f32> = sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+
+    // Memory data
+    let total_memory = sys.total_memory();
+    let used_memory = sys.used_memory();
+    let available_memory = sys.available_memory();
+
+    // Swap data
+    let swap_total = sys.total_swap();
+    let swap_used = sys.used_swap();
+
+    // Network data (enhanced) - calculate per-second rates
+    let networks = Networks::new_with_refreshed_list();
+    let mut current_transmitted = 0u64;
+    let mut current_received = 0u64;
+    let mut network_interfaces = Vec::new();
+
+    for (interface_name, data) in &networks {
+        current_transmitted += data.total_transmitted();
+        current_received += data.total_received();
+        network_interfaces.push(NetworkInterfaceInfo {
+            name: interface_name.clone(),
+            received: data.total_received(),
+            transmitted: data.total_transmitted(),
+            packets_received: 0, // Would need platform-specific APIs
+            packets_transmitted: 0,
+            errors_received: 0,
+            errors_transmitted: 0,
+        });
+    }
+
+    // Calculate per-second rates using state tracking
+    let mut network_up: u64 = 0;
+    let mut network_down: u64 = 0;
+
+    let mut network_state_guard = NETWORK_STATE.lock().unwrap();
+    let now = Instant::now();
+
+    if let Some(ref mut state) = *network_state_guard {
+        let elapsed = now.duration_since(state.last_update).as_secs_f64();
+
+        // Only calculate if we have a valid time interval (between 0.1 and 10 seconds)
+        if elapsed >= 0.1 && elapsed <= 10.0 {
+            // Calculate bytes per second (handle potential counter wraparound)
+            if current_transmitted >= state.last_transmitted {
+                network_up = ((current_transmitted - state.last_transmitted) as f64 / elapsed) as u64;
+            }
+            if current_received >= state.last_received {
+                network_down = ((current_received - state.last_received) as f64 / elapsed) as u64;
+            }
+        }
+
+        // Update state
+        state.last_transmitted = current_transmitted;
+        state.last_received = current_received;
+        state.last_update = now;
+    } else {
+        // First run - initialize state but return 0 for rates (need second measurement)
+        *network_state_guard = Some(NetworkState {
+            last_transmitted: current_transmitted,
+            last_received: current_received,
+            last_update: now,
+        });
+        // network_up and network_down remain 0 on first call
+    }
+
+    // Network connections
+    let active_connections = get_network_connections();
+
+    // Disk I/O data (enhanced) - calculate per-second rates
+    let (current_read_bytes, current_write_bytes, current_read_ops, current_write_ops) = {
+        #[cfg(target_os = "linux")]
+        {
+            get_disk_io_stats_linux()
+        }
+        #[cfg(target_os = "macos")]
+        {
+            // macOS implementation would go here
+            (0, 0, 0, 0)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            // Windows implementation would go here
+            (0, 0, 0, 0)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            (0, 0, 0, 0)
+        }
+    };
+
+    // Calculate per-second rates using state tracking
+    let mut disk_read_bytes: u64 = 0;
+    let mut disk_write_bytes: u64 = 0;
+    let mut disk_read_ops: u64 = 0;
+    let mut disk_write_ops: u64 = 0;
+
+    let mut disk_state_guard = DISK_IO_STATE.lock().unwrap();
+    let now = Instant::now();
+
+    if let Some(ref mut state) = *disk_state_guard {
+        let elapsed = now.duration_since(state.last_update).as_secs_f64();
+
+        if elapsed >= 0.1 && elapsed <= 10.0 {
+            if current_read_bytes >= state.last_read_bytes {
+                disk_read_bytes = ((current_read_bytes - state.last_read_bytes) as f64 / elapsed) as u64;
+            }
+            if current_write_bytes >= state.last_write_bytes {
+                disk_write_bytes = ((current_write_bytes - state.last_write_bytes) as f64 / elapsed) as u64;
+            }
+            if current_read_ops >= state.last_read_ops {
+                disk_read_ops = ((current_read_ops - state.last_read_ops) as f64 / elapsed) as u64;
+            }
+            if current_write_ops >= state.last_write_ops {
+                disk_write_ops = ((current_write_ops - state.last_write_ops) as f64 / elapsed) as u64;
+            }
+        }
+
+        state.last_read_bytes = current_read_bytes;
+        state.last_write_bytes = current_write_bytes;
+        state.last_read_ops = current_read_ops;
+        state.last_write_ops = current_write_ops;
+        state.last_update = now;
+    } else {
+        *disk_state_guard = Some(DiskIOState {
+            last_read_bytes: current_read_bytes,
+            last_write_bytes: current_write_bytes,
+            last_read_ops: current_read_ops,
+            last_write_ops: current_write_ops,
+            last_update: now,
+        });
+    }
+
+    // Function to read CPU temperature from lm-sensors
+    fn get_cpu_temperature_from_sensors() -> Option<f32> {
+        use std::process::Command;
+
+        // Try to run sensors command
+        let output = Command::new("sensors")
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        let mut package_temp: Option<f32> = None;
+
+        // First, look specifically for "Package id 0:" (most accurate CPU package temp)
+        for line in output_str.lines() {
+            if line.contains("Package id 0:") {
+                // Extract temperature value (e.g., "+85.0°C" -> 85.0)
+                if let Some(temp_str) = line.split('+').nth(1) {
+                    if let Some(temp_val) = temp_str.split('°').next() {
+                        if let Ok(temp) = temp_val.trim().parse::<f32>() {
+                            package_temp = Some(temp);
+                            break; // Found package temp, use this
+                        }
+                    }
+                }
+            }
+        }
+
+        // If we found package temp, return it
+        if package_temp.is_some() {
+            return package_temp;
+        }
+
+        // Fallback: Look for coretemp adapter and get temp1 (Package temp)
+        let mut in_coretemp = false;
+        for line in output_str.lines() {
+            if line.contains("coretemp") {
+                in_coretemp = true;
+                continue;
+            }
+            if in_coretemp && line.contains("temp1:") {
+                // Extract temperature value (e.g., "+85.0°C" -> 85.0)
+                if let Some(temp_str) = line.split('+').nth(1) {
+                    if let Some(temp_val) = temp_str.split('°').next() {
+                        if let Ok(temp) = temp_val.trim().parse::<f32>() {
+                            return Some(temp);
+                        }
+                    }
+                }
+            }
+            // Reset if we hit a new adapter
+            if line.starts_with("Adapter:") && in_coretemp {
+                in_coretemp = false;
+            }
+        }
+
+        None
+    }
+
+    // Temperature data from thermal zones (sysinfo)
+    let components = Components::new_with_refreshed_list();
+    let mut cpu_temp = 0.0;
+    let mut system_temp = 0.0;
+    let mut gpu_temp: Option<f32> = None;
+
+    for component in &components {
+        if let Some(temp) = component.temperature() {
+            let label = component.label().to_lowercase();
+
+            if label.contains("cpu") || label.contains("processor") || label.contains("x86_pkg_temp") {
+                cpu_temp = temp;
+            } else if label.contains("gpu") {
+                gpu_temp = Some(temp);
+            } else if temp > system_temp {
+                // Use the highest temperature as system temp
+                system_temp = temp;
+            }
+        }
+    }
+
+    // Fallback: Try to read x86_pkg_temp directly from thermal zones if sysinfo didn't find it
+    if cpu_temp == 0.0 {
+        use std::fs;
+        if let Ok(entries) = fs::read_dir("/sys/class/thermal") {
+            for entry in entries.flatten() {
+                if let Ok(zone_type) = fs::read_to_string(entry.path().join("type")) {
+                    if zone_type.trim() == "x86_pkg_temp" {
+                        if let Ok(temp_str) = fs::read_to_string(entry.path().join("temp")) {
+                            if let Ok(temp_millidegrees) = temp_str.trim().parse::<f32>() {
+                                cpu_temp = temp_millidegrees / 1000.0;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Get CPU temperature from lm-sensors (primary, most accurate)
+    // Fallback to thermal zone if sensors unavailable
+    let cpu_sensors_temp = get_cpu_temperature_from_sensors();
+    let cpu_temp_final = cpu_sensors_temp.unwrap_or(cpu_temp);
+
+    // GPU detection (enhanced with NVML support for NVIDIA GPUs)
+    let gpu_info = {
+        #[cfg(feature = "gpu-monitoring")]
+        {
+            // Try NVML first for NVIDIA GPUs
+            if let Ok(nvml) = nvml_wrapper::Nvml::init() {
+                if let Ok(device) = nvml.device_by_index(0) {
+                    if let (Ok(name), Ok(memory_info), Ok(utilization), Ok(temp)) = (
+                        device.name(),
+                        device.memory_info(),
+                        device.utilization_rates(),
+                        device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu)
+                    ) {
+                        Some(GpuInfo {
+                            name,
+                            usage: utilization.gpu as f32,
+                            memory_used: memory_info.used,
+                            memory_total: memory_info.total,
+                            temperature: Some(temp as f32),
+                        })
+                    } else {
+                        // Fallback to component-based detection
+                        get_gpu_info_from_components(&components)
+                    }
+                } else {
+                    get_gpu_info_from_components(&components)
+                }
+            } else {
+                get_gpu_info_from_components(&components)
+            }
+        }
+        #[cfg(not(feature = "gpu-monitoring"))]
+        {
+            get_gpu_info_from_components(&components)
+        }
+    };
+
+    // Process monitoring (top 10 by CPU usage)
+    let mut processes: Vec<_> = sys.processes().iter().collect();
+    processes.sort_by(|a, b| b.1.cpu_usage().partial_cmp(&a.1.cpu_usage()).unwrap_or(std::cmp::Ordering::Equal));
+
+    let top_processes: Vec<ProcessInfo> = processes.iter().take(10).map(|(pid, process)| {
+        ProcessInfo {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().to_string(),
+            cpu_usage: process.cpu_usage(),
+            memory_usage: process.memory(),
+            status: format!("{:?}", process.status()),
+            user_id: None, // Would need additional platform-specific code
+        }
+    }).collect();
+
+    // Load average (Unix systems only)
+    let load_average = {
+        #[cfg(unix)]
+        {
+            use std::fs;
+            if let Ok(content) = fs::read_to_string("/proc/loadavg") {
+                let parts: Vec<&str> = content.split_whitespace().collect();
+                if parts.len() >= 3 {
+                    if let (Ok(one), Ok(five), Ok(fifteen)) = (
+                        parts[0].parse::<f64>(),
+                        parts[1].parse::<f64>(),
+                        parts[2].parse::<f64>()
+                    ) {
+                        Some(LoadAverage {
+                            one_minute: one,
+                            five_minutes: five,
+                            fifteen_minutes: fifteen,
+                        })
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    };
+
+    // Network interfaces are already populated in the loop above (lines 674-682)
+
+    // Battery information (for laptops)
+    // NOTE: Battery monitoring has been removed due to security vulnerability
+    // in the nix crate dependency (RUSTSEC-2021-0119). The battery crate uses an
+    // outdated version of nix that contains an out-of-bounds write vulnerability.
+    // For security, we provide basic battery info via direct system file access.
+    let battery_info = get_battery_info_safely();
+
+    let temperatures = Temperatures {
+        cpu: cpu_temp_final,  // Primary: sensors first, thermal zone fallback
+        cpu_sensors: cpu_sensors_temp.unwrap_or(0.0),
+        system: system_temp,
+        gpu: gpu_temp,
+    };
+
+    SystemHealthData {
+        cpu_usage,
+        cpu_cores,
+        cpu_frequency,
+        core_usages,
+        total_memory,
+        used_memory,
+        available_memory,
+        gpu_info,
+        network_up,
+        network_down,
+        network_interfaces,
+        active_connections,
+        temperatures,
+        disk_read_bytes,
+        disk_write_bytes,
+        disk_read_ops,
+        disk_write_ops,
+        battery_info,
+        top_processes,
+        load_average,
+        swap_total,
+        swap_used,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+    }
+    }).await {
+        Ok(result) => Ok(result),
+        Err(_) => {
+            tracing::error!("System health monitoring timed out after {} seconds", health_timeout.as_secs());
+            Err("System health monitoring timed out. Please try again.".to_string())
+        }
+    }
+}
@@ -0,0 +1,934 @@
+//! Scans started from the UI, the file index they share and the disk usage tree.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::time::{timeout, Duration};
+use walkdir::WalkDir;
+
+use crate::db::DbAccess;
+use crate::jobs::{self, Job};
+use crate::leaderboard;
+use crate::notifications::{self, NotificationKind};
+use crate::safe_mode;
+use crate::scanner::{self, treemap, FileIndex, FilesystemHealthResults, HashMode, PathFilter, ScanOptions, ScanResults, StorageRecoveryResults, format_bytes};
+use crate::trash;
+
+use super::settings::{get_settings, load_settings};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TreeNode {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    #[serde(rename = "isDirectory")]
+    pub is_directory: bool,
+    #[serde(rename = "lastModified")]
+    pub last_modified: i64,  // Unix timestamp
+    #[serde(rename = "lastAccessed")]
+    pub last_accessed: i64,  // Unix timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<TreeNode>>,
+    pub expanded: bool,  // Frontend state, default false
+    pub selected: bool,  // Frontend state, default false
+    #[serde(rename = "riskLevel")]
+    pub risk_level: String,  // "safe" | "caution" | "warning"
+    #[serde(rename = "usagePattern", skip_serializing_if = "Option::is_none")]
+    pub usage_pattern: Option<String>,
+}
+
+/// The persistent file index for incremental rescans; starts empty if it can't be read
+pub(crate) fn load_file_index(app_handle: &tauri::AppHandle) -> scanner::SharedIndex {
+    app_handle
+        .db(FileIndex::load)
+        .unwrap_or_else(|e| {
+            tracing::warn!("File index unavailable, scanning from scratch: {}", e);
+            FileIndex::in_memory()
+        })
+        .shared()
+}
+
+/// Persist the index in the background; a timed-out scan may still be holding it
+pub(crate) fn save_file_index(app_handle: &tauri::AppHandle, index: &scanner::SharedIndex) {
+    let (app_handle, index) = (app_handle.clone(), Arc::clone(index));
+    let write = crate::shutdown::pending_write();
+    tokio::task::spawn_blocking(move || {
+        let _write = write;
+        let mut index = index.lock().unwrap_or_else(|e| e.into_inner());
+        tracing::info!("File index: {} entries reused, {} re-examined", index.reused, index.examined);
+        if let Err(e) = app_handle.db(|conn| index.save(conn)) {
+            tracing::warn!("Failed to save file index: {}", e);
+        }
+    });
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn start_scan(app_handle: tauri::AppHandle, mut options: ScanOptions) -> Result<ScanResults, String> {
+    tracing::info!("Starting system scan with async operations");
+
+    let scan_settings = get_settings(app_handle.clone()).await.unwrap_or_default().scan;
+    options.one_file_system = options.one_file_system.or(Some(scan_settings.one_file_system));
+    let (requested, throttle) = (options.throughput_profile, scan_settings.throttle);
+    let status = tokio::task::spawn_blocking(move || scanner::throttle::resolve(requested, &throttle))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?;
+    tracing::info!("Scanning with the {:?} profile on {:?} power", status.profile, status.power_source);
+    options.throughput_profile = Some(status.profile);
+
+    // Set timeout based on scan options (more comprehensive scans get more time)
+    let scan_timeout = if options.include_caches && options.include_packages {
+        Duration::from_secs(900) // 15 minutes for comprehensive scans
+    } else {
+        Duration::from_secs(600) // 10 minutes for basic scans
+    };
+
+    let active = scanner::begin_scan("system");
+    let index = load_file_index(&app_handle);
+    let checkpoint = scanner::checkpoint::Checkpoint::load(&app_handle, &options, options.resume.unwrap_or(true));
+    let outcome = timeout(scan_timeout, async {
+        scanner::scan_system_async(&options, Some(&app_handle), Arc::clone(active.flag()), Some(Arc::clone(&index)), Some(&checkpoint)).await
+    }).await;
+    save_file_index(&app_handle, &index);
+
+    match outcome {
+        Ok(Ok(mut results)) => {
+            if !results.cancelled {
+                checkpoint.clear();
+            }
+            leaderboard::filter_scan_results(&app_handle, &mut results);
+            scanner::identity::apply_stored_decisions(&app_handle, &mut results);
+            safe_mode::record_scanned(&results.items);
+            tracing::info!("Async scan {}: {} items, {} bytes", if results.cancelled { "cancelled" } else { "complete" }, results.total_items, results.total_size);
+            if !results.cancelled {
+                notifications::alert(
+                    &app_handle,
+                    NotificationKind::ScanComplete,
+                    "System scan complete",
+                    &format!("Found {} items totaling {}", results.total_items, format_bytes(results.total_size)),
+                )
+                .await;
+            }
+            Ok(results)
+        },
+        Ok(Err(e)) => {
+            tracing::error!("System scan failed: {}", e);
+            Err(format!("System scan failed: {}", e))
+        },
+        Err(_) => {
+            tracing::error!("System scan timed out after {} seconds", scan_timeout.as_secs());
+            Err(format!("System scan timed out after {} seconds. Scan again to resume where it stopped, or scan with fewer options enabled.", scan_timeout.as_secs()))
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn scan_filesystem_health(app_handle: tauri::AppHandle, include_junk_files: Option<bool>) -> Result<FilesystemHealthResults, String> {
+    tracing::info!("Starting filesystem health check");
+
+    // Set a reasonable timeout for filesystem scanning (5 minutes)
+    let scan_timeout = Duration::from_secs(300);
+
+    let one_file_system = load_settings(&app_handle).scan.one_file_system;
+    let junk_files = include_junk_files.unwrap_or(false);
+    let active = scanner::begin_scan("filesystem_health");
+    let cancel = Arc::clone(active.flag());
+    match timeout(scan_timeout, tokio::task::spawn_blocking(move || scanner::scan_filesystem_health(&cancel, one_file_system, junk_files))).await {
+        Ok(Err(e)) => Err(format!("Filesystem health check task failed: {}", e)),
+        Ok(Ok(results)) if results.cancelled => {
+            tracing::info!("Filesystem health check cancelled: {} items so far", results.total_items);
+            record_health_items(&results);
+            Ok(results)
+        }
+        Ok(Ok(results)) => {
+            record_health_items(&results);
+            tracing::info!("Filesystem health check complete: {} items, {} bytes", results.total_items, results.total_size);
+
+            // Store results in database for Dashboard display
+            let _ = app_handle.db(|conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO last_scan_results (scan_type, total_size, total_items, timestamp, scan_data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    (
+                        "filesystem_health",
+                        results.total_size as i64,
+                        results.total_items as i64,
+                        chrono::Utc::now().timestamp(),
+                        serde_json::to_string(&results).unwrap_or_default()
+                    )
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            });
+
+            notifications::alert(
+                &app_handle,
+                NotificationKind::ScanComplete,
+                "Filesystem health check complete",
+                &format!("Found {} items totaling {}", results.total_items, format_bytes(results.total_size)),
+            )
+            .await;
+
+            Ok(results)
+        },
+        Err(_) => {
+            tracing::error!("Filesystem health check timed out after {} seconds", scan_timeout.as_secs());
+            Err("Filesystem health check timed out. The scan took too long to complete.".to_string())
+        }
+    }
+}
+
+fn record_health_items(results: &FilesystemHealthResults) {
+    safe_mode::record_scanned(
+        results.empty_directories.iter()
+            .chain(&results.broken_symlinks)
+            .chain(&results.orphaned_temp_files)
+            .chain(&results.broken_launchers)
+            .chain(&results.junk_files),
+    );
+}
+
+// Helper function to populate file_access table with file metadata; runs as a
+// background job (jobs::Job::PopulateFileAccess)
+pub(crate) fn populate_file_access_table(app_handle: &tauri::AppHandle, paths: &[String]) -> Result<(), String> {
+    let home = match dirs::home_dir() {
+        Some(h) => h,
+        None => {
+            tracing::warn!("Cannot determine home directory for file_access table population");
+            return Ok(()); // Return success - this is non-critical
+        }
+    };
+
+    let scan_dirs = vec![
+        home.join("Downloads"),
+        home.join("Documents"),
+        home.join("Desktop"),
+        home.join("Pictures"),
+        home.join("Videos"),
+        home.join("Music"),
+    ];
+
+    let mut files_tracked = 0;
+    let mut errors_encountered = 0;
+    let timestamp = chrono::Utc::now().timestamp();
+    let one_file_system = load_settings(app_handle).scan.one_file_system;
+
+    for dir in scan_dirs {
+        if !dir.exists() {
+            continue;
+        }
+
+        // Limit depth and number of files to avoid performance issues
+        // Use filter_map to skip errors gracefully
+        for entry in WalkDir::new(&dir)
+            .max_depth(3)
+            .same_file_system(one_file_system)
+            .into_iter()
+            .filter_map(|e| {
+                match e {
+                    Ok(entry) => Some(entry),
+                    Err(e) => {
+                        tracing::debug!("WalkDir error (skipping): {}", e);
+                        None
+                    }
+                }
+            })
+            .take(10000) // Limit to 10k files per directory
+        {
+            let path = entry.path();
+            if path.is_file() {
+                if let Ok(metadata) = path.metadata() {
+                    let size = metadata.len();
+                    // Use modification time as last_access if available, otherwise use current time
+                    let last_access = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d: std::time::Duration| d.as_secs() as i64)
+                        .unwrap_or(timestamp);
+
+                    let path_str = path.to_string_lossy().to_string();
+
+                    if let Err(e) = app_handle.db(|conn| {
+                        conn.execute(
+                            "INSERT OR REPLACE INTO file_access (path, size, last_access) VALUES (?1, ?2, ?3)",
+                            (&path_str, size as i64, last_access),
+                        )?;
+                        Ok::<(), rusqlite::Error>(())
+                    }) {
+                        errors_encountered += 1;
+                        if errors_encountered <= 10 {
+                            tracing::warn!("Failed to insert file_access record for {}: {}", path_str, e);
+                        } else if errors_encountered == 11 {
+                            tracing::warn!("Suppressing further file_access insert errors ({} total so far)", errors_encountered);
+                        }
+                    } else {
+                        files_tracked += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    // Also track files from the scan results
+    for path in paths {
+        if let Ok(metadata) = std::fs::metadata(path) {
+            let last_access = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d: std::time::Duration| d.as_secs() as i64)
+                .unwrap_or(timestamp);
+
+            if let Err(e) = app_handle.db(|conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO file_access (path, size, last_access) VALUES (?1, ?2, ?3)",
+                    (path, metadata.len() as i64, last_access),
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            }) {
+                errors_encountered += 1;
+                if errors_encountered <= 10 {
+                    tracing::warn!("Failed to insert file_access record for {}: {}", path, e);
+                }
+            } else {
+                files_tracked += 1;
+            }
+        }
+    }
+
+    if files_tracked > 0 {
+        tracing::info!("Populated file_access table with {} files ({} errors encountered)", files_tracked, errors_encountered);
+    } else if errors_encountered > 0 {
+        tracing::warn!("File_access table population encountered {} errors, no files tracked", errors_encountered);
+    } else {
+        tracing::info!("File_access table population completed (no files to track)");
+    }
+
+    // Always return Ok - this is non-critical and shouldn't fail the scan
+    Ok(())
+}
+
+/// Dedicated scan command for DiskPulse that populates file_access table
+/// This is optimized for finding unused files rather than full system analysis
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn scan_for_old_files(_app_handle: tauri::AppHandle) -> Result<ScanResults, String> {
+    // Temporarily disabled due to compilation issues
+    Err("Function temporarily disabled".to_string())
+}
+
+#[tauri::command]
+#[allow(dead_code)]
+pub async fn scan_filesystem_tree(
+    root_path: String,
+    max_depth: usize,
+    include_hidden: bool,
+    size_threshold: u64,
+    filter_patterns: Vec<String>,
+) -> Result<Vec<TreeNode>, String> {
+    let scan_timeout = Duration::from_secs(60);
+
+    // Resolve the root path
+    let root_path_buf = if root_path == "~" {
+        dirs::home_dir().ok_or("Cannot determine home directory")?
+    } else {
+        PathBuf::from(root_path)
+    };
+
+    if !root_path_buf.exists() {
+        return Err(format!("Path does not exist: {}", root_path_buf.display()));
+    }
+
+    // Validate path for security
+    let canonical_path = root_path_buf.canonicalize()
+        .map_err(|e| format!("Failed to canonicalize path: {}", e))?;
+
+    // Scan the filesystem tree in a blocking task with timeout
+    let canonical_path_clone = canonical_path.clone();
+    let scan_future = tokio::task::spawn_blocking(move || {
+        scan_filesystem_tree_recursive(
+            &canonical_path_clone,
+            max_depth,
+            include_hidden,
+            size_threshold,
+            &filter_patterns,
+        )
+    });
+
+    match timeout(scan_timeout, scan_future).await {
+        Ok(Ok(Ok(items))) => Ok(items),
+        Ok(Ok(Err(e))) => Err(e),
+        Ok(Err(e)) => Err(format!("Scan task failed: {}", e)),
+        Err(_) => {
+            tracing::error!("Filesystem tree scan timed out after {} seconds", scan_timeout.as_secs());
+            Err(format!("Filesystem scan timed out after {} seconds", scan_timeout.as_secs()))
+        }
+    }
+}
+
+/// Directory sizes under `root` aggregated for a treemap, `depth` levels deep,
+/// measured in a single walk
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_disk_usage_treemap(root: String, depth: Option<usize>) -> Result<treemap::UsageNode, String> {
+    let scan_timeout = Duration::from_secs(120);
+    let root = if root == "~" {
+        dirs::home_dir().ok_or("Cannot determine home directory")?
+    } else {
+        PathBuf::from(root)
+    };
+    let root = root.canonicalize().map_err(|e| format!("Cannot open {}: {}", root.display(), e))?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    let depth = depth.unwrap_or(3).min(treemap::MAX_DEPTH);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let walk_cancel = Arc::clone(&cancel);
+    let walk = tokio::task::spawn_blocking(move || treemap::build(&root, depth, treemap::MAX_CHILDREN, &walk_cancel));
+    match timeout(scan_timeout, walk).await {
+        Ok(Ok(Some(tree))) => Ok(tree),
+        Ok(Ok(None)) => Err("Disk usage scan was cancelled".to_string()),
+        Ok(Err(e)) => Err(format!("Disk usage task failed: {}", e)),
+        Err(_) => {
+            cancel.store(true, Ordering::Relaxed);
+            Err(format!("Disk usage scan timed out after {} seconds", scan_timeout.as_secs()))
+        }
+    }
+}
+
+/// Recursively scan a directory and collect file/directory information
+#[allow(dead_code)]
+fn scan_filesystem_tree_recursive(
+    root_path: &Path,
+    max_depth: usize,
+    include_hidden: bool,
+    size_threshold: u64,
+    filter_patterns: &[String],
+) -> Result<Vec<TreeNode>, String> {
+    let mut result = Vec::new();
+
+    // Scan the root directory entries
+    let entries = std::fs::read_dir(root_path)
+        .map_err(|e| format!("Failed to read directory {}: {}", root_path.display(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let entry_path = entry.path();
+
+        // Skip hidden files if not requested
+        if !include_hidden {
+            if let Some(filename) = entry_path.file_name() {
+                if filename.to_string_lossy().starts_with('.') {
+                    continue;
+                }
+            }
+        }
+
+        // Check filter patterns
+        let should_include = if filter_patterns.is_empty() {
+            true
+        } else {
+            let filename = entry_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("");
+            filter_patterns.iter().any(|pattern| filename.contains(pattern))
+        };
+
+        if !should_include {
+            continue;
+        }
+
+        let metadata = entry.metadata()
+            .map_err(|e| format!("Failed to get metadata for {}: {}", entry_path.display(), e))?;
+
+        let size = if metadata.is_file() {
+            metadata.len()
+        } else {
+            // For directories, get size (simplified)
+            metadata.len() // Just use directory size for now
+        };
+
+        // Skip files below size threshold
+        if metadata.is_file() && size < size_threshold {
+            continue;
+        }
+
+        // Get file timestamps
+        let last_modified = metadata.modified()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        let last_accessed = metadata.accessed()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        let risk_level = assess_risk_level(&entry_path, metadata.is_dir());
+
+        let children = if metadata.is_dir() && max_depth > 0 {
+            match scan_filesystem_tree_recursive(&entry_path, max_depth - 1, include_hidden, size_threshold, filter_patterns) {
+                Ok(children) => Some(children),
+                Err(_) => None, // Skip directories we can't read
+            }
+        } else {
+            None
+        };
+
+        let node = TreeNode {
+            id: entry_path.to_string_lossy().to_string(),
+            name: entry_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            path: entry_path.to_string_lossy().to_string(),
+            size,
+            is_directory: metadata.is_dir(),
+            last_modified,
+            last_accessed,
+            children,
+            expanded: false,
+            selected: false,
+            risk_level,
+            usage_pattern: None,
+        };
+
+        result.push(node);
+    }
+
+    // Sort by name
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(result)
+}
+
+#[allow(dead_code)]
+fn scan_directory_recursive(
+    path: &Path,
+    results: &mut Vec<TreeNode>,
+    current_depth: usize,
+    max_depth: usize,
+    include_hidden: bool,
+    size_threshold: u64,
+    filter_patterns: &[String],
+) -> Result<(), String> {
+    if current_depth > max_depth {
+        return Ok(());
+    }
+
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| format!("Failed to read directory {}: {}", path.display(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let entry_path = entry.path();
+
+        // Skip hidden files if not requested
+        if !include_hidden {
+            if let Some(filename) = entry_path.file_name() {
+                if filename.to_string_lossy().starts_with('.') {
+                    continue;
+                }
+            }
+        }
+
+        // Check filter patterns
+        let should_include = if filter_patterns.is_empty() {
+            true
+        } else {
+            let filename = entry_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("");
+            filter_patterns.iter().any(|pattern| filename.contains(pattern))
+        };
+
+        if !should_include {
+            continue;
+        }
+
+        let metadata = entry.metadata()
+            .map_err(|e| format!("Failed to get metadata for {}: {}", entry_path.display(), e))?;
+
+        let size = if metadata.is_file() {
+            metadata.len()
+        } else {
+            // For directories, calculate total size recursively
+            trash::get_dir_size(&entry_path)
+        };
+
+        // Skip files below size threshold
+        if metadata.is_file() && size < size_threshold {
+            continue;
+        }
+
+        // Get file timestamps
+        let last_modified = metadata.modified()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        let last_accessed = metadata.accessed()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        let risk_level = assess_risk_level(&entry_path, metadata.is_dir());
+
+        let node = TreeNode {
+            id: entry_path.to_string_lossy().to_string(),
+            name: entry_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            path: entry_path.to_string_lossy().to_string(),
+            size,
+            is_directory: metadata.is_dir(),
+            last_modified,
+            last_accessed,
+            children: None, // Will be populated in build_tree_structure
+            expanded: false,
+            selected: false,
+            risk_level,
+            usage_pattern: None,
+        };
+
+        results.push(node);
+
+        // Recurse into directories
+        if metadata.is_dir() && current_depth < max_depth {
+            scan_directory_recursive(
+                &entry_path,
+                results,
+                current_depth + 1,
+                max_depth,
+                include_hidden,
+                size_threshold,
+                filter_patterns,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Build tree structure from flat list of nodes
+#[allow(dead_code)]
+fn build_tree_structure(items: &[TreeNode], root_path: &Path) -> Result<Vec<TreeNode>, String> {
+    let root_str = root_path.to_string_lossy().to_string();
+    let mut tree_map: std::collections::HashMap<String, TreeNode> = std::collections::HashMap::new();
+    let mut children_map: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+
+    // First pass: create map of all nodes
+    for item in items {
+        let path = item.path.clone();
+        tree_map.insert(path.clone(), item.clone());
+    }
+
+    // Second pass: build parent-child relationships
+    for item in items {
+        let item_path = Path::new(&item.path);
+        let parent_path = item_path.parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| root_str.clone());
+
+        // Only add to children map if parent exists and is not the same as the item
+        if parent_path != item.path && tree_map.contains_key(&parent_path) {
+            children_map.entry(parent_path)
+                .or_insert_with(Vec::new)
+                .push(item.path.clone());
+        }
+    }
+
+    // Third pass: find root-level items and build tree structure
+    let mut tree_items = Vec::new();
+    for item in items {
+        let item_path = Path::new(&item.path);
+        let parent_path = item_path.parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| root_str.clone());
+
+        // Check if this is a root-level item (parent is the root path)
+        if parent_path == root_str && item.path != root_str {
+            let mut node = item.clone();
+            if let Some(children_paths) = children_map.get(&node.path) {
+                let mut children = Vec::new();
+                for child_path in children_paths {
+                    if let Some(child_node) = build_tree_node_recursive(child_path.clone(), &tree_map, &children_map) {
+                        children.push(child_node);
+                    }
+                }
+                if !children.is_empty() {
+                    node.children = Some(children);
+                }
+            }
+            tree_items.push(node);
+        }
+    }
+
+    Ok(tree_items)
+}
+
+/// Recursively build tree node with children
+#[allow(dead_code)]
+fn build_tree_node_recursive(
+    path: String,
+    tree_map: &std::collections::HashMap<String, TreeNode>,
+    children_map: &std::collections::HashMap<String, Vec<String>>,
+) -> Option<TreeNode> {
+    let mut node = tree_map.get(&path)?.clone();
+
+    if let Some(children_paths) = children_map.get(&path) {
+        let mut children = Vec::new();
+        for child_path in children_paths {
+            if let Some(child_node) = build_tree_node_recursive(child_path.clone(), tree_map, children_map) {
+                children.push(child_node);
+            }
+        }
+        if !children.is_empty() {
+            node.children = Some(children);
+        }
+    }
+
+    Some(node)
+}
+
+/// Assess risk level based on file path and type
+#[allow(dead_code)]
+fn assess_risk_level(path: &Path, is_directory: bool) -> String {
+    if is_directory {
+        return "safe".to_string();
+    }
+
+    let path_str = path.to_string_lossy().to_lowercase();
+
+    // High risk paths
+    if path_str.contains("/etc/") ||
+       path_str.contains("/usr/bin/") ||
+       path_str.contains("/usr/sbin/") ||
+       path_str.contains("/bin/") ||
+       path_str.contains("/sbin/") ||
+       path_str.contains("/lib/") ||
+       path_str.contains("/opt/") {
+        return "warning".to_string();
+    }
+
+    // Medium risk - system configs
+    if path_str.contains("/.config/") ||
+       path_str.contains("/.local/share/") ||
+       path_str.contains("/.cache/") {
+        return "caution".to_string();
+    }
+
+    // Safe - user files
+    "safe".to_string()
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn scan_storage_recovery(
+    app_handle: tauri::AppHandle,
+    hash_mode: Option<HashMode>,
+    include_globs: Option<Vec<String>>,
+    exclude_globs: Option<Vec<String>>,
+    similar_images: Option<bool>,
+) -> Result<StorageRecoveryResults, String> {
+    tracing::info!("Starting storage recovery scan");
+
+    let home = dirs::home_dir().unwrap_or_default();
+    let scan_settings = get_settings(app_handle.clone()).await.unwrap_or_default().scan;
+    let filter = PathFilter::new(&include_globs.unwrap_or_default(), &exclude_globs.unwrap_or_default(), &home)
+        .with_one_file_system(scan_settings.one_file_system);
+
+    // An explicit mode (e.g. a one-off verification) overrides the setting
+    let hash_mode = hash_mode.unwrap_or(scan_settings.duplicate_hash_mode);
+
+    // Hashing reads file contents, so it follows the profile's IO priority and read rate
+    let throttle = scan_settings.throttle;
+    let status = tokio::task::spawn_blocking(move || scanner::throttle::resolve(None, &throttle))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?;
+    let profile = status.profile.limits();
+
+    // Set a reasonable timeout for storage scanning (10 minutes - more complex analysis)
+    let scan_timeout = Duration::from_secs(600);
+
+    let active = scanner::begin_scan("storage_recovery");
+    let cancel = Arc::clone(active.flag());
+    let index = load_file_index(&app_handle);
+    let scan_index = Arc::clone(&index);
+    let outcome = timeout(scan_timeout, async {
+        // Run scan in blocking task to prevent blocking the async runtime
+        // This also provides better panic isolation
+        tokio::task::spawn_blocking(move || {
+            let _io_priority = profile.io_priority.enter();
+            let read_budget = scanner::ReadBudget::new(profile.read_bytes_per_sec);
+            scanner::scan_storage_recovery(&cancel, &scan_index, hash_mode, &filter, similar_images.unwrap_or(false), &read_budget)
+        }).await
+    }).await;
+    save_file_index(&app_handle, &index);
+
+    match outcome {
+        Ok(Ok(results)) => {
+            let results = results.map_err(|e| {
+                let error_msg = format!("Storage recovery scan failed: {}", e);
+                tracing::error!("{}", error_msg);
+                error_msg
+            })?;
+
+            tracing::info!("Storage recovery scan complete: {} duplicates, {} large files, {} old downloads, {} bytes recoverable",
+                           results.duplicates.len(), results.large_files.len(), results.old_downloads.len(), results.total_recoverable_size);
+
+            // Populate file_access table with scanned files for old files detection,
+            // after the results are returned
+            let paths: Vec<String> = results.duplicates.iter()
+                .flat_map(|g| g.files.iter())
+                .chain(results.large_files.iter())
+                .chain(results.old_downloads.iter())
+                .map(|file| file.path.clone())
+                .collect();
+            jobs::submit(&app_handle, Job::PopulateFileAccess { paths });
+
+            // Indexed above so the leaderboard stays complete, but not suggested again
+            let mut results = results;
+            leaderboard::filter_storage_recovery(&app_handle, &mut results);
+
+            // After filtering, so the suggested keeper is a copy the user sees
+            let keep_dirs: Vec<PathBuf> = scan_settings.duplicate_keep_dirs.iter()
+                .map(|dir| scanner::glob::expand_home(dir, &home))
+                .collect();
+            crate::duplicates::mark_suggestions(&mut results.duplicates, scan_settings.duplicate_keep_strategy, &keep_dirs);
+            safe_mode::record_scanned(
+                results.duplicates.iter().flat_map(|g| g.files.iter())
+                    .chain(results.similar_images.iter().flat_map(|g| g.files.iter()))
+                    .chain(&results.large_files)
+                    .chain(&results.old_downloads)
+                    .chain(&results.old_snap_revisions)
+                    .chain(&results.stale_snapshots),
+            );
+
+            // Partial results would understate the Dashboard figures
+            if results.cancelled {
+                tracing::info!("Storage recovery scan cancelled, keeping last stored results");
+                return Ok(results);
+            }
+
+            // Store results in database for Dashboard display
+            // Non-critical, so we continue even if it fails
+            if let Err(e) = app_handle.db(|conn| {
+                let scan_data = serde_json::to_string(&results)
+                    .unwrap_or_else(|_| "{}".to_string());
+
+                conn.execute(
+                    "INSERT OR REPLACE INTO last_scan_results (scan_type, total_size, total_items, timestamp, scan_data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    (
+                        "storage_recovery",
+                        results.total_recoverable_size as i64,
+                        (results.duplicates.len() + results.large_files.len() + results.old_downloads.len()) as i64,
+                        chrono::Utc::now().timestamp(),
+                        scan_data
+                    )
+                )?;
+                Ok::<(), rusqlite::Error>(())
+            }) {
+                tracing::warn!("Failed to store scan results in database: {}", e);
+            }
+
+            notifications::alert(
+                &app_handle,
+                NotificationKind::ScanComplete,
+                "Storage recovery scan complete",
+                &format!("{} recoverable", format_bytes(results.total_recoverable_size)),
+            )
+            .await;
+
+            Ok(results)
+        },
+        Ok(Err(e)) => {
+            let error_msg = format!("Storage recovery scan task failed: {}", e);
+            tracing::error!("{}", error_msg);
+            Err(error_msg)
+        },
+        Err(_) => {
+            let error_msg = format!("Storage recovery scan timed out after {} seconds. The scan may be processing a large number of files. Try again later or reduce the scan scope.", scan_timeout.as_secs());
+            tracing::error!("{}", error_msg);
+            Err(error_msg)
+        }
+    }
+}
+
+/// Look for duplicates below the chosen directories only, e.g. `~/Pictures`,
+/// each up to its own file limit. Cancelled as "duplicates".
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn scan_duplicates(
+    app_handle: tauri::AppHandle,
+    roots: Vec<scanner::DuplicateRoot>,
+    hash_mode: Option<HashMode>,
+    exclude_globs: Option<Vec<String>>,
+) -> Result<Vec<scanner::DuplicateGroup>, String> {
+    let home = dirs::home_dir().unwrap_or_default();
+    if roots.is_empty() {
+        return Err("Choose at least one directory to scan for duplicates".to_string());
+    }
+    if let Some(missing) = roots.iter().find(|r| !scanner::glob::expand_home(&r.path, &home).is_dir()) {
+        return Err(format!("Not a directory: {}", missing.path));
+    }
+    tracing::info!("Starting duplicate scan of {} directories", roots.len());
+
+    let scan_settings = get_settings(app_handle.clone()).await.unwrap_or_default().scan;
+    let filter = PathFilter::new(&[], &exclude_globs.unwrap_or_default(), &home)
+        .with_one_file_system(scan_settings.one_file_system);
+    let hash_mode = hash_mode.unwrap_or(scan_settings.duplicate_hash_mode);
+
+    let throttle = scan_settings.throttle;
+    let status = tokio::task::spawn_blocking(move || scanner::throttle::resolve(None, &throttle))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?;
+    let profile = status.profile.limits();
+
+    let active = scanner::begin_scan("duplicates");
+    let cancel = Arc::clone(active.flag());
+    let index = load_file_index(&app_handle);
+    let scan_index = Arc::clone(&index);
+    let scan_home = home.clone();
+    let outcome = timeout(Duration::from_secs(600), tokio::task::spawn_blocking(move || {
+        let _io_priority = profile.io_priority.enter();
+        let read_budget = scanner::ReadBudget::new(profile.read_bytes_per_sec);
+        scanner::scan_duplicates_in(&roots, &scan_home, &cancel, &scan_index, hash_mode, &filter, &read_budget)
+    }))
+    .await;
+    save_file_index(&app_handle, &index);
+
+    let mut groups = match outcome {
+        Ok(Ok(groups)) => groups.map_err(|e| format!("Duplicate scan failed: {}", e))?,
+        Ok(Err(e)) => return Err(format!("Duplicate scan task failed: {}", e)),
+        Err(_) => return Err("Duplicate scan timed out after 600 seconds. Try fewer directories or lower file limits.".to_string()),
+    };
+
+    let keep_dirs: Vec<PathBuf> = scan_settings.duplicate_keep_dirs.iter()
+        .map(|dir| scanner::glob::expand_home(dir, &home))
+        .collect();
+    crate::duplicates::mark_suggestions(&mut groups, scan_settings.duplicate_keep_strategy, &keep_dirs);
+    safe_mode::record_scanned(groups.iter().flat_map(|g| g.files.iter()));
+    tracing::info!("Duplicate scan complete: {} groups", groups.len());
+    Ok(groups)
+}
+
+/// Stop running scans ("system", "filesystem_health", "storage_recovery",
+/// "duplicates", or all when omitted); each returns what it found so far with `cancelled` set
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn cancel_scan(scan: Option<String>) -> Result<usize, String> {
+    let signalled = scanner::cancel_scans(scan.as_deref());
+    tracing::info!("Cancel requested for {} running scan(s)", signalled);
+    Ok(signalled)
+}
@@ -0,0 +1,560 @@
+//! Application settings and the cleanup schedule they drive.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::time::{timeout, Duration};
+
+use crate::agent;
+use crate::db::DbAccess;
+use crate::duplicates::KeepStrategy;
+use crate::footprint::FootprintSettings;
+use crate::monitoring::MonitoringHandle;
+use crate::notifications::{self, NotificationKind};
+use crate::operations::{guard, OperationPlan};
+use crate::projects::ProjectSettings;
+use crate::read_only;
+use crate::safe_mode::{self, SafeModeSettings};
+use crate::scanner::{format_bytes, throttle::ThrottleSettings, HashMode};
+use crate::shortcuts;
+
+use super::cleanup::quick_clean_safe_inner;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AppSettings {
+    pub trash: TrashSettings,
+    pub monitoring: MonitoringSettings,
+    pub notifications: NotificationSettings,
+    pub scan: ScanSettings,
+    pub theme: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduling: Option<SchedulingSettings>,
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+    #[serde(default)]
+    pub journal: JournalSettings,
+    #[serde(default)]
+    pub projects: ProjectSettings,
+    #[serde(default)]
+    pub memory: FootprintSettings,
+    #[serde(default)]
+    pub safe_mode: SafeModeSettings,
+    /// Only reports and exports; see `read_only`
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashSettings {
+    pub retention_days: i64,
+    pub max_size_mb: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct MonitoringSettings {
+    pub enabled: bool,
+    pub interval_hours: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct NotificationSettings {
+    pub system: bool,
+    pub tray: bool,
+    pub in_app: bool,
+    /// Drop desktop notifications during Do-Not-Disturb instead of holding them until it ends
+    #[serde(default)]
+    pub suppress_during_dnd: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ScanSettings {
+    pub include_hidden: bool,
+    pub large_file_threshold_mb: u64,
+    /// Pass old downloads through clamscan before cleaning them
+    #[serde(default)]
+    pub antivirus_scan_downloads: bool,
+    /// How storage recovery compares duplicate candidates
+    #[serde(default)]
+    pub duplicate_hash_mode: HashMode,
+    /// Which copy of a duplicate group is suggested for keeping
+    #[serde(default)]
+    pub duplicate_keep_strategy: KeepStrategy,
+    /// Copies inside these directories (`~` allowed) are kept over others,
+    /// earlier entries first
+    #[serde(default)]
+    pub duplicate_keep_dirs: Vec<String>,
+    /// Globs skipped by every scan, see `ScanOptions::exclude_globs`
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// Throughput profile per power source
+    #[serde(default)]
+    pub throttle: ThrottleSettings,
+    /// Walks stay on the filesystem they start on, see `ScanOptions::one_file_system`
+    #[serde(default = "default_one_file_system")]
+    pub one_file_system: bool,
+}
+
+fn default_one_file_system() -> bool {
+    true
+}
+
+/// Global keyboard shortcuts, registered by the shortcuts module
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ShortcutSettings {
+    pub enabled: bool,
+    pub quick_clean: Option<String>, // accelerator, e.g. "Super+Shift+C"
+    pub toggle_window: Option<String>,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quick_clean: Some("Super+Shift+C".to_string()),
+            toggle_window: Some("Super+Shift+P".to_string()),
+        }
+    }
+}
+
+/// Targets `vacuum_journal` shrinks the systemd journal to; 0 leaves that limit out
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct JournalSettings {
+    pub max_size_mb: u32,
+    pub max_age_days: u32,
+}
+
+impl Default for JournalSettings {
+    fn default() -> Self {
+        Self { max_size_mb: 500, max_age_days: 30 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SchedulingSettings {
+    pub enabled: bool,
+    pub frequency: String, // "daily", "weekly", "on_startup"
+    pub time: Option<String>, // "HH:MM" format for daily/weekly
+    pub day_of_week: Option<u8>, // 0-6 (0=Sunday) for weekly
+    pub last_run: Option<i64>, // Unix timestamp
+    pub next_run: Option<i64>, // Unix timestamp
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ScheduleStatus {
+    pub enabled: bool,
+    pub next_run: Option<i64>,
+    pub last_run: Option<i64>,
+    pub status: String, // "active", "paused", "never_run"
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            trash: TrashSettings { retention_days: 3, max_size_mb: 1000 },
+            monitoring: MonitoringSettings { enabled: true, interval_hours: 24 },
+            notifications: NotificationSettings { system: true, tray: true, in_app: true, suppress_during_dnd: false },
+            scan: ScanSettings {
+                include_hidden: false,
+                large_file_threshold_mb: 100,
+                antivirus_scan_downloads: false,
+                duplicate_hash_mode: HashMode::Sampled,
+                duplicate_keep_strategy: KeepStrategy::Newest,
+                duplicate_keep_dirs: Vec::new(),
+                exclude_globs: Vec::new(),
+                throttle: ThrottleSettings::default(),
+                one_file_system: true,
+            },
+            theme: "system".to_string(),
+            scheduling: None, // Optional, user must configure
+            shortcuts: ShortcutSettings::default(),
+            journal: JournalSettings::default(),
+            projects: ProjectSettings::default(),
+            memory: FootprintSettings::default(),
+            safe_mode: SafeModeSettings::default(),
+            read_only: false,
+        }
+    }
+}
+
+/// Saved settings, or the defaults when none are saved or they can't be read
+pub fn load_settings(app_handle: &tauri::AppHandle) -> AppSettings {
+    app_handle.db(|conn| {
+            let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = 'app_settings'")?;
+            let json: Result<String, _> = stmt.query_row([], |row| row.get(0));
+
+            match json {
+                Ok(json) => serde_json::from_str(&json).map_err(|_| rusqlite::Error::InvalidQuery),
+                Err(_) => Ok(AppSettings::default()),
+            }
+        })
+        .unwrap_or_else(|_| AppSettings::default())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_settings(app_handle: tauri::AppHandle) -> Result<AppSettings, String> {
+    // Set a timeout for settings operations (5 seconds - database read)
+    let settings_timeout = Duration::from_secs(5);
+
+    match timeout(settings_timeout, async { Ok(load_settings(&app_handle)) }).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Settings retrieval timed out after {} seconds", settings_timeout.as_secs());
+            Err("Settings retrieval timed out. Using defaults.".to_string())
+        }
+    }
+}
+
+/// Save and apply `settings`. Turning safe mode off or shortening its
+/// retention takes a `confirmation_token` for `disable_safe_mode`.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn save_settings(app_handle: tauri::AppHandle, settings: AppSettings, confirmation_token: Option<String>) -> Result<(), String> {
+    if safe_mode::weakens(&load_settings(&app_handle).safe_mode, &settings.safe_mode) {
+        let token = confirmation_token.ok_or("Safe mode: confirm turning it off before saving")?;
+        guard::authorize(&app_handle, &OperationPlan::DisableSafeMode, &token)?;
+    }
+    // Set a timeout for settings operations (5 seconds - database write)
+    let settings_timeout = Duration::from_secs(5);
+
+    match timeout(settings_timeout, async {
+        let json = serde_json::to_string(&settings).map_err(|e| format!("Failed to serialize: {}", e))?;
+
+        app_handle.db(|conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES ('app_settings', ?1)",
+                    [&json],
+                )?;
+                Ok(())
+            })
+            .map_err(|e| format!("Failed to save: {}", e))?;
+
+        // Conflicts are reported through get_shortcut_status rather than failing the save
+        shortcuts::apply(&app_handle, &settings.shortcuts);
+        safe_mode::apply(&settings.safe_mode);
+        read_only::apply(settings.read_only);
+
+        Ok(())
+    }).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Settings save timed out after {} seconds", settings_timeout.as_secs());
+            Err("Settings save timed out. Please try again.".to_string())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_schedule_settings(app_handle: tauri::AppHandle) -> Result<Option<SchedulingSettings>, String> {
+    let timeout_duration = Duration::from_secs(5);
+
+    timeout(timeout_duration, async {
+        app_handle.db(|conn| {
+            let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
+            let result: Result<String, _> = stmt.query_row(["scheduling"], |row| row.get(0));
+
+            match result {
+                Ok(json_str) => {
+                    let settings: SchedulingSettings = serde_json::from_str(&json_str)
+                        .map_err(|_e| rusqlite::Error::InvalidColumnType(0, "scheduling".to_string(), rusqlite::types::Type::Text))?;
+                    Ok(Some(settings))
+                }
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+    })
+    .await
+    .map_err(|_| "Timeout getting schedule settings".to_string())?
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+#[tauri::command]
+#[allow(dead_code)]
+pub async fn save_schedule_settings(
+    app_handle: tauri::AppHandle,
+    settings: SchedulingSettings,
+) -> Result<(), String> {
+    let timeout_duration = Duration::from_secs(5);
+
+    timeout(timeout_duration, async {
+        let json_str = serde_json::to_string(&settings)
+            .map_err(|e| format!("Serialization error: {}", e))?;
+
+        app_handle.db(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+                ["scheduling", &json_str],
+            )?;
+            Ok(())
+        }).map_err(|e| format!("Database error: {}", e))?;
+
+        apply_schedule(&app_handle, Some(settings)).await
+    })
+    .await
+    .map_err(|_| "Timeout saving schedule settings".to_string())?
+    .map_err(|e: String| e)
+}
+
+/// Start/restart the scheduler if `settings` enable it, stop it otherwise
+pub async fn apply_schedule(app_handle: &tauri::AppHandle, settings: Option<SchedulingSettings>) -> Result<(), String> {
+    if !agent::is_agent_mode() && agent::is_agent_active() {
+        // The agent owns scheduling; restart it so it picks up the new settings
+        return agent::restart_agent();
+    }
+    match settings {
+        Some(settings) if settings.enabled => start_scheduler(app_handle.clone(), settings).await,
+        _ => stop_scheduler(app_handle).await,
+    }
+}
+
+#[tauri::command]
+#[allow(dead_code)]
+pub async fn get_schedule_status(app_handle: tauri::AppHandle) -> Result<ScheduleStatus, String> {
+    let timeout_duration = Duration::from_secs(5);
+
+    timeout(timeout_duration, async {
+        let settings_opt = get_schedule_settings(app_handle.clone()).await?;
+
+        match settings_opt {
+            Some(settings) => {
+                let status = if settings.enabled {
+                    if settings.last_run.is_none() {
+                        "never_run".to_string()
+                    } else {
+                        "active".to_string()
+                    }
+                } else {
+                    "paused".to_string()
+                };
+
+                Ok(ScheduleStatus {
+                    enabled: settings.enabled,
+                    next_run: settings.next_run,
+                    last_run: settings.last_run,
+                    status,
+                })
+            }
+            None => Ok(ScheduleStatus {
+                enabled: false,
+                next_run: None,
+                last_run: None,
+                status: "never_run".to_string(),
+            }),
+        }
+    })
+    .await
+    .map_err(|_| "Timeout getting schedule status".to_string())?
+}
+
+#[allow(dead_code)]
+async fn calculate_next_run(settings: &SchedulingSettings) -> i64 {
+    use chrono::{Local, Timelike, Datelike, Duration as ChronoDuration};
+
+    let now = Local::now();
+    let mut next = now;
+
+    match settings.frequency.as_str() {
+        "on_startup" => {
+            // Next run is immediate (handled separately)
+            return now.timestamp();
+        }
+        "daily" => {
+            if let Some(time_str) = &settings.time {
+                let parts: Vec<&str> = time_str.split(':').collect();
+                if parts.len() == 2 {
+                    if let (Ok(hour), Ok(minute)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+                        next = now.with_hour(hour).and_then(|d| d.with_minute(minute)).unwrap_or(now);
+                        if next <= now {
+                            next = next + ChronoDuration::days(1);
+                        }
+                    }
+                }
+            } else {
+                // Default to 2 AM
+                next = now.with_hour(2).and_then(|d| d.with_minute(0)).unwrap_or(now);
+                if next <= now {
+                    next = next + ChronoDuration::days(1);
+                }
+            }
+        }
+        "weekly" => {
+            if let Some(day) = settings.day_of_week {
+                let target_weekday = day as u32;
+                let current_weekday = now.weekday().num_days_from_sunday();
+
+                let days_ahead = if target_weekday > current_weekday {
+                    (target_weekday - current_weekday) as i64
+                } else if target_weekday < current_weekday {
+                    (7 - (current_weekday - target_weekday)) as i64
+                } else {
+                    // Same day - check if time has passed
+                    if let Some(time_str) = &settings.time {
+                        let parts: Vec<&str> = time_str.split(':').collect();
+                        if parts.len() == 2 {
+                            if let (Ok(hour), Ok(minute)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+                                let target_time = now.with_hour(hour).and_then(|d| d.with_minute(minute)).unwrap_or(now);
+                                if target_time > now {
+                                    return target_time.timestamp();
+                                }
+                            }
+                        }
+                    }
+                    7 // Next week
+                };
+
+                next = now + ChronoDuration::days(days_ahead);
+
+                if let Some(time_str) = &settings.time {
+                    let parts: Vec<&str> = time_str.split(':').collect();
+                    if parts.len() == 2 {
+                        if let (Ok(hour), Ok(minute)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
+                            next = next.with_hour(hour).and_then(|d| d.with_minute(minute)).unwrap_or(next);
+                        }
+                    }
+                } else {
+                    next = next.with_hour(2).and_then(|d| d.with_minute(0)).unwrap_or(next);
+                }
+            }
+        }
+        _ => {
+            // Default to daily at 2 AM
+            next = now.with_hour(2).and_then(|d| d.with_minute(0)).unwrap_or(now);
+            if next <= now {
+                next = next + ChronoDuration::days(1);
+            }
+        }
+    }
+
+    next.timestamp()
+}
+
+#[allow(dead_code)]
+async fn start_scheduler(app_handle: tauri::AppHandle, mut settings: SchedulingSettings) -> Result<(), String> {
+    // Calculate next run time
+    let next_run = calculate_next_run(&settings).await;
+    settings.next_run = Some(next_run);
+
+    // Save updated settings with next_run
+    let json_str = serde_json::to_string(&settings)
+        .map_err(|e| format!("Serialization error: {}", e))?;
+
+    app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+            ["scheduling", &json_str],
+        )?;
+        Ok(())
+    }).map_err(|e| format!("Database error: {}", e))?;
+
+    let monitoring = MonitoringHandle::of(&app_handle);
+    let task = tokio::spawn(async move {
+        scheduler_loop(app_handle, settings).await;
+    });
+
+    // Replaces the scheduler already running, if any
+    monitoring.replace_scheduler(task).await;
+
+    tracing::info!("Scheduler started, next run at {}", next_run);
+    Ok(())
+}
+
+/// Start the scheduler from saved settings (used by the headless agent on launch)
+pub(crate) async fn resume_scheduler(app_handle: tauri::AppHandle) -> Result<(), String> {
+    match get_schedule_settings(app_handle.clone()).await? {
+        Some(settings) if settings.enabled && settings.frequency != "on_startup" => {
+            start_scheduler(app_handle, settings).await
+        }
+        _ => Ok(()),
+    }
+}
+
+#[allow(dead_code)]
+async fn stop_scheduler(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    if MonitoringHandle::of(app_handle).stop_scheduler().await {
+        tracing::info!("Scheduler stopped");
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+async fn scheduler_loop(app_handle: tauri::AppHandle, mut settings: SchedulingSettings) {
+    use chrono::Local;
+    use tokio::time::{sleep, Duration};
+
+    loop {
+        // Handle on_startup separately
+        if settings.frequency == "on_startup" {
+            // This is handled in initialize_app, skip loop
+            break;
+        }
+
+        // Calculate time until next run
+        let now = Local::now().timestamp();
+        let next_run = settings.next_run.unwrap_or(now);
+
+        if next_run > now {
+            let wait_seconds = (next_run - now) as u64;
+            tracing::info!("Scheduler waiting {} seconds until next run", wait_seconds);
+            sleep(Duration::from_secs(wait_seconds)).await;
+        }
+
+        // Execute cleanup
+        tracing::info!("Scheduled cleanup starting");
+        match crate::metrics::timed("quick_clean_safe", quick_clean_safe_inner(app_handle.clone())).await {
+            Ok(result) => {
+                tracing::info!(
+                    "Scheduled cleanup completed: {} items, {} bytes",
+                    result.cleaned,
+                    result.total_size
+                );
+
+                // Update last_run
+                settings.last_run = Some(Local::now().timestamp());
+
+                // Calculate next run
+                let next = calculate_next_run(&settings).await;
+                settings.next_run = Some(next);
+
+                // Save updated settings
+                if let Ok(json_str) = serde_json::to_string(&settings) {
+                    let _ = app_handle.db(|conn| {
+                        conn.execute(
+                            "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+                            ["scheduling", &json_str],
+                        )?;
+                        Ok(())
+                    });
+                }
+
+                tracing::info!(
+                    "Scheduled cleanup completed: {} items, {} bytes freed",
+                    result.cleaned,
+                    format_bytes(result.total_size)
+                );
+                notifications::alert(
+                    &app_handle,
+                    NotificationKind::CleanupComplete,
+                    "Scheduled cleanup complete",
+                    &format!("Cleaned {} items, freed {}", result.cleaned, format_bytes(result.total_size)),
+                )
+                .await;
+            }
+            Err(e) => {
+                tracing::error!("Scheduled cleanup failed: {}", e);
+            }
+        }
+
+        // Recalculate next run for next iteration
+        let next = calculate_next_run(&settings).await;
+        settings.next_run = Some(next);
+    }
+}
@@ -0,0 +1,203 @@
+//! Pulito's trash: listing, restoring, retention and the background expiry checks.
+
+use tokio::time::{timeout, Duration};
+
+use crate::db::DbAccess;
+use crate::digest;
+use crate::notifications::{self, NotificationKind};
+use crate::operations::{guard, OperationPlan};
+use crate::read_only;
+use crate::safe_mode;
+use crate::trash::{self, TrashData};
+
+use super::diskpulse::ALERT_REPEAT_SECS;
+use super::settings::get_settings;
+
+/// Hours ahead covered by trash expiry reminders
+const TRASH_REMINDER_WINDOW_HOURS: u32 = 24;
+/// How often expired trash is purged and upcoming expiries checked
+const TRASH_EXPIRY_CHECK_SECS: u64 = 3600;
+
+/// Remind about trash items that will be permanently removed within the reminder window
+async fn notify_expiring_trash(app_handle: &tauri::AppHandle) {
+    let digest = trash::expiry_digest(TRASH_REMINDER_WINDOW_HOURS);
+    if digest.items.is_empty() {
+        return;
+    }
+    let since = chrono::Utc::now().timestamp() - ALERT_REPEAT_SECS;
+    if app_handle
+        .db(|conn| notifications::alerted_since(conn, NotificationKind::TrashExpiring, since))
+        .unwrap_or(false)
+    {
+        return;
+    }
+    notifications::alert(
+        app_handle,
+        NotificationKind::TrashExpiring,
+        "Trash items expiring soon",
+        &trash::digest_summary(&digest),
+    )
+    .await;
+}
+
+/// Purge expired trash, send expiry reminders and compact the trash when idle,
+/// for as long as the app runs
+pub(super) fn start_trash_expiry_checks(app_handle: tauri::AppHandle) {
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    STARTED.call_once(|| {
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(TRASH_EXPIRY_CHECK_SECS));
+            loop {
+                interval.tick().await;
+                // Nothing leaves the trash while only reports are allowed
+                if read_only::is_enabled() {
+                    continue;
+                }
+                match trash::cleanup_expired() {
+                    Ok(removed) if removed > 0 => tracing::info!("Removed {} expired trash items", removed),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Failed to cleanup expired trash: {}", e),
+                }
+                enforce_trash_size_limit(&app_handle).await;
+                notify_expiring_trash(&app_handle).await;
+                trash::compact::run_if_due(&app_handle).await;
+            }
+        });
+    });
+}
+
+/// Evict unpinned trash items beyond the configured `max_size_mb`
+pub(super) async fn enforce_trash_size_limit(app_handle: &tauri::AppHandle) {
+    let Ok(settings) = get_settings(app_handle.clone()).await else { return };
+    // Safe mode keeps everything for its full retention, even over the size limit
+    if settings.safe_mode.enabled {
+        return;
+    }
+    let max_bytes = settings.trash.max_size_mb.saturating_mul(1024 * 1024);
+    match tokio::task::spawn_blocking(move || trash::enforce_size_limit(max_bytes)).await {
+        Ok(Ok(evicted)) if evicted > 0 => tracing::info!("Evicted {} trash items to stay under {} MB", evicted, settings.trash.max_size_mb),
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => tracing::warn!("Failed to enforce trash size limit: {}", e),
+        Err(e) => tracing::warn!("Trash size limit task failed: {}", e),
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_trash_items() -> Result<TrashData, String> {
+    // Set a timeout for trash operations (10 seconds - file system operations)
+    let trash_timeout = Duration::from_secs(10);
+
+    match timeout(trash_timeout, async {
+        Ok(trash::get_trash_items())
+    }).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Trash items retrieval timed out after {} seconds", trash_timeout.as_secs());
+            Err("Trash items retrieval timed out. Please try again.".to_string())
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn restore_from_trash(id: String) -> Result<(), String> {
+    read_only::check("Restoring from trash")?;
+    // Set a timeout for trash operations (10 seconds - file system operations)
+    let trash_timeout = Duration::from_secs(10);
+
+    match timeout(trash_timeout, async {
+        trash::restore_from_trash(&id)
+    }).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Trash restore timed out after {} seconds", trash_timeout.as_secs());
+            Err("Trash restore operation timed out. Please try again.".to_string())
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn delete_from_trash(app_handle: tauri::AppHandle, id: String, confirmation_token: String) -> Result<(), String> {
+    guard::authorize(&app_handle, &OperationPlan::DeleteFromTrash { id: id.clone() }, &confirmation_token)?;
+
+    // Set a timeout for trash operations (10 seconds - file system operations)
+    let trash_timeout = Duration::from_secs(10);
+
+    match timeout(trash_timeout, async {
+        trash::delete_from_trash(&id)
+    }).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Trash delete timed out after {} seconds", trash_timeout.as_secs());
+            Err("Trash delete operation timed out. Please try again.".to_string())
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn empty_trash(app_handle: tauri::AppHandle, confirmation_token: String) -> Result<usize, String> {
+    guard::authorize(&app_handle, &OperationPlan::EmptyTrash, &confirmation_token)?;
+
+    // Set a timeout for trash operations (30 seconds - bulk file operations)
+    let trash_timeout = Duration::from_secs(30);
+
+    match timeout(trash_timeout, async {
+        trash::empty_trash()
+    }).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("Empty trash timed out after {} seconds", trash_timeout.as_secs());
+            Err("Empty trash operation timed out. Please try again.".to_string())
+        }
+    }
+}
+
+/// Trash items that will be permanently deleted within the reminder window
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_trash_expiry_digest() -> Result<trash::TrashExpiryDigest, String> {
+    tokio::task::spawn_blocking(|| trash::expiry_digest(TRASH_REMINDER_WINDOW_HOURS))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// "Keep longer" from an expiry reminder: give the items another full retention period
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn keep_trash_items_longer(app_handle: tauri::AppHandle, ids: Vec<String>) -> Result<usize, String> {
+    let retention_days = get_settings(app_handle).await?.trash.retention_days;
+    let extended = tokio::task::spawn_blocking(move || trash::extend_retention(&ids, retention_days))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    tracing::info!("Extended retention of {} trash items by {} days", extended, retention_days);
+    Ok(extended)
+}
+
+/// Push the expiry of trash items `extra_days` further out
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn extend_trash_retention(ids: Vec<String>, extra_days: i64) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || trash::extend_retention(&ids, extra_days))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Keep a trash item indefinitely; it is skipped by expiry and size-limit eviction
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn pin_trash_item(id: String) -> Result<trash::TrashItem, String> {
+    tokio::task::spawn_blocking(move || trash::set_pinned(&id, true))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn unpin_trash_item(id: String) -> Result<trash::TrashItem, String> {
+    tokio::task::spawn_blocking(move || trash::set_pinned(&id, false))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
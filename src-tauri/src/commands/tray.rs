@@ -0,0 +1,73 @@
+//! The tray icon's status color.
+
+use tauri::Manager;
+
+// Helper function to create a fallback colored icon
+#[cfg(desktop)]
+fn create_fallback_icon(status_color: &str) -> tauri::image::Image<'static> {
+    use tauri::image::Image;
+
+    let (r, g, b) = match status_color {
+        "green" => (76, 175, 80),   // Green
+        "yellow" => (255, 193, 7),   // Yellow/Amber
+        "red" => (244, 67, 54),      // Red
+        _ => (158, 158, 158),        // Gray (default)
+    };
+    // Create a 32x32 icon with the status color
+    let mut rgba = Vec::with_capacity(32 * 32 * 4);
+    for _ in 0..(32 * 32) {
+        rgba.push(r);
+        rgba.push(g);
+        rgba.push(b);
+        rgba.push(255); // Alpha
+    }
+    Image::new_owned(rgba, 32, 32)
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+#[cfg(desktop)]
+pub async fn update_tray_icon(app_handle: tauri::AppHandle, status_color: String) -> Result<(), String> {
+    use tauri::tray::TrayIcon;
+    use std::sync::Arc;
+
+    tracing::info!("Updating tray icon for status: {}", status_color);
+
+    // Get the tray icon - try to get it from managed state
+    let tray_icon = if let Some(tray_state) = app_handle.try_state::<Arc<TrayIcon<tauri::Wry>>>() {
+        Some(Arc::clone(tray_state.inner()))
+    } else {
+        // Fallback: try to get by default ID (first tray icon)
+        // In Tauri 2.x, if no ID is specified, it uses a default
+        app_handle.tray_by_id("default").map(Arc::new)
+    };
+
+    let Some(tray_icon) = tray_icon else {
+        tracing::warn!("Tray icon not found, cannot update");
+        return Err("Tray icon not available".to_string());
+    };
+
+    // Note: For now, we create a colored fallback icon
+    // To load custom icon files, we would need to enable image-png/image-ico features in Tauri
+    // and use Image::from_path(). For now, the colored icon provides visual feedback.
+
+    // Load the icon image
+    // For now, we'll use a colored fallback icon based on status
+    // In the future, we can add image-png/image-ico features to Tauri to load custom icons
+    let icon = create_fallback_icon(&status_color);
+
+    // Update the tray icon
+    tray_icon.set_icon(Some(icon))
+        .map_err(|e| format!("Failed to set tray icon: {}", e))?;
+
+    tracing::info!("Tray icon updated successfully for status: {}", status_color);
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+#[cfg(not(desktop))]
+pub async fn update_tray_icon(_app_handle: tauri::AppHandle, _status_color: String) -> Result<(), String> {
+    // Tray icons are only supported on desktop platforms
+    Err("Tray icons are not supported on this platform".to_string())
+}
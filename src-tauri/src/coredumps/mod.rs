@@ -0,0 +1,287 @@
+//! Core dumps and crash reports. systemd-coredump keeps compressed cores of
+//! crashed processes in /var/lib/systemd/coredump, apport writes `.crash` reports
+//! to /var/crash and ~/.local/share/apport. The systemd cores belong to root and
+//! are removed through the privileged helper; crash reports the user owns are
+//! ordinary files and go to trash like any other item.
+
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::privileged::{self, PrivilegedOp, PrivilegedOutput};
+use crate::scanner::builtin::new_item;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Crash Reports";
+pub const SYSTEMD_COREDUMP_DIR: &str = "/var/lib/systemd/coredump";
+const SYSTEM_CRASH_DIR: &str = "/var/crash";
+
+/// Dumps younger than this may still be wanted for a bug report
+pub const DEFAULT_MIN_AGE_DAYS: u32 = 1;
+
+/// A core dump or crash report on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreDump {
+    pub path: PathBuf,
+    pub program: String,
+    pub size: u64,
+    pub modified: i64,
+    pub owned: bool, // the current user can delete it
+}
+
+/// The crashed program in a dump's file name: systemd names cores
+/// `core.<comm>.<uid>.<boot id>.<pid>.<usec>[.zst]`, apport names reports after the
+/// executable path with `/` replaced, `_usr_bin_gedit.1000.crash`
+pub fn program_name(file_name: &str) -> Option<String> {
+    if let Some(rest) = file_name.strip_prefix("core.") {
+        // comm may itself contain dots, the four fields after it don't
+        let fields: Vec<&str> = rest.split('.').collect();
+        let suffix = if file_name.ends_with(".zst") || file_name.ends_with(".xz") || file_name.ends_with(".lz4") { 5 } else { 4 };
+        return (fields.len() > suffix).then(|| fields[..fields.len() - suffix].join("."));
+    }
+    let stem = file_name.strip_suffix(".crash")?;
+    let (executable, _uid) = stem.rsplit_once('.')?;
+    Some(executable.replace('_', "/"))
+}
+
+/// Core dumps and crash reports directly inside `dir`
+pub fn list_dumps(dir: &Path, uid: u32) -> Vec<CoreDump> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dumps: Vec<CoreDump> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            Some(CoreDump {
+                program: program_name(&name)?,
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.mtime(),
+                owned: metadata.uid() == uid,
+            })
+        })
+        .collect();
+    dumps.sort_by(|a, b| b.size.cmp(&a.size));
+    dumps
+}
+
+/// Crash report directories apport writes to
+pub fn crash_dirs(home: &Path) -> Vec<PathBuf> {
+    vec![PathBuf::from(SYSTEM_CRASH_DIR), home.join(".local/share/apport")]
+}
+
+/// `find` arguments deleting systemd core dumps at least `older_than_days` old
+pub fn removal_args(older_than_days: u32) -> Vec<String> {
+    vec![
+        SYSTEMD_COREDUMP_DIR.to_string(),
+        "-maxdepth".to_string(),
+        "1".to_string(),
+        "-type".to_string(),
+        "f".to_string(),
+        "-name".to_string(),
+        "core.*".to_string(),
+        "-mmin".to_string(),
+        format!("+{}", older_than_days as u64 * 24 * 60),
+        "-delete".to_string(),
+    ]
+}
+
+/// systemd core dumps at least `min_age_days` old at `now`
+pub fn old_coredumps(dir: &Path, min_age_days: u32, now: i64) -> Vec<CoreDump> {
+    let cutoff = now - min_age_days as i64 * 24 * 3600;
+    list_dumps(dir, unsafe { libc::getuid() }).into_iter().filter(|d| d.modified <= cutoff).collect()
+}
+
+fn age_days(dump: &CoreDump, now: i64) -> i64 {
+    (now - dump.modified).max(0) / (24 * 3600)
+}
+
+fn dump_item(dump: &CoreDump, item_type: &str, kind: &str, now: i64) -> ScanItem {
+    new_item(
+        dump.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        &dump.path,
+        dump.size,
+        item_type,
+        CATEGORY,
+        0,
+        &format!("{} of {}, {} days old", kind, dump.program, age_days(dump, now)),
+    )
+}
+
+pub struct CoredumpScanner;
+
+impl Scanner for CoredumpScanner {
+    fn id(&self) -> &'static str {
+        "coredumps"
+    }
+
+    fn name(&self) -> &'static str {
+        "Core dumps"
+    }
+
+    fn description(&self) -> &'static str {
+        "systemd core dumps and apport crash reports"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        vec![ScannerOptionSchema {
+            key: "min_age_days".to_string(),
+            label: "Only dumps older than (days)".to_string(),
+            value_type: "number".to_string(),
+            default_value: DEFAULT_MIN_AGE_DAYS.to_string(),
+        }]
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_logs
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let min_age_days = ctx.option("min_age_days", DEFAULT_MIN_AGE_DAYS);
+        let now = chrono::Utc::now().timestamp();
+        let uid = unsafe { libc::getuid() };
+        let mut items = Vec::new();
+
+        ctx.progress(0, "Looking for core dumps...");
+        let cores = old_coredumps(Path::new(SYSTEMD_COREDUMP_DIR), min_age_days, now);
+        if !cores.is_empty() {
+            // Removed together by the privileged helper, so listed as one item
+            let size = cores.iter().map(|d| d.size).sum();
+            let children: Vec<ScanItem> = cores.iter().map(|d| dump_item(d, "coredump", "Core dump", now)).collect();
+            let mut item = new_item(
+                "systemd core dumps".to_string(),
+                Path::new(SYSTEMD_COREDUMP_DIR),
+                size,
+                "coredump",
+                CATEGORY,
+                0,
+                &format!("{} core dumps older than {} days from crashed programs", cores.len(), min_age_days),
+            );
+            item.children = Some(children);
+            items.push(item);
+        }
+
+        ctx.progress(50, "Looking for crash reports...");
+        let cutoff = now - min_age_days as i64 * 24 * 3600;
+        for dir in crash_dirs(&home) {
+            ctx.check_cancelled()?;
+            for report in list_dumps(&dir, uid) {
+                // Reports of system services belong to root and stay for an administrator
+                if report.owned && report.modified <= cutoff && ctx.allows(&report.path) {
+                    items.push(dump_item(&report, "file", "Crash report", now));
+                }
+            }
+        }
+        Ok(items)
+    }
+}
+
+fn run_removal(app_handle: &tauri::AppHandle, older_than_days: u32) -> Result<PrivilegedOutput, String> {
+    let op = PrivilegedOp::CoredumpClean { older_than_days };
+    if let Some(output) = privileged::run_in_session(app_handle, op)? {
+        return Ok(output);
+    }
+    let output = Command::new("pkexec")
+        .arg("find")
+        .args(removal_args(older_than_days))
+        .output()
+        .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+    // pkexec exits with 126 when the user dismisses the authentication dialog
+    if output.status.code() == Some(126) {
+        return Err("Authentication was cancelled".to_string());
+    }
+    Ok(PrivilegedOutput::from(output))
+}
+
+/// Remove systemd core dumps at least `older_than_days` old. Dumps still present
+/// afterwards are counted as failed.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn remove_coredumps(
+    app_handle: tauri::AppHandle,
+    older_than_days: u32,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::RemoveCoredumps { older_than_days }, &confirmation_token)?;
+
+    let handle = app_handle.clone();
+    let (before, after) = tokio::task::spawn_blocking(move || {
+        let dir = Path::new(SYSTEMD_COREDUMP_DIR);
+        let before = old_coredumps(dir, older_than_days, chrono::Utc::now().timestamp());
+        if before.is_empty() {
+            return Ok((before, Vec::new()));
+        }
+        let output = run_removal(&handle, older_than_days)?;
+        if !output.success {
+            tracing::warn!("Removing core dumps reported errors: {}", output.stderr.trim());
+        }
+        Ok::<_, String>((before, list_dumps(dir, 0)))
+    })
+    .await
+    .map_err(|e| format!("Core dump task failed: {}", e))??;
+
+    let removed: Vec<&CoreDump> = before.iter().filter(|d| !after.iter().any(|a| a.path == d.path)).collect();
+    let cleaned = removed.len();
+    let failed = before.len() - cleaned;
+    let total_size: u64 = removed.iter().map(|d| d.size).sum();
+    tracing::info!("Removed {} core dumps ({} bytes), {} failed", cleaned, total_size, failed);
+
+    if total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_name() {
+        assert_eq!(
+            program_name("core.gnome-shell.1000.8f0c1f2a3b4c4d5e.2451.1700000000000000.zst").as_deref(),
+            Some("gnome-shell")
+        );
+        assert_eq!(program_name("core.python3.11.1000.8f0c1f2a.99.1700000000000000").as_deref(), Some("python3.11"));
+        assert_eq!(program_name("_usr_bin_gedit.1000.crash").as_deref(), Some("/usr/bin/gedit"));
+        assert_eq!(program_name("core.short.zst"), None);
+        assert_eq!(program_name("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_old_dumps_only() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let old = dir.path().join("core.firefox.1000.abc.42.1700000000000000.zst");
+        std::fs::write(&old, vec![0u8; 2048]).unwrap();
+        std::fs::write(dir.path().join("core.vim.1000.abc.43.1700000000000001.zst"), b"core").unwrap();
+        std::fs::write(dir.path().join("README"), b"not a dump").unwrap();
+        let two_days_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(2 * 24 * 3600);
+        std::fs::File::options().write(true).open(&old).unwrap().set_modified(two_days_ago).unwrap();
+
+        let all = list_dumps(dir.path(), unsafe { libc::getuid() });
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|d| d.owned));
+
+        let dumps = old_coredumps(dir.path(), 1, now);
+        assert_eq!(dumps.len(), 1);
+        assert_eq!((dumps[0].program.as_str(), dumps[0].size, age_days(&dumps[0], now)), ("firefox", 2048, 2));
+    }
+}
@@ -152,6 +152,26 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn test_add_column_if_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let conn = create_test_db(&db_path).unwrap();
+        initialize_test_database(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO disk_history (timestamp, used_bytes, total_bytes, available_bytes) VALUES (?, ?, ?, ?)",
+            [1234567890i64, 1000000i64, 5000000i64, 4000000i64],
+        ).unwrap();
+
+        // Upgrading twice must not fail, and old rows get the default
+        add_column_if_missing(&conn, "disk_history", "after_resume", "INTEGER NOT NULL DEFAULT 0").unwrap();
+        add_column_if_missing(&conn, "disk_history", "after_resume", "INTEGER NOT NULL DEFAULT 0").unwrap();
+        let after_resume: i64 = conn
+            .query_row("SELECT after_resume FROM disk_history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(after_resume, 0);
+    }
+
     #[test]
     fn test_monitoring_state_table_structure() {
         let temp_dir = TempDir::new().unwrap();
@@ -207,6 +227,37 @@ mod tests {
         assert!(result.is_err()); // Should fail due to UNIQUE constraint
     }
 
+    #[test]
+    fn test_migrate_timestamp_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let conn = create_test_db(&temp_dir.path().join("test.db")).unwrap();
+        initialize_test_database(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO trash_items VALUES ('a', '/a', '/t/a', '2024-03-01T12:00:00+00:00', '2024-03-31T14:00:00+02:00', 1, 'file', NULL);
+             INSERT INTO trash_items VALUES ('b', '/b', '/t/b', '1709294400', '1711886400000', 1, 'file', NULL);",
+        )
+        .unwrap();
+
+        migrate_timestamp_columns(&conn, "trash_items", &["deleted_at", "expires_at"]).unwrap();
+        // A second run finds INTEGER columns and leaves them be
+        migrate_timestamp_columns(&conn, "trash_items", &["deleted_at", "expires_at"]).unwrap();
+
+        let rows: Vec<(String, i64, i64)> = conn
+            .prepare("SELECT id, deleted_at, expires_at FROM trash_items ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        let expected = (1_709_294_400, 1_711_886_400);
+        assert_eq!(rows, vec![("a".to_string(), expected.0, expected.1), ("b".to_string(), expected.0, expected.1)]);
+
+        let index: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'idx_trash_items_expires'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(index, 1);
+    }
+
     #[test]
     fn test_indexes_created() {
         let temp_dir = TempDir::new().unwrap();
@@ -252,13 +303,16 @@ pub fn initialize_database(app_handle: &AppHandle) -> Result<()> {
         .map_err(|e| rusqlite::Error::InvalidPath(std::path::PathBuf::from(e)))?;
     let conn = Connection::open(&db_path)?;
 
+    // The GUI and pulito-agent share this file; wait for the other's write lock instead of failing
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+
     // Create tables
     conn.execute_batch(
         r#"
         -- Scan history
         CREATE TABLE IF NOT EXISTS scan_history (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
-            timestamp TEXT NOT NULL,
+            timestamp INTEGER NOT NULL, -- UTC unix seconds, as are all timestamps here
             total_size INTEGER NOT NULL,
             total_items INTEGER NOT NULL,
             scan_time_ms INTEGER NOT NULL,
@@ -270,8 +324,8 @@ pub fn initialize_database(app_handle: &AppHandle) -> Result<()> {
             id TEXT PRIMARY KEY,
             original_path TEXT NOT NULL,
             trash_path TEXT NOT NULL,
-            deleted_at TEXT NOT NULL,
-            expires_at TEXT NOT NULL,
+            deleted_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
             size INTEGER NOT NULL,
             item_type TEXT NOT NULL,
             metadata TEXT
@@ -299,7 +353,8 @@ pub fn initialize_database(app_handle: &AppHandle) -> Result<()> {
             timestamp INTEGER NOT NULL,
             used_bytes INTEGER NOT NULL,
             total_bytes INTEGER NOT NULL,
-            available_bytes INTEGER NOT NULL
+            available_bytes INTEGER NOT NULL,
+            after_resume INTEGER NOT NULL DEFAULT 0 -- sampled shortly after resume from suspend
         );
 
         -- DiskPulse: Monitoring state
@@ -317,16 +372,233 @@ pub fn initialize_database(app_handle: &AppHandle) -> Result<()> {
             last_access INTEGER NOT NULL
         );
 
+        -- Bytes freed by each cleanup, used for monthly digests
+        CREATE TABLE IF NOT EXISTS cleanup_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            category TEXT NOT NULL,
+            bytes_freed INTEGER NOT NULL, -- estimated from the files removed
+            measured_bytes INTEGER -- free space gained, NULL when not sampled
+        );
+
+        -- Monthly storage digests (aggregates kept after raw samples are pruned)
+        CREATE TABLE IF NOT EXISTS storage_digests (
+            month TEXT PRIMARY KEY, -- 'YYYY-MM'
+            average_used_bytes INTEGER NOT NULL,
+            total_bytes INTEGER NOT NULL,
+            net_growth_bytes INTEGER NOT NULL,
+            space_freed_bytes INTEGER NOT NULL,
+            biggest_new_files TEXT NOT NULL, -- JSON array
+            sample_count INTEGER NOT NULL,
+            generated_at INTEGER NOT NULL
+        );
+
+        -- Disk benchmark results for degradation tracking
+        CREATE TABLE IF NOT EXISTS disk_benchmarks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            target_path TEXT NOT NULL,
+            file_size_bytes INTEGER NOT NULL,
+            seq_read_mbps REAL NOT NULL,
+            seq_write_mbps REAL NOT NULL,
+            random_read_iops REAL NOT NULL,
+            random_write_iops REAL NOT NULL,
+            direct_io INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL
+        );
+
+        -- Cache directories redirected to a volatile (tmpfs) location, so they can be reverted
+        CREATE TABLE IF NOT EXISTS cache_redirections (
+            path TEXT PRIMARY KEY,
+            target TEXT NOT NULL,
+            original_size INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+
+        -- Remote hosts monitored over SSH (key-based auth only)
+        CREATE TABLE IF NOT EXISTS remote_hosts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            host TEXT NOT NULL,
+            user TEXT,
+            port INTEGER NOT NULL DEFAULT 22,
+            created_at INTEGER NOT NULL
+        );
+
+        -- Authorization decisions for destructive commands (operations::guard)
+        CREATE TABLE IF NOT EXISTS operation_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            operation TEXT NOT NULL,
+            allowed INTEGER NOT NULL,
+            reason TEXT
+        );
+
+        -- Filesystem snapshots taken before risky cleanups (snapshots module)
+        CREATE TABLE IF NOT EXISTS cleanup_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at INTEGER NOT NULL,
+            tool TEXT NOT NULL, -- 'snapper', 'timeshift', 'btrfs'
+            snapshot_ref TEXT NOT NULL,
+            mount_point TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            plan_fingerprint TEXT NOT NULL,
+            audit_id INTEGER, -- operation_audit row of the cleanup it protects
+            rollback_hint TEXT NOT NULL
+        );
+
+        -- Largest-files leaderboard marks (leaderboard module)
+        CREATE TABLE IF NOT EXISTS ignored_files (
+            path TEXT PRIMARY KEY,
+            ignored_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS pinned_files (
+            path TEXT PRIMARY KEY,
+            pinned_at INTEGER NOT NULL
+        );
+
+        -- One row per boot from systemd-analyze (boot module)
+        CREATE TABLE IF NOT EXISTS boot_analyses (
+            boot_id TEXT PRIMARY KEY,
+            booted_at INTEGER NOT NULL,
+            recorded_at INTEGER NOT NULL,
+            firmware_ms INTEGER,
+            loader_ms INTEGER,
+            kernel_ms INTEGER,
+            initrd_ms INTEGER,
+            userspace_ms INTEGER,
+            total_ms INTEGER NOT NULL,
+            blame TEXT NOT NULL,
+            user_blame TEXT NOT NULL,
+            critical_chain TEXT NOT NULL
+        );
+
+        -- Startup program toggles, compared against boot_analyses (boot::impact)
+        CREATE TABLE IF NOT EXISTS startup_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            program_id TEXT NOT NULL,
+            program_name TEXT NOT NULL,
+            unit TEXT,
+            enabled INTEGER NOT NULL,
+            changed_at INTEGER NOT NULL
+        );
+
+        -- Persistent file index for incremental rescans; mtime in nanoseconds,
+        -- size of a directory row is the total of the files directly inside it
+        CREATE TABLE IF NOT EXISTS file_index (
+            path TEXT PRIMARY KEY,
+            size INTEGER NOT NULL,
+            mtime_ns INTEGER NOT NULL,
+            is_dir INTEGER NOT NULL DEFAULT 0,
+            hash TEXT,
+            indexed_at INTEGER NOT NULL
+        );
+
+        -- In-app notification center
+        CREATE TABLE IF NOT EXISTS notifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            read INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+
+        -- Desktop notifications held back while Do-Not-Disturb was on
+        CREATE TABLE IF NOT EXISTS pending_notifications (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+
+        -- Where offloaded files were moved to (archive::find_archived)
+        CREATE TABLE IF NOT EXISTS archive_manifest (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            original_path TEXT NOT NULL,
+            location TEXT NOT NULL,
+            archive TEXT NOT NULL,
+            archived_at INTEGER NOT NULL
+        );
+
+        -- Deferred background work (jobs module); lower priority runs first
+        CREATE TABLE IF NOT EXISTS jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            payload TEXT NOT NULL, -- JSON of jobs::Job
+            priority INTEGER NOT NULL,
+            state TEXT NOT NULL, -- 'queued', 'running', 'done', 'failed'
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            run_after INTEGER NOT NULL, -- retries wait until then
+            finished_at INTEGER
+        );
+
+        -- What the user decided about a scan item, by its stable id (scanner::identity)
+        CREATE TABLE IF NOT EXISTS item_decisions (
+            item_id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            decision TEXT NOT NULL, -- 'ignored', 'selected'
+            decided_at INTEGER NOT NULL
+        );
+
+        -- Standing ignore rules: a path or glob, for one category or all (ignores)
+        CREATE TABLE IF NOT EXISTS ignore_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pattern TEXT NOT NULL,
+            category TEXT,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER, -- NULL keeps the rule for good
+            note TEXT
+        );
+
+        -- Named copies of app_settings and scheduling (profiles); the active one is settings.active_profile
+        CREATE TABLE IF NOT EXISTS settings_profiles (
+            name TEXT PRIMARY KEY,
+            settings TEXT, -- NULL when defaults were in effect
+            scheduling TEXT,
+            updated_at INTEGER NOT NULL
+        );
+
+        -- Results of each scanner that finished, until the scan they belong to completes (scanner::checkpoint)
+        CREATE TABLE IF NOT EXISTS scan_checkpoints (
+            options_key TEXT NOT NULL, -- hash of the scan options
+            scanner TEXT NOT NULL,
+            items TEXT NOT NULL, -- JSON array of scan items
+            completed_at INTEGER NOT NULL,
+            PRIMARY KEY (options_key, scanner)
+        );
+
         -- Create indexes
         CREATE INDEX IF NOT EXISTS idx_trash_items_expires ON trash_items(expires_at);
         CREATE INDEX IF NOT EXISTS idx_cache_events_timestamp ON cache_events(timestamp);
         CREATE INDEX IF NOT EXISTS idx_cache_events_source ON cache_events(source);
         CREATE INDEX IF NOT EXISTS idx_disk_history_timestamp ON disk_history(timestamp);
         CREATE INDEX IF NOT EXISTS idx_file_access_last_access ON file_access(last_access);
+        CREATE INDEX IF NOT EXISTS idx_cleanup_history_timestamp ON cleanup_history(timestamp);
+        CREATE INDEX IF NOT EXISTS idx_disk_benchmarks_timestamp ON disk_benchmarks(timestamp);
+        CREATE INDEX IF NOT EXISTS idx_startup_changes_changed_at ON startup_changes(changed_at);
+        CREATE INDEX IF NOT EXISTS idx_notifications_created_at ON notifications(created_at);
+        CREATE INDEX IF NOT EXISTS idx_file_index_indexed_at ON file_index(indexed_at);
+        CREATE INDEX IF NOT EXISTS idx_archive_manifest_archived_at ON archive_manifest(archived_at);
+        CREATE INDEX IF NOT EXISTS idx_jobs_state_priority ON jobs(state, priority, run_after);
         "#,
     )?;
 
+    // Columns added after the table first shipped
+    add_column_if_missing(&conn, "disk_history", "after_resume", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(&conn, "cleanup_history", "measured_bytes", "INTEGER")?;
+    // Timestamps first stored as RFC 3339 text
+    migrate_timestamp_columns(&conn, "scan_history", &["timestamp"])?;
+    migrate_timestamp_columns(&conn, "trash_items", &["deleted_at", "expires_at"])?;
+
     // Store connection in app state
+    crate::watchdog::register_database(&conn);
     let state: tauri::State<AppState> = app_handle.state();
     let mut db = state.db.lock().unwrap();
     *db = Some(conn);
@@ -335,6 +607,72 @@ pub fn initialize_database(app_handle: &AppHandle) -> Result<()> {
     Ok(())
 }
 
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+/// Rebuild `table` with `columns` as INTEGER unix seconds if they still have the
+/// TEXT type they shipped with, converting RFC 3339 and millisecond values.
+/// Column types can't be altered in place, hence the copy; the table's indexes
+/// are recreated on the new one.
+fn migrate_timestamp_columns(conn: &Connection, table: &str, columns: &[&str]) -> Result<()> {
+    let legacy = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+        .collect::<Result<Vec<_>>>()?
+        .iter()
+        .any(|(name, kind)| columns.contains(&name.as_str()) && kind.eq_ignore_ascii_case("TEXT"));
+    if !legacy {
+        return Ok(());
+    }
+
+    let mut schema: String =
+        conn.query_row("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1", [table], |row| row.get(0))?;
+    for column in columns {
+        schema = schema.replace(&format!("{} TEXT", column), &format!("{} INTEGER", column));
+    }
+    let indexes = conn
+        .prepare("SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1 AND sql IS NOT NULL")?
+        .query_map([table], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(&format!(
+        "ALTER TABLE {table} RENAME TO {table}_legacy; {schema}; \
+         INSERT INTO {table} SELECT * FROM {table}_legacy; DROP TABLE {table}_legacy;"
+    ))?;
+    for index in &indexes {
+        tx.execute_batch(index)?;
+    }
+    // INTEGER affinity already turned numeric text into numbers; the rest is RFC 3339
+    for column in columns {
+        let text: Vec<(i64, String)> = tx
+            .prepare(&format!("SELECT rowid, {column} FROM {table} WHERE typeof({column}) = 'text'"))?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        for (rowid, value) in text {
+            let seconds = crate::timestamps::parse_legacy(&value).unwrap_or(0);
+            tx.execute(&format!("UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"), rusqlite::params![seconds, rowid])?;
+        }
+        tx.execute(
+            &format!("UPDATE {table} SET {column} = {column} / 1000 WHERE typeof({column}) = 'integer' AND abs({column}) >= ?1"),
+            [crate::timestamps::MILLIS_THRESHOLD],
+        )?;
+    }
+    tx.commit()?;
+    tracing::info!("Migrated {} timestamps to unix seconds", table);
+    Ok(())
+}
+
 /// Helper trait to access database from app handle
 pub trait DbAccess {
     fn db<F, T>(&self, f: F) -> Result<T>
@@ -354,6 +692,7 @@ impl DbAccess for AppHandle {
         let conn = db.as_ref()
             .ok_or_else(|| rusqlite::Error::ExecuteReturnedResults)?;
 
+        let _held = crate::watchdog::hold_database();
         f(conn)
     }
 }
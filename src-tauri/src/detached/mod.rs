@@ -0,0 +1,201 @@
+//! Secondary webview windows (health monitor, trash manager) that can be
+//! detached from the main window and kept open after it closes.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+pub const MAIN_LABEL: &str = "main";
+
+lazy_static::lazy_static! {
+    /// Window label -> events it asked for; the main window receives everything
+    static ref SUBSCRIPTIONS: Mutex<HashMap<String, HashSet<String>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum DetachedView {
+    Health,
+    Trash,
+}
+
+impl DetachedView {
+    pub fn label(self) -> &'static str {
+        match self {
+            DetachedView::Health => "health",
+            DetachedView::Trash => "trash",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "health" => Some(DetachedView::Health),
+            "trash" => Some(DetachedView::Trash),
+            _ => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            DetachedView::Health => "Pulito - System Health",
+            DetachedView::Trash => "Pulito - Trash",
+        }
+    }
+
+    /// The app page renders only this view when `detached` is set
+    fn url(self) -> String {
+        let view = match self {
+            DetachedView::Health => "system-health",
+            DetachedView::Trash => "trash",
+        };
+        format!("app?detached={}", view)
+    }
+
+    fn size(self) -> (f64, f64) {
+        match self {
+            DetachedView::Health => (900.0, 700.0),
+            DetachedView::Trash => (900.0, 600.0),
+        }
+    }
+}
+
+/// Labels that should receive `event`: the main window plus any subscribers
+pub fn recipients(subscriptions: &HashMap<String, HashSet<String>>, event: &str) -> Vec<String> {
+    let mut labels = vec![MAIN_LABEL.to_string()];
+    let mut subscribed: Vec<String> = subscriptions
+        .iter()
+        .filter(|(label, events)| label.as_str() != MAIN_LABEL && events.contains(event))
+        .map(|(label, _)| label.clone())
+        .collect();
+    subscribed.sort();
+    labels.extend(subscribed);
+    labels
+}
+
+/// Emit to the main window and to detached windows subscribed to `event`
+pub fn emit<S: Serialize + Clone>(app_handle: &tauri::AppHandle, event: &str, payload: S) -> tauri::Result<()> {
+    let labels = {
+        let subscriptions = SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner());
+        recipients(&subscriptions, event)
+    };
+    for label in labels {
+        if app_handle.get_webview_window(&label).is_some() {
+            app_handle.emit_to(label.as_str(), event, payload.clone())?;
+        }
+    }
+    Ok(())
+}
+
+/// Show the main window, recreating it if it was closed while detached windows stayed open
+pub fn show_main(app_handle: &tauri::AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(MAIN_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+    let Some(config) = app_handle.config().app.windows.iter().find(|w| w.label == MAIN_LABEL).cloned() else {
+        return;
+    };
+    match WebviewWindowBuilder::from_config(app_handle, &config).and_then(|b| b.build()) {
+        Ok(window) => {
+            let _ = window.set_focus();
+        }
+        Err(e) => tracing::error!("Failed to recreate main window: {}", e),
+    }
+}
+
+/// Hide the main window if it is visible, otherwise bring it back
+pub fn toggle_main(app_handle: &tauri::AppHandle) {
+    match app_handle.get_webview_window(MAIN_LABEL) {
+        Some(window) if window.is_visible().unwrap_or(false) => {
+            let _ = window.hide();
+        }
+        // Hidden, or closed while a detached window kept the app running
+        _ => show_main(app_handle),
+    }
+}
+
+/// Drop routing entries for closed windows
+pub fn handle_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
+    if let tauri::WindowEvent::Destroyed = event {
+        SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner()).remove(window.label());
+    }
+}
+
+/// Open a detached view, or focus it if it is already open
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn open_detached_window(app_handle: tauri::AppHandle, view: DetachedView) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(view.label()) {
+        window.show().map_err(|e| format!("Failed to show window: {}", e))?;
+        return window.set_focus().map_err(|e| format!("Failed to focus window: {}", e));
+    }
+
+    let (width, height) = view.size();
+    WebviewWindowBuilder::new(&app_handle, view.label(), WebviewUrl::App(view.url().into()))
+        .title(view.title())
+        .inner_size(width, height)
+        .min_inner_size(600.0, 400.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+    tracing::info!("Opened detached {} window", view.label());
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn close_detached_window(app_handle: tauri::AppHandle, view: DetachedView) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(view.label()) {
+        window.close().map_err(|e| format!("Failed to close window: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Detached views that currently have a window
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_detached_windows(app_handle: tauri::AppHandle) -> Result<Vec<DetachedView>, String> {
+    let mut views: Vec<DetachedView> = app_handle
+        .webview_windows()
+        .keys()
+        .filter_map(|label| DetachedView::from_label(label))
+        .collect();
+    views.sort_by_key(|v| v.label());
+    Ok(views)
+}
+
+/// Choose which backend events the calling window receives (replaces earlier choices)
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn set_window_events(window: tauri::Window, events: Vec<String>) -> Result<(), String> {
+    let mut subscriptions = SUBSCRIPTIONS.lock().map_err(|e| format!("Lock failed: {}", e))?;
+    subscriptions.insert(window.label().to_string(), events.into_iter().collect());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_round_trip() {
+        for view in [DetachedView::Health, DetachedView::Trash] {
+            assert_eq!(DetachedView::from_label(view.label()), Some(view));
+        }
+        assert_eq!(DetachedView::from_label(MAIN_LABEL), None);
+    }
+
+    #[test]
+    fn test_recipients() {
+        let mut subscriptions = HashMap::new();
+        subscriptions.insert("health".to_string(), HashSet::from(["agent-event".to_string()]));
+        subscriptions.insert("trash".to_string(), HashSet::from(["trash-changed".to_string()]));
+
+        assert_eq!(recipients(&subscriptions, "agent-event"), vec!["main", "health"]);
+        assert_eq!(recipients(&subscriptions, "trash-changed"), vec!["main", "trash"]);
+        assert_eq!(recipients(&subscriptions, "scan-progress"), vec!["main"]);
+    }
+}
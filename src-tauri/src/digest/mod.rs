@@ -0,0 +1,355 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::time::{timeout, Duration};
+
+use crate::db::DbAccess;
+
+/// Raw disk_history samples older than this are dropped once their month has a digest
+const RAW_SAMPLE_RETENTION_DAYS: i64 = 90;
+
+/// Number of largest new files kept per monthly digest
+const BIGGEST_FILES_LIMIT: usize = 10;
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct StorageDigest {
+    pub month: String, // "YYYY-MM"
+    pub average_used_bytes: u64,
+    pub total_bytes: u64,
+    pub net_growth_bytes: i64,
+    pub space_freed_bytes: u64,
+    pub biggest_new_files: Vec<DigestFile>,
+    pub sample_count: usize,
+    pub generated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct DigestFile {
+    pub path: String,
+    pub size: u64,
+    pub modified_at: i64,
+}
+
+/// Parse a "YYYY-MM" month string into its [start, end) unix timestamp range
+pub fn month_bounds(month: &str) -> Result<(i64, i64), String> {
+    let (year, month_num) = month
+        .split_once('-')
+        .and_then(|(y, m)| Some((y.parse::<i32>().ok()?, m.parse::<u32>().ok()?)))
+        .ok_or_else(|| format!("Invalid month '{}', expected YYYY-MM", month))?;
+
+    let start = NaiveDate::from_ymd_opt(year, month_num, 1)
+        .ok_or_else(|| format!("Invalid month '{}', expected YYYY-MM", month))?;
+    let end = if month_num == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month_num + 1, 1)
+    }
+    .ok_or_else(|| format!("Invalid month '{}', expected YYYY-MM", month))?;
+
+    let to_ts = |d: NaiveDate| d.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp()).unwrap_or(0);
+    Ok((to_ts(start), to_ts(end)))
+}
+
+fn month_key(timestamp: i64) -> String {
+    let date = chrono::DateTime::from_timestamp(timestamp, 0).unwrap_or_else(Utc::now);
+    format!("{:04}-{:02}", date.year(), date.month())
+}
+
+/// Record bytes freed by a cleanup so monthly digests can report space reclaimed,
+/// with the free space actually gained when the operation was measured
+pub fn record_cleanup(conn: &Connection, category: &str, bytes_freed: u64) -> rusqlite::Result<()> {
+    if bytes_freed == 0 {
+        return Ok(());
+    }
+    let measured = crate::reclaim::finish();
+    conn.execute(
+        "INSERT INTO cleanup_history (timestamp, category, bytes_freed, measured_bytes) VALUES (?1, ?2, ?3, ?4)",
+        params![Utc::now().timestamp(), category, bytes_freed as i64, measured],
+    )?;
+    Ok(())
+}
+
+/// Compute the digest for a month from raw samples, the file index and cleanup history
+pub fn compute_digest(conn: &Connection, month: &str) -> Result<StorageDigest, String> {
+    let (start, end) = month_bounds(month)?;
+
+    let digest = (|| -> rusqlite::Result<StorageDigest> {
+        let (sample_count, average_used, total_bytes): (i64, Option<f64>, Option<i64>) = conn.query_row(
+            "SELECT COUNT(*), AVG(used_bytes), MAX(total_bytes) FROM disk_history WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![start, end],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+
+        let first_used: Option<i64> = conn.query_row(
+            "SELECT used_bytes FROM disk_history WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp ASC LIMIT 1",
+            params![start, end],
+            |row| row.get(0),
+        ).optional()?;
+        let last_used: Option<i64> = conn.query_row(
+            "SELECT used_bytes FROM disk_history WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp DESC LIMIT 1",
+            params![start, end],
+            |row| row.get(0),
+        ).optional()?;
+
+        let space_freed: Option<i64> = conn.query_row(
+            "SELECT SUM(bytes_freed) FROM cleanup_history WHERE timestamp >= ?1 AND timestamp < ?2",
+            params![start, end],
+            |row| row.get(0),
+        )?;
+
+        // By modification time from the scan index; file_access only has access times,
+        // which reading an old file moves into the month
+        let mut stmt = conn.prepare(
+            "SELECT path, size, mtime_ns FROM file_index WHERE is_dir = 0 AND mtime_ns >= ?1 AND mtime_ns < ?2 ORDER BY size DESC LIMIT ?3"
+        )?;
+        let biggest_new_files = stmt
+            .query_map(params![start * NANOS_PER_SEC, end * NANOS_PER_SEC, BIGGEST_FILES_LIMIT as i64], |row| {
+                Ok(DigestFile {
+                    path: row.get(0)?,
+                    size: row.get::<_, i64>(1)? as u64,
+                    modified_at: row.get::<_, i64>(2)?.div_euclid(NANOS_PER_SEC),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(StorageDigest {
+            month: month.to_string(),
+            average_used_bytes: average_used.unwrap_or(0.0) as u64,
+            total_bytes: total_bytes.unwrap_or(0) as u64,
+            net_growth_bytes: match (first_used, last_used) {
+                (Some(first), Some(last)) => last - first,
+                _ => 0,
+            },
+            space_freed_bytes: space_freed.unwrap_or(0) as u64,
+            biggest_new_files,
+            sample_count: sample_count as usize,
+            generated_at: Utc::now().timestamp(),
+        })
+    })()
+    .map_err(|e| format!("Failed to compute storage digest: {}", e))?;
+
+    Ok(digest)
+}
+
+pub fn store_digest(conn: &Connection, digest: &StorageDigest) -> rusqlite::Result<()> {
+    let files_json = serde_json::to_string(&digest.biggest_new_files).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT OR REPLACE INTO storage_digests (month, average_used_bytes, total_bytes, net_growth_bytes, space_freed_bytes, biggest_new_files, sample_count, generated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            digest.month,
+            digest.average_used_bytes as i64,
+            digest.total_bytes as i64,
+            digest.net_growth_bytes,
+            digest.space_freed_bytes as i64,
+            files_json,
+            digest.sample_count as i64,
+            digest.generated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn load_digest(conn: &Connection, month: &str) -> rusqlite::Result<Option<StorageDigest>> {
+    conn.query_row(
+        "SELECT month, average_used_bytes, total_bytes, net_growth_bytes, space_freed_bytes, biggest_new_files, sample_count, generated_at
+         FROM storage_digests WHERE month = ?1",
+        [month],
+        row_to_digest,
+    )
+    .optional()
+}
+
+fn row_to_digest(row: &rusqlite::Row) -> rusqlite::Result<StorageDigest> {
+    let files_json: String = row.get(5)?;
+    Ok(StorageDigest {
+        month: row.get(0)?,
+        average_used_bytes: row.get::<_, i64>(1)? as u64,
+        total_bytes: row.get::<_, i64>(2)? as u64,
+        net_growth_bytes: row.get(3)?,
+        space_freed_bytes: row.get::<_, i64>(4)? as u64,
+        biggest_new_files: serde_json::from_str(&files_json).unwrap_or_default(),
+        sample_count: row.get::<_, i64>(6)? as usize,
+        generated_at: row.get(7)?,
+    })
+}
+
+/// Digest every completed month that still has raw samples, then prune samples
+/// past the retention window so disk_history doesn't grow forever
+pub fn roll_up_completed_months(conn: &Connection) -> Result<usize, String> {
+    let current_month = month_key(Utc::now().timestamp());
+
+    let months: Vec<String> = (|| -> rusqlite::Result<Vec<String>> {
+        let mut stmt = conn.prepare("SELECT DISTINCT timestamp FROM disk_history ORDER BY timestamp ASC")?;
+        let timestamps = stmt
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut months: Vec<String> = timestamps.into_iter().map(month_key).collect();
+        months.dedup();
+        Ok(months)
+    })()
+    .map_err(|e| format!("Failed to list sampled months: {}", e))?;
+
+    let mut rolled_up = 0;
+    for month in months.into_iter().filter(|m| *m != current_month) {
+        let already_stored = load_digest(conn, &month)
+            .map_err(|e| format!("Failed to read digest {}: {}", month, e))?
+            .is_some();
+        if !already_stored {
+            let digest = compute_digest(conn, &month)?;
+            store_digest(conn, &digest).map_err(|e| format!("Failed to store digest {}: {}", month, e))?;
+            rolled_up += 1;
+        }
+    }
+
+    // Only prune samples whose month is already captured by a digest
+    let cutoff = Utc::now().timestamp() - RAW_SAMPLE_RETENTION_DAYS * 24 * 3600;
+    let (cutoff_month_start, _) = month_bounds(&month_key(cutoff))?;
+    conn.execute("DELETE FROM disk_history WHERE timestamp < ?1", [cutoff_month_start])
+        .map_err(|e| format!("Failed to prune disk history: {}", e))?;
+
+    if rolled_up > 0 {
+        tracing::info!("Rolled up {} monthly storage digests", rolled_up);
+    }
+    Ok(rolled_up)
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_storage_digest(app_handle: tauri::AppHandle, month: String) -> Result<StorageDigest, String> {
+    let digest_timeout = Duration::from_secs(10);
+
+    timeout(digest_timeout, async {
+        month_bounds(&month)?;
+
+        let stored = app_handle
+            .db(|conn| load_digest(conn, &month))
+            .map_err(|e| format!("Failed to load storage digest: {}", e))?;
+        if let Some(digest) = stored {
+            return Ok(digest);
+        }
+
+        // Months that are still in progress (or never rolled up) are computed on demand
+        app_handle
+            .db(|conn| Ok(compute_digest(conn, &month)))
+            .map_err(|e| format!("Database error: {}", e))?
+    })
+    .await
+    .map_err(|_| "Timeout getting storage digest".to_string())?
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_storage_digests(app_handle: tauri::AppHandle, year: i32) -> Result<Vec<StorageDigest>, String> {
+    app_handle
+        .db(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT month, average_used_bytes, total_bytes, net_growth_bytes, space_freed_bytes, biggest_new_files, sample_count, generated_at
+                 FROM storage_digests WHERE month LIKE ?1 ORDER BY month ASC",
+            )?;
+            let digests = stmt
+                .query_map([format!("{:04}-%", year)], row_to_digest)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(digests)
+        })
+        .map_err(|e| format!("Failed to list storage digests: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE disk_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                used_bytes INTEGER NOT NULL,
+                total_bytes INTEGER NOT NULL,
+                available_bytes INTEGER NOT NULL
+            );
+            CREATE TABLE file_index (
+                path TEXT PRIMARY KEY,
+                size INTEGER NOT NULL,
+                mtime_ns INTEGER NOT NULL,
+                is_dir INTEGER NOT NULL DEFAULT 0,
+                hash TEXT,
+                indexed_at INTEGER NOT NULL
+            );
+            CREATE TABLE cleanup_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                category TEXT NOT NULL,
+                bytes_freed INTEGER NOT NULL,
+                measured_bytes INTEGER
+            );
+            CREATE TABLE storage_digests (
+                month TEXT PRIMARY KEY,
+                average_used_bytes INTEGER NOT NULL,
+                total_bytes INTEGER NOT NULL,
+                net_growth_bytes INTEGER NOT NULL,
+                space_freed_bytes INTEGER NOT NULL,
+                biggest_new_files TEXT NOT NULL,
+                sample_count INTEGER NOT NULL,
+                generated_at INTEGER NOT NULL
+            );
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let (start, end) = month_bounds("2025-12").unwrap();
+        assert_eq!(end - start, 31 * 24 * 3600);
+        assert!(month_bounds("2025-13").is_err());
+        assert!(month_bounds("december").is_err());
+    }
+
+    #[test]
+    fn test_compute_and_store_digest() {
+        let conn = setup();
+        let (start, _) = month_bounds("2025-03").unwrap();
+
+        for (offset, used) in [(0i64, 1000i64), (86400, 1500), (2 * 86400, 1300)] {
+            conn.execute(
+                "INSERT INTO disk_history (timestamp, used_bytes, total_bytes, available_bytes) VALUES (?1, ?2, 5000, ?3)",
+                params![start + offset, used, 5000 - used],
+            ).unwrap();
+        }
+        let insert = |path: &str, mtime: i64, is_dir: bool| {
+            conn.execute(
+                "INSERT INTO file_index (path, size, mtime_ns, is_dir, indexed_at) VALUES (?1, 4000, ?2, ?3, 0)",
+                params![path, mtime * NANOS_PER_SEC, is_dir],
+            ).unwrap();
+        };
+        insert("/home/u/big.iso", start + 100, false);
+        // Modified before the month, and a directory: neither is new
+        insert("/home/u/old.iso", start - 100, false);
+        insert("/home/u/Videos", start + 100, true);
+        conn.execute(
+            "INSERT INTO cleanup_history (timestamp, category, bytes_freed) VALUES (?1, 'Cache', 700)",
+            [start + 200],
+        ).unwrap();
+
+        let digest = compute_digest(&conn, "2025-03").unwrap();
+        assert_eq!(digest.sample_count, 3);
+        assert_eq!(digest.average_used_bytes, 1266);
+        assert_eq!(digest.net_growth_bytes, 300);
+        assert_eq!(digest.space_freed_bytes, 700);
+        assert_eq!(digest.biggest_new_files.len(), 1);
+        assert_eq!(digest.biggest_new_files[0].modified_at, start + 100);
+
+        store_digest(&conn, &digest).unwrap();
+        let loaded = load_digest(&conn, "2025-03").unwrap().unwrap();
+        assert_eq!(loaded.biggest_new_files[0].path, "/home/u/big.iso");
+    }
+}
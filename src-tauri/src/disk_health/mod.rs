@@ -0,0 +1,523 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::process::Command;
+
+use crate::privileged::{self, PrivilegedOp, PrivilegedOutput};
+use crate::read_only;
+
+/// Maximum number of kernel log excerpts returned to the UI
+const MAX_DISK_ERROR_EVENTS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct DiskErrorEvent {
+    pub kind: String, // "io_error", "read_only_remount", "fs_corruption"
+    pub device: Option<String>,
+    pub excerpt: String, // raw kernel log line
+    pub timestamp: Option<String>,
+}
+
+/// Classify a kernel log message as a disk/filesystem problem, if it is one.
+/// Read-only remounts are checked first since they usually also mention an error.
+pub fn classify_kernel_message(message: &str) -> Option<&'static str> {
+    let lower = message.to_lowercase();
+
+    if lower.contains("remounting filesystem read-only")
+        || lower.contains("forced readonly")
+        || lower.contains("forced read-only")
+        || lower.contains("shutting down filesystem")
+    {
+        return Some("read_only_remount");
+    }
+
+    if lower.contains("ext4-fs error")
+        || lower.contains("ext3-fs error")
+        || lower.contains("corruption detected")
+        || lower.contains("metadata corruption")
+        || lower.contains("csum failed")
+        || lower.contains("checksum error")
+        || lower.contains("bad checksum")
+        || (lower.contains("btrfs") && lower.contains("corrupt"))
+        || (lower.contains("fat-fs") && lower.contains("error"))
+    {
+        return Some("fs_corruption");
+    }
+
+    if lower.contains("i/o error")
+        || lower.contains("medium error")
+        || lower.contains("uncorrectable error")
+        || lower.contains("unrecovered read error")
+        || lower.contains("blk_update_request")
+        || lower.contains("failed command: read")
+        || lower.contains("failed command: write")
+    {
+        return Some("io_error");
+    }
+
+    None
+}
+
+/// Pull the device name out of common kernel message shapes:
+/// "EXT4-fs (sda1): ...", "BTRFS error (device nvme0n1p2): ...", "I/O error, dev sdb, sector ..."
+pub fn extract_device(message: &str) -> Option<String> {
+    if let Some(start) = message.find("(device ") {
+        let rest = &message[start + "(device ".len()..];
+        if let Some(end) = rest.find(')') {
+            return Some(rest[..end].to_string());
+        }
+    }
+
+    // "dev" as a word of its own, not the end of "udev" or "blkdev"
+    let word_start = |start: usize| !message[..start].ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if let Some((start, _)) = message.match_indices("dev ").find(|(start, _)| word_start(*start)) {
+        let rest = &message[start + "dev ".len()..];
+        let device: String = rest.chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+        if !device.is_empty() {
+            return Some(device);
+        }
+    }
+
+    if let Some(start) = message.find("-fs (") {
+        let rest = &message[start + "-fs (".len()..];
+        if let Some(end) = rest.find(')') {
+            return Some(rest[..end].to_string());
+        }
+    }
+
+    None
+}
+
+/// Split a journalctl short-iso / dmesg iso line into (timestamp, message)
+fn split_log_line(line: &str) -> (Option<String>, &str) {
+    let mut parts = line.splitn(2, ' ');
+    let first = parts.next().unwrap_or("");
+    let rest = parts.next().unwrap_or("");
+
+    // ISO timestamps start with a 4-digit year followed by '-'
+    let looks_like_timestamp = first.len() > 10
+        && first.as_bytes()[..4].iter().all(|b| b.is_ascii_digit())
+        && first.as_bytes()[4] == b'-';
+    if !looks_like_timestamp {
+        return (None, line);
+    }
+
+    // journalctl prefixes "hostname kernel: "
+    let message = match rest.find("kernel: ") {
+        Some(idx) => &rest[idx + "kernel: ".len()..],
+        None => rest,
+    };
+    (Some(first.to_string()), message)
+}
+
+/// Unix seconds of a journalctl short-iso or dmesg iso timestamp, such as
+/// "2026-10-16T09:13:44+0000" or "2026-10-16T09:13:44,123456+00:00"
+fn parse_log_timestamp(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_str(&timestamp.replace(',', "."), "%Y-%m-%dT%H:%M:%S%.f%z").ok().map(|t| t.timestamp())
+}
+
+/// The lines of `output` logged at `since` or later; dmesg has no `--since`
+fn lines_since(output: &str, since: i64) -> String {
+    output
+        .lines()
+        .filter(|line| split_log_line(line.trim()).0.and_then(|t| parse_log_timestamp(&t)).is_some_and(|t| t >= since))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+pub fn parse_kernel_log(output: &str) -> Vec<DiskErrorEvent> {
+    let mut events = Vec::new();
+
+    for line in output.lines() {
+        let (timestamp, message) = split_log_line(line.trim());
+        if let Some(kind) = classify_kernel_message(message) {
+            events.push(DiskErrorEvent {
+                kind: kind.to_string(),
+                device: extract_device(message),
+                excerpt: message.trim().to_string(),
+                timestamp,
+            });
+        }
+    }
+
+    // Keep the most recent excerpts
+    if events.len() > MAX_DISK_ERROR_EVENTS {
+        events.drain(..events.len() - MAX_DISK_ERROR_EVENTS);
+    }
+    events
+}
+
+/// Read recent kernel messages - journalctl first, dmesg as a fallback
+/// (dmesg is often restricted to root via kernel.dmesg_restrict)
+fn read_kernel_log(hours: u32) -> Option<String> {
+    let since = format!("-{}h", hours);
+    if let Ok(output) = Command::new("journalctl")
+        .args(["-k", "--since", &since, "-p", "warning", "-o", "short-iso", "--no-pager", "-q"])
+        .output()
+    {
+        if output.status.success() {
+            return Some(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+    }
+
+    match Command::new("dmesg").args(["--time-format", "iso", "--level", "emerg,alert,crit,err,warn"]).output() {
+        Ok(output) if output.status.success() => {
+            let since = chrono::Utc::now().timestamp() - hours as i64 * 3600;
+            Some(lines_since(&String::from_utf8_lossy(&output.stdout), since))
+        }
+        _ => None,
+    }
+}
+
+/// Scan the kernel log for I/O errors, read-only remounts and filesystem corruption
+pub fn recent_disk_errors(hours: u32) -> Vec<DiskErrorEvent> {
+    match read_kernel_log(hours) {
+        Some(output) => parse_kernel_log(&output),
+        None => {
+            tracing::debug!("Kernel log not readable, skipping disk error check");
+            Vec::new()
+        }
+    }
+}
+
+/// NVMe "data units" are reported in thousands of 512-byte sectors
+const NVME_DATA_UNIT_BYTES: u64 = 512 * 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SsdDeviceHealth {
+    pub device: String, // e.g. "/dev/nvme0n1"
+    pub model: Option<String>,
+    pub is_nvme: bool,
+    pub percentage_used: Option<u8>, // vendor wear estimate, may exceed 100
+    pub bytes_written: Option<u64>,  // total bytes written (TBW)
+    pub bytes_read: Option<u64>,
+    pub power_on_hours: Option<u64>,
+    pub media_errors: Option<u64>,
+    pub wear_source: Option<String>, // "smartctl", "nvme-cli"; None when SMART data wasn't readable
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrimStatus {
+    pub timer_enabled: bool,
+    pub timer_active: bool,
+    pub last_trim: Option<String>,
+    pub next_trim: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SsdHealthReport {
+    pub devices: Vec<SsdDeviceHealth>,
+    pub trim: TrimStatus,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct WearData {
+    pub percentage_used: Option<u8>,
+    pub bytes_written: Option<u64>,
+    pub bytes_read: Option<u64>,
+    pub power_on_hours: Option<u64>,
+    pub media_errors: Option<u64>,
+}
+
+/// Parse `smartctl -j -a` output for both NVMe and SATA SSDs
+pub fn parse_smartctl_json(output: &str) -> Option<WearData> {
+    let json: serde_json::Value = serde_json::from_str(output).ok()?;
+
+    if let Some(log) = json.get("nvme_smart_health_information_log") {
+        return Some(WearData {
+            percentage_used: log.get("percentage_used").and_then(|v| v.as_u64()).map(|v| v.min(255) as u8),
+            bytes_written: log.get("data_units_written").and_then(|v| v.as_u64()).map(|v| v * NVME_DATA_UNIT_BYTES),
+            bytes_read: log.get("data_units_read").and_then(|v| v.as_u64()).map(|v| v * NVME_DATA_UNIT_BYTES),
+            power_on_hours: log.get("power_on_hours").and_then(|v| v.as_u64()),
+            media_errors: log.get("media_errors").and_then(|v| v.as_u64()),
+        });
+    }
+
+    // SATA SSDs: wear lives in vendor-specific attributes
+    let table = json.get("ata_smart_attributes")?.get("table")?.as_array()?;
+    let mut wear = WearData {
+        power_on_hours: json.get("power_on_time").and_then(|p| p.get("hours")).and_then(|v| v.as_u64()),
+        ..Default::default()
+    };
+    for attr in table {
+        let id = attr.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+        let value = attr.get("value").and_then(|v| v.as_u64());
+        let raw = attr.get("raw").and_then(|r| r.get("value")).and_then(|v| v.as_u64());
+        match id {
+            // Wear_Leveling_Count / Media_Wearout_Indicator / SSD_Life_Left: normalized, 100 = new
+            177 | 233 | 231 => {
+                if wear.percentage_used.is_none() {
+                    wear.percentage_used = value.map(|v| 100u64.saturating_sub(v) as u8);
+                }
+            }
+            // Total_LBAs_Written / Total_LBAs_Read (512-byte LBAs on most drives)
+            241 => wear.bytes_written = raw.map(|v| v * 512),
+            242 => wear.bytes_read = raw.map(|v| v * 512),
+            _ => {}
+        }
+    }
+    Some(wear)
+}
+
+/// Parse `nvme smart-log -o json` output (field names differ between nvme-cli versions)
+pub fn parse_nvme_cli_json(output: &str) -> Option<WearData> {
+    let json: serde_json::Value = serde_json::from_str(output).ok()?;
+    let get = |keys: &[&str]| keys.iter().find_map(|k| json.get(*k).and_then(|v| v.as_u64()));
+
+    Some(WearData {
+        percentage_used: get(&["percent_used", "percentage_used"]).map(|v| v.min(255) as u8),
+        bytes_written: get(&["data_units_written"]).map(|v| v * NVME_DATA_UNIT_BYTES),
+        bytes_read: get(&["data_units_read"]).map(|v| v * NVME_DATA_UNIT_BYTES),
+        power_on_hours: get(&["power_on_hours"]),
+        media_errors: get(&["media_errors"]),
+    })
+}
+
+/// Non-rotational block devices, skipping virtual ones (loop, zram, ram, dm)
+fn list_ssd_devices() -> Vec<(String, bool)> {
+    let mut devices = Vec::new();
+    let Ok(entries) = std::fs::read_dir("/sys/block") else {
+        return devices;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if ["loop", "zram", "ram", "dm-", "sr", "md"].iter().any(|p| name.starts_with(p)) {
+            continue;
+        }
+        let rotational = std::fs::read_to_string(entry.path().join("queue/rotational"))
+            .map(|s| s.trim() == "1")
+            .unwrap_or(true);
+        if !rotational {
+            devices.push((name.clone(), name.starts_with("nvme")));
+        }
+    }
+    devices.sort();
+    devices
+}
+
+fn read_wear_data(device: &str, is_nvme: bool) -> Option<(WearData, &'static str)> {
+    if let Ok(output) = Command::new("smartctl").args(["-j", "-a", device]).output() {
+        // smartctl uses bit flags in its exit status; bits 0-1 mean the device couldn't be read
+        let unreadable = output.status.code().map(|c| c & 0b11 != 0).unwrap_or(true);
+        if !unreadable {
+            if let Some(wear) = parse_smartctl_json(&String::from_utf8_lossy(&output.stdout)) {
+                return Some((wear, "smartctl"));
+            }
+        }
+    }
+
+    if is_nvme {
+        if let Ok(output) = Command::new("nvme").args(["smart-log", device, "-o", "json"]).output() {
+            if output.status.success() {
+                if let Some(wear) = parse_nvme_cli_json(&String::from_utf8_lossy(&output.stdout)) {
+                    return Some((wear, "nvme-cli"));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn systemctl_show(unit: &str, property: &str) -> Option<String> {
+    let output = Command::new("systemctl")
+        .args(["show", unit, "-p", property, "--value"])
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // systemd reports unset timestamps as empty or "n/a"
+    if value.is_empty() || value == "n/a" {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn get_trim_status() -> TrimStatus {
+    let check = |verb: &str, expected: &str| {
+        Command::new("systemctl")
+            .args([verb, "fstrim.timer"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim() == expected)
+            .unwrap_or(false)
+    };
+
+    TrimStatus {
+        timer_enabled: check("is-enabled", "enabled"),
+        timer_active: check("is-active", "active"),
+        last_trim: systemctl_show("fstrim.timer", "LastTriggerUSec"),
+        next_trim: systemctl_show("fstrim.timer", "NextElapseUSecRealtime"),
+    }
+}
+
+fn collect_ssd_health() -> SsdHealthReport {
+    let devices = list_ssd_devices()
+        .into_iter()
+        .map(|(name, is_nvme)| {
+            let device = format!("/dev/{}", name);
+            let model = std::fs::read_to_string(format!("/sys/block/{}/device/model", name))
+                .ok()
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty());
+            let (wear, source) = match read_wear_data(&device, is_nvme) {
+                Some((wear, source)) => (wear, Some(source.to_string())),
+                None => (WearData::default(), None),
+            };
+
+            SsdDeviceHealth {
+                device,
+                model,
+                is_nvme,
+                percentage_used: wear.percentage_used,
+                bytes_written: wear.bytes_written,
+                bytes_read: wear.bytes_read,
+                power_on_hours: wear.power_on_hours,
+                media_errors: wear.media_errors,
+                wear_source: source,
+            }
+        })
+        .collect();
+
+    SsdHealthReport {
+        devices,
+        trim: get_trim_status(),
+    }
+}
+
+/// SSD wear indicators and periodic TRIM status.
+/// SMART data usually needs root; devices are still listed when it can't be read.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_ssd_health() -> Result<SsdHealthReport, String> {
+    tokio::time::timeout(
+        std::time::Duration::from_secs(15),
+        tokio::task::spawn_blocking(collect_ssd_health),
+    )
+    .await
+    .map_err(|_| "SSD health check timed out".to_string())?
+    .map_err(|e| format!("SSD health task failed: {}", e))
+}
+
+/// Enable periodic TRIM via fstrim.timer (prompts for authentication through polkit)
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn enable_periodic_trim(app_handle: tauri::AppHandle) -> Result<TrimStatus, String> {
+    read_only::check("Enabling periodic TRIM")?;
+    tracing::info!("Enabling fstrim.timer");
+
+    // Reuse an active privileged session, otherwise authenticate just for this call
+    let output = tokio::task::spawn_blocking(move || {
+        if let Some(output) = privileged::run_in_session(&app_handle, PrivilegedOp::EnableFstrimTimer)? {
+            return Ok(output);
+        }
+        let output = Command::new("pkexec")
+            .args(["systemctl", "enable", "--now", "fstrim.timer"])
+            .output()
+            .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+        // pkexec exits with 126 when the user dismisses the authentication dialog
+        if output.status.code() == Some(126) {
+            return Err("Authentication was cancelled".to_string());
+        }
+        Ok(PrivilegedOutput::from(output))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+
+    if !output.success {
+        return Err(format!("Failed to enable fstrim.timer: {}", output.stderr.trim()));
+    }
+
+    tokio::task::spawn_blocking(get_trim_status)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_kernel_message() {
+        assert_eq!(
+            classify_kernel_message("EXT4-fs (sda1): Remounting filesystem read-only"),
+            Some("read_only_remount")
+        );
+        assert_eq!(
+            classify_kernel_message("EXT4-fs error (device sda1): ext4_lookup:1234: inode #2: comm ls: deleted inode referenced"),
+            Some("fs_corruption")
+        );
+        assert_eq!(
+            classify_kernel_message("blk_update_request: I/O error, dev sdb, sector 12345 op 0x0:(READ)"),
+            Some("io_error")
+        );
+        assert_eq!(classify_kernel_message("usb 1-1: new high-speed USB device"), None);
+    }
+
+    #[test]
+    fn test_extract_device() {
+        assert_eq!(extract_device("BTRFS error (device nvme0n1p2): bdev errs"), Some("nvme0n1p2".to_string()));
+        assert_eq!(extract_device("I/O error, dev sdb, sector 12345"), Some("sdb".to_string()));
+        assert_eq!(extract_device("EXT4-fs (sda1): Remounting filesystem read-only"), Some("sda1".to_string()));
+        assert_eq!(extract_device("something unrelated"), None);
+        assert_eq!(extract_device("systemd-udev settle: I/O error, dev sdc, sector 8"), Some("sdc".to_string()));
+        assert_eq!(extract_device("udev worker failed on sda"), None);
+    }
+
+    #[test]
+    fn test_dmesg_lines_since() {
+        let output = "2026-10-15T08:00:00,000000+00:00 I/O error, dev sda, sector 1\n\
+                      2026-10-16T09:13:44,123456+00:00 I/O error, dev sdb, sector 2\n\
+                      continuation without a timestamp\n";
+        let since = parse_log_timestamp("2026-10-16T00:00:00+0000").unwrap();
+        let events = parse_kernel_log(&lines_since(output, since));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].device.as_deref(), Some("sdb"));
+        assert_eq!(parse_log_timestamp("2026-10-16T09:13:44+0000"), parse_log_timestamp("2026-10-16T09:13:44.9+00:00"));
+    }
+
+    #[test]
+    fn test_parse_journalctl_output() {
+        let output = "2026-10-16T09:12:01+0000 host kernel: usb 1-1: new device\n\
+                      2026-10-16T09:13:44+0000 host kernel: I/O error, dev sda, sector 2048 op 0x1:(WRITE)\n";
+        let events = parse_kernel_log(output);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, "io_error");
+        assert_eq!(events[0].device.as_deref(), Some("sda"));
+        assert_eq!(events[0].timestamp.as_deref(), Some("2026-10-16T09:13:44+0000"));
+        assert!(events[0].excerpt.starts_with("I/O error"));
+    }
+
+    #[test]
+    fn test_parse_smartctl_nvme() {
+        let output = r#"{"nvme_smart_health_information_log": {"percentage_used": 7, "data_units_written": 2000, "data_units_read": 1000, "power_on_hours": 1234, "media_errors": 0}}"#;
+        let wear = parse_smartctl_json(output).unwrap();
+        assert_eq!(wear.percentage_used, Some(7));
+        assert_eq!(wear.bytes_written, Some(2000 * NVME_DATA_UNIT_BYTES));
+        assert_eq!(wear.power_on_hours, Some(1234));
+        assert_eq!(wear.media_errors, Some(0));
+    }
+
+    #[test]
+    fn test_parse_smartctl_sata() {
+        let output = r#"{"power_on_time": {"hours": 500}, "ata_smart_attributes": {"table": [
+            {"id": 177, "value": 92, "raw": {"value": 40}},
+            {"id": 241, "value": 99, "raw": {"value": 1000}}
+        ]}}"#;
+        let wear = parse_smartctl_json(output).unwrap();
+        assert_eq!(wear.percentage_used, Some(8));
+        assert_eq!(wear.bytes_written, Some(512_000));
+        assert_eq!(wear.power_on_hours, Some(500));
+    }
+
+    #[test]
+    fn test_parse_nvme_cli_json() {
+        let output = r#"{"percent_used": 3, "data_units_written": 10, "power_on_hours": 42}"#;
+        let wear = parse_nvme_cli_json(output).unwrap();
+        assert_eq!(wear.percentage_used, Some(3));
+        assert_eq!(wear.bytes_written, Some(10 * NVME_DATA_UNIT_BYTES));
+        assert!(parse_nvme_cli_json("not json").is_none());
+    }
+}
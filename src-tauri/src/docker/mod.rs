@@ -0,0 +1,334 @@
+//! Docker artifacts that can be reclaimed: dangling images, stopped containers,
+//! unused volumes and the builder cache. Sizes come from `docker system df` and
+//! `docker image ls`; removal goes through the matching `docker ... prune`, so
+//! nothing is moved to trash.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use std::process::Command;
+
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Docker";
+
+/// Scan items stand for a kind of artifact rather than a file: `docker://<artifact>`
+pub const PATH_PREFIX: &str = "docker://";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum DockerArtifact {
+    DanglingImages,
+    StoppedContainers,
+    UnusedVolumes,
+    BuildCache,
+}
+
+impl DockerArtifact {
+    pub const ALL: [DockerArtifact; 4] = [
+        DockerArtifact::DanglingImages,
+        DockerArtifact::StoppedContainers,
+        DockerArtifact::UnusedVolumes,
+        DockerArtifact::BuildCache,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            DockerArtifact::DanglingImages => "dangling_images",
+            DockerArtifact::StoppedContainers => "stopped_containers",
+            DockerArtifact::UnusedVolumes => "unused_volumes",
+            DockerArtifact::BuildCache => "build_cache",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DockerArtifact::DanglingImages => "Dangling images",
+            DockerArtifact::StoppedContainers => "Stopped containers",
+            DockerArtifact::UnusedVolumes => "Unused volumes",
+            DockerArtifact::BuildCache => "Build cache",
+        }
+    }
+
+    /// Volumes hold application data (databases, uploads) that exists nowhere else
+    pub fn risk_level(self) -> u8 {
+        match self {
+            DockerArtifact::DanglingImages | DockerArtifact::BuildCache => 0,
+            DockerArtifact::StoppedContainers => 1,
+            DockerArtifact::UnusedVolumes => 2,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            DockerArtifact::DanglingImages => "Untagged image layers left behind by rebuilds and pulls",
+            DockerArtifact::StoppedContainers => "Exited containers and their writable layers",
+            DockerArtifact::UnusedVolumes => "Volumes no container uses; their data is lost when removed",
+            DockerArtifact::BuildCache => "BuildKit cache, rebuilt on the next image build",
+        }
+    }
+
+    /// The `docker system df` row this artifact's reclaimable size is read from
+    fn df_type(self) -> &'static str {
+        match self {
+            DockerArtifact::DanglingImages => "Images",
+            DockerArtifact::StoppedContainers => "Containers",
+            DockerArtifact::UnusedVolumes => "Local Volumes",
+            DockerArtifact::BuildCache => "Build Cache",
+        }
+    }
+
+    pub fn prune_args(self) -> [&'static str; 3] {
+        match self {
+            DockerArtifact::DanglingImages => ["image", "prune", "--force"],
+            DockerArtifact::StoppedContainers => ["container", "prune", "--force"],
+            DockerArtifact::UnusedVolumes => ["volume", "prune", "--force"],
+            DockerArtifact::BuildCache => ["builder", "prune", "--force"],
+        }
+    }
+
+    pub fn path(self) -> String {
+        format!("{}{}", PATH_PREFIX, self.id())
+    }
+}
+
+/// Reclaimable space of one kind of artifact
+#[derive(Debug, Clone, PartialEq)]
+pub struct DockerUsage {
+    pub artifact: DockerArtifact,
+    pub count: usize,
+    pub size: u64,
+}
+
+/// One line of `docker system df --format '{{json .}}'`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DfRow {
+    r#type: String,
+    total_count: String,
+    active: String,
+    reclaimable: String,
+}
+
+/// Parse a size as docker prints it ("1.23GB", "512kB", "0B (0%)"); units are decimal
+pub fn parse_size(text: &str) -> Option<u64> {
+    let token = text.split_whitespace().next()?;
+    let split = token.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = token.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "PB" => 1e15,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as u64)
+}
+
+/// Usage of the artifacts `docker system df` reports on, from its JSON lines output.
+/// Dangling images aren't broken out there and are filled in from `docker image ls`.
+pub fn parse_system_df(output: &str) -> Vec<DockerUsage> {
+    let rows: Vec<DfRow> = output
+        .lines()
+        .filter_map(|line| serde_json::from_str(line.trim()).ok())
+        .collect();
+    DockerArtifact::ALL
+        .iter()
+        .filter(|a| **a != DockerArtifact::DanglingImages)
+        .filter_map(|&artifact| {
+            let row = rows.iter().find(|r| r.r#type == artifact.df_type())?;
+            let total: usize = row.total_count.parse().unwrap_or(0);
+            let active: usize = row.active.parse().unwrap_or(0);
+            Some(DockerUsage {
+                artifact,
+                count: total.saturating_sub(active),
+                size: parse_size(&row.reclaimable).unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Count and total size of `docker image ls --format '{{.Size}}'` output
+pub fn parse_image_sizes(output: &str) -> (usize, u64) {
+    output
+        .lines()
+        .filter_map(parse_size)
+        .fold((0, 0), |(count, size), s| (count + 1, size + s))
+}
+
+/// Space a prune reports as freed: "Total reclaimed space: 1.2GB", or "Total: 1.2GB" for the builder
+pub fn parse_reclaimed(output: &str) -> u64 {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Total reclaimed space:").or_else(|| line.trim().strip_prefix("Total:")))
+        .filter_map(parse_size)
+        .sum()
+}
+
+fn run_docker(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if !output.status.success() {
+        // Usually the daemon isn't running or the user isn't in the docker group
+        return Err(format!("docker {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn docker_available() -> bool {
+    crate::snapshots::tool_available("docker")
+}
+
+/// Reclaimable space per artifact, skipping artifacts with nothing to remove
+pub fn usage() -> Result<Vec<DockerUsage>, String> {
+    let mut usage = parse_system_df(&run_docker(&["system", "df", "--format", "{{json .}}"])?);
+    let images = run_docker(&["image", "ls", "--filter", "dangling=true", "--format", "{{.Size}}"])?;
+    let (count, size) = parse_image_sizes(&images);
+    usage.insert(0, DockerUsage { artifact: DockerArtifact::DanglingImages, count, size });
+    usage.retain(|u| u.count > 0 || u.size > 0);
+    Ok(usage)
+}
+
+/// Remove one kind of artifact, returning the space docker reports as freed
+pub fn prune(artifact: DockerArtifact) -> Result<u64, String> {
+    run_docker(&artifact.prune_args()).map(|output| parse_reclaimed(&output))
+}
+
+pub fn usage_item(usage: &DockerUsage) -> ScanItem {
+    let artifact = usage.artifact;
+    new_item(
+        artifact.label().to_string(),
+        Path::new(&artifact.path()),
+        usage.size,
+        "docker",
+        CATEGORY,
+        artifact.risk_level(),
+        &format!("{} ({} item{})", artifact.description(), usage.count, if usage.count == 1 { "" } else { "s" }),
+    )
+}
+
+pub struct DockerScanner;
+
+impl Scanner for DockerScanner {
+    fn id(&self) -> &'static str {
+        "docker"
+    }
+
+    fn name(&self) -> &'static str {
+        "Docker"
+    }
+
+    fn description(&self) -> &'static str {
+        "Dangling images, stopped containers, unused volumes and build cache"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_packages && docker_available()
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        if !docker_available() {
+            return Ok(Vec::new());
+        }
+        ctx.progress(0, "Querying Docker disk usage...");
+        let usage = usage().map_err(|e| ScannerError::IoError(std::io::Error::other(e)))?;
+        ctx.check_cancelled()?;
+        Ok(usage.iter().map(usage_item).collect())
+    }
+}
+
+/// Prune the selected kinds of Docker artifacts. This can't be undone.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clean_docker_artifacts(
+    app_handle: tauri::AppHandle,
+    artifacts: Vec<DockerArtifact>,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    let plan = OperationPlan::CleanDockerArtifacts { artifacts: artifacts.clone() };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    let mut artifacts = artifacts;
+    artifacts.sort();
+    artifacts.dedup();
+
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+    for artifact in artifacts {
+        match tokio::task::spawn_blocking(move || prune(artifact))
+            .await
+            .map_err(|e| format!("Docker prune task failed: {}", e))?
+        {
+            Ok(freed) => {
+                cleaned += 1;
+                total_size += freed;
+                tracing::info!("Pruned docker {} ({} bytes)", artifact.id(), freed);
+            }
+            Err(e) => {
+                tracing::error!("Failed to prune docker {}: {}", artifact.id(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+        tracing::warn!("Failed to record cleanup history: {}", e);
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1.23GB (45%)"), Some(1_230_000_000));
+        assert_eq!(parse_size("512kB"), Some(512_000));
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("N/A"), None);
+    }
+
+    #[test]
+    fn test_parse_system_df() {
+        let output = r#"{"Active":"3","Reclaimable":"2.5GB (40%)","Size":"6.2GB","TotalCount":"12","Type":"Images"}
+{"Active":"1","Reclaimable":"120MB (90%)","Size":"130MB","TotalCount":"4","Type":"Containers"}
+{"Active":"2","Reclaimable":"0B (0%)","Size":"1GB","TotalCount":"2","Type":"Local Volumes"}
+{"Active":"0","Reclaimable":"3.1GB","Size":"3.1GB","TotalCount":"57","Type":"Build Cache"}"#;
+        let usage = parse_system_df(output);
+        assert_eq!(
+            usage,
+            vec![
+                DockerUsage { artifact: DockerArtifact::StoppedContainers, count: 3, size: 120_000_000 },
+                DockerUsage { artifact: DockerArtifact::UnusedVolumes, count: 0, size: 0 },
+                DockerUsage { artifact: DockerArtifact::BuildCache, count: 57, size: 3_100_000_000 },
+            ]
+        );
+        assert_eq!(parse_image_sizes("1.2GB\n245MB\n"), (2, 1_445_000_000));
+    }
+
+    #[test]
+    fn test_parse_reclaimed() {
+        assert_eq!(parse_reclaimed("Deleted Images:\ndeleted: sha256:abc\n\nTotal reclaimed space: 1.5GB\n"), 1_500_000_000);
+        assert_eq!(parse_reclaimed("ID\tRECLAIMABLE\tSIZE\nxyz\ttrue\t10MB\nTotal:\t10MB\n"), 10_000_000);
+        assert_eq!(parse_reclaimed(""), 0);
+    }
+}
@@ -0,0 +1,313 @@
+//! Duplicate-resolution decisions: which copy of a duplicate group to keep, and
+//! exporting those decisions for review and execution outside the app.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::scanner::{DuplicateGroup, ScanItem};
+
+pub mod trees;
+
+/// Which copy of a duplicate group survives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum KeepStrategy {
+    #[default]
+    Newest,
+    Oldest,
+    ShortestPath,
+    FirstListed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum DuplicateExportFormat {
+    ShellScript,
+    Fdupes, // one group per paragraph, keeper first (as `fdupes -dN` preserves it)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct DuplicateExport {
+    pub content: String,
+    pub written_to: Option<String>,
+    pub groups: usize,
+    pub files_to_delete: usize,
+    pub bytes_to_free: u64,
+}
+
+fn modified(item: &ScanItem) -> Option<SystemTime> {
+    std::fs::metadata(&item.path).and_then(|m| m.modified()).ok()
+}
+
+/// Index of the file to keep in `files` under `strategy`
+pub fn choose_keeper(files: &[ScanItem], strategy: KeepStrategy) -> usize {
+    choose_among(files, &(0..files.len()).collect::<Vec<_>>(), strategy)
+}
+
+/// `choose_keeper` limited to the files at `candidates`
+fn choose_among(files: &[ScanItem], candidates: &[usize], strategy: KeepStrategy) -> usize {
+    let indices = candidates.iter().copied();
+    let chosen = match strategy {
+        KeepStrategy::FirstListed => None,
+        KeepStrategy::ShortestPath => indices.min_by_key(|&i| (files[i].path.len(), &files[i].path)),
+        // Files whose mtime can't be read never win
+        KeepStrategy::Newest => indices.filter(|&i| modified(&files[i]).is_some()).max_by_key(|&i| modified(&files[i])),
+        KeepStrategy::Oldest => indices.filter(|&i| modified(&files[i]).is_some()).min_by_key(|&i| modified(&files[i])),
+    };
+    chosen.or(candidates.first().copied()).unwrap_or(0)
+}
+
+/// Index of the file to suggest keeping. Copies inside a preferred directory
+/// come first, earlier directories before later ones; `strategy` decides
+/// among them, or among all copies when none is in one.
+pub fn suggest_keeper(files: &[ScanItem], strategy: KeepStrategy, preferred_dirs: &[PathBuf]) -> usize {
+    let candidates = preferred_dirs
+        .iter()
+        .map(|dir| (0..files.len()).filter(|&i| Path::new(&files[i].path).starts_with(dir)).collect::<Vec<_>>())
+        .find(|inside| !inside.is_empty())
+        .unwrap_or_else(|| (0..files.len()).collect());
+    choose_among(files, &candidates, strategy)
+}
+
+/// Fill in each group's suggested keeper and pre-select its other copies for deletion
+pub fn mark_suggestions(groups: &mut [DuplicateGroup], strategy: KeepStrategy, preferred_dirs: &[PathBuf]) {
+    for group in groups.iter_mut().filter(|g| !g.files.is_empty()) {
+        let keep = suggest_keeper(&group.files, strategy, preferred_dirs);
+        group.suggested_keeper = Some(group.files[keep].path.clone());
+        group.preselected = group.files.iter().enumerate().filter(|(i, _)| *i != keep).map(|(_, f)| f.id.clone()).collect();
+    }
+}
+
+/// Quote for POSIX sh: single quotes, with embedded quotes spliced in
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r#"'\''"#))
+}
+
+/// Paths with newlines or other control characters are refused rather than
+/// escaped: a newline would end the `# keep` comment in the script and split
+/// an entry in the fdupes list.
+fn check_path(path: &str) -> Result<&str, String> {
+    if path.chars().any(char::is_control) {
+        return Err(format!("Refusing to export a path containing control characters: {:?}", path));
+    }
+    Ok(path)
+}
+
+struct Decision<'a> {
+    keeper: &'a ScanItem,
+    remove: Vec<&'a ScanItem>,
+}
+
+fn decide<'a>(groups: &'a [DuplicateGroup], strategy: KeepStrategy, keepers: &HashMap<String, String>) -> Vec<Decision<'a>> {
+    groups
+        .iter()
+        .filter(|g| g.files.len() > 1)
+        .map(|g| {
+            let keep = keepers
+                .get(&g.id)
+                .and_then(|path| g.files.iter().position(|f| &f.path == path))
+                .unwrap_or_else(|| choose_keeper(&g.files, strategy));
+            Decision {
+                keeper: &g.files[keep],
+                remove: g.files.iter().enumerate().filter(|(i, _)| *i != keep).map(|(_, f)| f).collect(),
+            }
+        })
+        .collect()
+}
+
+fn render_script(decisions: &[Decision], strategy: KeepStrategy) -> Result<String, String> {
+    let mut out = String::new();
+    out.push_str("#!/bin/sh\n");
+    out.push_str("# Duplicate cleanup exported by Pulito\n");
+    out.push_str(&format!("# Generated: {}\n", chrono::Utc::now().to_rfc3339()));
+    out.push_str(&format!("# Keep strategy: {:?}\n", strategy));
+    out.push_str("# Review before running. Each copy is removed only if the kept file still\n");
+    out.push_str("# exists and is byte-for-byte identical to it.\n");
+    out.push_str("set -u\n\n");
+    out.push_str("remove_dup() {\n");
+    out.push_str("    if [ -f \"$1\" ] && cmp -s -- \"$1\" \"$2\"; then\n");
+    out.push_str("        rm -v -- \"$2\"\n");
+    out.push_str("    else\n");
+    out.push_str("        echo \"skipped (not identical or keeper missing): $2\" >&2\n");
+    out.push_str("    fi\n");
+    out.push_str("}\n");
+
+    for (i, decision) in decisions.iter().enumerate() {
+        out.push_str(&format!("\n# Group {} ({} bytes each)\n", i + 1, decision.keeper.size));
+        let keeper = shell_quote(check_path(&decision.keeper.path)?);
+        out.push_str(&format!("# keep {}\n", keeper));
+        for item in &decision.remove {
+            out.push_str(&format!("remove_dup {} {}\n", keeper, shell_quote(check_path(&item.path)?)));
+        }
+    }
+    Ok(out)
+}
+
+fn render_fdupes(decisions: &[Decision]) -> Result<String, String> {
+    let groups = decisions
+        .iter()
+        .map(|d| {
+            let mut group = check_path(&d.keeper.path)?.to_string();
+            for item in &d.remove {
+                group.push('\n');
+                group.push_str(check_path(&item.path)?);
+            }
+            group.push('\n');
+            Ok(group)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(groups.join("\n"))
+}
+
+fn write_export(path: &str, content: &str, executable: bool) -> Result<(), String> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return Err(format!("Expected an absolute output path, got {}", path.display()));
+    }
+    // create_new: never overwrite an existing file with a delete script
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(if executable { 0o700 } else { 0o600 })
+        .open(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Export duplicate-resolution decisions as a shell script or an fdupes-style
+/// list. `keepers` maps group id to the path the user chose to keep; other
+/// groups follow `strategy`. Written to `output_path` when given.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn export_duplicate_decisions(
+    groups: Vec<DuplicateGroup>,
+    strategy: KeepStrategy,
+    format: DuplicateExportFormat,
+    keepers: Option<HashMap<String, String>>,
+    output_path: Option<String>,
+) -> Result<DuplicateExport, String> {
+    tokio::task::spawn_blocking(move || {
+        let decisions = decide(&groups, strategy, &keepers.unwrap_or_default());
+        let content = match format {
+            DuplicateExportFormat::ShellScript => render_script(&decisions, strategy)?,
+            DuplicateExportFormat::Fdupes => render_fdupes(&decisions)?,
+        };
+
+        if let Some(path) = &output_path {
+            write_export(path, &content, format == DuplicateExportFormat::ShellScript)?;
+            tracing::info!("Exported {} duplicate groups to {}", decisions.len(), path);
+        }
+
+        Ok(DuplicateExport {
+            groups: decisions.len(),
+            files_to_delete: decisions.iter().map(|d| d.remove.len()).sum(),
+            bytes_to_free: decisions.iter().flat_map(|d| d.remove.iter()).map(|f| f.size).sum(),
+            content,
+            written_to: output_path,
+        })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::builtin::new_item;
+
+    fn item(path: &str) -> ScanItem {
+        // Ids are the paths, so pre-selections read as paths
+        ScanItem { id: path.to_string(), ..new_item(path.to_string(), Path::new(path), 2048, "file", "duplicate", 2, "") }
+    }
+
+    fn group(id: &str, paths: &[&str]) -> DuplicateGroup {
+        DuplicateGroup {
+            id: id.to_string(),
+            files: paths.iter().map(|p| item(p)).collect(),
+            total_size: 2048 * paths.len() as u64,
+            group_size: paths.len(),
+            verified: false,
+            suggested_keeper: None,
+            preselected: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/home/u/a b.txt"), "'/home/u/a b.txt'");
+        assert_eq!(shell_quote("/home/u/it's"), r#"'/home/u/it'\''s'"#);
+    }
+
+    #[test]
+    fn test_keeper_selection() {
+        let files = vec![item("/home/u/Downloads/copy/photo.jpg"), item("/home/u/photo.jpg")];
+        assert_eq!(choose_keeper(&files, KeepStrategy::ShortestPath), 1);
+        assert_eq!(choose_keeper(&files, KeepStrategy::FirstListed), 0);
+        // Neither file exists, so mtime strategies fall back to the first
+        assert_eq!(choose_keeper(&files, KeepStrategy::Newest), 0);
+    }
+
+    #[test]
+    fn test_preferred_directory_wins() {
+        let files = vec![item("/home/u/photo.jpg"), item("/home/u/Backup/2023/photo.jpg"), item("/home/u/Pictures/photo.jpg")];
+        let preferred = [PathBuf::from("/home/u/Pictures"), PathBuf::from("/home/u/Backup")];
+        assert_eq!(suggest_keeper(&files, KeepStrategy::ShortestPath, &preferred), 2);
+        assert_eq!(suggest_keeper(&files, KeepStrategy::ShortestPath, &preferred[1..]), 1);
+        // Component-wise, so a sibling sharing the prefix doesn't count
+        assert_eq!(suggest_keeper(&files, KeepStrategy::ShortestPath, &[PathBuf::from("/home/u/Pict")]), 0);
+        assert_eq!(suggest_keeper(&files, KeepStrategy::FirstListed, &[]), 0);
+    }
+
+    #[test]
+    fn test_mark_suggestions() {
+        let mut groups = vec![group("g1", &["/a/long/x", "/b/x", "/c/x"])];
+        mark_suggestions(&mut groups, KeepStrategy::ShortestPath, &[PathBuf::from("/c")]);
+        assert_eq!(groups[0].suggested_keeper.as_deref(), Some("/c/x"));
+        assert_eq!(groups[0].preselected, ["/a/long/x", "/b/x"]);
+    }
+
+    #[test]
+    fn test_explicit_keeper_overrides_strategy() {
+        let groups = vec![group("g1", &["/a/x", "/b/long/x"])];
+        let keepers: HashMap<String, String> = [("g1".to_string(), "/b/long/x".to_string())].into_iter().collect();
+        let decisions = decide(&groups, KeepStrategy::ShortestPath, &keepers);
+        assert_eq!(decisions[0].keeper.path, "/b/long/x");
+        assert_eq!(decisions[0].remove[0].path, "/a/x");
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let groups = vec![group("g1", &["/a/x", "/b/x", "/c/x"]), group("g2", &["/d/y", "/e/y"])];
+        let decisions = decide(&groups, KeepStrategy::FirstListed, &HashMap::new());
+
+        let script = render_script(&decisions, KeepStrategy::FirstListed).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("remove_dup '/a/x' '/b/x'\n"));
+        assert!(script.contains("remove_dup '/d/y' '/e/y'\n"));
+        assert!(!script.contains("remove_dup '/a/x' '/a/x'"));
+
+        assert_eq!(render_fdupes(&decisions).unwrap(), "/a/x\n/b/x\n/c/x\n\n/d/y\n/e/y\n");
+    }
+
+    #[test]
+    fn test_newline_in_path_is_refused() {
+        // As a keeper the newline would end the `# keep` comment line
+        let groups = vec![group("g1", &["/a/x\nrm -rf ~\n", "/b/x"])];
+        let decisions = decide(&groups, KeepStrategy::FirstListed, &HashMap::new());
+        assert!(render_script(&decisions, KeepStrategy::FirstListed).is_err());
+        assert!(render_fdupes(&decisions).is_err());
+
+        let groups = vec![group("g1", &["/a/x", "/b/x\tcopy"])];
+        let decisions = decide(&groups, KeepStrategy::FirstListed, &HashMap::new());
+        assert!(render_script(&decisions, KeepStrategy::FirstListed).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! Near-duplicate directory trees: a photo library next to its Dropbox copy and
+//! an old "backup of backup". Files are matched by size and sampled content hash
+//! (through the file index); every directory a few levels below the scan root
+//! collects the fingerprints found inside it, and pairs of unrelated directories
+//! sharing most of their bytes are reported with the overlap percentage.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+use walkdir::WalkDir;
+
+use crate::commands;
+use crate::scanner::{self, CancelFlag, HashMode, PathFilter, SharedIndex};
+
+/// Directories this far below the root are compared; deeper ones count towards them
+const MAX_TREE_DEPTH: usize = 3;
+/// Smaller files (sidecars, thumbnails) are counted in tree sizes but not matched
+const MIN_FILE_SIZE: u64 = 4 * 1024;
+/// The smaller tree of a reported pair holds at least this much
+const MIN_TREE_SIZE: u64 = 100 * 1024 * 1024;
+/// Share of the smaller tree's bytes that must also be in the other one
+const MIN_OVERLAP_PERCENT: f64 = 50.0;
+const MAX_FILES: usize = 200_000;
+/// Copies made by sync clients and file managers keep the mtime, give or take
+const MTIME_TOLERANCE_SECS: i64 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TreeOverlap {
+    pub larger: String,
+    pub smaller: String,
+    pub larger_size: u64,
+    pub smaller_size: u64,
+    pub overlap_size: u64, // bytes of distinct content found in both
+    pub overlap_percent: f64, // of the smaller tree
+    pub matched_files: usize,
+    /// Matched files that also share their name and modification time; low when
+    /// one side was renamed or re-exported rather than copied
+    pub name_date_matches: usize,
+}
+
+struct FileEntry {
+    path: PathBuf,
+    size: u64,
+    mtime: i64,
+    trees: Vec<usize>,
+}
+
+#[derive(Default)]
+struct PairTally {
+    overlap_size: u64,
+    matched_files: usize,
+    name_date_matches: usize,
+}
+
+/// Indices of the trees (directories 1..=MAX_TREE_DEPTH below `root`) containing `path`
+fn trees_for(path: &Path, root: &Path, trees: &mut Vec<(PathBuf, u64)>, ids: &mut HashMap<PathBuf, usize>) -> Vec<usize> {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return Vec::new();
+    };
+    let mut dir = root.to_path_buf();
+    let mut found = Vec::new();
+    // The last component is the file itself
+    let components: Vec<_> = relative.components().collect();
+    for component in components.iter().take(components.len().saturating_sub(1)).take(MAX_TREE_DEPTH) {
+        dir.push(component);
+        let id = *ids.entry(dir.clone()).or_insert_with(|| {
+            trees.push((dir.clone(), 0));
+            trees.len() - 1
+        });
+        found.push(id);
+    }
+    found
+}
+
+fn same_name_and_date(a: &FileEntry, b: &FileEntry) -> bool {
+    a.path.file_name() == b.path.file_name() && (a.mtime - b.mtime).abs() <= MTIME_TOLERANCE_SECS
+}
+
+/// Pairs of directory trees under `root` whose content largely overlaps, most shared bytes first.
+/// A pair is left out when a pair of their ancestors is already reported.
+pub fn find_similar_trees(
+    root: &Path,
+    min_tree_size: u64,
+    cancel: &CancelFlag,
+    index: &SharedIndex,
+    filter: &PathFilter,
+) -> Vec<TreeOverlap> {
+    let mut trees: Vec<(PathBuf, u64)> = Vec::new();
+    let mut ids: HashMap<PathBuf, usize> = HashMap::new();
+    let mut files: Vec<FileEntry> = Vec::new();
+    let mut size_counts: HashMap<u64, usize> = HashMap::new();
+
+    // Hidden directories are application state, not copies people make
+    let walker = WalkDir::new(root)
+        .follow_links(false)
+        .same_file_system(filter.one_file_system())
+        .into_iter()
+        .filter_entry(|e| {
+            !e.file_type().is_dir()
+                || e.depth() == 0
+                || (!e.file_name().to_string_lossy().starts_with('.') && filter.enters(e.path()))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && filter.allows(e.path()))
+        .take(MAX_FILES);
+
+    for entry in walker {
+        if scanner::is_cancelled(cancel) {
+            return Vec::new();
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let size = metadata.len();
+        let file_trees = trees_for(entry.path(), root, &mut trees, &mut ids);
+        for &id in &file_trees {
+            trees[id].1 += size;
+        }
+        if size >= MIN_FILE_SIZE && !file_trees.is_empty() {
+            *size_counts.entry(size).or_insert(0) += 1;
+            files.push(FileEntry {
+                path: entry.into_path(),
+                size,
+                mtime: scanner::index::mtime_ns(&metadata) / 1_000_000_000,
+                trees: file_trees,
+            });
+        }
+    }
+
+    // A file can only have a copy elsewhere if another file has the same size
+    let mut by_content: HashMap<(u64, String), Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        if size_counts.get(&file.size).copied().unwrap_or(0) < 2 {
+            continue;
+        }
+        if scanner::is_cancelled(cancel) {
+            return Vec::new();
+        }
+        let Ok(metadata) = std::fs::metadata(&file.path) else {
+            continue;
+        };
+        let hashed = index
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .file_hash(&file.path, &metadata, HashMode::Sampled.index_kind(), || scanner::compute_file_hash_chunked(&file.path));
+        if let Ok(hash) = hashed {
+            by_content.entry((file.size, hash)).or_default().push(i);
+        }
+    }
+
+    let mut pairs: HashMap<(usize, usize), PairTally> = HashMap::new();
+    for ((size, _), copies) in by_content.iter().filter(|(_, copies)| copies.len() > 1) {
+        // Each distinct content counts once per pair of trees, however many copies they hold
+        let mut containing: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &i in copies {
+            for &tree in &files[i].trees {
+                containing.entry(tree).or_default().push(i);
+            }
+        }
+        let mut tree_ids: Vec<usize> = containing.keys().copied().collect();
+        tree_ids.sort_unstable();
+        for (n, &a) in tree_ids.iter().enumerate() {
+            for &b in &tree_ids[n + 1..] {
+                // A directory trivially overlaps with its own subdirectories
+                if trees[a].0.starts_with(&trees[b].0) || trees[b].0.starts_with(&trees[a].0) {
+                    continue;
+                }
+                let tally = pairs.entry((a, b)).or_default();
+                tally.overlap_size += size;
+                tally.matched_files += 1;
+                let named_alike = containing[&a]
+                    .iter()
+                    .any(|&x| containing[&b].iter().any(|&y| same_name_and_date(&files[x], &files[y])));
+                if named_alike {
+                    tally.name_date_matches += 1;
+                }
+            }
+        }
+    }
+
+    let mut overlaps: Vec<TreeOverlap> = pairs
+        .into_iter()
+        .filter_map(|((a, b), tally)| {
+            let (larger, smaller) = if trees[a].1 >= trees[b].1 { (&trees[a], &trees[b]) } else { (&trees[b], &trees[a]) };
+            if smaller.1 < min_tree_size {
+                return None;
+            }
+            let overlap_percent = tally.overlap_size as f64 * 100.0 / smaller.1 as f64;
+            (overlap_percent >= MIN_OVERLAP_PERCENT).then(|| TreeOverlap {
+                larger: larger.0.to_string_lossy().to_string(),
+                smaller: smaller.0.to_string_lossy().to_string(),
+                larger_size: larger.1,
+                smaller_size: smaller.1,
+                overlap_size: tally.overlap_size,
+                overlap_percent: overlap_percent.min(100.0),
+                matched_files: tally.matched_files,
+                name_date_matches: tally.name_date_matches,
+            })
+        })
+        .collect();
+    // Ancestor pairs share at least as much as their subdirectories, so they sort first
+    overlaps.sort_by(|a, b| {
+        b.overlap_size
+            .cmp(&a.overlap_size)
+            .then_with(|| (a.larger.len() + a.smaller.len()).cmp(&(b.larger.len() + b.smaller.len())))
+            .then_with(|| a.larger.cmp(&b.larger))
+    });
+
+    let mut reported: Vec<TreeOverlap> = Vec::new();
+    for overlap in overlaps {
+        let nested = reported.iter().any(|r| {
+            let within = |path: &str, a: &str, b: &str| Path::new(path).starts_with(a) || Path::new(path).starts_with(b);
+            within(&overlap.larger, &r.larger, &r.smaller) && within(&overlap.smaller, &r.larger, &r.smaller)
+        });
+        if !nested {
+            reported.push(overlap);
+        }
+    }
+    reported
+}
+
+/// Near-duplicate directory trees in the home directory, such as a photo
+/// library and its cloud or backup copies
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn scan_duplicate_trees(
+    app_handle: tauri::AppHandle,
+    exclude_globs: Option<Vec<String>>,
+) -> Result<Vec<TreeOverlap>, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let one_file_system = commands::load_settings(&app_handle).scan.one_file_system;
+    let filter = PathFilter::new(&[], &exclude_globs.unwrap_or_default(), &home).with_one_file_system(one_file_system);
+
+    let active = scanner::begin_scan("duplicate_trees");
+    let cancel = Arc::clone(active.flag());
+    let index = commands::load_file_index(&app_handle);
+    let scan_index = Arc::clone(&index);
+    let outcome = timeout(
+        Duration::from_secs(600),
+        tokio::task::spawn_blocking(move || find_similar_trees(&home, MIN_TREE_SIZE, &cancel, &scan_index, &filter)),
+    )
+    .await;
+    commands::save_file_index(&app_handle, &index);
+
+    match outcome {
+        Ok(joined) => joined.map_err(|e| format!("Duplicate folder scan failed: {}", e)),
+        Err(_) => Err("Duplicate folder scan timed out".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+
+    fn write(path: &Path, seed: u8, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![seed; len]).unwrap();
+    }
+
+    #[test]
+    fn test_finds_copied_photo_library() {
+        let home = tempfile::TempDir::new().unwrap();
+        let root = home.path();
+        for (i, name) in ["a.jpg", "b.jpg", "c.jpg", "d.jpg"].iter().enumerate() {
+            let len = 10_000 + i * 1000;
+            write(&root.join("Pictures/2020").join(name), i as u8, len);
+            write(&root.join("Dropbox/Photos/2020").join(name), i as u8, len);
+        }
+        // Only part of the library made it into the old backup, renamed
+        write(&root.join("Backup/old/IMG_0001.jpg"), 0, 10_000);
+        write(&root.join("Backup/old/notes.txt"), 9, 30_000);
+        write(&root.join("Documents/report.pdf"), 7, 50_000);
+
+        let cancel: CancelFlag = Arc::new(AtomicBool::new(false));
+        let index = scanner::FileIndex::in_memory().shared();
+        let filter = PathFilter::new(&[], &[], root);
+        let overlaps = find_similar_trees(root, 1, &cancel, &index, &filter);
+
+        // The matching subdirectories are implied by the top-level pair
+        assert_eq!(overlaps.len(), 1);
+        let overlap = &overlaps[0];
+        // Both trees hold the same bytes, so either may be reported as the larger
+        let mut sides = [overlap.larger.clone(), overlap.smaller.clone()];
+        sides.sort();
+        let expected = [root.join("Dropbox"), root.join("Pictures")].map(|p| p.to_string_lossy().to_string());
+        assert_eq!(sides, expected);
+        assert_eq!(overlap.overlap_size, 46_000);
+        assert_eq!(overlap.overlap_percent, 100.0);
+        assert_eq!((overlap.matched_files, overlap.name_date_matches), (4, 4));
+
+        // Backup/old holds 25% library content: below the reporting threshold
+        assert!(!overlaps.iter().any(|o| o.smaller.contains("Backup") || o.larger.contains("Backup")));
+    }
+}
@@ -0,0 +1,228 @@
+//! Caches of Electron apps. VS Code, Slack, Discord and the like keep their
+//! Chromium caches next to their settings in `~/.config/<App>` (or the
+//! Flatpak's `~/.var/app/<id>/config/<App>`) instead of `~/.cache`, so the
+//! cache cleanup never sees them. Only the cache folders are listed; settings,
+//! local storage and logins next to them are left alone.
+
+use std::path::{Path, PathBuf};
+
+use crate::commands::CleanResult;
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::db::DbAccess;
+use crate::digest;
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+use crate::trash::{self, TrashMetadata};
+
+pub const CATEGORY: &str = "App Caches";
+
+/// Chromium cache folders, rebuilt by the app as needed
+const CACHE_DIRS: &[&str] =
+    &["Cache", "Code Cache", "GPUCache", "DawnCache", "DawnGraphiteCache", "DawnWebGPUCache", "CachedData", "Service Worker/CacheStorage"];
+/// Only Chromium writes these at the top of its data folder; browsers keep theirs per profile
+const MARKERS: &[&str] = &["Code Cache", "GPUCache"];
+/// Folder names that don't say which app it is
+const DISPLAY_NAMES: &[(&str, &str)] =
+    &[("Code", "VS Code"), ("discord", "Discord"), ("obsidian", "Obsidian"), ("Element", "Element"), ("Signal", "Signal")];
+
+/// An Electron app's data folder and the cache folders in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElectronCache {
+    pub app: String,
+    pub app_dir: PathBuf,
+    pub cache_dirs: Vec<PathBuf>,
+}
+
+impl ElectronCache {
+    /// Name of the entry in `get_cache_items`, also used to clear it
+    pub fn item_name(&self) -> String {
+        format!("{} cache", self.app)
+    }
+
+    /// Electron holds this lock while the app runs
+    pub fn is_running(&self) -> bool {
+        self.app_dir.join("SingletonLock").symlink_metadata().is_ok()
+    }
+}
+
+fn display_name(dir_name: &str) -> String {
+    DISPLAY_NAMES.iter().find(|(dir, _)| *dir == dir_name).map_or_else(|| dir_name.to_string(), |(_, name)| name.to_string())
+}
+
+fn cache_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    CACHE_DIRS.iter().map(|d| data_dir.join(d)).filter(|d| d.is_dir()).collect()
+}
+
+/// Config folders apps keep their data in, native and Flatpak
+fn config_roots(home: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![dirs::config_dir().filter(|d| d.starts_with(home)).unwrap_or_else(|| home.join(".config"))];
+    roots.extend(std::fs::read_dir(home.join(".var/app")).into_iter().flatten().filter_map(|e| e.ok()).map(|e| e.path().join("config")));
+    roots
+}
+
+/// Electron apps with cache folders; apps such as Slack keep a further data
+/// folder per workspace under `Partitions`, with caches of its own
+pub fn find_electron_caches(home: &Path) -> Vec<ElectronCache> {
+    let mut caches = Vec::new();
+    for root in config_roots(home) {
+        for entry in std::fs::read_dir(&root).into_iter().flatten().filter_map(|e| e.ok()) {
+            let app_dir = entry.path();
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) || !MARKERS.iter().any(|m| app_dir.join(m).is_dir()) {
+                continue;
+            }
+            let mut dirs = cache_dirs(&app_dir);
+            for partition in std::fs::read_dir(app_dir.join("Partitions")).into_iter().flatten().filter_map(|e| e.ok()) {
+                dirs.extend(cache_dirs(&partition.path()));
+            }
+            if !dirs.is_empty() {
+                caches.push(ElectronCache { app: display_name(&entry.file_name().to_string_lossy()), app_dir, cache_dirs: dirs });
+            }
+        }
+    }
+    caches.sort_by(|a, b| a.app.to_lowercase().cmp(&b.app.to_lowercase()).then(a.app_dir.cmp(&b.app_dir)));
+    caches
+}
+
+/// The Electron cache `get_cache_items` listed as `item_name`
+pub fn find_by_item_name(home: &Path, item_name: &str) -> Option<ElectronCache> {
+    find_electron_caches(home).into_iter().find(|c| c.item_name() == item_name)
+}
+
+/// Move an app's cache folders to trash
+pub fn clear(app_handle: &tauri::AppHandle, cache: &ElectronCache) -> CleanResult {
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+    for dir in &cache.cache_dirs {
+        let path_str = dir.to_string_lossy().to_string();
+        if let Err(e) = validate_path_comprehensive(&path_str, SecurityContext::CacheCleanup) {
+            tracing::warn!("Path validation failed for {}: {}", path_str, e);
+            failed += 1;
+            continue;
+        }
+        let size = trash::get_dir_size(dir);
+        let metadata = TrashMetadata {
+            category: CATEGORY.to_string(),
+            risk_level: 0,
+            reason: format!("{} cache, rebuilt by the app", cache.app),
+        };
+        match trash::move_to_trash(&path_str, 3, Some(metadata)) {
+            Ok(_) => {
+                cleaned += 1;
+                total_size += size;
+            }
+            Err(e) => {
+                tracing::error!("Failed to clear {}: {}", path_str, e);
+                failed += 1;
+            }
+        }
+    }
+    tracing::info!("Cleared {} cache: {} folders, {} bytes, {} failed", cache.app, cleaned, total_size, failed);
+    if total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+    CleanResult { cleaned, failed, total_size, pending_release: Vec::new() }
+}
+
+pub struct ElectronCacheScanner;
+
+impl Scanner for ElectronCacheScanner {
+    fn id(&self) -> &'static str {
+        "electron_caches"
+    }
+
+    fn name(&self) -> &'static str {
+        "Electron app caches"
+    }
+
+    fn description(&self) -> &'static str {
+        "Chromium caches of VS Code, Slack, Discord and other Electron apps in ~/.config"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_caches && dirs::home_dir().is_some_and(|home| !find_electron_caches(&home).is_empty())
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        ctx.progress(0, "Looking for Electron app caches...");
+
+        let mut items = Vec::new();
+        for cache in find_electron_caches(&home) {
+            ctx.check_cancelled()?;
+            if !ctx.allows(&cache.app_dir) {
+                continue;
+            }
+            // Clearing under a running app can break its open windows until restart
+            let risk_level = if cache.is_running() { 1 } else { 0 };
+            let children: Vec<ScanItem> = cache
+                .cache_dirs
+                .iter()
+                .filter(|dir| ctx.allows(dir))
+                .map(|dir| new_item(
+                    dir.strip_prefix(&cache.app_dir).unwrap_or(dir).to_string_lossy().to_string(),
+                    dir,
+                    ctx.dir_size(dir),
+                    "cache",
+                    CATEGORY,
+                    risk_level,
+                    &format!("{} cache, rebuilt by the app", cache.app),
+                ))
+                .filter(|item| item.size > 0)
+                .collect();
+            let size: u64 = children.iter().map(|c| c.size).sum();
+            if size == 0 {
+                continue;
+            }
+            let description = if risk_level > 0 {
+                format!("{} cache folders; {} is running", children.len(), cache.app)
+            } else {
+                format!("{} cache folders in {}", children.len(), cache.app_dir.display())
+            };
+            // The app's data folder stays; only the caches listed as children are removed
+            let mut item = new_item(
+                cache.item_name(),
+                &cache.app_dir,
+                size,
+                "electron_app",
+                CATEGORY,
+                risk_level,
+                &description,
+            );
+            item.children = Some(children);
+            items.push(item);
+        }
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_electron_caches() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let home = dir.path();
+        let config = home.join(".config");
+        fs::create_dir_all(config.join("Code/Code Cache/js")).unwrap();
+        fs::create_dir_all(config.join("Code/CachedData")).unwrap();
+        fs::create_dir_all(config.join("Code/User")).unwrap();
+        fs::create_dir_all(config.join("Slack/GPUCache")).unwrap();
+        fs::create_dir_all(config.join("Slack/Partitions/team-a/Cache")).unwrap();
+        // Browsers keep their caches per profile and are not Electron apps
+        fs::create_dir_all(config.join("google-chrome/Default/Code Cache")).unwrap();
+        fs::create_dir_all(home.join(".var/app/com.discordapp.Discord/config/discord/GPUCache")).unwrap();
+
+        let found: Vec<String> = find_electron_caches(home)
+            .iter()
+            .flat_map(|c| c.cache_dirs.iter().map(move |d| format!("{}: {}", c.app, d.strip_prefix(&c.app_dir).unwrap().display())))
+            .collect();
+        assert_eq!(found, vec!["Discord: GPUCache", "Slack: GPUCache", "Slack: Partitions/team-a/Cache", "VS Code: Code Cache", "VS Code: CachedData"]);
+    }
+}
@@ -0,0 +1,195 @@
+//! What Flatpak apps keep in the home directory. Each app is sandboxed into
+//! `~/.var/app/<id>`: `cache/` holds what it rebuilds on its own, `data/` and
+//! `config/` what it can't. The cache scan and DiskPulse list every app with
+//! its cache and data sizes; only the cache is offered for clearing. Clearing
+//! from DiskPulse empties the `cache` folder rather than removing it, so a
+//! sandbox that is running keeps its mount; Flatpak recreates it otherwise.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::trash::{self, TrashMetadata};
+
+pub const CATEGORY: &str = "Flatpak App Caches";
+
+/// An app's folder under `~/.var/app`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatpakApp {
+    pub app_id: String,
+    pub dir: PathBuf,
+}
+
+/// Sizes of what an app keeps, see the module docs
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AppUsage {
+    pub cache: u64,
+    /// `data/` and `config/` together
+    pub data: u64,
+}
+
+impl FlatpakApp {
+    /// Name of the entry in `get_cache_items`, also used to clear it
+    pub fn item_name(&self) -> String {
+        format!("{} Flatpak cache", self.app_id)
+    }
+
+    pub fn cache_dir(&self) -> PathBuf {
+        self.dir.join("cache")
+    }
+
+    /// Entries of the cache folder; these go, the folder stays
+    pub fn cache_entries(&self) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> =
+            std::fs::read_dir(self.cache_dir()).into_iter().flatten().filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        entries.sort();
+        entries
+    }
+
+    pub fn usage(&self) -> AppUsage {
+        AppUsage {
+            cache: trash::get_dir_size(&self.cache_dir()),
+            data: trash::get_dir_size(&self.dir.join("data")) + trash::get_dir_size(&self.dir.join("config")),
+        }
+    }
+}
+
+/// Apps with a folder under `home`'s `.var/app`, by id
+pub fn find_apps(home: &Path) -> Vec<FlatpakApp> {
+    let mut apps: Vec<FlatpakApp> = std::fs::read_dir(home.join(".var/app"))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| FlatpakApp { app_id: e.file_name().to_string_lossy().to_string(), dir: e.path() })
+        .collect();
+    apps.sort_by(|a, b| a.app_id.cmp(&b.app_id));
+    apps
+}
+
+/// The app `get_cache_items` listed as `item_name`
+pub fn find_by_item_name(home: &Path, item_name: &str) -> Option<FlatpakApp> {
+    find_apps(home).into_iter().find(|a| a.item_name() == item_name)
+}
+
+/// Ids of running Flatpak apps. Every sandbox gets an instance folder under
+/// `$XDG_RUNTIME_DIR/.flatpak` whose `info` file names the app.
+pub fn running_app_ids(runtime_dir: &Path) -> HashSet<String> {
+    std::fs::read_dir(runtime_dir.join(".flatpak"))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| std::fs::read_to_string(e.path().join("info")).ok())
+        .filter_map(|info| app_name(&info))
+        .collect()
+}
+
+/// `name` of the `[Application]` group of an instance's `info` file
+fn app_name(info: &str) -> Option<String> {
+    let mut in_application = false;
+    for line in info.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_application = line == "[Application]";
+        } else if let Some(name) = line.strip_prefix("name=").filter(|_| in_application) {
+            return Some(name.trim().to_string());
+        }
+    }
+    None
+}
+
+/// Running apps on this session
+pub fn running_now() -> HashSet<String> {
+    dirs::runtime_dir().map(|dir| running_app_ids(&dir)).unwrap_or_default()
+}
+
+/// Move the contents of an app's cache folder to trash
+pub fn clear(app_handle: &tauri::AppHandle, app: &FlatpakApp) -> CleanResult {
+    let mut cleaned = 0;
+    let mut failed = 0;
+    let mut total_size: u64 = 0;
+    for entry in app.cache_entries() {
+        let path_str = entry.to_string_lossy().to_string();
+        if let Err(e) = validate_path_comprehensive(&path_str, SecurityContext::CacheCleanup) {
+            tracing::warn!("Path validation failed for {}: {}", path_str, e);
+            failed += 1;
+            continue;
+        }
+        let size = if entry.is_dir() { trash::get_dir_size(&entry) } else { entry.metadata().map(|m| m.len()).unwrap_or(0) };
+        let metadata = TrashMetadata {
+            category: CATEGORY.to_string(),
+            risk_level: 0,
+            reason: format!("{} cache, rebuilt by the app", app.app_id),
+        };
+        match trash::move_to_trash(&path_str, 3, Some(metadata)) {
+            Ok(_) => {
+                cleaned += 1;
+                total_size += size;
+            }
+            Err(e) => {
+                tracing::error!("Failed to clear {}: {}", path_str, e);
+                failed += 1;
+            }
+        }
+    }
+    tracing::info!("Cleared {} cache: {} entries, {} bytes, {} failed", app.app_id, cleaned, total_size, failed);
+    if total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+    CleanResult { cleaned, failed, total_size, pending_release: Vec::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_apps_and_usage() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let home = dir.path();
+        let gimp = home.join(".var/app/org.gimp.GIMP");
+        fs::create_dir_all(gimp.join("cache/fontconfig")).unwrap();
+        fs::write(gimp.join("cache/fontconfig/fonts.cache"), vec![0u8; 4096]).unwrap();
+        fs::write(gimp.join("cache/thumb.png"), vec![0u8; 1024]).unwrap();
+        fs::create_dir_all(gimp.join("config/GIMP")).unwrap();
+        fs::write(gimp.join("config/GIMP/gimprc"), vec![0u8; 100]).unwrap();
+        fs::create_dir_all(gimp.join("data")).unwrap();
+        fs::write(gimp.join("data/brush.gbr"), vec![0u8; 2000]).unwrap();
+        fs::create_dir_all(home.join(".var/app/com.spotify.Client")).unwrap();
+        fs::write(home.join(".var/app/stray-file"), b"x").unwrap();
+
+        let apps = find_apps(home);
+        let ids: Vec<&str> = apps.iter().map(|a| a.app_id.as_str()).collect();
+        assert_eq!(ids, ["com.spotify.Client", "org.gimp.GIMP"]);
+
+        let gimp_app = &apps[1];
+        let usage = gimp_app.usage();
+        assert!(usage.cache >= 5120 && usage.data >= 2100, "{:?}", usage);
+        assert_eq!(apps[0].usage(), AppUsage::default());
+        assert_eq!(gimp_app.cache_entries(), [gimp.join("cache/fontconfig"), gimp.join("cache/thumb.png")]);
+        assert_eq!(find_by_item_name(home, "org.gimp.GIMP Flatpak cache").as_ref(), Some(gimp_app));
+        assert!(find_by_item_name(home, "org.gimp.GIMP cache").is_none());
+    }
+
+    #[test]
+    fn test_running_app_ids() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let instances = dir.path().join(".flatpak");
+        fs::create_dir_all(instances.join("1234")).unwrap();
+        fs::write(
+            instances.join("1234/info"),
+            "[Application]\nname=org.gimp.GIMP\nruntime=runtime/org.gnome.Platform/x86_64/46\n\n[Instance]\ninstance-id=1234\n",
+        )
+        .unwrap();
+        fs::create_dir_all(instances.join("5678")).unwrap();
+        fs::write(instances.join("5678/info"), "[Runtime]\nname=org.gnome.Platform\n").unwrap();
+
+        assert_eq!(running_app_ids(dir.path()), HashSet::from(["org.gimp.GIMP".to_string()]));
+        assert!(running_app_ids(&dir.path().join("missing")).is_empty());
+    }
+}
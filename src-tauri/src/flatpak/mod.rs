@@ -0,0 +1,308 @@
+//! Flatpak runtimes nothing depends on anymore and deployments left behind by
+//! updates. Unused refs come from `flatpak uninstall --unused --dry-run` and are
+//! removed through `flatpak uninstall`, so the installation stays consistent; old
+//! deployments in the user installation are plain directories and go to trash.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub mod appdata;
+
+pub const CATEGORY: &str = "Flatpak";
+
+const SYSTEM_INSTALLATION: &str = "/var/lib/flatpak";
+const REF_KINDS: &[&str] = &["runtime", "app"];
+
+/// The per-user installation, ~/.local/share/flatpak
+pub fn user_installation() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("flatpak"))
+}
+
+fn installations() -> Vec<PathBuf> {
+    user_installation().into_iter().chain(std::iter::once(PathBuf::from(SYSTEM_INSTALLATION))).collect()
+}
+
+/// An installed ref, `kind/id/arch/branch`, and where it is deployed
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledRef {
+    pub reference: String,
+    pub deploy_dir: PathBuf,
+    pub system: bool,
+}
+
+/// (id, branch) pairs from the transaction table `flatpak uninstall --unused` prints:
+///
+/// ```text
+///         ID                                      Branch    Op
+///  1.     org.freedesktop.Platform.GL.default     20.08     r
+/// ```
+pub fn parse_unused(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let number = fields.next()?;
+            if !number.ends_with('.') || number.trim_end_matches('.').parse::<u32>().is_err() {
+                return None;
+            }
+            let id = fields.next()?;
+            let branch = fields.next()?;
+            Some((id.to_string(), branch.to_string()))
+        })
+        .collect()
+}
+
+/// Find the deployment of `id`/`branch` in an installation: `<root>/<kind>/<id>/<arch>/<branch>`
+fn find_ref(root: &Path, id: &str, branch: &str) -> Option<(String, PathBuf)> {
+    REF_KINDS.iter().find_map(|kind| {
+        let arches = std::fs::read_dir(root.join(kind).join(id)).ok()?;
+        arches.filter_map(|e| e.ok()).find_map(|arch| {
+            let dir = arch.path().join(branch);
+            dir.is_dir()
+                .then(|| (format!("{}/{}/{}/{}", kind, id, arch.file_name().to_string_lossy(), branch), dir))
+        })
+    })
+}
+
+fn resolve(unused: &[(String, String)], roots: &[PathBuf]) -> Vec<InstalledRef> {
+    unused
+        .iter()
+        .filter_map(|(id, branch)| {
+            roots.iter().find_map(|root| {
+                find_ref(root, id, branch).map(|(reference, deploy_dir)| InstalledRef {
+                    reference,
+                    deploy_dir,
+                    system: root == Path::new(SYSTEM_INSTALLATION),
+                })
+            })
+        })
+        .collect()
+}
+
+fn run_flatpak(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("flatpak")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run flatpak: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("flatpak {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn flatpak_available() -> bool {
+    crate::snapshots::tool_available("flatpak")
+}
+
+/// Refs `flatpak uninstall --unused` would remove, in the user or system installation
+pub fn unused_refs() -> Result<Vec<InstalledRef>, String> {
+    let output = run_flatpak(&["uninstall", "--unused", "--dry-run"])?;
+    Ok(resolve(&parse_unused(&output), &installations()))
+}
+
+/// Deployments of a ref other than the one its `active` link points at, plus
+/// whatever is left in `.removed` (deployments kept while an app was running)
+pub fn old_deployments(root: &Path) -> Vec<PathBuf> {
+    let mut old = Vec::new();
+    let list = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default()
+    };
+
+    for kind in REF_KINDS {
+        for id in list(&root.join(kind)) {
+            for arch in list(&id) {
+                for branch in list(&arch) {
+                    let Ok(active) = std::fs::read_link(branch.join("active")) else {
+                        continue;
+                    };
+                    let active = active.file_name().map(|n| n.to_os_string());
+                    for deploy in list(&branch) {
+                        let name = deploy.file_name().map(|n| n.to_os_string());
+                        if name.as_deref() != Some(OsStr::new("active")) && name != active && deploy.is_dir() && !deploy.is_symlink() {
+                            old.push(deploy);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    old.extend(list(&root.join(".removed")));
+    old.sort();
+    old
+}
+
+/// Uninstall refs, returning the bytes their deployments took
+pub fn uninstall(refs: &[InstalledRef]) -> Result<u64, String> {
+    let size: u64 = refs.iter().map(|r| crate::trash::get_dir_size(&r.deploy_dir)).sum();
+    let mut args = vec!["uninstall", "--noninteractive", "-y"];
+    args.extend(refs.iter().map(|r| r.reference.as_str()));
+    run_flatpak(&args).map(|_| size)
+}
+
+fn ref_item(installed: &InstalledRef, size: u64) -> ScanItem {
+    let mut parts = installed.reference.split('/');
+    let (kind, id, branch) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""), parts.nth(1).unwrap_or(""));
+    new_item(
+        installed.reference.clone(),
+        &installed.deploy_dir,
+        size,
+        "flatpak_ref",
+        CATEGORY,
+        0,
+        &format!(
+            "Unused {} {} {} in the {} installation, no installed app needs it",
+            kind,
+            id,
+            branch,
+            if installed.system { "system" } else { "user" }
+        ),
+    )
+}
+
+pub struct FlatpakScanner;
+
+impl Scanner for FlatpakScanner {
+    fn id(&self) -> &'static str {
+        "flatpak"
+    }
+
+    fn name(&self) -> &'static str {
+        "Flatpak"
+    }
+
+    fn description(&self) -> &'static str {
+        "Unused Flatpak runtimes and leftover deployments"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_packages && flatpak_available()
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        if !flatpak_available() {
+            return Ok(Vec::new());
+        }
+        ctx.progress(0, "Looking for unused Flatpak runtimes...");
+        let mut items = Vec::new();
+        for installed in unused_refs().map_err(|e| ScannerError::IoError(std::io::Error::other(e)))? {
+            ctx.check_cancelled()?;
+            items.push(ref_item(&installed, ctx.dir_size(&installed.deploy_dir)));
+        }
+
+        ctx.progress(50, "Checking for old Flatpak deployments...");
+        if let Some(root) = user_installation() {
+            for deploy in old_deployments(&root) {
+                ctx.check_cancelled()?;
+                let size = ctx.dir_size(&deploy);
+                items.push(new_item(
+                    deploy.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                    &deploy,
+                    size,
+                    "directory",
+                    CATEGORY,
+                    1,
+                    "Old Flatpak deployment superseded by an update",
+                ));
+            }
+        }
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        Ok(items)
+    }
+}
+
+/// Uninstall unused refs. Refs that are no longer unused (an app was installed
+/// that needs them) are left alone and counted as failed.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn uninstall_flatpak_refs(
+    app_handle: tauri::AppHandle,
+    refs: Vec<String>,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    let plan = OperationPlan::UninstallFlatpakRefs { refs: refs.clone() };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    let mut refs = refs;
+    refs.sort();
+    refs.dedup();
+    let unused = tokio::task::spawn_blocking(unused_refs)
+        .await
+        .map_err(|e| format!("Flatpak task failed: {}", e))??;
+    let targets: Vec<InstalledRef> = unused.into_iter().filter(|r| refs.contains(&r.reference)).collect();
+    let failed = refs.len() - targets.len();
+    if failed > 0 {
+        tracing::warn!("{} Flatpak refs are no longer unused, skipping them", failed);
+    }
+
+    if targets.is_empty() {
+        return Ok(CleanResult { cleaned: 0, failed, total_size: 0, pending_release: Vec::new() });
+    }
+    let cleaned = targets.len();
+    let total_size = tokio::task::spawn_blocking(move || uninstall(&targets))
+        .await
+        .map_err(|e| format!("Flatpak task failed: {}", e))??;
+    tracing::info!("Uninstalled {} unused Flatpak refs ({} bytes)", cleaned, total_size);
+
+    if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+        tracing::warn!("Failed to record cleanup history: {}", e);
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_unused() {
+        let output = "\n        ID                                      Branch    Op\n \
+                      1.     org.freedesktop.Platform.GL.default     20.08     r\n \
+                      2.     org.gnome.Platform                      3.38      r\n\n";
+        assert_eq!(
+            parse_unused(output),
+            vec![
+                ("org.freedesktop.Platform.GL.default".to_string(), "20.08".to_string()),
+                ("org.gnome.Platform".to_string(), "3.38".to_string()),
+            ]
+        );
+        assert!(parse_unused("Nothing unused to uninstall\n").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_and_old_deployments() {
+        let root = tempfile::TempDir::new().unwrap();
+        let branch = root.path().join("runtime/org.gnome.Platform/x86_64/3.38");
+        fs::create_dir_all(branch.join("aaaa")).unwrap();
+        fs::create_dir_all(branch.join("bbbb")).unwrap();
+        std::os::unix::fs::symlink("bbbb", branch.join("active")).unwrap();
+        fs::create_dir_all(root.path().join(".removed/org.example.App-cccc")).unwrap();
+
+        let refs = resolve(
+            &[("org.gnome.Platform".to_string(), "3.38".to_string()), ("org.missing".to_string(), "1".to_string())],
+            &[root.path().to_path_buf()],
+        );
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].reference, "runtime/org.gnome.Platform/x86_64/3.38");
+        assert_eq!(refs[0].deploy_dir, branch);
+
+        assert_eq!(old_deployments(root.path()), vec![root.path().join(".removed/org.example.App-cccc"), branch.join("aaaa")]);
+    }
+}
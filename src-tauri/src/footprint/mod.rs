@@ -0,0 +1,188 @@
+//! Pulito's own memory use. The resident set size is sampled every minute into
+//! a small history; while it is above the configured limit the app is "under
+//! pressure": in-memory buffers are shrunk, freed heap is handed back to the
+//! system and the job queue holds off until usage drops again.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const SAMPLE_INTERVAL_SECS: u64 = 60;
+/// Samples kept, 12 hours at one per minute
+const HISTORY_LEN: usize = 720;
+/// Minimum time between two rounds of relief
+const RELIEF_COOLDOWN_SECS: i64 = 5 * 60;
+
+static UNDER_PRESSURE: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    static ref HISTORY: Mutex<History> = Mutex::new(History::default());
+}
+
+#[derive(Default)]
+struct History {
+    samples: VecDeque<MemorySample>,
+    last_relief_at: Option<i64>,
+}
+
+/// How much memory the app may use before it sheds load
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct FootprintSettings {
+    pub limit_mb: u64,
+}
+
+impl Default for FootprintSettings {
+    fn default() -> Self {
+        Self { limit_mb: 400 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct MemorySample {
+    pub timestamp: i64,
+    pub rss_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct MemoryFootprint {
+    pub rss_bytes: u64,
+    pub peak_rss_bytes: u64, // since the process started
+    pub limit_bytes: u64,
+    pub under_pressure: bool,
+    pub last_relief_at: Option<i64>,
+    pub history: Vec<MemorySample>, // oldest first
+}
+
+/// Current and peak resident set size in bytes from the text of /proc/self/status
+fn parse_status(status: &str) -> (Option<u64>, Option<u64>) {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+    };
+    (field("VmRSS:"), field("VmHWM:"))
+}
+
+fn read_status() -> (Option<u64>, Option<u64>) {
+    std::fs::read_to_string("/proc/self/status").map(|s| parse_status(&s)).unwrap_or((None, None))
+}
+
+/// Whether the app is over its memory limit; background work should wait
+pub fn under_pressure() -> bool {
+    UNDER_PRESSURE.load(Ordering::Relaxed)
+}
+
+fn push_sample(history: &mut History, sample: MemorySample) {
+    if history.samples.len() == HISTORY_LEN {
+        history.samples.pop_front();
+    }
+    history.samples.push_back(sample);
+}
+
+/// Give memory back: trim in-memory buffers and return freed heap pages to the system
+fn relieve() {
+    crate::metrics::shrink();
+    // glibc keeps freed memory in its arenas until asked to release it
+    #[cfg(target_env = "gnu")]
+    unsafe {
+        libc::malloc_trim(0);
+    }
+}
+
+/// Record a sample and shed load when it is over `limit_bytes`
+fn check(rss_bytes: u64, limit_bytes: u64, now: i64) {
+    let over = rss_bytes > limit_bytes;
+    let was_over = UNDER_PRESSURE.swap(over, Ordering::Relaxed);
+    let relieve_now = {
+        let mut history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+        push_sample(&mut history, MemorySample { timestamp: now, rss_bytes });
+        let due = history.last_relief_at.map_or(true, |at| now - at >= RELIEF_COOLDOWN_SECS);
+        if over && due {
+            history.last_relief_at = Some(now);
+        }
+        over && due
+    };
+
+    if relieve_now {
+        tracing::warn!("Using {} MB, over the {} MB limit; shedding load", rss_bytes / (1024 * 1024), limit_bytes / (1024 * 1024));
+        relieve();
+    } else if was_over && !over {
+        tracing::info!("Memory use back under the limit at {} MB", rss_bytes / (1024 * 1024));
+    }
+}
+
+async fn limit_bytes(app_handle: &tauri::AppHandle) -> u64 {
+    let settings = crate::commands::get_settings(app_handle.clone()).await.unwrap_or_default();
+    settings.memory.limit_mb.saturating_mul(1024 * 1024)
+}
+
+/// Sample memory use for the life of the process
+pub fn start_sampler(app_handle: tauri::AppHandle) {
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    STARTED.call_once(|| {
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(SAMPLE_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let (Some(rss), _) = read_status() {
+                    check(rss, limit_bytes(&app_handle).await, chrono::Utc::now().timestamp());
+                }
+            }
+        });
+    });
+}
+
+/// The app's memory use now and over the last hours
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_memory_footprint(app_handle: tauri::AppHandle) -> Result<MemoryFootprint, String> {
+    let (rss, peak) = read_status();
+    let rss_bytes = rss.ok_or("Cannot read the app's memory use")?;
+    let history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(MemoryFootprint {
+        rss_bytes,
+        peak_rss_bytes: peak.unwrap_or(rss_bytes),
+        limit_bytes: limit_bytes(&app_handle).await,
+        under_pressure: under_pressure(),
+        last_relief_at: history.last_relief_at,
+        history: history.samples.iter().copied().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = "Name:\tpulito\nVmPeak:\t 2480000 kB\nVmHWM:\t  310000 kB\nVmRSS:\t  204800 kB\nThreads:\t24\n";
+        assert_eq!(parse_status(status), (Some(204_800 * 1024), Some(310_000 * 1024)));
+        assert_eq!(parse_status("Name:\tkthreadd\n"), (None, None));
+    }
+
+    #[test]
+    fn test_pressure_follows_limit() {
+        let mb = 1024 * 1024;
+        check(500 * mb, 400 * mb, 1_000);
+        assert!(under_pressure());
+        let first_relief = HISTORY.lock().unwrap().last_relief_at;
+        assert_eq!(first_relief, Some(1_000));
+
+        // Still over, but relief has a cooldown
+        check(450 * mb, 400 * mb, 1_060);
+        assert_eq!(HISTORY.lock().unwrap().last_relief_at, Some(1_000));
+
+        check(300 * mb, 400 * mb, 1_120);
+        assert!(!under_pressure());
+        let history = HISTORY.lock().unwrap();
+        assert_eq!(history.samples.back(), Some(&MemorySample { timestamp: 1_120, rss_bytes: 300 * mb }));
+    }
+}
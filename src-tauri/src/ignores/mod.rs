@@ -0,0 +1,264 @@
+//! Standing "ignore this" rules. A rule is a path, which also covers
+//! everything below it, or a glob such as `~/Videos/**/*.mkv`; it applies to
+//! every category or to one, and may expire. Rules are kept in `ignore_rules`
+//! next to the leaderboard's `ignored_files`, which count as global path rules,
+//! and are applied to scan results, storage recovery, the largest files board
+//! and cache suggestions.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+
+use crate::db::DbAccess;
+use crate::scanner::glob;
+use crate::scanner::ScanItem;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct IgnoreRule {
+    pub id: i64,
+    pub pattern: String, // a path or glob; `~` is the home directory
+    pub category: Option<String>, // None ignores in every category
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    pattern: PathBuf,
+    glob: bool,
+    category: Option<String>,
+}
+
+/// Active rules, ready to match paths against
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreSet {
+    rules: Vec<CompiledRule>,
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains('*') || pattern.contains('?')
+}
+
+impl IgnoreSet {
+    /// Global rules for exact paths
+    pub fn from_paths<I: IntoIterator<Item = String>>(paths: I) -> Self {
+        let rules = paths.into_iter().map(|p| CompiledRule { pattern: PathBuf::from(p), glob: false, category: None }).collect();
+        Self { rules }
+    }
+
+    fn push(&mut self, rule: &IgnoreRule, home: &Path) {
+        self.rules.push(CompiledRule {
+            pattern: glob::expand_home(&rule.pattern, home),
+            glob: is_glob(&rule.pattern),
+            category: rule.category.clone(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path`, listed under `category`, is ignored
+    pub fn ignores(&self, path: &str, category: &str) -> bool {
+        let path = Path::new(path);
+        self.rules.iter().any(|rule| {
+            let applies = rule.category.as_deref().map_or(true, |c| c.eq_ignore_ascii_case(category));
+            let matched = if rule.glob {
+                path.ancestors().any(|p| glob::matches(&rule.pattern, p))
+            } else {
+                path.starts_with(&rule.pattern)
+            };
+            applies && matched
+        })
+    }
+
+    /// Drop ignored items, children included
+    pub fn retain_items(&self, items: &mut Vec<ScanItem>) {
+        items.retain(|item| !self.ignores(&item.path, &item.category));
+        for item in items.iter_mut() {
+            if let Some(children) = item.children.as_mut() {
+                self.retain_items(children);
+            }
+        }
+    }
+}
+
+fn rule_from_row(row: &rusqlite::Row) -> rusqlite::Result<IgnoreRule> {
+    Ok(IgnoreRule {
+        id: row.get(0)?,
+        pattern: row.get(1)?,
+        category: row.get(2)?,
+        created_at: row.get(3)?,
+        expires_at: row.get(4)?,
+        note: row.get(5)?,
+    })
+}
+
+/// Rules newest first; expired ones only with `include_expired`
+pub fn list_rules(conn: &Connection, now: i64, include_expired: bool) -> rusqlite::Result<Vec<IgnoreRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, pattern, category, created_at, expires_at, note FROM ignore_rules
+         WHERE ?2 OR expires_at IS NULL OR expires_at > ?1
+         ORDER BY created_at DESC, id DESC",
+    )?;
+    let rules = stmt.query_map(params![now, include_expired], rule_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rules)
+}
+
+pub fn add_rule(
+    conn: &Connection,
+    pattern: &str,
+    category: Option<&str>,
+    expires_at: Option<i64>,
+    note: Option<&str>,
+    now: i64,
+) -> rusqlite::Result<IgnoreRule> {
+    // The same rule again only renews it
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM ignore_rules WHERE pattern = ?1 AND category IS ?2",
+            params![pattern, category],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let id = match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE ignore_rules SET created_at = ?2, expires_at = ?3, note = COALESCE(?4, note) WHERE id = ?1",
+                params![id, now, expires_at, note],
+            )?;
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO ignore_rules (pattern, category, created_at, expires_at, note) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![pattern, category, now, expires_at, note],
+            )?;
+            conn.last_insert_rowid()
+        }
+    };
+    conn.query_row("SELECT id, pattern, category, created_at, expires_at, note FROM ignore_rules WHERE id = ?1", [id], rule_from_row)
+}
+
+/// Active rules plus the leaderboard's ignored files
+pub fn load(conn: &Connection, now: i64, home: &Path) -> rusqlite::Result<IgnoreSet> {
+    let mut set = IgnoreSet::from_paths(crate::leaderboard::ignored_paths(conn)?);
+    for rule in list_rules(conn, now, false)? {
+        set.push(&rule, home);
+    }
+    Ok(set)
+}
+
+/// The active rules, or none if they can't be read
+pub fn load_active(app_handle: &tauri::AppHandle) -> IgnoreSet {
+    let home = dirs::home_dir().unwrap_or_default();
+    app_handle.db(|conn| load(conn, chrono::Utc::now().timestamp(), &home)).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load ignore rules: {}", e);
+        IgnoreSet::default()
+    })
+}
+
+fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() || !(pattern.starts_with('/') || pattern.starts_with('~')) {
+        return Err(format!("Expected an absolute path or one starting with ~, got '{}'", pattern));
+    }
+    if pattern == "/" || pattern == "~" || pattern == "/**" || pattern == "~/**" {
+        return Err("A rule for everything would hide all results".to_string());
+    }
+    Ok(())
+}
+
+/// Ignore a path or glob in `category`, or everywhere, for `expires_in_days` or for good
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn add_ignore_rule(
+    app_handle: tauri::AppHandle,
+    pattern: String,
+    category: Option<String>,
+    expires_in_days: Option<u32>,
+    note: Option<String>,
+) -> Result<IgnoreRule, String> {
+    validate_pattern(&pattern)?;
+    let now = chrono::Utc::now().timestamp();
+    let expires_at = expires_in_days.map(|days| now + i64::from(days) * 24 * 3600);
+    let rule = app_handle
+        .db(|conn| add_rule(conn, pattern.trim(), category.as_deref(), expires_at, note.as_deref(), now))
+        .map_err(|e| format!("Failed to add ignore rule: {}", e))?;
+    tracing::info!("Ignoring {} in {}", rule.pattern, rule.category.as_deref().unwrap_or("all categories"));
+    Ok(rule)
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_ignore_rules(app_handle: tauri::AppHandle, include_expired: Option<bool>) -> Result<Vec<IgnoreRule>, String> {
+    app_handle
+        .db(|conn| list_rules(conn, chrono::Utc::now().timestamp(), include_expired.unwrap_or(false)))
+        .map_err(|e| format!("Failed to list ignore rules: {}", e))
+}
+
+/// Revoke a rule; what it hid shows up again in the next scan
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn remove_ignore_rule(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let removed = app_handle
+        .db(|conn| conn.execute("DELETE FROM ignore_rules WHERE id = ?1", [id]))
+        .map_err(|e| format!("Failed to remove ignore rule: {}", e))?;
+    if removed == 0 {
+        return Err(format!("No ignore rule {}", id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE ignore_rules (id INTEGER PRIMARY KEY AUTOINCREMENT, pattern TEXT NOT NULL, category TEXT,
+             created_at INTEGER NOT NULL, expires_at INTEGER, note TEXT);
+             CREATE TABLE ignored_files (path TEXT PRIMARY KEY, ignored_at INTEGER NOT NULL);
+             INSERT INTO ignored_files VALUES ('/home/u/keep.iso', 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_rules_by_category_and_expiry() {
+        let conn = rules_db();
+        let home = Path::new("/home/u");
+        add_rule(&conn, "~/Videos/**/*.mkv", None, None, Some("films"), 100).unwrap();
+        add_rule(&conn, "~/.cache/pip", Some("Cache"), None, None, 100).unwrap();
+        add_rule(&conn, "/var/log/app.log", None, Some(200), None, 100).unwrap();
+        // Renewed, not duplicated
+        let renewed = add_rule(&conn, "~/.cache/pip", Some("Cache"), None, None, 150).unwrap();
+        assert_eq!((renewed.id, renewed.created_at), (2, 150));
+        assert_eq!(list_rules(&conn, 100, false).unwrap().len(), 3);
+
+        let set = load(&conn, 100, home).unwrap();
+        assert!(set.ignores("/home/u/Videos/2023/trip.mkv", "Large Files"));
+        assert!(!set.ignores("/home/u/Videos/trip.mp4", "Large Files"));
+        assert!(set.ignores("/home/u/.cache/pip/wheels", "cache"));
+        assert!(!set.ignores("/home/u/.cache/pip", "Packages"));
+        assert!(set.ignores("/home/u/keep.iso", "Downloads"));
+        assert!(set.ignores("/var/log/app.log", "Logs"));
+
+        // Expired rules stop applying but can still be reviewed
+        assert!(!load(&conn, 300, home).unwrap().ignores("/var/log/app.log", "Logs"));
+        assert_eq!(list_rules(&conn, 300, false).unwrap().len(), 2);
+        assert_eq!(list_rules(&conn, 300, true).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_validate_pattern() {
+        assert!(validate_pattern("~/Downloads/*.iso").is_ok());
+        assert!(validate_pattern("relative/path").is_err());
+        assert!(validate_pattern("~").is_err());
+    }
+}
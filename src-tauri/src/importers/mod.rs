@@ -0,0 +1,406 @@
+//! Import analysis results from external tools (ncdu, fdupes, rmlint).
+//!
+//! Imported entries become regular `ScanItem`s and `DuplicateGroup`s, so they are
+//! reviewed and cleaned through the same validated trash/restore pipeline as
+//! Pulito's own scans. Nothing is acted on during import.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::{compute_file_hash_full, CancelFlag, DuplicateGroup, ScanItem};
+
+/// Largest import file accepted
+const MAX_IMPORT_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Files kept from an ncdu export, largest first
+const MAX_NCDU_FILES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum ImportFormat {
+    NcduJson,
+    Fdupes,
+    RmlintJson,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ImportedResults {
+    pub format: ImportFormat,
+    pub source: String,
+    pub items: Vec<ScanItem>,
+    pub duplicates: Vec<DuplicateGroup>,
+    pub total_size: u64,
+    pub missing_locally: usize, // entries whose path doesn't exist on this machine
+    pub warnings: Vec<String>,
+}
+
+fn imported_item(path: &str, size: u64, category: &str, risk_level: u8, description: &str) -> ScanItem {
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    new_item(name, Path::new(path), size, "file", category, risk_level, description)
+}
+
+fn duplicate_group(id: String, files: Vec<ScanItem>) -> DuplicateGroup {
+    let group_size = files.len();
+    let size = files.first().map(|f| f.size).unwrap_or(0);
+    DuplicateGroup {
+        id,
+        files,
+        total_size: size * group_size as u64,
+        group_size,
+        verified: false,
+        suggested_keeper: None,
+        preselected: Vec::new(),
+    }
+}
+
+/// Whether every copy in an imported group is a regular file here with the
+/// reported size and the same content; the other tool's word isn't enough to
+/// pre-select anything for deletion
+fn verify_locally(group: &DuplicateGroup) -> bool {
+    let cancel = CancelFlag::default();
+    let mut first_hash = None;
+    for file in &group.files {
+        let path = Path::new(&file.path);
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_file() && metadata.len() == file.size => {}
+            _ => return false,
+        }
+        let Ok(hash) = compute_file_hash_full(path, &cancel) else {
+            return false;
+        };
+        match &first_hash {
+            None => first_hash = Some(hash),
+            Some(first) if *first == hash => {}
+            Some(_) => return false,
+        }
+    }
+    group.files.len() > 1
+}
+
+/// Guess the format from file content
+pub fn detect_format(content: &str) -> Option<ImportFormat> {
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('[') {
+        let value: Value = serde_json::from_str(trimmed).ok()?;
+        let array = value.as_array()?;
+        // ncdu: [major, minor, {metadata}, [root dir...]]
+        if array.first().map(|v| v.is_u64()).unwrap_or(false) {
+            return Some(ImportFormat::NcduJson);
+        }
+        if array.iter().any(|v| v.get("type").is_some() && v.get("path").is_some()) {
+            return Some(ImportFormat::RmlintJson);
+        }
+        return None;
+    }
+    if trimmed.starts_with('/') || trimmed.split_whitespace().nth(1) == Some("bytes") {
+        return Some(ImportFormat::Fdupes);
+    }
+    None
+}
+
+/// ncdu JSON export: a directory is an array whose first element describes
+/// it and whose remaining elements are files (objects) or subdirectories (arrays)
+pub fn parse_ncdu(content: &str) -> Result<Vec<ScanItem>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| format!("Invalid ncdu JSON: {}", e))?;
+    let root = value
+        .as_array()
+        .and_then(|a| a.get(3))
+        .ok_or("Not an ncdu export: missing root directory")?;
+
+    fn walk(dir: &Value, parent: &str, out: &mut Vec<ScanItem>) {
+        let Some(entries) = dir.as_array() else { return };
+        let Some(name) = entries.first().and_then(|info| info.get("name")).and_then(|n| n.as_str()) else {
+            return;
+        };
+        let dir_path = if parent.is_empty() { name.to_string() } else { format!("{}/{}", parent.trim_end_matches('/'), name) };
+
+        for entry in &entries[1..] {
+            if entry.is_array() {
+                walk(entry, &dir_path, out);
+                continue;
+            }
+            let Some(name) = entry.get("name").and_then(|n| n.as_str()) else { continue };
+            // Skip excluded and non-regular entries
+            if entry.get("excluded").is_some() || entry.get("notreg").and_then(|v| v.as_bool()).unwrap_or(false) {
+                continue;
+            }
+            let size = entry
+                .get("dsize")
+                .or_else(|| entry.get("asize"))
+                .and_then(|s| s.as_u64())
+                .unwrap_or(0);
+            let path = format!("{}/{}", dir_path.trim_end_matches('/'), name);
+            out.push(imported_item(&path, size, "imported_ncdu", 2, "File from ncdu export"));
+        }
+    }
+
+    let mut items = Vec::new();
+    walk(root, "", &mut items);
+    items.sort_by(|a, b| b.size.cmp(&a.size));
+    items.truncate(MAX_NCDU_FILES);
+    Ok(items)
+}
+
+/// fdupes output: groups of paths separated by blank lines, optionally
+/// preceded by an "N bytes each:" line (`fdupes -S`)
+pub fn parse_fdupes(content: &str) -> Vec<DuplicateGroup> {
+    let mut groups = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut size: Option<u64> = None;
+
+    fn flush(current: &mut Vec<String>, size: &mut Option<u64>, groups: &mut Vec<DuplicateGroup>) {
+        if current.len() > 1 {
+            let files = current
+                .iter()
+                .map(|p| {
+                    let size = size.or_else(|| std::fs::metadata(p).ok().map(|m| m.len())).unwrap_or(0);
+                    imported_item(p, size, "duplicate", 2, "Duplicate file reported by fdupes")
+                })
+                .collect();
+            groups.push(duplicate_group(format!("fdupes_group_{}", groups.len()), files));
+        }
+        current.clear();
+        *size = None;
+    }
+
+    for line in content.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            flush(&mut current, &mut size, &mut groups);
+        } else if let Some(bytes) = line.strip_suffix(" bytes each:").or_else(|| line.strip_suffix(" byte each:")) {
+            size = bytes.trim().parse().ok();
+        } else {
+            current.push(line.to_string());
+        }
+    }
+    flush(&mut current, &mut size, &mut groups);
+    groups
+}
+
+/// rmlint JSON: header, one object per finding, footer. Duplicates are grouped
+/// by checksum with the original first; other lint types become plain items.
+pub fn parse_rmlint(content: &str) -> Result<(Vec<ScanItem>, Vec<DuplicateGroup>), String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| format!("Invalid rmlint JSON: {}", e))?;
+    let entries = value.as_array().ok_or("Not an rmlint export: expected a JSON array")?;
+
+    let mut items = Vec::new();
+    let mut by_checksum: BTreeMap<String, Vec<(bool, ScanItem)>> = BTreeMap::new();
+
+    for entry in entries {
+        let (Some(kind), Some(path)) = (
+            entry.get("type").and_then(|t| t.as_str()),
+            entry.get("path").and_then(|p| p.as_str()),
+        ) else {
+            continue; // header and footer
+        };
+        let size = entry.get("size").and_then(|s| s.as_u64()).unwrap_or(0);
+
+        match kind {
+            "duplicate_file" => {
+                let checksum = entry.get("checksum").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+                let original = entry.get("is_original").and_then(|o| o.as_bool()).unwrap_or(false);
+                let item = imported_item(path, size, "duplicate", 2, "Duplicate file reported by rmlint");
+                by_checksum.entry(checksum).or_default().push((original, item));
+            }
+            "emptyfile" => items.push(imported_item(path, size, "empty_file", 1, "Empty file reported by rmlint")),
+            "emptydir" => {
+                let mut item = imported_item(path, size, "empty_directory", 1, "Empty directory reported by rmlint");
+                item.item_type = "directory".to_string();
+                items.push(item);
+            }
+            "badlink" => items.push(imported_item(path, size, "broken_symlink", 1, "Broken symlink reported by rmlint")),
+            other => items.push(imported_item(path, size, &format!("rmlint_{}", other), 2, "Finding reported by rmlint")),
+        }
+    }
+
+    let duplicates = by_checksum
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .enumerate()
+        .map(|(i, mut files)| {
+            // Originals first so "keep first" keeps what rmlint would keep
+            files.sort_by_key(|(original, _)| !*original);
+            duplicate_group(format!("rmlint_group_{}", i), files.into_iter().map(|(_, f)| f).collect())
+        })
+        .collect();
+
+    Ok((items, duplicates))
+}
+
+pub fn import_content(content: &str, format: ImportFormat, source: &str) -> Result<ImportedResults, String> {
+    let (items, mut duplicates) = match format {
+        ImportFormat::NcduJson => (parse_ncdu(content)?, Vec::new()),
+        ImportFormat::Fdupes => (Vec::new(), parse_fdupes(content)),
+        ImportFormat::RmlintJson => parse_rmlint(content)?,
+    };
+    for group in &mut duplicates {
+        group.verified = verify_locally(group);
+    }
+    // Groups list what the tool itself would keep first; copies are only
+    // pre-selected once the group is confirmed on this machine
+    crate::duplicates::mark_suggestions(&mut duplicates, crate::duplicates::KeepStrategy::FirstListed, &[]);
+    for group in duplicates.iter_mut().filter(|g| !g.verified) {
+        group.preselected.clear();
+    }
+    let unverified = duplicates.iter().filter(|g| !g.verified).count();
+
+    let all_paths = items.iter().chain(duplicates.iter().flat_map(|g| g.files.iter())).map(|i| &i.path);
+    let mut warnings = Vec::new();
+    let mut missing_locally = 0;
+    let mut relative = 0;
+    for path in all_paths {
+        if !Path::new(path).is_absolute() {
+            relative += 1;
+        } else if std::fs::symlink_metadata(path).is_err() {
+            missing_locally += 1;
+        }
+    }
+    if relative > 0 {
+        warnings.push(format!("{} entries have relative paths and cannot be cleaned", relative));
+    }
+    if missing_locally > 0 {
+        warnings.push(format!(
+            "{} entries don't exist on this machine; they can be reviewed but not cleaned here",
+            missing_locally
+        ));
+    }
+    if unverified > 0 {
+        warnings.push(format!(
+            "{} duplicate groups don't match by size and content here, so none of their copies are pre-selected",
+            unverified
+        ));
+    }
+
+    let total_size = items.iter().map(|i| i.size).sum::<u64>() + duplicates.iter().map(|g| g.total_size).sum::<u64>();
+    Ok(ImportedResults {
+        format,
+        source: source.to_string(),
+        items,
+        duplicates,
+        total_size,
+        missing_locally,
+        warnings,
+    })
+}
+
+/// Import an ncdu/fdupes/rmlint result file. The format is detected when not given.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn import_external_results(path: String, format: Option<ImportFormat>) -> Result<ImportedResults, String> {
+    tokio::task::spawn_blocking(move || {
+        let metadata = std::fs::metadata(&path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        if metadata.len() > MAX_IMPORT_BYTES {
+            return Err(format!("Import file is too large ({} bytes, max {})", metadata.len(), MAX_IMPORT_BYTES));
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let format = format
+            .or_else(|| detect_format(&content))
+            .ok_or("Unrecognized format; expected ncdu JSON, fdupes output or rmlint JSON")?;
+
+        let results = import_content(&content, format, &path)?;
+        crate::safe_mode::record_scanned(results.items.iter().chain(results.duplicates.iter().flat_map(|g| g.files.iter())));
+        tracing::info!(
+            "Imported {:?} results from {}: {} items, {} duplicate groups",
+            format,
+            path,
+            results.items.len(),
+            results.duplicates.len()
+        );
+        Ok(results)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NCDU: &str = r#"[1,2,{"progname":"ncdu","progver":"1.19","timestamp":1700000000},
+        [{"name":"/srv/data","asize":4096,"dsize":4096},
+         {"name":"big.iso","asize":1000,"dsize":1024},
+         [{"name":"logs"},
+          {"name":"old.log","asize":300},
+          {"name":"fifo","notreg":true}],
+         {"name":"skip.me","excluded":"pattern"}]]"#;
+
+    const RMLINT: &str = r#"[
+        {"description":"rmlint json-dump of lint files","cwd":"/srv"},
+        {"type":"duplicate_file","path":"/srv/b/x","size":10,"checksum":"aa","is_original":false},
+        {"type":"duplicate_file","path":"/srv/a/x","size":10,"checksum":"aa","is_original":true},
+        {"type":"emptydir","path":"/srv/empty","size":0},
+        {"type":"badlink","path":"/srv/link","size":0},
+        {"aborted":false,"progress":100}
+    ]"#;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(NCDU), Some(ImportFormat::NcduJson));
+        assert_eq!(detect_format(RMLINT), Some(ImportFormat::RmlintJson));
+        assert_eq!(detect_format("/a/x\n/b/x\n"), Some(ImportFormat::Fdupes));
+        assert_eq!(detect_format("4096 bytes each:\n/a/x\n/b/x\n"), Some(ImportFormat::Fdupes));
+        assert_eq!(detect_format("hello"), None);
+    }
+
+    #[test]
+    fn test_parse_ncdu() {
+        let items = parse_ncdu(NCDU).unwrap();
+        let paths: Vec<&str> = items.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["/srv/data/big.iso", "/srv/data/logs/old.log"]);
+        assert_eq!(items[0].size, 1024);
+        assert_eq!(items[1].size, 300);
+    }
+
+    #[test]
+    fn test_parse_fdupes() {
+        let groups = parse_fdupes("4096 bytes each:\n/a/x\n/b/x\n\n/c/y\n/d/y\n/e/y\n\n/lonely\n");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].files[0].size, 4096);
+        assert_eq!(groups[0].total_size, 8192);
+        assert_eq!(groups[1].group_size, 3);
+    }
+
+    #[test]
+    fn test_parse_rmlint() {
+        let (items, duplicates) = parse_rmlint(RMLINT).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].item_type, "directory");
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].files[0].path, "/srv/a/x");
+    }
+
+    #[test]
+    fn test_missing_paths_are_reported() {
+        let results = import_content(RMLINT, ImportFormat::RmlintJson, "lint.json").unwrap();
+        assert_eq!(results.missing_locally, 4);
+        assert_eq!(results.warnings.len(), 2);
+        assert!(results.duplicates[0].preselected.is_empty());
+    }
+
+    #[test]
+    fn test_only_verified_groups_are_preselected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        std::fs::write(path("a"), b"same").unwrap();
+        std::fs::write(path("b"), b"same").unwrap();
+        std::fs::write(path("c"), b"same").unwrap();
+        std::fs::write(path("d"), b"diff").unwrap();
+        let content = format!("{}\n{}\n\n{}\n{}\n", path("a"), path("b"), path("c"), path("d"));
+
+        let results = import_content(&content, ImportFormat::Fdupes, "dupes.txt").unwrap();
+        assert!(results.duplicates[0].verified);
+        assert_eq!(results.duplicates[0].preselected.len(), 1);
+        assert!(!results.duplicates[1].verified);
+        assert!(results.duplicates[1].preselected.is_empty());
+        assert_eq!(results.duplicates[1].suggested_keeper.as_deref(), Some(path("c").as_str()));
+    }
+}
@@ -0,0 +1,257 @@
+//! Superseded installers in Downloads. Every update of an app shipped as a
+//! `.deb`, AppImage or tarball, and every point release of a distribution ISO,
+//! is a new download next to the old one. Files are grouped by product from
+//! their names and all but the newest version of each are listed; duplicate
+//! detection misses them because every version has different content.
+
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Installers";
+
+/// Installer extensions, longest first so `.tar.gz` wins over `.gz`
+const EXTENSIONS: &[&str] = &[".tar.gz", ".tar.xz", ".tar.bz2", ".tgz", ".appimage", ".deb", ".rpm", ".iso", ".run", ".flatpakref"];
+/// Downloads are sorted into folders at most this deep
+const MAX_DEPTH: usize = 2;
+
+/// An installer file name taken apart: `code_1.85.1-1702462158_amd64.deb` is
+/// product `code`, version `1.85.1-1702462158` and variant `amd64`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallerName {
+    pub product: String,
+    pub version: String,
+    pub variant: String,
+    pub extension: &'static str,
+}
+
+impl InstallerName {
+    /// Installers of the same product, variant and format supersede each other
+    fn key(&self) -> (String, String, &'static str) {
+        (self.product.to_lowercase(), self.variant.to_lowercase(), self.extension)
+    }
+}
+
+fn is_version_token(token: &str) -> bool {
+    let token = token.strip_prefix(['v', 'V']).unwrap_or(token);
+    token.starts_with(|c: char| c.is_ascii_digit())
+}
+
+pub fn parse_installer(file_name: &str) -> Option<InstallerName> {
+    let lower = file_name.to_lowercase();
+    let extension = *EXTENSIONS.iter().find(|ext| lower.ends_with(*ext))?;
+    let stem = &file_name[..file_name.len() - extension.len()];
+    // Browsers name repeated downloads `name (1).deb`
+    let stem = match stem.rfind(" (") {
+        Some(at) if stem.ends_with(')') && stem[at + 2..stem.len() - 1].chars().all(|c| c.is_ascii_digit()) => &stem[..at],
+        _ => stem,
+    };
+
+    let tokens: Vec<&str> = stem.split(['-', '_', ' ']).filter(|t| !t.is_empty()).collect();
+    let start = tokens.iter().position(|t| is_version_token(t))?;
+    if start == 0 {
+        return None;
+    }
+    let end = tokens[start..].iter().position(|t| !is_version_token(t)).map_or(tokens.len(), |i| start + i);
+    Some(InstallerName {
+        product: tokens[..start].join("-"),
+        version: tokens[start..end].join("-"),
+        variant: tokens[end..].join("-"),
+        extension,
+    })
+}
+
+/// Numeric parts of a version for ordering, `v1.85.1-170` is [1, 85, 1, 170]
+fn version_key(version: &str) -> Vec<u64> {
+    version.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty()).filter_map(|p| p.parse().ok()).collect()
+}
+
+/// An installer file in Downloads
+#[derive(Debug, Clone, PartialEq)]
+pub struct Installer {
+    pub path: PathBuf,
+    pub name: InstallerName,
+    pub size: u64,
+    pub modified: i64,
+}
+
+/// A product with more than one installer; `superseded` are all but `newest`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstallerGroup {
+    pub newest: Installer,
+    pub superseded: Vec<Installer>,
+}
+
+/// Group installers by product and keep those with more than one file, newest
+/// version first; of the same version the latest download is kept
+pub fn group_installers(installers: Vec<Installer>) -> Vec<InstallerGroup> {
+    let mut groups: Vec<((String, String, &'static str), Vec<Installer>)> = Vec::new();
+    for installer in installers {
+        let key = installer.name.key();
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, members)) => members.push(installer),
+            None => groups.push((key, vec![installer])),
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(_, mut members)| {
+            members.sort_by(|a, b| {
+                version_key(&b.name.version).cmp(&version_key(&a.name.version)).then(b.modified.cmp(&a.modified))
+            });
+            let newest = members.remove(0);
+            InstallerGroup { newest, superseded: members }
+        })
+        .collect()
+}
+
+/// Installers below `dir`
+pub fn find_installers(dir: &Path, ctx: Option<&ScanContext>) -> Result<Vec<Installer>, ScannerError> {
+    let mut installers = Vec::new();
+    let mut walker = WalkDir::new(dir)
+        .max_depth(MAX_DEPTH)
+        .follow_links(false)
+        .same_file_system(ctx.map_or(true, |c| c.one_file_system()))
+        .sort_by_file_name()
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
+        if let Some(ctx) = ctx {
+            ctx.check_cancelled()?;
+            ctx.pace();
+            if entry.file_type().is_dir() && !ctx.enters(entry.path()) {
+                walker.skip_current_dir();
+                continue;
+            }
+            if !ctx.allows(entry.path()) {
+                continue;
+            }
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(name) = parse_installer(&entry.file_name().to_string_lossy()) else { continue };
+        let Ok(meta) = entry.metadata() else { continue };
+        installers.push(Installer { path: entry.path().to_path_buf(), name, size: meta.len(), modified: meta.mtime() });
+    }
+    Ok(installers)
+}
+
+fn downloads_dir() -> Option<PathBuf> {
+    dirs::download_dir().or_else(|| dirs::home_dir().map(|home| home.join("Downloads"))).filter(|dir| dir.is_dir())
+}
+
+pub struct OldInstallersScanner;
+
+impl Scanner for OldInstallersScanner {
+    fn id(&self) -> &'static str {
+        "old_installers"
+    }
+
+    fn name(&self) -> &'static str {
+        "Old installers"
+    }
+
+    fn description(&self) -> &'static str {
+        "Older versions of installers, AppImages and ISOs in Downloads"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_large_files && downloads_dir().is_some()
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let Some(downloads) = downloads_dir() else {
+            return Ok(Vec::new());
+        };
+        ctx.progress(0, "Looking for superseded installers in Downloads...");
+
+        let mut items = Vec::new();
+        for group in group_installers(find_installers(&downloads, Some(ctx))?) {
+            let newest = &group.newest;
+            let children: Vec<ScanItem> = group
+                .superseded
+                .iter()
+                .map(|old| {
+                    let same_version = version_key(&old.name.version) == version_key(&newest.name.version);
+                    let description = if same_version {
+                        format!("Another download of version {}", old.name.version)
+                    } else {
+                        format!("Version {}, superseded by {}", old.name.version, newest.name.version)
+                    };
+                    new_item(
+                        old.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                        &old.path,
+                        old.size,
+                        "file",
+                        CATEGORY,
+                        1,
+                        &description,
+                    )
+                })
+                .collect();
+            let size: u64 = children.iter().map(|c| c.size).sum();
+            // The newest installer is kept; only the older ones listed as children are removed
+            let mut item = new_item(
+                format!("{} installers", newest.name.product),
+                &newest.path,
+                size,
+                "installers",
+                CATEGORY,
+                1,
+                &format!("{} older versions; keeping {}", children.len(), newest.name.version),
+            );
+            item.children = Some(children);
+            items.push(item);
+        }
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(product: &str, version: &str, variant: &str, extension: &'static str) -> Option<InstallerName> {
+        Some(InstallerName { product: product.to_string(), version: version.to_string(), variant: variant.to_string(), extension })
+    }
+
+    #[test]
+    fn test_parse_installer() {
+        assert_eq!(parse_installer("code_1.85.1-1702462158_amd64.deb"), name("code", "1.85.1-1702462158", "amd64", ".deb"));
+        assert_eq!(parse_installer("Obsidian-1.4.16.AppImage"), name("Obsidian", "1.4.16", "", ".appimage"));
+        assert_eq!(parse_installer("ubuntu-22.04.3-desktop-amd64.iso"), name("ubuntu", "22.04.3", "desktop-amd64", ".iso"));
+        assert_eq!(parse_installer("node-v20.10.0-linux-x64.tar.xz"), name("node", "v20.10.0", "linux-x64", ".tar.xz"));
+        assert_eq!(parse_installer("jetbrains-toolbox-2.1.2.18853 (1).tar.gz"), name("jetbrains-toolbox", "2.1.2.18853", "", ".tar.gz"));
+        assert_eq!(parse_installer("notes.tar.gz"), None);
+        assert_eq!(parse_installer("2023-report.iso"), None);
+        assert_eq!(parse_installer("photo-1.jpg"), None);
+    }
+
+    #[test]
+    fn test_group_installers() {
+        let installer = |file: &str, modified: i64| Installer {
+            path: PathBuf::from("/home/u/Downloads").join(file),
+            name: parse_installer(file).unwrap(),
+            size: 100,
+            modified,
+        };
+        let groups = group_installers(vec![
+            installer("code_1.84.2-1699528352_amd64.deb", 10),
+            installer("code_1.85.1-1702462158_amd64.deb", 20),
+            installer("code_1.85.1-1702462158_amd64 (1).deb", 30),
+            installer("ubuntu-22.04.3-desktop-amd64.iso", 10),
+            installer("ubuntu-22.04.3-live-server-amd64.iso", 10),
+            installer("code-1.80.0-x64.tar.gz", 5),
+        ]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].newest.path.file_name().unwrap(), "code_1.85.1-1702462158_amd64 (1).deb");
+        let old: Vec<_> = groups[0].superseded.iter().map(|i| i.path.file_name().unwrap().to_string_lossy().to_string()).collect();
+        assert_eq!(old, vec!["code_1.85.1-1702462158_amd64.deb", "code_1.84.2-1699528352_amd64.deb"]);
+    }
+}
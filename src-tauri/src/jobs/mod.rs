@@ -0,0 +1,338 @@
+//! Persistent queue for deferrable background work. Jobs are rows of the `jobs`
+//! table, so work queued before a restart still runs afterwards; one worker
+//! takes them by priority and retries failures with a growing delay. Commands
+//! queue work here instead of doing it before they return.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::db::DbAccess;
+use crate::digest;
+use crate::footprint;
+
+/// How often the worker looks for jobs whose retry delay ran out
+const IDLE_POLL_SECS: u64 = 60;
+/// Attempts before a job is left failed
+const MAX_ATTEMPTS: u32 = 3;
+/// Retry delay per attempt already made
+const RETRY_DELAY_SECS: i64 = 5 * 60;
+/// Finished jobs are kept this long for the job list
+const FINISHED_RETENTION_DAYS: i64 = 7;
+
+lazy_static::lazy_static! {
+    /// Wakes the worker when a job is queued
+    static ref WAKE: Notify = Notify::new();
+}
+
+/// Set at exit; the worker takes no new jobs
+static STOPPING: AtomicBool = AtomicBool::new(false);
+
+/// Work that can wait; stored as JSON in the `payload` column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Job {
+    /// Fold completed months into storage digests
+    RollUpDigests,
+    /// Record sizes and modification times for old-file detection
+    PopulateFileAccess { paths: Vec<String> },
+    /// Walk directories into the file index so the next scan of them is incremental
+    IndexDirSizes { roots: Vec<String> },
+}
+
+impl Job {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Job::RollUpDigests => "roll_up_digests",
+            Job::PopulateFileAccess { .. } => "populate_file_access",
+            Job::IndexDirSizes { .. } => "index_dir_sizes",
+        }
+    }
+
+    /// Lower runs first: reports someone may be waiting for, then data for
+    /// views, then warming the index
+    pub fn priority(&self) -> i64 {
+        match self {
+            Job::RollUpDigests => 0,
+            Job::PopulateFileAccess { .. } => 1,
+            Job::IndexDirSizes { .. } => 2,
+        }
+    }
+}
+
+/// A row of the queue as shown to the UI
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct JobRecord {
+    pub id: i64,
+    pub kind: String,
+    pub state: String, // "queued", "running", "done" or "failed"
+    pub priority: i64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+/// Queue `job` unless an identical one is still waiting; returns the new row's id
+pub fn enqueue(conn: &Connection, job: &Job, now: i64) -> rusqlite::Result<Option<i64>> {
+    let payload = serde_json::to_string(job).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    let waiting: Option<i64> = conn
+        .query_row("SELECT id FROM jobs WHERE state = 'queued' AND payload = ?1", [&payload], |row| row.get(0))
+        .optional()?;
+    if waiting.is_some() {
+        return Ok(None);
+    }
+    conn.execute(
+        "INSERT INTO jobs (kind, payload, priority, state, created_at, run_after) VALUES (?1, ?2, ?3, 'queued', ?4, ?4)",
+        params![job.name(), payload, job.priority(), now],
+    )?;
+    Ok(Some(conn.last_insert_rowid()))
+}
+
+/// Take the most urgent job that is due and mark it running
+pub fn claim_next(conn: &Connection, now: i64) -> rusqlite::Result<Option<(i64, Job)>> {
+    loop {
+        let next: Option<(i64, String)> = conn
+            .query_row(
+                "SELECT id, payload FROM jobs WHERE state = 'queued' AND run_after <= ?1 ORDER BY priority, id LIMIT 1",
+                [now],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((id, payload)) = next else {
+            return Ok(None);
+        };
+        match serde_json::from_str::<Job>(&payload) {
+            Ok(job) => {
+                conn.execute("UPDATE jobs SET state = 'running', attempts = attempts + 1 WHERE id = ?1", [id])?;
+                return Ok(Some((id, job)));
+            }
+            // Queued by a version that had other kinds of jobs
+            Err(e) => finish(conn, id, Err(format!("Unknown job: {}", e)), now, MAX_ATTEMPTS)?,
+        }
+    }
+}
+
+/// Record how a claimed job went; failures are queued again until `max_attempts`
+pub fn finish(conn: &Connection, id: i64, outcome: Result<(), String>, now: i64, max_attempts: u32) -> rusqlite::Result<()> {
+    match outcome {
+        Ok(()) => conn.execute("UPDATE jobs SET state = 'done', last_error = NULL, finished_at = ?2 WHERE id = ?1", params![id, now]),
+        Err(error) => {
+            let attempts: u32 = conn.query_row("SELECT attempts FROM jobs WHERE id = ?1", [id], |row| row.get(0))?;
+            if attempts < max_attempts {
+                let run_after = now + RETRY_DELAY_SECS * attempts as i64;
+                conn.execute(
+                    "UPDATE jobs SET state = 'queued', last_error = ?2, run_after = ?3 WHERE id = ?1",
+                    params![id, error, run_after],
+                )
+            } else {
+                conn.execute(
+                    "UPDATE jobs SET state = 'failed', last_error = ?2, finished_at = ?3 WHERE id = ?1",
+                    params![id, error, now],
+                )
+            }
+        }
+    }
+    .map(|_| ())
+}
+
+/// Jobs that were running when the app last stopped are queued again
+pub fn requeue_interrupted(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute("UPDATE jobs SET state = 'queued' WHERE state = 'running'", [])
+}
+
+/// Drop finished jobs older than `before`
+pub fn prune_finished(conn: &Connection, before: i64) -> rusqlite::Result<usize> {
+    conn.execute("DELETE FROM jobs WHERE state IN ('done', 'failed') AND finished_at < ?1", [before])
+}
+
+pub fn list(conn: &Connection, limit: u32) -> rusqlite::Result<Vec<JobRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, state, priority, attempts, last_error, created_at, finished_at
+         FROM jobs ORDER BY id DESC LIMIT ?1",
+    )?;
+    let jobs = stmt
+        .query_map([limit], |row| {
+            Ok(JobRecord {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                state: row.get(2)?,
+                priority: row.get(3)?,
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                created_at: row.get(6)?,
+                finished_at: row.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(jobs)
+}
+
+/// Queue `job` for the worker
+pub fn submit(app_handle: &tauri::AppHandle, job: Job) {
+    let name = job.name();
+    match app_handle.db(|conn| enqueue(conn, &job, chrono::Utc::now().timestamp())) {
+        Ok(Some(id)) => {
+            tracing::debug!("Queued {} job {}", name, id);
+            WAKE.notify_one();
+        }
+        Ok(None) => tracing::debug!("A {} job is already queued", name),
+        Err(e) => tracing::warn!("Failed to queue {} job: {}", name, e),
+    }
+}
+
+async fn execute(app_handle: &tauri::AppHandle, job: Job) -> Result<(), String> {
+    match job {
+        Job::RollUpDigests => app_handle
+            .db(|conn| Ok(digest::roll_up_completed_months(conn)))
+            .map_err(|e| format!("Database error: {}", e))?
+            .map(|_| ()),
+        Job::PopulateFileAccess { paths } => {
+            let handle = app_handle.clone();
+            tokio::task::spawn_blocking(move || crate::commands::populate_file_access_table(&handle, &paths))
+                .await
+                .map_err(|e| format!("Task failed: {}", e))?
+        }
+        Job::IndexDirSizes { roots } => {
+            let index = crate::commands::load_file_index(app_handle);
+            let handle = app_handle.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut index = index.lock().unwrap_or_else(|e| e.into_inner());
+                for root in roots.iter().map(Path::new).filter(|r| r.is_dir()) {
+                    index.dir_size(root);
+                }
+                tracing::info!("Indexed directory sizes: {} entries reused, {} examined", index.reused, index.examined);
+                handle.db(|conn| index.save(conn)).map_err(|e| format!("Failed to save file index: {}", e))
+            })
+            .await
+            .map_err(|e| format!("Task failed: {}", e))?
+        }
+    }
+}
+
+/// Run queued jobs one at a time for the life of the process
+pub fn start_worker(app_handle: tauri::AppHandle) {
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    STARTED.call_once(|| {
+        tauri::async_runtime::spawn(async move {
+            let now = chrono::Utc::now().timestamp();
+            if let Err(e) = app_handle.db(|conn| {
+                let requeued = requeue_interrupted(conn)?;
+                if requeued > 0 {
+                    tracing::info!("Resuming {} interrupted jobs", requeued);
+                }
+                prune_finished(conn, now - FINISHED_RETENTION_DAYS * 24 * 3600)
+            }) {
+                tracing::warn!("Failed to prepare the job queue: {}", e);
+            }
+
+            loop {
+                if STOPPING.load(Ordering::SeqCst) {
+                    tracing::info!("Job worker stopped");
+                    break;
+                }
+                // Jobs warm caches and fill tables; hold them while the app is over its memory limit
+                if footprint::under_pressure() {
+                    tokio::time::sleep(Duration::from_secs(IDLE_POLL_SECS)).await;
+                    continue;
+                }
+                let claimed = app_handle.db(|conn| claim_next(conn, chrono::Utc::now().timestamp()));
+                let (id, job) = match claimed {
+                    Ok(Some(claimed)) => claimed,
+                    Ok(None) => {
+                        let _ = tokio::time::timeout(Duration::from_secs(IDLE_POLL_SECS), WAKE.notified()).await;
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to read the job queue: {}", e);
+                        tokio::time::sleep(Duration::from_secs(IDLE_POLL_SECS)).await;
+                        continue;
+                    }
+                };
+
+                // Shutdown waits for the job and its outcome to be written
+                let _write = crate::shutdown::pending_write();
+                let name = job.name();
+                let outcome = execute(&app_handle, job).await;
+                if let Err(e) = &outcome {
+                    tracing::warn!("Job {} ({}) failed: {}", id, name, e);
+                }
+                if let Err(e) = app_handle.db(|conn| finish(conn, id, outcome, chrono::Utc::now().timestamp(), MAX_ATTEMPTS)) {
+                    tracing::warn!("Failed to record the outcome of job {}: {}", id, e);
+                }
+            }
+        });
+    });
+}
+
+/// Let the worker finish the job it is running and take no more
+pub fn stop_worker() {
+    STOPPING.store(true, Ordering::SeqCst);
+    WAKE.notify_one();
+}
+
+/// Recent jobs, newest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_jobs(app_handle: tauri::AppHandle, limit: Option<u32>) -> Result<Vec<JobRecord>, String> {
+    app_handle
+        .db(|conn| list(conn, limit.unwrap_or(50)))
+        .map_err(|e| format!("Failed to load jobs: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE jobs (id INTEGER PRIMARY KEY AUTOINCREMENT, kind TEXT NOT NULL, payload TEXT NOT NULL,
+             priority INTEGER NOT NULL, state TEXT NOT NULL, attempts INTEGER NOT NULL DEFAULT 0, last_error TEXT,
+             created_at INTEGER NOT NULL, run_after INTEGER NOT NULL, finished_at INTEGER);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_jobs_run_by_priority_and_retry() {
+        let conn = queue_db();
+        let index = Job::IndexDirSizes { roots: vec!["/home/u/.cache".to_string()] };
+        assert!(enqueue(&conn, &index, 100).unwrap().is_some());
+        assert!(enqueue(&conn, &Job::RollUpDigests, 100).unwrap().is_some());
+        // Already waiting
+        assert_eq!(enqueue(&conn, &Job::RollUpDigests, 101).unwrap(), None);
+
+        let (digests, job) = claim_next(&conn, 100).unwrap().unwrap();
+        assert_eq!(job, Job::RollUpDigests);
+        finish(&conn, digests, Err("database is locked".to_string()), 100, 2).unwrap();
+
+        // The retry waits, so the index job goes next
+        let (indexing, job) = claim_next(&conn, 100).unwrap().unwrap();
+        assert_eq!(job, index);
+        assert_eq!(claim_next(&conn, 100).unwrap(), None);
+
+        // A restart while it ran queues it again
+        assert_eq!(requeue_interrupted(&conn).unwrap(), 1);
+        assert_eq!(claim_next(&conn, 100).unwrap().map(|(id, _)| id), Some(indexing));
+        finish(&conn, indexing, Ok(()), 110, 2).unwrap();
+
+        let (retry, _) = claim_next(&conn, 100 + RETRY_DELAY_SECS).unwrap().unwrap();
+        assert_eq!(retry, digests);
+        finish(&conn, retry, Err("database is locked".to_string()), 120, 2).unwrap();
+
+        let jobs = list(&conn, 10).unwrap();
+        let states: Vec<(&str, &str, u32)> = jobs.iter().map(|j| (j.kind.as_str(), j.state.as_str(), j.attempts)).collect();
+        assert_eq!(states, vec![("roll_up_digests", "failed", 2), ("index_dir_sizes", "done", 2)]);
+        assert_eq!(jobs[0].last_error.as_deref(), Some("database is locked"));
+
+        assert_eq!(prune_finished(&conn, 115).unwrap(), 1);
+        assert_eq!(list(&conn, 10).unwrap().len(), 1);
+    }
+}
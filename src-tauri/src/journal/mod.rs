@@ -0,0 +1,242 @@
+//! The systemd journal. `clear_logs` only covers log folders in the home
+//! directory, while journald's own files under /var/log/journal regularly grow to
+//! several GB. They are reported from `journalctl --disk-usage` and shrunk with
+//! `journalctl --vacuum-size/--vacuum-time` as root, never deleted by hand, so
+//! journald's index of the remaining files stays valid.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::commands::{CleanResult, JournalSettings};
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::privileged::{self, PrivilegedOp, PrivilegedOutput};
+use crate::scanner::builtin::new_item;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Logs";
+pub const JOURNAL_DIR: &str = "/var/log/journal";
+
+/// Bytes in a journalctl size such as `1.2G`, `512.0M` or `8.0K` (powers of 1024)
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim().trim_end_matches('.');
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Total from `journalctl --disk-usage`:
+/// "Archived and active journals take up 1.2G in the file system."
+pub fn parse_disk_usage(output: &str) -> Option<u64> {
+    let (_, rest) = output.split_once("take up")?;
+    rest.split_whitespace().next().and_then(parse_size)
+}
+
+/// `journalctl` arguments vacuuming down to `settings`; limits set to 0 are left out
+pub fn vacuum_args(settings: &JournalSettings) -> Vec<String> {
+    let mut args = Vec::new();
+    if settings.max_size_mb > 0 {
+        args.push(format!("--vacuum-size={}M", settings.max_size_mb));
+    }
+    if settings.max_age_days > 0 {
+        args.push(format!("--vacuum-time={}d", settings.max_age_days));
+    }
+    args
+}
+
+/// Bytes a vacuum to `settings` frees at least; age-based vacuuming can free more
+pub fn reclaimable(usage: u64, settings: &JournalSettings) -> u64 {
+    if settings.max_size_mb == 0 {
+        return 0;
+    }
+    usage.saturating_sub(settings.max_size_mb as u64 * 1024 * 1024)
+}
+
+pub fn journalctl_available() -> bool {
+    crate::snapshots::tool_available("journalctl")
+}
+
+/// Size of the journals the current user can read: all of them for members of
+/// `systemd-journal` or `adm`, otherwise only the user's own
+pub fn disk_usage() -> Result<u64, String> {
+    let output = Command::new("journalctl")
+        .arg("--disk-usage")
+        .env("LANG", "C")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run journalctl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("journalctl --disk-usage failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    parse_disk_usage(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "journalctl did not report a disk usage".to_string())
+}
+
+pub struct JournalScanner;
+
+impl Scanner for JournalScanner {
+    fn id(&self) -> &'static str {
+        "journal"
+    }
+
+    fn name(&self) -> &'static str {
+        "systemd journal"
+    }
+
+    fn description(&self) -> &'static str {
+        "journald logs above the configured size"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        let defaults = JournalSettings::default();
+        vec![
+            ScannerOptionSchema {
+                key: "max_size_mb".to_string(),
+                label: "Keep at most (MB)".to_string(),
+                value_type: "number".to_string(),
+                default_value: defaults.max_size_mb.to_string(),
+            },
+            ScannerOptionSchema {
+                key: "max_age_days".to_string(),
+                label: "Keep entries for (days)".to_string(),
+                value_type: "number".to_string(),
+                default_value: defaults.max_age_days.to_string(),
+            },
+        ]
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_logs && journalctl_available()
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        if !journalctl_available() {
+            return Ok(Vec::new());
+        }
+        ctx.progress(0, "Measuring the systemd journal...");
+        let defaults = JournalSettings::default();
+        let settings = JournalSettings {
+            max_size_mb: ctx.option("max_size_mb", defaults.max_size_mb),
+            max_age_days: ctx.option("max_age_days", defaults.max_age_days),
+        };
+        let usage = disk_usage().map_err(|e| ScannerError::IoError(std::io::Error::other(e)))?;
+        let size = reclaimable(usage, &settings);
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(vec![new_item(
+            "systemd journal".to_string(),
+            Path::new(JOURNAL_DIR),
+            size,
+            "journal",
+            CATEGORY,
+            0,
+            &format!(
+                "journald keeps {} of logs; vacuuming to {} MB removes the oldest entries",
+                crate::scanner::format_bytes(usage),
+                settings.max_size_mb
+            ),
+        )])
+    }
+}
+
+fn run_vacuum(app_handle: &tauri::AppHandle, settings: &JournalSettings) -> Result<PrivilegedOutput, String> {
+    let op = PrivilegedOp::JournalVacuum { max_size_mb: settings.max_size_mb, max_age_days: settings.max_age_days };
+    if let Some(output) = privileged::run_in_session(app_handle, op)? {
+        return Ok(output);
+    }
+    let output = Command::new("pkexec")
+        .arg("journalctl")
+        .args(vacuum_args(settings))
+        .output()
+        .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+    // pkexec exits with 126 when the user dismisses the authentication dialog
+    if output.status.code() == Some(126) {
+        return Err("Authentication was cancelled".to_string());
+    }
+    Ok(PrivilegedOutput::from(output))
+}
+
+/// Vacuum the systemd journal down to a size and/or age (0 leaves a limit out)
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn vacuum_journal(
+    app_handle: tauri::AppHandle,
+    max_size_mb: u32,
+    max_age_days: u32,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::VacuumJournal { max_size_mb, max_age_days }, &confirmation_token)?;
+
+    let settings = JournalSettings { max_size_mb, max_age_days };
+    if vacuum_args(&settings).is_empty() {
+        return Err("Set a size or age limit to vacuum the journal to".to_string());
+    }
+
+    let handle = app_handle.clone();
+    let (output, freed) = tokio::task::spawn_blocking(move || {
+        let before = disk_usage().unwrap_or(0);
+        let output = run_vacuum(&handle, &settings)?;
+        let after = disk_usage().unwrap_or(before);
+        Ok::<_, String>((output, before.saturating_sub(after)))
+    })
+    .await
+    .map_err(|e| format!("Journal task failed: {}", e))??;
+
+    if !output.success {
+        return Err(format!("journalctl vacuum failed: {}", output.stderr.trim()));
+    }
+    tracing::info!("Vacuumed the systemd journal, {} bytes freed", freed);
+
+    if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, freed)) {
+        tracing::warn!("Failed to record cleanup history: {}", e);
+    }
+
+    Ok(CleanResult {
+        cleaned: 1,
+        failed: 0,
+        total_size: freed,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_disk_usage() {
+        assert_eq!(
+            parse_disk_usage("Archived and active journals take up 1.5G in the file system.\n"),
+            Some(1_610_612_736)
+        );
+        assert_eq!(parse_disk_usage("Journals take up 8.0M on disk.\n"), Some(8 * 1024 * 1024));
+        assert_eq!(parse_size("512B"), Some(512));
+        assert_eq!(parse_size("3.9T"), Some((3.9 * (1u64 << 40) as f64) as u64));
+        assert_eq!(parse_size("12Q"), None);
+        assert_eq!(parse_disk_usage("No journal files were found.\n"), None);
+    }
+
+    #[test]
+    fn test_vacuum_targets() {
+        let settings = JournalSettings { max_size_mb: 500, max_age_days: 30 };
+        assert_eq!(vacuum_args(&settings), vec!["--vacuum-size=500M", "--vacuum-time=30d"]);
+        assert_eq!(reclaimable(2 << 30, &settings), (2 << 30) - 500 * 1024 * 1024);
+        assert_eq!(reclaimable(100, &settings), 0);
+
+        let age_only = JournalSettings { max_size_mb: 0, max_age_days: 7 };
+        assert_eq!(vacuum_args(&age_only), vec!["--vacuum-time=7d"]);
+        assert_eq!(reclaimable(2 << 30, &age_only), 0);
+    }
+}
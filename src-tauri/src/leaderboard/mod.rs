@@ -0,0 +1,332 @@
+//! Persistent "largest files" leaderboard backed by the file index (`file_access`).
+//!
+//! Users can pin files to keep them at the top of the board, or ignore them
+//! forever. Ignored paths are stored in `ignored_files` and, with the rules of
+//! the `ignores` module, removed from the results of later scans.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::db::DbAccess;
+use crate::ignores::{self, IgnoreSet};
+use crate::scanner::{ScanItem, ScanResults, StorageRecoveryResults};
+
+pub const DEFAULT_LEADERBOARD_SIZE: usize = 100;
+/// Category name of ignore rules that apply to the board only
+pub const CATEGORY: &str = "Largest Files";
+const MAX_LEADERBOARD_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct LargestFile {
+    pub rank: usize,
+    pub path: String,
+    pub name: String,
+    pub parent: String, // containing folder, for "open containing folder"
+    pub size: u64,
+    pub last_access: i64,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct IgnoredFile {
+    pub path: String,
+    pub ignored_at: i64,
+}
+
+fn to_entry(path: String, size: i64, last_access: i64, pinned: bool) -> LargestFile {
+    let p = Path::new(&path);
+    LargestFile {
+        rank: 0,
+        name: p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.clone()),
+        parent: p.parent().map(|d| d.to_string_lossy().to_string()).unwrap_or_default(),
+        path,
+        size: size.max(0) as u64,
+        last_access,
+        pinned,
+    }
+}
+
+/// Leaderboard rows straight from the index: pinned files first, then the
+/// largest non-ignored files. Files are not checked for existence here.
+pub fn query_leaderboard(conn: &Connection, limit: usize, offset: usize) -> rusqlite::Result<Vec<LargestFile>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.path, f.size, f.last_access, p.path IS NOT NULL
+         FROM file_access f
+         LEFT JOIN pinned_files p ON p.path = f.path
+         WHERE f.path NOT IN (SELECT path FROM ignored_files)
+         ORDER BY (p.path IS NOT NULL) DESC, f.size DESC
+         LIMIT ?1 OFFSET ?2",
+    )?;
+    let rows = stmt
+        .query_map(params![limit as i64, offset as i64], |row| {
+            Ok(to_entry(row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+pub fn ignored_paths(conn: &Connection) -> rusqlite::Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT path FROM ignored_files")?;
+    let paths = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<HashSet<_>>>()?;
+    Ok(paths)
+}
+
+/// Drop ignored files from general scan results
+pub fn filter_scan_results(app_handle: &tauri::AppHandle, results: &mut ScanResults) {
+    let ignored = ignores::load_active(app_handle);
+    if ignored.is_empty() {
+        return;
+    }
+    ignored.retain_items(&mut results.items);
+    results.shared_items.retain(|s| !ignored.ignores(&s.path, &s.category));
+    crate::scanner::dedup::recount(results);
+}
+
+/// Drop ignored files from storage recovery results. Duplicate groups left
+/// with a single file are no longer duplicates and are removed.
+pub fn filter_storage_recovery(app_handle: &tauri::AppHandle, results: &mut StorageRecoveryResults) {
+    let ignored = ignores::load_active(app_handle);
+    if ignored.is_empty() {
+        return;
+    }
+    apply_ignores(&ignored, results);
+}
+
+fn apply_ignores(ignored: &IgnoreSet, results: &mut StorageRecoveryResults) {
+    let keep = |i: &ScanItem| !ignored.ignores(&i.path, &i.category);
+    results.large_files.retain(keep);
+    results.old_downloads.retain(keep);
+    results.old_snap_revisions.retain(keep);
+    results.stale_snapshots.retain(keep);
+    for group in results.duplicates.iter_mut() {
+        let file_size = group.files.first().map(|f| f.size).unwrap_or(0);
+        group.files.retain(keep);
+        group.group_size = group.files.len();
+        group.total_size = file_size * group.group_size as u64;
+    }
+    results.duplicates.retain(|g| g.group_size > 1);
+    for group in results.similar_images.iter_mut() {
+        group.files.retain(keep);
+        group.recoverable_size = group.files.iter().skip(1).map(|f| f.size).sum();
+    }
+    results.similar_images.retain(|g| g.files.len() > 1);
+
+    results.total_duplicate_size = results.duplicates.iter().map(|g| g.total_size).sum();
+    results.total_large_files_size = results.large_files.iter().map(|i| i.size).sum();
+    results.total_old_downloads_size = results.old_downloads.iter().map(|i| i.size).sum();
+    results.total_stale_snapshots_size = results.stale_snapshots.iter().map(|i| i.size).sum();
+    results.total_recoverable_size = (results.total_duplicate_size
+        + results.total_large_files_size
+        + results.total_old_downloads_size
+        + results.total_snap_revisions_size
+        + results.total_stale_snapshots_size)
+        .saturating_sub(results.snapshot_held_size);
+}
+
+fn validate_path(path: &str) -> Result<(), String> {
+    if path.is_empty() || !Path::new(path).is_absolute() {
+        return Err(format!("Expected an absolute path, got '{}'", path));
+    }
+    Ok(())
+}
+
+/// Top `limit` largest indexed files (default 100). Index rows for files that
+/// no longer exist are pruned on the way.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_largest_files(app_handle: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<LargestFile>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LEADERBOARD_SIZE).clamp(1, MAX_LEADERBOARD_SIZE);
+
+    tokio::task::spawn_blocking(move || {
+        // The query leaves out ignored_files; folder, glob and category rules apply here
+        let ignored = ignores::load_active(&app_handle);
+        let mut board = Vec::with_capacity(limit);
+        let mut offset = 0;
+        loop {
+            let page = app_handle
+                .db(|conn| query_leaderboard(conn, limit, offset))
+                .map_err(|e| format!("Failed to read file index: {}", e))?;
+            let fetched = page.len();
+
+            let (present, missing): (Vec<_>, Vec<_>) = page.into_iter().partition(|f| Path::new(&f.path).exists());
+            if !missing.is_empty() {
+                let _ = app_handle.db(|conn| {
+                    for f in &missing {
+                        conn.execute("DELETE FROM file_access WHERE path = ?1", [&f.path])?;
+                    }
+                    Ok(())
+                });
+            }
+            // Pruned rows shift the following rows up
+            offset += fetched - missing.len();
+            board.extend(present.into_iter().filter(|f| !ignored.ignores(&f.path, CATEGORY)).take(limit - board.len()));
+
+            if board.len() >= limit || fetched < limit {
+                break;
+            }
+        }
+
+        for (i, entry) in board.iter_mut().enumerate() {
+            entry.rank = i + 1;
+        }
+        Ok(board)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Never show `path` in the leaderboard or in scan results again
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn ignore_file(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
+    validate_path(&path)?;
+    app_handle
+        .db(|conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO ignored_files (path, ignored_at) VALUES (?1, ?2)",
+                params![path, chrono::Utc::now().timestamp()],
+            )?;
+            conn.execute("DELETE FROM pinned_files WHERE path = ?1", [&path])?;
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to ignore file: {}", e))?;
+    tracing::info!("Ignoring {} in future scans", path);
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn unignore_file(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
+    app_handle
+        .db(|conn| conn.execute("DELETE FROM ignored_files WHERE path = ?1", [&path]))
+        .map_err(|e| format!("Failed to remove ignore: {}", e))?;
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_ignored_files(app_handle: tauri::AppHandle) -> Result<Vec<IgnoredFile>, String> {
+    app_handle
+        .db(|conn| {
+            let mut stmt = conn.prepare("SELECT path, ignored_at FROM ignored_files ORDER BY ignored_at DESC")?;
+            let rows = stmt.query_map([], |row| Ok(IgnoredFile { path: row.get(0)?, ignored_at: row.get(1)? }))?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("Failed to list ignored files: {}", e))
+}
+
+/// Pin or unpin a file at the top of the leaderboard
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn set_file_pinned(app_handle: tauri::AppHandle, path: String, pinned: bool) -> Result<(), String> {
+    validate_path(&path)?;
+    app_handle
+        .db(|conn| {
+            if pinned {
+                conn.execute(
+                    "INSERT OR IGNORE INTO pinned_files (path, pinned_at) VALUES (?1, ?2)",
+                    params![path, chrono::Utc::now().timestamp()],
+                )?;
+            } else {
+                conn.execute("DELETE FROM pinned_files WHERE path = ?1", [&path])?;
+            }
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to update pin: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE file_access (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                last_access INTEGER NOT NULL
+            );
+            CREATE TABLE ignored_files (path TEXT PRIMARY KEY, ignored_at INTEGER NOT NULL);
+            CREATE TABLE pinned_files (path TEXT PRIMARY KEY, pinned_at INTEGER NOT NULL);
+            INSERT INTO file_access (path, size, last_access) VALUES
+                ('/home/u/a.iso', 500, 0),
+                ('/home/u/b.mkv', 300, 0),
+                ('/home/u/c.zip', 100, 0),
+                ('/home/u/d.log', 50, 0);
+            INSERT INTO ignored_files VALUES ('/home/u/a.iso', 1);
+            INSERT INTO pinned_files VALUES ('/home/u/c.zip', 1);
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_leaderboard_order_pins_and_ignores() {
+        let conn = setup();
+        let board = query_leaderboard(&conn, 10, 0).unwrap();
+        let paths: Vec<&str> = board.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["/home/u/c.zip", "/home/u/b.mkv", "/home/u/d.log"]);
+        assert!(board[0].pinned);
+        assert_eq!(board[1].parent, "/home/u");
+        assert_eq!(board[1].name, "b.mkv");
+    }
+
+    #[test]
+    fn test_ignores_applied_to_storage_recovery() {
+        let item = |path: &str| ScanItem {
+            id: path.to_string(),
+            name: path.to_string(),
+            path: path.to_string(),
+            size: 10,
+            apparent_size: None,
+            item_type: "file".to_string(),
+            category: "duplicate".to_string(),
+            risk_level: 2,
+            description: String::new(),
+            children: None,
+            dependencies: None,
+            dependents: None,
+        };
+        let mut results = StorageRecoveryResults {
+            duplicates: vec![crate::scanner::DuplicateGroup {
+                id: "g".to_string(),
+                files: vec![item("/a"), item("/b")],
+                total_size: 20,
+                group_size: 2,
+                verified: false,
+                suggested_keeper: None,
+                preselected: Vec::new(),
+            }],
+            large_files: vec![item("/big"), item("/a")],
+            old_downloads: vec![],
+            total_duplicate_size: 20,
+            total_large_files_size: 20,
+            total_old_downloads_size: 0,
+            old_snap_revisions: vec![],
+            total_snap_revisions_size: 0,
+            similar_images: vec![],
+            stale_snapshots: vec![],
+            total_stale_snapshots_size: 0,
+            snapshot_held_size: 0,
+            total_recoverable_size: 40,
+            cancelled: false,
+        };
+
+        let ignored = IgnoreSet::from_paths(["/a".to_string()]);
+        apply_ignores(&ignored, &mut results);
+        assert!(results.duplicates.is_empty());
+        assert_eq!(results.large_files.len(), 1);
+        assert_eq!(results.total_recoverable_size, 10);
+    }
+}
@@ -0,0 +1,304 @@
+//! Configuration and data folders of applications that are no longer installed.
+//! Each folder directly in `~/.config` and `~/.local/share` is matched by name
+//! against everything installed: distribution packages, Flatpaks, snaps,
+//! desktop entries, programs on `PATH` and folders in /opt. Matching is loose
+//! on purpose (`BraveSoftware` counts as Brave's), so a folder is only offered
+//! when nothing resembles it and it hasn't changed in months. Opt-in, as names
+//! alone can't prove a folder is unused.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Orphaned App Data";
+
+/// Folders changed more recently than this are assumed to be in use
+pub const DEFAULT_MIN_IDLE_DAYS: u64 = 90;
+
+/// Entries looked at per folder to find its latest change
+const MAX_ENTRIES_CHECKED: usize = 5_000;
+
+const MAX_ITEMS: usize = 100;
+
+/// Folders shared by the desktop and its libraries rather than owned by one
+/// package, or named differently from anything installed
+const SHARED_DIRS: &[&str] = &[
+    "applications", "autostart", "backgrounds", "dconf", "desktop-directories", "environment.d", "fontconfig", "fonts",
+    "gnome-session", "gtk-2.0", "gtk-3.0", "gtk-4.0", "gvfs-metadata", "icons", "keyrings", "kwalletd", "menus",
+    "mime", "nautilus", "pulse", "recently-used", "session", "sounds", "systemd", "themes", "trash", "user-dirs",
+    "xorg", "flatpak", "containers", "applications-merged", "kxmlgui5", "plasma-workspace", "linux-cleaner", "pulito",
+];
+
+const DESKTOP_ENTRY_DIRS: &[&str] = &[
+    "/usr/share/applications",
+    "/usr/local/share/applications",
+    "/var/lib/flatpak/exports/share/applications",
+    "/var/lib/snapd/desktop/applications",
+];
+
+/// Lowercase letters and digits only, so `Google-Chrome` and `google_chrome` compare equal
+pub fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+/// Whether two normalized names likely belong to the same application: one
+/// starts with the other, or they share a prefix long enough to be a vendor
+fn related(a: &str, b: &str) -> bool {
+    let common = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    let shorter = a.len().min(b.len());
+    (common == shorter && shorter >= 3) || common >= 5
+}
+
+/// Names from a desktop entry: its program, window class and name
+pub fn desktop_entry_names(content: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for line in content.lines() {
+        if let Some(exec) = line.strip_prefix("Exec=") {
+            let program = exec.split_whitespace().find(|word| !word.contains('=') && *word != "env");
+            if let Some(program) = program {
+                names.push(Path::new(program.trim_matches('"')).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+            }
+        } else if let Some(class) = line.strip_prefix("StartupWMClass=") {
+            names.push(class.to_string());
+        } else if let Some(name) = line.strip_prefix("Name=") {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Lines of a command's output, None when it isn't available or fails
+fn command_lines(program: &str, args: &[&str]) -> Option<Vec<String>> {
+    let output = Command::new(program).args(args).output().ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+}
+
+fn dir_names(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Normalized names of everything installed
+#[derive(Debug, Default)]
+pub struct Installed {
+    names: HashSet<String>,
+    /// A package manager answered; without one every folder would look orphaned
+    pub has_packages: bool,
+}
+
+impl Installed {
+    pub fn add(&mut self, name: &str) {
+        // Reverse-DNS ids (org.gnome.Calculator) are also known without their leading parts
+        for (dot, _) in name.match_indices('.') {
+            self.add_normalized(&name[dot + 1..]);
+        }
+        self.add_normalized(name);
+    }
+
+    fn add_normalized(&mut self, name: &str) {
+        let name = normalize(name);
+        if name.len() >= 2 {
+            self.names.insert(name);
+        }
+    }
+
+    pub fn detect(home: &Path) -> Installed {
+        let mut installed = Installed::default();
+
+        let packages = [
+            ("dpkg-query", &["-W", "-f", "${Package}\n"][..]),
+            ("rpm", &["-qa", "--qf", "%{NAME}\n"][..]),
+            ("pacman", &["-Qq"][..]),
+        ];
+        for (program, args) in packages {
+            if let Some(names) = command_lines(program, args) {
+                installed.has_packages |= !names.is_empty();
+                names.iter().for_each(|n| installed.add(n));
+            }
+        }
+
+        let flatpak_roots = [home.join(".local/share/flatpak/app"), PathBuf::from("/var/lib/flatpak/app")];
+        for root in flatpak_roots.iter().map(PathBuf::as_path).chain([Path::new("/snap"), Path::new("/opt")]) {
+            dir_names(root).iter().for_each(|n| installed.add(n));
+        }
+
+        let user_entries = [home.join(".local/share/applications"), home.join(".local/share/flatpak/exports/share/applications")];
+        for dir in DESKTOP_ENTRY_DIRS.iter().map(PathBuf::from).chain(user_entries) {
+            for name in dir_names(&dir).into_iter().filter(|n| n.ends_with(".desktop")) {
+                installed.add(name.trim_end_matches(".desktop"));
+                if let Ok(content) = std::fs::read_to_string(dir.join(&name)) {
+                    desktop_entry_names(&content).iter().for_each(|n| installed.add(n));
+                }
+            }
+        }
+
+        for dir in std::env::var_os("PATH").map(|p| std::env::split_paths(&p).collect::<Vec<_>>()).unwrap_or_default() {
+            dir_names(&dir).iter().for_each(|n| installed.add(n));
+        }
+        installed
+    }
+
+    /// Whether something installed could own a folder named `dir_name`
+    pub fn owns(&self, dir_name: &str) -> bool {
+        let dir = normalize(dir_name);
+        dir.len() < 3 || self.names.contains(&dir) || self.names.iter().any(|name| related(name, &dir))
+    }
+}
+
+fn is_shared(dir_name: &str) -> bool {
+    let name = normalize(dir_name);
+    SHARED_DIRS.iter().any(|shared| normalize(shared) == name)
+}
+
+/// Folders directly in `roots` that nothing installed seems to own
+pub fn orphaned_dirs(roots: &[PathBuf], installed: &Installed) -> Vec<PathBuf> {
+    let mut orphaned = Vec::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root) else { continue };
+        for entry in entries.filter_map(|e| e.ok()) {
+            // Symlinked folders usually come from a dotfiles manager
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !is_shared(&name) && !installed.owns(&name) {
+                orphaned.push(entry.path());
+            }
+        }
+    }
+    orphaned.sort();
+    orphaned
+}
+
+/// When anything in `dir` last changed, looking at no more than `MAX_ENTRIES_CHECKED` entries
+pub fn last_change(dir: &Path) -> Option<SystemTime> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .take(MAX_ENTRIES_CHECKED)
+        .filter_map(|e| e.metadata().ok()?.modified().ok())
+        .max()
+}
+
+pub struct OrphanedConfigScanner;
+
+impl Scanner for OrphanedConfigScanner {
+    fn id(&self) -> &'static str {
+        "orphaned_configs"
+    }
+
+    fn name(&self) -> &'static str {
+        "Orphaned app data"
+    }
+
+    fn description(&self) -> &'static str {
+        "Folders in ~/.config and ~/.local/share of applications that no longer seem to be installed"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        vec![ScannerOptionSchema {
+            key: "min_idle_days".to_string(),
+            label: "Only folders unchanged for (days)".to_string(),
+            value_type: "number".to_string(),
+            default_value: DEFAULT_MIN_IDLE_DAYS.to_string(),
+        }]
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let min_idle_days = ctx.option("min_idle_days", DEFAULT_MIN_IDLE_DAYS);
+        let now = SystemTime::now();
+
+        ctx.progress(0, "Listing installed applications...");
+        let installed = Installed::detect(&home);
+        if !installed.has_packages {
+            tracing::info!("No package manager answered; not guessing which app folders are orphaned");
+            return Ok(Vec::new());
+        }
+
+        let candidates = orphaned_dirs(&[home.join(".config"), home.join(".local/share")], &installed);
+        let mut items = Vec::new();
+        for (index, dir) in candidates.iter().enumerate() {
+            ctx.check_cancelled()?;
+            ctx.progress((10 + index * 90 / candidates.len().max(1)) as u8, &format!("Checking {}...", dir.display()));
+            if !ctx.allows(dir) {
+                continue;
+            }
+            let idle_days = last_change(dir)
+                .and_then(|changed| now.duration_since(changed).ok())
+                .map_or(0, |idle| idle.as_secs() / (24 * 3600));
+            if idle_days < min_idle_days {
+                continue;
+            }
+            let parent = dir.parent().and_then(|p| p.strip_prefix(&home).ok()).map(|p| p.display().to_string()).unwrap_or_default();
+            items.push(new_item(
+                dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                dir,
+                ctx.dir_size(dir),
+                "directory",
+                CATEGORY,
+                2,
+                &format!("No installed application matches this ~/{} folder; unchanged for {} days", parent, idle_days),
+            ));
+        }
+
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        items.truncate(MAX_ITEMS);
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn installed(names: &[&str]) -> Installed {
+        let mut installed = Installed { has_packages: true, ..Installed::default() };
+        names.iter().for_each(|n| installed.add(n));
+        installed
+    }
+
+    #[test]
+    fn test_owns() {
+        let installed = installed(&["google-chrome-stable", "brave-browser", "vlc", "org.gnome.Calculator", "code"]);
+        assert!(installed.owns("google-chrome"));
+        assert!(installed.owns("BraveSoftware"));
+        assert!(installed.owns("VLC"));
+        assert!(installed.owns("gnome-calculator"));
+        assert!(installed.owns("Code"));
+        // Too short to tell
+        assert!(installed.owns("ab"));
+        assert!(!installed.owns("Slack"));
+        assert!(!installed.owns("spotify"));
+    }
+
+    #[test]
+    fn test_desktop_entry_names() {
+        let entry = "[Desktop Entry]\nName=Visual Studio Code\nExec=env BAMF=1 /usr/share/code/code --unity-launch %F\nStartupWMClass=Code\nIcon=vscode\n";
+        assert_eq!(desktop_entry_names(entry), vec!["Visual Studio Code", "code", "Code"]);
+    }
+
+    #[test]
+    fn test_orphaned_dirs() {
+        let home = tempfile::TempDir::new().unwrap();
+        let config = home.path().join(".config");
+        for dir in ["Slack", "vlc", "gtk-3.0", "Trash"] {
+            fs::create_dir_all(config.join(dir)).unwrap();
+        }
+        fs::write(config.join("stray.conf"), b"").unwrap();
+        std::os::unix::fs::symlink(config.join("vlc"), config.join("linked-app")).unwrap();
+
+        let found = orphaned_dirs(&[config.clone(), home.path().join("missing")], &installed(&["vlc"]));
+        assert_eq!(found, vec![config.join("Slack")]);
+        assert!(last_change(&config.join("Slack")).is_some());
+    }
+}
@@ -0,0 +1,265 @@
+//! Folders managed by an application's own library: Zotero storage, Calibre
+//! libraries, photo catalogs, note vaults. The app's database references the
+//! files inside them, so removing single files corrupts the library; cleanups
+//! skip them unless the whole library is explicitly opted in, and scan results
+//! list each library as one item.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::ScanItem;
+
+enum Marker {
+    File(&'static str),
+    Dir(&'static str),
+    Extension(&'static str),
+}
+
+/// Application and the markers that must all be present in a library's root
+const LIBRARY_MARKERS: &[(&str, &[Marker])] = &[
+    ("Zotero", &[Marker::File("zotero.sqlite")]),
+    ("Calibre", &[Marker::File("metadata.db"), Marker::File("metadata_db_prefs_backup.json")]),
+    ("digiKam", &[Marker::File("digikam4.db")]),
+    ("darktable", &[Marker::File("library.db"), Marker::File("data.db")]),
+    ("Lightroom", &[Marker::Extension("lrcat")]),
+    ("Obsidian", &[Marker::Dir(".obsidian")]),
+    ("Logseq", &[Marker::Dir("logseq"), Marker::Dir("pages")]),
+];
+
+/// Files listed in a library's breakdown
+const BREAKDOWN_FILES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ManagedLibrary {
+    pub app: String,
+    pub root: String,
+    pub file_count: usize, // old files found inside it
+    pub total_size: u64,
+    #[serde(default)]
+    pub largest_files: Vec<LibraryFile>, // up to BREAKDOWN_FILES, largest first
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct LibraryFile {
+    pub path: String,
+    pub size: u64,
+}
+
+fn has_marker(dir: &Path, marker: &Marker) -> bool {
+    match marker {
+        Marker::File(name) => dir.join(name).is_file(),
+        Marker::Dir(name) => dir.join(name).is_dir(),
+        Marker::Extension(ext) => std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .any(|e| e.path().extension().is_some_and(|x| x == *ext))
+            })
+            .unwrap_or(false),
+    }
+}
+
+/// The application whose library `dir` is the root of
+pub fn detect_library(dir: &Path) -> Option<&'static str> {
+    LIBRARY_MARKERS
+        .iter()
+        .find(|(_, markers)| markers.iter().all(|m| has_marker(dir, m)))
+        .map(|(app, _)| *app)
+}
+
+/// Finds the library a path belongs to, remembering which directories were checked
+pub struct LibraryDetector {
+    stop_at: PathBuf,
+    checked: HashMap<PathBuf, Option<&'static str>>,
+}
+
+impl LibraryDetector {
+    /// Directories from `stop_at` upwards are never treated as libraries
+    pub fn new(stop_at: &Path) -> Self {
+        Self { stop_at: stop_at.to_path_buf(), checked: HashMap::new() }
+    }
+
+    /// Application and root of the library containing `path`, if any
+    pub fn library_for(&mut self, path: &Path) -> Option<(&'static str, PathBuf)> {
+        // Outermost match wins, so a library nested in another counts as part of it
+        let mut found = None;
+        for dir in path.ancestors().skip(1) {
+            if dir == self.stop_at || !dir.starts_with(&self.stop_at) {
+                break;
+            }
+            let app = *self.checked.entry(dir.to_path_buf()).or_insert_with(|| detect_library(dir));
+            if let Some(app) = app {
+                found = Some((app, dir.to_path_buf()));
+            }
+        }
+        found
+    }
+}
+
+/// Split (path, size) pairs into loose files and the libraries the rest belong to,
+/// largest library first
+pub fn partition(files: Vec<(String, u64)>, detector: &mut LibraryDetector) -> (Vec<(String, u64)>, Vec<ManagedLibrary>) {
+    let mut loose = Vec::new();
+    let mut libraries: HashMap<PathBuf, ManagedLibrary> = HashMap::new();
+    for (path, size) in files {
+        match detector.library_for(Path::new(&path)) {
+            Some((app, root)) => {
+                let library = libraries.entry(root.clone()).or_insert_with(|| ManagedLibrary {
+                    app: app.to_string(),
+                    root: root.to_string_lossy().to_string(),
+                    file_count: 0,
+                    total_size: 0,
+                    largest_files: Vec::new(),
+                });
+                library.file_count += 1;
+                library.total_size += size;
+                library.largest_files.push(LibraryFile { path, size });
+                if library.largest_files.len() > BREAKDOWN_FILES * 2 {
+                    keep_largest(&mut library.largest_files);
+                }
+            }
+            None => loose.push((path, size)),
+        }
+    }
+    let mut libraries: Vec<ManagedLibrary> = libraries.into_values().collect();
+    for library in &mut libraries {
+        keep_largest(&mut library.largest_files);
+    }
+    libraries.sort_by(|a, b| b.total_size.cmp(&a.total_size).then_with(|| a.root.cmp(&b.root)));
+    (loose, libraries)
+}
+
+fn keep_largest(files: &mut Vec<LibraryFile>) {
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(BREAKDOWN_FILES);
+}
+
+/// Replace scan items inside an app's library with one item per library whose
+/// children are the original items, so the library is only offered as a whole.
+/// The result is sorted by size, largest first.
+pub fn group_scan_items(items: Vec<ScanItem>, detector: &mut LibraryDetector) -> Vec<ScanItem> {
+    let mut grouped = Vec::new();
+    let mut libraries: HashMap<PathBuf, (&'static str, Vec<ScanItem>)> = HashMap::new();
+    for item in items {
+        match detector.library_for(Path::new(&item.path)) {
+            Some((app, root)) => libraries.entry(root).or_insert_with(|| (app, Vec::new())).1.push(item),
+            None => grouped.push(item),
+        }
+    }
+
+    for (root, (app, mut files)) in libraries {
+        files.sort_by(|a, b| b.size.cmp(&a.size));
+        let category = files[0].category.clone();
+        let mut item = new_item(
+            format!("{} library", app),
+            &root,
+            files.iter().map(|f| f.size).sum(),
+            "library",
+            &category,
+            3,
+            &format!(
+                "{} file{} inside a {} library - manage them in {} or remove the library as a whole",
+                files.len(),
+                if files.len() == 1 { "" } else { "s" },
+                app,
+                app
+            ),
+        );
+        item.children = Some(files);
+        grouped.push(item);
+    }
+
+    grouped.sort_by(|a, b| b.size.cmp(&a.size));
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_partition_groups_library_files() {
+        let home = tempfile::TempDir::new().unwrap();
+        let zotero = home.path().join("Zotero");
+        let calibre = home.path().join("Documents/Calibre Library");
+        fs::create_dir_all(zotero.join("storage/ABCD1234")).unwrap();
+        fs::write(zotero.join("zotero.sqlite"), b"").unwrap();
+        fs::create_dir_all(calibre.join("Author/Book (1)")).unwrap();
+        fs::write(calibre.join("metadata.db"), b"").unwrap();
+        // metadata.db alone is too generic to count as a Calibre library
+        fs::create_dir_all(home.path().join("Documents/other")).unwrap();
+        fs::write(home.path().join("Documents/other/metadata.db"), b"").unwrap();
+        fs::write(calibre.join("metadata_db_prefs_backup.json"), b"").unwrap();
+
+        let path = |p: &Path| p.to_string_lossy().to_string();
+        let files = vec![
+            (path(&zotero.join("storage/ABCD1234/paper.pdf")), 100),
+            (path(&zotero.join("storage/ABCD1234/.zotero-ft-cache")), 5),
+            (path(&calibre.join("Author/Book (1)/book.epub")), 300),
+            (path(&home.path().join("Documents/other/notes.txt")), 7),
+        ];
+
+        let mut detector = LibraryDetector::new(home.path());
+        let (loose, libraries) = partition(files, &mut detector);
+        assert_eq!(loose, vec![(path(&home.path().join("Documents/other/notes.txt")), 7)]);
+        let summary: Vec<(&str, usize, u64)> =
+            libraries.iter().map(|l| (l.app.as_str(), l.file_count, l.total_size)).collect();
+        assert_eq!(summary, vec![("Calibre", 1, 300), ("Zotero", 2, 105)]);
+        assert_eq!(libraries[1].root, path(&zotero));
+        assert_eq!(libraries[1].largest_files[0], LibraryFile { path: path(&zotero.join("storage/ABCD1234/paper.pdf")), size: 100 });
+    }
+
+    #[test]
+    fn test_group_scan_items() {
+        let home = tempfile::TempDir::new().unwrap();
+        let library = home.path().join("Pictures/Catalog");
+        fs::create_dir_all(library.join("2024")).unwrap();
+        fs::write(library.join("Catalog.lrcat"), b"").unwrap();
+
+        let item = |path: PathBuf, size: u64| ScanItem {
+            id: String::new(),
+            name: String::new(),
+            path: path.to_string_lossy().to_string(),
+            size,
+            apparent_size: None,
+            item_type: "file".to_string(),
+            category: "Large Files".to_string(),
+            risk_level: 2,
+            description: String::new(),
+            children: None,
+            dependencies: None,
+            dependents: None,
+        };
+        let items = vec![
+            item(home.path().join("Downloads/a.iso"), 500),
+            item(library.join("2024/b.dng"), 300),
+            item(library.join("2024/c.dng"), 400),
+        ];
+
+        let grouped = group_scan_items(items, &mut LibraryDetector::new(home.path()));
+        assert_eq!(grouped.len(), 2);
+        assert_eq!((grouped[0].item_type.as_str(), grouped[0].size, grouped[0].risk_level), ("library", 700, 3));
+        assert_eq!(grouped[0].path, library.to_string_lossy());
+        let children = grouped[0].children.as_ref().unwrap();
+        assert_eq!(children.iter().map(|c| c.size).collect::<Vec<_>>(), vec![400, 300]);
+        assert_eq!(grouped[1].size, 500);
+    }
+
+    #[test]
+    fn test_detect_by_extension_and_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(detect_library(dir.path()), None);
+        fs::write(dir.path().join("Photos.lrcat"), b"").unwrap();
+        assert_eq!(detect_library(dir.path()), Some("Lightroom"));
+
+        let vault = tempfile::TempDir::new().unwrap();
+        fs::create_dir(vault.path().join(".obsidian")).unwrap();
+        assert_eq!(detect_library(vault.path()), Some("Obsidian"));
+    }
+}
@@ -1,20 +1,76 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+pub mod agent;
+pub mod analysis;
+pub mod antivirus;
+pub mod archive;
+pub mod batch;
+pub mod benchmark;
+pub mod boot;
+pub mod browsers;
 pub mod cache;
+pub mod cache_redirect;
+pub mod coalesce;
 pub mod commands;
+pub mod coredumps;
+pub mod detached;
 pub mod db;
+pub mod digest;
+pub mod disk_health;
+pub mod docker;
+pub mod duplicates;
+pub mod electron;
+pub mod flatpak;
+pub mod footprint;
+pub mod ignores;
+pub mod importers;
+pub mod installers;
+pub mod jobs;
+pub mod journal;
+pub mod leaderboard;
+pub mod leftovers;
+pub mod libraries;
+pub mod maintenance;
+pub mod metrics;
+pub mod monitoring;
+pub mod mounts;
+pub mod notifications;
+pub mod opener;
+pub mod operations;
+pub mod output;
 pub mod packages;
+pub mod path_validation;
+pub mod power;
+pub mod privileged;
+pub mod profiles;
+pub mod projects;
+pub mod read_only;
+pub mod reclaim;
+pub mod recovery;
+pub mod remote;
+pub mod safe_mode;
 pub mod scanner;
+pub mod shortcuts;
+pub mod shutdown;
+pub mod snaps;
+pub mod snapshots;
 pub mod startup;
+pub mod support;
+pub mod thumbnails;
+pub mod timestamps;
 pub mod trash;
+pub mod watchdog;
+pub mod window_state;
+pub mod wine;
+pub mod xdg_trash;
 
 use db::AppState;
 
 use tauri::Manager;
 
 use std::sync::Mutex;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 fn main() {
     // Initialize comprehensive logging with structured formatting
@@ -25,8 +81,10 @@ fn main() {
 
     // Configure log formatting - structured output with consistent formatting
     // In release builds, we keep it simple for readability while maintaining structure
+    // Logs go to stderr, keeping stdout for `--json` output
     #[cfg(debug_assertions)]
     let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
         .with_target(false) // Hide module paths for cleaner output
         .with_thread_ids(true)
         .with_thread_names(true)
@@ -35,17 +93,36 @@ fn main() {
 
     #[cfg(not(debug_assertions))]
     let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
         .with_target(false)
         .with_thread_ids(false)
         .with_thread_names(false)
         .with_file(false)
         .with_line_number(false);
 
+    // Recent lines are also kept in memory for support bundles
+    let support_layer = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_target(false)
+        .with_writer(support::recent_log_writer);
+
+    // Command timing reads Tauri's IPC spans whatever RUST_LOG says, so it gets its own filter
     tracing_subscriber::registry()
-        .with(fmt_layer)
-        .with(filter)
+        .with(fmt_layer.and_then(support_layer).with_filter(filter))
+        .with(metrics::IpcTiming.with_filter(tracing_subscriber::filter::filter_fn(metrics::is_ipc_span)))
         .init();
 
+    // Root helper started by pkexec for a privileged session: no GUI, just the op loop
+    if privileged::is_helper_mode() {
+        std::process::exit(privileged::run_helper());
+    }
+
+    // Reject a malformed `--json` request before starting the app at all
+    if let Err(e) = output::cli_request(std::env::args()) {
+        eprintln!("pulito: {}", e);
+        std::process::exit(2);
+    }
+
     tracing::info!(
         version = env!("CARGO_PKG_VERSION"),
         "🚀 Starting Pulito - System Cleanup Tool"
@@ -69,14 +146,22 @@ fn main() {
         // Register all the types we want to export
         let mut collection = TypeCollection::default();
         collection.register::<commands::SystemStats>();
+        collection.register::<mounts::MountStats>();
         collection.register::<commands::AppSettings>();
         collection.register::<commands::TrashSettings>();
         collection.register::<commands::MonitoringSettings>();
         collection.register::<commands::NotificationSettings>();
         collection.register::<commands::ScanSettings>();
+        collection.register::<commands::ShortcutSettings>();
+        collection.register::<commands::JournalSettings>();
+        collection.register::<projects::ProjectSettings>();
         collection.register::<commands::CacheEvent>();
         collection.register::<commands::DiskPulseHealth>();
         collection.register::<commands::OldFilesSummary>();
+        collection.register::<libraries::ManagedLibrary>();
+        collection.register::<libraries::LibraryFile>();
+        collection.register::<docker::DockerArtifact>();
+        collection.register::<archive::ArchivedFile>();
         collection.register::<commands::CacheItem>();
         collection.register::<commands::SystemHealthData>();
         collection.register::<commands::GpuInfo>();
@@ -87,19 +172,42 @@ fn main() {
         collection.register::<commands::ProcessInfo>();
         collection.register::<commands::LoadAverage>();
         collection.register::<commands::TreeNode>();
+        collection.register::<scanner::treemap::UsageNode>();
         collection.register::<commands::CleanResult>();
         collection.register::<commands::CacheAnalytics>();
         collection.register::<commands::CacheContributor>();
         collection.register::<commands::CacheGrowthPoint>();
         collection.register::<scanner::ScanItem>();
         collection.register::<scanner::ScanResults>();
+        collection.register::<scanner::dedup::SharedItem>();
+        collection.register::<scanner::identity::ItemDecision>();
+        collection.register::<scanner::identity::ItemDecisionRecord>();
+        collection.register::<scanner::reclaimable::ReclaimableSource>();
+        collection.register::<scanner::similar::SimilarImageGroup>();
         collection.register::<scanner::ScanOptions>();
         collection.register::<scanner::FilesystemHealthResults>();
         collection.register::<scanner::StorageRecoveryResults>();
         collection.register::<scanner::DuplicateGroup>();
+        collection.register::<scanner::DuplicateRoot>();
+        collection.register::<scanner::HashMode>();
+        collection.register::<scanner::throttle::ThroughputProfile>();
+        collection.register::<scanner::throttle::ThrottleSettings>();
+        collection.register::<scanner::throttle::ThroughputStatus>();
+        collection.register::<power::PowerSource>();
+        collection.register::<footprint::FootprintSettings>();
+        collection.register::<footprint::MemorySample>();
+        collection.register::<footprint::MemoryFootprint>();
+        collection.register::<jobs::JobRecord>();
+        collection.register::<metrics::CommandMetrics>();
+        collection.register::<metrics::PerformanceMetrics>();
         collection.register::<trash::TrashItem>();
         collection.register::<trash::TrashMetadata>();
         collection.register::<trash::TrashData>();
+        collection.register::<trash::TrashCategoryStats>();
+        collection.register::<trash::TrashRiskStats>();
+        collection.register::<trash::TrashExpiryDigest>();
+        collection.register::<trash::TrashPayload>();
+        collection.register::<trash::compact::TrashCompactionReport>();
         collection.register::<commands::QuickCleanResult>();
         collection.register::<commands::SchedulingSettings>();
         collection.register::<commands::ScheduleStatus>();
@@ -107,6 +215,91 @@ fn main() {
         collection.register::<commands::PreviewItem>();
         collection.register::<startup::StartupProgram>();
         collection.register::<startup::StartupProgramsList>();
+        collection.register::<digest::StorageDigest>();
+        collection.register::<digest::DigestFile>();
+        collection.register::<benchmark::DiskBenchmarkResult>();
+        collection.register::<disk_health::DiskErrorEvent>();
+        collection.register::<disk_health::SsdDeviceHealth>();
+        collection.register::<disk_health::TrimStatus>();
+        collection.register::<disk_health::SsdHealthReport>();
+        collection.register::<cache_redirect::CacheRedirectSuggestion>();
+        collection.register::<agent::AgentStatus>();
+        collection.register::<agent::ipc::AgentLiveState>();
+        collection.register::<agent::ipc::AgentEvent>();
+        collection.register::<remote::RemoteHost>();
+        collection.register::<remote::RemoteFilesystem>();
+        collection.register::<remote::RemoteDirUsage>();
+        collection.register::<remote::RemoteDiskSummary>();
+        collection.register::<scanner::registry::ScannerInfo>();
+        collection.register::<scanner::registry::ScannerOptionSchema>();
+        collection.register::<scanner::custom::CustomLocation>();
+        collection.register::<scanner::custom::CustomLocationsStatus>();
+        collection.register::<scanner::plugins::AnalyzerPluginInfo>();
+        collection.register::<operations::OperationPlan>();
+        collection.register::<operations::OperationItem>();
+        collection.register::<operations::OperationDescription>();
+        collection.register::<operations::guard::OperationAuditEntry>();
+        collection.register::<privileged::PrivilegedOp>();
+        collection.register::<privileged::PrivilegedSessionStatus>();
+        collection.register::<snapshots::SnapshotSupport>();
+        collection.register::<snapshots::CleanupSnapshot>();
+        collection.register::<recovery::OpenHandle>();
+        collection.register::<recovery::PendingRelease>();
+        collection.register::<recovery::HeldFile>();
+        collection.register::<recovery::RecoveryTool>();
+        collection.register::<recovery::RecoveryReport>();
+        collection.register::<trash::TrashConsistency>();
+        collection.register::<maintenance::MaintenanceReport>();
+        collection.register::<analysis::AgeBucket>();
+        collection.register::<analysis::AgeCell>();
+        collection.register::<analysis::AgeHeatmapRow>();
+        collection.register::<analysis::AgeHeatmap>();
+        collection.register::<leaderboard::LargestFile>();
+        collection.register::<leaderboard::IgnoredFile>();
+        collection.register::<duplicates::KeepStrategy>();
+        collection.register::<duplicates::DuplicateExportFormat>();
+        collection.register::<duplicates::DuplicateExport>();
+        collection.register::<duplicates::trees::TreeOverlap>();
+        collection.register::<ignores::IgnoreRule>();
+        collection.register::<reclaim::ReclaimRecord>();
+        collection.register::<reclaim::ReclaimedSpace>();
+        collection.register::<safe_mode::SafeModeSettings>();
+        collection.register::<read_only::ReadOnlyStatus>();
+        collection.register::<profiles::SettingsProfile>();
+        collection.register::<batch::BatchStepReport>();
+        collection.register::<batch::BatchReport>();
+        collection.register::<output::ResultKind>();
+        collection.register::<output::JsonExport>();
+        collection.register::<timestamps::TimeContext>();
+        collection.register::<timestamps::LocalizedTimestamp>();
+        collection.register::<support::SupportBundleFile>();
+        collection.register::<support::SupportBundlePreview>();
+        collection.register::<support::SupportBundle>();
+        collection.register::<watchdog::HealthIssue>();
+        collection.register::<watchdog::BackendHealth>();
+        collection.register::<snapshots::existing::ExistingSnapshot>();
+        collection.register::<importers::ImportFormat>();
+        collection.register::<importers::ImportedResults>();
+        collection.register::<antivirus::MalwareDetection>();
+        collection.register::<antivirus::AntivirusReport>();
+        collection.register::<startup::persistence::SuspiciousProcess>();
+        collection.register::<startup::units::UserUnitHealth>();
+        collection.register::<startup::units::UnitAction>();
+        collection.register::<boot::BootTimes>();
+        collection.register::<boot::UnitTiming>();
+        collection.register::<boot::CriticalChainEntry>();
+        collection.register::<boot::BootAnalysis>();
+        collection.register::<boot::BootHistoryEntry>();
+        collection.register::<boot::impact::BootWindow>();
+        collection.register::<boot::impact::UnitImpact>();
+        collection.register::<boot::impact::StartupImpact>();
+        collection.register::<detached::DetachedView>();
+        collection.register::<notifications::NotificationKind>();
+        collection.register::<notifications::AppNotification>();
+        collection.register::<notifications::PendingNotification>();
+        collection.register::<shortcuts::ShortcutAction>();
+        collection.register::<shortcuts::ShortcutStatus>();
+        collection.register::<window_state::WindowState>();
         let types = collection;
 
         match Typescript::default()
@@ -133,9 +326,18 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| shortcuts::handle(app, shortcut, event))
+                .build(),
+        )
         .manage(AppState {
             db: Mutex::new(None),
         })
+        .manage(privileged::PrivilegedSessionState::default())
+        .manage(coalesce::InFlight::default())
+        .manage(monitoring::MonitoringHandle::new())
         .setup(|app| {
             tracing::debug!("Running application setup...");
 
@@ -150,6 +352,48 @@ fn main() {
                 }
             }
 
+            // Enforce safe and read-only mode before any command can run
+            let settings = commands::load_settings(&app_handle);
+            safe_mode::apply(&settings.safe_mode);
+            read_only::init_from_launch();
+            read_only::apply(settings.read_only);
+
+            // `--json`: compute one result, write it out and exit
+            if output::is_cli_mode() {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.destroy();
+                }
+                tauri::async_runtime::spawn(output::run_cli(app_handle.clone()));
+                return Ok(());
+            }
+
+            // Recreate tmpfs targets for redirected caches (wiped on reboot)
+            cache_redirect::restore_redirect_targets(&app_handle);
+
+            // Run background jobs, including those queued before the last exit
+            jobs::start_worker(app_handle.clone());
+
+            // Watch the app's own memory use and shed load above the limit
+            footprint::start_sampler(app_handle.clone());
+
+            // Detect a stuck database query or scan, report it through `ping` and reset it
+            watchdog::start();
+
+            // Stop the same way on SIGTERM (systemctl stop) or Ctrl+C as on quitting
+            shutdown::handle_signals(&app_handle);
+
+            // Headless agent: no window or tray, just background monitoring
+            if agent::is_agent_mode() {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.destroy();
+                }
+                tauri::async_runtime::spawn(agent::run(app_handle.clone()));
+                return Ok(());
+            }
+
+            // Reopen the main window where it was left
+            window_state::restore(&app_handle);
+
             // Set up system tray
             #[cfg(desktop)]
             {
@@ -169,23 +413,11 @@ fn main() {
                         match event {
                             tauri::tray::TrayIconEvent::Click { .. } => {
                                 tracing::info!("Tray icon clicked - toggling main window");
-                                if let Some(window) = app_handle_for_tray.get_webview_window("main") {
-                                    if let Ok(visible) = window.is_visible() {
-                                        if visible {
-                                            let _ = window.hide();
-                                        } else {
-                                            let _ = window.show();
-                                            let _ = window.set_focus();
-                                        }
-                                    }
-                                }
+                                detached::toggle_main(&app_handle_for_tray);
                             }
                             tauri::tray::TrayIconEvent::DoubleClick { .. } => {
                                 tracing::info!("Tray icon double-clicked - showing main window");
-                                if let Some(window) = app_handle_for_tray.get_webview_window("main") {
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                }
+                                detached::show_main(&app_handle_for_tray);
                             }
                             _ => {}
                         }
@@ -196,48 +428,182 @@ fn main() {
                 app.manage(tray);
             }
 
+            // Register global shortcuts saved in settings
+            tauri::async_runtime::spawn(shortcuts::restore(app_handle.clone()));
+
+            // Deliver notifications held back during Do-Not-Disturb
+            notifications::start_pending_delivery(app_handle.clone());
+
             // TypeScript types are generated earlier in main() function
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            detached::handle_window_event(window, event);
+            window_state::handle_window_event(window, event);
+        })
         .invoke_handler(tauri::generate_handler![
             commands::initialize_app,
-            commands::get_system_stats,
-            commands::get_system_health,
-            commands::scan_filesystem_tree,
-            commands::start_scan,
-            commands::scan_filesystem_health,
-            commands::scan_storage_recovery,
-            commands::scan_for_old_files,
-            commands::get_cache_analytics,
-            commands::clean_items,
-            commands::clear_cache,
-            commands::clean_packages,
-            commands::clear_logs,
-            commands::quick_clean_safe,
-            commands::get_cleanup_preview,
-            commands::get_trash_items,
-            commands::restore_from_trash,
-            commands::delete_from_trash,
-            commands::empty_trash,
-            commands::get_settings,
-            commands::save_settings,
-            commands::get_schedule_settings,
-            commands::save_schedule_settings,
-            commands::get_schedule_status,
+            commands::health::get_system_stats,
+            commands::health::get_system_health,
+            commands::scan::scan_filesystem_tree,
+            commands::scan::get_disk_usage_treemap,
+            commands::scan::start_scan,
+            commands::scan::scan_filesystem_health,
+            commands::scan::scan_storage_recovery,
+            commands::scan::scan_duplicates,
+            commands::scan::cancel_scan,
+            commands::scan::scan_for_old_files,
+            commands::diskpulse::get_cache_analytics,
+            commands::cleanup::clean_items,
+            commands::cleanup::clear_cache,
+            commands::cleanup::clean_packages,
+            commands::cleanup::clear_logs,
+            commands::cleanup::quick_clean_safe,
+            docker::clean_docker_artifacts,
+            flatpak::uninstall_flatpak_refs,
+            snaps::remove_disabled_snaps,
+            journal::vacuum_journal,
+            coredumps::remove_coredumps,
+            packages::kernels::remove_old_kernels,
+            archive::offload_files,
+            archive::find_archived,
+            commands::cleanup::get_cleanup_preview,
+            commands::trash::get_trash_items,
+            commands::trash::restore_from_trash,
+            commands::trash::delete_from_trash,
+            commands::trash::empty_trash,
+            commands::trash::get_trash_expiry_digest,
+            commands::trash::keep_trash_items_longer,
+            commands::trash::extend_trash_retention,
+            commands::trash::pin_trash_item,
+            commands::trash::unpin_trash_item,
+            trash::compact::compact_trash_now,
+            trash::compact::get_last_trash_compaction,
+            commands::settings::get_settings,
+            commands::settings::save_settings,
+            commands::settings::get_schedule_settings,
+            commands::settings::save_schedule_settings,
+            commands::settings::get_schedule_status,
             startup::get_startup_programs,
             startup::toggle_startup_program,
             // DiskPulse commands
-            commands::start_diskpulse_monitoring,
-            commands::stop_diskpulse_monitoring,
-            commands::get_diskpulse_health,
-            commands::get_old_files_summary,
-            commands::get_recent_cache_events,
-            commands::get_cache_items,
-            commands::clear_cache_item,
-            commands::cleanup_old_files,
-            commands::update_tray_icon,
+            commands::diskpulse::start_diskpulse_monitoring,
+            commands::diskpulse::stop_diskpulse_monitoring,
+            commands::diskpulse::get_diskpulse_health,
+            commands::diskpulse::get_old_files_summary,
+            commands::diskpulse::get_recent_cache_events,
+            commands::diskpulse::get_cache_items,
+            commands::diskpulse::clear_cache_item,
+            commands::diskpulse::cleanup_old_files,
+            commands::tray::update_tray_icon,
+            digest::get_storage_digest,
+            digest::list_storage_digests,
+            benchmark::run_disk_benchmark,
+            benchmark::get_benchmark_history,
+            disk_health::get_ssd_health,
+            disk_health::enable_periodic_trim,
+            cache_redirect::get_cache_redirect_suggestions,
+            cache_redirect::enable_cache_redirect,
+            cache_redirect::disable_cache_redirect,
+            agent::get_agent_status,
+            agent::install_agent,
+            agent::uninstall_agent,
+            agent::ipc::get_agent_live_state,
+            agent::ipc::trigger_agent_sample,
+            agent::ipc::subscribe_agent_events,
+            agent::ipc::ping_agent,
+            remote::list_remote_hosts,
+            remote::add_remote_host,
+            remote::remove_remote_host,
+            remote::get_remote_disk_summary,
+            scanner::registry::list_scanners,
+            scanner::dedup::reconcile_scan_results,
+            scanner::identity::set_item_decision,
+            scanner::identity::list_item_decisions,
+            jobs::list_jobs,
+            metrics::get_performance_metrics,
+            footprint::get_memory_footprint,
+            scanner::throttle::get_throughput_status,
+            scanner::custom::get_custom_scan_locations,
+            scanner::plugins::list_analyzer_plugins,
+            operations::describe_operation,
+            operations::guard::get_operation_audit_log,
+            privileged::start_privileged_session,
+            privileged::get_privileged_session,
+            privileged::end_privileged_session,
+            snapshots::get_snapshot_support,
+            snapshots::create_cleanup_snapshot,
+            snapshots::list_cleanup_snapshots,
+            recovery::attempt_recovery,
+            recovery::get_pending_release,
+            recovery::terminate_holder,
+            maintenance::run_maintenance,
+            maintenance::get_last_maintenance_report,
+            analysis::get_age_heatmap,
+            leaderboard::get_largest_files,
+            leaderboard::ignore_file,
+            leaderboard::unignore_file,
+            leaderboard::list_ignored_files,
+            leaderboard::set_file_pinned,
+            opener::open_path,
+            opener::reveal_in_file_manager,
+            duplicates::export_duplicate_decisions,
+            duplicates::trees::scan_duplicate_trees,
+            ignores::add_ignore_rule,
+            ignores::list_ignore_rules,
+            ignores::remove_ignore_rule,
+            reclaim::get_reclaimed_space,
+            safe_mode::acknowledge_risky_item,
+            read_only::get_read_only_status,
+            profiles::list_settings_profiles,
+            profiles::create_settings_profile,
+            profiles::switch_profile,
+            profiles::delete_settings_profile,
+            batch::run_batch,
+            output::export_result_json,
+            timestamps::get_time_context,
+            timestamps::localize_timestamps,
+            support::preview_support_bundle,
+            support::create_support_bundle,
+            watchdog::ping,
+            snapshots::existing::list_filesystem_snapshots,
+            snapshots::existing::delete_filesystem_snapshots,
+            xdg_trash::empty_system_trash,
+            importers::import_external_results,
+            antivirus::scan_with_clamav,
+            startup::get_suspicious_processes,
+            startup::units::get_user_unit_health,
+            startup::units::manage_user_unit,
+            boot::get_boot_analysis,
+            boot::get_boot_history,
+            boot::impact::get_startup_impact,
+            detached::open_detached_window,
+            detached::close_detached_window,
+            detached::get_detached_windows,
+            detached::set_window_events,
+            notifications::get_notifications,
+            notifications::get_unread_notification_count,
+            notifications::mark_notifications_read,
+            notifications::clear_notifications,
+            notifications::get_pending_notifications,
+            notifications::clear_pending_notifications,
+            shortcuts::get_shortcut_status,
+            shortcuts::apply_shortcuts,
+            window_state::get_window_state,
+            window_state::set_last_view,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::ExitRequested { api, code, .. } = event {
+                // The agent has no windows; keep it alive until systemd stops it
+                if code.is_none() && agent::is_agent_mode() {
+                    api.prevent_exit();
+                } else if !shutdown::allow_exit(app_handle, code) {
+                    // Exits again once background work has stopped
+                    api.prevent_exit();
+                }
+            }
+        });
 }
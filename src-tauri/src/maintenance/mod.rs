@@ -0,0 +1,276 @@
+//! Integrity check and self-repair of Pulito's own storage.
+//!
+//! Runs `PRAGMA integrity_check`, rebuilds indexes, reconciles trash metadata
+//! with the trash directory and prunes `file_access` rows for files that are
+//! gone. Triggered manually or monthly from the DiskPulse monitoring loop.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+
+use crate::db::DbAccess;
+use crate::read_only;
+use crate::trash;
+
+/// Automatic maintenance runs at most this often
+const MAINTENANCE_INTERVAL_SECS: i64 = 30 * 24 * 3600;
+
+/// monitoring_state key holding the last report
+const LAST_RUN_KEY: &str = "maintenance_last_run";
+
+/// Rows deleted per statement when pruning file_access
+const PRUNE_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct MaintenanceReport {
+    pub ran_at: i64,
+    pub integrity_ok: bool,
+    pub integrity_messages: Vec<String>, // from integrity_check, empty when ok
+    pub indexes_rebuilt: bool,
+    pub repaired: bool, // integrity problems were fixed by the rebuild
+    pub orphaned_file_access_pruned: usize,
+    pub trash: trash::TrashConsistency,
+    pub issues: Vec<String>,
+    pub duration_ms: u64,
+}
+
+/// Problems reported by `PRAGMA integrity_check`; empty when the database is ok
+pub fn integrity_check(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(messages.into_iter().filter(|m| m != "ok").collect())
+}
+
+pub fn rebuild_indexes(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch("REINDEX; PRAGMA optimize;")
+}
+
+pub fn file_access_paths(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT path FROM file_access")?;
+    let paths = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(paths)
+}
+
+pub fn delete_file_access_rows(conn: &Connection, paths: &[String]) -> rusqlite::Result<usize> {
+    let mut deleted = 0;
+    for chunk in paths.chunks(PRUNE_BATCH_SIZE) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        deleted += conn.execute(
+            &format!("DELETE FROM file_access WHERE path IN ({})", placeholders),
+            rusqlite::params_from_iter(chunk),
+        )?;
+    }
+    Ok(deleted)
+}
+
+fn run_blocking(app_handle: &tauri::AppHandle) -> Result<MaintenanceReport, String> {
+    let start = std::time::Instant::now();
+    let mut issues = Vec::new();
+
+    let integrity_messages = app_handle
+        .db(integrity_check)
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+    let integrity_ok = integrity_messages.is_empty();
+
+    let indexes_rebuilt = match app_handle.db(rebuild_indexes) {
+        Ok(()) => true,
+        Err(e) => {
+            issues.push(format!("Failed to rebuild indexes: {}", e));
+            false
+        }
+    };
+
+    // Index corruption is the common case and REINDEX fixes it; check again
+    let repaired = !integrity_ok
+        && indexes_rebuilt
+        && app_handle.db(integrity_check).map(|m| m.is_empty()).unwrap_or(false);
+    if !integrity_ok && !repaired {
+        issues.push(format!(
+            "Database integrity check reported {} problem(s) that could not be repaired",
+            integrity_messages.len()
+        ));
+    }
+
+    // Existence checks happen outside the database lock
+    let orphaned_file_access_pruned = match app_handle.db(file_access_paths) {
+        Ok(paths) => {
+            let orphaned: Vec<String> = paths.into_iter().filter(|p| !Path::new(p).exists()).collect();
+            match app_handle.db(|conn| delete_file_access_rows(conn, &orphaned)) {
+                Ok(deleted) => deleted,
+                Err(e) => {
+                    issues.push(format!("Failed to prune file_access: {}", e));
+                    0
+                }
+            }
+        }
+        Err(e) => {
+            issues.push(format!("Failed to read file_access: {}", e));
+            0
+        }
+    };
+
+    let trash = match trash::repair_metadata() {
+        Ok(report) => report,
+        Err(e) => {
+            issues.push(e);
+            trash::TrashConsistency::default()
+        }
+    };
+    if trash.metadata_unreadable {
+        issues.push("Trash metadata is unreadable; trashed items cannot be listed or restored".to_string());
+    }
+    if !trash.untracked_files.is_empty() {
+        issues.push(format!("{} file(s) in the trash directory have no metadata", trash.untracked_files.len()));
+    }
+
+    Ok(MaintenanceReport {
+        ran_at: chrono::Utc::now().timestamp(),
+        integrity_ok,
+        integrity_messages,
+        indexes_rebuilt,
+        repaired,
+        orphaned_file_access_pruned,
+        trash,
+        issues,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+fn save_report(app_handle: &tauri::AppHandle, report: &MaintenanceReport) {
+    let Ok(json) = serde_json::to_string(report) else { return };
+    if let Err(e) = app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO monitoring_state (key, value, updated_at) VALUES (?1, ?2, ?3)",
+            params![LAST_RUN_KEY, json, report.ran_at],
+        )?;
+        Ok(())
+    }) {
+        tracing::warn!("Failed to store maintenance report: {}", e);
+    }
+}
+
+fn last_run_at(app_handle: &tauri::AppHandle) -> Option<i64> {
+    app_handle
+        .db(|conn| {
+            conn.query_row(
+                "SELECT updated_at FROM monitoring_state WHERE key = ?1",
+                [LAST_RUN_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+        .ok()
+        .flatten()
+}
+
+async fn run(app_handle: tauri::AppHandle) -> Result<MaintenanceReport, String> {
+    let handle = app_handle.clone();
+    let report = tokio::task::spawn_blocking(move || run_blocking(&handle))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+
+    tracing::info!(
+        "Maintenance finished: integrity_ok={}, repaired={}, {} file_access rows pruned, {} trash entries removed, {} issue(s)",
+        report.integrity_ok,
+        report.repaired,
+        report.orphaned_file_access_pruned,
+        report.trash.removed_entries,
+        report.issues.len()
+    );
+    save_report(&app_handle, &report);
+    Ok(report)
+}
+
+/// Run maintenance if the last run is older than a month (or never happened)
+pub async fn run_if_due(app_handle: &tauri::AppHandle) {
+    // It repairs the trash, so waits for read-only mode to end
+    if read_only::is_enabled() {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    if last_run_at(app_handle).is_some_and(|last| now - last < MAINTENANCE_INTERVAL_SECS) {
+        return;
+    }
+    tracing::info!("Running scheduled storage maintenance");
+    if let Err(e) = run(app_handle.clone()).await {
+        tracing::error!("Scheduled maintenance failed: {}", e);
+    }
+}
+
+/// Check and repair Pulito's own database and trash
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn run_maintenance(app_handle: tauri::AppHandle) -> Result<MaintenanceReport, String> {
+    read_only::check("Storage maintenance")?;
+    run(app_handle).await
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_last_maintenance_report(app_handle: tauri::AppHandle) -> Result<Option<MaintenanceReport>, String> {
+    let json: Option<String> = app_handle
+        .db(|conn| {
+            conn.query_row(
+                "SELECT value FROM monitoring_state WHERE key = ?1",
+                [LAST_RUN_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+        .map_err(|e| format!("Failed to read maintenance report: {}", e))?;
+    Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE file_access (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                last_access INTEGER NOT NULL
+            );
+            CREATE INDEX idx_file_access_last_access ON file_access(last_access);
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_integrity_check_on_healthy_db() {
+        let conn = setup();
+        assert!(integrity_check(&conn).unwrap().is_empty());
+        rebuild_indexes(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_prune_orphaned_file_access() {
+        let conn = setup();
+        for (i, path) in ["/kept", "/gone-a", "/gone-b"].iter().enumerate() {
+            conn.execute(
+                "INSERT INTO file_access (path, size, last_access) VALUES (?1, 1, ?2)",
+                params![path, i as i64],
+            )
+            .unwrap();
+        }
+
+        let orphaned: Vec<String> = file_access_paths(&conn).unwrap().into_iter().filter(|p| p != "/kept").collect();
+        assert_eq!(orphaned.len(), 2);
+        assert_eq!(delete_file_access_rows(&conn, &orphaned).unwrap(), 2);
+
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM file_access", [], |r| r.get(0)).unwrap();
+        assert_eq!(remaining, 1);
+    }
+}
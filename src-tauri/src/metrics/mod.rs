@@ -0,0 +1,339 @@
+//! Per-command timing. [`IpcTiming`] times every IPC command from dispatch to
+//! reply using the spans Tauri opens per request, and [`timed`] covers commands
+//! the backend runs itself (scheduler, shortcuts, batch steps, the agent). The
+//! samples are kept in a fixed-size ring buffer in memory and summarized per
+//! command by `get_performance_metrics`, so slow commands show up without a profiler.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::timestamps;
+
+/// Samples kept; the oldest are dropped first
+const CAPACITY: usize = 2_000;
+
+lazy_static::lazy_static! {
+    static ref SAMPLES: Mutex<VecDeque<CommandSample>> = Mutex::new(VecDeque::with_capacity(CAPACITY));
+}
+
+/// One command call
+#[derive(Debug, Clone)]
+pub struct CommandSample {
+    pub command: String,
+    pub duration_ms: f64,
+    pub success: bool,
+    pub request_bytes: u64,  // serialized arguments, 0 when run by the backend
+    pub response_bytes: u64, // serialized result, 0 on failure
+    pub timestamp: i64,      // UTC unix seconds
+}
+
+/// Summary of the buffered calls of one command
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CommandMetrics {
+    pub command: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub avg_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub avg_request_bytes: u64,
+    pub avg_response_bytes: u64,
+    pub last_called_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct PerformanceMetrics {
+    pub commands: Vec<CommandMetrics>, // slowest p95 first
+    pub sample_count: usize,
+    pub oldest_sample_at: Option<i64>,
+}
+
+/// Add samples to the ring buffer
+pub fn record(samples: impl IntoIterator<Item = CommandSample>) {
+    let mut buffer = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    for sample in samples {
+        if buffer.len() == CAPACITY {
+            buffer.pop_front();
+        }
+        buffer.push_back(sample);
+    }
+}
+
+/// Time a command the backend runs itself rather than one called over IPC, such
+/// as a scheduled or shortcut cleanup; recorded under `command`
+pub async fn timed<T, F>(command: &str, task: F) -> Result<T, String>
+where
+    T: Serialize,
+    F: Future<Output = Result<T, String>>,
+{
+    let started = Instant::now();
+    let result = task.await;
+    record([CommandSample {
+        command: command.to_string(),
+        duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        success: result.is_ok(),
+        request_bytes: 0,
+        response_bytes: result.as_ref().ok().and_then(|value| serde_json::to_vec(value).ok()).map_or(0, |b| b.len() as u64),
+        timestamp: timestamps::now(),
+    }]);
+    result
+}
+
+/// The fields of Tauri's IPC spans the timing reads
+#[derive(Default)]
+struct IpcFields {
+    cmd: Option<String>,
+    payload_bytes: Option<u64>, // `request` or `response`, serialized
+    error: bool,
+}
+
+impl Visit for IpcFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "cmd" => self.cmd = Some(value.to_string()),
+            "request" => self.payload_bytes = Some(value.len() as u64),
+            // The post-message path reports a failure as the `InvokeError`'s Debug form
+            "response" if value.starts_with("InvokeError(") => self.error = true,
+            "response" => self.payload_bytes = Some(value.len() as u64),
+            // The custom-protocol path reports a failure in its own field
+            "error" => self.error = true,
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, _value: &dyn std::fmt::Debug) {
+        if field.name() == "error" {
+            self.error = true;
+        }
+    }
+}
+
+struct RequestBytes(u64);
+
+struct PendingCall {
+    command: String,
+    started: Instant,
+    request_bytes: u64,
+    response_bytes: u64,
+    success: bool,
+}
+
+/// Times IPC commands from the spans of Tauri's `tracing` feature: a request's
+/// `ipc::request::handle` span opens at dispatch and closes once the reply is
+/// sent, and the `ipc::request::response` span below it tells success from
+/// failure. Installed with [`is_ipc_span`] as its filter, independent of RUST_LOG.
+pub struct IpcTiming;
+
+/// Filter for [`IpcTiming`]: only the spans Tauri opens per IPC request
+pub fn is_ipc_span(metadata: &tracing::Metadata<'_>) -> bool {
+    metadata.is_span() && metadata.name().starts_with("ipc::request")
+}
+
+impl<S> Layer<S> for IpcTiming
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = IpcFields::default();
+        attrs.record(&mut fields);
+        match attrs.metadata().name() {
+            "ipc::request" => {
+                if let Some(bytes) = fields.payload_bytes {
+                    span.extensions_mut().insert(RequestBytes(bytes));
+                }
+            }
+            "ipc::request::handle" => {
+                let request_bytes = span.parent().and_then(|p| p.extensions().get::<RequestBytes>().map(|b| b.0)).unwrap_or(0);
+                span.extensions_mut().insert(PendingCall {
+                    command: fields.cmd.unwrap_or_default(),
+                    started: Instant::now(),
+                    request_bytes,
+                    response_bytes: 0,
+                    success: true,
+                });
+            }
+            "ipc::request::response" => {
+                // Opened inside `ipc::request::respond`, a child of the request's handle span
+                if let Some(handle) = span.parent().and_then(|respond| respond.parent()) {
+                    if let Some(call) = handle.extensions_mut().get_mut::<PendingCall>() {
+                        call.success = !fields.error;
+                        call.response_bytes = if fields.error { 0 } else { fields.payload_bytes.unwrap_or(0) };
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        if span.name() != "ipc::request" {
+            return;
+        }
+        let mut fields = IpcFields::default();
+        values.record(&mut fields);
+        if let Some(bytes) = fields.payload_bytes {
+            span.extensions_mut().insert(RequestBytes(bytes));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(call) = span.extensions_mut().remove::<PendingCall>() else { return };
+        record([CommandSample {
+            command: call.command,
+            duration_ms: call.started.elapsed().as_secs_f64() * 1000.0,
+            success: call.success,
+            request_bytes: call.request_bytes,
+            response_bytes: call.response_bytes,
+            timestamp: timestamps::now(),
+        }]);
+    }
+}
+
+/// Drop the older half of the buffer to free memory
+pub fn shrink() {
+    let mut buffer = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    let drop = buffer.len() / 2;
+    buffer.drain(..drop);
+    buffer.shrink_to_fit();
+}
+
+/// Nearest-rank percentile of sorted durations
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn summarize<'a>(samples: impl IntoIterator<Item = &'a CommandSample>) -> Vec<CommandMetrics> {
+    let mut by_command: HashMap<&str, Vec<&CommandSample>> = HashMap::new();
+    for sample in samples {
+        by_command.entry(sample.command.as_str()).or_default().push(sample);
+    }
+
+    let mut commands: Vec<CommandMetrics> = by_command
+        .into_iter()
+        .map(|(command, calls)| {
+            let mut durations: Vec<f64> = calls.iter().map(|s| s.duration_ms).collect();
+            durations.sort_by(|a, b| a.total_cmp(b));
+            let count = calls.len() as u64;
+            CommandMetrics {
+                command: command.to_string(),
+                calls: count,
+                failures: calls.iter().filter(|s| !s.success).count() as u64,
+                avg_ms: durations.iter().sum::<f64>() / count as f64,
+                p95_ms: percentile(&durations, 95.0),
+                max_ms: durations.last().copied().unwrap_or(0.0),
+                avg_request_bytes: calls.iter().map(|s| s.request_bytes).sum::<u64>() / count,
+                avg_response_bytes: calls.iter().map(|s| s.response_bytes).sum::<u64>() / count,
+                last_called_at: calls.iter().map(|s| s.timestamp).max().unwrap_or(0),
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms).then_with(|| a.command.cmp(&b.command)));
+    commands
+}
+
+/// Per-command summary of the buffered samples
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_performance_metrics() -> Result<PerformanceMetrics, String> {
+    let buffer = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(PerformanceMetrics {
+        commands: summarize(buffer.iter()),
+        sample_count: buffer.len(),
+        oldest_sample_at: buffer.front().map(|s| s.timestamp),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(command: &str, duration_ms: f64, success: bool) -> CommandSample {
+        CommandSample {
+            command: command.to_string(),
+            duration_ms,
+            success,
+            request_bytes: 10,
+            response_bytes: if success { 1000 } else { 0 },
+            timestamp: duration_ms as i64,
+        }
+    }
+
+    #[test]
+    fn test_summarize_per_command() {
+        let mut samples: Vec<CommandSample> = (1..=20).map(|i| sample("get_cache_items", i as f64, true)).collect();
+        samples.push(sample("get_system_stats", 30_000.0, false));
+        samples.push(sample("get_system_stats", 200.0, true));
+
+        let metrics = summarize(&samples);
+        assert_eq!(metrics.iter().map(|m| m.command.as_str()).collect::<Vec<_>>(), vec!["get_system_stats", "get_cache_items"]);
+
+        let stats = &metrics[0];
+        assert_eq!((stats.calls, stats.failures, stats.max_ms, stats.p95_ms), (2, 1, 30_000.0, 30_000.0));
+        assert_eq!((stats.avg_request_bytes, stats.avg_response_bytes), (10, 500));
+
+        let cache = &metrics[1];
+        assert_eq!((cache.avg_ms, cache.p95_ms, cache.last_called_at), (10.5, 19.0, 20));
+    }
+
+    #[test]
+    fn test_ipc_spans_are_timed() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let subscriber = tracing_subscriber::registry().with(IpcTiming.with_filter(tracing_subscriber::filter::filter_fn(is_ipc_span)));
+        tracing::subscriber::with_default(subscriber, || {
+            // A failing call on each path: the custom protocol sets `error`, post-message
+            // puts the `InvokeError` in `response`
+            for (cmd, custom_protocol) in [("metrics_test_failing_protocol", true), ("metrics_test_failing_message", false)] {
+                let request = tracing::trace_span!("ipc::request", request = "{\"id\":7}").entered();
+                let handle = tracing::trace_span!("ipc::request::handle", cmd);
+                drop(request);
+                {
+                    let _respond = tracing::trace_span!(parent: &handle, "ipc::request::respond").entered();
+                    let _response = if custom_protocol {
+                        tracing::trace_span!("ipc::request::response", error = "boom").entered()
+                    } else {
+                        tracing::trace_span!("ipc::request::response", response = "InvokeError(String(\"boom\"))").entered()
+                    };
+                }
+                drop(handle);
+            }
+        });
+
+        let buffer = SAMPLES.lock().unwrap();
+        for cmd in ["metrics_test_failing_protocol", "metrics_test_failing_message"] {
+            let sample = buffer.iter().find(|s| s.command == cmd).expect("call recorded");
+            assert!(!sample.success, "{cmd}");
+            assert_eq!((sample.request_bytes, sample.response_bytes), (8, 0));
+            assert!(sample.timestamp < timestamps::MILLIS_THRESHOLD);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed() {
+        let result: Result<Vec<u8>, String> = timed("metrics_test_timed", async { Ok(vec![1, 2]) }).await;
+        assert!(result.is_ok());
+        let buffer = SAMPLES.lock().unwrap();
+        let sample = buffer.iter().find(|s| s.command == "metrics_test_timed").expect("run recorded");
+        assert!(sample.success);
+        assert_eq!(sample.response_bytes, 5); // [1,2]
+    }
+}
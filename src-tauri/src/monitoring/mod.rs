@@ -0,0 +1,154 @@
+//! Handles to the background tasks the app keeps running: DiskPulse disk
+//! sampling with its cache watcher, and the cleanup scheduler. One
+//! [`MonitoringHandle`] is created at startup and kept in managed state, so the
+//! GUI, the headless agent and tests each work on their own instance.
+
+use std::future::Future;
+use std::sync::Arc;
+use tauri::Manager;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::task::JoinHandle;
+
+#[derive(Default)]
+struct DiskPulse {
+    task: Option<JoinHandle<()>>,
+    cache_watcher: Option<notify::RecommendedWatcher>,
+}
+
+#[derive(Default)]
+struct MonitoringState {
+    diskpulse: Option<DiskPulse>,
+    scheduler: Option<JoinHandle<()>>,
+}
+
+/// Cheap to clone; clones share the same tasks
+#[derive(Clone, Default)]
+pub struct MonitoringHandle {
+    state: Arc<AsyncMutex<MonitoringState>>,
+}
+
+impl MonitoringHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The handle in `app_handle`'s managed state
+    pub fn of(app_handle: &tauri::AppHandle) -> Self {
+        app_handle.state::<MonitoringHandle>().inner().clone()
+    }
+
+    pub async fn diskpulse_running(&self) -> bool {
+        self.state.lock().await.diskpulse.is_some()
+    }
+
+    /// Start DiskPulse with what `start` spawns, unless it's already running.
+    /// The state stays locked meanwhile, so concurrent callers start it once.
+    /// False when it was running already.
+    pub async fn start_diskpulse<F, Fut>(&self, start: F) -> Result<bool, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(JoinHandle<()>, notify::RecommendedWatcher), String>>,
+    {
+        let mut state = self.state.lock().await;
+        if state.diskpulse.is_some() {
+            return Ok(false);
+        }
+        let (task, cache_watcher) = start().await?;
+        state.diskpulse = Some(DiskPulse { task: Some(task), cache_watcher: Some(cache_watcher) });
+        Ok(true)
+    }
+
+    /// Abort the sampling task and drop the cache watcher. False when DiskPulse wasn't running.
+    pub async fn stop_diskpulse(&self) -> bool {
+        let Some(mut diskpulse) = self.state.lock().await.diskpulse.take() else {
+            return false;
+        };
+        if let Some(task) = diskpulse.task.take() {
+            task.abort();
+        }
+        diskpulse.cache_watcher = None;
+        true
+    }
+
+    pub async fn scheduler_running(&self) -> bool {
+        self.state.lock().await.scheduler.is_some()
+    }
+
+    /// Run `task` as the scheduler, aborting the one it replaces
+    pub async fn replace_scheduler(&self, task: JoinHandle<()>) {
+        if let Some(previous) = self.state.lock().await.scheduler.replace(task) {
+            previous.abort();
+        }
+    }
+
+    /// False when no scheduler was running
+    pub async fn stop_scheduler(&self) -> bool {
+        match self.state.lock().await.scheduler.take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn forever() -> JoinHandle<()> {
+        tokio::spawn(std::future::pending())
+    }
+
+    fn watcher() -> notify::RecommendedWatcher {
+        notify::recommended_watcher(|_: notify::Result<notify::Event>| {}).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_diskpulse_starts_once() {
+        let monitoring = MonitoringHandle::new();
+        let starts = AtomicUsize::new(0);
+        let counter = &starts;
+        let start = move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok((forever(), watcher()))
+        };
+
+        let other = monitoring.clone();
+        let (a, b) = tokio::join!(monitoring.start_diskpulse(start), other.start_diskpulse(start));
+        assert_eq!(starts.load(Ordering::SeqCst), 1);
+        assert!(a.unwrap() ^ b.unwrap());
+        assert!(monitoring.diskpulse_running().await);
+
+        assert!(monitoring.stop_diskpulse().await);
+        assert!(!monitoring.stop_diskpulse().await);
+        assert!(!monitoring.diskpulse_running().await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_leaves_diskpulse_stopped() {
+        let monitoring = MonitoringHandle::new();
+        let result = monitoring.start_diskpulse(|| async { Err("no inotify".to_string()) }).await;
+        assert_eq!(result, Err("no inotify".to_string()));
+        assert!(!monitoring.diskpulse_running().await);
+    }
+
+    #[tokio::test]
+    async fn test_replacing_scheduler_aborts_previous() {
+        let monitoring = MonitoringHandle::new();
+        let first = forever();
+        let first_abort = first.abort_handle();
+        monitoring.replace_scheduler(first).await;
+        monitoring.replace_scheduler(forever()).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(first_abort.is_finished());
+        assert!(monitoring.scheduler_running().await);
+
+        assert!(monitoring.stop_scheduler().await);
+        assert!(!monitoring.scheduler_running().await);
+    }
+}
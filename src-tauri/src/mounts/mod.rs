@@ -0,0 +1,109 @@
+//! Mounted volumes: per-mount space usage and which mount a path lives on.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use sysinfo::Disks;
+
+/// Virtual and read-only image filesystems that don't hold user data
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "tmpfs", "devtmpfs", "overlay", "squashfs", "proc", "sysfs", "efivarfs", "ramfs", "cgroup2", "fuse.portal",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct MountStats {
+    pub mount_point: String,
+    pub device: String,
+    pub file_system: String,
+    pub total_space: u64,
+    pub used_space: u64,
+    pub available_space: u64,
+    pub is_removable: bool,
+}
+
+/// Drop pseudo filesystems and empty mounts, keep one mount per device (btrfs
+/// subvolumes and bind mounts show up several times), root first
+pub fn filter_mounts(mounts: Vec<MountStats>) -> Vec<MountStats> {
+    let mut kept: Vec<MountStats> = Vec::new();
+    for mount in mounts {
+        if mount.total_space == 0 || PSEUDO_FILESYSTEMS.contains(&mount.file_system.as_str()) {
+            continue;
+        }
+        match kept.iter_mut().find(|m| m.device == mount.device) {
+            // The shortest mount point of a device is the one users recognise
+            Some(existing) if mount.mount_point.len() < existing.mount_point.len() => *existing = mount,
+            Some(_) => {}
+            None => kept.push(mount),
+        }
+    }
+    kept.sort_by(|a, b| (a.mount_point != "/").cmp(&(b.mount_point != "/")).then_with(|| a.mount_point.cmp(&b.mount_point)));
+    kept
+}
+
+/// Every mounted volume that holds data
+pub fn list_mounts() -> Vec<MountStats> {
+    let disks = Disks::new_with_refreshed_list();
+    let mounts = disks
+        .list()
+        .iter()
+        .map(|disk| MountStats {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            device: disk.name().to_string_lossy().to_string(),
+            file_system: disk.file_system().to_string_lossy().to_string(),
+            total_space: disk.total_space(),
+            used_space: disk.total_space().saturating_sub(disk.available_space()),
+            available_space: disk.available_space(),
+            is_removable: disk.is_removable(),
+        })
+        .collect();
+    filter_mounts(mounts)
+}
+
+/// The mount `path` is on: the longest mount point that contains it
+pub fn mount_for_path<'a>(mounts: &'a [MountStats], path: &Path) -> Option<&'a MountStats> {
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(mount_point: &str, device: &str, file_system: &str, total_space: u64) -> MountStats {
+        MountStats {
+            mount_point: mount_point.to_string(),
+            device: device.to_string(),
+            file_system: file_system.to_string(),
+            total_space,
+            used_space: total_space / 2,
+            available_space: total_space / 2,
+            is_removable: false,
+        }
+    }
+
+    #[test]
+    fn test_filter_mounts() {
+        let mounts = filter_mounts(vec![
+            mount("/mnt/media", "/dev/sdb1", "ext4", 2000),
+            mount("/home", "/dev/nvme0n1p2", "btrfs", 1000),
+            mount("/", "/dev/nvme0n1p2", "btrfs", 1000),
+            mount("/tmp", "tmpfs", "tmpfs", 100),
+            mount("/boot/efi", "/dev/nvme0n1p1", "vfat", 500),
+            mount("/snap/core/1", "/dev/loop0", "squashfs", 50),
+        ]);
+        let points: Vec<&str> = mounts.iter().map(|m| m.mount_point.as_str()).collect();
+        assert_eq!(points, vec!["/", "/boot/efi", "/mnt/media"]);
+    }
+
+    #[test]
+    fn test_mount_for_path() {
+        let mounts = vec![mount("/", "a", "ext4", 1), mount("/mnt/media", "b", "ext4", 1)];
+        let on = |p: &str| mount_for_path(&mounts, Path::new(p)).map(|m| m.mount_point.as_str());
+        assert_eq!(on("/mnt/media/films/a.mkv"), Some("/mnt/media"));
+        assert_eq!(on("/mnt/mediaold/a"), Some("/"));
+        assert_eq!(on("/home/user"), Some("/"));
+    }
+}
@@ -0,0 +1,405 @@
+//! Notifications: the in-app notification center and desktop notifications
+//! that respect the desktop's Do-Not-Disturb state.
+//!
+//! Every alert is stored in the `notifications` table whatever the settings say;
+//! the desktop copy is only sent when system notifications are enabled.
+//! DND is read from GNOME (`show-banners`) and from the freedesktop notification
+//! server's `Inhibited` property (KDE Plasma and others). While it is on,
+//! desktop notifications are queued in the database and delivered once it ends,
+//! or dropped when the user chose to suppress them.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::process::{Command, Stdio};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db::DbAccess;
+
+/// How often queued notifications are retried
+const DND_POLL_SECS: u64 = 5 * 60;
+/// Oldest notification center entries beyond this are pruned
+const MAX_STORED: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum NotificationKind {
+    LowDisk,
+    ScanComplete,
+    TrashExpiring,
+    CleanupComplete,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::LowDisk => "low_disk",
+            NotificationKind::ScanComplete => "scan_complete",
+            NotificationKind::TrashExpiring => "trash_expiring",
+            NotificationKind::CleanupComplete => "cleanup_complete",
+        }
+    }
+
+    pub fn from_db(kind: &str) -> Option<Self> {
+        match kind {
+            "low_disk" => Some(NotificationKind::LowDisk),
+            "scan_complete" => Some(NotificationKind::ScanComplete),
+            "trash_expiring" => Some(NotificationKind::TrashExpiring),
+            "cleanup_complete" => Some(NotificationKind::CleanupComplete),
+            _ => None,
+        }
+    }
+}
+
+/// An entry in the in-app notification center
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AppNotification {
+    pub id: i64,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub read: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct PendingNotification {
+    pub id: i64,
+    pub title: String,
+    pub body: String,
+    pub created_at: i64,
+}
+
+/// `gsettings get org.gnome.desktop.notifications show-banners` prints "true" or "false"
+pub fn parse_gsettings_bool(output: &str) -> Option<bool> {
+    match output.trim() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// `gdbus call ... Properties.Get` prints a variant tuple such as "(<true>,)"
+pub fn parse_gdbus_bool(output: &str) -> Option<bool> {
+    let inner = output.trim().strip_prefix("(<")?.strip_suffix(">,)")?;
+    parse_gsettings_bool(inner)
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Whether the desktop currently has Do-Not-Disturb on; false when it can't be determined
+pub fn is_dnd_active() -> bool {
+    let gnome_banners_off = command_output("gsettings", &["get", "org.gnome.desktop.notifications", "show-banners"])
+        .and_then(|o| parse_gsettings_bool(&o))
+        .map(|show| !show);
+    if gnome_banners_off == Some(true) {
+        return true;
+    }
+
+    command_output(
+        "gdbus",
+        &[
+            "call", "--session",
+            "--dest", "org.freedesktop.Notifications",
+            "--object-path", "/org/freedesktop/Notifications",
+            "--method", "org.freedesktop.DBus.Properties.Get",
+            "org.freedesktop.Notifications", "Inhibited",
+        ],
+    )
+    .and_then(|o| parse_gdbus_bool(&o))
+    .unwrap_or(false)
+}
+
+pub fn queue(conn: &Connection, title: &str, body: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO pending_notifications (title, body, created_at) VALUES (?1, ?2, ?3)",
+        params![title, body, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+pub fn list_pending(conn: &Connection) -> rusqlite::Result<Vec<PendingNotification>> {
+    let mut stmt = conn.prepare("SELECT id, title, body, created_at FROM pending_notifications ORDER BY created_at, id")?;
+    let pending = stmt
+        .query_map([], |row| {
+            Ok(PendingNotification { id: row.get(0)?, title: row.get(1)?, body: row.get(2)?, created_at: row.get(3)? })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(pending)
+}
+
+pub fn clear_pending(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute("DELETE FROM pending_notifications", [])
+}
+
+pub fn record(conn: &Connection, kind: NotificationKind, title: &str, body: &str) -> rusqlite::Result<AppNotification> {
+    let created_at = chrono::Utc::now().timestamp();
+    conn.execute(
+        "INSERT INTO notifications (kind, title, body, read, created_at) VALUES (?1, ?2, ?3, 0, ?4)",
+        params![kind.as_str(), title, body, created_at],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "DELETE FROM notifications WHERE id NOT IN (SELECT id FROM notifications ORDER BY created_at DESC, id DESC LIMIT ?1)",
+        [MAX_STORED],
+    )?;
+    Ok(AppNotification { id, kind, title: title.to_string(), body: body.to_string(), read: false, created_at })
+}
+
+/// Newest first; entries with an unknown kind (from a newer version) are skipped
+pub fn list(conn: &Connection, unread_only: bool, limit: u32) -> rusqlite::Result<Vec<AppNotification>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, title, body, read, created_at FROM notifications
+         WHERE (?1 = 0 OR read = 0) ORDER BY created_at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt
+        .query_map(params![unread_only as i64, limit], |row| {
+            let kind: String = row.get(1)?;
+            Ok(match NotificationKind::from_db(&kind) {
+                Some(kind) => Some(AppNotification {
+                    id: row.get(0)?,
+                    kind,
+                    title: row.get(2)?,
+                    body: row.get(3)?,
+                    read: row.get::<_, i64>(4)? != 0,
+                    created_at: row.get(5)?,
+                }),
+                None => None,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+pub fn unread_count(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM notifications WHERE read = 0", [], |row| row.get(0))
+}
+
+/// Mark the given notifications read, or all of them when `ids` is None
+pub fn mark_read(conn: &Connection, ids: Option<&[i64]>) -> rusqlite::Result<usize> {
+    match ids {
+        None => conn.execute("UPDATE notifications SET read = 1 WHERE read = 0", []),
+        Some(ids) => {
+            let mut stmt = conn.prepare("UPDATE notifications SET read = 1 WHERE id = ?1 AND read = 0")?;
+            let mut updated = 0;
+            for id in ids {
+                updated += stmt.execute([id])?;
+            }
+            Ok(updated)
+        }
+    }
+}
+
+pub fn clear(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.execute("DELETE FROM notifications", [])
+}
+
+/// Whether an alert of `kind` was recorded at or after `since`; used to avoid repeating alerts
+pub fn alerted_since(conn: &Connection, kind: NotificationKind, since: i64) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM notifications WHERE kind = ?1 AND created_at >= ?2)",
+        params![kind.as_str(), since],
+        |row| row.get(0),
+    )
+}
+
+fn show(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app_handle.notification().builder().title(title).body(body).show() {
+        tracing::warn!("Failed to show notification: {}", e);
+    }
+}
+
+/// Send a desktop notification, honouring the notification settings and DND
+pub async fn send(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    let settings = crate::commands::get_settings(app_handle.clone()).await.unwrap_or_default().notifications;
+    if !settings.system {
+        return;
+    }
+
+    let dnd = tokio::task::spawn_blocking(is_dnd_active).await.unwrap_or(false);
+    if !dnd {
+        show(app_handle, title, body);
+    } else if settings.suppress_during_dnd {
+        tracing::debug!("Do-Not-Disturb active, dropping notification: {}", title);
+    } else if let Err(e) = app_handle.db(|conn| queue(conn, title, body)) {
+        tracing::warn!("Failed to queue notification: {}", e);
+    }
+}
+
+/// Record an alert in the notification center, tell open windows about it and
+/// send the desktop notification
+pub async fn alert(app_handle: &tauri::AppHandle, kind: NotificationKind, title: &str, body: &str) {
+    match app_handle.db(|conn| record(conn, kind, title, body)) {
+        Ok(notification) => {
+            if let Err(e) = crate::detached::emit(app_handle, "notification-added", &notification) {
+                tracing::warn!("Failed to emit notification: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to record notification: {}", e),
+    }
+    send(app_handle, title, body).await;
+}
+
+/// Deliver queued notifications once DND is off; several are folded into one summary
+pub async fn deliver_pending(app_handle: &tauri::AppHandle) {
+    let pending = match app_handle.db(list_pending) {
+        Ok(pending) if !pending.is_empty() => pending,
+        _ => return,
+    };
+    if tokio::task::spawn_blocking(is_dnd_active).await.unwrap_or(true) {
+        return;
+    }
+
+    if let [only] = pending.as_slice() {
+        show(app_handle, &only.title, &only.body);
+    } else {
+        let titles: Vec<&str> = pending.iter().map(|n| n.title.as_str()).collect();
+        show(app_handle, &format!("{} notifications while Do Not Disturb was on", pending.len()), &titles.join("\n"));
+    }
+    if let Err(e) = app_handle.db(clear_pending) {
+        tracing::warn!("Failed to clear delivered notifications: {}", e);
+    }
+}
+
+/// Retry queued notifications periodically for the life of the process
+pub fn start_pending_delivery(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(DND_POLL_SECS));
+        loop {
+            interval.tick().await;
+            deliver_pending(&app_handle).await;
+        }
+    });
+}
+
+/// Notification center entries, newest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_notifications(
+    app_handle: tauri::AppHandle,
+    unread_only: Option<bool>,
+    limit: Option<u32>,
+) -> Result<Vec<AppNotification>, String> {
+    let limit = limit.unwrap_or(100).min(MAX_STORED as u32);
+    app_handle
+        .db(|conn| list(conn, unread_only.unwrap_or(false), limit))
+        .map_err(|e| format!("Failed to load notifications: {}", e))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_unread_notification_count(app_handle: tauri::AppHandle) -> Result<i64, String> {
+    app_handle.db(unread_count).map_err(|e| format!("Failed to count notifications: {}", e))
+}
+
+/// Mark notifications read; all of them when `ids` is omitted
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn mark_notifications_read(app_handle: tauri::AppHandle, ids: Option<Vec<i64>>) -> Result<usize, String> {
+    app_handle
+        .db(|conn| mark_read(conn, ids.as_deref()))
+        .map_err(|e| format!("Failed to mark notifications read: {}", e))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clear_notifications(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    app_handle.db(clear).map_err(|e| format!("Failed to clear notifications: {}", e))
+}
+
+/// Notifications held back while Do-Not-Disturb was on, oldest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_pending_notifications(app_handle: tauri::AppHandle) -> Result<Vec<PendingNotification>, String> {
+    app_handle.db(list_pending).map_err(|e| format!("Failed to load pending notifications: {}", e))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn clear_pending_notifications(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    app_handle.db(clear_pending).map_err(|e| format!("Failed to clear pending notifications: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dnd_outputs() {
+        assert_eq!(parse_gsettings_bool("false\n"), Some(false));
+        assert_eq!(parse_gsettings_bool("true"), Some(true));
+        assert_eq!(parse_gsettings_bool("No such schema"), None);
+        assert_eq!(parse_gdbus_bool("(<true>,)\n"), Some(true));
+        assert_eq!(parse_gdbus_bool("(<false>,)"), Some(false));
+        assert_eq!(parse_gdbus_bool("(<uint32 1>,)"), None);
+    }
+
+    fn center_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notifications (id INTEGER PRIMARY KEY AUTOINCREMENT, kind TEXT NOT NULL, title TEXT NOT NULL,
+             body TEXT NOT NULL, read INTEGER NOT NULL DEFAULT 0, created_at INTEGER NOT NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_notification_center() {
+        let conn = center_db();
+        let scan = record(&conn, NotificationKind::ScanComplete, "Scan complete", "Found 12 items").unwrap();
+        let disk = record(&conn, NotificationKind::LowDisk, "Low disk space", "4 GB left").unwrap();
+        conn.execute("INSERT INTO notifications (kind, title, body, created_at) VALUES ('future_kind', 'x', 'y', 0)", [])
+            .unwrap();
+
+        let all = list(&conn, false, 10).unwrap();
+        assert_eq!(all.iter().map(|n| n.id).collect::<Vec<_>>(), vec![disk.id, scan.id]);
+        assert_eq!(unread_count(&conn).unwrap(), 3);
+
+        assert_eq!(mark_read(&conn, Some(&[scan.id, scan.id])).unwrap(), 1);
+        let unread = list(&conn, true, 10).unwrap();
+        assert_eq!(unread, vec![disk.clone()]);
+        assert_eq!(mark_read(&conn, None).unwrap(), 2);
+        assert_eq!(unread_count(&conn).unwrap(), 0);
+
+        assert!(alerted_since(&conn, NotificationKind::LowDisk, disk.created_at).unwrap());
+        assert!(!alerted_since(&conn, NotificationKind::TrashExpiring, 0).unwrap());
+        assert_eq!(clear(&conn).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_kind_round_trip() {
+        for kind in [
+            NotificationKind::LowDisk,
+            NotificationKind::ScanComplete,
+            NotificationKind::TrashExpiring,
+            NotificationKind::CleanupComplete,
+        ] {
+            assert_eq!(NotificationKind::from_db(kind.as_str()), Some(kind));
+            assert_eq!(serde_json::to_string(&kind).unwrap(), format!("\"{}\"", kind.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_queue_and_clear() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE pending_notifications (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL,
+             body TEXT NOT NULL, created_at INTEGER NOT NULL);",
+        )
+        .unwrap();
+        queue(&conn, "Scheduled cleanup complete", "Freed 1.2 GB").unwrap();
+        queue(&conn, "Low disk space", "Only 4 GB left").unwrap();
+        let pending = list_pending(&conn).unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].title, "Scheduled cleanup complete");
+        assert_eq!(clear_pending(&conn).unwrap(), 2);
+        assert!(list_pending(&conn).unwrap().is_empty());
+    }
+}
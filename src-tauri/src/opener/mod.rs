@@ -0,0 +1,141 @@
+//! Open scan results with the default application or show them in the file manager.
+//!
+//! Paths go through the same validation as deletions, so these commands can't be
+//! used to poke at system locations. Executables and `.desktop` launchers are
+//! never opened, since xdg-open would run them instead of showing them.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+
+const FILE_MANAGER_DEST: &str = "org.freedesktop.FileManager1";
+const FILE_MANAGER_PATH: &str = "/org/freedesktop/FileManager1";
+
+/// `file://` URI for an absolute path, percent-encoding everything but
+/// unreserved characters and separators
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(*byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn is_launcher(path: &Path) -> bool {
+    if path.extension().map(|e| e == "desktop").unwrap_or(false) {
+        return true;
+    }
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+fn validate(path: &str) -> Result<(), String> {
+    validate_path_comprehensive(path, SecurityContext::Deletion).map_err(|e| format!("Security validation failed: {}", e))
+}
+
+/// Start a detached helper process, reaping it in the background
+fn spawn_detached(program: &str, args: &[&str]) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+fn show_items(path: &Path) -> Result<(), String> {
+    let uri = file_uri(path);
+    let status = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--print-reply",
+            &format!("--dest={}", FILE_MANAGER_DEST),
+            FILE_MANAGER_PATH,
+            "org.freedesktop.FileManager1.ShowItems",
+            &format!("array:string:{}", uri),
+            "string:",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run dbus-send: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("No file manager implements org.freedesktop.FileManager1".to_string())
+    }
+}
+
+/// Open a file or folder from scan results with its default application
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn open_path(path: String) -> Result<(), String> {
+    validate(&path)?;
+    let target = Path::new(&path);
+    if is_launcher(target) {
+        return Err("Refusing to open an executable or launcher; use reveal instead".to_string());
+    }
+
+    tracing::info!("Opening {}", path);
+    spawn_detached("xdg-open", &[&path])
+}
+
+/// Show a file selected in the file manager (FileManager1 D-Bus interface),
+/// falling back to opening its containing folder
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    validate(&path)?;
+
+    tokio::task::spawn_blocking(move || {
+        let target = Path::new(&path);
+        match show_items(target) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                tracing::debug!("FileManager1 unavailable ({}), opening parent folder", e);
+                let parent = if target.is_dir() { target } else { target.parent().unwrap_or(target) };
+                spawn_detached("xdg-open", &[&parent.to_string_lossy()])
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri_encoding() {
+        assert_eq!(file_uri(Path::new("/home/u/file.txt")), "file:///home/u/file.txt");
+        assert_eq!(file_uri(Path::new("/home/u/My Files/a#b.txt")), "file:///home/u/My%20Files/a%23b.txt");
+        assert_eq!(file_uri(Path::new("/tmp/é")), "file:///tmp/%C3%A9");
+    }
+
+    #[test]
+    fn test_launchers_are_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("run.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let doc = dir.path().join("notes.txt");
+        std::fs::write(&doc, "x").unwrap();
+
+        assert!(is_launcher(&script));
+        assert!(is_launcher(&dir.path().join("app.desktop")));
+        assert!(!is_launcher(&doc));
+        assert!(!is_launcher(dir.path()));
+    }
+}
@@ -0,0 +1,299 @@
+//! Authorization layer in front of destructive commands.
+//!
+//! A destructive command only runs with a confirmation token issued by
+//! `describe_operation` for the same plan within the last few minutes, and only
+//! while the per-minute operation budget isn't exhausted, and never in
+//! read-only mode or while safe mode refuses it. Tokens are single-use.
+//! Every decision is written to the `operation_audit` table. A confirmed
+//! operations file counts once against the budget, not once per step.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::OperationPlan;
+use crate::db::DbAccess;
+use crate::read_only;
+use crate::safe_mode;
+use crate::snapshots;
+
+/// How long a confirmation token stays valid after `describe_operation`
+pub const TOKEN_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Destructive operations allowed in any sliding one-minute window
+pub const MAX_OPERATIONS_PER_MINUTE: usize = 10;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct IssuedToken {
+    fingerprint: String,
+    issued_at: Instant,
+    /// Issued by a `BatchGrant`, whose batch was already counted against the budget
+    batch_step: bool,
+    /// Item paths each step of a batch dry run listed
+    reviewed_items: Vec<Vec<String>>,
+}
+
+#[derive(Default)]
+struct GuardState {
+    tokens: HashMap<String, IssuedToken>,
+    recent_operations: VecDeque<Instant>,
+}
+
+impl GuardState {
+    fn issue(&mut self, fingerprint: String, now: Instant) -> String {
+        self.insert(IssuedToken { fingerprint, issued_at: now, batch_step: false, reviewed_items: Vec::new() })
+    }
+
+    fn insert(&mut self, issued: IssuedToken) -> String {
+        self.tokens.retain(|_, t| issued.issued_at.duration_since(t.issued_at) < TOKEN_TTL);
+        let token = uuid::Uuid::new_v4().to_string();
+        self.tokens.insert(token.clone(), issued);
+        token
+    }
+
+    fn authorize(&mut self, fingerprint: &str, token: &str, now: Instant) -> Result<IssuedToken, String> {
+        let batch_step = self.tokens.get(token).is_some_and(|t| t.batch_step);
+        if !batch_step {
+            self.check_rate(now)?;
+        }
+        let issued = self.redeem(fingerprint, token, now)?;
+        if !batch_step {
+            self.recent_operations.push_back(now);
+        }
+        Ok(issued)
+    }
+
+    /// Count an operation against the budget without a token
+    fn admit(&mut self, now: Instant) -> Result<(), String> {
+        self.check_rate(now)?;
+        self.recent_operations.push_back(now);
+        Ok(())
+    }
+
+    fn check_rate(&mut self, now: Instant) -> Result<(), String> {
+        while let Some(oldest) = self.recent_operations.front() {
+            if now.duration_since(*oldest) >= RATE_WINDOW {
+                self.recent_operations.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.recent_operations.len() >= MAX_OPERATIONS_PER_MINUTE {
+            return Err(format!(
+                "Rate limit exceeded: at most {} destructive operations per minute",
+                MAX_OPERATIONS_PER_MINUTE
+            ));
+        }
+        Ok(())
+    }
+
+    fn redeem(&mut self, fingerprint: &str, token: &str, now: Instant) -> Result<IssuedToken, String> {
+        // Consumed even when it doesn't match, so a token can't be probed repeatedly
+        let issued = self.tokens.remove(token).ok_or("Missing or unknown confirmation token")?;
+        if now.duration_since(issued.issued_at) >= TOKEN_TTL {
+            return Err("Confirmation token expired; review the operation again".to_string());
+        }
+        if issued.fingerprint != fingerprint {
+            return Err("Confirmation token was issued for a different operation".to_string());
+        }
+        Ok(issued)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref STATE: Mutex<GuardState> = Mutex::new(GuardState::default());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct OperationAuditEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    pub operation: String,
+    pub allowed: bool,
+    pub reason: Option<String>, // denial reason
+}
+
+/// Issue a confirmation token for `plan`
+pub fn issue_token(plan: &OperationPlan) -> String {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.issue(plan.fingerprint(), Instant::now())
+}
+
+/// Check the rate limit and confirmation token for `plan`, recording the decision.
+/// Destructive commands call this before touching anything.
+pub fn authorize(app_handle: &tauri::AppHandle, plan: &OperationPlan, token: &str) -> Result<(), String> {
+    decide(app_handle, plan, |state, now| state.authorize(&plan.fingerprint(), token, now).map(|_| ()))
+}
+
+/// Like `authorize`, for runs the user set up in advance rather than confirmed
+/// just now, such as a global shortcut: no token, but the same modes, rate limit
+/// and audit entry
+pub fn authorize_internal(app_handle: &tauri::AppHandle, plan: &OperationPlan) -> Result<(), String> {
+    decide(app_handle, plan, |state, now| state.admit(now))
+}
+
+fn decide(
+    app_handle: &tauri::AppHandle,
+    plan: &OperationPlan,
+    check: impl FnOnce(&mut GuardState, Instant) -> Result<(), String>,
+) -> Result<(), String> {
+    let denial = read_only::check(plan.name()).err().or_else(|| safe_mode::denial(plan));
+    let result = match denial {
+        Some(reason) => Err(reason),
+        None => {
+            let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+            check(&mut state, Instant::now())
+        }
+    };
+
+    if let Err(reason) = &result {
+        tracing::warn!("Denied destructive operation {}: {}", plan.name(), reason);
+    }
+    let audit_id = record_decision(app_handle, plan.name(), result.as_ref().err());
+    if let (Ok(()), Some(audit_id)) = (&result, audit_id) {
+        snapshots::link_to_audit(app_handle, &plan.fingerprint(), audit_id);
+    }
+    if result.is_ok() {
+        crate::reclaim::begin(plan.name());
+    }
+    result
+}
+
+/// A confirmed run of an operations file. Only `authorize_batch` hands one
+/// out, so batch steps can't run unless the user confirmed the file's dry run.
+pub struct BatchGrant {
+    reviewed_items: Vec<Vec<String>>,
+}
+
+impl BatchGrant {
+    /// Confirmation token for one step of the confirmed batch. The step still
+    /// goes through `authorize` for its modes and audit entry; the batch as a
+    /// whole already counted against the rate limit.
+    pub fn step_token(&self, plan: &OperationPlan) -> String {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.insert(IssuedToken { fingerprint: plan.fingerprint(), issued_at: Instant::now(), batch_step: true, reviewed_items: Vec::new() })
+    }
+
+    /// Item paths the dry run listed for step `index`, sorted; empty for steps
+    /// it listed nothing for
+    pub fn reviewed_items(&self, index: usize) -> &[String] {
+        self.reviewed_items.get(index).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// Issue the token a dry run of an operations file returns; `fingerprint`
+/// identifies the file's contents and `reviewed_items` holds the item paths
+/// each step listed, the only ones the real run may clean
+pub fn issue_batch_token(fingerprint: &str, reviewed_items: Vec<Vec<String>>) -> String {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    state.insert(IssuedToken { fingerprint: fingerprint.to_string(), issued_at: Instant::now(), batch_step: false, reviewed_items })
+}
+
+/// Redeem the token of a reviewed dry run to run the same operations file for
+/// real; the whole run counts as one operation against the rate limit
+pub fn authorize_batch(app_handle: &tauri::AppHandle, fingerprint: &str, token: &str) -> Result<BatchGrant, String> {
+    let result = read_only::check("run_batch").and_then(|()| {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.authorize(fingerprint, token, Instant::now())
+    });
+    if let Err(reason) = &result {
+        tracing::warn!("Denied operations file run: {}", reason);
+    }
+    record_decision(app_handle, "run_batch", result.as_ref().err());
+    result.map(|issued| BatchGrant { reviewed_items: issued.reviewed_items })
+}
+
+fn record_decision(app_handle: &tauri::AppHandle, operation: &str, denial: Option<&String>) -> Option<i64> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let result = app_handle.db(|conn| {
+        conn.execute(
+            "INSERT INTO operation_audit (timestamp, operation, allowed, reason) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![timestamp, operation, denial.is_none(), denial],
+        )?;
+        Ok(conn.last_insert_rowid())
+    });
+    match result {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::warn!("Failed to record operation audit entry: {}", e);
+            None
+        }
+    }
+}
+
+/// Most recent authorization decisions, newest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_operation_audit_log(app_handle: tauri::AppHandle, limit: Option<u32>) -> Result<Vec<OperationAuditEntry>, String> {
+    let limit = limit.unwrap_or(100).min(1000);
+    app_handle.db(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, operation, allowed, reason FROM operation_audit ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit], |row| {
+            Ok(OperationAuditEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                operation: row.get(2)?,
+                allowed: row.get(3)?,
+                reason: row.get(4)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>()
+    }).map_err(|e| format!("Failed to read operation audit log: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_single_use_and_bound_to_plan() {
+        let mut state = GuardState::default();
+        let now = Instant::now();
+        let token = state.issue("clear_cache".to_string(), now);
+
+        assert!(state.authorize("clear_logs", &token, now).is_err());
+        let token = state.issue("clear_cache".to_string(), now);
+        assert!(state.authorize("clear_cache", &token, now).is_ok());
+        assert!(state.authorize("clear_cache", &token, now).is_err());
+        assert!(state.authorize("clear_cache", "bogus", now).is_err());
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        let mut state = GuardState::default();
+        let issued_at = Instant::now();
+        let token = state.issue("empty_trash".to_string(), issued_at);
+        assert!(state.authorize("empty_trash", &token, issued_at + TOKEN_TTL).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut state = GuardState::default();
+        let now = Instant::now();
+        for _ in 0..MAX_OPERATIONS_PER_MINUTE {
+            let token = state.issue("clear_cache".to_string(), now);
+            assert!(state.authorize("clear_cache", &token, now).is_ok());
+        }
+        let token = state.issue("clear_cache".to_string(), now);
+        assert!(state.authorize("clear_cache", &token, now).unwrap_err().contains("Rate limit"));
+
+        assert!(state.admit(now).unwrap_err().contains("Rate limit"));
+
+        // Steps of a confirmed batch don't use up the budget
+        let token = state.insert(IssuedToken { fingerprint: "clear_cache".to_string(), issued_at: now, batch_step: true, reviewed_items: Vec::new() });
+        assert!(state.authorize("clear_cache", &token, now).is_ok());
+
+        let later = now + RATE_WINDOW;
+        let token = state.issue("clear_cache".to_string(), later);
+        assert!(state.authorize("clear_cache", &token, later).is_ok());
+        assert!(state.admit(later).is_ok());
+        assert_eq!(state.recent_operations.len(), 2);
+    }
+}
@@ -0,0 +1,958 @@
+//! Structured descriptions of destructive operations.
+//!
+//! Confirmation dialogs are built from `describe_operation` rather than text the
+//! frontend keeps in sync by hand: each plan resolves the same targets the matching
+//! command would act on and reports sizes, risk and side effects. The description
+//! carries the confirmation token the command must be called with (see [`guard`]).
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+use crate::cache_redirect;
+use crate::commands;
+use crate::path_validation::{self, SecurityContext};
+use crate::coredumps;
+use crate::db::DbAccess;
+use crate::docker::{self, DockerArtifact};
+use crate::electron::{self, ElectronCache};
+use crate::flatpak::appdata::{self, FlatpakApp};
+use crate::flatpak;
+use crate::journal;
+use crate::packages::kernels;
+use crate::read_only;
+use crate::recovery;
+use crate::safe_mode;
+use crate::snaps;
+use crate::snapshots::{self, existing, SnapshotSupport};
+use crate::trash;
+use crate::xdg_trash;
+
+pub mod guard;
+
+/// Items returned in full; the totals always cover every item
+const MAX_LISTED_ITEMS: usize = 200;
+
+/// A destructive command and the arguments it would be called with
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[specta(export)]
+pub enum OperationPlan {
+    CleanItems {
+        item_paths: Vec<String>,
+        use_trash: bool,
+        #[serde(default = "default_retention_days")]
+        retention_days: i64,
+    },
+    ClearCache,
+    CleanPackages,
+    ClearLogs,
+    ClearCacheItem {
+        item_name: String,
+    },
+    CleanupOldFiles {
+        days_cutoff: u32,
+        /// Roots of application libraries to trash as a whole
+        #[serde(default)]
+        include_libraries: Vec<String>,
+    },
+    EmptyTrash,
+    DeleteFromTrash {
+        id: String,
+    },
+    QuickCleanSafe,
+    CleanDockerArtifacts {
+        artifacts: Vec<DockerArtifact>,
+    },
+    UninstallFlatpakRefs {
+        refs: Vec<String>,
+    },
+    OffloadFiles {
+        item_paths: Vec<String>,
+        destination: String,
+    },
+    RemoveDisabledSnaps,
+    VacuumJournal {
+        max_size_mb: u32,
+        max_age_days: u32,
+    },
+    RemoveCoredumps {
+        older_than_days: u32,
+    },
+    RemoveOldKernels {
+        releases: Vec<String>,
+    },
+    TerminateHolder {
+        pid: u32,
+    },
+    DeleteFilesystemSnapshots {
+        ids: Vec<String>,
+    },
+    EmptySystemTrash {
+        /// Trash folders to empty of entries deleted at least `older_than_days` ago
+        trash_dirs: Vec<String>,
+        /// Single trashed entries, whatever their age
+        #[serde(default)]
+        entries: Vec<String>,
+        older_than_days: u32,
+    },
+    RedirectCache {
+        name: String,
+    },
+    /// Turn safe mode off, or shorten its minimum trash retention
+    DisableSafeMode,
+}
+
+fn default_retention_days() -> i64 {
+    3
+}
+
+impl OperationPlan {
+    /// Name of the command this plan describes
+    pub fn name(&self) -> &'static str {
+        match self {
+            OperationPlan::CleanItems { .. } => "clean_items",
+            OperationPlan::ClearCache => "clear_cache",
+            OperationPlan::CleanPackages => "clean_packages",
+            OperationPlan::ClearLogs => "clear_logs",
+            OperationPlan::ClearCacheItem { .. } => "clear_cache_item",
+            OperationPlan::CleanupOldFiles { .. } => "cleanup_old_files",
+            OperationPlan::EmptyTrash => "empty_trash",
+            OperationPlan::DeleteFromTrash { .. } => "delete_from_trash",
+            OperationPlan::QuickCleanSafe => "quick_clean_safe",
+            OperationPlan::CleanDockerArtifacts { .. } => "clean_docker_artifacts",
+            OperationPlan::UninstallFlatpakRefs { .. } => "uninstall_flatpak_refs",
+            OperationPlan::OffloadFiles { .. } => "offload_files",
+            OperationPlan::RemoveDisabledSnaps => "remove_disabled_snaps",
+            OperationPlan::VacuumJournal { .. } => "vacuum_journal",
+            OperationPlan::RemoveCoredumps { .. } => "remove_coredumps",
+            OperationPlan::RemoveOldKernels { .. } => "remove_old_kernels",
+            OperationPlan::TerminateHolder { .. } => "terminate_holder",
+            OperationPlan::DeleteFilesystemSnapshots { .. } => "delete_filesystem_snapshots",
+            OperationPlan::EmptySystemTrash { .. } => "empty_system_trash",
+            OperationPlan::RedirectCache { .. } => "enable_cache_redirect",
+            OperationPlan::DisableSafeMode => "disable_safe_mode",
+        }
+    }
+
+    /// Whether the plan removes data without going through the trash
+    pub fn is_permanent(&self) -> bool {
+        match self {
+            OperationPlan::CleanItems { use_trash, .. } => !use_trash,
+            OperationPlan::EmptyTrash
+            | OperationPlan::DeleteFromTrash { .. }
+            | OperationPlan::CleanDockerArtifacts { .. }
+            | OperationPlan::UninstallFlatpakRefs { .. }
+            | OperationPlan::RemoveDisabledSnaps
+            | OperationPlan::VacuumJournal { .. }
+            | OperationPlan::RemoveCoredumps { .. }
+            | OperationPlan::RemoveOldKernels { .. }
+            | OperationPlan::DeleteFilesystemSnapshots { .. }
+            | OperationPlan::EmptySystemTrash { .. }
+            | OperationPlan::RedirectCache { .. } => true,
+            OperationPlan::ClearCache
+            | OperationPlan::CleanPackages
+            | OperationPlan::ClearLogs
+            | OperationPlan::ClearCacheItem { .. }
+            | OperationPlan::CleanupOldFiles { .. }
+            | OperationPlan::QuickCleanSafe
+            | OperationPlan::OffloadFiles { .. }
+            | OperationPlan::TerminateHolder { .. }
+            | OperationPlan::DisableSafeMode => false,
+        }
+    }
+
+    /// Identity a confirmation token is bound to. Item order and trash retention
+    /// don't change what gets removed, so they are normalized away.
+    pub fn fingerprint(&self) -> String {
+        let mut plan = self.clone();
+        match &mut plan {
+            OperationPlan::CleanItems { item_paths, retention_days, .. } => {
+                item_paths.sort();
+                item_paths.dedup();
+                *retention_days = 0;
+            }
+            OperationPlan::CleanupOldFiles { include_libraries, .. } => {
+                include_libraries.sort();
+                include_libraries.dedup();
+            }
+            OperationPlan::CleanDockerArtifacts { artifacts } => {
+                artifacts.sort();
+                artifacts.dedup();
+            }
+            OperationPlan::UninstallFlatpakRefs { refs } | OperationPlan::DeleteFilesystemSnapshots { ids: refs } => {
+                refs.sort();
+                refs.dedup();
+            }
+            OperationPlan::OffloadFiles { item_paths, .. } => {
+                item_paths.sort();
+                item_paths.dedup();
+            }
+            OperationPlan::EmptySystemTrash { trash_dirs, entries, .. } => {
+                trash_dirs.sort();
+                trash_dirs.dedup();
+                entries.sort();
+                entries.dedup();
+            }
+            _ => {}
+        }
+        serde_json::to_string(&plan).unwrap_or_else(|_| self.name().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct OperationItem {
+    pub path: String,
+    pub size: u64,
+    pub risk_level: u8, // 0 = safe, 1 = low, 2 = medium, 3 = high
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct OperationDescription {
+    pub operation: String, // command name, e.g. "clear_cache"
+    pub title: String,
+    pub message: String,
+    pub dialog_type: String, // "info", "warning" or "danger"
+    pub items: Vec<OperationItem>, // largest first, capped at MAX_LISTED_ITEMS
+    pub item_count: usize,
+    pub total_size: u64,
+    pub highest_risk_level: u8,
+    pub irreversible: bool, // items are permanently deleted rather than moved to trash
+    pub retention_days: Option<i64>,
+    pub requires_privileges: bool,
+    pub affected_apps: Vec<String>,
+    pub warnings: Vec<String>,
+    pub confirmation_token: String, // pass to the command; single use, expires after guard::TOKEN_TTL
+    pub snapshot: Option<SnapshotSupport>, // offered for high-risk plans on btrfs, see create_cleanup_snapshot
+    /// Safe mode only: items `acknowledge_risky_item` must be called for one by one
+    #[serde(default)]
+    pub acknowledgement_required: Vec<String>,
+}
+
+fn path_size(path: &Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => trash::get_dir_size(path),
+        Ok(m) => m.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Application owning a path, taken from the directory directly below a per-app
+/// location such as ~/.cache or ~/.config
+pub fn affected_app(path: &Path, home: &Path) -> Option<String> {
+    let app_roots = [".cache", ".config", ".local/share", ".var/app", ".mozilla"];
+    let relative = path.strip_prefix(home).ok()?;
+    app_roots.iter().find_map(|root| {
+        relative
+            .strip_prefix(root)
+            .ok()
+            .and_then(|rest| rest.components().next())
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+    })
+}
+
+/// Risk of removing an arbitrary path, following the levels the scanner assigns
+pub fn classify_risk(path: &Path, home: &Path) -> (u8, &'static str) {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if path.starts_with(home.join(".cache")) || path.starts_with(home.join(".thumbnails")) {
+        (0, "Cache")
+    } else if name.ends_with(".log") || name.contains(".log.") {
+        (1, "Logs")
+    } else if path.starts_with(home.join(".config")) || path.starts_with(home.join(".local/share")) {
+        (3, "Application Data")
+    } else {
+        (2, "Files")
+    }
+}
+
+fn dialog_type(highest_risk_level: u8, irreversible: bool) -> &'static str {
+    if irreversible || highest_risk_level >= 3 {
+        "danger"
+    } else if highest_risk_level >= 1 {
+        "warning"
+    } else {
+        "info"
+    }
+}
+
+struct Builder {
+    operation: &'static str,
+    title: String,
+    message: String,
+    items: Vec<OperationItem>,
+    irreversible: bool,
+    retention_days: Option<i64>,
+    requires_privileges: bool,
+    affected_apps: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl Builder {
+    fn new(operation: &'static str, title: &str, message: &str) -> Self {
+        Self {
+            operation,
+            title: title.to_string(),
+            message: message.to_string(),
+            items: Vec::new(),
+            irreversible: false,
+            retention_days: None,
+            requires_privileges: false,
+            affected_apps: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, path: &Path, size: u64, risk_level: u8, category: &str, home: &Path) {
+        if let Some(app) = affected_app(path, home) {
+            if !self.affected_apps.contains(&app) {
+                self.affected_apps.push(app);
+            }
+        }
+        self.items.push(OperationItem {
+            path: path.to_string_lossy().to_string(),
+            size,
+            risk_level,
+            category: category.to_string(),
+        });
+    }
+
+    /// Record a path the command would skip because validation rejects it
+    fn validate(&mut self, path: &str, context: SecurityContext) -> bool {
+        match path_validation::validate_path_comprehensive(path, context) {
+            Ok(()) => true,
+            Err(e) => {
+                self.warnings.push(format!("{} will be skipped: {}", path, e));
+                false
+            }
+        }
+    }
+
+    fn finish(mut self) -> OperationDescription {
+        self.items.sort_by(|a, b| b.size.cmp(&a.size));
+        self.affected_apps.sort();
+        let item_count = self.items.len();
+        let total_size = self.items.iter().map(|i| i.size).sum();
+        let highest_risk_level = self.items.iter().map(|i| i.risk_level).max().unwrap_or(0);
+        self.items.truncate(MAX_LISTED_ITEMS);
+
+        if self.irreversible {
+            self.warnings.insert(0, "Items will be permanently deleted and cannot be restored".to_string());
+        }
+        if highest_risk_level >= 3 {
+            self.warnings.push("Includes application data; affected apps may lose settings or state".to_string());
+        }
+
+        OperationDescription {
+            operation: self.operation.to_string(),
+            title: self.title,
+            message: self.message,
+            dialog_type: dialog_type(highest_risk_level, self.irreversible).to_string(),
+            items: self.items,
+            item_count,
+            total_size,
+            highest_risk_level,
+            irreversible: self.irreversible,
+            retention_days: self.retention_days,
+            requires_privileges: self.requires_privileges,
+            affected_apps: self.affected_apps,
+            warnings: self.warnings,
+            confirmation_token: String::new(),
+            snapshot: None,
+            acknowledgement_required: Vec::new(),
+        }
+    }
+}
+
+fn describe_clean_items(item_paths: &[String], use_trash: bool, retention_days: i64, home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "clean_items",
+        "Clean selected items",
+        if use_trash {
+            "Selected items will be moved to trash and can be restored until they expire."
+        } else {
+            "Selected items will be permanently deleted."
+        },
+    );
+    builder.irreversible = !use_trash;
+    builder.retention_days = use_trash.then_some(retention_days);
+
+    for path_str in item_paths {
+        if !builder.validate(path_str, SecurityContext::Deletion) {
+            continue;
+        }
+        let path = PathBuf::from(path_str);
+        let (risk_level, category) = classify_risk(&path, home);
+        builder.push(&path, path_size(&path), risk_level, category, home);
+    }
+    builder.finish()
+}
+
+fn describe_clear_cache(home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "clear_cache",
+        "Clear application caches",
+        "This will clear application caches and temporary files. This is generally safe but applications may need to rebuild their caches.",
+    );
+    builder.retention_days = Some(3);
+
+    for path in commands::cache_cleanup_targets(&home.join(".cache")) {
+        if builder.validate(&path.to_string_lossy(), SecurityContext::CacheCleanup) {
+            builder.push(&path, path_size(&path), 0, "Cache", home);
+        }
+    }
+    builder.finish()
+}
+
+fn describe_clear_electron_cache(home: &Path, cache: &ElectronCache) -> OperationDescription {
+    let mut builder = Builder::new(
+        "clear_cache_item",
+        &format!("Clear {}", cache.item_name()),
+        &format!("This will clear the Chromium caches of {}. Settings and logins are kept; the app rebuilds its caches as needed.", cache.app),
+    );
+    builder.retention_days = Some(3);
+
+    for path in &cache.cache_dirs {
+        if builder.validate(&path.to_string_lossy(), SecurityContext::CacheCleanup) {
+            builder.push(path, path_size(path), 0, electron::CATEGORY, home);
+        }
+    }
+    if cache.is_running() {
+        builder.warnings.push(format!("{} is running; restart it after clearing its cache", cache.app));
+    }
+    builder.finish()
+}
+
+fn describe_clear_flatpak_cache(home: &Path, app: &FlatpakApp) -> OperationDescription {
+    let mut builder = Builder::new(
+        "clear_cache_item",
+        &format!("Clear {}", app.item_name()),
+        &format!("This will clear the cache folder of the {} Flatpak app. Its data and settings are kept.", app.app_id),
+    );
+    builder.retention_days = Some(3);
+
+    for path in app.cache_entries() {
+        if builder.validate(&path.to_string_lossy(), SecurityContext::CacheCleanup) {
+            builder.push(&path, path_size(&path), 0, appdata::CATEGORY, home);
+        }
+    }
+    if appdata::running_now().contains(&app.app_id) {
+        builder.warnings.push(format!("{} is running; restart it after clearing its cache", app.app_id));
+    }
+    builder.finish()
+}
+
+fn describe_clean_packages(home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "clean_packages",
+        "Clean package caches",
+        "This will clean the package manager cache and remove orphaned packages.",
+    );
+    builder.requires_privileges = true;
+    builder.retention_days = Some(3);
+
+    let apt_archives = Path::new("/var/cache/apt/archives");
+    if apt_archives.exists() {
+        builder.push(apt_archives, path_size(apt_archives), 0, "Package Cache", home);
+    }
+    builder.warnings.push("Orphaned packages are removed with 'apt autoremove' and cannot be restored from trash".to_string());
+    builder.affected_apps.push("apt".to_string());
+
+    let pip_cache = home.join(".cache/pip");
+    if pip_cache.exists() && builder.validate(&pip_cache.to_string_lossy(), SecurityContext::PackageManagement) {
+        builder.push(&pip_cache, path_size(&pip_cache), 0, "Package Cache", home);
+    }
+    builder.finish()
+}
+
+fn describe_clear_logs(home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "clear_logs",
+        "Clear user logs",
+        "This will clear application logs in your home directory. System logs are not touched.",
+    );
+    builder.retention_days = Some(7);
+
+    for path in commands::log_cleanup_targets(home) {
+        if path.exists() && builder.validate(&path.to_string_lossy(), SecurityContext::LogCleanup) {
+            builder.push(&path, path_size(&path), 1, "Logs", home);
+        }
+    }
+    builder.finish()
+}
+
+fn describe_cleanup_old_files(
+    app_handle: &tauri::AppHandle,
+    days_cutoff: u32,
+    include_libraries: &[String],
+    home: &Path,
+) -> Result<OperationDescription, String> {
+    let (old_files, libraries) = commands::old_file_targets(app_handle, days_cutoff)?;
+
+    let mut builder = Builder::new(
+        "cleanup_old_files",
+        "Clean up old files",
+        &format!("Files not accessed in {} days will be moved to trash.", days_cutoff),
+    );
+    builder.retention_days = Some(30);
+
+    for (path_str, _) in old_files {
+        let path = PathBuf::from(&path_str);
+        if !path.exists() || !builder.validate(&path_str, SecurityContext::Deletion) {
+            continue;
+        }
+        let (risk_level, _) = classify_risk(&path, home);
+        builder.push(&path, path_size(&path), risk_level.max(1), "Old Files", home);
+    }
+
+    let (opted_in, skipped): (Vec<_>, Vec<_>) = libraries.into_iter().partition(|l| include_libraries.contains(&l.root));
+    for library in opted_in {
+        let path = PathBuf::from(&library.root);
+        if path.exists() && builder.validate(&library.root, SecurityContext::Deletion) {
+            builder.push(&path, path_size(&path), 2, &format!("{} library", library.app), home);
+            builder.warnings.push(format!("The entire {} library at {} is moved to trash", library.app, library.root));
+        }
+    }
+    if !skipped.is_empty() {
+        let names: Vec<String> = skipped.iter().map(|l| format!("{} ({})", l.app, l.root)).collect();
+        builder.warnings.push(format!("Files inside application libraries are left alone: {}", names.join(", ")));
+    }
+    Ok(builder.finish())
+}
+
+fn describe_quick_clean(home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "quick_clean_safe",
+        "Quick clean",
+        "This will clear application caches and user logs, and remove empty directories, broken symlinks and orphaned temp files.",
+    );
+    builder.retention_days = Some(3);
+
+    for path in commands::cache_cleanup_targets(&home.join(".cache")) {
+        if builder.validate(&path.to_string_lossy(), SecurityContext::CacheCleanup) {
+            builder.push(&path, path_size(&path), 0, "Cache", home);
+        }
+    }
+    for path in commands::log_cleanup_targets(home) {
+        if path.exists() && builder.validate(&path.to_string_lossy(), SecurityContext::LogCleanup) {
+            builder.push(&path, path_size(&path), 1, "Logs", home);
+        }
+    }
+    builder.warnings.push(
+        "Empty directories, broken symlinks and orphaned temp files (risk 0-1) are deleted directly, not moved to trash".to_string(),
+    );
+    builder.finish()
+}
+
+fn describe_trash(id: Option<&str>, home: &Path) -> Result<OperationDescription, String> {
+    let mut builder = match id {
+        Some(_) => Builder::new("delete_from_trash", "Delete from trash", "This item will be permanently deleted."),
+        None => Builder::new("empty_trash", "Empty trash", "All items in trash will be permanently deleted."),
+    };
+    builder.irreversible = true;
+
+    let data = trash::get_trash_items();
+    let items: Vec<_> = data.items.iter().filter(|i| id.map(|id| i.id == id).unwrap_or(true)).collect();
+    if let Some(id) = id {
+        if items.is_empty() {
+            return Err(format!("Trash item not found: {}", id));
+        }
+    }
+
+    for item in items {
+        let (risk_level, category) = match &item.metadata {
+            Some(meta) => (meta.risk_level, meta.category.as_str()),
+            None => classify_risk(Path::new(&item.original_path), home),
+        };
+        builder.push(Path::new(&item.original_path), item.size, risk_level, category, home);
+    }
+    Ok(builder.finish())
+}
+
+fn describe_empty_system_trash(
+    trash_dirs: &[String],
+    entries: &[String],
+    older_than_days: u32,
+    home: &Path,
+) -> Result<OperationDescription, String> {
+    let mut builder = Builder::new(
+        "empty_system_trash",
+        "Empty system trash",
+        "These files were deleted through the file manager. They will be permanently deleted and can no longer be restored from its trash.",
+    );
+    builder.irreversible = true;
+
+    let found = xdg_trash::list_trash_dirs(home);
+    let (selected_dirs, selected_entries) = xdg_trash::resolve_targets(&found, trash_dirs, entries)?;
+    let now = chrono::Utc::now().timestamp();
+    let mut targets: Vec<&xdg_trash::TrashEntry> =
+        selected_dirs.iter().flat_map(|d| xdg_trash::expired(d, older_than_days, now)).collect();
+    targets.extend(selected_entries);
+    targets.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    targets.dedup_by(|a, b| a.file_path == b.file_path);
+    for entry in targets {
+        let shown = entry.original_path.as_deref().map(PathBuf::from).unwrap_or_else(|| entry.file_path.clone());
+        builder.push(&shown, entry.size, 1, xdg_trash::CATEGORY, home);
+    }
+    if older_than_days > 0 && !trash_dirs.is_empty() {
+        builder.warnings.push(format!("Items deleted in the last {} days stay in the trash", older_than_days));
+    }
+    Ok(builder.finish())
+}
+
+fn describe_clean_docker(artifacts: &[DockerArtifact], home: &Path) -> Result<OperationDescription, String> {
+    let mut builder = Builder::new(
+        "clean_docker_artifacts",
+        "Prune Docker artifacts",
+        "The selected Docker artifacts will be removed by the Docker daemon.",
+    );
+    builder.irreversible = true;
+    builder.affected_apps.push("docker".to_string());
+
+    for usage in docker::usage()?.iter().filter(|u| artifacts.contains(&u.artifact)) {
+        let artifact = usage.artifact;
+        builder.push(Path::new(&artifact.path()), usage.size, artifact.risk_level(), docker::CATEGORY, home);
+    }
+    if artifacts.contains(&DockerArtifact::UnusedVolumes) {
+        builder.warnings.push("Data in unused volumes (databases, uploads) is deleted with them".to_string());
+    }
+    Ok(builder.finish())
+}
+
+fn describe_uninstall_flatpak(refs: &[String], home: &Path) -> Result<OperationDescription, String> {
+    let mut builder = Builder::new(
+        "uninstall_flatpak_refs",
+        "Uninstall unused Flatpak runtimes",
+        "The selected runtimes are uninstalled with flatpak. They are downloaded again if an app needs them later.",
+    );
+    builder.irreversible = true;
+    builder.affected_apps.push("flatpak".to_string());
+
+    let unused = flatpak::unused_refs()?;
+    for reference in refs {
+        match unused.iter().find(|r| &r.reference == reference) {
+            Some(installed) => {
+                builder.requires_privileges |= installed.system;
+                builder.push(&installed.deploy_dir, path_size(&installed.deploy_dir), 0, flatpak::CATEGORY, home);
+            }
+            None => builder.warnings.push(format!("{} is no longer unused and will be skipped", reference)),
+        }
+    }
+    Ok(builder.finish())
+}
+
+fn describe_offload(item_paths: &[String], destination: &str, home: &Path) -> Result<OperationDescription, String> {
+    archive::check_destination(Path::new(destination), item_paths)?;
+    let mut builder = Builder::new(
+        "offload_files",
+        "Offload files",
+        &format!("Selected items will be moved to {}. Use \"Find archived file\" to look up where each one went.", destination),
+    );
+
+    for path_str in item_paths {
+        if !builder.validate(path_str, SecurityContext::Deletion) {
+            continue;
+        }
+        let path = PathBuf::from(path_str);
+        let (risk_level, category) = classify_risk(&path, home);
+        builder.push(&path, path_size(&path), risk_level, category, home);
+    }
+    Ok(builder.finish())
+}
+
+fn describe_remove_disabled_snaps(home: &Path) -> Result<OperationDescription, String> {
+    let mut builder = Builder::new(
+        "remove_disabled_snaps",
+        "Remove old snap revisions",
+        "All disabled snap revisions will be removed. Snaps can no longer be reverted to them.",
+    );
+    builder.irreversible = true;
+    builder.requires_privileges = true;
+    builder.affected_apps.push("snapd".to_string());
+
+    for revision in snaps::disabled_revisions()? {
+        builder.push(&revision.path, snaps::revision_size(&revision), 0, snaps::CATEGORY, home);
+    }
+    Ok(builder.finish())
+}
+
+fn describe_vacuum_journal(max_size_mb: u32, max_age_days: u32, home: &Path) -> Result<OperationDescription, String> {
+    let settings = commands::JournalSettings { max_size_mb, max_age_days };
+    let mut limits = Vec::new();
+    if max_size_mb > 0 {
+        limits.push(format!("{} MB", max_size_mb));
+    }
+    if max_age_days > 0 {
+        limits.push(format!("the last {} days", max_age_days));
+    }
+    if limits.is_empty() {
+        return Err("Set a size or age limit to vacuum the journal to".to_string());
+    }
+    let mut builder = Builder::new(
+        "vacuum_journal",
+        "Vacuum the systemd journal",
+        &format!("journalctl removes the oldest journal files until the journal is within {}.", limits.join(" and ")),
+    );
+    builder.irreversible = true;
+    builder.requires_privileges = true;
+    builder.affected_apps.push("systemd-journald".to_string());
+
+    let usage = journal::disk_usage()?;
+    builder.push(Path::new(journal::JOURNAL_DIR), journal::reclaimable(usage, &settings), 0, journal::CATEGORY, home);
+    if max_size_mb == 0 {
+        builder.warnings.push("How much an age limit frees is only known afterwards".to_string());
+    }
+    Ok(builder.finish())
+}
+
+fn describe_remove_coredumps(older_than_days: u32, home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "remove_coredumps",
+        "Remove core dumps",
+        &format!("Core dumps older than {} days will be deleted. coredumpctl can no longer debug those crashes.", older_than_days),
+    );
+    builder.irreversible = true;
+    builder.requires_privileges = true;
+    builder.affected_apps.push("systemd-coredump".to_string());
+
+    let now = chrono::Utc::now().timestamp();
+    for dump in coredumps::old_coredumps(Path::new(coredumps::SYSTEMD_COREDUMP_DIR), older_than_days, now) {
+        builder.push(&dump.path, dump.size, 0, coredumps::CATEGORY, home);
+    }
+    builder.finish()
+}
+
+fn describe_remove_old_kernels(releases: &[String], home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "remove_old_kernels",
+        "Remove old kernels",
+        "The selected kernels are purged with apt together with their modules and headers. They no longer show up in the boot menu.",
+    );
+    builder.irreversible = true;
+    builder.requires_privileges = true;
+    builder.affected_apps.push("apt".to_string());
+
+    let installed = kernels::installed_kernels();
+    let running = kernels::running_release();
+    match kernels::check_removal(releases, &installed, running.as_deref()) {
+        Ok(targets) => {
+            for kernel in targets {
+                builder.push(&kernel.image_path(), kernel.size, 1, kernels::CATEGORY, home);
+            }
+        }
+        Err(e) => builder.warnings.push(format!("{}; nothing will be removed", e)),
+    }
+    builder.finish()
+}
+
+fn describe_delete_snapshots(ids: &[String], home: &Path) -> OperationDescription {
+    let mut builder = Builder::new(
+        "delete_filesystem_snapshots",
+        "Delete filesystem snapshots",
+        "The selected snapshots are deleted. Files can no longer be restored from them, and blocks only they held are freed.",
+    );
+    builder.irreversible = true;
+
+    let listed = existing::list_snapshots();
+    for id in ids {
+        match listed.iter().find(|s| &s.id == id) {
+            Some(snapshot) => {
+                builder.requires_privileges |= snapshot.tool != "snapper";
+                let risk = if snapshot.created_by_pulito { 1 } else { 3 };
+                builder.push(Path::new(&snapshot.path), snapshot.exclusive_bytes.unwrap_or(0), risk, existing::CATEGORY, home);
+                if !builder.affected_apps.contains(&snapshot.tool) {
+                    builder.affected_apps.push(snapshot.tool.clone());
+                }
+            }
+            None => builder.warnings.push(format!("{} no longer exists and will be skipped", id)),
+        }
+    }
+    if listed.iter().any(|s| ids.contains(&s.id) && !s.created_by_pulito) {
+        builder.warnings.push("Some of these snapshots belong to a backup or timeline policy, not to Pulito".to_string());
+    }
+    builder.finish()
+}
+
+fn describe_redirect_cache(name: &str, home: &Path) -> Result<OperationDescription, String> {
+    let (path, label, owners) = cache_redirect::lookup_cache(name)?;
+    let mut builder = Builder::new(
+        "enable_cache_redirect",
+        &format!("Move the {} cache to memory", label),
+        "The cache folder is replaced by a link to a folder in memory. Its current contents are deleted and rebuilt by the app, and the cache starts empty after every logout.",
+    );
+    builder.irreversible = true;
+    builder.push(&path, path_size(&path), 0, "Cache", home);
+    if let Err(reason) = cache_redirect::check_owner_closed(label, owners) {
+        builder.warnings.push(reason);
+    }
+    Ok(builder.finish())
+}
+
+fn describe_disable_safe_mode() -> OperationDescription {
+    let mut builder = Builder::new(
+        "disable_safe_mode",
+        "Relax safe mode",
+        "Safe mode will be turned off or keep trashed items for a shorter time. Permanent deletion and removing personal files without a per-item confirmation become possible again.",
+    );
+    builder.warnings.push("Only continue if you set up safe mode for this computer".to_string());
+    builder.finish()
+}
+
+fn describe_terminate_holder(pid: u32, home: &Path) -> Result<OperationDescription, String> {
+    let holder = recovery::find_holder(pid)?;
+    let mut builder = Builder::new(
+        "terminate_holder",
+        &format!("Stop {}", holder.process_name),
+        &format!(
+            "{} (process {}) still holds deleted files open. It will be asked to quit so their space is released.",
+            holder.process_name, pid
+        ),
+    );
+    builder.affected_apps.push(holder.process_name.clone());
+    for file in &holder.files {
+        builder.push(Path::new(&file.path), file.size, 2, "Open deleted files", home);
+    }
+    builder.warnings.push(format!("Unsaved work in {} may be lost", holder.process_name));
+    Ok(builder.finish())
+}
+
+/// Structured confirmation data for a destructive command, resolved against the
+/// current system state without modifying anything
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn describe_operation(app_handle: tauri::AppHandle, plan: OperationPlan) -> Result<OperationDescription, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let issued_for = plan.clone();
+
+    let mut description = tokio::task::spawn_blocking(move || match plan {
+        OperationPlan::CleanItems { item_paths, use_trash, retention_days } => {
+            Ok(describe_clean_items(&item_paths, use_trash, retention_days, &home))
+        }
+        OperationPlan::ClearCache => Ok(describe_clear_cache(&home)),
+        OperationPlan::CleanPackages => Ok(describe_clean_packages(&home)),
+        OperationPlan::ClearLogs => Ok(describe_clear_logs(&home)),
+        // Mirrors the dispatch in commands::clear_cache_item
+        OperationPlan::ClearCacheItem { item_name } => match item_name.as_str() {
+            "Chrome temporary files" | "Firefox cache" => Ok(describe_clear_cache(&home)),
+            "Python packages cache" => Ok(describe_clean_packages(&home)),
+            _ => match appdata::find_by_item_name(&home, item_name) {
+                Some(app) => Ok(describe_clear_flatpak_cache(&home, &app)),
+                None => match electron::find_by_item_name(&home, item_name) {
+                    Some(cache) => Ok(describe_clear_electron_cache(&home, &cache)),
+                    None => Err(format!("Unknown cache item: {}", item_name)),
+                },
+            },
+        },
+        OperationPlan::CleanupOldFiles { days_cutoff, include_libraries } => {
+            describe_cleanup_old_files(&app_handle, days_cutoff, &include_libraries, &home)
+        }
+        OperationPlan::EmptyTrash => describe_trash(None, &home),
+        OperationPlan::DeleteFromTrash { id } => describe_trash(Some(&id), &home),
+        OperationPlan::QuickCleanSafe => Ok(describe_quick_clean(&home)),
+        OperationPlan::CleanDockerArtifacts { artifacts } => describe_clean_docker(&artifacts, &home),
+        OperationPlan::UninstallFlatpakRefs { refs } => describe_uninstall_flatpak(&refs, &home),
+        OperationPlan::OffloadFiles { item_paths, destination } => describe_offload(&item_paths, &destination, &home),
+        OperationPlan::RemoveDisabledSnaps => describe_remove_disabled_snaps(&home),
+        OperationPlan::VacuumJournal { max_size_mb, max_age_days } => describe_vacuum_journal(max_size_mb, max_age_days, &home),
+        OperationPlan::RemoveCoredumps { older_than_days } => Ok(describe_remove_coredumps(older_than_days, &home)),
+        OperationPlan::RemoveOldKernels { releases } => Ok(describe_remove_old_kernels(&releases, &home)),
+        OperationPlan::TerminateHolder { pid } => describe_terminate_holder(pid, &home),
+        OperationPlan::DeleteFilesystemSnapshots { ids } => Ok(describe_delete_snapshots(&ids, &home)),
+        OperationPlan::EmptySystemTrash { trash_dirs, entries, older_than_days } => {
+            describe_empty_system_trash(&trash_dirs, &entries, older_than_days, &home)
+        }
+        OperationPlan::RedirectCache { name } => describe_redirect_cache(&name, &home),
+        OperationPlan::DisableSafeMode => Ok(describe_disable_safe_mode()),
+    })
+    .await
+    .map_err(|e| format!("Failed to describe operation: {}", e))??;
+
+    if description.irreversible || description.highest_risk_level >= 2 {
+        let plan = issued_for.clone();
+        description.snapshot = tokio::task::spawn_blocking(move || snapshots::support_for(&plan))
+            .await
+            .ok()
+            .flatten()
+            .filter(|support| support.is_btrfs && !support.tools.is_empty());
+    }
+    if safe_mode::is_enabled() {
+        if let Some(reason) = safe_mode::denial(&issued_for) {
+            description.warnings.insert(0, reason);
+        }
+        description.retention_days = description.retention_days.map(|days| days.max(trash::min_retention_days()));
+        if let OperationPlan::CleanItems { item_paths, .. } = &issued_for {
+            let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+            description.acknowledgement_required =
+                safe_mode::needing_acknowledgement(item_paths, &home).iter().map(|p| p.to_string_lossy().to_string()).collect();
+        }
+    }
+    // Still described, so the report shows what would have been removed
+    if let Err(reason) = read_only::check(issued_for.name()) {
+        description.warnings.insert(0, reason);
+    }
+    description.operation = issued_for.name().to_string();
+    description.confirmation_token = guard::issue_token(&issued_for);
+    Ok(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affected_app_and_risk() {
+        let home = Path::new("/home/u");
+        assert_eq!(affected_app(Path::new("/home/u/.cache/google-chrome/Default"), home).as_deref(), Some("google-chrome"));
+        assert_eq!(affected_app(Path::new("/home/u/.local/share/Steam"), home).as_deref(), Some("Steam"));
+        assert_eq!(affected_app(Path::new("/home/u/Downloads/a.iso"), home), None);
+
+        assert_eq!(classify_risk(Path::new("/home/u/.cache/pip"), home).0, 0);
+        assert_eq!(classify_risk(Path::new("/home/u/app.log"), home).0, 1);
+        assert_eq!(classify_risk(Path::new("/home/u/Downloads/a.iso"), home).0, 2);
+        assert_eq!(classify_risk(Path::new("/home/u/.config/foo"), home).0, 3);
+    }
+
+    #[test]
+    fn test_plan_deserialization_and_dialog_type() {
+        let plan: OperationPlan = serde_json::from_str(r#"{"kind": "cleanup_old_files", "days_cutoff": 90}"#).unwrap();
+        assert!(matches!(plan, OperationPlan::CleanupOldFiles { days_cutoff: 90, ref include_libraries } if include_libraries.is_empty()));
+        let a: OperationPlan =
+            serde_json::from_str(r#"{"kind": "cleanup_old_files", "days_cutoff": 90, "include_libraries": ["/b", "/a"]}"#).unwrap();
+        let b: OperationPlan =
+            serde_json::from_str(r#"{"kind": "cleanup_old_files", "days_cutoff": 90, "include_libraries": ["/a", "/b"]}"#).unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), plan.fingerprint());
+        let plan: OperationPlan = serde_json::from_str(r#"{"kind": "empty_trash"}"#).unwrap();
+        assert!(matches!(plan, OperationPlan::EmptyTrash));
+
+        let a: OperationPlan = serde_json::from_str(r#"{"kind": "clean_items", "item_paths": ["/b", "/a"], "use_trash": true}"#).unwrap();
+        let b: OperationPlan = serde_json::from_str(r#"{"kind": "clean_items", "item_paths": ["/a", "/b"], "use_trash": true, "retention_days": 7}"#).unwrap();
+        let c: OperationPlan = serde_json::from_str(r#"{"kind": "clean_items", "item_paths": ["/a", "/b"], "use_trash": false}"#).unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+
+        assert_eq!(dialog_type(0, false), "info");
+        assert_eq!(dialog_type(2, false), "warning");
+        assert_eq!(dialog_type(0, true), "danger");
+    }
+
+    #[test]
+    fn test_builder_totals() {
+        let home = Path::new("/home/u");
+        let mut builder = Builder::new("clean_items", "t", "m");
+        builder.push(Path::new("/home/u/.cache/a/x"), 10, 0, "Cache", home);
+        builder.push(Path::new("/home/u/.config/b"), 30, 3, "Application Data", home);
+        builder.irreversible = true;
+        let description = builder.finish();
+
+        assert_eq!(description.item_count, 2);
+        assert_eq!(description.total_size, 40);
+        assert_eq!(description.highest_risk_level, 3);
+        assert_eq!(description.items[0].size, 30);
+        assert_eq!(description.affected_apps, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(description.dialog_type, "danger");
+    }
+}
@@ -0,0 +1,320 @@
+//! Machine-readable JSON output for downstream tooling. Every result is wrapped
+//! in a document that names its schema and schema version, so consumers can
+//! tell what they're reading and notice when the shape changes:
+//!
+//! ```json
+//! { "schema": "pulito.scan", "schema_version": 1, "pulito_version": "1.0.0",
+//!   "generated_at": "2026-01-01T00:00:00+00:00", "data": { ... } }
+//! ```
+//!
+//! The app exports results it already holds with `export_result_json`; launched
+//! as `pulito --json <kind> [--output <path>]` it runs headless, computes the
+//! result, writes the document to the path or stdout and exits.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io::Write;
+use std::path::Path;
+
+use crate::batch::{self, BatchReport};
+use crate::commands::{self, CacheAnalytics, SystemStats};
+use crate::disk_health::{self, SsdHealthReport};
+use crate::scanner::{glob, FilesystemHealthResults, ScanOptions, ScanResults, StorageRecoveryResults};
+
+/// Bumped whenever a field is removed or changes meaning; new fields don't bump it
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const JSON_FLAG: &str = "--json";
+pub const OUTPUT_FLAG: &str = "--output";
+pub const BATCH_FLAG: &str = "--batch";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum ResultKind {
+    Scan,
+    StorageRecovery,
+    FilesystemHealth,
+    SystemStats,
+    DiskHealth,
+    CacheAnalytics,
+    Batch,
+}
+
+impl ResultKind {
+    pub const ALL: [ResultKind; 7] = [
+        ResultKind::Scan,
+        ResultKind::StorageRecovery,
+        ResultKind::FilesystemHealth,
+        ResultKind::SystemStats,
+        ResultKind::DiskHealth,
+        ResultKind::CacheAnalytics,
+        ResultKind::Batch,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResultKind::Scan => "scan",
+            ResultKind::StorageRecovery => "storage_recovery",
+            ResultKind::FilesystemHealth => "filesystem_health",
+            ResultKind::SystemStats => "system_stats",
+            ResultKind::DiskHealth => "disk_health",
+            ResultKind::CacheAnalytics => "cache_analytics",
+            ResultKind::Batch => "batch",
+        }
+    }
+
+    fn parse(name: &str) -> Option<ResultKind> {
+        ResultKind::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct JsonExport {
+    pub kind: ResultKind,
+    pub schema_version: u32,
+    pub content: String,
+    pub written_to: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonDocument<'a, T: Serialize> {
+    schema: String,
+    schema_version: u32,
+    pulito_version: &'static str,
+    generated_at: String,
+    data: &'a T,
+}
+
+/// Pretty-printed document for `data`, ending in a newline
+pub fn to_document<T: Serialize>(kind: ResultKind, data: &T) -> Result<String, String> {
+    let document = JsonDocument {
+        schema: format!("pulito.{}", kind.as_str()),
+        schema_version: SCHEMA_VERSION,
+        pulito_version: env!("CARGO_PKG_VERSION"),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        data,
+    };
+    let mut content =
+        serde_json::to_string_pretty(&document).map_err(|e| format!("Failed to serialize {}: {}", kind.as_str(), e))?;
+    content.push('\n');
+    Ok(content)
+}
+
+fn typed_document<T: Serialize + serde::de::DeserializeOwned>(
+    kind: ResultKind,
+    data: serde_json::Value,
+) -> Result<String, String> {
+    let typed: T = serde_json::from_value(data).map_err(|e| format!("Not a {} result: {}", kind.as_str(), e))?;
+    to_document(kind, &typed)
+}
+
+/// Document for a result passed in from the frontend, checked against its type
+/// so the output has the same shape whichever side produced it
+fn document_from_value(kind: ResultKind, data: serde_json::Value) -> Result<String, String> {
+    match kind {
+        ResultKind::Scan => typed_document::<ScanResults>(kind, data),
+        ResultKind::StorageRecovery => typed_document::<StorageRecoveryResults>(kind, data),
+        ResultKind::FilesystemHealth => typed_document::<FilesystemHealthResults>(kind, data),
+        ResultKind::SystemStats => typed_document::<SystemStats>(kind, data),
+        ResultKind::DiskHealth => typed_document::<SsdHealthReport>(kind, data),
+        ResultKind::CacheAnalytics => typed_document::<CacheAnalytics>(kind, data),
+        ResultKind::Batch => typed_document::<BatchReport>(kind, data),
+    }
+}
+
+/// Write `content` to `path` through a temporary file, so readers never see half a
+/// document, or to stdout without a path
+pub fn write_output(content: &str, path: Option<&Path>) -> Result<(), String> {
+    let Some(path) = path else {
+        let mut stdout = std::io::stdout().lock();
+        return stdout
+            .write_all(content.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("Failed to write to stdout: {}", e));
+    };
+
+    let file_name = path.file_name().ok_or_else(|| format!("{} is not a file path", path.display()))?;
+    let partial = path.with_file_name(format!(".{}.partial", file_name.to_string_lossy()));
+    std::fs::write(&partial, content).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    std::fs::rename(&partial, path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial);
+        format!("Failed to write {}: {}", path.display(), e)
+    })
+}
+
+/// Export a result the frontend holds as a JSON document, written to `output_path` when given
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn export_result_json(
+    kind: ResultKind,
+    data: serde_json::Value,
+    output_path: Option<String>,
+) -> Result<JsonExport, String> {
+    tokio::task::spawn_blocking(move || {
+        let content = document_from_value(kind, data)?;
+        let written_to = match output_path {
+            Some(path) => {
+                let home = dirs::home_dir().unwrap_or_default();
+                let path = glob::expand_home(path.trim(), &home);
+                write_output(&content, Some(&path))?;
+                tracing::info!("Exported {} result to {}", kind.as_str(), path.display());
+                Some(path.to_string_lossy().to_string())
+            }
+            None => None,
+        };
+        Ok(JsonExport { kind, schema_version: SCHEMA_VERSION, content, written_to })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// What `pulito --json <kind> [--output <path>] [--batch <file>]` asks for
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliRequest {
+    pub kind: ResultKind,
+    pub output: Option<String>, // stdout when unset
+    pub batch_file: Option<String>, // operations file for the batch kind
+}
+
+/// Parse the launch arguments; None when not launched with `--json`
+pub fn cli_request(args: impl Iterator<Item = String>) -> Result<Option<CliRequest>, String> {
+    let mut kind = None;
+    let mut output = None;
+    let mut batch_file = None;
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            JSON_FLAG => {
+                let name = args.next().unwrap_or_default();
+                let parsed = ResultKind::parse(&name).ok_or_else(|| {
+                    let kinds: Vec<&str> = ResultKind::ALL.iter().map(|k| k.as_str()).collect();
+                    format!("{} expects one of: {}", JSON_FLAG, kinds.join(", "))
+                })?;
+                kind = Some(parsed);
+                continue;
+            }
+            OUTPUT_FLAG => &mut output,
+            BATCH_FLAG => &mut batch_file,
+            _ => continue,
+        };
+        *target = Some(args.next().filter(|v| !v.starts_with("--")).ok_or_else(|| format!("{} expects a path", arg))?);
+    }
+
+    let Some(kind) = kind else {
+        return Ok(None);
+    };
+    if (kind == ResultKind::Batch) != batch_file.is_some() {
+        return Err(format!("{} is required with `{} batch`, and only there", BATCH_FLAG, JSON_FLAG));
+    }
+    Ok(Some(CliRequest { kind, output, batch_file }))
+}
+
+pub fn is_cli_mode() -> bool {
+    std::env::args().any(|arg| arg == JSON_FLAG)
+}
+
+async fn produce(app_handle: &tauri::AppHandle, request: &CliRequest) -> Result<String, String> {
+    let handle = app_handle.clone();
+    match request.kind {
+        ResultKind::Scan => to_document(request.kind, &commands::start_scan(handle, ScanOptions::default()).await?),
+        ResultKind::StorageRecovery => {
+            to_document(request.kind, &commands::scan_storage_recovery(handle, None, None, None, None).await?)
+        }
+        ResultKind::FilesystemHealth => to_document(request.kind, &commands::scan_filesystem_health(handle, None).await?),
+        ResultKind::SystemStats => to_document(request.kind, &commands::get_system_stats(handle).await?),
+        ResultKind::DiskHealth => to_document(request.kind, &disk_health::get_ssd_health().await?),
+        ResultKind::CacheAnalytics => to_document(request.kind, &commands::get_cache_analytics(handle).await?),
+        ResultKind::Batch => {
+            let file = request.batch_file.clone().unwrap_or_default();
+            // Nobody is there to confirm a real run, so the command line only describes one
+            let mut report = batch::run_batch(handle, file, Some(true), None).await?;
+            report.confirmation_token = None;
+            to_document(request.kind, &report)
+        }
+    }
+}
+
+/// Headless run for `--json`: write the document, then exit with 0, or 1 after
+/// printing the error to stderr
+pub async fn run_cli(app_handle: tauri::AppHandle) {
+    let result = match cli_request(std::env::args()) {
+        Ok(Some(request)) => match produce(&app_handle, &request).await {
+            Ok(content) => {
+                let home = dirs::home_dir().unwrap_or_default();
+                let path = request.output.as_deref().map(|p| glob::expand_home(p, &home));
+                write_output(&content, path.as_deref())
+            }
+            Err(e) => Err(e),
+        },
+        Ok(None) => Err(format!("Missing {}", JSON_FLAG)),
+        Err(e) => Err(e),
+    };
+    let code = match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("pulito: {}", e);
+            1
+        }
+    };
+    app_handle.exit(code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter().map(|a| a.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn test_cli_request() {
+        assert_eq!(cli_request(args(&["pulito"])).unwrap(), None);
+        assert_eq!(
+            cli_request(args(&["pulito", "--json", "storage_recovery", "--output", "/tmp/out.json"])).unwrap(),
+            Some(CliRequest {
+                kind: ResultKind::StorageRecovery,
+                output: Some("/tmp/out.json".to_string()),
+                batch_file: None
+            })
+        );
+        assert!(cli_request(args(&["pulito", "--json", "everything"])).is_err());
+        assert!(cli_request(args(&["pulito", "--json", "scan", "--output"])).is_err());
+        assert!(cli_request(args(&["pulito", "--json", "batch"])).is_err());
+        assert!(cli_request(args(&["pulito", "--json", "batch", "--batch", "ops.json"])).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_document() {
+        let stats = serde_json::json!({ "cleaned": 1 });
+        assert!(document_from_value(ResultKind::Scan, stats).is_err());
+
+        let report = BatchReport {
+            path: "/tmp/ops.json".to_string(),
+            name: None,
+            dry_run: true,
+            started_at: 0,
+            duration_ms: 5,
+            steps: Vec::new(),
+            total_found: 0,
+            total_freed: 0,
+            total_would_free: 0,
+            failed_steps: 0,
+            stopped_early: false,
+            confirmation_token: None,
+        };
+        let content = document_from_value(ResultKind::Batch, serde_json::to_value(&report).unwrap()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["schema"], "pulito.batch");
+        assert_eq!(parsed["schema_version"], SCHEMA_VERSION);
+        assert_eq!(parsed["data"]["duration_ms"], 5);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        write_output(&content, Some(&path)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,340 @@
+//! Old kernels on Debian and Ubuntu. Every kernel update installs a new
+//! `linux-image-*` with matching modules and headers and keeps the previous ones,
+//! which fill up /boot. All but the running kernel and the newest kernel of
+//! each flavour (generic, lowlatency, ...) are offered for removal, and removal
+//! refuses those whatever it is asked.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::privileged::{self, PrivilegedOp};
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Packages";
+const BOOT_DIR: &str = "/boot";
+
+/// Package name prefixes followed by a kernel release; longer prefixes first
+const KERNEL_PACKAGE_PREFIXES: &[&str] =
+    &["linux-image-unsigned-", "linux-image-", "linux-modules-extra-", "linux-modules-", "linux-headers-"];
+
+/// An installed kernel and the packages that belong to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledKernel {
+    pub release: String, // as `uname -r` prints it, e.g. 6.5.0-14-generic
+    pub packages: Vec<String>,
+    pub size: u64, // installed size of the packages plus the generated initramfs
+}
+
+impl InstalledKernel {
+    pub fn image_path(&self) -> PathBuf {
+        Path::new(BOOT_DIR).join(format!("vmlinuz-{}", self.release))
+    }
+}
+
+/// The kernel release in a kernel package's name; meta packages such as
+/// `linux-image-generic` have none
+fn package_release(package: &str) -> Option<&str> {
+    let release = KERNEL_PACKAGE_PREFIXES.iter().find_map(|prefix| package.strip_prefix(prefix))?;
+    release.starts_with(|c: char| c.is_ascii_digit()).then_some(release)
+}
+
+/// Numeric parts of a release for ordering, 6.5.0-14-generic is [6, 5, 0, 14]
+fn release_key(release: &str) -> Vec<u64> {
+    release.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty()).filter_map(|p| p.parse().ok()).collect()
+}
+
+/// What follows the version in a release, 6.5.0-14-generic is `generic`
+fn flavour(release: &str) -> &str {
+    release.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+/// Group installed kernel packages, given as (name, installed bytes), by the
+/// image they belong to. Headers are often named without the flavour
+/// (`linux-headers-6.5.0-14` next to `linux-headers-6.5.0-14-generic`); those
+/// are left out when kernels of another flavour share the version and need them.
+pub fn group_kernels(packages: &[(String, u64)]) -> Vec<InstalledKernel> {
+    let mut kernels: Vec<InstalledKernel> = packages
+        .iter()
+        .filter(|(name, _)| name.starts_with("linux-image-"))
+        .filter_map(|(name, _)| package_release(name))
+        .map(|release| InstalledKernel { release: release.to_string(), packages: Vec::new(), size: 0 })
+        .collect();
+    kernels.sort_by(|a, b| release_key(&a.release).cmp(&release_key(&b.release)));
+    kernels.dedup_by(|a, b| a.release == b.release);
+
+    let releases: Vec<String> = kernels.iter().map(|k| k.release.clone()).collect();
+    let unflavoured_of = |version: &str, release: &str| release.starts_with(&format!("{}-", version));
+    for kernel in &mut kernels {
+        for (name, size) in packages {
+            let belongs = package_release(name).is_some_and(|version| {
+                version == kernel.release
+                    || (unflavoured_of(version, &kernel.release) && releases.iter().filter(|r| unflavoured_of(version, r)).count() == 1)
+            });
+            if belongs {
+                kernel.packages.push(name.clone());
+                kernel.size += size;
+            }
+        }
+    }
+    kernels
+}
+
+fn parse_dpkg_query(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('|');
+            let (name, size, status) = (fields.next()?, fields.next()?, fields.next()?);
+            // "ii" is installed; removed packages with leftover config are "rc"
+            status.starts_with("ii").then(|| (name.to_string(), size.trim().parse::<u64>().unwrap_or(0) * 1024))
+        })
+        .collect()
+}
+
+/// Kernels installed through dpkg, oldest first
+pub fn installed_kernels() -> Vec<InstalledKernel> {
+    let output = Command::new("dpkg-query")
+        .args(["-W", "-f", "${Package}|${Installed-Size}|${db:Status-Abbrev}\n"])
+        .args(KERNEL_PACKAGE_PREFIXES.iter().map(|prefix| format!("{}*", prefix)))
+        .output();
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    let mut kernels = group_kernels(&parse_dpkg_query(&String::from_utf8_lossy(&output.stdout)));
+    // The initramfs is generated on install and isn't part of any package
+    for kernel in &mut kernels {
+        let initrd = Path::new(BOOT_DIR).join(format!("initrd.img-{}", kernel.release));
+        kernel.size += std::fs::metadata(initrd).map(|m| m.len()).unwrap_or(0);
+    }
+    kernels
+}
+
+pub fn running_release() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease").ok().map(|r| r.trim().to_string())
+}
+
+/// Kernels that are neither running nor the newest installed of their
+/// flavour, so switching to a lowlatency kernel doesn't lose the generic one
+pub fn removable_kernels(kernels: &[InstalledKernel], running: Option<&str>) -> Vec<InstalledKernel> {
+    let is_newest = |kernel: &InstalledKernel| {
+        !kernels
+            .iter()
+            .any(|k| flavour(&k.release) == flavour(&kernel.release) && release_key(&k.release) > release_key(&kernel.release))
+    };
+    kernels
+        .iter()
+        .filter(|k| !is_newest(k) && Some(k.release.as_str()) != running)
+        .cloned()
+        .collect()
+}
+
+/// The kernels to remove for `releases`, refusing the whole request if it
+/// names the running kernel, the newest of a flavour, or a kernel that isn't installed
+pub fn check_removal(releases: &[String], kernels: &[InstalledKernel], running: Option<&str>) -> Result<Vec<InstalledKernel>, String> {
+    // Without knowing what is running, nothing is safe to remove
+    let running = running.ok_or("Cannot determine the running kernel")?;
+    let removable = removable_kernels(kernels, Some(running));
+    releases
+        .iter()
+        .map(|release| {
+            if release == running {
+                return Err(format!("Refusing to remove the running kernel {}", release));
+            }
+            match removable.iter().find(|k| &k.release == release) {
+                Some(kernel) => Ok(kernel.clone()),
+                None if kernels.iter().any(|k| &k.release == release) => {
+                    Err(format!("Refusing to remove {}, the newest installed {} kernel", release, flavour(release)))
+                }
+                None => Err(format!("Kernel {} is not installed", release)),
+            }
+        })
+        .collect()
+}
+
+/// The apt command purging the kernel `release`, for the privileged helper to
+/// run; it checks the release against the installed kernels itself
+pub fn purge_command(release: &str) -> Result<(&'static str, Vec<String>), String> {
+    let kernels = check_removal(&[release.to_string()], &installed_kernels(), running_release().as_deref())?;
+    let packages = kernels.into_iter().flat_map(|k| k.packages);
+    Ok(("apt-get", ["purge".to_string(), "-y".to_string()].into_iter().chain(packages).collect()))
+}
+
+pub struct OldKernelsScanner;
+
+impl Scanner for OldKernelsScanner {
+    fn id(&self) -> &'static str {
+        "old_kernels"
+    }
+
+    fn name(&self) -> &'static str {
+        "Old kernels"
+    }
+
+    fn description(&self) -> &'static str {
+        "Kernels other than the running one and the newest of each flavour"
+    }
+
+    /// On for dpkg-based systems
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_packages && Path::new("/var/lib/dpkg/status").exists()
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        ctx.progress(0, "Looking for old kernels...");
+        let Some(running) = running_release() else {
+            return Ok(Vec::new());
+        };
+        let kernels = installed_kernels();
+        ctx.check_cancelled()?;
+
+        Ok(removable_kernels(&kernels, Some(&running))
+            .into_iter()
+            .map(|kernel| {
+                // Still a fallback if the newer kernels fail to boot
+                let mut item = new_item(
+                    format!("Linux {}", kernel.release),
+                    &kernel.image_path(),
+                    kernel.size,
+                    "kernel",
+                    CATEGORY,
+                    1,
+                    &format!("Older kernel, {} packages; running {}", kernel.packages.len(), running),
+                );
+                item.dependencies = Some(kernel.packages);
+                item
+            })
+            .collect())
+    }
+}
+
+/// Purge old kernels and their modules and headers with apt. The running and
+/// newest kernels are refused even if the confirmed plan named them.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn remove_old_kernels(
+    app_handle: tauri::AppHandle,
+    releases: Vec<String>,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::RemoveOldKernels { releases: releases.clone() }, &confirmation_token)?;
+
+    let handle = app_handle.clone();
+    let (before, after) = tokio::task::spawn_blocking(move || {
+        let targets = check_removal(&releases, &installed_kernels(), running_release().as_deref())?;
+        if targets.is_empty() {
+            return Ok((targets, Vec::new()));
+        }
+        let ops = targets.iter().map(|k| PrivilegedOp::PurgeKernel { release: k.release.clone() }).collect();
+        for (kernel, output) in targets.iter().zip(privileged::run_all(&handle, ops)?) {
+            if !output.success {
+                tracing::warn!("Removing kernel {} reported errors: {}", kernel.release, output.stderr.trim());
+            }
+        }
+        Ok::<_, String>((targets, installed_kernels()))
+    })
+    .await
+    .map_err(|e| format!("Kernel task failed: {}", e))??;
+
+    let removed: Vec<&InstalledKernel> = before.iter().filter(|k| !after.iter().any(|a| a.release == k.release)).collect();
+    let cleaned = removed.len();
+    let failed = before.len() - cleaned;
+    let total_size: u64 = removed.iter().map(|k| k.size).sum();
+    tracing::info!("Removed {} old kernels ({} bytes), {} failed", cleaned, total_size, failed);
+
+    if total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed() -> Vec<InstalledKernel> {
+        let output = "linux-image-6.5.0-14-generic|14000|ii \n\
+                      linux-modules-6.5.0-14-generic|100000|ii \n\
+                      linux-modules-extra-6.5.0-14-generic|300000|ii \n\
+                      linux-headers-6.5.0-14|80000|ii \n\
+                      linux-headers-6.5.0-14-generic|20000|ii \n\
+                      linux-image-6.5.0-9-generic|14000|ii \n\
+                      linux-modules-6.5.0-9-generic|100000|ii \n\
+                      linux-image-6.2.0-39-generic|13000|rc \n\
+                      linux-image-6.8.0-31-generic|15000|ii \n\
+                      linux-image-generic|10|ii \n\
+                      linux-headers-generic|10|ii \n";
+        group_kernels(&parse_dpkg_query(output))
+    }
+
+    #[test]
+    fn test_group_kernels() {
+        let kernels = installed();
+        let releases: Vec<&str> = kernels.iter().map(|k| k.release.as_str()).collect();
+        assert_eq!(releases, vec!["6.5.0-9-generic", "6.5.0-14-generic", "6.8.0-31-generic"]);
+        assert_eq!(kernels[1].packages.len(), 5);
+        assert_eq!(kernels[1].size, 514_000 * 1024);
+        assert_eq!(kernels[1].image_path(), PathBuf::from("/boot/vmlinuz-6.5.0-14-generic"));
+    }
+
+    #[test]
+    fn test_shared_headers_stay_with_other_flavours() {
+        let output = "linux-image-6.5.0-14-generic|14000|ii \n\
+                      linux-headers-6.5.0-14-generic|20000|ii \n\
+                      linux-image-6.5.0-14-lowlatency|14000|ii \n\
+                      linux-headers-6.5.0-14-lowlatency|20000|ii \n\
+                      linux-headers-6.5.0-14|80000|ii \n";
+        let kernels = group_kernels(&parse_dpkg_query(output));
+        assert_eq!(kernels.len(), 2);
+        for kernel in &kernels {
+            assert_eq!(kernel.packages.len(), 2);
+            assert!(!kernel.packages.contains(&"linux-headers-6.5.0-14".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_running_and_newest_are_kept() {
+        let kernels = installed();
+        let removable = removable_kernels(&kernels, Some("6.5.0-14-generic"));
+        assert_eq!(removable.iter().map(|k| k.release.as_str()).collect::<Vec<_>>(), vec!["6.5.0-9-generic"]);
+
+        let ask = |releases: &[&str]| releases.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+        assert_eq!(check_removal(&ask(&["6.5.0-9-generic"]), &kernels, Some("6.5.0-14-generic")).unwrap(), removable);
+        assert!(check_removal(&ask(&["6.5.0-9-generic", "6.5.0-14-generic"]), &kernels, Some("6.5.0-14-generic"))
+            .unwrap_err()
+            .contains("running"));
+        assert!(check_removal(&ask(&["6.8.0-31-generic"]), &kernels, Some("6.5.0-14-generic")).unwrap_err().contains("newest"));
+        assert!(check_removal(&ask(&["6.2.0-39-generic"]), &kernels, Some("6.5.0-14-generic")).is_err());
+        assert!(check_removal(&ask(&["6.5.0-9-generic"]), &kernels, None).is_err());
+    }
+
+    #[test]
+    fn test_newest_of_each_flavour_is_kept() {
+        let output = "linux-image-6.5.0-14-generic|14000|ii \n\
+                      linux-image-6.8.0-31-generic|15000|ii \n\
+                      linux-image-6.5.0-14-lowlatency|14000|ii \n\
+                      linux-image-6.8.0-31-lowlatency|15000|ii \n";
+        let kernels = group_kernels(&parse_dpkg_query(output));
+        assert_eq!(flavour("6.8.0-31-lowlatency"), "lowlatency");
+
+        let removable = removable_kernels(&kernels, Some("6.8.0-31-generic"));
+        let mut releases: Vec<&str> = removable.iter().map(|k| k.release.as_str()).collect();
+        releases.sort();
+        assert_eq!(releases, vec!["6.5.0-14-generic", "6.5.0-14-lowlatency"]);
+
+        let ask = |releases: &[&str]| releases.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+        assert!(check_removal(&ask(&["6.8.0-31-lowlatency"]), &kernels, Some("6.8.0-31-generic")).unwrap_err().contains("newest"));
+    }
+}
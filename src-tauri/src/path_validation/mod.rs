@@ -0,0 +1,582 @@
+//! Validation of paths before anything destructive happens to them. Every
+//! check reads the system through a `PathEnvironment`, so tests and fuzzers
+//! can drive `validate_path_with` against a made-up home directory, symlinks
+//! and owners instead of the real filesystem.
+//!
+//! Security layers:
+//! 1. Path traversal protection, also after percent-decoding and folding
+//!    Unicode lookalikes of `.`, `/` and `\`
+//! 2. Absolute POSIX path requirement (Windows-style paths are refused)
+//! 3. Canonicalization, which resolves symlinks
+//! 4. Context-aware system-critical path detection
+//! 5. File system boundary validation
+//! 6. Permission and ownership verification
+//!
+//! `ReadWrite` only creates a file of Pulito's own, so it skips the boundary
+//! and ownership checks: any mounted directory qualifies.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The kind of operation a path is validated for
+#[derive(Debug, Clone)]
+pub enum SecurityContext {
+    Deletion,
+    CacheCleanup,
+    PackageManagement,
+    LogCleanup,
+    #[allow(dead_code)]
+    StartupManagement,
+    /// Writing, reading back and removing a scratch file in a directory, as the
+    /// disk benchmark does; nothing already there is touched
+    ReadWrite,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecurityError {
+    #[error("Path traversal detected: {path}")]
+    PathTraversal { path: String },
+    #[error("Non-absolute path: {path}")]
+    NonAbsolutePath { path: String },
+    #[error("System critical path: {path}")]
+    SystemCriticalPath { path: String },
+    #[error("Permission denied: {path}")]
+    PermissionDenied { path: String },
+    #[error("Path outside allowed boundaries: {path}")]
+    OutsideBoundaries { path: String },
+    #[error("File does not exist: {path}")]
+    PathDoesNotExist { path: String },
+    #[error("Security violation: {message}")]
+    SecurityViolation { message: String },
+}
+
+/// What the permission check needs to know about a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileInfo {
+    pub readonly: bool,
+    pub uid: u32,
+}
+
+/// The parts of the system path validation looks at
+pub trait PathEnvironment {
+    fn home_dir(&self) -> Option<PathBuf>;
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    fn file_info(&self, path: &Path) -> io::Result<FileInfo>;
+    fn current_uid(&self) -> u32;
+}
+
+/// The real filesystem and user
+pub struct SystemEnvironment;
+
+impl PathEnvironment for SystemEnvironment {
+    fn home_dir(&self) -> Option<PathBuf> {
+        dirs::home_dir()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn file_info(&self, path: &Path) -> io::Result<FileInfo> {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = path.metadata()?;
+        Ok(FileInfo { readonly: metadata.permissions().readonly(), uid: metadata.uid() })
+    }
+
+    fn current_uid(&self) -> u32 {
+        unsafe { libc::getuid() }
+    }
+}
+
+/// Always forbidden, whatever the context
+const ALWAYS_FORBIDDEN: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/run", "/sbin", "/sys",
+    "/usr/bin", "/usr/sbin", "/usr/lib", "/usr/local/bin",
+    "/var/lib", "/var/run", "/var/lock", "/var/spool",
+    "/root", "/home/root",
+    "/etc/passwd", "/etc/shadow", "/etc/sudoers",
+];
+
+/// Outside home, only these are ever touched
+const ALLOWED_SYSTEM_PATHS: &[&str] = &["/var/cache", "/tmp"];
+
+/// Characters that Unicode normalization (NFKC) or a careless renderer turns
+/// into `.`, `/` or `\`
+const LOOKALIKES: &[(char, &str)] = &[
+    ('\u{FF0E}', "."),   // fullwidth full stop
+    ('\u{FE52}', "."),   // small full stop
+    ('\u{2024}', "."),   // one dot leader
+    ('\u{2025}', ".."),  // two dot leader
+    ('\u{2026}', "..."), // horizontal ellipsis
+    ('\u{FF0F}', "/"),   // fullwidth solidus
+    ('\u{2215}', "/"),   // division slash
+    ('\u{2044}', "/"),   // fraction slash
+    ('\u{29F8}', "/"),   // big solidus
+    ('\u{FF3C}', "\\"),  // fullwidth reverse solidus
+    ('\u{FE68}', "\\"),  // small reverse solidus
+    ('\u{29F9}', "\\"),  // big reverse solidus
+];
+
+/// Comprehensive path validation with multiple security layers
+pub fn validate_path_comprehensive(path: &str, context: SecurityContext) -> Result<(), SecurityError> {
+    validate_path_with(&SystemEnvironment, path, context)
+}
+
+/// `validate_path_comprehensive` against any environment
+pub fn validate_path_with(env: &impl PathEnvironment, path: &str, context: SecurityContext) -> Result<(), SecurityError> {
+    // Layer 1: Multi-level path traversal protection
+    validate_path_traversal(path)?;
+
+    // Layer 2: Absolute path requirement
+    if is_windows_style(path) || !Path::new(path).is_absolute() {
+        return Err(SecurityError::NonAbsolutePath { path: path.to_string() });
+    }
+
+    // Layer 3: Canonical path resolution (resolves symlinks and relative paths)
+    let canonical_path = env.canonicalize(Path::new(path)).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => SecurityError::PathDoesNotExist { path: path.to_string() },
+        _ => SecurityError::SecurityViolation { message: format!("Cannot canonicalize path {}: {}", path, e) },
+    })?;
+
+    // Layer 4: Context-aware system-critical path validation
+    validate_system_critical_paths(&canonical_path, &context)?;
+
+    // Layer 5: File system boundary validation
+    if !matches!(context, SecurityContext::ReadWrite) {
+        validate_filesystem_boundaries(env, &canonical_path)?;
+    }
+
+    // Layer 6: Permission validation
+    validate_permissions(env, &canonical_path, &context)
+}
+
+/// Replace Unicode lookalikes of `.`, `/` and `\` with the ASCII character
+fn fold_lookalikes(path: &str) -> String {
+    let mut folded = String::with_capacity(path.len());
+    for c in path.chars() {
+        match LOOKALIKES.iter().find(|(lookalike, _)| *lookalike == c) {
+            Some((_, ascii)) => folded.push_str(ascii),
+            None => folded.push(c),
+        }
+    }
+    folded
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|value| value as u8)
+}
+
+/// One round of percent-decoding, case-insensitive. Overlong UTF-8 forms of
+/// ASCII (`%c0%ae` for `.`) decode to the ASCII character, as lenient decoders do.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = match bytes.get(i + 1..i + 3) {
+            Some(&[high, low]) => hex_value(high).zip(hex_value(low)).map(|(high, low)| (high << 4) | low),
+            _ => None,
+        };
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    let mut folded = Vec::with_capacity(decoded.len());
+    let mut i = 0;
+    while i < decoded.len() {
+        match decoded.get(i..i + 2) {
+            Some(&[lead @ (0xC0 | 0xC1), next]) if next & 0xC0 == 0x80 => {
+                folded.push(((lead & 0x1F) << 6) | (next & 0x3F));
+                i += 2;
+            }
+            _ => {
+                folded.push(decoded[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&folded).into_owned()
+}
+
+/// The path and the forms a lenient decoder could turn it into: percent-decoded
+/// up to three times (double and triple encoding), each with lookalikes folded
+fn decoded_forms(path: &str) -> Vec<String> {
+    let mut forms = vec![path.to_string()];
+    for _ in 0..3 {
+        let next = percent_decode(forms.last().unwrap());
+        if forms.contains(&next) {
+            break;
+        }
+        forms.push(next);
+    }
+    let folded: Vec<String> = forms.iter().map(|form| fold_lookalikes(form)).collect();
+    forms.extend(folded);
+    forms
+}
+
+/// Multi-level path traversal protection
+pub(crate) fn validate_path_traversal(path: &str) -> Result<(), SecurityError> {
+    // `..` also covers `..\` and `\..` with Windows separators; a NUL would
+    // end the path early for C code that sees it later
+    if decoded_forms(path).iter().any(|form| form.contains("..") || form.contains('\0')) {
+        return Err(SecurityError::PathTraversal { path: path.to_string() });
+    }
+
+    Ok(())
+}
+
+/// Drive letters (`C:\`, `c:/`) and UNC or device paths (`\\server\share`, `\\?\`),
+/// which no POSIX tool reads as the path they look like
+fn is_windows_style(path: &str) -> bool {
+    let folded = fold_lookalikes(path);
+    let bytes = folded.as_bytes();
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    drive || folded.starts_with("\\\\") || folded.starts_with('\\')
+}
+
+fn forbidden_prefix<'a>(canonical_path: &Path, prefixes: &[&'a str]) -> Option<&'a str> {
+    prefixes.iter().find(|prefix| canonical_path.starts_with(prefix)).copied()
+}
+
+/// Context-aware system-critical path validation. Prefixes match whole
+/// components, so `/bin` covers `/bin/ls` but not `/binaries`.
+fn validate_system_critical_paths(canonical_path: &Path, context: &SecurityContext) -> Result<(), SecurityError> {
+    let critical = |prefix: &str| SecurityError::SystemCriticalPath { path: prefix.to_string() };
+
+    if let Some(prefix) = forbidden_prefix(canonical_path, ALWAYS_FORBIDDEN) {
+        return Err(critical(prefix));
+    }
+
+    // Context-specific restrictions
+    match context {
+        SecurityContext::Deletion => {
+            // For general deletion, be more restrictive
+            if let Some(prefix) = forbidden_prefix(canonical_path, &["/usr", "/opt", "/var"]) {
+                return Err(critical(prefix));
+            }
+        }
+        SecurityContext::CacheCleanup => {
+            // For cache cleanup, allow more system paths but still protect critical ones
+            if let Some(prefix) = forbidden_prefix(canonical_path, &["/etc", "/usr/bin"]) {
+                return Err(critical(prefix));
+            }
+        }
+        SecurityContext::PackageManagement => {
+            // Package management can operate in system areas but not critical config
+            if canonical_path.starts_with("/etc") && !canonical_path.starts_with("/etc/apt") {
+                return Err(critical("/etc"));
+            }
+        }
+        SecurityContext::LogCleanup => {
+            // Log cleanup can be more permissive in user areas
+        }
+        SecurityContext::StartupManagement => {
+            // Only allow modification of user-owned files; block system-wide service files
+            if canonical_path.starts_with("/etc/systemd/system") {
+                return Err(critical("/etc/systemd/system"));
+            }
+        }
+        SecurityContext::ReadWrite => {
+            // Only Pulito's own scratch file is written, so ALWAYS_FORBIDDEN is enough
+        }
+    }
+
+    Ok(())
+}
+
+/// File system boundary validation: the home directory, plus a few system caches
+fn validate_filesystem_boundaries(env: &impl PathEnvironment, canonical_path: &Path) -> Result<(), SecurityError> {
+    let home = env.home_dir().ok_or_else(|| SecurityError::SecurityViolation {
+        message: "Cannot determine home directory".to_string(),
+    })?;
+
+    if !canonical_path.starts_with(home) && forbidden_prefix(canonical_path, ALLOWED_SYSTEM_PATHS).is_none() {
+        return Err(SecurityError::OutsideBoundaries { path: canonical_path.to_string_lossy().to_string() });
+    }
+
+    Ok(())
+}
+
+/// Permission validation: writable, and owned by the current user unless that's
+/// root or only a scratch file is written there
+fn validate_permissions(env: &impl PathEnvironment, canonical_path: &Path, context: &SecurityContext) -> Result<(), SecurityError> {
+    let denied = || SecurityError::PermissionDenied { path: canonical_path.to_string_lossy().to_string() };
+    let info = env.file_info(canonical_path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => SecurityError::PathDoesNotExist { path: canonical_path.to_string_lossy().to_string() },
+        _ => SecurityError::SecurityViolation { message: format!("Cannot access file metadata: {}", e) },
+    })?;
+
+    if info.readonly {
+        return Err(denied());
+    }
+    let current_uid = env.current_uid();
+    if current_uid != 0 && current_uid != info.uid && !matches!(context, SecurityContext::ReadWrite) {
+        return Err(denied());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    const HOME: &str = "/home/alice";
+    const UID: u32 = 1000;
+
+    /// A made-up filesystem: known files with their owners, and symlinks
+    #[derive(Default)]
+    struct FakeEnvironment {
+        files: HashMap<PathBuf, FileInfo>,
+        links: HashMap<PathBuf, PathBuf>,
+    }
+
+    impl FakeEnvironment {
+        fn new() -> Self {
+            Self::default()
+        }
+
+        fn file(mut self, path: &str, uid: u32) -> Self {
+            self.files.insert(PathBuf::from(path), FileInfo { readonly: false, uid });
+            self
+        }
+
+        fn link(mut self, path: &str, target: &str) -> Self {
+            self.links.insert(PathBuf::from(path), PathBuf::from(target));
+            self
+        }
+    }
+
+    impl PathEnvironment for FakeEnvironment {
+        fn home_dir(&self) -> Option<PathBuf> {
+            Some(PathBuf::from(HOME))
+        }
+
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            let resolved = self.links.get(path).cloned().unwrap_or_else(|| path.to_path_buf());
+            if self.files.contains_key(&resolved) {
+                Ok(resolved)
+            } else {
+                Err(io::ErrorKind::NotFound.into())
+            }
+        }
+
+        fn file_info(&self, path: &Path) -> io::Result<FileInfo> {
+            self.files.get(path).copied().ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn current_uid(&self) -> u32 {
+            UID
+        }
+    }
+
+    #[test]
+    fn test_path_traversal_protection() {
+        // Test basic path traversal
+        assert!(validate_path_traversal("/home/user/../etc/passwd").is_err());
+        assert!(validate_path_traversal("/home/user/../../etc/passwd").is_err());
+        assert!(validate_path_traversal("/home/user/..\\etc\\passwd").is_err());
+
+        // Test URL-encoded traversal
+        assert!(validate_path_traversal("/home/user/%2e%2e%2fetc/passwd").is_err());
+        assert!(validate_path_traversal("/home/user/%2e%2e/etc/passwd").is_err());
+
+        // Test valid paths (without ..)
+        assert!(validate_path_traversal("/home/user/documents").is_ok());
+        assert!(validate_path_traversal("/home/user/.cache").is_ok());
+    }
+
+    #[test]
+    fn test_encoded_and_lookalike_traversal() {
+        for path in [
+            "/home/user/%2E%2E%2Fetc",            // upper-case escapes
+            "/home/user/%252e%252e/etc",          // double encoding
+            "/home/user/%25252e%25252e/etc",      // triple encoding
+            "/home/user/%2e%2e%5cetc",            // encoded backslash
+            "/home/user/%c0%ae%c0%ae/etc",        // overlong UTF-8
+            "/home/user/\u{FF0E}\u{FF0E}/etc",    // fullwidth full stops
+            "/home/user/\u{2025}\u{FF0F}etc",     // two dot leader, fullwidth solidus
+            "/home/user/%EF%BC%8E%EF%BC%8E/etc",  // fullwidth full stops, encoded
+            "/home/user/file\0.txt",
+            "/home/user/file%00.txt",
+        ] {
+            assert!(validate_path_traversal(path).is_err(), "{:?}", path);
+        }
+        assert!(validate_path_traversal("/home/user/50%25 off.pdf").is_ok());
+        assert!(validate_path_traversal("/home/user/caf\u{E9}/r\u{E9}sum\u{E9}.txt").is_ok());
+    }
+
+    #[test]
+    fn test_windows_style_paths_are_not_absolute() {
+        let env = FakeEnvironment::new();
+        for path in ["C:\\Users\\alice", "c:/Users/alice", "\\\\server\\share\\x", "\\\\?\\C:\\x", "\u{FF3C}\u{FF3C}server\\x"] {
+            assert!(matches!(validate_path_with(&env, path, SecurityContext::Deletion), Err(SecurityError::NonAbsolutePath { .. })), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_system_critical_path_protection() {
+        // Test system paths are blocked for deletion context
+        // Note: These will fail on canonicalization/non-existence, but the intent is clear
+        let result = validate_path_comprehensive("/etc/passwd", SecurityContext::Deletion);
+        assert!(result.is_err());
+
+        let result = validate_path_comprehensive("/bin/ls", SecurityContext::Deletion);
+        assert!(result.is_err());
+
+        let result = validate_path_comprehensive("/usr/bin", SecurityContext::Deletion);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_critical_prefixes_match_whole_components() {
+        let critical = |path: &str| validate_system_critical_paths(Path::new(path), &SecurityContext::LogCleanup).is_err();
+        assert!(critical("/bin/ls"));
+        assert!(critical("/home/root/x"));
+        assert!(!critical("/binaries/x"));
+        assert!(!critical("/home/rootbeer/x"));
+    }
+
+    #[test]
+    fn test_symlink_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target.txt");
+        let symlink = temp_dir.path().join("symlink.txt");
+
+        std::fs::write(&target, "target").unwrap();
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&target, &symlink).unwrap();
+
+            // Canonicalization should resolve symlinks before validation
+            let canonical = symlink.canonicalize().unwrap();
+            assert_eq!(canonical, target.canonicalize().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_symlinks_are_validated_by_target() {
+        let env = FakeEnvironment::new()
+            .file("/home/alice/notes.txt", UID)
+            .file("/etc/shadow", 0)
+            .file("/srv/data", UID)
+            .link("/home/alice/shadow", "/etc/shadow")
+            .link("/home/alice/data", "/srv/data")
+            .link("/home/alice/notes", "/home/alice/notes.txt");
+        let validate = |path: &str| validate_path_with(&env, path, SecurityContext::Deletion);
+
+        assert!(validate("/home/alice/notes").is_ok());
+        assert!(matches!(validate("/home/alice/shadow"), Err(SecurityError::SystemCriticalPath { .. })));
+        assert!(matches!(validate("/home/alice/data"), Err(SecurityError::OutsideBoundaries { .. })));
+        assert!(matches!(validate("/home/alice/missing"), Err(SecurityError::PathDoesNotExist { .. })));
+    }
+
+    #[test]
+    fn test_home_boundary_and_ownership() {
+        let env = FakeEnvironment::new()
+            .file("/home/alice/mine", UID)
+            .file("/home/alice/theirs", UID + 1)
+            .file("/home/alicex/file", UID)
+            .file("/tmp/scratch", UID);
+        let validate = |path: &str| validate_path_with(&env, path, SecurityContext::CacheCleanup);
+
+        assert!(validate("/home/alice/mine").is_ok());
+        assert!(validate("/tmp/scratch").is_ok());
+        assert!(matches!(validate("/home/alice/theirs"), Err(SecurityError::PermissionDenied { .. })));
+        // A sibling whose name merely starts with the home directory's
+        assert!(matches!(validate("/home/alicex/file"), Err(SecurityError::OutsideBoundaries { .. })));
+    }
+
+    #[test]
+    fn test_read_write_allows_other_drives() {
+        let env = FakeEnvironment::new()
+            .file("/mnt/data", 0)
+            .file("/media/alice/usb", UID)
+            .file("/var/lib/docker", 0)
+            .link("/home/alice/disk", "/mnt/data")
+            .link("/home/alice/sys", "/var/lib/docker");
+        let validate = |path: &str| validate_path_with(&env, path, SecurityContext::ReadWrite);
+
+        assert!(validate("/mnt/data").is_ok());
+        assert!(validate("/media/alice/usb").is_ok());
+        assert!(validate("/home/alice/disk").is_ok());
+        assert!(matches!(validate("/home/alice/sys"), Err(SecurityError::SystemCriticalPath { .. })));
+        assert!(matches!(validate_path_with(&env, "/mnt/data", SecurityContext::Deletion), Err(SecurityError::OutsideBoundaries { .. })));
+    }
+
+    fn segment() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9_ -]{1,12}(\\.[a-z]{1,4})?"
+    }
+
+    /// `..` in one of the encodings and lookalikes a decoder might undo
+    fn dot_dot() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("..".to_string()),
+            Just("%2e%2e".to_string()),
+            Just("%2E%2E".to_string()),
+            Just("%252e%252e".to_string()),
+            Just("%c0%ae%c0%ae".to_string()),
+            Just("\u{FF0E}\u{FF0E}".to_string()),
+            Just(".\u{2024}".to_string()),
+            Just("\u{2025}".to_string()),
+        ]
+    }
+
+    fn separator() -> impl Strategy<Value = &'static str> {
+        prop_oneof![Just("/"), Just("\\"), Just("%2f"), Just("%5C"), Just("\u{FF0F}"), Just("\u{2215}")]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_traversal_is_always_rejected(
+            before in prop::collection::vec(segment(), 0..4),
+            dots in dot_dot(),
+            sep in separator(),
+            after in prop::collection::vec(segment(), 0..4),
+        ) {
+            let path = format!("/{}{}{}{}{}", before.join("/"), sep, dots, sep, after.join("/"));
+            prop_assert!(validate_path_traversal(&path).is_err(), "{:?}", path);
+        }
+
+        #[test]
+        fn prop_plain_paths_pass_traversal(segments in prop::collection::vec(segment(), 1..6)) {
+            let path = format!("/{}", segments.join("/"));
+            prop_assert!(validate_path_traversal(&path).is_ok(), "{:?}", path);
+        }
+
+        #[test]
+        fn prop_files_in_home_are_accepted(segments in prop::collection::vec(segment(), 1..6)) {
+            let path = format!("{}/{}", HOME, segments.join("/"));
+            let env = FakeEnvironment::new().file(&path, UID);
+            prop_assert!(validate_path_with(&env, &path, SecurityContext::Deletion).is_ok(), "{:?}", path);
+        }
+
+        #[test]
+        fn prop_links_out_of_home_are_rejected(
+            name in segment(),
+            target in prop::sample::select(vec!["/etc/passwd", "/usr/lib/x", "/var/lib/y", "/boot/vmlinuz", "/home/bob/z", "/home/alicex/w"]),
+        ) {
+            let link = format!("{}/{}", HOME, name);
+            let env = FakeEnvironment::new().file(target, UID).link(&link, target);
+            prop_assert!(validate_path_with(&env, &link, SecurityContext::Deletion).is_err(), "{} -> {}", link, target);
+        }
+
+        #[test]
+        fn prop_validation_never_panics(path in "\\PC{0,64}") {
+            let env = FakeEnvironment::new().file("/home/alice/x", UID);
+            let _ = validate_path_with(&env, &path, SecurityContext::Deletion);
+            let _ = decoded_forms(&path);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Suspend/resume awareness for background sampling, and whether the machine
+//! runs on AC or battery.
+//!
+//! logind's `PrepareForSleep` signal is followed through `dbus-monitor`; the gap
+//! between CLOCK_BOOTTIME and CLOCK_MONOTONIC catches resumes when it isn't available.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Once;
+use tokio::sync::broadcast;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Quiet period after resume before sampling, so it doesn't pile onto the wake-up I/O
+pub const RESUME_SETTLE_SECS: i64 = 120;
+/// Samples taken this soon after a resume are tagged in disk_history
+pub const RESUME_TAG_SECS: i64 = 600;
+
+static LAST_RESUME: AtomicI64 = AtomicI64::new(0);
+static LISTENER: Once = Once::new();
+
+lazy_static::lazy_static! {
+    static ref EVENTS: broadcast::Sender<PowerEvent> = broadcast::channel(8).0;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    Suspending,
+    Resumed,
+}
+
+pub fn subscribe() -> broadcast::Receiver<PowerEvent> {
+    EVENTS.subscribe()
+}
+
+pub fn note_resume(now: i64) {
+    LAST_RESUME.store(now, Ordering::Relaxed);
+    let _ = EVENTS.send(PowerEvent::Resumed);
+}
+
+pub fn last_resume() -> Option<i64> {
+    Some(LAST_RESUME.load(Ordering::Relaxed)).filter(|&t| t > 0)
+}
+
+pub fn is_after_resume(now: i64) -> bool {
+    last_resume().is_some_and(|r| now >= r && now - r < RESUME_TAG_SECS)
+}
+
+/// Seconds until the next sample: one interval after the last, and never inside the settle window
+pub fn next_sample_delay(now: i64, last_sample: Option<i64>, last_resume: Option<i64>, interval_secs: i64) -> u64 {
+    let mut due = last_sample.map_or(now, |t| t + interval_secs);
+    if let Some(resumed) = last_resume {
+        due = due.max(resumed + RESUME_SETTLE_SECS);
+    }
+    (due - now).max(0) as u64
+}
+
+/// `dbus-monitor` prints the signal header, then its argument on the next line
+pub fn parse_monitor_line(line: &str, pending: &mut bool) -> Option<PowerEvent> {
+    if line.starts_with("signal ") {
+        *pending = line.contains("member=PrepareForSleep");
+        return None;
+    }
+    if !std::mem::take(pending) {
+        return None;
+    }
+    match line.trim() {
+        "boolean true" => Some(PowerEvent::Suspending),
+        "boolean false" => Some(PowerEvent::Resumed),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+/// On battery when a system battery is discharging and no mains or USB supply is
+/// online; machines without a battery count as AC. Batteries of peripherals
+/// (`scope` Device) are ignored.
+pub fn power_source_in(root: &Path) -> PowerSource {
+    let read = |dir: &Path, name: &str| std::fs::read_to_string(dir.join(name)).map(|v| v.trim().to_string()).unwrap_or_default();
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return PowerSource::Ac;
+    };
+    let mut discharging = false;
+    for supply in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        match read(&supply, "type").as_str() {
+            "Mains" | "USB" if read(&supply, "online") == "1" => return PowerSource::Ac,
+            "Battery" if read(&supply, "scope") != "Device" => discharging |= read(&supply, "status") == "Discharging",
+            _ => {}
+        }
+    }
+    if discharging {
+        PowerSource::Battery
+    } else {
+        PowerSource::Ac
+    }
+}
+
+pub fn power_source() -> PowerSource {
+    power_source_in(Path::new(POWER_SUPPLY_DIR))
+}
+
+fn clock_ms(clock: libc::clockid_t) -> i64 {
+    let mut ts: libc::timespec = unsafe { std::mem::zeroed() };
+    unsafe { libc::clock_gettime(clock, &mut ts) };
+    ts.tv_sec * 1000 + ts.tv_nsec / 1_000_000
+}
+
+/// Total time spent suspended since boot
+fn suspended_ms() -> i64 {
+    clock_ms(libc::CLOCK_BOOTTIME) - clock_ms(libc::CLOCK_MONOTONIC)
+}
+
+/// Detects resumes by polling the suspended-time counter
+pub struct SuspendClock {
+    suspended_ms: i64,
+}
+
+impl SuspendClock {
+    pub fn new() -> Self {
+        Self { suspended_ms: suspended_ms() }
+    }
+
+    /// Records a resume if the system slept since the last check
+    pub fn check(&mut self) -> bool {
+        let current = suspended_ms();
+        let slept = current - self.suspended_ms > 1000;
+        self.suspended_ms = current;
+        // logind usually reported it already
+        let now = chrono::Utc::now().timestamp();
+        if slept && !is_after_resume(now) {
+            note_resume(now);
+        }
+        slept
+    }
+}
+
+impl Default for SuspendClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Follow logind sleep signals for the life of the process; started once
+pub fn spawn_listener() {
+    LISTENER.call_once(|| {
+        std::thread::spawn(|| {
+            let child = Command::new("dbus-monitor")
+                .args(["--system", "type='signal',interface='org.freedesktop.login1.Manager',member='PrepareForSleep'"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn();
+            let Some(stdout) = child.ok().and_then(|mut c| c.stdout.take()) else {
+                tracing::debug!("dbus-monitor unavailable, detecting resume from clocks only");
+                return;
+            };
+
+            let mut pending = false;
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                match parse_monitor_line(&line, &mut pending) {
+                    Some(PowerEvent::Resumed) => {
+                        tracing::info!("System resumed from suspend");
+                        note_resume(chrono::Utc::now().timestamp());
+                    }
+                    Some(PowerEvent::Suspending) => {
+                        let _ = EVENTS.send(PowerEvent::Suspending);
+                    }
+                    None => {}
+                }
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_monitor_output() {
+        let mut pending = false;
+        let header = "signal time=1700000000.1 sender=:1.3 -> destination=(null destination) serial=812 \
+                      path=/org/freedesktop/login1; interface=org.freedesktop.login1.Manager; member=PrepareForSleep";
+        assert_eq!(parse_monitor_line(header, &mut pending), None);
+        assert_eq!(parse_monitor_line("   boolean true", &mut pending), Some(PowerEvent::Suspending));
+        assert_eq!(parse_monitor_line("   boolean false", &mut pending), None);
+
+        parse_monitor_line(header, &mut pending);
+        assert_eq!(parse_monitor_line("   boolean false", &mut pending), Some(PowerEvent::Resumed));
+
+        // Arguments of other signals are ignored
+        parse_monitor_line("signal time=1 sender=org.freedesktop.DBus -> member=NameAcquired", &mut pending);
+        assert_eq!(parse_monitor_line("   boolean false", &mut pending), None);
+    }
+
+    #[test]
+    fn test_power_source() {
+        let root = tempfile::TempDir::new().unwrap();
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            let dir = root.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            for (file, value) in files {
+                std::fs::write(dir.join(file), format!("{}\n", value)).unwrap();
+            }
+        };
+        assert_eq!(power_source_in(root.path()), PowerSource::Ac);
+
+        supply("BAT0", &[("type", "Battery"), ("status", "Discharging")]);
+        supply("AC", &[("type", "Mains"), ("online", "0")]);
+        assert_eq!(power_source_in(root.path()), PowerSource::Battery);
+
+        supply("AC", &[("type", "Mains"), ("online", "1")]);
+        assert_eq!(power_source_in(root.path()), PowerSource::Ac);
+
+        // A discharging wireless mouse doesn't put a desktop on battery
+        let desktop = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(desktop.path().join("hidpp_battery_0")).unwrap();
+        for (file, value) in [("type", "Battery"), ("scope", "Device"), ("status", "Discharging")] {
+            std::fs::write(desktop.path().join("hidpp_battery_0").join(file), value).unwrap();
+        }
+        assert_eq!(power_source_in(desktop.path()), PowerSource::Ac);
+    }
+
+    #[test]
+    fn test_next_sample_delay() {
+        let hour = 3600;
+        // First sample right away, then one interval later
+        assert_eq!(next_sample_delay(1000, None, None, 4 * hour), 0);
+        assert_eq!(next_sample_delay(1000, Some(1000), None, 4 * hour), 4 * 3600);
+        // Overdue after a long suspend: wait for the settle window instead of firing on wake-up
+        assert_eq!(next_sample_delay(100_000, Some(1000), Some(100_000), 4 * hour), RESUME_SETTLE_SECS as u64);
+        assert_eq!(next_sample_delay(100_200, Some(1000), Some(100_000), 4 * hour), 0);
+        // An old resume doesn't delay the regular schedule
+        assert_eq!(next_sample_delay(20_000, Some(10_000), Some(5000), 4 * hour), 4 * 3600 - 10_000);
+    }
+}
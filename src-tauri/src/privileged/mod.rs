@@ -0,0 +1,426 @@
+//! Privileged sessions for system-level cleanups.
+//!
+//! Instead of prompting through pkexec for every operation, the user authenticates
+//! once: pkexec starts this binary as root in helper mode (`--privileged-helper`),
+//! and the app keeps the helper's stdin/stdout in managed state for the session
+//! lifetime. The helper only executes a fixed set of operations ([`PrivilegedOp`])
+//! and refuses work after its own deadline, so a session can't be stretched by the
+//! unprivileged side. Operations only take numeric parameters, or the id of
+//! something the helper looks up itself, so they can't smuggle extra arguments
+//! into the command line.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::read_only;
+
+/// Command line flag that runs the binary as the privileged helper
+pub const HELPER_FLAG: &str = "--privileged-helper";
+
+/// How long one authentication stays valid
+pub const SESSION_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// First line the helper writes once pkexec authentication succeeded
+const READY_LINE: &str = "ready";
+
+/// Operations the helper is allowed to run as root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum PrivilegedOp {
+    AptClean,
+    AptAutoremove,
+    EnableFstrimTimer,
+    TimeshiftCreate,
+    SnapRemoveDisabled,
+    /// `journalctl --vacuum-size/--vacuum-time`; a session scoped to it allows any targets
+    JournalVacuum {
+        max_size_mb: u32,
+        max_age_days: u32,
+    },
+    /// Delete systemd core dumps at least this many days old
+    CoredumpClean {
+        older_than_days: u32,
+    },
+    /// Delete a btrfs, ZFS or snapper snapshot; the id must be one the helper lists
+    DeleteSnapshot {
+        id: String,
+    },
+    /// Purge an old kernel's packages; never the running or newest one
+    PurgeKernel {
+        release: String,
+    },
+}
+
+impl PrivilegedOp {
+    pub fn all() -> Vec<PrivilegedOp> {
+        vec![
+            PrivilegedOp::AptClean,
+            PrivilegedOp::AptAutoremove,
+            PrivilegedOp::EnableFstrimTimer,
+            PrivilegedOp::TimeshiftCreate,
+            PrivilegedOp::SnapRemoveDisabled,
+            PrivilegedOp::JournalVacuum { max_size_mb: 0, max_age_days: 0 },
+            PrivilegedOp::CoredumpClean { older_than_days: 0 },
+            PrivilegedOp::DeleteSnapshot { id: String::new() },
+            PrivilegedOp::PurgeKernel { release: String::new() },
+        ]
+    }
+
+    /// Whether a session scoped to `self` may run `op`: same operation, any parameters
+    fn covers(&self, op: &PrivilegedOp) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(op)
+    }
+
+    fn command(&self) -> Result<(&'static str, Vec<String>), String> {
+        let fixed = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+        Ok(match self {
+            PrivilegedOp::AptClean => ("apt-get", fixed(&["clean"])),
+            PrivilegedOp::AptAutoremove => ("apt-get", fixed(&["autoremove", "-y"])),
+            PrivilegedOp::EnableFstrimTimer => ("systemctl", fixed(&["enable", "--now", "fstrim.timer"])),
+            PrivilegedOp::TimeshiftCreate => ("timeshift", fixed(&["--create", "--scripted", "--comments", "Pulito: before cleanup"])),
+            PrivilegedOp::SnapRemoveDisabled => ("sh", fixed(&["-c", crate::snaps::REMOVE_DISABLED_SCRIPT])),
+            PrivilegedOp::JournalVacuum { max_size_mb, max_age_days } => {
+                let args = crate::journal::vacuum_args(&crate::commands::JournalSettings {
+                    max_size_mb: *max_size_mb,
+                    max_age_days: *max_age_days,
+                });
+                // Without limits journalctl would dump the whole journal; report its size instead
+                ("journalctl", if args.is_empty() { fixed(&["--disk-usage"]) } else { args })
+            }
+            PrivilegedOp::CoredumpClean { older_than_days } => ("find", crate::coredumps::removal_args(*older_than_days)),
+            PrivilegedOp::DeleteSnapshot { id } => crate::snapshots::existing::deletion_command(id)?,
+            PrivilegedOp::PurgeKernel { release } => crate::packages::kernels::purge_command(release)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HelperRequest {
+    op: PrivilegedOp,
+}
+
+/// Result of an operation run through the helper (or its unprivileged fallback)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivilegedOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl From<std::process::Output> for PrivilegedOutput {
+    fn from(output: std::process::Output) -> Self {
+        PrivilegedOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct PrivilegedSessionStatus {
+    pub active: bool,
+    pub started_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub remaining_seconds: u64,
+    pub scope: Vec<PrivilegedOp>,
+}
+
+pub fn is_helper_mode() -> bool {
+    std::env::args().any(|arg| arg == HELPER_FLAG)
+}
+
+/// Entry point of the root helper: one JSON request per stdin line, one JSON
+/// `PrivilegedOutput` per stdout line. Returns the process exit code.
+pub fn run_helper() -> i32 {
+    let deadline = Instant::now() + SESSION_TTL;
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    if writeln!(stdout, "{}", READY_LINE).and_then(|_| stdout.flush()).is_err() {
+        return 1;
+    }
+
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        if Instant::now() >= deadline {
+            break;
+        }
+
+        let output = match serde_json::from_str::<HelperRequest>(&line).map_err(|e| format!("Invalid request: {}", e)) {
+            Ok(request) => match request.op.command() {
+                Ok((program, args)) => Command::new(program)
+                    .args(args)
+                    .env("DEBIAN_FRONTEND", "noninteractive")
+                    .stdin(Stdio::null())
+                    .output()
+                    .map(PrivilegedOutput::from)
+                    .unwrap_or_else(|e| PrivilegedOutput {
+                        success: false,
+                        stdout: String::new(),
+                        stderr: format!("Failed to run {}: {}", program, e),
+                    }),
+                Err(e) => PrivilegedOutput { success: false, stdout: String::new(), stderr: e },
+            },
+            Err(e) => PrivilegedOutput { success: false, stdout: String::new(), stderr: e },
+        };
+
+        let Ok(json) = serde_json::to_string(&output) else { break };
+        if writeln!(stdout, "{}", json).and_then(|_| stdout.flush()).is_err() {
+            break;
+        }
+    }
+    0
+}
+
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    started_at: i64,
+    expires_at: i64,
+    deadline: Instant,
+    scope: Vec<PrivilegedOp>,
+}
+
+impl Session {
+    fn request(&mut self, op: PrivilegedOp) -> Result<PrivilegedOutput, String> {
+        let request = serde_json::to_string(&HelperRequest { op }).map_err(|e| e.to_string())?;
+        writeln!(self.stdin, "{}", request)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Privileged helper is gone: {}", e))?;
+
+        let mut line = String::new();
+        match self.stdout.read_line(&mut line) {
+            Ok(0) => Err("Privileged helper exited".to_string()),
+            Ok(_) => serde_json::from_str(&line).map_err(|e| format!("Invalid helper response: {}", e)),
+            Err(e) => Err(format!("Failed to read helper response: {}", e)),
+        }
+    }
+
+    fn end(mut self) {
+        // Closing stdin lets the helper exit on its own; kill in case it is mid-operation
+        drop(self.stdin);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Managed state holding the current privileged session, if any
+#[derive(Default)]
+pub struct PrivilegedSessionState {
+    session: Mutex<Option<Session>>,
+}
+
+impl PrivilegedSessionState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Session>> {
+        let mut guard = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.as_ref().map(|s| Instant::now() >= s.deadline).unwrap_or(false) {
+            tracing::info!("Privileged session expired");
+            if let Some(session) = guard.take() {
+                session.end();
+            }
+        }
+        guard
+    }
+
+    pub fn status(&self) -> PrivilegedSessionStatus {
+        match self.lock().as_ref() {
+            Some(session) => PrivilegedSessionStatus {
+                active: true,
+                started_at: Some(session.started_at),
+                expires_at: Some(session.expires_at),
+                remaining_seconds: session.deadline.saturating_duration_since(Instant::now()).as_secs(),
+                scope: session.scope.clone(),
+            },
+            None => PrivilegedSessionStatus {
+                active: false,
+                started_at: None,
+                expires_at: None,
+                remaining_seconds: 0,
+                scope: Vec::new(),
+            },
+        }
+    }
+
+    fn end(&self) {
+        if let Some(session) = self.lock().take() {
+            session.end();
+        }
+    }
+}
+
+/// Run `op` through the active session. `Ok(None)` when there is no session or
+/// `op` is outside its scope, so callers can fall back to their unprivileged path.
+/// Blocking; call from a blocking task.
+pub fn run_in_session(app_handle: &tauri::AppHandle, op: PrivilegedOp) -> Result<Option<PrivilegedOutput>, String> {
+    let state = app_handle.state::<PrivilegedSessionState>();
+    let mut guard = state.lock();
+    let Some(session) = guard.as_mut() else {
+        return Ok(None);
+    };
+    if !session.scope.iter().any(|allowed| allowed.covers(&op)) {
+        return Ok(None);
+    }
+
+    tracing::info!("Running {:?} in privileged session", op);
+    match session.request(op) {
+        Ok(output) => Ok(Some(output)),
+        Err(e) => {
+            if let Some(session) = guard.take() {
+                session.end();
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Run `ops` in order through the active session, or, when it doesn't cover them,
+/// through a helper started for just these: one authentication for all of them
+/// rather than a pkexec prompt each. Blocking; call from a blocking task.
+pub fn run_all(app_handle: &tauri::AppHandle, ops: Vec<PrivilegedOp>) -> Result<Vec<PrivilegedOutput>, String> {
+    if ops.is_empty() {
+        return Ok(Vec::new());
+    }
+    let state = app_handle.state::<PrivilegedSessionState>();
+    let mut guard = state.lock();
+    if let Some(session) = guard.as_mut().filter(|s| ops.iter().all(|op| s.scope.iter().any(|allowed| allowed.covers(op)))) {
+        tracing::info!("Running {} operations in privileged session", ops.len());
+        let outputs = ops.into_iter().map(|op| session.request(op)).collect::<Result<Vec<_>, _>>();
+        if outputs.is_err() {
+            if let Some(session) = guard.take() {
+                session.end();
+            }
+        }
+        return outputs;
+    }
+    drop(guard);
+
+    read_only::check("Running privileged operations")?;
+    let mut session = spawn_helper(ops.clone())?;
+    tracing::info!("Running {} operations in a one-off privileged helper", ops.len());
+    let outputs = ops.into_iter().map(|op| session.request(op)).collect::<Result<Vec<_>, _>>();
+    session.end();
+    outputs
+}
+
+fn spawn_helper(scope: Vec<PrivilegedOp>) -> Result<Session, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Cannot locate executable: {}", e))?;
+    let mut child = Command::new("pkexec")
+        .arg(&exe)
+        .arg(HELPER_FLAG)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+
+    let stdin = child.stdin.take().ok_or("Failed to open helper stdin")?;
+    let mut stdout = BufReader::new(child.stdout.take().ok_or("Failed to open helper stdout")?);
+
+    // Blocks while the polkit authentication dialog is shown
+    let mut line = String::new();
+    let read = stdout.read_line(&mut line);
+    if !matches!(read, Ok(n) if n > 0) || line.trim() != READY_LINE {
+        let status = child.wait().ok();
+        // pkexec exits with 126 when the user dismisses the authentication dialog
+        if status.and_then(|s| s.code()) == Some(126) {
+            return Err("Authentication was cancelled".to_string());
+        }
+        return Err("Privileged helper failed to start".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    Ok(Session {
+        child,
+        stdin,
+        stdout,
+        started_at: now,
+        expires_at: now + SESSION_TTL.as_secs() as i64,
+        // Slightly shorter than the helper's own deadline so we never hand it a request it will refuse
+        deadline: Instant::now() + SESSION_TTL - Duration::from_secs(5),
+        scope,
+    })
+}
+
+/// Authenticate once via polkit and keep a privileged helper for SESSION_TTL.
+/// `scope` limits which operations the session may run (default: all).
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn start_privileged_session(
+    app_handle: tauri::AppHandle,
+    scope: Option<Vec<PrivilegedOp>>,
+) -> Result<PrivilegedSessionStatus, String> {
+    read_only::check("Starting a privileged session")?;
+    let scope = scope.filter(|s| !s.is_empty()).unwrap_or_else(PrivilegedOp::all);
+    tracing::info!("Starting privileged session with scope {:?}", scope);
+
+    let session = tokio::task::spawn_blocking(move || spawn_helper(scope))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+
+    let state = app_handle.state::<PrivilegedSessionState>();
+    let previous = state.lock().replace(session);
+    if let Some(previous) = previous {
+        previous.end();
+    }
+    Ok(state.status())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_privileged_session(app_handle: tauri::AppHandle) -> Result<PrivilegedSessionStatus, String> {
+    Ok(app_handle.state::<PrivilegedSessionState>().status())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn end_privileged_session(app_handle: tauri::AppHandle) -> Result<PrivilegedSessionStatus, String> {
+    let state = app_handle.state::<PrivilegedSessionState>();
+    state.end();
+    tracing::info!("Privileged session ended");
+    Ok(state.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_serialization() {
+        let json = serde_json::to_string(&HelperRequest { op: PrivilegedOp::EnableFstrimTimer }).unwrap();
+        assert_eq!(json, r#"{"op":"enable_fstrim_timer"}"#);
+        assert!(serde_json::from_str::<HelperRequest>(r#"{"op":"rm_rf"}"#).is_err());
+    }
+
+    #[test]
+    fn test_ops_map_to_fixed_commands() {
+        assert_eq!(PrivilegedOp::AptClean.command(), Ok(("apt-get", vec!["clean".to_string()])));
+        assert_eq!(PrivilegedOp::EnableFstrimTimer.command().unwrap().0, "systemctl");
+        assert_eq!(PrivilegedOp::all().len(), 9);
+
+        let vacuum = PrivilegedOp::JournalVacuum { max_size_mb: 200, max_age_days: 0 };
+        assert_eq!(vacuum.command(), Ok(("journalctl", vec!["--vacuum-size=200M".to_string()])));
+        assert!(PrivilegedOp::all().iter().any(|op| op.covers(&vacuum)));
+        assert!(!PrivilegedOp::AptClean.covers(&vacuum));
+
+        // Only snapshots the helper finds itself, never a path it's handed
+        assert!(PrivilegedOp::DeleteSnapshot { id: "btrfs:/home".to_string() }.command().is_err());
+    }
+
+    #[test]
+    fn test_inactive_status() {
+        let state = PrivilegedSessionState::default();
+        let status = state.status();
+        assert!(!status.active);
+        assert_eq!(status.remaining_seconds, 0);
+        assert!(status.scope.is_empty());
+    }
+}
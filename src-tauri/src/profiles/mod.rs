@@ -0,0 +1,234 @@
+//! Named settings profiles, for a laptop that alternates between a corporate
+//! and a personal usage policy. A profile holds a full copy of the app
+//! settings (exclusions, retention, thresholds...) and the cleanup schedule.
+//! The current settings stay where they always were; switching copies them
+//! into the active profile first, then copies the chosen profile over them,
+//! so each profile keeps whatever was last saved while it was active.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::commands::{self, AppSettings};
+use crate::db::DbAccess;
+use crate::read_only;
+use crate::safe_mode;
+use crate::shortcuts;
+
+const SETTINGS_KEY: &str = "app_settings";
+const SCHEDULE_KEY: &str = "scheduling";
+const ACTIVE_KEY: &str = "active_profile";
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SettingsProfile {
+    pub name: String,
+    pub updated_at: i64, // last time the current settings were copied into it
+    pub active: bool,
+}
+
+fn read_key(conn: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0)).optional()
+}
+
+/// Store `value` under `key`; None removes it, so defaults apply
+fn write_key(conn: &Connection, key: &str, value: Option<&str>) -> rusqlite::Result<()> {
+    match value {
+        Some(value) => conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)", [key, value])?,
+        None => conn.execute("DELETE FROM settings WHERE key = ?1", [key])?,
+    };
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LEN || name.chars().any(char::is_control) {
+        return Err(format!("Profile names are up to {} printable characters", MAX_NAME_LEN));
+    }
+    Ok(name.to_string())
+}
+
+pub fn active_profile(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    read_key(conn, ACTIVE_KEY)
+}
+
+pub fn list_profiles(conn: &Connection) -> rusqlite::Result<Vec<SettingsProfile>> {
+    let active = active_profile(conn)?;
+    let mut stmt = conn.prepare("SELECT name, updated_at FROM settings_profiles ORDER BY name COLLATE NOCASE")?;
+    let profiles = stmt
+        .query_map([], |row| {
+            let name: String = row.get(0)?;
+            Ok(SettingsProfile { active: active.as_deref() == Some(name.as_str()), name, updated_at: row.get(1)? })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(profiles)
+}
+
+pub fn profile_exists(conn: &Connection, name: &str) -> rusqlite::Result<bool> {
+    conn.query_row("SELECT COUNT(*) FROM settings_profiles WHERE name = ?1", [name], |row| row.get::<_, i64>(0)).map(|n| n > 0)
+}
+
+/// Copy the current settings and schedule into profile `name`
+pub fn capture(conn: &Connection, name: &str, now: i64) -> rusqlite::Result<()> {
+    let settings = read_key(conn, SETTINGS_KEY)?;
+    let schedule = read_key(conn, SCHEDULE_KEY)?;
+    conn.execute(
+        "INSERT INTO settings_profiles (name, settings, scheduling, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(name) DO UPDATE SET settings = excluded.settings, scheduling = excluded.scheduling, updated_at = excluded.updated_at",
+        params![name, settings, schedule, now],
+    )?;
+    Ok(())
+}
+
+/// The app settings stored in profile `name`, None when there is no such
+/// profile or its settings can't be read
+pub fn profile_settings(conn: &Connection, name: &str) -> rusqlite::Result<Option<AppSettings>> {
+    let settings: Option<Option<String>> =
+        conn.query_row("SELECT settings FROM settings_profiles WHERE name = ?1", [name], |row| row.get(0)).optional()?;
+    Ok(settings.flatten().and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Make `name` the active profile, keeping the current settings in the one
+/// active before. False when there is no such profile.
+pub fn switch(conn: &Connection, name: &str, now: i64) -> rusqlite::Result<bool> {
+    let tx = conn.unchecked_transaction()?;
+    let target: Option<(Option<String>, Option<String>)> = tx
+        .query_row("SELECT settings, scheduling FROM settings_profiles WHERE name = ?1", [name], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?;
+    let Some((settings, schedule)) = target else { return Ok(false) };
+    if let Some(active) = active_profile(&tx)? {
+        if active != name {
+            capture(&tx, &active, now)?;
+        }
+    }
+    write_key(&tx, SETTINGS_KEY, settings.as_deref())?;
+    write_key(&tx, SCHEDULE_KEY, schedule.as_deref())?;
+    write_key(&tx, ACTIVE_KEY, Some(name))?;
+    tx.commit()?;
+    Ok(true)
+}
+
+/// Remove profile `name`; the active profile can't be removed
+pub fn delete(conn: &Connection, name: &str) -> Result<(), String> {
+    if active_profile(conn).map_err(|e| e.to_string())?.as_deref() == Some(name) {
+        return Err("Switch to another profile before deleting this one".to_string());
+    }
+    let removed = conn.execute("DELETE FROM settings_profiles WHERE name = ?1", [name]).map_err(|e| e.to_string())?;
+    if removed == 0 {
+        return Err(format!("No profile named {}", name));
+    }
+    Ok(())
+}
+
+/// Settings profiles, alphabetically
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_settings_profiles(app_handle: tauri::AppHandle) -> Result<Vec<SettingsProfile>, String> {
+    app_handle.db(list_profiles).map_err(|e| format!("Failed to list profiles: {}", e))
+}
+
+/// Save the current settings as a new profile and make it active
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn create_settings_profile(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
+    let name = validate_name(&name)?;
+    let now = chrono::Utc::now().timestamp();
+    let created = app_handle
+        .db(|conn| {
+            if profile_exists(conn, &name)? {
+                return Ok(false);
+            }
+            // The profile active until now keeps the same settings the new one starts from
+            if let Some(active) = active_profile(conn)? {
+                capture(conn, &active, now)?;
+            }
+            capture(conn, &name, now)?;
+            write_key(conn, ACTIVE_KEY, Some(&name))?;
+            Ok(true)
+        })
+        .map_err(|e| format!("Failed to create profile: {}", e))?;
+    if !created {
+        return Err(format!("A profile named {} already exists", name));
+    }
+    Ok(())
+}
+
+/// Switch to profile `name` and apply its settings and schedule; returns the
+/// settings now in effect
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn switch_profile(app_handle: tauri::AppHandle, name: String) -> Result<AppSettings, String> {
+    let now = chrono::Utc::now().timestamp();
+    let target = app_handle.db(|conn| profile_settings(conn, &name)).map_err(|e| format!("Failed to read profile: {}", e))?;
+    if target.is_some_and(|target| safe_mode::weakens(&commands::load_settings(&app_handle).safe_mode, &target.safe_mode)) {
+        return Err(format!("Profile {} relaxes safe mode; turn safe mode off in Settings before switching", name));
+    }
+    let switched = app_handle.db(|conn| switch(conn, &name, now)).map_err(|e| format!("Failed to switch profile: {}", e))?;
+    if !switched {
+        return Err(format!("No profile named {}", name));
+    }
+    tracing::info!("Switched to settings profile {}", name);
+
+    // What save_settings and save_schedule_settings would apply
+    let settings = commands::load_settings(&app_handle);
+    shortcuts::apply(&app_handle, &settings.shortcuts);
+    safe_mode::apply(&settings.safe_mode);
+    read_only::apply(settings.read_only);
+    let schedule = commands::get_schedule_settings(app_handle.clone()).await?;
+    commands::apply_schedule(&app_handle, schedule).await?;
+    Ok(settings)
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn delete_settings_profile(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
+    app_handle.db(|conn| Ok(delete(conn, &name))).map_err(|e| format!("Failed to delete profile: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profiles_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+             CREATE TABLE settings_profiles (name TEXT PRIMARY KEY, settings TEXT, scheduling TEXT, updated_at INTEGER NOT NULL);
+             INSERT INTO settings VALUES ('app_settings', 'work settings');
+             INSERT INTO settings VALUES ('scheduling', 'work schedule');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_switch_keeps_each_profile() {
+        let conn = profiles_db();
+        capture(&conn, "Work", 1).unwrap();
+        write_key(&conn, ACTIVE_KEY, Some("Work")).unwrap();
+        // A profile made before any schedule existed
+        conn.execute("INSERT INTO settings_profiles VALUES ('Personal', 'personal settings', NULL, 1)", []).unwrap();
+
+        assert!(switch(&conn, "Personal", 2).unwrap());
+        assert_eq!(read_key(&conn, SETTINGS_KEY).unwrap().as_deref(), Some("personal settings"));
+        assert_eq!(read_key(&conn, SCHEDULE_KEY).unwrap(), None);
+
+        // Changes made while Personal is active stay with it
+        write_key(&conn, SETTINGS_KEY, Some("personal, edited")).unwrap();
+        assert!(switch(&conn, "Work", 3).unwrap());
+        assert_eq!(read_key(&conn, SCHEDULE_KEY).unwrap().as_deref(), Some("work schedule"));
+        assert!(switch(&conn, "Personal", 4).unwrap());
+        assert_eq!(read_key(&conn, SETTINGS_KEY).unwrap().as_deref(), Some("personal, edited"));
+
+        assert!(!switch(&conn, "Travel", 5).unwrap());
+        let names: Vec<(String, bool)> = list_profiles(&conn).unwrap().into_iter().map(|p| (p.name, p.active)).collect();
+        assert_eq!(names, vec![("Personal".to_string(), true), ("Work".to_string(), false)]);
+        assert!(delete(&conn, "Personal").is_err());
+        assert!(delete(&conn, "Work").is_ok());
+        assert!(validate_name("  ").is_err());
+    }
+}
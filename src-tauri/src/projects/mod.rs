@@ -0,0 +1,329 @@
+//! Build and dependency folders inside development projects, such as
+//! `node_modules`, Cargo's `target` and Python virtualenvs. Each kind is found under the configured project roots and
+//! judged by the project around it: folders of projects nobody touched for
+//! `stale_days` are safe to remove and are restored by reinstalling, those of
+//! active projects are reported but marked as in use.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::glob::expand_home;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub mod python;
+
+pub const CATEGORY: &str = "Development";
+
+/// How deep below a root projects are looked for
+const MAX_ROOT_DEPTH: usize = 6;
+/// Files of a project looked at to find when it was last worked on
+const MAX_PROJECT_FILES: usize = 5_000;
+const MAX_PROJECT_DEPTH: usize = 4;
+/// Folders that don't count as working on a project
+const GENERATED_DIRS: &[&str] = &["node_modules", "target", "__pycache__", "venv", ".venv", ".git"];
+
+/// Where projects are searched and when one counts as stale
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ProjectSettings {
+    pub roots: Vec<String>, // "~" expands to the home directory
+    pub stale_days: u32,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            roots: ["~/Projects", "~/projects", "~/src", "~/code", "~/dev", "~/workspace"].map(String::from).to_vec(),
+            stale_days: 90,
+        }
+    }
+}
+
+/// A kind of generated folder inside a project
+pub struct ArtifactKind {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether `dir` is this kind of folder; not descended into when it is
+    pub matches: fn(&Path) -> bool,
+    pub restore_hint: &'static str,
+}
+
+pub static NODE_MODULES: ArtifactKind = ArtifactKind {
+    id: "node_modules",
+    name: "node_modules",
+    description: "node_modules folders of JavaScript projects under the project roots",
+    matches: is_node_modules,
+    restore_hint: "npm install",
+};
+
+pub static CARGO_TARGET: ArtifactKind = ArtifactKind {
+    id: "cargo_target",
+    name: "Cargo target",
+    description: "Cargo build output of Rust projects under the project roots",
+    matches: is_cargo_target,
+    restore_hint: "cargo build",
+};
+
+pub static PYTHON_VENV: ArtifactKind = ArtifactKind {
+    id: "python_venv",
+    name: "Python virtualenv",
+    description: "Virtual environments of Python projects under the project roots",
+    matches: is_virtualenv,
+    restore_hint: "python -m venv",
+};
+
+/// A folder named `name` next to `manifest`
+fn beside_manifest(dir: &Path, name: &str, manifest: &str) -> bool {
+    dir.file_name().is_some_and(|n| n == name) && dir.parent().is_some_and(|p| p.join(manifest).is_file())
+}
+
+/// A `node_modules` next to the `package.json` that declares it
+fn is_node_modules(dir: &Path) -> bool {
+    beside_manifest(dir, "node_modules", "package.json")
+}
+
+/// A `target` next to a `Cargo.toml`; removing it is what `cargo clean` does
+fn is_cargo_target(dir: &Path) -> bool {
+    beside_manifest(dir, "target", "Cargo.toml")
+}
+
+/// A virtual environment, whatever it is called, has a `pyvenv.cfg` at its top
+pub fn is_virtualenv(dir: &Path) -> bool {
+    dir.join("pyvenv.cfg").is_file()
+}
+
+/// Folders skipped when looking for a project's own files
+fn is_generated(entry: &walkdir::DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && (entry.file_name().to_str().is_some_and(|n| GENERATED_DIRS.contains(&n)) || is_virtualenv(entry.path()))
+}
+
+/// A generated folder and the project it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactDir {
+    pub path: PathBuf,
+    pub project: PathBuf,
+    pub last_touched: i64, // newest modification in the project outside generated folders
+}
+
+/// Newest modification time among a project's own files; directories don't
+/// count, installing dependencies changes the project folder's own mtime
+pub fn last_touched(project: &Path) -> i64 {
+    WalkDir::new(project)
+        .max_depth(MAX_PROJECT_DEPTH)
+        .into_iter()
+        .filter_entry(|e| !is_generated(e))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .take(MAX_PROJECT_FILES)
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.mtime())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Folders of `kind` below `root`, nested ones inside a match left out
+pub fn find_artifacts(root: &Path, kind: &ArtifactKind, ctx: Option<&ScanContext>) -> Result<Vec<ArtifactDir>, ScannerError> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(root)
+        .max_depth(MAX_ROOT_DEPTH)
+        .follow_links(false)
+        .same_file_system(ctx.map_or(true, |c| c.one_file_system()))
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        if let Some(ctx) = ctx {
+            ctx.check_cancelled()?;
+            ctx.pace();
+            if !ctx.enters(entry.path()) {
+                walker.skip_current_dir();
+                continue;
+            }
+        }
+        let name = entry.file_name().to_string_lossy();
+        if (kind.matches)(entry.path()) {
+            let project = entry.path().parent().unwrap_or(root).to_path_buf();
+            found.push(ArtifactDir { path: entry.path().to_path_buf(), last_touched: last_touched(&project), project });
+            walker.skip_current_dir();
+        } else if entry.depth() > 0 && (name.starts_with('.') || GENERATED_DIRS.contains(&name.as_ref())) {
+            walker.skip_current_dir();
+        }
+    }
+    Ok(found)
+}
+
+pub fn artifact_item(kind: &ArtifactKind, dir: &ArtifactDir, size: u64, stale_days: u32, now: i64) -> ScanItem {
+    let idle_days = (now - dir.last_touched).max(0) / (24 * 3600);
+    let stale = idle_days >= stale_days as i64;
+    let project = dir.project.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let description = if stale {
+        format!("{} untouched for {} days; `{}` restores it", dir.project.display(), idle_days, kind.restore_hint)
+    } else {
+        format!("{} is in active use, last changed {} days ago", dir.project.display(), idle_days)
+    };
+    new_item(
+        format!("{} ({})", kind.name, project),
+        &dir.path,
+        size,
+        "directory",
+        CATEGORY,
+        if stale { 0 } else { 3 },
+        &description,
+    )
+}
+
+/// Scans the project roots for one [`ArtifactKind`]
+pub struct ProjectArtifactScanner(pub &'static ArtifactKind);
+
+/// Project roots from the comma-separated `roots` scanner option, or the defaults
+pub fn roots_option(options: &HashMap<String, String>, home: &Path) -> Vec<PathBuf> {
+    match options.get("roots") {
+        Some(roots) => roots.split(',').map(str::trim).filter(|r| !r.is_empty()).map(|r| expand_home(r, home)).collect(),
+        None => ProjectSettings::default().roots.iter().map(|r| expand_home(r, home)).collect(),
+    }
+}
+
+/// The `roots` and `stale_days` options every project scanner takes
+pub fn project_options_schema() -> Vec<ScannerOptionSchema> {
+    let defaults = ProjectSettings::default();
+    vec![
+        ScannerOptionSchema {
+            key: "roots".to_string(),
+            label: "Project folders (comma-separated)".to_string(),
+            value_type: "string".to_string(),
+            default_value: defaults.roots.join(","),
+        },
+        ScannerOptionSchema {
+            key: "stale_days".to_string(),
+            label: "Untouched for (days)".to_string(),
+            value_type: "number".to_string(),
+            default_value: defaults.stale_days.to_string(),
+        },
+    ]
+}
+
+/// Project scanners are on when caches are included and one of their roots exists
+pub fn roots_exist(scanner_id: &str, options: &ScanOptions) -> bool {
+    let home = dirs::home_dir().unwrap_or_default();
+    let scanner_options = options.scanner_options.as_ref().and_then(|o| o.get(scanner_id)).cloned().unwrap_or_default();
+    options.include_caches && roots_option(&scanner_options, &home).iter().any(|r| r.is_dir())
+}
+
+impl Scanner for ProjectArtifactScanner {
+    fn id(&self) -> &'static str {
+        self.0.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.0.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.0.description
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        project_options_schema()
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        roots_exist(self.id(), options)
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let stale_days = ctx.option("stale_days", ProjectSettings::default().stale_days);
+        let now = chrono::Utc::now().timestamp();
+
+        let roots: Vec<PathBuf> = roots_option(&ctx.options, &home).into_iter().filter(|r| r.is_dir()).collect();
+        let mut items = Vec::new();
+        for (index, root) in roots.iter().enumerate() {
+            ctx.progress((index * 100 / roots.len()) as u8, &format!("Looking for {} in {}...", self.0.name, root.display()));
+            for dir in find_artifacts(root, self.0, Some(ctx))? {
+                ctx.check_cancelled()?;
+                let size = ctx.dir_size(&dir.path);
+                items.push(artifact_item(self.0, &dir, size, stale_days, now));
+            }
+        }
+        // Stale folders first, then by size
+        items.sort_by(|a, b| a.risk_level.cmp(&b.risk_level).then(b.size.cmp(&a.size)));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_node_modules() {
+        let root = tempfile::TempDir::new().unwrap();
+        let app = root.path().join("client/app");
+        fs::create_dir_all(app.join("node_modules/left-pad/node_modules/dep")).unwrap();
+        fs::write(app.join("package.json"), "{}").unwrap();
+        fs::write(app.join("node_modules/left-pad/package.json"), "{}").unwrap();
+        // Not a project's dependencies without a package.json next to it
+        fs::create_dir_all(root.path().join("notes/node_modules")).unwrap();
+
+        let found = find_artifacts(root.path(), &NODE_MODULES, None).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, app.join("node_modules"));
+        assert_eq!(found[0].project, app);
+        assert!(found[0].last_touched > 0);
+
+        let now = found[0].last_touched + 100 * 24 * 3600;
+        let item = artifact_item(&NODE_MODULES, &found[0], 1024, 90, now);
+        assert_eq!((item.name.as_str(), item.risk_level), ("node_modules (app)", 0));
+        assert_eq!(artifact_item(&NODE_MODULES, &found[0], 1024, 180, now).risk_level, 3);
+    }
+
+    #[test]
+    fn test_find_virtualenvs() {
+        let root = tempfile::TempDir::new().unwrap();
+        let service = root.path().join("service");
+        fs::create_dir_all(service.join(".venv/lib/python3.12/site-packages")).unwrap();
+        fs::write(service.join(".venv/pyvenv.cfg"), "home = /usr/bin").unwrap();
+        fs::create_dir_all(service.join("env/bin")).unwrap();
+        fs::write(service.join("env/pyvenv.cfg"), "home = /usr/bin").unwrap();
+        fs::write(service.join("main.py"), "print()").unwrap();
+
+        let mut found: Vec<PathBuf> = find_artifacts(root.path(), &PYTHON_VENV, None).unwrap().into_iter().map(|d| d.path).collect();
+        found.sort();
+        assert_eq!(found, vec![service.join(".venv"), service.join("env")]);
+
+        // Installing into a virtualenv isn't working on the project
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(10 * 24 * 3600);
+        fs::File::options().write(true).open(service.join("main.py")).unwrap().set_modified(old).unwrap();
+        let age = chrono::Utc::now().timestamp() - last_touched(&service);
+        assert!(age >= 10 * 24 * 3600 - 60, "age {}", age);
+    }
+
+    #[test]
+    fn test_find_cargo_target() {
+        let root = tempfile::TempDir::new().unwrap();
+        let workspace = root.path().join("tools");
+        fs::create_dir_all(workspace.join("target/debug/build")).unwrap();
+        fs::create_dir_all(workspace.join("crates/cli/src")).unwrap();
+        fs::write(workspace.join("Cargo.toml"), "[workspace]").unwrap();
+        fs::write(workspace.join("crates/cli/Cargo.toml"), "[package]").unwrap();
+        // A Maven build folder isn't Cargo's
+        fs::create_dir_all(root.path().join("server/target/classes")).unwrap();
+        fs::write(root.path().join("server/pom.xml"), "<project/>").unwrap();
+
+        let found = find_artifacts(root.path(), &CARGO_TARGET, None).unwrap();
+        assert_eq!(found.iter().map(|d| d.path.clone()).collect::<Vec<_>>(), vec![workspace.join("target")]);
+        assert_eq!(artifact_item(&CARGO_TARGET, &found[0], 1, 90, found[0].last_touched).name, "Cargo target (tools)");
+    }
+}
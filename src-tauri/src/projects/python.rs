@@ -0,0 +1,277 @@
+//! Python leftovers besides project virtualenvs (see [`super::PYTHON_VENV`]):
+//! `__pycache__` bytecode, which a package scatters over every source folder and
+//! is therefore listed per project, and conda environments, which live in the
+//! conda installation rather than next to a project.
+
+use std::collections::BTreeMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::{is_virtualenv, last_touched, project_options_schema, roots_exist, roots_option, ProjectSettings, CATEGORY};
+use super::{GENERATED_DIRS, MAX_ROOT_DEPTH};
+use crate::scanner::builtin::new_item;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+/// Files that mark the top of a Python project
+const PROJECT_MARKERS: &[&str] = &["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt", ".git"];
+/// Where conda installations keep named environments, relative to home
+const CONDA_ENV_DIRS: &[&str] = &["miniconda3/envs", "anaconda3/envs", "miniforge3/envs", "mambaforge/envs", ".conda/envs"];
+/// Packages nest deeper below a root than projects do
+const MAX_DEPTH: usize = MAX_ROOT_DEPTH * 2;
+
+/// The project a `__pycache__` belongs to: the nearest folder below `root` with a
+/// project marker, or the package folder holding it
+fn project_of(cache_dir: &Path, root: &Path) -> PathBuf {
+    let package = cache_dir.parent().unwrap_or(root);
+    package
+        .ancestors()
+        .take_while(|dir| dir.starts_with(root) && *dir != root)
+        .find(|dir| PROJECT_MARKERS.iter().any(|marker| dir.join(marker).exists()))
+        .unwrap_or(package)
+        .to_path_buf()
+}
+
+/// `__pycache__` folders below `root` by project; those inside virtualenvs
+/// and other generated folders are left out
+pub fn find_pycaches(root: &Path, ctx: Option<&ScanContext>) -> Result<BTreeMap<PathBuf, Vec<PathBuf>>, ScannerError> {
+    let mut projects: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+    let mut walker = WalkDir::new(root)
+        .max_depth(MAX_DEPTH)
+        .follow_links(false)
+        .same_file_system(ctx.map_or(true, |c| c.one_file_system()))
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() || entry.depth() == 0 {
+            continue;
+        }
+        if let Some(ctx) = ctx {
+            ctx.check_cancelled()?;
+            ctx.pace();
+            if !ctx.enters(entry.path()) {
+                walker.skip_current_dir();
+                continue;
+            }
+        }
+        let name = entry.file_name().to_string_lossy();
+        if name == "__pycache__" {
+            projects.entry(project_of(entry.path(), root)).or_default().push(entry.path().to_path_buf());
+            walker.skip_current_dir();
+        } else if name.starts_with('.') || GENERATED_DIRS.contains(&name.as_ref()) || is_virtualenv(entry.path()) {
+            walker.skip_current_dir();
+        }
+    }
+    projects.values_mut().for_each(|caches| caches.sort());
+    Ok(projects)
+}
+
+fn idle_days(last_used: i64, now: i64) -> i64 {
+    (now - last_used).max(0) / (24 * 3600)
+}
+
+/// Bytecode caches of Python projects, one item per project
+pub struct PycacheScanner;
+
+impl Scanner for PycacheScanner {
+    fn id(&self) -> &'static str {
+        "pycache"
+    }
+
+    fn name(&self) -> &'static str {
+        "Python bytecode"
+    }
+
+    fn description(&self) -> &'static str {
+        "__pycache__ folders of Python projects under the project roots"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        project_options_schema()
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        roots_exist(self.id(), options)
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let stale_days = ctx.option("stale_days", ProjectSettings::default().stale_days) as i64;
+        let now = chrono::Utc::now().timestamp();
+
+        let roots: Vec<PathBuf> = roots_option(&ctx.options, &home).into_iter().filter(|r| r.is_dir()).collect();
+        let mut items = Vec::new();
+        for (index, root) in roots.iter().enumerate() {
+            ctx.progress((index * 100 / roots.len()) as u8, &format!("Looking for Python bytecode in {}...", root.display()));
+            for (project, caches) in find_pycaches(root, Some(ctx))? {
+                ctx.check_cancelled()?;
+                let idle = idle_days(last_touched(&project), now);
+                // Regenerated on the next run either way, but pointless to remove from active projects
+                let risk_level = if idle >= stale_days { 0 } else { 1 };
+                let children: Vec<ScanItem> = caches
+                    .iter()
+                    .map(|cache| new_item(
+                        cache.strip_prefix(&project).unwrap_or(cache).to_string_lossy().to_string(),
+                        cache,
+                        ctx.dir_size(cache),
+                        "directory",
+                        CATEGORY,
+                        risk_level,
+                        "Compiled bytecode, rebuilt when the module is next imported",
+                    ))
+                    .collect();
+                let size: u64 = children.iter().map(|c| c.size).sum();
+                if size == 0 {
+                    continue;
+                }
+                // The project itself is never removed, only the caches listed as children
+                let mut item = new_item(
+                    format!("Python bytecode ({})", project.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()),
+                    &project,
+                    size,
+                    "pycache",
+                    CATEGORY,
+                    risk_level,
+                    &format!("{} __pycache__ folders in {}, last changed {} days ago", children.len(), project.display(), idle),
+                );
+                item.children = Some(children);
+                items.push(item);
+            }
+        }
+        items.sort_by(|a, b| a.risk_level.cmp(&b.risk_level).then(b.size.cmp(&a.size)));
+        Ok(items)
+    }
+}
+
+/// A named conda environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct CondaEnv {
+    pub path: PathBuf,
+    pub name: String,
+    pub last_used: i64,
+}
+
+/// When an environment was last changed or run: conda logs every install to
+/// `conda-meta/history`, and starting its interpreter updates the binary's
+/// access time (at most daily under relatime, which is precise enough here)
+fn conda_last_used(env: &Path) -> i64 {
+    let history = std::fs::metadata(env.join("conda-meta/history")).map(|m| m.mtime()).unwrap_or(0);
+    let python = std::fs::metadata(env.join("bin/python")).map(|m| m.atime().max(m.mtime())).unwrap_or(0);
+    history.max(python)
+}
+
+/// Named environments of the conda installations in `home`
+pub fn find_conda_envs(home: &Path) -> Vec<CondaEnv> {
+    let mut envs: Vec<CondaEnv> = CONDA_ENV_DIRS
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(home.join(dir)).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| path.join("conda-meta").is_dir())
+        .map(|path| CondaEnv {
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            last_used: conda_last_used(&path),
+            path,
+        })
+        .collect();
+    envs.sort_by(|a, b| a.path.cmp(&b.path));
+    envs
+}
+
+pub struct CondaEnvScanner;
+
+impl Scanner for CondaEnvScanner {
+    fn id(&self) -> &'static str {
+        "conda_envs"
+    }
+
+    fn name(&self) -> &'static str {
+        "Conda environments"
+    }
+
+    fn description(&self) -> &'static str {
+        "Named environments of Anaconda, Miniconda and Miniforge installations"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        project_options_schema().into_iter().filter(|o| o.key == "stale_days").collect()
+    }
+
+    /// On when a conda installation has named environments
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_caches && dirs::home_dir().is_some_and(|home| !find_conda_envs(&home).is_empty())
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let stale_days = ctx.option("stale_days", ProjectSettings::default().stale_days) as i64;
+        let now = chrono::Utc::now().timestamp();
+
+        ctx.progress(0, "Looking for conda environments...");
+        let mut items = Vec::new();
+        for env in find_conda_envs(&home) {
+            ctx.check_cancelled()?;
+            if !ctx.allows(&env.path) {
+                continue;
+            }
+            let idle = idle_days(env.last_used, now);
+            let stale = idle >= stale_days;
+            let description = if stale {
+                format!("Not used for {} days; `conda create -n {}` rebuilds it", idle, env.name)
+            } else {
+                format!("Used {} days ago", idle)
+            };
+            items.push(new_item(
+                format!("Conda env ({})", env.name),
+                &env.path,
+                ctx.dir_size(&env.path),
+                "directory",
+                CATEGORY,
+                if stale { 0 } else { 3 },
+                &description,
+            ));
+        }
+        items.sort_by(|a, b| a.risk_level.cmp(&b.risk_level).then(b.size.cmp(&a.size)));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_pycaches_grouped_by_project() {
+        let root = tempfile::TempDir::new().unwrap();
+        let api = root.path().join("work/api");
+        fs::create_dir_all(api.join("src/api/__pycache__")).unwrap();
+        fs::create_dir_all(api.join("tests/__pycache__")).unwrap();
+        fs::create_dir_all(api.join(".venv/lib/site/__pycache__")).unwrap();
+        fs::write(api.join(".venv/pyvenv.cfg"), "").unwrap();
+        fs::write(api.join("pyproject.toml"), "").unwrap();
+        // A loose script folder is its own project
+        fs::create_dir_all(root.path().join("scripts/__pycache__")).unwrap();
+
+        let found = find_pycaches(root.path(), None).unwrap();
+        assert_eq!(found.keys().cloned().collect::<Vec<_>>(), vec![root.path().join("scripts"), api.clone()]);
+        assert_eq!(found[&api], vec![api.join("src/api/__pycache__"), api.join("tests/__pycache__")]);
+    }
+
+    #[test]
+    fn test_find_conda_envs() {
+        let home = tempfile::TempDir::new().unwrap();
+        let env = home.path().join("miniconda3/envs/ml");
+        fs::create_dir_all(env.join("conda-meta")).unwrap();
+        fs::write(env.join("conda-meta/history"), "==> 2024-01-01 <==").unwrap();
+        fs::create_dir_all(home.path().join(".conda/envs/broken")).unwrap();
+
+        let envs = find_conda_envs(home.path());
+        assert_eq!(envs.len(), 1);
+        assert_eq!((envs[0].name.as_str(), &envs[0].path), ("ml", &env));
+        assert!(envs[0].last_used > 0);
+    }
+}
@@ -0,0 +1,78 @@
+//! Read-only report mode, for helping someone diagnose their machine without
+//! risking changes. Scans, reports and exports work as usual; every command
+//! that deletes, moves or restores files, changes services or schedules
+//! cleanups is refused here in the backend, and background trash purges
+//! pause. Turned on in settings, or for a whole session by launching with
+//! `--read-only` (or `PULITO_READ_ONLY=1`), which settings can't turn off.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const READ_ONLY_FLAG: &str = "--read-only";
+pub const READ_ONLY_ENV: &str = "PULITO_READ_ONLY";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ReadOnlyStatus {
+    pub enabled: bool,
+    /// Enabled at launch; stays on until the app is restarted without it
+    pub locked: bool,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the launch arguments or environment ask for read-only mode
+fn requested_at_launch(mut args: impl Iterator<Item = String>, env: Option<&str>) -> bool {
+    args.any(|arg| arg == READ_ONLY_FLAG) || env.is_some_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
+/// Lock read-only mode on when launched with `--read-only`; called once at startup
+pub fn init_from_launch() {
+    let env = std::env::var(READ_ONLY_ENV).ok();
+    if requested_at_launch(std::env::args(), env.as_deref()) {
+        LOCKED.store(true, Ordering::Relaxed);
+        ENABLED.store(true, Ordering::Relaxed);
+        tracing::info!("Read-only mode enabled for this session");
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Apply the saved setting; a mode locked at launch stays on
+pub fn apply(enabled: bool) {
+    if !LOCKED.load(Ordering::Relaxed) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+    }
+}
+
+/// Refuse `action` while read-only mode is on
+pub fn check(action: &str) -> Result<(), String> {
+    if is_enabled() {
+        return Err(format!("Read-only mode is on: {} is not available; reports and exports still work", action));
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_read_only_status() -> Result<ReadOnlyStatus, String> {
+    Ok(ReadOnlyStatus { enabled: is_enabled(), locked: LOCKED.load(Ordering::Relaxed) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_at_launch() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>().into_iter();
+        assert!(requested_at_launch(args(&["pulito", "--read-only"]), None));
+        assert!(requested_at_launch(args(&["pulito"]), Some("1")));
+        assert!(!requested_at_launch(args(&["pulito", "--agent"]), Some("0")));
+        assert!(!requested_at_launch(args(&["pulito"]), None));
+    }
+}
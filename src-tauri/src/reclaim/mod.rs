@@ -0,0 +1,147 @@
+//! Space a cleanup actually gave back. Cleanups record the bytes they
+//! removed, but copy-on-write filesystems keep blocks shared with snapshots,
+//! hardlinked files free nothing until the last link goes, files still held
+//! open by a process keep their space, and anything moved to trash stays on
+//! disk until the trash is emptied. So free space is sampled on every mount
+//! when a destructive operation is authorized and again when its cleanup is
+//! recorded, and the difference is stored next to the estimate.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::db::DbAccess;
+use crate::mounts;
+
+/// A baseline older than this belongs to an operation that never recorded a cleanup
+const MAX_BASELINE_AGE: Duration = Duration::from_secs(3600);
+
+struct Baseline {
+    operation: &'static str,
+    taken_at: Instant,
+    /// Mount point -> available bytes
+    free: HashMap<String, u64>,
+}
+
+lazy_static::lazy_static! {
+    /// Free space when the running operation was authorized. Confirmed
+    /// operations rarely overlap; one authorized meanwhile takes over the baseline.
+    static ref BASELINE: Mutex<Option<Baseline>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ReclaimRecord {
+    pub timestamp: i64,
+    pub category: String,
+    pub estimated_bytes: u64,
+    pub measured_bytes: Option<i64>, // None when free space wasn't sampled around it
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ReclaimedSpace {
+    pub estimated_bytes: u64,
+    /// Free space gained by the cleanups that were measured
+    pub measured_bytes: i64,
+    /// What those measured cleanups estimated, to compare against `measured_bytes`
+    pub measured_estimate_bytes: u64,
+    pub measured_cleanups: usize,
+    pub history: Vec<ReclaimRecord>, // newest first
+}
+
+fn sample() -> HashMap<String, u64> {
+    mounts::list_mounts().into_iter().map(|m| (m.mount_point, m.available_space)).collect()
+}
+
+/// Bytes gained over the mounts present in both samples; negative when
+/// something else filled the disk meanwhile
+pub fn measured(before: &HashMap<String, u64>, after: &HashMap<String, u64>) -> i64 {
+    before
+        .iter()
+        .filter_map(|(mount, free)| after.get(mount).map(|now| *now as i64 - *free as i64))
+        .sum()
+}
+
+/// Sample free space before `operation` runs
+pub fn begin(operation: &'static str) {
+    let free = sample();
+    *BASELINE.lock().unwrap_or_else(|e| e.into_inner()) = Some(Baseline { operation, taken_at: Instant::now(), free });
+}
+
+/// Free space gained since `begin`, or since the last cleanup recorded for the
+/// same operation, which becomes the new baseline. None without a recent baseline.
+pub fn finish() -> Option<i64> {
+    let mut baseline = BASELINE.lock().unwrap_or_else(|e| e.into_inner());
+    let current = baseline.as_mut().filter(|b| b.taken_at.elapsed() < MAX_BASELINE_AGE)?;
+    let after = sample();
+    let gained = measured(&current.free, &after);
+    tracing::debug!("{} freed {} bytes of disk space", current.operation, gained);
+    current.free = after;
+    Some(gained)
+}
+
+pub fn summarize(history: Vec<ReclaimRecord>) -> ReclaimedSpace {
+    let measured: Vec<&ReclaimRecord> = history.iter().filter(|r| r.measured_bytes.is_some()).collect();
+    ReclaimedSpace {
+        estimated_bytes: history.iter().map(|r| r.estimated_bytes).sum(),
+        measured_bytes: measured.iter().filter_map(|r| r.measured_bytes).sum(),
+        measured_estimate_bytes: measured.iter().map(|r| r.estimated_bytes).sum(),
+        measured_cleanups: measured.len(),
+        history,
+    }
+}
+
+pub fn load_history(conn: &Connection, since: i64) -> rusqlite::Result<Vec<ReclaimRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, category, bytes_freed, measured_bytes FROM cleanup_history
+         WHERE timestamp >= ?1 ORDER BY timestamp DESC, id DESC",
+    )?;
+    let records = stmt
+        .query_map(params![since], |row| {
+            Ok(ReclaimRecord {
+                timestamp: row.get(0)?,
+                category: row.get(1)?,
+                estimated_bytes: row.get::<_, i64>(2)? as u64,
+                measured_bytes: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(records)
+}
+
+/// Estimated and measured space reclaimed over the last `days` (default 30)
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_reclaimed_space(app_handle: tauri::AppHandle, days: Option<u32>) -> Result<ReclaimedSpace, String> {
+    let since = chrono::Utc::now().timestamp() - i64::from(days.unwrap_or(30)) * 24 * 3600;
+    app_handle
+        .db(|conn| load_history(conn, since))
+        .map(summarize)
+        .map_err(|e| format!("Failed to load cleanup history: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measured_and_summary() {
+        let before = HashMap::from([("/".to_string(), 1000), ("/home".to_string(), 5000), ("/media/usb".to_string(), 10)]);
+        let after = HashMap::from([("/".to_string(), 1200), ("/home".to_string(), 4900)]);
+        assert_eq!(measured(&before, &after), 100);
+
+        let record = |category: &str, estimated, measured| ReclaimRecord {
+            timestamp: 0,
+            category: category.to_string(),
+            estimated_bytes: estimated,
+            measured_bytes: measured,
+        };
+        let summary = summarize(vec![record("Cache", 800, Some(300)), record("Logs", 100, None), record("Docker", 50, Some(60))]);
+        assert_eq!(summary.estimated_bytes, 950);
+        assert_eq!((summary.measured_bytes, summary.measured_estimate_bytes, summary.measured_cleanups), (360, 850, 2));
+    }
+}
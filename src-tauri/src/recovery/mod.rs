@@ -0,0 +1,521 @@
+//! Recovery guidance for permanently deleted items.
+//!
+//! `attempt_recovery` checks, in order of reliability: Pulito's own trash, processes
+//! that still hold the deleted file open (its data stays readable through
+//! /proc/<pid>/fd until the last handle closes), pre-cleanup snapshots, and finally
+//! which undelete/carving tools apply to the filesystem. It only restores data
+//! itself from open handles, and not in read-only mode; everything else is
+//! reported as guidance with a realistic expectation.
+//!
+//! The same open handles explain why deleting a file can free nothing: its
+//! blocks stay allocated until the process holding it closes it.
+//! `pending_release` reports those processes after a cleanup, and
+//! `terminate_holder` asks one to quit.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+use crate::cache_redirect::{self, MountEntry};
+use crate::db::DbAccess;
+use crate::operations::{guard, OperationPlan};
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::read_only;
+use crate::snapshots;
+use crate::trash;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct OpenHandle {
+    pub pid: u32,
+    pub process_name: String,
+    pub fd: u32,
+    pub path: String, // the deleted file this descriptor points to
+}
+
+/// Deleted files a process still holds open, whose space comes back once it
+/// closes them or exits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct PendingRelease {
+    pub pid: u32,
+    pub process_name: String,
+    pub bytes: u64,
+    pub files: Vec<HeldFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct HeldFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct RecoveryTool {
+    pub name: String,
+    pub installed: bool,
+    pub description: String,
+    pub command_hint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct RecoveryReport {
+    pub path: String,
+    pub still_exists: bool,
+    pub trash_item_id: Option<String>, // restorable with restore_from_trash
+    pub open_handles: Vec<OpenHandle>,
+    pub recovered_files: Vec<String>,  // files restored from open handles
+    pub snapshot_hints: Vec<String>,   // rollback pointers of snapshots on the same filesystem
+    pub filesystem: Option<String>,
+    pub device: Option<String>,
+    pub trim_likely: bool, // SSD or discard mount: freed blocks are probably already erased
+    pub tools: Vec<RecoveryTool>,
+    pub likelihood: String, // "recovered", "high", "possible", "unlikely"
+    pub expectation: String,
+    pub steps: Vec<String>,
+}
+
+/// Target of a /proc/<pid>/fd link if it refers to a deleted file
+pub fn deleted_link_target(link: &str) -> Option<&str> {
+    link.strip_suffix(" (deleted)")
+}
+
+/// Open descriptors of processes we can inspect that point at `path` itself
+/// after deletion; files below it don't count, so recovering a directory
+/// never copies out everything deleted inside it
+fn find_open_handles(path: &Path) -> Vec<OpenHandle> {
+    deleted_handles(|target| target == path)
+}
+
+/// Open descriptors of processes we can inspect that point at a deleted file `matches` accepts
+fn deleted_handles(matches: impl Fn(&Path) -> bool) -> Vec<OpenHandle> {
+    let mut handles = Vec::new();
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return handles;
+    };
+
+    for entry in procs.filter_map(|e| e.ok()) {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Other users' processes aren't readable; skip them quietly
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let process_name = std::fs::read_to_string(entry.path().join("comm"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+
+        for fd in fds.filter_map(|e| e.ok()) {
+            let Some(fd_num) = fd.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+            let Ok(link) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let link = link.to_string_lossy();
+            let Some(target) = deleted_link_target(&link) else {
+                continue;
+            };
+            if matches(Path::new(target)) {
+                handles.push(OpenHandle {
+                    pid,
+                    process_name: process_name.clone(),
+                    fd: fd_num,
+                    path: target.to_string(),
+                });
+            }
+        }
+    }
+
+    handles
+}
+
+/// Holders of the deleted files in `handles` with the size of each, largest
+/// first; a file open on several descriptors counts once per process
+pub fn group_by_process(handles: Vec<(OpenHandle, u64)>) -> Vec<PendingRelease> {
+    let mut by_pid: BTreeMap<u32, PendingRelease> = BTreeMap::new();
+    for (handle, size) in handles {
+        let entry = by_pid.entry(handle.pid).or_insert_with(|| PendingRelease {
+            pid: handle.pid,
+            process_name: handle.process_name.clone(),
+            bytes: 0,
+            files: Vec::new(),
+        });
+        if !entry.files.iter().any(|f| f.path == handle.path) {
+            entry.bytes += size;
+            entry.files.push(HeldFile { path: handle.path, size });
+        }
+    }
+    let mut holders: Vec<PendingRelease> = by_pid.into_values().collect();
+    holders.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.pid.cmp(&b.pid)));
+    holders
+}
+
+fn with_sizes(handles: Vec<OpenHandle>) -> Vec<(OpenHandle, u64)> {
+    handles
+        .into_iter()
+        .map(|handle| {
+            // The descriptor link still reaches the deleted file's data
+            let size = std::fs::metadata(format!("/proc/{}/fd/{}", handle.pid, handle.fd)).map(|m| m.len()).unwrap_or(0);
+            (handle, size)
+        })
+        .collect()
+}
+
+/// Processes keeping the space of deleted files in `paths` (or below them)
+/// allocated; every deleted file still open when `paths` is empty
+pub fn pending_release(paths: &[PathBuf]) -> Vec<PendingRelease> {
+    let handles = deleted_handles(|target| paths.is_empty() || paths.iter().any(|p| target.starts_with(p)));
+    group_by_process(with_sizes(handles))
+}
+
+/// Where a recovered file is written: the original path if it is free,
+/// otherwise a `.recovered` sibling
+pub fn restore_destination(original: &Path) -> PathBuf {
+    if !original.exists() {
+        return original.to_path_buf();
+    }
+    let name = original.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    (0..)
+        .map(|i| {
+            let suffix = if i == 0 { ".recovered".to_string() } else { format!(".recovered-{}", i) };
+            original.with_file_name(format!("{}{}", name, suffix))
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| original.to_path_buf())
+}
+
+fn restore_from_handle(handle: &OpenHandle) -> Result<PathBuf, String> {
+    let source = PathBuf::from(format!("/proc/{}/fd/{}", handle.pid, handle.fd));
+    if !source.metadata().map(|m| m.is_file()).unwrap_or(false) {
+        return Err("not a regular file".to_string());
+    }
+
+    let original = Path::new(&handle.path);
+    if let Some(parent) = original.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    let destination = restore_destination(original);
+    let mut input = std::fs::File::open(&source).map_err(|e| e.to_string())?;
+    // create_new: never overwrite anything that appeared in the meantime
+    let mut output = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&destination)
+        .map_err(|e| format!("Cannot create {}: {}", destination.display(), e))?;
+    std::io::copy(&mut input, &mut output).map_err(|e| format!("Copy failed: {}", e))?;
+    Ok(destination)
+}
+
+/// Whether the block device behind `source` (e.g. /dev/nvme0n1p2) is non-rotational
+fn is_ssd(source: &str) -> Option<bool> {
+    let name = Path::new(source).file_name()?.to_str()?;
+    let mut dir = std::fs::canonicalize(format!("/sys/class/block/{}", name)).ok()?;
+    // Partitions keep the queue attributes on their parent disk
+    if dir.join("partition").exists() {
+        dir = dir.parent()?.to_path_buf();
+    }
+    let rotational = std::fs::read_to_string(dir.join("queue/rotational")).ok()?;
+    Some(rotational.trim() == "0")
+}
+
+/// Undelete and carving tools relevant to a filesystem type
+pub fn recovery_tools(fs_type: &str, device: &str, relative_path: &str) -> Vec<RecoveryTool> {
+    let mut tools = Vec::new();
+    if fs_type.starts_with("ext") {
+        tools.push(RecoveryTool {
+            name: "extundelete".to_string(),
+            installed: false,
+            description: "Restores deleted files with their names from ext3/ext4 journals".to_string(),
+            command_hint: format!("sudo extundelete {} --restore-file {}", device, relative_path),
+        });
+        tools.push(RecoveryTool {
+            name: "ext4magic".to_string(),
+            installed: false,
+            description: "Journal-based ext3/ext4 recovery, often works where extundelete doesn't".to_string(),
+            command_hint: format!("sudo ext4magic {} -f {} -r -d /path/on/another/disk", device, relative_path),
+        });
+    }
+    if fs_type == "btrfs" {
+        tools.push(RecoveryTool {
+            name: "btrfs".to_string(),
+            installed: false,
+            description: "`btrfs restore` can copy files out of older tree roots on an unmounted btrfs".to_string(),
+            command_hint: format!("sudo btrfs restore -i --path-regex '^/{}' {} /path/on/another/disk", relative_path, device),
+        });
+    }
+    if matches!(fs_type, "ntfs" | "ntfs3" | "fuseblk") {
+        tools.push(RecoveryTool {
+            name: "ntfsundelete".to_string(),
+            installed: false,
+            description: "Lists and recovers deleted files on NTFS".to_string(),
+            command_hint: format!("sudo ntfsundelete {} --scan", device),
+        });
+    }
+    tools.push(RecoveryTool {
+        name: "photorec".to_string(),
+        installed: false,
+        description: "Carves files by content on any filesystem; file names and folders are lost".to_string(),
+        command_hint: format!("sudo photorec {}", device),
+    });
+
+    for tool in &mut tools {
+        tool.installed = snapshots::tool_available(&tool.name);
+    }
+    tools
+}
+
+fn snapshot_hints(app_handle: &tauri::AppHandle, mount_point: &str) -> Vec<String> {
+    app_handle
+        .db(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT rollback_hint FROM cleanup_snapshots WHERE mount_point = ?1 ORDER BY created_at DESC LIMIT 5",
+            )?;
+            let rows = stmt.query_map([mount_point], |row| row.get::<_, String>(0))?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .unwrap_or_default()
+}
+
+fn build_report(app_handle: &tauri::AppHandle, path: &Path) -> RecoveryReport {
+    let path_str = path.to_string_lossy().to_string();
+    let still_exists = path.exists();
+
+    let trash_item_id = trash::get_trash_items()
+        .items
+        .into_iter()
+        .find(|item| item.original_path == path_str)
+        .map(|item| item.id);
+
+    // Restore each deleted file once, even if several descriptors point at it
+    let open_handles = if still_exists { Vec::new() } else { find_open_handles(path) };
+    let mut recovered_files = Vec::new();
+    let mut seen = HashSet::new();
+    let mut steps = Vec::new();
+    let writable = match read_only::check("Restoring from open handles") {
+        Ok(()) => true,
+        Err(e) => {
+            if !open_handles.is_empty() {
+                steps.push(e);
+            }
+            false
+        }
+    };
+    for handle in open_handles.iter().filter(|_| writable) {
+        if !seen.insert(handle.path.clone()) {
+            continue;
+        }
+        match restore_from_handle(handle) {
+            Ok(destination) => recovered_files.push(destination.to_string_lossy().to_string()),
+            Err(e) => {
+                tracing::warn!("Recovery from {} (pid {}) failed: {}", handle.path, handle.pid, e);
+                seen.remove(&handle.path); // another descriptor may still work
+            }
+        }
+    }
+
+    let mount: Option<MountEntry> = std::fs::read_to_string("/proc/mounts")
+        .ok()
+        .and_then(|mounts| cache_redirect::mount_entry(&mounts, path));
+    let snapshot_hints = mount.as_ref().map(|m| snapshot_hints(app_handle, &m.mount_point)).unwrap_or_default();
+    let trim_likely = mount
+        .as_ref()
+        .map(|m| m.options.split(',').any(|o| o == "discard" || o.starts_with("discard=")) || is_ssd(&m.source).unwrap_or(false))
+        .unwrap_or(false);
+    let tools = mount
+        .as_ref()
+        .map(|m| {
+            let relative = path.strip_prefix(&m.mount_point).unwrap_or(path).to_string_lossy().to_string();
+            recovery_tools(&m.fs_type, &m.source, &relative)
+        })
+        .unwrap_or_default();
+
+    let (likelihood, expectation) = if still_exists {
+        ("high", "The path still exists; nothing needs to be recovered.".to_string())
+    } else if !recovered_files.is_empty() {
+        ("recovered", "A complete copy was read from a process that still had the file open.".to_string())
+    } else if trash_item_id.is_some() {
+        ("high", "The item is in Pulito's trash and can be restored completely.".to_string())
+    } else if !snapshot_hints.is_empty() {
+        ("high", "A snapshot taken before a cleanup on this filesystem most likely still contains it.".to_string())
+    } else if trim_likely {
+        (
+            "unlikely",
+            "The filesystem is on an SSD or mounted with discard: freed blocks are usually erased by TRIM soon after deletion, so undelete tools rarely find intact data.".to_string(),
+        )
+    } else if tools.iter().any(|t| t.installed) {
+        (
+            "possible",
+            "Data may still be on disk until it is overwritten. Results are not guaranteed; carving tools recover content without original names.".to_string(),
+        )
+    } else {
+        (
+            "unlikely",
+            "No open handles, trash entry or snapshot found, and no recovery tool is installed.".to_string(),
+        )
+    };
+
+    if !still_exists && recovered_files.is_empty() {
+        if let Some(id) = &trash_item_id {
+            steps.push(format!("Restore it from the Trash view (item {})", id));
+        }
+        steps.extend(snapshot_hints.iter().cloned());
+        if trash_item_id.is_none() && snapshot_hints.is_empty() {
+            steps.push("Stop writing to this filesystem now; new data can overwrite the deleted blocks".to_string());
+            steps.push("Recover to a different disk, never to the same filesystem".to_string());
+            if let Some(m) = &mount {
+                steps.push(format!("Unmount {} or boot a live system before running recovery tools", m.mount_point));
+            }
+            for tool in tools.iter().filter(|t| t.installed) {
+                steps.push(tool.command_hint.clone());
+            }
+            if !tools.iter().any(|t| t.installed) {
+                steps.push("Install testdisk (provides photorec) or a filesystem-specific undelete tool".to_string());
+            }
+        }
+    }
+
+    RecoveryReport {
+        path: path_str,
+        still_exists,
+        trash_item_id,
+        open_handles,
+        recovered_files,
+        snapshot_hints,
+        filesystem: mount.as_ref().map(|m| m.fs_type.clone()),
+        device: mount.map(|m| m.source),
+        trim_likely,
+        tools,
+        likelihood: likelihood.to_string(),
+        expectation,
+        steps,
+    }
+}
+
+/// Try to get a permanently deleted file back and explain the remaining options
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn attempt_recovery(app_handle: tauri::AppHandle, path: String) -> Result<RecoveryReport, String> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_absolute() || path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err("Path must be absolute and must not contain '..'".to_string());
+    }
+    // A copy is written next to the original, so the nearest folder that
+    // still exists has to be one Pulito may write to
+    let folder = path.ancestors().skip(1).find(|dir| dir.is_dir()).ok_or_else(|| format!("Nothing to recover at {}", path.display()))?;
+    validate_path_comprehensive(&folder.to_string_lossy(), SecurityContext::Deletion).map_err(|e| format!("Recovery refused: {}", e))?;
+    tracing::info!("Attempting recovery of {}", path.display());
+
+    tokio::task::spawn_blocking(move || build_report(&app_handle, &path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Deleted files still held open by processes, below `paths` or anywhere
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_pending_release(paths: Option<Vec<String>>) -> Result<Vec<PendingRelease>, String> {
+    let paths: Vec<PathBuf> = paths.unwrap_or_default().into_iter().map(PathBuf::from).collect();
+    tokio::task::spawn_blocking(move || pending_release(&paths))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Ask a process holding deleted files to quit (SIGTERM) so their space is
+/// released. Only processes that hold deleted files can be stopped this way.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn terminate_holder(app_handle: tauri::AppHandle, pid: u32, confirmation_token: String) -> Result<(), String> {
+    guard::authorize(&app_handle, &OperationPlan::TerminateHolder { pid }, &confirmation_token)?;
+
+    let holder = tokio::task::spawn_blocking(move || find_holder(pid))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(format!("Failed to stop {} ({}): {}", holder.process_name, pid, std::io::Error::last_os_error()));
+    }
+    tracing::info!("Asked {} ({}) to quit, releasing {} bytes", holder.process_name, pid, holder.bytes);
+    Ok(())
+}
+
+/// The deleted files `pid` holds, or why it can't be stopped
+pub fn find_holder(pid: u32) -> Result<PendingRelease, String> {
+    if pid <= 1 || pid == std::process::id() {
+        return Err(format!("Process {} cannot be stopped", pid));
+    }
+    pending_release(&[])
+        .into_iter()
+        .find(|h| h.pid == pid)
+        .ok_or_else(|| format!("Process {} holds no deleted files", pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deleted_link_target() {
+        assert_eq!(deleted_link_target("/home/u/video.mp4 (deleted)"), Some("/home/u/video.mp4"));
+        assert_eq!(deleted_link_target("/home/u/video.mp4"), None);
+        assert_eq!(deleted_link_target("socket:[12345]"), None);
+    }
+
+    #[test]
+    fn test_restore_destination() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let original = dir.path().join("a.txt");
+        assert_eq!(restore_destination(&original), original);
+
+        std::fs::write(&original, b"x").unwrap();
+        assert_eq!(restore_destination(&original), dir.path().join("a.txt.recovered"));
+        std::fs::write(dir.path().join("a.txt.recovered"), b"x").unwrap();
+        assert_eq!(restore_destination(&original), dir.path().join("a.txt.recovered-1"));
+    }
+
+    #[test]
+    fn test_open_handle_recovery() {
+        use std::io::Write;
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("held.bin");
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"still here").unwrap();
+        file.flush().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let handles = find_open_handles(&path);
+        let own = handles.iter().find(|h| h.pid == std::process::id()).expect("own descriptor");
+        let restored = restore_from_handle(own).unwrap();
+        assert_eq!(std::fs::read(restored).unwrap(), b"still here");
+        // Only the exact path matches, not everything below a folder
+        assert!(!find_open_handles(dir.path()).iter().any(|h| h.pid == std::process::id()));
+        drop(file);
+    }
+
+    #[test]
+    fn test_group_by_process() {
+        let handle = |pid, fd, path: &str| OpenHandle { pid, process_name: format!("p{}", pid), fd, path: path.to_string() };
+        let holders = group_by_process(vec![
+            (handle(10, 3, "/tmp/a.log"), 100),
+            (handle(10, 4, "/tmp/a.log"), 100),
+            (handle(10, 5, "/tmp/b.log"), 50),
+            (handle(20, 3, "/tmp/a.log"), 100),
+            (handle(30, 7, "/tmp/big.bin"), 500),
+        ]);
+        assert_eq!(holders.iter().map(|h| (h.pid, h.bytes, h.files.len())).collect::<Vec<_>>(), vec![(30, 500, 1), (10, 150, 2), (20, 100, 1)]);
+        assert_eq!(holders[1].process_name, "p10");
+    }
+
+    #[test]
+    fn test_recovery_tools_by_filesystem() {
+        let names = |fs: &str| recovery_tools(fs, "/dev/sda1", "u/a.txt").into_iter().map(|t| t.name).collect::<Vec<_>>();
+        assert_eq!(names("ext4"), vec!["extundelete", "ext4magic", "photorec"]);
+        assert_eq!(names("btrfs"), vec!["btrfs", "photorec"]);
+        assert_eq!(names("xfs"), vec!["photorec"]);
+    }
+}
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::process::Command;
+use tokio::time::{timeout, Duration};
+
+use crate::commands::usage_status;
+use crate::db::DbAccess;
+
+const SSH_CONNECT_TIMEOUT_SECS: u64 = 10;
+const REMOTE_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Single round trip collecting everything the remote views need. Every probe is
+/// read-only and failures are silenced so one missing tool doesn't void the rest.
+const REMOTE_PROBE_SCRIPT: &str = r#"
+echo '##DF'; df -B1 -P -x tmpfs -x devtmpfs -x squashfs -x overlay 2>/dev/null
+echo '##JOURNAL'; du -sb /var/log/journal 2>/dev/null
+echo '##PACKAGE_CACHE'; du -sb /var/cache/apt/archives /var/cache/dnf /var/cache/pacman/pkg 2>/dev/null
+echo '##HOME'; timeout 20 du -b --max-depth=1 "$HOME" 2>/dev/null
+echo '##AGENT'; systemctl --user is-active pulito-agent 2>/dev/null
+echo '##END'
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct RemoteHost {
+    pub id: i64,
+    pub name: String,
+    pub host: String,
+    pub user: Option<String>,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct RemoteFilesystem {
+    pub device: String,
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct RemoteDirUsage {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct RemoteDiskSummary {
+    pub host: RemoteHost,
+    pub filesystems: Vec<RemoteFilesystem>,
+    pub disk_usage_percent: f32, // root filesystem
+    pub status_color: String,
+    pub status_message: String,
+    pub journal_bytes: u64,
+    pub package_cache_bytes: u64,
+    pub home_usage: Vec<RemoteDirUsage>, // largest first
+    pub agent_installed: bool,
+    pub read_only: bool, // cleanup actions are only offered when the agent runs remotely
+    pub collected_at: i64,
+}
+
+/// Hostnames and user names are passed to ssh as arguments; restrict them to
+/// characters that can't be mistaken for options or shell syntax
+pub fn validate_ssh_identifier(value: &str, what: &str) -> Result<(), String> {
+    if value.is_empty() || value.len() > 253 {
+        return Err(format!("Invalid {}: must be 1-253 characters", what));
+    }
+    if value.starts_with('-') {
+        return Err(format!("Invalid {}: must not start with '-'", what));
+    }
+    if !value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':')) {
+        return Err(format!("Invalid {}: contains unsupported characters", what));
+    }
+    Ok(())
+}
+
+/// Split probe output into its `##SECTION` blocks
+pub fn parse_sections(output: &str) -> HashMap<String, Vec<String>> {
+    let mut sections: HashMap<String, Vec<String>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("##") {
+            current = Some(name.trim().to_string());
+            sections.entry(name.trim().to_string()).or_default();
+        } else if let Some(name) = &current {
+            if !line.trim().is_empty() {
+                sections.entry(name.clone()).or_default().push(line.to_string());
+            }
+        }
+    }
+
+    sections
+}
+
+/// Parse `df -B1 -P` lines (header skipped)
+pub fn parse_df_lines(lines: &[String]) -> Vec<RemoteFilesystem> {
+    lines
+        .iter()
+        .filter(|line| !line.starts_with("Filesystem"))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            Some(RemoteFilesystem {
+                device: fields[0].to_string(),
+                total_bytes: fields[1].parse().ok()?,
+                used_bytes: fields[2].parse().ok()?,
+                available_bytes: fields[3].parse().ok()?,
+                // Mount points may contain spaces; everything after the capacity column
+                mount_point: fields[5..].join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Parse `du -sb` and `du -b` lines ("<bytes>\t<path>")
+pub fn parse_du_lines(lines: &[String]) -> Vec<RemoteDirUsage> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let (size, path) = line.split_once('\t')?;
+            Some(RemoteDirUsage {
+                path: path.to_string(),
+                size: size.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+fn build_summary(host: RemoteHost, output: &str) -> Result<RemoteDiskSummary, String> {
+    let sections = parse_sections(output);
+    if !sections.contains_key("END") {
+        return Err("Remote probe output was incomplete".to_string());
+    }
+    let section = |name: &str| sections.get(name).cloned().unwrap_or_default();
+
+    let filesystems = parse_df_lines(&section("DF"));
+    let disk_usage_percent = filesystems
+        .iter()
+        .find(|fs| fs.mount_point == "/")
+        .filter(|fs| fs.total_bytes > 0)
+        .map(|fs| (fs.used_bytes as f32 / fs.total_bytes as f32) * 100.0)
+        .unwrap_or(0.0);
+    let (status_color, status_message) = usage_status(disk_usage_percent);
+
+    let journal_bytes = parse_du_lines(&section("JOURNAL")).iter().map(|d| d.size).sum();
+    let package_cache_bytes = parse_du_lines(&section("PACKAGE_CACHE")).iter().map(|d| d.size).sum();
+
+    // du --max-depth=1 also prints the home directory itself (the shortest path)
+    let mut home_usage = parse_du_lines(&section("HOME"));
+    if let Some(root) = home_usage.iter().map(|d| d.path.clone()).min_by_key(|p| p.len()) {
+        home_usage.retain(|d| d.path != root);
+    }
+    home_usage.sort_by(|a, b| b.size.cmp(&a.size));
+    home_usage.truncate(20);
+
+    let agent_installed = section("AGENT").first().map(|s| s.trim() == "active").unwrap_or(false);
+
+    Ok(RemoteDiskSummary {
+        host,
+        filesystems,
+        disk_usage_percent,
+        status_color: status_color.to_string(),
+        status_message,
+        journal_bytes,
+        package_cache_bytes,
+        home_usage,
+        agent_installed,
+        read_only: !agent_installed,
+        collected_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+fn run_ssh(host: &RemoteHost, script: &str) -> Result<String, String> {
+    let destination = match &host.user {
+        Some(user) => format!("{}@{}", user, host.host),
+        None => host.host.clone(),
+    };
+
+    let connect_timeout = format!("ConnectTimeout={}", SSH_CONNECT_TIMEOUT_SECS);
+    let port = host.port.to_string();
+    let output = Command::new("ssh")
+        .args([
+            // Never prompt: the GUI has no terminal for passwords or host key questions
+            "-o", "BatchMode=yes",
+            "-o", connect_timeout.as_str(),
+            "-p", port.as_str(),
+            "--",
+            destination.as_str(),
+            script,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to execute ssh: {}", e))?;
+
+    // Exit status 255 is ssh's own failure (connection, auth); anything else came from the script
+    if output.status.code() == Some(255) {
+        return Err(format!(
+            "SSH connection to {} failed: {}",
+            destination,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn load_host(app_handle: &tauri::AppHandle, host_id: i64) -> Result<RemoteHost, String> {
+    app_handle.db(|conn| {
+        conn.query_row(
+            "SELECT id, name, host, user, port FROM remote_hosts WHERE id = ?1",
+            [host_id],
+            |row| Ok(RemoteHost {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                host: row.get(2)?,
+                user: row.get(3)?,
+                port: row.get::<_, i64>(4)? as u16,
+            }),
+        )
+    }).map_err(|_| format!("Remote host {} not found", host_id))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_remote_hosts(app_handle: tauri::AppHandle) -> Result<Vec<RemoteHost>, String> {
+    app_handle.db(|conn| {
+        let mut stmt = conn.prepare("SELECT id, name, host, user, port FROM remote_hosts ORDER BY name")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RemoteHost {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                host: row.get(2)?,
+                user: row.get(3)?,
+                port: row.get::<_, i64>(4)? as u16,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+    }).map_err(|e| format!("Failed to list remote hosts: {}", e))
+}
+
+/// Register a host reachable with key-based SSH auth (e.g. an entry from ~/.ssh/config)
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn add_remote_host(
+    app_handle: tauri::AppHandle,
+    name: String,
+    host: String,
+    user: Option<String>,
+    port: Option<u16>,
+) -> Result<RemoteHost, String> {
+    validate_ssh_identifier(&host, "host")?;
+    if let Some(user) = &user {
+        validate_ssh_identifier(user, "user")?;
+    }
+    let port = port.unwrap_or(22);
+    let name = if name.trim().is_empty() { host.clone() } else { name.trim().to_string() };
+
+    let id = app_handle.db(|conn| {
+        conn.execute(
+            "INSERT INTO remote_hosts (name, host, user, port, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![name, host, user, port as i64, chrono::Utc::now().timestamp()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }).map_err(|e| format!("Failed to save remote host: {}", e))?;
+
+    Ok(RemoteHost { id, name, host, user, port })
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn remove_remote_host(app_handle: tauri::AppHandle, host_id: i64) -> Result<(), String> {
+    app_handle.db(|conn| {
+        conn.execute("DELETE FROM remote_hosts WHERE id = ?1", [host_id])?;
+        Ok(())
+    }).map_err(|e| format!("Failed to remove remote host: {}", e))
+}
+
+/// DiskPulse-style summary of a remote host, gathered with read-only commands over SSH
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_remote_disk_summary(app_handle: tauri::AppHandle, host_id: i64) -> Result<RemoteDiskSummary, String> {
+    let host = load_host(&app_handle, host_id)?;
+    tracing::info!("Collecting remote disk summary from {}", host.host);
+
+    let ssh_host = host.clone();
+    let output = timeout(
+        REMOTE_COMMAND_TIMEOUT,
+        tokio::task::spawn_blocking(move || run_ssh(&ssh_host, REMOTE_PROBE_SCRIPT)),
+    )
+    .await
+    .map_err(|_| format!("Remote probe of {} timed out", host.host))?
+    .map_err(|e| format!("Remote probe task failed: {}", e))??;
+
+    build_summary(host, &output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_ssh_identifier() {
+        assert!(validate_ssh_identifier("nas.local", "host").is_ok());
+        assert!(validate_ssh_identifier("192.168.1.10", "host").is_ok());
+        assert!(validate_ssh_identifier("-oProxyCommand=evil", "host").is_err());
+        assert!(validate_ssh_identifier("host; rm -rf ~", "host").is_err());
+        assert!(validate_ssh_identifier("", "user").is_err());
+    }
+
+    #[test]
+    fn test_parse_df_lines() {
+        let df = lines(
+            "Filesystem     1B-blocks        Used   Available Capacity Mounted on\n\
+             /dev/sda2   100000000000 60000000000 40000000000      60% /\n\
+             /dev/sdb1   200000000000 10000000000 190000000000      5% /mnt/media disk",
+        );
+        let filesystems = parse_df_lines(&df);
+        assert_eq!(filesystems.len(), 2);
+        assert_eq!(filesystems[0].mount_point, "/");
+        assert_eq!(filesystems[0].used_bytes, 60_000_000_000);
+        assert_eq!(filesystems[1].mount_point, "/mnt/media disk");
+    }
+
+    #[test]
+    fn test_build_summary() {
+        let output = "##DF\n\
+                      Filesystem 1B-blocks Used Available Capacity Mounted on\n\
+                      /dev/sda2 1000 900 100 90% /\n\
+                      ##JOURNAL\n\
+                      4096\t/var/log/journal\n\
+                      ##PACKAGE_CACHE\n\
+                      1000\t/var/cache/apt/archives\n\
+                      ##HOME\n\
+                      500\t/home/me/.cache\n\
+                      900\t/home/me/Videos\n\
+                      1500\t/home/me\n\
+                      ##AGENT\n\
+                      inactive\n\
+                      ##END\n";
+        let host = RemoteHost { id: 1, name: "nas".into(), host: "nas".into(), user: None, port: 22 };
+        let summary = build_summary(host, output).unwrap();
+
+        assert_eq!(summary.status_color, "red");
+        assert_eq!(summary.journal_bytes, 4096);
+        assert_eq!(summary.package_cache_bytes, 1000);
+        assert!(summary.read_only);
+        assert_eq!(summary.home_usage.len(), 2);
+        assert_eq!(summary.home_usage[0].path, "/home/me/Videos");
+
+        let truncated = RemoteHost { id: 1, name: "nas".into(), host: "nas".into(), user: None, port: 22 };
+        assert!(build_summary(truncated, "##DF\n").is_err());
+    }
+
+    #[test]
+    fn test_home_probe_runs_du() {
+        let home = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(home.path().join("Videos")).unwrap();
+        std::fs::write(home.path().join("Videos/clip.mp4"), vec![0u8; 64 * 1024]).unwrap();
+        std::fs::create_dir(home.path().join(".cache")).unwrap();
+
+        // Run the probe's own line, so an option du rejects shows up as an empty section
+        let probe = REMOTE_PROBE_SCRIPT.lines().find(|l| l.starts_with("echo '##HOME'")).unwrap();
+        let output = Command::new("sh").arg("-c").arg(probe).env("HOME", home.path()).output().unwrap();
+        let sections = parse_sections(&String::from_utf8_lossy(&output.stdout));
+        let usage = parse_du_lines(&sections["HOME"]);
+
+        assert_eq!(usage.len(), 3);
+        let videos = usage.iter().find(|d| d.path.ends_with("/Videos")).unwrap();
+        assert!(videos.size >= 64 * 1024);
+        assert!(usage.iter().any(|d| d.path == home.path().to_string_lossy()));
+    }
+}
@@ -0,0 +1,223 @@
+//! Safe mode, for computers shared with less technical family members. While
+//! it is on nothing is deleted permanently: commands that bypass the trash are
+//! refused, everything moved to trash stays there for at least
+//! `min_retention_days`, and `clean_items` only removes an item of medium or
+//! higher risk with an acknowledgement issued for that item on its own, and
+//! only for items a scan reported. Turning it off or shortening its retention
+//! needs a confirmation token like any destructive operation. Enforced here
+//! rather than in the UI, so no page can skip it.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::operations::{classify_risk, OperationPlan};
+use crate::scanner::ScanItem;
+use crate::trash;
+
+/// How long an item acknowledgement stays valid
+pub const ACKNOWLEDGEMENT_TTL: Duration = Duration::from_secs(10 * 60);
+/// Items at or above this risk level need an acknowledgement each
+pub const ACKNOWLEDGED_RISK_LEVEL: u8 = 2;
+/// Scanned paths remembered for acknowledgements; the oldest are forgotten
+/// all at once past this
+const MAX_SCANNED_PATHS: usize = 200_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SafeModeSettings {
+    pub enabled: bool,
+    /// Trash retention never drops below this while safe mode is on
+    pub min_retention_days: i64,
+}
+
+impl Default for SafeModeSettings {
+    fn default() -> Self {
+        Self { enabled: false, min_retention_days: 14 }
+    }
+}
+
+struct Acknowledgement {
+    path: PathBuf,
+    issued_at: Instant,
+}
+
+#[derive(Default)]
+struct Acknowledgements {
+    issued: HashMap<String, Acknowledgement>,
+}
+
+impl Acknowledgements {
+    fn issue(&mut self, path: PathBuf, now: Instant) -> String {
+        self.issued.retain(|_, a| now.duration_since(a.issued_at) < ACKNOWLEDGEMENT_TTL);
+        let token = uuid::Uuid::new_v4().to_string();
+        self.issued.insert(token.clone(), Acknowledgement { path, issued_at: now });
+        token
+    }
+
+    /// Check that every path in `risky` has a fresh acknowledgement among
+    /// `tokens`; those are consumed only when all of them are there
+    fn take(&mut self, risky: &[PathBuf], tokens: &[String], now: Instant) -> Result<(), String> {
+        let fresh = |token: &String| {
+            self.issued.get(token).filter(|a| now.duration_since(a.issued_at) < ACKNOWLEDGEMENT_TTL).map(|a| a.path.as_path())
+        };
+        if let Some(missing) = risky.iter().find(|path| !tokens.iter().any(|t| fresh(t) == Some(path.as_path()))) {
+            return Err(format!("Safe mode: {} has to be acknowledged before it is removed", missing.display()));
+        }
+        for token in tokens {
+            self.issued.remove(token);
+        }
+        Ok(())
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref ACKNOWLEDGEMENTS: Mutex<Acknowledgements> = Mutex::new(Acknowledgements::default());
+    static ref SCANNED: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+/// Remember the paths of scan results, children included; only those can be
+/// acknowledged
+pub fn record_scanned<'a>(items: impl IntoIterator<Item = &'a ScanItem>) {
+    fn add(scanned: &mut HashSet<PathBuf>, item: &ScanItem) {
+        scanned.insert(PathBuf::from(&item.path));
+        for child in item.children.iter().flatten() {
+            add(scanned, child);
+        }
+    }
+    let mut scanned = SCANNED.lock().unwrap_or_else(|e| e.into_inner());
+    if scanned.len() > MAX_SCANNED_PATHS {
+        scanned.clear();
+    }
+    for item in items {
+        add(&mut scanned, item);
+    }
+}
+
+/// Mirrors the saved setting, so checks don't read the database
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Apply saved settings; called at startup and whenever settings are saved
+pub fn apply(settings: &SafeModeSettings) {
+    ENABLED.store(settings.enabled, Ordering::Relaxed);
+    trash::set_min_retention_days(if settings.enabled { settings.min_retention_days.max(0) } else { 0 });
+}
+
+/// Whether going from `current` to `new` relaxes safe mode
+pub fn weakens(current: &SafeModeSettings, new: &SafeModeSettings) -> bool {
+    current.enabled && (!new.enabled || new.min_retention_days < current.min_retention_days)
+}
+
+/// Why safe mode refuses `plan`, regardless of whether it is on
+fn refusal(plan: &OperationPlan) -> Option<String> {
+    if plan.is_permanent() {
+        return Some(format!("Safe mode is on: {} deletes permanently and is not available", plan.name()));
+    }
+    if let OperationPlan::CleanupOldFiles { .. } = plan {
+        // Selects files by age without listing them, so they can't be acknowledged one by one
+        return Some("Safe mode is on: remove old files from the scan results instead, one at a time".to_string());
+    }
+    None
+}
+
+/// Why safe mode refuses `plan`, while it is on
+pub fn denial(plan: &OperationPlan) -> Option<String> {
+    if is_enabled() {
+        refusal(plan)
+    } else {
+        None
+    }
+}
+
+/// Paths among `item_paths` that need an acknowledgement of their own
+pub fn needing_acknowledgement(item_paths: &[String], home: &Path) -> Vec<PathBuf> {
+    item_paths
+        .iter()
+        .map(PathBuf::from)
+        .filter(|path| classify_risk(path, home).0 >= ACKNOWLEDGED_RISK_LEVEL)
+        .collect()
+}
+
+/// While safe mode is on, require and consume an acknowledgement for every
+/// risky path in `item_paths`
+pub fn check_acknowledgements(item_paths: &[String], tokens: &[String]) -> Result<(), String> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let risky = needing_acknowledgement(item_paths, &home);
+    ACKNOWLEDGEMENTS.lock().unwrap_or_else(|e| e.into_inner()).take(&risky, tokens, Instant::now())
+}
+
+/// Acknowledge removing one item a scan reported in safe mode; pass the token
+/// to `clean_items` in `acknowledgements`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn acknowledge_risky_item(path: String) -> Result<String, String> {
+    if !SCANNED.lock().map_err(|e| format!("Lock failed: {}", e))?.contains(Path::new(&path)) {
+        return Err(format!("Safe mode: {} isn't in any scan result, so it can't be acknowledged", path));
+    }
+    let mut acknowledgements = ACKNOWLEDGEMENTS.lock().map_err(|e| format!("Lock failed: {}", e))?;
+    Ok(acknowledgements.issue(PathBuf::from(path), Instant::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refusal() {
+        let clean = |use_trash| OperationPlan::CleanItems { item_paths: vec!["/home/u/a".to_string()], use_trash, retention_days: 3 };
+        assert!(refusal(&clean(false)).is_some());
+        assert!(refusal(&clean(true)).is_none());
+        assert!(refusal(&OperationPlan::EmptyTrash).is_some());
+        assert!(refusal(&OperationPlan::CleanupOldFiles { days_cutoff: 90, include_libraries: Vec::new() }).is_some());
+        assert!(refusal(&OperationPlan::ClearCache).is_none());
+    }
+
+    #[test]
+    fn test_weakens() {
+        let on = SafeModeSettings { enabled: true, min_retention_days: 14 };
+        assert!(weakens(&on, &SafeModeSettings { enabled: false, ..on.clone() }));
+        assert!(weakens(&on, &SafeModeSettings { min_retention_days: 7, ..on.clone() }));
+        assert!(!weakens(&on, &SafeModeSettings { min_retention_days: 30, ..on.clone() }));
+        assert!(!weakens(&SafeModeSettings::default(), &on));
+        assert!(!weakens(&SafeModeSettings::default(), &SafeModeSettings::default()));
+    }
+
+    #[tokio::test]
+    async fn test_only_scanned_items_are_acknowledged() {
+        use crate::scanner::builtin::new_item;
+        let mut dir = new_item("ack-test".to_string(), Path::new("/home/u/ack-test"), 10, "directory", "Cache", 2, "");
+        dir.children = Some(vec![new_item("a".to_string(), Path::new("/home/u/ack-test/a"), 10, "file", "Cache", 2, "")]);
+        assert!(acknowledge_risky_item("/home/u/ack-test/a".to_string()).await.is_err());
+        record_scanned([&dir]);
+        assert!(acknowledge_risky_item("/home/u/ack-test/a".to_string()).await.is_ok());
+        assert!(acknowledge_risky_item("/home/u/ack-test/b".to_string()).await.is_err());
+    }
+
+    #[test]
+    fn test_acknowledgements_are_per_item_and_single_use() {
+        let home = Path::new("/home/u");
+        let paths = vec!["/home/u/.cache/pip".to_string(), "/home/u/Videos/a.mp4".to_string(), "/home/u/.config/app".to_string()];
+        let risky = needing_acknowledgement(&paths, home);
+        assert_eq!(risky, vec![PathBuf::from("/home/u/Videos/a.mp4"), PathBuf::from("/home/u/.config/app")]);
+
+        let mut acks = Acknowledgements::default();
+        let now = Instant::now();
+        let video = acks.issue(PathBuf::from("/home/u/Videos/a.mp4"), now);
+        assert!(acks.take(&risky, &[video.clone()], now).unwrap_err().contains(".config/app"));
+        let config = acks.issue(PathBuf::from("/home/u/.config/app"), now);
+        assert!(acks.take(&risky, &[video.clone(), config.clone()], now + ACKNOWLEDGEMENT_TTL).is_err());
+        assert!(acks.take(&risky, &[video.clone(), config.clone()], now).is_ok());
+        assert!(acks.take(&risky, &[video, config], now).is_err());
+    }
+}
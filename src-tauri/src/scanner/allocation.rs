@@ -0,0 +1,97 @@
+//! Bytes a file really takes on disk. The apparent size (`st_size`) overstates
+//! sparse files such as VM images and files on compressed filesystems, while
+//! `st_blocks` is what deleting the file gives back. A file with
+//! several hard links takes its blocks once, so walks count each (device,
+//! inode) pair once; cached per-directory sizes, which can't remember pairs
+//! across walks, count a share of the blocks per link instead.
+
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+
+/// Bytes allocated on disk; `st_blocks` is always in 512-byte units
+pub fn allocated(metadata: &Metadata) -> u64 {
+    metadata.blocks() * 512
+}
+
+/// Apparent size, when it isn't what the file takes on disk to within a block
+pub fn apparent_if_different(metadata: &Metadata) -> Option<u64> {
+    let apparent = metadata.len();
+    let block = metadata.blksize().max(512);
+    (apparent.abs_diff(allocated(metadata)) >= block).then_some(apparent)
+}
+
+/// Allocated bytes divided over the file's links
+pub fn shared(metadata: &Metadata) -> u64 {
+    allocated(metadata) / metadata.nlink().max(1)
+}
+
+/// Files already counted in a walk, by (device, inode)
+#[derive(Debug, Default)]
+pub struct InodeSet {
+    seen: HashSet<(u64, u64)>,
+}
+
+impl InodeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether this is the first link of its file met in the walk
+    pub fn first(&mut self, metadata: &Metadata) -> bool {
+        metadata.nlink() <= 1 || self.seen.insert((metadata.dev(), metadata.ino()))
+    }
+
+    /// Allocated bytes the first time a file's inode is met, 0 for further links
+    pub fn count(&mut self, metadata: &Metadata) -> u64 {
+        if self.first(metadata) {
+            allocated(metadata)
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Seek, SeekFrom, Write};
+
+    #[test]
+    fn test_hardlinks_and_sparse_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        // Incompressible, so transparent compression doesn't shrink it
+        let mut state = 0x2545_F491u32;
+        let bytes: Vec<u8> = (0..64 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        fs::write(&data, bytes).unwrap();
+        fs::hard_link(&data, dir.path().join("link")).unwrap();
+
+        let mut seen = InodeSet::new();
+        let first = seen.count(&fs::metadata(&data).unwrap());
+        assert!(first >= 64 * 1024);
+        assert_eq!(seen.count(&fs::metadata(dir.path().join("link")).unwrap()), 0);
+        assert_eq!(shared(&fs::metadata(&data).unwrap()), first / 2);
+
+        // A hole at the start of a file takes no blocks on filesystems that support them
+        let sparse = dir.path().join("sparse");
+        let mut file = fs::File::create(&sparse).unwrap();
+        file.seek(SeekFrom::Start(16 * 1024 * 1024)).unwrap();
+        file.write_all(b"end").unwrap();
+        drop(file);
+        let metadata = fs::metadata(&sparse).unwrap();
+        assert_eq!(metadata.len(), 16 * 1024 * 1024 + 3);
+        if allocated(&metadata) < 1024 * 1024 {
+            assert_eq!(apparent_if_different(&metadata), Some(metadata.len()));
+        }
+        assert_eq!(apparent_if_different(&fs::metadata(&data).unwrap()), None);
+    }
+}
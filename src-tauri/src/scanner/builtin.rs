@@ -0,0 +1,316 @@
+//! Built-in scan categories
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use walkdir::WalkDir;
+
+use super::allocation;
+use super::registry::{ScanContext, Scanner, ScannerOptionSchema};
+use crate::flatpak::appdata;
+use crate::libraries::LibraryDetector;
+use super::{ScanItem, ScanOptions, ScannerError};
+
+pub fn builtin_scanners() -> Vec<Arc<dyn Scanner>> {
+    vec![
+        Arc::new(CachesScanner),
+        Arc::new(crate::xdg_trash::XdgTrashScanner),
+        Arc::new(PackageCachesScanner),
+        Arc::new(super::logs::LogsScanner),
+        Arc::new(LargeFilesScanner),
+        Arc::new(super::custom::CustomLocationsScanner),
+        Arc::new(super::plugins::PluginAnalyzerScanner),
+        Arc::new(crate::docker::DockerScanner),
+        Arc::new(crate::flatpak::FlatpakScanner),
+        Arc::new(crate::snaps::SnapScanner),
+        Arc::new(crate::leftovers::OrphanedConfigScanner),
+        Arc::new(crate::journal::JournalScanner),
+        Arc::new(crate::coredumps::CoredumpScanner),
+        Arc::new(crate::thumbnails::StaleThumbnailScanner),
+        Arc::new(crate::browsers::BrowserProfileScanner),
+        Arc::new(crate::electron::ElectronCacheScanner),
+        Arc::new(crate::installers::OldInstallersScanner),
+        Arc::new(crate::wine::WinePrefixScanner),
+        Arc::new(crate::packages::kernels::OldKernelsScanner),
+        Arc::new(crate::projects::ProjectArtifactScanner(&crate::projects::NODE_MODULES)),
+        Arc::new(crate::projects::ProjectArtifactScanner(&crate::projects::CARGO_TARGET)),
+        Arc::new(crate::projects::ProjectArtifactScanner(&crate::projects::PYTHON_VENV)),
+        Arc::new(crate::projects::python::PycacheScanner),
+        Arc::new(crate::projects::python::CondaEnvScanner),
+    ]
+}
+
+pub(crate) fn home_dir() -> Result<PathBuf, ScannerError> {
+    dirs::home_dir()
+        .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))
+}
+
+pub(crate) fn file_name_or_path(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+pub(crate) fn new_item(name: String, path: &Path, size: u64, item_type: &str, category: &str, risk_level: u8, description: &str) -> ScanItem {
+    ScanItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        path: path.to_string_lossy().to_string(),
+        size,
+        apparent_size: None,
+        item_type: item_type.to_string(),
+        category: category.to_string(),
+        risk_level,
+        description: description.to_string(),
+        children: None,
+        dependencies: None,
+        dependents: None,
+    }
+}
+
+pub struct CachesScanner;
+
+impl CachesScanner {
+    /// Largest application caches inside a cache directory (top 10 over 5MB)
+    fn scan_subdirs(path: &Path, ctx: &ScanContext) -> Result<Option<Vec<ScanItem>>, ScannerError> {
+        let mut children = Vec::new();
+
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                ctx.check_cancelled()?;
+                let entry_path = entry.path();
+                if entry_path.is_dir() {
+                    let size = ctx.dir_size(&entry_path);
+                    if size > 5 * 1024 * 1024 {
+                        children.push(new_item(
+                            file_name_or_path(&entry_path),
+                            &entry_path,
+                            size,
+                            "directory",
+                            "Cache",
+                            0,
+                            "Application cache",
+                        ));
+                    }
+                }
+            }
+        }
+
+        if children.is_empty() {
+            Ok(None)
+        } else {
+            children.sort_by(|a, b| b.size.cmp(&a.size));
+            Ok(Some(children.into_iter().take(10).collect()))
+        }
+    }
+}
+
+impl Scanner for CachesScanner {
+    fn id(&self) -> &'static str {
+        "caches"
+    }
+
+    fn name(&self) -> &'static str {
+        "Caches"
+    }
+
+    fn description(&self) -> &'static str {
+        "User cache directories, thumbnails, browser caches and Flatpak app caches"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_caches
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let mut items = Vec::new();
+        let home = home_dir()?;
+
+        let cache_dirs = [
+            (home.join(".cache"), "User Cache"),
+            (home.join(".thumbnails"), "Thumbnails"),
+        ];
+
+        for (path, name) in &cache_dirs {
+            ctx.check_cancelled()?;
+            ctx.progress(0, &format!("Scanning {}...", name));
+            if path.exists() && ctx.allows(path) {
+                let size = ctx.dir_size(path);
+                if size > 0 {
+                    let mut item = new_item(name.to_string(), path, size, "cache", "Cache", 0, "Cache directory - safe to remove");
+                    item.children = Self::scan_subdirs(path, ctx)?;
+                    items.push(item);
+                }
+            }
+        }
+
+        ctx.progress(75, "Scanning browser caches...");
+
+        let browser_caches = [
+            (home.join(".cache/google-chrome"), "Chrome Cache"),
+            (home.join(".cache/mozilla/firefox"), "Firefox Cache"),
+            (home.join(".cache/chromium"), "Chromium Cache"),
+        ];
+
+        for (path, name) in &browser_caches {
+            ctx.check_cancelled()?;
+            if path.exists() && ctx.allows(path) {
+                let size = ctx.dir_size(path);
+                if size > 10 * 1024 * 1024 {
+                    items.push(new_item(name.to_string(), path, size, "cache", "Browser", 0, "Browser cache - safe to remove"));
+                }
+            }
+        }
+
+        ctx.progress(90, "Scanning Flatpak app caches...");
+
+        // Sandboxed apps keep their caches in ~/.var/app/<id>/cache, next to data that stays
+        let running = appdata::running_now();
+        for app in appdata::find_apps(&home) {
+            ctx.check_cancelled()?;
+            let cache_dir = app.cache_dir();
+            if !ctx.allows(&cache_dir) {
+                continue;
+            }
+            let size = ctx.dir_size(&cache_dir);
+            if size == 0 {
+                continue;
+            }
+            let data = ctx.dir_size(&app.dir.join("data")) + ctx.dir_size(&app.dir.join("config"));
+            let is_running = running.contains(&app.app_id);
+            let description = format!(
+                "Flatpak app cache - {} of app data and settings are kept{}",
+                super::format_bytes(data),
+                if is_running { "; the app is running" } else { "" }
+            );
+            let risk_level = if is_running { 1 } else { 0 };
+            items.push(new_item(app.item_name(), &cache_dir, size, "cache", appdata::CATEGORY, risk_level, &description));
+        }
+
+        Ok(items)
+    }
+}
+
+pub struct PackageCachesScanner;
+
+impl Scanner for PackageCachesScanner {
+    fn id(&self) -> &'static str {
+        "packages"
+    }
+
+    fn name(&self) -> &'static str {
+        "Package Caches"
+    }
+
+    fn description(&self) -> &'static str {
+        "Downloaded APT packages and pip/npm caches"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_packages
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = home_dir()?;
+
+        let package_caches = [
+            (PathBuf::from("/var/cache/apt/archives"), "APT Package Cache", "Package Manager", "Downloaded .deb packages - safe to remove"),
+            (home.join(".cache/pip"), "pip Cache", "Python", "Python package cache - safe to remove"),
+            (home.join(".npm/_cacache"), "npm Cache", "Node.js", "Node.js package cache - safe to remove"),
+        ];
+
+        let mut items = Vec::new();
+        for (index, (path, name, category, description)) in package_caches.iter().enumerate() {
+            ctx.check_cancelled()?;
+            ctx.progress((index * 100 / package_caches.len()) as u8, &format!("Scanning {}...", name));
+            if path.exists() && ctx.allows(path) {
+                let size = ctx.dir_size(path);
+                if size > 0 {
+                    items.push(new_item(name.to_string(), path, size, "cache", category, 0, description));
+                }
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+pub struct LargeFilesScanner;
+
+impl Scanner for LargeFilesScanner {
+    fn id(&self) -> &'static str {
+        "large_files"
+    }
+
+    fn name(&self) -> &'static str {
+        "Large Files"
+    }
+
+    fn description(&self) -> &'static str {
+        "The 20 largest files in Downloads and Documents, grouped per app library"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        vec![ScannerOptionSchema {
+            key: "min_size_mb".to_string(),
+            label: "Minimum file size (MB)".to_string(),
+            value_type: "number".to_string(),
+            default_value: "100".to_string(),
+        }]
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_large_files
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = home_dir()?;
+        let threshold = ctx.option("min_size_mb", 100u64) * 1024 * 1024;
+        let home_mount = crate::mounts::mount_for_path(&crate::mounts::list_mounts(), &home).map(|m| m.mount_point.clone());
+        // Libraries are looked for below `library_stop`
+        let (scan_dirs, library_stop) = match ctx.mount_point() {
+            // A volume without the home directory: look through the whole volume
+            Some(mount_point) if home_mount.as_deref() != Some(mount_point) => {
+                (vec![PathBuf::from(mount_point)], PathBuf::from(mount_point))
+            }
+            _ => (vec![home.join("Downloads"), home.join("Documents")], home.clone()),
+        };
+        let mut items = Vec::new();
+
+        for (index, dir) in scan_dirs.iter().enumerate() {
+            ctx.progress((index * 100 / scan_dirs.len()) as u8, &format!("Scanning {}...", dir.display()));
+            if !dir.exists() {
+                continue;
+            }
+
+            for entry in WalkDir::new(dir)
+                .max_depth(ctx.limits.max_depth)
+                .same_file_system(ctx.one_file_system())
+                .into_iter()
+                .filter_entry(|e| !e.file_type().is_dir() || ctx.enters(e.path()))
+                .filter_map(|e| e.ok())
+                .take(ctx.limits.max_files)
+            {
+                ctx.check_cancelled()?;
+                ctx.pace();
+                let path = entry.path();
+                if path.is_file() {
+                    if let Ok(metadata) = path.metadata() {
+                        let size = allocation::allocated(&metadata);
+                        if size > threshold {
+                            let mut item = new_item(file_name_or_path(path), path, size, "file", "Large Files", 2, "Large file - review before removing");
+                            item.apparent_size = allocation::apparent_if_different(&metadata);
+                            items.push(item);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Files inside an app's library become one item per library
+        let mut items = crate::libraries::group_scan_items(items, &mut LibraryDetector::new(&library_stop));
+        items.truncate(20); // Limit results
+        Ok(items)
+    }
+}
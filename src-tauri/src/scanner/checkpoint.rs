@@ -0,0 +1,162 @@
+//! Checkpoints that let a system scan pick up where a timed-out or cancelled
+//! attempt stopped. Each scanner's results are saved to `scan_checkpoints` as
+//! soon as it finishes, keyed by the scan options, and the next `start_scan`
+//! with the same options reuses them instead of running that scanner again. A
+//! scanner that was interrupted midway starts over, as its output was partial.
+
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{ScanItem, ScanOptions};
+use crate::db::DbAccess;
+
+/// Saved results older than this are scanned again rather than trusted
+pub const MAX_CHECKPOINT_AGE_SECS: i64 = 6 * 60 * 60;
+
+/// Identity of a scan for resuming: its options, minus those that only change
+/// how fast it runs or whether it resumes
+pub fn options_key(options: &ScanOptions) -> String {
+    let mut options = options.clone();
+    options.throughput_profile = None;
+    options.max_memory_mb = None;
+    options.resume = None;
+    // serde_json maps are sorted, so scanner_options hash the same in any order
+    let canonical = serde_json::to_value(&options).map(|v| v.to_string()).unwrap_or_default();
+    blake3::hash(canonical.as_bytes()).to_hex().to_string()
+}
+
+pub fn save_phase(conn: &Connection, key: &str, scanner: &str, items: &[ScanItem], now: i64) -> rusqlite::Result<()> {
+    let items = serde_json::to_string(items).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO scan_checkpoints (options_key, scanner, items, completed_at) VALUES (?1, ?2, ?3, ?4)",
+        params![key, scanner, items, now],
+    )?;
+    Ok(())
+}
+
+/// Results saved for `key` by scanner id, dropping every checkpoint that's too old
+pub fn load_phases(conn: &Connection, key: &str, now: i64) -> rusqlite::Result<HashMap<String, Vec<ScanItem>>> {
+    conn.execute(
+        "DELETE FROM scan_checkpoints WHERE completed_at < ?1",
+        [now - MAX_CHECKPOINT_AGE_SECS],
+    )?;
+    let mut stmt = conn.prepare("SELECT scanner, items FROM scan_checkpoints WHERE options_key = ?1")?;
+    let rows = stmt.query_map([key], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    let mut phases = HashMap::new();
+    for row in rows {
+        let (scanner, items) = row?;
+        match serde_json::from_str::<Vec<ScanItem>>(&items) {
+            Ok(items) => {
+                phases.insert(scanner, items);
+            }
+            Err(e) => tracing::warn!("Ignoring unreadable {} checkpoint: {}", scanner, e),
+        }
+    }
+    Ok(phases)
+}
+
+pub fn clear_phases(conn: &Connection, key: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM scan_checkpoints WHERE options_key = ?1", [key])?;
+    Ok(())
+}
+
+/// Checkpoint of one system scan, see the module docs
+pub struct Checkpoint {
+    app_handle: tauri::AppHandle,
+    key: String,
+    saved: HashMap<String, Vec<ScanItem>>,
+}
+
+impl Checkpoint {
+    /// Load what an earlier attempt with the same options saved; with `resume`
+    /// off, that is discarded and the scan starts over
+    pub fn load(app_handle: &tauri::AppHandle, options: &ScanOptions, resume: bool) -> Checkpoint {
+        let key = options_key(options);
+        let now = chrono::Utc::now().timestamp();
+        let saved = if resume {
+            app_handle.db(|conn| load_phases(conn, &key, now)).unwrap_or_else(|e| {
+                tracing::warn!("Failed to load scan checkpoint: {}", e);
+                HashMap::new()
+            })
+        } else {
+            if let Err(e) = app_handle.db(|conn| clear_phases(conn, &key)) {
+                tracing::warn!("Failed to discard scan checkpoint: {}", e);
+            }
+            HashMap::new()
+        };
+        if !saved.is_empty() {
+            tracing::info!("Resuming scan: {} scanner(s) already finished", saved.len());
+        }
+        Checkpoint { app_handle: app_handle.clone(), key, saved }
+    }
+
+    /// Results an earlier attempt saved for `scanner`, minus files removed since
+    pub fn saved(&self, scanner: &str) -> Option<Vec<ScanItem>> {
+        let mut items = self.saved.get(scanner)?.clone();
+        items.retain(|item| {
+            let path = Path::new(&item.path);
+            !path.is_absolute() || path.symlink_metadata().is_ok()
+        });
+        Some(items)
+    }
+
+    pub fn record(&self, scanner: &str, items: &[ScanItem]) {
+        let now = chrono::Utc::now().timestamp();
+        if let Err(e) = self.app_handle.db(|conn| save_phase(conn, &self.key, scanner, items, now)) {
+            tracing::warn!("Failed to save {} scan checkpoint: {}", scanner, e);
+        }
+    }
+
+    /// Forget the checkpoint once the scan has finished
+    pub fn clear(&self) {
+        if let Err(e) = self.app_handle.db(|conn| clear_phases(conn, &self.key)) {
+            tracing::warn!("Failed to clear scan checkpoint: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::builtin::new_item;
+    use crate::scanner::ThroughputProfile;
+
+    fn item(path: &str) -> ScanItem {
+        new_item(path.to_string(), Path::new(path), 100, "file", "Logs", 0, "")
+    }
+
+    #[test]
+    fn test_checkpoint_phases() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE scan_checkpoints (options_key TEXT NOT NULL, scanner TEXT NOT NULL, items TEXT NOT NULL,
+             completed_at INTEGER NOT NULL, PRIMARY KEY (options_key, scanner));",
+        )
+        .unwrap();
+
+        save_phase(&conn, "a", "logs", &[item("/var/log/old.log")], 1_000).unwrap();
+        save_phase(&conn, "a", "caches", &[], 1_000).unwrap();
+        save_phase(&conn, "b", "logs", &[item("/tmp/other.log")], 1_000).unwrap();
+
+        let phases = load_phases(&conn, "a", 2_000).unwrap();
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases["logs"][0].path, "/var/log/old.log");
+
+        clear_phases(&conn, "a").unwrap();
+        assert!(load_phases(&conn, "a", 2_000).unwrap().is_empty());
+
+        // Stale checkpoints are dropped instead of resumed
+        assert!(load_phases(&conn, "b", 1_000 + MAX_CHECKPOINT_AGE_SECS + 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_options_key() {
+        let options = ScanOptions::default();
+        let faster =
+            ScanOptions { throughput_profile: Some(ThroughputProfile::Performance), resume: Some(false), ..options.clone() };
+        assert_eq!(options_key(&options), options_key(&faster));
+        let narrower = ScanOptions { include_logs: false, ..options.clone() };
+        assert_ne!(options_key(&options), options_key(&narrower));
+    }
+}
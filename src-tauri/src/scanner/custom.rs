@@ -0,0 +1,298 @@
+//! User-defined cleanup locations loaded from ~/.config/pulito/custom_locations.json
+//!
+//! ```json
+//! {
+//!   "locations": [
+//!     {
+//!       "name": "Foo editor cache",
+//!       "path": "~/.config/Foo/Cache/*",
+//!       "category": "Custom",
+//!       "risk_level": 0,
+//!       "description": "Foo's rebuildable render cache",
+//!       "min_age_days": 7
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The file is re-read on every scan so edits apply without restarting.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use super::allocation;
+use super::builtin::{file_name_or_path, home_dir, new_item};
+use super::glob;
+use super::registry::{ScanContext, Scanner};
+use super::{ScanItem, ScanOptions, ScannerError};
+
+const CONFIG_FILE_NAME: &str = "custom_locations.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CustomLocation {
+    pub name: String,
+    pub path: String, // glob, "~" expands to the home directory
+    #[serde(default = "default_category")]
+    pub category: String,
+    #[serde(default = "default_risk_level")]
+    pub risk_level: u8,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_age_days: Option<u32>,
+}
+
+fn default_category() -> String {
+    "Custom".to_string()
+}
+
+fn default_risk_level() -> u8 {
+    1
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CustomLocationsConfig {
+    #[serde(default)]
+    pub locations: Vec<CustomLocation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CustomLocationsStatus {
+    pub config_path: String,
+    pub exists: bool,
+    pub locations: Vec<CustomLocation>,
+    pub error: Option<String>, // parse or validation error, if any
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("pulito").join(CONFIG_FILE_NAME))
+}
+
+pub fn load_config() -> Result<CustomLocationsConfig, String> {
+    let Some(path) = config_path() else {
+        return Ok(CustomLocationsConfig::default());
+    };
+    if !path.exists() {
+        return Ok(CustomLocationsConfig::default());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+/// Custom locations may only point inside the home directory or the shared temp dirs
+pub fn validate_location(location: &CustomLocation, home: &Path) -> Result<PathBuf, String> {
+    let pattern = glob::expand_home(location.path.trim(), home);
+    if !pattern.is_absolute() {
+        return Err(format!("{}: path must be absolute or start with ~/", location.name));
+    }
+    if location.path.contains("..") {
+        return Err(format!("{}: path must not contain '..'", location.name));
+    }
+
+    let (base, _) = glob::base_and_depth(&pattern);
+    let allowed = [home.to_path_buf(), PathBuf::from("/tmp"), PathBuf::from("/var/tmp")];
+    if base == home || !allowed.iter().any(|root| base.starts_with(root)) {
+        return Err(format!(
+            "{}: only locations below the home directory, /tmp or /var/tmp are allowed",
+            location.name
+        ));
+    }
+    if location.risk_level > 3 {
+        return Err(format!("{}: risk_level must be 0-3", location.name));
+    }
+
+    Ok(pattern)
+}
+
+fn is_older_than(path: &Path, min_age: Option<Duration>) -> bool {
+    let Some(min_age) = min_age else {
+        return true;
+    };
+    std::fs::symlink_metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map(|age| age >= min_age)
+        .unwrap_or(false)
+}
+
+pub struct CustomLocationsScanner;
+
+impl Scanner for CustomLocationsScanner {
+    fn id(&self) -> &'static str {
+        "custom"
+    }
+
+    fn name(&self) -> &'static str {
+        "Custom Locations"
+    }
+
+    fn description(&self) -> &'static str {
+        "Locations declared in ~/.config/pulito/custom_locations.json"
+    }
+
+    fn enabled_by_default(&self, _options: &ScanOptions) -> bool {
+        load_config().map(|c| !c.locations.is_empty()).unwrap_or(false)
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let config = load_config().map_err(ScannerError::PathValidationError)?;
+        let home = home_dir()?;
+        let mut items = Vec::new();
+
+        for (index, location) in config.locations.iter().enumerate() {
+            ctx.check_cancelled()?;
+            ctx.progress((index * 100 / config.locations.len().max(1)) as u8, &format!("Scanning {}...", location.name));
+
+            let pattern = match validate_location(location, &home) {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    tracing::warn!("Skipping custom location: {}", e);
+                    continue;
+                }
+            };
+            let (base, depth) = glob::base_and_depth(&pattern);
+            if !base.exists() {
+                continue;
+            }
+
+            let min_age = location.min_age_days.map(|days| Duration::from_secs(days as u64 * 24 * 3600));
+            let max_depth = depth.unwrap_or(ctx.limits.max_depth).min(ctx.limits.max_depth);
+
+            for entry in WalkDir::new(&base)
+                .min_depth(depth.map(|d| d.min(max_depth)).unwrap_or(0))
+                .max_depth(max_depth)
+                .follow_links(false)
+                .same_file_system(ctx.one_file_system())
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .take(ctx.limits.max_files)
+            {
+                ctx.check_cancelled()?;
+                let path = entry.path();
+                if !glob::matches(&pattern, path) || !is_older_than(path, min_age) {
+                    continue;
+                }
+
+                let size = if entry.file_type().is_dir() {
+                    ctx.dir_size(path)
+                } else {
+                    entry.metadata().map(|m| allocation::allocated(&m)).unwrap_or(0)
+                };
+                if size == 0 {
+                    continue;
+                }
+
+                let item_type = if entry.file_type().is_dir() { "directory" } else { "file" };
+                let description = if location.description.is_empty() {
+                    format!("Custom location: {}", location.name)
+                } else {
+                    location.description.clone()
+                };
+                items.push(new_item(
+                    file_name_or_path(path),
+                    path,
+                    size,
+                    item_type,
+                    &location.category,
+                    location.risk_level,
+                    &description,
+                ));
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+/// Current custom locations file, for the settings UI
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_custom_scan_locations() -> Result<CustomLocationsStatus, String> {
+    let path = config_path().ok_or("Cannot determine config directory")?;
+    let home = home_dir().map_err(|e| e.to_string())?;
+
+    let (locations, error) = match load_config() {
+        Ok(config) => {
+            let invalid: Vec<String> = config
+                .locations
+                .iter()
+                .filter_map(|l| validate_location(l, &home).err())
+                .collect();
+            let error = if invalid.is_empty() { None } else { Some(invalid.join("; ")) };
+            (config.locations, error)
+        }
+        Err(e) => (Vec::new(), Some(e)),
+    };
+
+    Ok(CustomLocationsStatus {
+        config_path: path.to_string_lossy().to_string(),
+        exists: path.exists(),
+        locations,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(path: &str) -> CustomLocation {
+        CustomLocation {
+            name: "test".to_string(),
+            path: path.to_string(),
+            category: default_category(),
+            risk_level: 1,
+            description: String::new(),
+            min_age_days: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_location() {
+        let home = Path::new("/home/u");
+        assert_eq!(validate_location(&location("~/.config/App/Cache/*"), home).unwrap(), PathBuf::from("/home/u/.config/App/Cache/*"));
+        assert!(validate_location(&location("/tmp/app-*"), home).is_ok());
+        assert!(validate_location(&location("/etc/*"), home).is_err());
+        assert!(validate_location(&location("~/*"), home).is_err());
+        assert!(validate_location(&location("~/../other/*"), home).is_err());
+        assert!(validate_location(&location("relative/*"), home).is_err());
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: CustomLocationsConfig = serde_json::from_str(r#"{"locations": [{"name": "a", "path": "~/x"}]}"#).unwrap();
+        assert_eq!(config.locations[0].category, "Custom");
+        assert_eq!(config.locations[0].risk_level, 1);
+        assert!(config.locations[0].min_age_days.is_none());
+    }
+
+    #[test]
+    fn test_scanner_matches_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("cache/a")).unwrap();
+        std::fs::write(dir.path().join("cache/a/blob.bin"), vec![0u8; 64]).unwrap();
+        std::fs::write(dir.path().join("cache/keep.txt"), b"x").unwrap();
+
+        let pattern = dir.path().join("cache/*/*.bin");
+        let (base, depth) = glob::base_and_depth(&pattern);
+        assert_eq!(depth, Some(2));
+        let matched: Vec<_> = WalkDir::new(&base)
+            .min_depth(2)
+            .max_depth(2)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| glob::matches(&pattern, e.path()))
+            .collect();
+        assert_eq!(matched.len(), 1);
+        assert!(matched[0].path().ends_with("blob.bin"));
+    }
+}
@@ -0,0 +1,223 @@
+//! One listing per file across scan categories. Scanners look at the disk
+//! independently, so a big old log in `~/.local/share` is found by both the
+//! logs and the large files scanner. Each file or folder is kept under one
+//! primary category and the others are recorded as tags, so it is counted and
+//! cleaned once; `reconcile_scan_results` drops what a cleanup removed.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use super::{ScanItem, ScanResults};
+
+/// Category of the catch-all large files scanner; any more specific category wins
+const CATCH_ALL_CATEGORY: &str = "Large Files";
+/// Items that stand for their children rather than for their own path
+const GROUPED_TYPES: &[&str] = &["pycache", "thumbnails", "browser_profile", "browser_storage", "installers", "electron_app", "log_group"];
+
+/// An item that more than one category found, listed only under `category`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SharedItem {
+    pub item_id: String,
+    pub path: String,
+    pub category: String,
+    pub also_in: Vec<String>,
+}
+
+/// What makes two listings the same file: the inode where the path exists, so
+/// hard links and differently spelled paths match, otherwise the path itself
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Identity {
+    Inode(u64, u64),
+    Path(String),
+}
+
+fn identity(path: &str) -> Identity {
+    match Path::new(path).symlink_metadata() {
+        Ok(meta) => Identity::Inode(meta.dev(), meta.ino()),
+        Err(_) => Identity::Path(path.to_string()),
+    }
+}
+
+fn precedence(category: &str) -> u8 {
+    u8::from(category == CATCH_ALL_CATEGORY)
+}
+
+/// Remove top-level items that are also listed under a category that takes
+/// precedence: a more specific one, or among equals the one scanned first.
+/// Children are never removed, but a child can be the primary listing.
+pub fn dedupe(items: &mut Vec<ScanItem>) -> Vec<SharedItem> {
+    // Primary listing per identity as (precedence, position, item id, category)
+    let mut primary: HashMap<Identity, (u8, usize, String, String)> = HashMap::new();
+    let mut listings: Vec<Option<Identity>> = Vec::with_capacity(items.len());
+    let mut position = 0;
+    let mut consider = |item: &ScanItem, primary: &mut HashMap<Identity, (u8, usize, String, String)>| {
+        let key = identity(&item.path);
+        let candidate = (precedence(&item.category), position, item.id.clone(), item.category.clone());
+        position += 1;
+        match primary.get(&key) {
+            Some(current) if (current.0, current.1) <= (candidate.0, candidate.1) => {}
+            _ => {
+                primary.insert(key.clone(), candidate);
+            }
+        }
+        key
+    };
+    for item in items.iter() {
+        // Grouped items share their path with what they group, so only their children count
+        let grouped = GROUPED_TYPES.contains(&item.item_type.as_str());
+        listings.push((!grouped).then(|| consider(item, &mut primary)));
+        for child in item.children.iter().flatten() {
+            if !GROUPED_TYPES.contains(&child.item_type.as_str()) {
+                consider(child, &mut primary);
+            }
+        }
+    }
+
+    let mut shared: Vec<SharedItem> = Vec::new();
+    let mut keep = Vec::with_capacity(items.len());
+    for (item, key) in items.drain(..).zip(listings) {
+        match key.and_then(|key| primary.get(&key)) {
+            Some((_, _, id, category)) if *id != item.id => {
+                match shared.iter_mut().find(|s| s.item_id == *id) {
+                    Some(entry) => {
+                        if !entry.also_in.contains(&item.category) {
+                            entry.also_in.push(item.category);
+                        }
+                    }
+                    None => shared.push(SharedItem {
+                        item_id: id.clone(),
+                        path: item.path,
+                        category: category.clone(),
+                        also_in: vec![item.category],
+                    }),
+                }
+            }
+            _ => keep.push(item),
+        }
+    }
+    *items = keep;
+    shared
+}
+
+/// Size and count of the listed items; children count as items of their own
+pub fn recount(results: &mut ScanResults) {
+    results.total_size = results.items.iter().map(|i| i.size).sum();
+    results.total_items = results.items.iter().map(|i| 1 + i.children.as_ref().map_or(0, |c| c.len())).sum();
+}
+
+/// Drop items whose file or folder is gone; grouped items shrink to what is left
+fn retain_existing(items: &mut Vec<ScanItem>) {
+    items.retain_mut(|item| {
+        if GROUPED_TYPES.contains(&item.item_type.as_str()) {
+            let Some(children) = item.children.as_mut() else { return false };
+            retain_existing(children);
+            item.size = children.iter().map(|c| c.size).sum();
+            return !children.is_empty();
+        }
+        // Items of external tools (docker://...) have no path on disk
+        if !Path::new(&item.path).is_absolute() {
+            return true;
+        }
+        if Path::new(&item.path).symlink_metadata().is_err() {
+            return false;
+        }
+        if let Some(children) = item.children.as_mut() {
+            retain_existing(children);
+        }
+        true
+    });
+}
+
+/// Bring scan results up to date after a cleanup: items that were removed are
+/// dropped along with their tags, and the totals recounted
+pub fn reconcile(results: &mut ScanResults) {
+    retain_existing(&mut results.items);
+    let mut listed = Vec::new();
+    for item in &results.items {
+        listed.push(item.id.as_str());
+        listed.extend(item.children.iter().flatten().map(|c| c.id.as_str()));
+    }
+    results.shared_items.retain(|s| listed.contains(&s.item_id.as_str()));
+    recount(results);
+}
+
+/// Scan results without the items a cleanup removed
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn reconcile_scan_results(mut results: ScanResults) -> Result<ScanResults, String> {
+    tokio::task::spawn_blocking(move || {
+        reconcile(&mut results);
+        results
+    })
+    .await
+    .map_err(|e| format!("Failed to reconcile scan results: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::builtin::new_item;
+    use std::fs;
+
+    fn item(id: &str, path: &Path, category: &str) -> ScanItem {
+        ScanItem { id: id.to_string(), ..new_item(id.to_string(), path, 100, "file", category, 1, "") }
+    }
+
+    #[test]
+    fn test_dedupe_prefers_specific_category() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("app.log");
+        fs::write(&log, "log").unwrap();
+        let link = dir.path().join("app-link.log");
+        fs::hard_link(&log, &link).unwrap();
+        let other = dir.path().join("other.iso");
+        fs::write(&other, "iso").unwrap();
+
+        let mut cache = item("cache", dir.path(), "Cache");
+        cache.item_type = "cache".to_string();
+        cache.children = Some(vec![item("cached-log", &link, "Cache")]);
+        let mut items = vec![item("large", &log, "Large Files"), item("logs", &log, "Logs"), cache, item("iso", &other, "Large Files")];
+
+        let shared = dedupe(&mut items);
+        assert_eq!(items.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["logs", "cache", "iso"]);
+        assert_eq!(shared.len(), 1);
+        assert_eq!((shared[0].item_id.as_str(), shared[0].category.as_str()), ("logs", "Logs"));
+        assert!(shared[0].also_in.contains(&"Large Files".to_string()));
+    }
+
+    #[test]
+    fn test_reconcile_after_cleanup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let kept = dir.path().join("kept.log");
+        fs::write(&kept, "log").unwrap();
+        let mut group = item("group", dir.path(), "Thumbnails");
+        group.item_type = "thumbnails".to_string();
+        group.children = Some(vec![item("gone-child", &dir.path().join("a.png"), "Thumbnails")]);
+
+        let mut results = ScanResults {
+            items: vec![item("kept", &kept, "Logs"), item("gone", &dir.path().join("gone.log"), "Logs"), group],
+            total_size: 300,
+            total_items: 4,
+            scan_time_ms: 0,
+            timestamp: 0,
+            failed_categories: Vec::new(),
+            cancelled: false,
+            shared_items: vec![SharedItem {
+                item_id: "gone".to_string(),
+                path: String::new(),
+                category: "Logs".to_string(),
+                also_in: vec!["Large Files".to_string()],
+            }],
+            preselected: Vec::new(),
+            resumed_categories: Vec::new(),
+        };
+        reconcile(&mut results);
+        assert_eq!(results.items.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["kept"]);
+        assert!(results.shared_items.is_empty());
+        assert_eq!((results.total_items, results.total_size), (1, 100));
+    }
+}
@@ -0,0 +1,200 @@
+//! Minimal path glob matching for user-defined scan locations and the
+//! include/exclude patterns of `ScanOptions`.
+//!
+//! Supports `*` and `?` within a path component and `**` for any number of
+//! components. A leading `~` expands to the home directory.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Expand a leading `~` or `~/`
+pub fn expand_home(pattern: &str, home: &Path) -> PathBuf {
+    if pattern == "~" {
+        home.to_path_buf()
+    } else if let Some(rest) = pattern.strip_prefix("~/") {
+        home.join(rest)
+    } else {
+        PathBuf::from(pattern)
+    }
+}
+
+fn has_wildcard(component: &str) -> bool {
+    component.contains('*') || component.contains('?')
+}
+
+fn components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+            Component::RootDir => Some("/".to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Match a single path component against a pattern with `*` and `?`
+fn match_component(pattern: &[char], text: &[char]) -> bool {
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            match_component(&pattern[1..], text) || (!text.is_empty() && match_component(pattern, &text[1..]))
+        }
+        (Some('?'), Some(_)) => match_component(&pattern[1..], &text[1..]),
+        (Some(p), Some(t)) if p == t => match_component(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+fn match_components(pattern: &[String], path: &[String]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(p) if p == "**" => {
+            (0..=path.len()).any(|skip| match_components(&pattern[1..], &path[skip..]))
+        }
+        Some(p) => {
+            let Some(first) = path.first() else {
+                return false;
+            };
+            let p_chars: Vec<char> = p.chars().collect();
+            let t_chars: Vec<char> = first.chars().collect();
+            // Like shells, wildcards don't match hidden entries unless the pattern starts with '.'
+            if first.starts_with('.') && !p.starts_with('.') && has_wildcard(p) {
+                return false;
+            }
+            match_component(&p_chars, &t_chars) && match_components(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+/// Whether `path` matches the (already home-expanded) glob `pattern`
+pub fn matches(pattern: &Path, path: &Path) -> bool {
+    match_components(&components(pattern), &components(path))
+}
+
+/// Split a pattern into its literal base directory and how many components follow it
+/// (`None` when a `**` makes the depth unbounded)
+pub fn base_and_depth(pattern: &Path) -> (PathBuf, Option<usize>) {
+    let parts = components(pattern);
+    let literal_len = parts.iter().take_while(|c| !has_wildcard(c)).count();
+
+    let mut base = PathBuf::new();
+    for part in &parts[..literal_len] {
+        base.push(part);
+    }
+
+    let rest = &parts[literal_len..];
+    let depth = if rest.iter().any(|c| c == "**") { None } else { Some(rest.len()) };
+    (base, depth)
+}
+
+/// Include/exclude globs for a scan. A pattern covers the paths it matches and
+/// everything below them; patterns not starting with `/` or `~` match at any
+/// depth, so `node_modules` behaves like `**/node_modules`.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Vec<PathBuf>,
+    exclude: Vec<PathBuf>,
+    /// Walks descend into filesystems mounted below their root
+    cross_filesystems: bool,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String], home: &Path) -> Self {
+        let expand = |patterns: &[String]| -> Vec<PathBuf> {
+            patterns
+                .iter()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .map(|p| if p.starts_with('/') || p.starts_with('~') { expand_home(p, home) } else { Path::new("/**").join(p) })
+                .collect()
+        };
+        Self { include: expand(include), exclude: expand(exclude), cross_filesystems: false }
+    }
+
+    /// Whether walks stay on the filesystem they start on, as `find -xdev` does
+    pub fn with_one_file_system(mut self, one_file_system: bool) -> Self {
+        self.cross_filesystems = !one_file_system;
+        self
+    }
+
+    /// Pass to `WalkDir::same_file_system`: network shares, external drives
+    /// and bind mounts below home are skipped unless turned off
+    pub fn one_file_system(&self) -> bool {
+        !self.cross_filesystems
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    fn covers(patterns: &[PathBuf], path: &Path) -> bool {
+        path.ancestors().any(|p| patterns.iter().any(|pattern| matches(pattern, p)))
+    }
+
+    /// Whether an item at `path` is reported
+    pub fn allows(&self, path: &Path) -> bool {
+        !Self::covers(&self.exclude, path) && (self.include.is_empty() || Self::covers(&self.include, path))
+    }
+
+    /// Whether a walk should descend into `dir`: it isn't excluded and could hold included paths
+    pub fn enters(&self, dir: &Path) -> bool {
+        !Self::covers(&self.exclude, dir)
+            && (self.include.is_empty()
+                || self.include.iter().any(|pattern| {
+                    let (base, _) = base_and_depth(pattern);
+                    dir.starts_with(&base) || base.starts_with(dir)
+                }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches(Path::new("/home/u/.config/App/Cache/*"), Path::new("/home/u/.config/App/Cache/data_1")));
+        assert!(!matches(Path::new("/home/u/.config/App/Cache/*"), Path::new("/home/u/.config/App/Cache/a/b")));
+        assert!(matches(Path::new("/home/u/**/*.log"), Path::new("/home/u/a/b/c.log")));
+        assert!(matches(Path::new("/home/u/**/*.log"), Path::new("/home/u/c.log")));
+        assert!(matches(Path::new("/tmp/file-?.tmp"), Path::new("/tmp/file-1.tmp")));
+        assert!(!matches(Path::new("/tmp/file-?.tmp"), Path::new("/tmp/file-10.tmp")));
+        assert!(!matches(Path::new("/home/u/*"), Path::new("/home/u/.hidden")));
+        assert!(matches(Path::new("/home/u/.*"), Path::new("/home/u/.hidden")));
+    }
+
+    #[test]
+    fn test_base_and_depth() {
+        assert_eq!(base_and_depth(Path::new("/home/u/App/*/cache")), (PathBuf::from("/home/u/App"), Some(2)));
+        assert_eq!(base_and_depth(Path::new("/home/u/App/**/*.tmp")), (PathBuf::from("/home/u/App"), None));
+        assert_eq!(base_and_depth(Path::new("/home/u/App/cache")), (PathBuf::from("/home/u/App/cache"), Some(0)));
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let home = Path::new("/home/u");
+        let filter = PathFilter::new(&[], &["node_modules".to_string(), "~/.cache/**/*.bin".to_string()], home);
+        assert!(filter.allows(Path::new("/home/u/code/app/src/main.rs")));
+        assert!(!filter.allows(Path::new("/home/u/code/app/node_modules/left-pad/index.js")));
+        assert!(!filter.enters(Path::new("/home/u/code/app/node_modules")));
+        assert!(!filter.allows(Path::new("/home/u/.cache/x/y.bin")));
+
+        let filter = PathFilter::new(&["~/Videos/**".to_string()], &["~/Videos/raw".to_string()], home);
+        assert!(filter.allows(Path::new("/home/u/Videos/trip.mkv")));
+        assert!(!filter.allows(Path::new("/home/u/Documents/a.pdf")));
+        assert!(!filter.allows(Path::new("/home/u/Videos/raw/a.mov")));
+        assert!(filter.enters(Path::new("/home/u")));
+        assert!(!filter.enters(Path::new("/home/u/Documents")));
+        assert!(PathFilter::new(&[" ".to_string()], &[], home).is_empty());
+
+        // Walks stay on one filesystem unless told otherwise
+        assert!(filter.one_file_system());
+        assert!(!filter.with_one_file_system(false).one_file_system());
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = Path::new("/home/u");
+        assert_eq!(expand_home("~/.cache/x", home), PathBuf::from("/home/u/.cache/x"));
+        assert_eq!(expand_home("/var/tmp/x", home), PathBuf::from("/var/tmp/x"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Scan item ids that survive a rescan. An item's id is derived from what it
+//! is and which file it stands for (canonical path plus inode), so the same
+//! cache folder gets the same id every scan and decisions taken on it, such as
+//! ignoring it or selecting it for cleanup, are remembered in `item_decisions`
+//! and applied to the next results.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use super::{dedup, ScanItem, ScanResults};
+use crate::db::DbAccess;
+
+/// A user's standing decision about a scan item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum ItemDecision {
+    /// Left out of scan results
+    Ignored,
+    /// Selected for cleanup when it shows up again
+    Selected,
+}
+
+impl ItemDecision {
+    fn as_str(self) -> &'static str {
+        match self {
+            ItemDecision::Ignored => "ignored",
+            ItemDecision::Selected => "selected",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ignored" => Some(ItemDecision::Ignored),
+            "selected" => Some(ItemDecision::Selected),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ItemDecisionRecord {
+    pub item_id: String,
+    pub path: String,
+    pub decision: ItemDecision,
+    pub decided_at: i64,
+}
+
+/// Stable id of an item: its type, category and name plus the file it stands
+/// for. Paths are canonicalized where they exist, and the inode is included so
+/// a different file at the same path is a different item.
+pub fn stable_id(item: &ScanItem) -> String {
+    let path = Path::new(&item.path);
+    let canonical = path.canonicalize().map(|p| p.to_string_lossy().to_string()).unwrap_or_else(|_| item.path.clone());
+    let inode = path.symlink_metadata().map(|m| (m.dev(), m.ino())).unwrap_or((0, 0));
+
+    let mut hasher = blake3::Hasher::new();
+    for part in [item.item_type.as_str(), item.category.as_str(), item.name.as_str(), canonical.as_str()] {
+        hasher.update(part.as_bytes());
+        hasher.update(&[0]);
+    }
+    hasher.update(&inode.0.to_le_bytes());
+    hasher.update(&inode.1.to_le_bytes());
+    hasher.finalize().to_hex()[..32].to_string()
+}
+
+/// Replace the ids scanners gave their items, children included, with stable ones
+pub fn assign_ids(items: &mut [ScanItem]) {
+    for item in items {
+        item.id = stable_id(item);
+        if let Some(children) = item.children.as_mut() {
+            assign_ids(children);
+        }
+    }
+}
+
+pub fn load_decisions(conn: &Connection) -> rusqlite::Result<HashMap<String, ItemDecision>> {
+    let mut stmt = conn.prepare("SELECT item_id, decision FROM item_decisions")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    let mut decisions = HashMap::new();
+    for row in rows {
+        let (id, decision) = row?;
+        if let Some(decision) = ItemDecision::parse(&decision) {
+            decisions.insert(id, decision);
+        }
+    }
+    Ok(decisions)
+}
+
+/// Record a decision, or forget the item's decision when `decision` is None
+pub fn save_decision(conn: &Connection, item_id: &str, path: &str, decision: Option<ItemDecision>, now: i64) -> rusqlite::Result<()> {
+    match decision {
+        Some(decision) => conn.execute(
+            "INSERT INTO item_decisions (item_id, path, decision, decided_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(item_id) DO UPDATE SET path = excluded.path, decision = excluded.decision, decided_at = excluded.decided_at",
+            params![item_id, path, decision.as_str(), now],
+        )?,
+        None => conn.execute("DELETE FROM item_decisions WHERE item_id = ?1", [item_id])?,
+    };
+    Ok(())
+}
+
+fn drop_ignored(items: &mut Vec<ScanItem>, decisions: &HashMap<String, ItemDecision>) {
+    items.retain(|item| decisions.get(&item.id) != Some(&ItemDecision::Ignored));
+    for item in items.iter_mut() {
+        if let Some(children) = item.children.as_mut() {
+            drop_ignored(children, decisions);
+        }
+    }
+}
+
+/// Leave out ignored items and list the ones selected last time in `preselected`
+pub fn apply_decisions(results: &mut ScanResults, decisions: &HashMap<String, ItemDecision>) {
+    if decisions.is_empty() {
+        return;
+    }
+    drop_ignored(&mut results.items, decisions);
+    results.shared_items.retain(|s| decisions.get(&s.item_id) != Some(&ItemDecision::Ignored));
+    let mut preselected = Vec::new();
+    let mut collect = vec![&results.items];
+    while let Some(items) = collect.pop() {
+        for item in items {
+            if decisions.get(&item.id) == Some(&ItemDecision::Selected) {
+                preselected.push(item.id.clone());
+            }
+            collect.extend(item.children.as_ref());
+        }
+    }
+    results.preselected = preselected;
+    dedup::recount(results);
+}
+
+/// Apply the stored decisions to fresh scan results
+pub fn apply_stored_decisions(app_handle: &tauri::AppHandle, results: &mut ScanResults) {
+    match app_handle.db(load_decisions) {
+        Ok(decisions) => apply_decisions(results, &decisions),
+        Err(e) => tracing::warn!("Failed to load item decisions: {}", e),
+    }
+}
+
+/// Remember a decision about a scan item for later scans; None forgets it
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn set_item_decision(
+    app_handle: tauri::AppHandle,
+    item_id: String,
+    path: String,
+    decision: Option<ItemDecision>,
+) -> Result<(), String> {
+    app_handle
+        .db(|conn| save_decision(conn, &item_id, &path, decision, chrono::Utc::now().timestamp()))
+        .map_err(|e| format!("Failed to save decision: {}", e))
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_item_decisions(app_handle: tauri::AppHandle) -> Result<Vec<ItemDecisionRecord>, String> {
+    app_handle
+        .db(|conn| {
+            let mut stmt = conn.prepare("SELECT item_id, path, decision, decided_at FROM item_decisions ORDER BY decided_at DESC")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?))
+            })?;
+            let mut records = Vec::new();
+            for row in rows {
+                let (item_id, path, decision, decided_at) = row?;
+                if let Some(decision) = ItemDecision::parse(&decision) {
+                    records.push(ItemDecisionRecord { item_id, path, decision, decided_at });
+                }
+            }
+            Ok(records)
+        })
+        .map_err(|e| format!("Failed to list decisions: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::builtin::new_item;
+    use std::fs;
+
+    fn item(name: &str, path: &Path) -> ScanItem {
+        new_item(name.to_string(), path, 100, "directory", "Cache", 0, "")
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = dir.path().join("pip");
+        fs::create_dir(&cache).unwrap();
+
+        let first = stable_id(&item("pip", &cache));
+        assert_eq!(stable_id(&item("pip", &dir.path().join("./pip"))), first);
+        assert_ne!(stable_id(&item("pip wheels", &cache)), first);
+        assert_eq!(stable_id(&item("pip", &cache)).len(), 32);
+    }
+
+    #[test]
+    fn test_decisions_apply_to_next_scan() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE item_decisions (item_id TEXT PRIMARY KEY, path TEXT NOT NULL, decision TEXT NOT NULL, decided_at INTEGER NOT NULL);",
+        )
+        .unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut items = vec![item("a", &dir.path().join("a")), item("b", &dir.path().join("b")), item("c", &dir.path().join("c"))];
+        assign_ids(&mut items);
+
+        save_decision(&conn, &items[0].id, &items[0].path, Some(ItemDecision::Ignored), 1).unwrap();
+        save_decision(&conn, &items[1].id, &items[1].path, Some(ItemDecision::Ignored), 1).unwrap();
+        save_decision(&conn, &items[1].id, &items[1].path, Some(ItemDecision::Selected), 2).unwrap();
+        save_decision(&conn, &items[2].id, &items[2].path, Some(ItemDecision::Selected), 2).unwrap();
+        save_decision(&conn, &items[2].id, &items[2].path, None, 3).unwrap();
+
+        // A later scan finds the same items under fresh random ids
+        let mut rescanned = vec![item("a", &dir.path().join("a")), item("b", &dir.path().join("b")), item("c", &dir.path().join("c"))];
+        assign_ids(&mut rescanned);
+        let mut results = ScanResults {
+            items: rescanned,
+            total_size: 300,
+            total_items: 3,
+            scan_time_ms: 0,
+            timestamp: 0,
+            failed_categories: Vec::new(),
+            cancelled: false,
+            shared_items: Vec::new(),
+            preselected: Vec::new(),
+            resumed_categories: Vec::new(),
+        };
+        apply_decisions(&mut results, &load_decisions(&conn).unwrap());
+        assert_eq!(results.items.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), vec!["b", "c"]);
+        assert_eq!(results.preselected, vec![items[1].id.clone()]);
+        assert_eq!(results.total_size, 200);
+    }
+}
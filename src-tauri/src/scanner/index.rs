@@ -0,0 +1,284 @@
+//! Persistent file index (path, size, mtime, hash) so repeat scans only
+//! re-examine what changed since the last one.
+//!
+//! Files are keyed by size and mtime: a cached content hash is reused while both
+//! match. Directories store the total size of the files directly inside them
+//! together with the directory's own mtime; while that mtime is unchanged those
+//! files are not stat'ed again. Rewriting a file in place does not touch its
+//! directory's mtime, so every entry is re-verified once it is older than
+//! `REVERIFY_SECS`.
+
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use super::allocation;
+
+/// Entries older than this are examined again even if they look unchanged
+const REVERIFY_SECS: i64 = 7 * 24 * 3600;
+/// Entries not verified for this long are dropped (deleted or no longer scanned paths)
+const PRUNE_SECS: i64 = 30 * 24 * 3600;
+
+/// Index handle shared with the blocking scan threads
+pub type SharedIndex = Arc<Mutex<FileIndex>>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct IndexEntry {
+    size: u64, // file size, or for a directory the sum of the files directly inside it
+    mtime_ns: i64,
+    is_dir: bool,
+    hash: Option<String>,
+    indexed_at: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct FileIndex {
+    entries: HashMap<String, IndexEntry>,
+    dirty: HashSet<String>,
+    now: i64,
+    /// Entries taken from the index during this scan
+    pub reused: usize,
+    /// Entries that had to be examined again
+    pub examined: usize,
+}
+
+pub fn mtime_ns(metadata: &Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+impl FileIndex {
+    pub fn load(conn: &Connection) -> rusqlite::Result<Self> {
+        let mut stmt = conn.prepare("SELECT path, size, mtime_ns, is_dir, hash, indexed_at FROM file_index")?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    IndexEntry {
+                        size: row.get::<_, i64>(1)? as u64,
+                        mtime_ns: row.get(2)?,
+                        is_dir: row.get::<_, i64>(3)? != 0,
+                        hash: row.get(4)?,
+                        indexed_at: row.get(5)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok(Self { entries, now: chrono::Utc::now().timestamp(), ..Self::default() })
+    }
+
+    /// An empty index, for when the stored one can't be loaded
+    pub fn in_memory() -> Self {
+        Self { now: chrono::Utc::now().timestamp(), ..Self::default() }
+    }
+
+    pub fn shared(self) -> SharedIndex {
+        Arc::new(Mutex::new(self))
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn fresh(&self, key: &str, size: Option<u64>, mtime_ns: i64, is_dir: bool) -> Option<&IndexEntry> {
+        self.entries.get(key).filter(|e| {
+            e.is_dir == is_dir
+                && e.mtime_ns == mtime_ns
+                && size.map_or(true, |size| e.size == size)
+                && self.now - e.indexed_at < REVERIFY_SECS
+        })
+    }
+
+    fn update(&mut self, key: String, entry: IndexEntry) {
+        self.dirty.insert(key.clone());
+        self.entries.insert(key, entry);
+    }
+
+    /// Content hash of a file, computed only when its size or mtime changed.
+    /// `kind` names the hash algorithm; a stored hash of another kind is replaced.
+    pub fn file_hash<E>(
+        &mut self,
+        path: &Path,
+        metadata: &Metadata,
+        kind: &str,
+        compute: impl FnOnce() -> Result<String, E>,
+    ) -> Result<String, E> {
+        let key = path.to_string_lossy().to_string();
+        let (size, mtime_ns) = (metadata.len(), mtime_ns(metadata));
+        let cached = self
+            .fresh(&key, Some(size), mtime_ns, false)
+            .and_then(|e| e.hash.as_deref())
+            .and_then(|h| h.strip_prefix(kind))
+            .and_then(|h| h.strip_prefix(':'))
+            .map(str::to_string);
+        if let Some(hash) = cached {
+            self.reused += 1;
+            return Ok(hash);
+        }
+
+        self.examined += 1;
+        let hash = compute()?;
+        let indexed_at = self.now;
+        let stored = format!("{}:{}", kind, hash);
+        self.update(key, IndexEntry { size, mtime_ns, is_dir: false, hash: Some(stored), indexed_at });
+        Ok(hash)
+    }
+
+    /// Bytes on disk of a directory tree. Subdirectories are always listed, but
+    /// the files of a directory whose mtime is unchanged are not stat'ed again.
+    /// Symlinks are not followed; a hard-linked file counts a share per link.
+    pub fn dir_size(&mut self, path: &Path) -> u64 {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return 0;
+        };
+        if !metadata.is_dir() {
+            return if metadata.is_file() { allocation::allocated(&metadata) } else { 0 };
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            return 0;
+        };
+
+        let key = path.to_string_lossy().to_string();
+        let mtime_ns = mtime_ns(&metadata);
+        let cached_files_size = self.fresh(&key, None, mtime_ns, true).map(|e| e.size);
+
+        let mut files_size = 0u64;
+        let mut subdirs_size = 0u64;
+        for entry in entries.filter_map(|e| e.ok()) {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                subdirs_size += self.dir_size(&entry.path());
+            } else if file_type.is_file() && cached_files_size.is_none() {
+                files_size += entry.metadata().map(|m| allocation::shared(&m)).unwrap_or(0);
+            }
+        }
+
+        match cached_files_size {
+            Some(size) => {
+                self.reused += 1;
+                size + subdirs_size
+            }
+            None => {
+                self.examined += 1;
+                let indexed_at = self.now;
+                self.update(key, IndexEntry { size: files_size, mtime_ns, is_dir: true, hash: None, indexed_at });
+                files_size + subdirs_size
+            }
+        }
+    }
+
+    /// Write entries examined during this scan and prune stale ones
+    pub fn save(&mut self, conn: &Connection) -> rusqlite::Result<()> {
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO file_index (path, size, mtime_ns, is_dir, hash, indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for key in &self.dirty {
+                if let Some(e) = self.entries.get(key) {
+                    stmt.execute(params![key, e.size as i64, e.mtime_ns, e.is_dir as i64, e.hash, e.indexed_at])?;
+                }
+            }
+            tx.execute("DELETE FROM file_index WHERE indexed_at < ?1", [self.now - PRUNE_SECS])?;
+        }
+        tx.commit()?;
+        self.dirty.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE file_index (path TEXT PRIMARY KEY, size INTEGER NOT NULL, mtime_ns INTEGER NOT NULL,
+             is_dir INTEGER NOT NULL DEFAULT 0, hash TEXT, indexed_at INTEGER NOT NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_file_hash_reused_until_changed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("a.bin");
+        fs::write(&path, b"hello").unwrap();
+        let conn = index_db();
+
+        let mut index = FileIndex::load(&conn).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let hash = index.file_hash(&path, &metadata, "sampled", || Ok::<_, ()>("h1".to_string())).unwrap();
+        assert_eq!(hash, "h1");
+        index.save(&conn).unwrap();
+
+        // Reloaded from the database: unchanged file is not hashed again
+        let mut index = FileIndex::load(&conn).unwrap();
+        let hash = index.file_hash(&path, &metadata, "sampled", || -> Result<String, ()> { panic!("rehashed") }).unwrap();
+        assert_eq!((hash.as_str(), index.reused, index.examined), ("h1", 1, 0));
+
+        // A different hash kind is computed, not served from the sampled entry
+        let hash = index.file_hash(&path, &metadata, "blake3", || Ok::<_, ()>("b1".to_string())).unwrap();
+        assert_eq!((hash.as_str(), index.examined), ("b1", 1));
+
+        fs::write(&path, b"hello, world").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let hash = index.file_hash(&path, &metadata, "sampled", || Ok::<_, ()>("h2".to_string())).unwrap();
+        assert_eq!((hash.as_str(), index.examined), ("h2", 2));
+    }
+
+    #[test]
+    fn test_dir_size_matches_full_walk() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+        fs::write(dir.path().join("top.txt"), vec![0u8; 100]).unwrap();
+        fs::write(dir.path().join("sub/mid.txt"), vec![0u8; 200]).unwrap();
+        fs::write(dir.path().join("sub/deeper/low.txt"), vec![0u8; 300]).unwrap();
+        let conn = index_db();
+
+        let full_walk = crate::trash::get_dir_size(dir.path());
+
+        let mut index = FileIndex::load(&conn).unwrap();
+        assert_eq!(index.dir_size(dir.path()), full_walk);
+        assert_eq!(index.examined, 3);
+        index.save(&conn).unwrap();
+
+        let mut index = FileIndex::load(&conn).unwrap();
+        assert_eq!(index.entry_count(), 3);
+        assert_eq!(index.dir_size(dir.path()), full_walk);
+        assert_eq!((index.reused, index.examined), (3, 0));
+
+        // A new file changes the directory's mtime, so only that directory is re-read
+        fs::write(dir.path().join("sub/new.txt"), vec![0u8; 50]).unwrap();
+        assert_eq!(index.dir_size(dir.path()), crate::trash::get_dir_size(dir.path()));
+        assert_eq!(index.examined, 1);
+    }
+
+    #[test]
+    fn test_stale_entries_are_reverified_and_pruned() {
+        let conn = index_db();
+        let old = chrono::Utc::now().timestamp() - PRUNE_SECS - 1;
+        conn.execute(
+            "INSERT INTO file_index (path, size, mtime_ns, is_dir, hash, indexed_at) VALUES ('/gone', 1, 1, 0, 'h', ?1)",
+            [old],
+        )
+        .unwrap();
+
+        let mut index = FileIndex::load(&conn).unwrap();
+        assert!(index.fresh("/gone", Some(1), 1, false).is_none());
+        index.save(&conn).unwrap();
+        assert_eq!(FileIndex::load(&conn).unwrap().entry_count(), 0);
+    }
+}
@@ -0,0 +1,156 @@
+//! Junk files: empty files, and artifacts other systems and editors leave
+//! behind — `.DS_Store` and AppleDouble `._` files from macOS, `Thumbs.db`
+//! and `desktop.ini` from Windows, and swap or backup files an editor didn't
+//! get to remove. Hidden directories and source repositories are left alone,
+//! since apps and projects use empty files there as markers.
+
+use std::path::Path;
+use std::time::Duration;
+use walkdir::WalkDir;
+
+use super::allocation;
+use super::builtin::new_item;
+use super::{is_cancelled, CancelFlag, ScanItem};
+
+/// Left behind by file managers and other operating systems
+const ARTIFACT_NAMES: &[&str] = &[".DS_Store", "Thumbs.db", "ehthumbs.db", "desktop.ini", "Desktop.ini"];
+
+/// Empty on purpose
+const MEANINGFUL_EMPTY: &[&str] = &[".gitkeep", ".keep", ".nomedia", "__init__.py", "py.typed", ".hushlogin"];
+
+/// An editor may still have a swap file open; older ones are leftovers
+const SWAP_MIN_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Junk {
+    Empty,
+    Artifact,
+    EditorLeftover,
+}
+
+impl Junk {
+    fn description(self) -> &'static str {
+        match self {
+            Junk::Empty => "Empty file",
+            Junk::Artifact => "File manager artifact from another system",
+            Junk::EditorLeftover => "Editor swap or backup file left behind",
+        }
+    }
+}
+
+/// vim swap files (`.name.swp`, `.swo`, ...), emacs autosaves (`#name#`) and backups (`name~`)
+fn is_editor_leftover(name: &str) -> bool {
+    let vim_swap = name.starts_with('.')
+        && name.rsplit_once('.').is_some_and(|(_, ext)| ext.len() == 3 && ext.starts_with("sw") && ext != "swf");
+    let emacs_autosave = name.len() > 2 && name.starts_with('#') && name.ends_with('#');
+    vim_swap || emacs_autosave || (name.len() > 1 && name.ends_with('~'))
+}
+
+fn classify(name: &str, len: u64) -> Option<Junk> {
+    if ARTIFACT_NAMES.contains(&name) || (name.starts_with("._") && name.len() > 2) {
+        Some(Junk::Artifact)
+    } else if is_editor_leftover(name) {
+        Some(Junk::EditorLeftover)
+    } else if len == 0 && !MEANINGFUL_EMPTY.contains(&name) {
+        Some(Junk::Empty)
+    } else {
+        None
+    }
+}
+
+/// Directories whose files aren't ours to judge
+fn skipped_dir(dir: &Path, root: &Path) -> bool {
+    let hidden = dir.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+    dir != root && (hidden || dir.join(".git").exists())
+}
+
+pub(crate) fn scan_junk_files(home: &Path, cancel: &CancelFlag, one_file_system: bool) -> Vec<ScanItem> {
+    let mut items = Vec::new();
+
+    let walker = WalkDir::new(home)
+        .follow_links(false)
+        .same_file_system(one_file_system)
+        .into_iter()
+        .filter_entry(|e| !e.file_type().is_dir() || !skipped_dir(e.path(), home))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+
+    for entry in walker {
+        if is_cancelled(cancel) {
+            break;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy();
+        let Some(junk) = classify(&name, metadata.len()) else {
+            continue;
+        };
+        if junk == Junk::EditorLeftover {
+            let age = metadata.modified().ok().and_then(|m| m.elapsed().ok());
+            if age.map_or(true, |age| age < SWAP_MIN_AGE) {
+                continue;
+            }
+        }
+        let mut item = new_item(
+            name.to_string(),
+            entry.path(),
+            allocation::allocated(&metadata),
+            "file",
+            "junk_file",
+            if junk == Junk::EditorLeftover { 1 } else { 0 },
+            junk.description(),
+        );
+        item.apparent_size = allocation::apparent_if_different(&metadata);
+        items.push(item);
+    }
+
+    tracing::info!("Found {} junk files", items.len());
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testfs::{cancel_flag, SimFs};
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(".DS_Store", 6148), Some(Junk::Artifact));
+        assert_eq!(classify("._photo.jpg", 4096), Some(Junk::Artifact));
+        assert_eq!(classify(".notes.txt.swp", 12288), Some(Junk::EditorLeftover));
+        assert_eq!(classify(".notes.txt.swo", 12288), Some(Junk::EditorLeftover));
+        assert_eq!(classify("#draft.org#", 10), Some(Junk::EditorLeftover));
+        assert_eq!(classify("report.tex~", 10), Some(Junk::EditorLeftover));
+        assert_eq!(classify("empty.txt", 0), Some(Junk::Empty));
+        assert_eq!(classify("__init__.py", 0), None);
+        assert_eq!(classify(".movie.swf", 100), None);
+        assert_eq!(classify("notes.txt", 10), None);
+        assert_eq!(classify("~", 10), None);
+    }
+
+    #[test]
+    fn test_junk_files_on_simulated_tree() {
+        let fs = SimFs::new();
+        fs.content("Pictures/.DS_Store", b"\0\0\0\x01Bud1")
+            .content("Pictures/Thumbs.db", b"thumbs")
+            .content("Pictures/photo.jpg", b"jpeg")
+            .content("Documents/empty.txt", b"")
+            .content("Documents/.old.txt.swp", b"swap")
+            .aged("Documents/.old.txt.swp", 30)
+            .content("Documents/.open.txt.swp", b"swap")
+            .content("Documents/pkg/__init__.py", b"")
+            .content("code/app/.git/HEAD", b"ref: refs/heads/main\n")
+            .content("code/app/empty.log", b"")
+            .content(".config/app/initialized", b"")
+            .content(".DS_Store", b"x");
+
+        let items = scan_junk_files(fs.root(), &cancel_flag(false), true);
+        assert_eq!(
+            fs.relative(&items),
+            [".DS_Store", "Documents/.old.txt.swp", "Documents/empty.txt", "Pictures/.DS_Store", "Pictures/Thumbs.db"]
+        );
+        assert!(items.iter().all(|i| i.category == "junk_file" && i.risk_level <= 1));
+        assert!(scan_junk_files(fs.root(), &cancel_flag(true), true).is_empty());
+    }
+}
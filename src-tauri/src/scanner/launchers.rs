@@ -0,0 +1,213 @@
+//! Desktop launchers whose program is gone: `.desktop` files in the user's
+//! applications and autostart folders whose `TryExec`, or failing that the
+//! first word of `Exec`, no longer resolves to a file. Uninstallers routinely
+//! leave these behind in the home directory, where menus and login then try
+//! to start a missing program. System-wide entries aren't the user's to remove.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use super::allocation;
+use super::builtin::new_item;
+use super::{is_cancelled, CancelFlag, ScanItem};
+
+const LAUNCHER_DIRS: &[&str] = &[".local/share/applications", ".config/autostart"];
+
+/// What the `[Desktop Entry]` group of a launcher says about its program
+#[derive(Debug, Default, PartialEq)]
+struct Entry {
+    name: Option<String>,
+    exec: Option<String>,
+    try_exec: Option<String>,
+    hidden: bool,
+}
+
+fn parse_entry(content: &str) -> Entry {
+    let mut entry = Entry::default();
+    let mut in_main_group = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_group {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Name" => entry.name = Some(value),
+            "Exec" => entry.exec = Some(value),
+            "TryExec" => entry.try_exec = Some(value),
+            "Hidden" => entry.hidden = value == "true",
+            _ => {}
+        }
+    }
+    entry
+}
+
+/// Words of an `Exec` value, with the spec's double quoting and backslash escapes undone
+fn exec_words(exec: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let (mut quoted, mut in_word) = (false, false);
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            '\\' if quoted => word.extend(chars.next()),
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// The program an `Exec` line starts, past `env` and its variable assignments
+fn exec_program(exec: &str) -> Option<String> {
+    let words = exec_words(exec);
+    let mut words = words.into_iter().peekable();
+    if words.peek().map(String::as_str) == Some("env") {
+        words.next();
+        while words.peek().is_some_and(|w| w.contains('=') || w.starts_with('-')) {
+            words.next();
+        }
+    }
+    words.next().filter(|w| !w.is_empty() && !w.starts_with('%'))
+}
+
+/// Whether `program` names a file: a path as is, a bare name on `search_path`
+fn resolves(program: &str, search_path: &[PathBuf]) -> bool {
+    if program.contains('/') {
+        return Path::new(program).exists();
+    }
+    search_path.iter().any(|dir| {
+        dir.join(program)
+            .metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    })
+}
+
+/// The program a launcher needs and can't find. Entries that are hidden (how a
+/// user switches off a system launcher) or start nothing are never broken.
+fn missing_program(entry: &Entry, search_path: &[PathBuf]) -> Option<String> {
+    if entry.hidden {
+        return None;
+    }
+    let program = match &entry.try_exec {
+        Some(try_exec) if !try_exec.is_empty() => try_exec.clone(),
+        _ => exec_program(entry.exec.as_deref()?)?,
+    };
+    (!resolves(&program, search_path)).then_some(program)
+}
+
+/// Launchers below `home` whose program isn't there; bare program names are
+/// looked up on `search_path`, as `PATH` would be
+pub(crate) fn scan_broken_launchers(home: &Path, search_path: &[PathBuf], cancel: &CancelFlag) -> Vec<ScanItem> {
+    let mut items = Vec::new();
+
+    for dir in LAUNCHER_DIRS.iter().map(|d| home.join(d)) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            if is_cancelled(cancel) {
+                return items;
+            }
+            let path = entry.path();
+            if path.extension().map_or(true, |e| e != "desktop") || !path.is_file() {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let launcher = parse_entry(&content);
+            let Some(program) = missing_program(&launcher, search_path) else {
+                continue;
+            };
+            let size = path.metadata().map(|m| allocation::allocated(&m)).unwrap_or(0);
+            let name = launcher.name.unwrap_or_else(|| entry.file_name().to_string_lossy().to_string());
+            let description = format!("Launcher for a program that is no longer installed: {}", program);
+            items.push(new_item(name, &path, size, "file", "broken_launcher", 0, &description));
+        }
+    }
+
+    tracing::info!("Found {} broken launchers", items.len());
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testfs::{cancel_flag, SimFs};
+    use super::*;
+
+    #[test]
+    fn test_exec_program() {
+        assert_eq!(exec_program("firefox %u").as_deref(), Some("firefox"));
+        assert_eq!(exec_program(r#""/opt/My App/run" --flag"#).as_deref(), Some("/opt/My App/run"));
+        assert_eq!(exec_program(r#""/opt/say \"hi\"/x""#).as_deref(), Some("/opt/say \"hi\"/x"));
+        assert_eq!(exec_program("env -i GDK_BACKEND=x11 slack %U").as_deref(), Some("slack"));
+        assert_eq!(exec_program("   ").as_deref(), None);
+        assert_eq!(exec_program("%f").as_deref(), None);
+    }
+
+    #[test]
+    fn test_parse_entry_reads_main_group_only() {
+        let entry = parse_entry(
+            "[Desktop Entry]\nName=Editor\nExec=editor %F\nHidden=false\n\n[Desktop Action new]\nExec=other --new\nTryExec=x\n",
+        );
+        assert_eq!(entry, Entry { name: Some("Editor".into()), exec: Some("editor %F".into()), try_exec: None, hidden: false });
+    }
+
+    #[test]
+    fn test_broken_launchers_on_simulated_tree() {
+        let fs = SimFs::new();
+        let bin = fs.path("bin");
+        fs.content("bin/tool", b"#!/bin/sh\n")
+            .content("bin/notes.txt", b"not a program")
+            .content(".local/share/applications/tool.desktop", b"[Desktop Entry]\nName=Tool\nExec=tool %f\n")
+            .content(".local/share/applications/gone.desktop", b"[Desktop Entry]\nName=Gone\nExec=gone-app\n")
+            .content(".local/share/applications/text.desktop", b"[Desktop Entry]\nExec=notes.txt\n")
+            .content(".local/share/applications/masked.desktop", b"[Desktop Entry]\nExec=gone-app\nHidden=true\n")
+            .content(".local/share/applications/link.desktop", b"[Desktop Entry]\nType=Link\nURL=https://example.com\n")
+            .content(".local/share/applications/readme.txt", b"Exec=gone-app\n");
+        let absolute = format!("[Desktop Entry]\nName=Sync\nExec=\"{}\" --start\n", bin.join("sync daemon").display());
+        fs.content(".config/autostart/sync.desktop", absolute.as_bytes());
+        let try_exec = format!("[Desktop Entry]\nTryExec={}\nExec=tool\n", bin.join("missing").display());
+        fs.content(".config/autostart/try.desktop", try_exec.as_bytes());
+        std::fs::set_permissions(bin.join("tool"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let items = scan_broken_launchers(fs.root(), &[bin], &cancel_flag(false));
+        assert_eq!(
+            fs.relative(&items),
+            [
+                ".config/autostart/sync.desktop",
+                ".config/autostart/try.desktop",
+                ".local/share/applications/gone.desktop",
+                ".local/share/applications/text.desktop",
+            ]
+        );
+        assert!(items.iter().all(|i| i.category == "broken_launcher"));
+        let gone = items.iter().find(|i| i.name == "Gone").unwrap();
+        assert!(gone.description.ends_with(": gone-app"));
+
+        assert!(scan_broken_launchers(fs.root(), &[], &cancel_flag(true)).is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Application logs in the home directory: live `.log` files, their rotated
+//! (`.1`, `.old`, `-20240301`) and compressed (`.gz`, `.xz`, ...) copies,
+//! anything inside an app's `log`/`logs` folder and the X session error log.
+//! Files are grouped per app; a group lists the files themselves, so cleaning
+//! it never touches the app's folder.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use super::allocation;
+use super::builtin::{file_name_or_path, home_dir, new_item};
+use super::registry::{ScanContext, Scanner, ScannerOptionSchema};
+use super::{ScanItem, ScanOptions, ScannerError};
+
+const CATEGORY: &str = "Logs";
+
+/// Files listed per app, largest first
+const MAX_FILES_PER_APP: usize = 200;
+
+const COMPRESSED_SUFFIXES: &[&str] = &[".gz", ".xz", ".bz2", ".zst", ".lz4", ".z"];
+
+/// Session logs directly in the home directory, grouped under this name
+const SESSION_GROUP: &str = "X session";
+const SESSION_LOGS: &[&str] = &[".xsession-errors", ".xsession-errors.old"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogKind {
+    Active,     // may still be written to
+    Rotated,    // an older copy the app moved aside
+    Compressed, // a rotated copy that was compressed
+}
+
+/// A numeric rotation suffix (`1`, `12`) or a date one (`20240301`, `2024-03-01`)
+fn is_rotation_suffix(suffix: &str) -> bool {
+    !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '_')
+        && suffix.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Whether `name` is a log file, and which kind. Inside a log folder every
+/// file counts; elsewhere only names that say they are logs.
+pub fn classify(name: &str, in_log_dir: bool) -> Option<LogKind> {
+    let lower = name.to_lowercase();
+    let (base, compressed) = match COMPRESSED_SUFFIXES.iter().find(|s| lower.ends_with(*s)) {
+        Some(suffix) => (&lower[..lower.len() - suffix.len()], true),
+        None => (lower.as_str(), false),
+    };
+
+    let rotated = if SESSION_LOGS.contains(&base) || base.ends_with(".log") {
+        base.ends_with(".old")
+    } else if let Some((stem, suffix)) = base.rsplit_once(['.', '-']) {
+        let log_stem = stem.ends_with(".log") || stem.contains(".log.") || in_log_dir;
+        if log_stem && (is_rotation_suffix(suffix) || suffix == "old") {
+            true
+        } else if in_log_dir {
+            false
+        } else {
+            return None;
+        }
+    } else if in_log_dir {
+        false
+    } else {
+        return None;
+    };
+
+    Some(match (compressed, rotated) {
+        (true, _) => LogKind::Compressed,
+        (false, true) => LogKind::Rotated,
+        (false, false) => LogKind::Active,
+    })
+}
+
+fn is_log_dir(name: &str) -> bool {
+    matches!(name.to_lowercase().as_str(), "log" | "logs")
+}
+
+/// Whether a folder between `root` and `path` is a log folder
+fn in_log_dir(root: &Path, path: &Path) -> bool {
+    path.parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+        .is_some_and(|relative| relative.components().any(|c| is_log_dir(&c.as_os_str().to_string_lossy())))
+}
+
+/// The app a log below `root` belongs to: the first folder under it
+fn app_of(root: &Path, path: &Path) -> Option<(String, PathBuf)> {
+    let first = path.strip_prefix(root).ok()?.components().next()?;
+    let dir = root.join(first.as_os_str());
+    (dir != path).then(|| (first.as_os_str().to_string_lossy().to_string(), dir))
+}
+
+fn age_days(modified: Option<SystemTime>, now: SystemTime) -> Option<u64> {
+    modified.and_then(|m| now.duration_since(m).ok()).map(|age| age.as_secs() / (24 * 3600))
+}
+
+fn describe(kind: LogKind, age: Option<u64>) -> String {
+    let what = match kind {
+        LogKind::Active => "Log file that may still be in use",
+        LogKind::Rotated => "Rotated log, no longer written to",
+        LogKind::Compressed => "Compressed old log",
+    };
+    match age {
+        Some(days) => format!("{}, last written {} days ago", what, days),
+        None => what.to_string(),
+    }
+}
+
+/// Log files found for one app
+#[derive(Default)]
+struct AppLogs {
+    dir: PathBuf,
+    files: Vec<(ScanItem, Option<u64>)>, // with their age in days
+}
+
+/// One item per app whose logs take at least `min_size` bytes
+fn group(apps: BTreeMap<String, AppLogs>, min_size: u64) -> Vec<ScanItem> {
+    let mut groups = Vec::new();
+    for (app, mut logs) in apps {
+        logs.files.sort_by(|a, b| b.0.size.cmp(&a.0.size));
+        logs.files.truncate(MAX_FILES_PER_APP);
+        let size: u64 = logs.files.iter().map(|(f, _)| f.size).sum();
+        if size < min_size || logs.files.is_empty() {
+            continue;
+        }
+        let old = logs.files.iter().filter(|(f, _)| f.risk_level == 0).count();
+        let oldest = logs.files.iter().filter_map(|(_, age)| *age).max();
+        let count = logs.files.len();
+        let mut description = format!("{} log file{}", count, if count == 1 { "" } else { "s" });
+        if old > 0 {
+            description.push_str(&format!(", {} rotated or compressed", old));
+        }
+        if let Some(days) = oldest {
+            description.push_str(&format!(", the oldest written {} days ago", days));
+        }
+        let children: Vec<ScanItem> = logs.files.into_iter().map(|(f, _)| f).collect();
+        let mut item = new_item(format!("{} logs", app), &logs.dir, size, "log_group", CATEGORY, 1, &description);
+        item.risk_level = children.iter().map(|c| c.risk_level).max().unwrap_or(1);
+        item.children = Some(children);
+        groups.push(item);
+    }
+    groups.sort_by(|a, b| b.size.cmp(&a.size));
+    groups
+}
+
+pub struct LogsScanner;
+
+impl LogsScanner {
+    fn add(apps: &mut BTreeMap<String, AppLogs>, app: String, dir: PathBuf, path: &Path, kind: LogKind, min_age: u64, now: SystemTime) {
+        let Ok(metadata) = path.metadata() else { return };
+        let age = age_days(metadata.modified().ok(), now);
+        if age.unwrap_or(0) < min_age {
+            return;
+        }
+        let risk_level = if kind == LogKind::Active { 1 } else { 0 };
+        let mut item = new_item(
+            file_name_or_path(path),
+            path,
+            allocation::allocated(&metadata),
+            "file",
+            CATEGORY,
+            risk_level,
+            &describe(kind, age),
+        );
+        item.apparent_size = allocation::apparent_if_different(&metadata);
+        let logs = apps.entry(app).or_default();
+        // An app with logs under several roots is shown at the first
+        if logs.dir.as_os_str().is_empty() {
+            logs.dir = dir;
+        }
+        logs.files.push((item, age));
+    }
+}
+
+impl Scanner for LogsScanner {
+    fn id(&self) -> &'static str {
+        "logs"
+    }
+
+    fn name(&self) -> &'static str {
+        "Logs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Application logs and their rotated or compressed copies under ~/.local and ~/.config, grouped per app"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        vec![
+            ScannerOptionSchema {
+                key: "min_size_mb".to_string(),
+                label: "Minimum log size per app (MB)".to_string(),
+                value_type: "number".to_string(),
+                default_value: "10".to_string(),
+            },
+            ScannerOptionSchema {
+                key: "min_age_days".to_string(),
+                label: "Only logs last written at least this many days ago".to_string(),
+                value_type: "number".to_string(),
+                default_value: "0".to_string(),
+            },
+        ]
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_logs
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = home_dir()?;
+        let min_size = ctx.option("min_size_mb", 10u64) * 1024 * 1024;
+        let min_age = ctx.option("min_age_days", 0u64);
+        let now = SystemTime::now();
+        let mut apps: BTreeMap<String, AppLogs> = BTreeMap::new();
+
+        for name in SESSION_LOGS {
+            let path = home.join(name);
+            if path.is_file() {
+                let kind = classify(name, false).unwrap_or(LogKind::Active);
+                Self::add(&mut apps, SESSION_GROUP.to_string(), home.clone(), &path, kind, min_age, now);
+            }
+        }
+
+        let roots = [home.join(".local/share"), home.join(".local/state"), home.join(".config")];
+        // The desktop trash and Pulito's own are covered by the trash scanners
+        let skipped = [home.join(".local/share/Trash"), crate::trash::get_trash_dir()];
+        for (index, root) in roots.iter().enumerate() {
+            ctx.progress((index * 100 / roots.len()) as u8, &format!("Scanning {}...", root.display()));
+            for entry in WalkDir::new(root)
+                .max_depth(ctx.limits.max_depth)
+                .same_file_system(ctx.one_file_system())
+                .into_iter()
+                .filter_entry(|e| !e.file_type().is_dir() || (ctx.enters(e.path()) && !skipped.iter().any(|s| s == e.path())))
+                .filter_map(|e| e.ok())
+                .take(ctx.limits.max_files)
+            {
+                ctx.check_cancelled()?;
+                ctx.pace();
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let path = entry.path();
+                let Some(kind) = classify(&entry.file_name().to_string_lossy(), in_log_dir(root, path)) else {
+                    continue;
+                };
+                let (app, dir) = app_of(root, path).unwrap_or_else(|| (file_name_or_path(root), root.clone()));
+                Self::add(&mut apps, app, dir, path, kind, min_age, now);
+            }
+        }
+
+        Ok(group(apps, min_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("app.log", false), Some(LogKind::Active));
+        assert_eq!(classify("app.log.1", false), Some(LogKind::Rotated));
+        assert_eq!(classify("app.log.old", false), Some(LogKind::Rotated));
+        assert_eq!(classify("app.log-20240301", false), Some(LogKind::Rotated));
+        assert_eq!(classify("app.log.2.gz", false), Some(LogKind::Compressed));
+        assert_eq!(classify("app.log.xz", false), Some(LogKind::Compressed));
+        assert_eq!(classify(".xsession-errors", false), Some(LogKind::Active));
+        assert_eq!(classify(".xsession-errors.old", false), Some(LogKind::Rotated));
+        // Inside a log folder any file is a log, numbered ones rotated
+        assert_eq!(classify("main.txt", true), Some(LogKind::Active));
+        assert_eq!(classify("server.3", true), Some(LogKind::Rotated));
+        assert_eq!(classify("trace", true), Some(LogKind::Active));
+        // Elsewhere, names have to say so
+        assert_eq!(classify("backup.1", false), None);
+        assert_eq!(classify("photo.tar.gz", false), None);
+        assert_eq!(classify("catalog", false), None);
+    }
+
+    #[test]
+    fn test_log_dir_and_app() {
+        let root = Path::new("/home/u/.config");
+        let path = Path::new("/home/u/.config/Code/logs/20240301T101010/main.txt");
+        assert!(in_log_dir(root, path));
+        assert!(!in_log_dir(root, Path::new("/home/u/.config/Code/settings.json")));
+        // "logs" above the root doesn't count
+        assert!(!in_log_dir(Path::new("/srv/logs/home"), Path::new("/srv/logs/home/app/data.db")));
+        assert_eq!(app_of(root, path), Some(("Code".to_string(), PathBuf::from("/home/u/.config/Code"))));
+        assert_eq!(app_of(root, Path::new("/home/u/.config/loose.log")), None);
+    }
+
+    #[test]
+    fn test_group_per_app() {
+        let file = |name: &str, size: u64, risk_level: u8| {
+            new_item(name.to_string(), Path::new(name), size, "file", CATEGORY, risk_level, "")
+        };
+        let mut apps = BTreeMap::new();
+        apps.insert(
+            "Steam".to_string(),
+            AppLogs {
+                dir: PathBuf::from("/s"),
+                files: vec![(file("/s/a.log.1.gz", 6, 0), Some(40)), (file("/s/b.log", 8, 1), Some(0))],
+            },
+        );
+        apps.insert("tiny".to_string(), AppLogs { dir: PathBuf::from("/t"), files: vec![(file("/t/x.log", 2, 1), None)] });
+
+        let groups = group(apps, 5);
+        assert_eq!(groups.len(), 1);
+        let steam = &groups[0];
+        assert_eq!((steam.item_type.as_str(), steam.size, steam.risk_level), ("log_group", 14, 1));
+        assert_eq!(steam.description, "2 log files, 1 rotated or compressed, the oldest written 40 days ago");
+        let children: Vec<&str> = steam.children.as_ref().unwrap().iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(children, vec!["/s/b.log", "/s/a.log.1.gz"]);
+    }
+}
@@ -5,6 +5,9 @@ use std::time::{Duration, Instant};
 use std::io::{Read, Seek, SeekFrom};
 use std::fs::File;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::hash::{Hash, Hasher};
 use walkdir::WalkDir;
 use anyhow::{Context, Result as AnyhowResult};
@@ -12,7 +15,31 @@ use tokio::time::timeout;
 use thiserror::Error;
 use tauri::Emitter;
 
-use crate::trash;
+pub mod allocation;
+pub(crate) mod builtin;
+pub mod checkpoint;
+pub mod custom;
+pub mod dedup;
+pub(crate) mod glob;
+pub mod identity;
+pub mod index;
+mod junk;
+mod launchers;
+pub mod logs;
+pub mod plugins;
+pub mod reclaimable;
+pub mod registry;
+pub mod similar;
+pub mod sizing;
+#[cfg(test)]
+pub(crate) mod testfs;
+pub mod throttle;
+pub mod treemap;
+
+pub use glob::PathFilter;
+pub use index::{FileIndex, SharedIndex};
+pub use registry::{CancelFlag, ScanContext, Scanner};
+pub use throttle::{IoPriority, ReadBudget, ThroughputProfile};
 
 /// Scanner-specific error types
 #[derive(Debug, Error)]
@@ -26,7 +53,6 @@ pub enum ScannerError {
     #[error("Timeout exceeded")]
     Timeout,
     #[error("Operation cancelled")]
-    #[allow(dead_code)] // Reserved for future cancellation support
     Cancelled,
 }
 
@@ -38,6 +64,11 @@ pub struct ScanLimits {
     pub max_memory_mb: usize,
     #[allow(dead_code)] // Reserved for future timeout configuration
     pub timeout_seconds: u64,
+    pub threads: usize, // scanners run at once
+    pub pause_every: u64, // see `ScanContext::pace`
+    pub pause: Duration,
+    pub io_priority: IoPriority, // applied to each scanner's thread
+    pub read_budget: Arc<ReadBudget>, // shared by the scan's scanners for file content reads
 }
 
 /// Check if current memory usage is within limits
@@ -86,7 +117,10 @@ pub struct ScanItem {
     pub id: String,
     pub name: String,
     pub path: String,
-    pub size: u64,
+    pub size: u64, // bytes allocated on disk, hard links counted once
+    /// Apparent size where it differs from `size`, as for sparse files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apparent_size: Option<u64>,
     #[serde(rename = "type")]
     pub item_type: String,
     pub category: String,
@@ -114,9 +148,23 @@ pub struct ScanResults {
     pub total_size: u64,
     pub total_items: usize,
     pub scan_time_ms: u64,
-    pub timestamp: String,
+    /// UTC unix seconds; results kept by older releases have RFC 3339 strings here
+    #[serde(deserialize_with = "crate::timestamps::deserialize")]
+    pub timestamp: i64,
     #[serde(default)]
     pub failed_categories: Vec<FailedCategory>,
+    /// Stopped by cancel_scan; the results cover only what was scanned so far
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Items more than one category found; each is listed once, under its primary category
+    #[serde(default)]
+    pub shared_items: Vec<dedup::SharedItem>,
+    /// Ids of items the user selected last time they were listed
+    #[serde(default)]
+    pub preselected: Vec<String>,
+    /// Scanners whose results were reused from an interrupted attempt, see `checkpoint`
+    #[serde(default)]
+    pub resumed_categories: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -132,6 +180,47 @@ pub struct ScanOptions {
     pub max_depth: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_memory_mb: Option<usize>,
+    /// Scanner ids to run; overrides the include_* flags when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+    /// Per-scanner options keyed by scanner id, see `list_scanners` for each schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanner_options: Option<HashMap<String, HashMap<String, String>>>,
+    /// Only report items on this mounted volume, see `SystemStats::mounts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount_point: Option<String>,
+    /// Only report paths under one of these globs, e.g. `~/Videos/**`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_globs: Option<Vec<String>>,
+    /// Skip paths under any of these globs, e.g. `**/node_modules`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_globs: Option<Vec<String>>,
+    /// Overrides the profile picked from the power source, see `throttle::ThrottleSettings`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throughput_profile: Option<ThroughputProfile>,
+    /// Also group similar photos in storage recovery; decodes every image, so off unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similar_images: Option<bool>,
+    /// Stay on the filesystem each walk starts on, skipping network shares,
+    /// external drives and bind mounts below it; on unless set to false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one_file_system: Option<bool>,
+    /// Reuse what an interrupted scan with the same options finished, see
+    /// `checkpoint`; on unless set to false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume: Option<bool>,
+}
+
+impl ScanOptions {
+    pub fn path_filter(&self) -> PathFilter {
+        let home = dirs::home_dir().unwrap_or_default();
+        PathFilter::new(
+            self.include_globs.as_deref().unwrap_or_default(),
+            self.exclude_globs.as_deref().unwrap_or_default(),
+            &home,
+        )
+        .with_one_file_system(self.one_file_system.unwrap_or(true))
+    }
 }
 
 impl Default for ScanOptions {
@@ -144,10 +233,105 @@ impl Default for ScanOptions {
             max_files: None,
             max_depth: None,
             max_memory_mb: None,
+            categories: None,
+            scanner_options: None,
+            mount_point: None,
+            include_globs: None,
+            exclude_globs: None,
+            throughput_profile: None,
+            similar_images: None,
+            one_file_system: None,
+            resume: None,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Running scans by kind ("system", "filesystem_health", "storage_recovery")
+    static ref ACTIVE_SCANS: Mutex<HashMap<&'static str, RunningScan>> = Mutex::new(HashMap::new());
+}
+
+struct RunningScan {
+    flag: CancelFlag,
+    started: Instant,
+}
+
+pub(crate) fn is_cancelled(cancel: &CancelFlag) -> bool {
+    cancel.load(Ordering::Relaxed)
+}
+
+/// Registration of a running scan; unregisters itself when dropped
+pub struct ActiveScan {
+    kind: &'static str,
+    flag: CancelFlag,
+}
+
+impl ActiveScan {
+    pub fn flag(&self) -> &CancelFlag {
+        &self.flag
+    }
+}
+
+impl Drop for ActiveScan {
+    fn drop(&mut self) {
+        let mut active = ACTIVE_SCANS.lock().unwrap_or_else(|e| e.into_inner());
+        // A newer scan of the same kind may have replaced this one
+        if active.get(self.kind).is_some_and(|scan| Arc::ptr_eq(&scan.flag, &self.flag)) {
+            active.remove(self.kind);
         }
     }
 }
 
+/// Register a scan so `cancel_scans` can reach it
+pub fn begin_scan(kind: &'static str) -> ActiveScan {
+    let flag: CancelFlag = Arc::new(AtomicBool::new(false));
+    let scan = RunningScan { flag: Arc::clone(&flag), started: Instant::now() };
+    ACTIVE_SCANS.lock().unwrap_or_else(|e| e.into_inner()).insert(kind, scan);
+    ActiveScan { kind, flag }
+}
+
+/// Signal running scans of `kind` (all kinds when `None`); returns how many were signalled
+pub fn cancel_scans(kind: Option<&str>) -> usize {
+    let active = ACTIVE_SCANS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut signalled = 0;
+    for (_, scan) in active.iter().filter(|(k, _)| kind.map_or(true, |wanted| **k == wanted)) {
+        scan.flag.store(true, Ordering::Relaxed);
+        signalled += 1;
+    }
+    signalled
+}
+
+/// Scans registered and not yet finished
+pub fn active_scans() -> usize {
+    ACTIVE_SCANS.lock().unwrap_or_else(|e| e.into_inner()).len()
+}
+
+/// A scan registered for longer than a watchdog threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct LongRunningScan {
+    pub kind: &'static str,
+    pub running_for: Duration,
+    pub cancelled: bool,
+}
+
+/// Scans registered for at least `threshold`
+pub fn scans_running_longer_than(threshold: Duration) -> Vec<LongRunningScan> {
+    let active = ACTIVE_SCANS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut scans: Vec<LongRunningScan> = active
+        .iter()
+        .map(|(kind, scan)| LongRunningScan { kind: *kind, running_for: scan.started.elapsed(), cancelled: is_cancelled(&scan.flag) })
+        .filter(|scan| scan.running_for >= threshold)
+        .collect();
+    scans.sort_by_key(|scan| scan.kind);
+    scans
+}
+
+/// Unregister a scan that ignores its cancel flag, so shutdown and new scans
+/// stop waiting on it. Its thread runs on until it gets unstuck, if ever.
+pub fn abandon_scan(kind: &str) -> bool {
+    ACTIVE_SCANS.lock().unwrap_or_else(|e| e.into_inner()).remove(kind).is_some()
+}
+
 /// Progress event structure for real-time scan updates
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanProgress {
@@ -158,212 +342,183 @@ pub struct ScanProgress {
     pub current_size: u64,
 }
 
-/// Async version of main scan function with proper error handling and memory bounds
-/// Emits progress events via app_handle if provided
+/// Main scan entry point: runs every selected scanner from the registry with memory
+/// bounds, emitting progress events via app_handle if provided. Setting `cancel`
+/// stops the scan at the next check and returns what was found so far, marked `cancelled`.
+/// With an `index`, directory sizes are only recomputed where something changed.
+/// With a `checkpoint`, scanners it holds results for aren't run again, and every
+/// scanner that finishes is saved to it.
 pub async fn scan_system_async(
     options: &ScanOptions,
     app_handle: Option<&tauri::AppHandle>,
+    cancel: CancelFlag,
+    index: Option<SharedIndex>,
+    checkpoint: Option<&checkpoint::Checkpoint>,
 ) -> Result<ScanResults, ScannerError> {
     let start = Instant::now();
 
-    // Set memory and time limits for the scan (use provided limits or defaults)
+    // Set memory and time limits for the scan (use provided limits or the profile's)
+    let profile = options.throughput_profile.unwrap_or_default().limits();
     let scan_limits = ScanLimits {
         max_files: options.max_files.unwrap_or(50_000), // Limit to prevent excessive memory usage
         max_depth: options.max_depth.unwrap_or(10),     // Prevent infinite recursion
-        max_memory_mb: options.max_memory_mb.unwrap_or(500), // 500MB memory limit
+        max_memory_mb: options.max_memory_mb.unwrap_or(profile.max_memory_mb),
         timeout_seconds: 300, // 5 minute timeout (internal, not configurable)
+        threads: profile.threads.max(1),
+        pause_every: profile.pause_every,
+        pause: profile.pause,
+        io_priority: profile.io_priority,
+        read_budget: Arc::new(ReadBudget::new(profile.read_bytes_per_sec)),
     };
 
     let mut items = Vec::new();
     let mut total_size: u64 = 0;
     let mut total_items: usize = 0;
     let mut failed_categories = Vec::new();
+    let mut resumed_categories = Vec::new();
+    let mut cancelled = false;
+
+    // Mounts are only listed when the scan is limited to one of them
+    let mounts = if options.mount_point.is_some() { crate::mounts::list_mounts() } else { Vec::new() };
+    let target_mount = match &options.mount_point {
+        Some(mount_point) if !mounts.iter().any(|m| &m.mount_point == mount_point) => {
+            return Err(ScannerError::PathValidationError(format!("Not a mounted volume: {}", mount_point)));
+        }
+        target => target.clone(),
+    };
 
-    // Calculate total number of scan phases for progress tracking
-    let total_phases = [
-        options.include_caches,
-        options.include_packages,
-        options.include_logs,
-        options.include_large_files,
-    ]
-    .iter()
-    .filter(|&&enabled| enabled)
-    .count();
+    let filter = Arc::new(options.path_filter());
 
-    let mut completed_phases = 0;
+    let selected = registry::selected_scanners(options);
+    let total_phases = selected.len();
 
     // Check memory usage periodically
     let memory_check_interval = Duration::from_secs(10);
     let mut last_memory_check = start;
 
-    // Helper function to emit progress events
-    // Note: We pass completed_phases as a parameter to avoid borrowing issues
-    let emit_progress = |category: &str, phase_progress: u8, message: &str, items_found: usize, current_size: u64, phases_completed: usize| {
-        if let Some(handle) = app_handle {
-            let overall_progress = if total_phases > 0 {
-                ((phases_completed * 100 + phase_progress as usize) / total_phases).min(100) as u8
-            } else {
-                0
-            };
-
-            let progress_event = ScanProgress {
-                category: category.to_string(),
-                progress: overall_progress,
-                message: message.to_string(),
-                items_found,
-                current_size,
-            };
-
-            if let Err(e) = handle.emit("scan-progress", &progress_event) {
-                tracing::warn!("Failed to emit scan progress event: {}", e);
-            }
+    // Up to `threads` scanners run side by side; results are merged in registry order
+    for (batch_index, batch) in selected.chunks(scan_limits.threads).enumerate() {
+        if is_cancelled(&cancel) {
+            cancelled = true;
+            break;
         }
-    };
-
-    if options.include_caches {
-        emit_progress("caches", 0, "Scanning cache directories...", 0, 0, completed_phases);
-
-        match scan_caches_async(&scan_limits).await {
-            Ok(cache_items) => {
-                let cache_size: u64 = cache_items.iter().map(|i| i.size).sum();
-                let cache_count = cache_items.len();
 
-                for item in &cache_items {
-                    total_size += item.size;
-                    total_items += 1;
-                    if let Some(children) = &item.children {
-                        total_items += children.len();
+        let mut running = Vec::new();
+        for (offset, scanner) in batch.iter().enumerate() {
+            let phase = batch_index * scan_limits.threads + offset;
+            let category = scanner.id();
+            let saved = checkpoint.and_then(|c| c.saved(category));
+            let message = if saved.is_some() {
+                format!("Reusing {} results from the previous attempt...", scanner.name())
+            } else {
+                format!("Running {}...", scanner.name())
+            };
+            emit_scan_progress(app_handle, category, 0, &message, 0, 0, phase, total_phases);
+            let resumed = saved.is_some();
+
+            let run_scanner = Arc::clone(scanner);
+            let run_limits = scan_limits.clone();
+            let run_options = options
+                .scanner_options
+                .as_ref()
+                .and_then(|o| o.get(category).cloned())
+                .unwrap_or_default();
+            let run_cancel = Arc::clone(&cancel);
+            let run_index = index.clone();
+            let run_mount = target_mount.clone();
+            let run_filter = Arc::clone(&filter);
+            let progress_handle = app_handle.cloned();
+
+            let task = tokio::spawn(timeout(
+                scanner.timeout(),
+                tokio::task::spawn_blocking(move || {
+                    if let Some(saved) = saved {
+                        return Ok(saved);
                     }
-                }
-                items.extend(cache_items);
-
-                completed_phases += 1;
-                emit_progress("caches", 100, &format!("Found {} cache items", cache_count), cache_count, cache_size, completed_phases);
-            }
-            Err(e) => {
-                tracing::warn!("Cache scanning failed: {}", e);
-                failed_categories.push(FailedCategory {
-                    category: "caches".to_string(),
-                    error: e.to_string(),
-                });
-                completed_phases += 1;
-                emit_progress("caches", 100, &format!("Cache scan failed: {}", e), 0, 0, completed_phases);
-                // Continue with other scans even if cache scan fails
-            }
+                    let _io_priority = run_limits.io_priority.enter();
+                    let progress = move |percent: u8, message: &str| {
+                        emit_scan_progress(progress_handle.as_ref(), category, percent, message, 0, 0, phase, total_phases);
+                    };
+                    let ctx = ScanContext::new(&run_limits, run_options, &progress, &run_cancel);
+                    let ctx = match &run_index {
+                        Some(index) => ctx.with_index(index),
+                        None => ctx,
+                    };
+                    let ctx = match &run_mount {
+                        Some(mount_point) => ctx.with_mount(mount_point),
+                        None => ctx,
+                    };
+                    let ctx = ctx.with_filter(&run_filter);
+                    run_scanner.run(&ctx)
+                }),
+            ));
+            running.push((phase, scanner, task, resumed));
         }
 
-        // Check memory usage
-        let now = Instant::now();
-        if now.duration_since(last_memory_check) > memory_check_interval {
-            if let Err(e) = check_memory_limits(&scan_limits).await {
-                return Err(ScannerError::MemoryLimitExceeded(e.to_string()));
-            }
-            last_memory_check = now;
-        }
-    }
-
-    if options.include_packages {
-        emit_progress("packages", 0, "Scanning package caches...", 0, 0, completed_phases);
+        for (phase, scanner, task, resumed) in running {
+            let category = scanner.id();
+            let result = task
+                .await
+                .map_err(|e| ScannerError::IoError(std::io::Error::other(e)))
+                .and_then(|timed| timed.map_err(|_| ScannerError::Timeout))
+                .and_then(|joined| joined.map_err(|e| ScannerError::IoError(std::io::Error::other(e))))
+                .and_then(|result| result);
+
+            match result {
+                Ok(mut scanner_items) => {
+                    if let Some(target) = &target_mount {
+                        scanner_items.retain(|item| {
+                            crate::mounts::mount_for_path(&mounts, Path::new(&item.path)).is_some_and(|m| &m.mount_point == target)
+                        });
+                    }
+                    if !filter.is_empty() {
+                        scanner_items.retain(|item| filter.allows(Path::new(&item.path)));
+                    }
+                    let phase_size: u64 = scanner_items.iter().map(|i| i.size).sum();
+                    let phase_count = scanner_items.len();
+                    match checkpoint {
+                        Some(_) if resumed => resumed_categories.push(category.to_string()),
+                        Some(checkpoint) => checkpoint.record(category, &scanner_items),
+                        None => {}
+                    }
 
-        match scan_package_caches_async().await {
-            Ok(package_items) => {
-                let package_size: u64 = package_items.iter().map(|i| i.size).sum();
-                let package_count = package_items.len();
+                    for item in &scanner_items {
+                        total_size += item.size;
+                        total_items += 1;
+                        if let Some(children) = &item.children {
+                            total_items += children.len();
+                        }
+                    }
+                    items.extend(scanner_items);
 
-                for item in &package_items {
-                    total_size += item.size;
-                    total_items += 1;
+                    emit_scan_progress(app_handle, category, 100, &format!("Found {} items in {}", phase_count, scanner.name()), phase_count, phase_size, phase + 1, total_phases);
                 }
-                items.extend(package_items);
-
-                completed_phases += 1;
-                emit_progress("packages", 100, &format!("Found {} package cache items", package_count), package_count, package_size, completed_phases);
-            }
-            Err(e) => {
-                tracing::warn!("Package cache scanning failed: {}", e);
-                failed_categories.push(FailedCategory {
-                    category: "packages".to_string(),
-                    error: e.to_string(),
-                });
-                completed_phases += 1;
-                emit_progress("packages", 100, &format!("Package scan failed: {}", e), 0, 0, completed_phases);
-            }
-        }
-
-        // Check memory usage
-        let now = Instant::now();
-        if now.duration_since(last_memory_check) > memory_check_interval {
-            if let Err(e) = check_memory_limits(&scan_limits).await {
-                return Err(ScannerError::MemoryLimitExceeded(e.to_string()));
-            }
-            last_memory_check = now;
-        }
-    }
-
-    if options.include_logs {
-        emit_progress("logs", 0, "Scanning log files...", 0, 0, completed_phases);
-
-        match scan_logs_async(&scan_limits).await {
-            Ok(log_items) => {
-                let log_size: u64 = log_items.iter().map(|i| i.size).sum();
-                let log_count = log_items.len();
-
-                for item in &log_items {
-                    total_size += item.size;
-                    total_items += 1;
+                Err(ScannerError::Cancelled) => {
+                    // A scanner's own partial output is discarded; other scanners' results are kept
+                    cancelled = true;
+                }
+                Err(e) => {
+                    // Continue with other scanners even if one fails
+                    tracing::warn!("{} scanning failed: {}", scanner.name(), e);
+                    failed_categories.push(FailedCategory {
+                        category: category.to_string(),
+                        error: e.to_string(),
+                    });
+                    emit_scan_progress(app_handle, category, 100, &format!("{} scan failed: {}", scanner.name(), e), 0, 0, phase + 1, total_phases);
                 }
-                items.extend(log_items);
-
-                completed_phases += 1;
-                emit_progress("logs", 100, &format!("Found {} log files", log_count), log_count, log_size, completed_phases);
-            }
-            Err(e) => {
-                tracing::warn!("Log scanning failed: {}", e);
-                failed_categories.push(FailedCategory {
-                    category: "logs".to_string(),
-                    error: e.to_string(),
-                });
-                completed_phases += 1;
-                emit_progress("logs", 100, &format!("Log scan failed: {}", e), 0, 0, completed_phases);
             }
         }
-    }
+        if cancelled {
+            break;
+        }
 
-    if options.include_large_files {
         // Check memory usage
         let now = Instant::now();
         if now.duration_since(last_memory_check) > memory_check_interval {
             if let Err(e) = check_memory_limits(&scan_limits).await {
                 return Err(ScannerError::MemoryLimitExceeded(e.to_string()));
             }
-            // Note: This is the last memory check, so we don't update last_memory_check
-        }
-
-        emit_progress("large_files", 0, "Scanning for large files...", 0, 0, completed_phases);
-
-        match scan_large_files_async(&scan_limits).await {
-            Ok(large_files) => {
-                let large_size: u64 = large_files.iter().map(|i| i.size).sum();
-                let large_count = large_files.len();
-
-                for item in &large_files {
-                    total_size += item.size;
-                    total_items += 1;
-                }
-                items.extend(large_files);
-
-                completed_phases += 1;
-                emit_progress("large_files", 100, &format!("Found {} large files", large_count), large_count, large_size, completed_phases);
-            }
-            Err(e) => {
-                tracing::warn!("Large files scanning failed: {}", e);
-                failed_categories.push(FailedCategory {
-                    category: "large_files".to_string(),
-                    error: e.to_string(),
-                });
-                completed_phases += 1;
-                emit_progress("large_files", 100, &format!("Large files scan failed: {}", e), 0, 0, completed_phases);
-            }
+            last_memory_check = now;
         }
     }
 
@@ -372,379 +527,65 @@ pub async fn scan_system_async(
     // Final memory check
     check_memory_limits(&scan_limits).await?;
 
-    // Emit final completion event
-    emit_progress("complete", 100, &format!("Scan complete: {} items found", total_items), total_items, total_size, completed_phases);
-
-    Ok(ScanResults {
+    // Scanners overlap; list each file once, under an id that is the same next scan
+    identity::assign_ids(&mut items);
+    let shared_items = dedup::dedupe(&mut items);
+    let mut results = ScanResults {
         items,
         total_size,
         total_items,
         scan_time_ms: elapsed.as_millis() as u64,
-        timestamp: chrono::Utc::now().to_rfc3339(),
+        timestamp: crate::timestamps::now(),
         failed_categories,
-    })
-}
-
-
-/// Async version of cache scanning with proper error handling
-async fn scan_caches_async(_limits: &ScanLimits) -> Result<Vec<ScanItem>, ScannerError> {
-    let mut items = Vec::new();
-
-    let home = dirs::home_dir()
-        .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
-
-    let cache_dirs = vec![
-        (home.join(".cache"), "User Cache"),
-        (home.join(".local/share/Trash"), "User Trash"),
-        (home.join(".thumbnails"), "Thumbnails"),
-    ];
-
-    for (path, name) in cache_dirs {
-        if path.exists() {
-            // Clone path for the blocking task
-            let path_clone = path.clone();
-            // Use tokio::task::spawn_blocking for CPU-intensive directory size calculation
-            let size = timeout(
-                Duration::from_secs(30),
-                tokio::task::spawn_blocking(move || trash::get_dir_size(&path_clone))
-            ).await
-            .map_err(|_| ScannerError::Timeout)?
-            .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-            if size > 0 {
-                let mut item = ScanItem {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    name: name.to_string(),
-                    path: path.to_string_lossy().to_string(),
-                    size,
-                    item_type: "cache".to_string(),
-                    category: "Cache".to_string(),
-                    risk_level: 0,
-                    description: "Cache directory - safe to remove".to_string(),
-                    children: None,
-                    dependencies: None,
-                    dependents: None,
-                };
-
-                // Scan subdirectories with depth limit
-                item.children = scan_cache_subdirs_async(&path, _limits.max_depth).await?;
-                items.push(item);
-            }
-        }
-    }
-
-    // Browser caches
-    let browser_caches = vec![
-        (home.join(".cache/google-chrome"), "Chrome Cache"),
-        (home.join(".cache/mozilla/firefox"), "Firefox Cache"),
-        (home.join(".cache/chromium"), "Chromium Cache"),
-    ];
-
-    for (path, name) in browser_caches {
-        if path.exists() {
-            let path_clone = path.clone();
-            let size = timeout(
-                Duration::from_secs(30),
-                tokio::task::spawn_blocking(move || trash::get_dir_size(&path_clone))
-            ).await
-            .map_err(|_| ScannerError::Timeout)?
-            .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-            if size > 10 * 1024 * 1024 {
-                items.push(ScanItem {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    name: name.to_string(),
-                    path: path.to_string_lossy().to_string(),
-                    size,
-                    item_type: "cache".to_string(),
-                    category: "Browser".to_string(),
-                    risk_level: 0,
-                    description: "Browser cache - safe to remove".to_string(),
-                    children: None,
-                    dependencies: None,
-                    dependents: None,
-                });
-            }
-        }
-    }
-
-    Ok(items)
-}
-
-
-/// Async version of cache subdirectory scanning
-async fn scan_cache_subdirs_async(path: &Path, _max_depth: usize) -> Result<Option<Vec<ScanItem>>, ScannerError> {
-    let mut children = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.filter_map(|e| e.ok()) {
-            let entry_path = entry.path();
-            if entry_path.is_dir() {
-                // Use blocking task for directory size calculation
-                let size = timeout(
-                    Duration::from_secs(10),
-                    tokio::task::spawn_blocking({
-                        let path_clone = entry_path.clone();
-                        move || trash::get_dir_size(&path_clone)
-                    })
-                ).await
-                .map_err(|_| ScannerError::Timeout)?
-                .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-                if size > 5 * 1024 * 1024 {
-                    children.push(ScanItem {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        name: entry_path.file_name()
-                            .and_then(|n| n.to_str())
-                            .map(|s| s.to_string())
-                            .unwrap_or_else(|| entry_path.to_string_lossy().to_string()),
-                        path: entry_path.to_string_lossy().to_string(),
-                        size,
-                        item_type: "directory".to_string(),
-                        category: "Cache".to_string(),
-                        risk_level: 0,
-                        description: "Application cache".to_string(),
-                        children: None,
-                        dependencies: None,
-                        dependents: None,
-                    });
-                }
-            }
-        }
-    }
+        cancelled,
+        shared_items,
+        preselected: Vec::new(),
+        resumed_categories,
+    };
+    dedup::recount(&mut results);
 
-    if children.is_empty() {
-        Ok(None)
+    // Emit final completion event
+    let message = if cancelled {
+        format!("Scan cancelled: {} items found", results.total_items)
     } else {
-        children.sort_by(|a, b| b.size.cmp(&a.size));
-        Ok(Some(children.into_iter().take(10).collect()))
-    }
-}
-
+        format!("Scan complete: {} items found", results.total_items)
+    };
+    emit_scan_progress(app_handle, "complete", 100, &message, results.total_items, results.total_size, total_phases, total_phases);
 
-/// Async version of package cache scanning
-async fn scan_package_caches_async() -> Result<Vec<ScanItem>, ScannerError> {
-    let mut items = Vec::new();
+    Ok(results)
+}
 
-    let home = dirs::home_dir()
-        .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
-
-    // APT cache
-    let apt_cache = PathBuf::from("/var/cache/apt/archives");
-    if apt_cache.exists() {
-        let apt_cache_clone = apt_cache.clone();
-        let size = timeout(
-            Duration::from_secs(30),
-            tokio::task::spawn_blocking(move || trash::get_dir_size(&apt_cache_clone))
-        ).await
-        .map_err(|_| ScannerError::Timeout)?
-        .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-        if size > 0 {
-            items.push(ScanItem {
-                id: uuid::Uuid::new_v4().to_string(),
-                name: "APT Package Cache".to_string(),
-                path: apt_cache.to_string_lossy().to_string(),
-                size,
-                item_type: "cache".to_string(),
-                category: "Package Manager".to_string(),
-                risk_level: 0,
-                description: "Downloaded .deb packages - safe to remove".to_string(),
-                children: None,
-                dependencies: None,
-                dependents: None,
-            });
-        }
-    }
+/// Emit a "scan-progress" event, scaling a scanner's own progress into overall progress
+#[allow(clippy::too_many_arguments)]
+fn emit_scan_progress(
+    app_handle: Option<&tauri::AppHandle>,
+    category: &str,
+    phase_progress: u8,
+    message: &str,
+    items_found: usize,
+    current_size: u64,
+    phases_completed: usize,
+    total_phases: usize,
+) {
+    if let Some(handle) = app_handle {
+        let overall_progress = if total_phases > 0 {
+            ((phases_completed * 100 + phase_progress as usize) / total_phases).min(100) as u8
+        } else {
+            0
+        };
 
-    // pip cache
-    let pip_cache = home.join(".cache/pip");
-    if pip_cache.exists() {
-        let pip_cache_clone = pip_cache.clone();
-        let size = timeout(
-            Duration::from_secs(30),
-            tokio::task::spawn_blocking(move || trash::get_dir_size(&pip_cache_clone))
-        ).await
-        .map_err(|_| ScannerError::Timeout)?
-        .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-        if size > 0 {
-            items.push(ScanItem {
-                id: uuid::Uuid::new_v4().to_string(),
-                name: "pip Cache".to_string(),
-                path: pip_cache.to_string_lossy().to_string(),
-                size,
-                item_type: "cache".to_string(),
-                category: "Python".to_string(),
-                risk_level: 0,
-                description: "Python package cache - safe to remove".to_string(),
-                children: None,
-                dependencies: None,
-                dependents: None,
-            });
-        }
-    }
+        let progress_event = ScanProgress {
+            category: category.to_string(),
+            progress: overall_progress,
+            message: message.to_string(),
+            items_found,
+            current_size,
+        };
 
-    // npm cache
-    let npm_cache = home.join(".npm/_cacache");
-    if npm_cache.exists() {
-        let npm_cache_clone = npm_cache.clone();
-        let size = timeout(
-            Duration::from_secs(30),
-            tokio::task::spawn_blocking(move || trash::get_dir_size(&npm_cache_clone))
-        ).await
-        .map_err(|_| ScannerError::Timeout)?
-        .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-        if size > 0 {
-            items.push(ScanItem {
-                id: uuid::Uuid::new_v4().to_string(),
-                name: "npm Cache".to_string(),
-                path: npm_cache.to_string_lossy().to_string(),
-                size,
-                item_type: "cache".to_string(),
-                category: "Node.js".to_string(),
-                risk_level: 0,
-                description: "Node.js package cache - safe to remove".to_string(),
-                children: None,
-                dependencies: None,
-                dependents: None,
-            });
+        if let Err(e) = handle.emit("scan-progress", &progress_event) {
+            tracing::warn!("Failed to emit scan progress event: {}", e);
         }
     }
-
-    Ok(items)
-}
-
-
-/// Async version of log scanning with proper limits
-async fn scan_logs_async(limits: &ScanLimits) -> Result<Vec<ScanItem>, ScannerError> {
-    let mut items = Vec::new();
-
-    let home = dirs::home_dir()
-        .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
-
-    // Clone limits data to move into the closure
-    let max_depth = limits.max_depth;
-    let max_files = limits.max_files;
-    let home_clone = home.clone();
-
-    // Use tokio::task::spawn_blocking for the synchronous WalkDir operation
-    let log_items = timeout(
-        Duration::from_secs(60),
-        tokio::task::spawn_blocking(move || {
-            let mut sync_items = Vec::new();
-            for entry in WalkDir::new(home_clone.join(".local/share"))
-                .max_depth(max_depth)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .take(max_files)
-            {
-                let path = entry.path();
-                if path.is_file() {
-                    let name = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| path.to_string_lossy().to_string());
-
-                    if name.ends_with(".log") || name.ends_with(".log.1") || name.contains(".log.") {
-                        if let Ok(metadata) = path.metadata() {
-                            let size = metadata.len();
-                            if size > 10 * 1024 * 1024 {
-                                sync_items.push(ScanItem {
-                                    id: uuid::Uuid::new_v4().to_string(),
-                                    name: name.to_string(),
-                                    path: path.to_string_lossy().to_string(),
-                                    size,
-                                    item_type: "file".to_string(),
-                                    category: "Logs".to_string(),
-                                    risk_level: 1,
-                                    description: "Log file - review before removing".to_string(),
-                                    children: None,
-                                    dependencies: None,
-                                    dependents: None,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-            sync_items
-        })
-    ).await
-    .map_err(|_| ScannerError::Timeout)?
-    .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-    items.extend(log_items);
-    Ok(items)
-}
-
-
-/// Async version of large files scanning with proper limits
-async fn scan_large_files_async(limits: &ScanLimits) -> Result<Vec<ScanItem>, ScannerError> {
-    let mut items = Vec::new();
-
-    let home = dirs::home_dir()
-        .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
-
-    let scan_dirs = vec![home.join("Downloads"), home.join("Documents")];
-    let threshold = 100 * 1024 * 1024; // 100MB
-
-    // Clone limits data to move into the closure
-    let max_depth = limits.max_depth;
-    let max_files = limits.max_files;
-    let scan_dirs_clone = scan_dirs.clone();
-
-    // Use tokio::task::spawn_blocking for the synchronous WalkDir operation
-    let large_files = timeout(
-        Duration::from_secs(120),
-        tokio::task::spawn_blocking(move || {
-            let mut sync_items = Vec::new();
-            for dir in scan_dirs_clone {
-                if dir.exists() {
-                    for entry in WalkDir::new(&dir)
-                        .max_depth(max_depth)
-                        .into_iter()
-                        .filter_map(|e| e.ok())
-                        .take(max_files)
-                    {
-                        let path = entry.path();
-                        if path.is_file() {
-                            if let Ok(metadata) = path.metadata() {
-                                let size = metadata.len();
-                                if size > threshold {
-                                    sync_items.push(ScanItem {
-                                        id: uuid::Uuid::new_v4().to_string(),
-                                        name: path.file_name()
-                                            .and_then(|n| n.to_str())
-                                            .map(|s| s.to_string())
-                                            .unwrap_or_else(|| path.to_string_lossy().to_string()),
-                                        path: path.to_string_lossy().to_string(),
-                                        size,
-                                        item_type: "file".to_string(),
-                                        category: "Large Files".to_string(),
-                                        risk_level: 2,
-                                        description: "Large file - review before removing".to_string(),
-                                        children: None,
-                                        dependencies: None,
-                                        dependents: None,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            sync_items
-        })
-    ).await
-    .map_err(|_| ScannerError::Timeout)?
-    .map_err(|e| ScannerError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-    items.extend(large_files);
-    items.sort_by(|a, b| b.size.cmp(&a.size));
-    items.truncate(20); // Limit results
-    Ok(items)
 }
 
 
@@ -755,24 +596,47 @@ pub struct FilesystemHealthResults {
     pub empty_directories: Vec<ScanItem>,
     pub broken_symlinks: Vec<ScanItem>,
     pub orphaned_temp_files: Vec<ScanItem>,
+    /// `.desktop` files in the user's applications and autostart folders whose program is gone
+    #[serde(default)]
+    pub broken_launchers: Vec<ScanItem>,
+    /// Empty files and leftovers like `.DS_Store` or old editor swap files, when asked for
+    #[serde(default)]
+    pub junk_files: Vec<ScanItem>,
     pub total_size: u64,
     pub total_items: usize,
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
-pub fn scan_filesystem_health() -> FilesystemHealthResults {
+/// Setting `cancel` stops the walk early and returns what was found so far.
+/// With `one_file_system`, other filesystems mounted below home are skipped.
+/// Junk files are only looked for with `junk_files`.
+pub fn scan_filesystem_health(cancel: &CancelFlag, one_file_system: bool, junk_files: bool) -> FilesystemHealthResults {
     let start_time = Instant::now();
 
     tracing::info!("Starting filesystem health check scan");
 
-    let empty_dirs = scan_empty_directories();
-    let broken_links = scan_broken_symlinks();
-    let orphaned_temp = scan_orphaned_temp_files();
+    let search_path: Vec<_> = std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()).collect();
+    let (empty_dirs, broken_links, orphaned_temp, broken_launchers, mut junk) = match dirs::home_dir() {
+        Some(home) => (
+            scan_empty_directories(&home, cancel, one_file_system),
+            scan_broken_symlinks(&home, cancel, one_file_system),
+            scan_orphaned_temp_files(&home, cancel, one_file_system),
+            launchers::scan_broken_launchers(&home, &search_path, cancel),
+            if junk_files { junk::scan_junk_files(&home, cancel, one_file_system) } else { Vec::new() },
+        ),
+        None => Default::default(),
+    };
+    // Old swap and backup files may already be listed as orphaned temp files
+    junk.retain(|item| !orphaned_temp.iter().any(|temp| temp.path == item.path));
 
     let total_size = empty_dirs.iter().map(|i| i.size).sum::<u64>() +
                      broken_links.iter().map(|i| i.size).sum::<u64>() +
-                     orphaned_temp.iter().map(|i| i.size).sum::<u64>();
+                     orphaned_temp.iter().map(|i| i.size).sum::<u64>() +
+                     broken_launchers.iter().map(|i| i.size).sum::<u64>() +
+                     junk.iter().map(|i| i.size).sum::<u64>();
 
-    let total_items = empty_dirs.len() + broken_links.len() + orphaned_temp.len();
+    let total_items = empty_dirs.len() + broken_links.len() + orphaned_temp.len() + broken_launchers.len() + junk.len();
 
     let scan_time = start_time.elapsed().as_millis() as u64;
     tracing::info!("Filesystem health check completed in {}ms: {} items, {} bytes",
@@ -782,90 +646,50 @@ pub fn scan_filesystem_health() -> FilesystemHealthResults {
         empty_directories: empty_dirs,
         broken_symlinks: broken_links,
         orphaned_temp_files: orphaned_temp,
+        broken_launchers,
+        junk_files: junk,
         total_size,
         total_items,
+        cancelled: is_cancelled(cancel),
     }
 }
 
-fn scan_empty_directories() -> Vec<ScanItem> {
+fn scan_empty_directories(home: &Path, cancel: &CancelFlag, one_file_system: bool) -> Vec<ScanItem> {
     let mut items = Vec::new();
 
-    if let Some(home) = dirs::home_dir() {
-        let walker = WalkDir::new(&home)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok());
-
-        for entry in walker {
-            let path = entry.path();
+    let walker = WalkDir::new(home)
+        .follow_links(false)
+        .same_file_system(one_file_system)
+        .into_iter()
+        .filter_map(|e| e.ok());
 
-            if path.is_dir() {
-                // Check if directory is empty
-                if let Ok(read_dir) = std::fs::read_dir(path) {
-                    if read_dir.count() == 0 {
-                        // Double-check it's still empty (in case of race condition)
-                        if let Ok(recheck) = std::fs::read_dir(path) {
-                            if recheck.count() == 0 {
-                                let path_str = path.to_string_lossy().to_string();
-                                items.push(ScanItem {
-                                    id: format!("empty_dir_{}", items.len()),
-                                    name: path.file_name()
-                                        .and_then(|n| n.to_str())
-                                        .map(|s| s.to_string())
-                                        .unwrap_or_else(|| path.to_string_lossy().to_string()),
-                                    path: path_str,
-                                    size: 0,
-                                    item_type: "directory".to_string(),
-                                    category: "empty_directory".to_string(),
-                                    risk_level: 0, // Safe to remove
-                                    description: "Empty directory with no contents".to_string(),
-                                    children: None,
-                                    dependencies: None,
-                                    dependents: None,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
+    for entry in walker {
+        if is_cancelled(cancel) {
+            break;
         }
-    }
-
-    tracing::info!("Found {} empty directories", items.len());
-    items
-}
-
-fn scan_broken_symlinks() -> Vec<ScanItem> {
-    let mut items = Vec::new();
-
-    if let Some(home) = dirs::home_dir() {
-        let walker = WalkDir::new(&home)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok());
-
-        for entry in walker {
-            let path = entry.path();
+        let path = entry.path();
 
-            if let Ok(metadata) = std::fs::symlink_metadata(path) {
-                if metadata.file_type().is_symlink() {
-                    // Check if symlink target exists
-                    if let Ok(target) = std::fs::read_link(path) {
-                        if !target.exists() {
+        if path.is_dir() {
+            // Check if directory is empty
+            if let Ok(read_dir) = std::fs::read_dir(path) {
+                if read_dir.count() == 0 {
+                    // Double-check it's still empty (in case of race condition)
+                    if let Ok(recheck) = std::fs::read_dir(path) {
+                        if recheck.count() == 0 {
                             let path_str = path.to_string_lossy().to_string();
                             items.push(ScanItem {
-                                id: format!("broken_link_{}", items.len()),
+                                id: format!("empty_dir_{}", items.len()),
                                 name: path.file_name()
                                     .and_then(|n| n.to_str())
                                     .map(|s| s.to_string())
                                     .unwrap_or_else(|| path.to_string_lossy().to_string()),
                                 path: path_str,
                                 size: 0,
-                                item_type: "symlink".to_string(),
-                                category: "broken_symlink".to_string(),
+                                apparent_size: None,
+                                item_type: "directory".to_string(),
+                                category: "empty_directory".to_string(),
                                 risk_level: 0, // Safe to remove
-                                description: format!("Broken symlink pointing to non-existent target: {}",
-                                                   target.display()),
+                                description: "Empty directory with no contents".to_string(),
                                 children: None,
                                 dependencies: None,
                                 dependents: None,
@@ -877,93 +701,146 @@ fn scan_broken_symlinks() -> Vec<ScanItem> {
         }
     }
 
+    tracing::info!("Found {} empty directories", items.len());
+    items
+}
+
+fn scan_broken_symlinks(home: &Path, cancel: &CancelFlag, one_file_system: bool) -> Vec<ScanItem> {
+    let mut items = Vec::new();
+
+    let walker = WalkDir::new(home)
+        .follow_links(false)
+        .same_file_system(one_file_system)
+        .into_iter()
+        .filter_map(|e| e.ok());
+
+    for entry in walker {
+        if is_cancelled(cancel) {
+            break;
+        }
+        let path = entry.path();
+
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if metadata.file_type().is_symlink() {
+                // Check if symlink target exists
+                if let Ok(target) = std::fs::read_link(path) {
+                    // Through the link, so relative targets resolve from its directory
+                    if !path.exists() {
+                        let path_str = path.to_string_lossy().to_string();
+                        items.push(ScanItem {
+                            id: format!("broken_link_{}", items.len()),
+                            name: path.file_name()
+                                .and_then(|n| n.to_str())
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+                            path: path_str,
+                            size: 0,
+                            apparent_size: None,
+                            item_type: "symlink".to_string(),
+                            category: "broken_symlink".to_string(),
+                            risk_level: 0, // Safe to remove
+                            description: format!("Broken symlink pointing to non-existent target: {}",
+                                               target.display()),
+                            children: None,
+                            dependencies: None,
+                            dependents: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
     tracing::info!("Found {} broken symlinks", items.len());
     items
 }
 
-fn scan_orphaned_temp_files() -> Vec<ScanItem> {
+fn scan_orphaned_temp_files(home: &Path, cancel: &CancelFlag, one_file_system: bool) -> Vec<ScanItem> {
     let mut items = Vec::new();
 
-    if let Some(home) = dirs::home_dir() {
-        // Common temp file patterns
-        let temp_patterns = [
-            "*.tmp", "*.temp", "*.swp", "*.bak", "*.orig",
-            "*.old", "~*", "*~", "*.lock", "*.pid"
-        ];
-
-        // Common temp directories
-        let temp_dirs = [
-            home.join("tmp"),
-            home.join(".tmp"),
-            home.join("temp"),
-            home.join("Temp"),
-            home.join("TEMP"),
-        ];
-
-        // Also check common temp locations in home
-        let walker = WalkDir::new(&home)
-            .follow_links(false)
-            .max_depth(3) // Don't go too deep
-            .into_iter()
-            .filter_map(|e| e.ok());
+    // Common temp file patterns
+    let temp_patterns = [
+        "*.tmp", "*.temp", "*.swp", "*.bak", "*.orig",
+        "*.old", "~*", "*~", "*.lock", "*.pid"
+    ];
 
-        for entry in walker {
-            let path = entry.path();
+    // Common temp directories
+    let temp_dirs = [
+        home.join("tmp"),
+        home.join(".tmp"),
+        home.join("temp"),
+        home.join("Temp"),
+        home.join("TEMP"),
+    ];
 
-            if path.is_file() {
-                if let Some(filename) = path.file_name() {
-                    let filename_str = filename.to_string_lossy();
+    // Also check common temp locations in home
+    let walker = WalkDir::new(home)
+        .follow_links(false)
+        .same_file_system(one_file_system)
+        .max_depth(3) // Don't go too deep
+        .into_iter()
+        .filter_map(|e| e.ok());
 
-                    // Check if it's in a temp directory or matches temp patterns
-                    let is_in_temp_dir = temp_dirs.iter().any(|temp_dir| {
-                        path.starts_with(temp_dir)
-                    });
+    for entry in walker {
+        if is_cancelled(cancel) {
+            break;
+        }
+        let path = entry.path();
 
-                    let matches_temp_pattern = temp_patterns.iter().any(|pattern| {
-                        // Simple glob matching
-                        if let Some(prefix_stripped) = pattern.strip_prefix("*.") {
-                            if let Some(suffix_stripped) = prefix_stripped.strip_suffix('*') {
-                                filename_str.contains(suffix_stripped)
-                            } else {
-                                filename_str.ends_with(prefix_stripped)
-                            }
-                        } else if let Some(suffix_stripped) = pattern.strip_suffix('*') {
-                            filename_str.starts_with(suffix_stripped)
+        if path.is_file() {
+            if let Some(filename) = path.file_name() {
+                let filename_str = filename.to_string_lossy();
+
+                // Check if it's in a temp directory or matches temp patterns
+                let is_in_temp_dir = temp_dirs.iter().any(|temp_dir| {
+                    path.starts_with(temp_dir)
+                });
+
+                let matches_temp_pattern = temp_patterns.iter().any(|pattern| {
+                    // Simple glob matching
+                    if let Some(prefix_stripped) = pattern.strip_prefix("*.") {
+                        if let Some(suffix_stripped) = prefix_stripped.strip_suffix('*') {
+                            filename_str.contains(suffix_stripped)
                         } else {
-                            filename_str == *pattern
+                            filename_str.ends_with(prefix_stripped)
                         }
-                    });
+                    } else if let Some(suffix_stripped) = pattern.strip_suffix('*') {
+                        filename_str.starts_with(suffix_stripped)
+                    } else {
+                        filename_str == *pattern
+                    }
+                });
 
-                    if is_in_temp_dir || matches_temp_pattern {
-                        // Check if file is older than 30 days (orphaned temp file)
-                        if let Ok(metadata) = std::fs::metadata(path) {
-                            if let Ok(modified) = metadata.modified() {
-                                let age_days = match modified.elapsed() {
-                                    Ok(duration) => duration.as_secs() / (24 * 3600),
-                                    Err(_) => {
-                                        // File modified in the future (clock skew) - skip
-                                        continue;
-                                    }
-                                };
-
-                                if age_days > 30 {
-                                    let path_str = path.to_string_lossy().to_string();
-                                    let size = metadata.len();
-
-                                    items.push(ScanItem {
-                                        id: format!("orphaned_temp_{}", items.len()),
-                                        name: filename.to_string_lossy().to_string(),
-                                        path: path_str,
-                                        size,
-                                        item_type: "file".to_string(),
-                                        category: "orphaned_temp".to_string(),
-                                        risk_level: 1, // Low risk, review suggested
-                                        description: format!("Orphaned temporary file, {} days old", age_days),
-                                        children: None,
-                                        dependencies: None,
-                                        dependents: None,
-                                    });
+                if is_in_temp_dir || matches_temp_pattern {
+                    // Check if file is older than 30 days (orphaned temp file)
+                    if let Ok(metadata) = std::fs::metadata(path) {
+                        if let Ok(modified) = metadata.modified() {
+                            let age_days = match modified.elapsed() {
+                                Ok(duration) => duration.as_secs() / (24 * 3600),
+                                Err(_) => {
+                                    // File modified in the future (clock skew) - skip
+                                    continue;
                                 }
+                            };
+
+                            if age_days > 30 {
+                                let path_str = path.to_string_lossy().to_string();
+                                let size = allocation::allocated(&metadata);
+
+                                items.push(ScanItem {
+                                    id: format!("orphaned_temp_{}", items.len()),
+                                    name: filename.to_string_lossy().to_string(),
+                                    path: path_str,
+                                    size,
+                                    apparent_size: allocation::apparent_if_different(&metadata),
+                                    item_type: "file".to_string(),
+                                    category: "orphaned_temp".to_string(),
+                                    risk_level: 1, // Low risk, review suggested
+                                    description: format!("Orphaned temporary file, {} days old", age_days),
+                                    children: None,
+                                    dependencies: None,
+                                    dependents: None,
+                                });
                             }
                         }
                     }
@@ -986,7 +863,27 @@ pub struct StorageRecoveryResults {
     pub total_duplicate_size: u64,
     pub total_large_files_size: u64,
     pub total_old_downloads_size: u64,
+    /// Disabled snap revisions (see `snaps`), removed with `remove_disabled_snaps`
+    #[serde(default)]
+    pub old_snap_revisions: Vec<ScanItem>,
+    #[serde(default)]
+    pub total_snap_revisions_size: u64,
+    /// Near-identical photos, only when asked for; not part of `total_recoverable_size`
+    #[serde(default)]
+    pub similar_images: Vec<similar::SimilarImageGroup>,
+    /// btrfs and ZFS snapshots past `STALE_AFTER_DAYS` (see `snapshots::existing`),
+    /// removed with `delete_filesystem_snapshots`
+    #[serde(default)]
+    pub stale_snapshots: Vec<ScanItem>,
+    #[serde(default)]
+    pub total_stale_snapshots_size: u64,
+    /// Part of the files above that snapshots still hold, so deleting them frees
+    /// nothing yet; already taken off `total_recoverable_size`
+    #[serde(default)]
+    pub snapshot_held_size: u64,
     pub total_recoverable_size: u64,
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -996,33 +893,112 @@ pub struct DuplicateGroup {
     pub files: Vec<ScanItem>,
     pub total_size: u64,
     pub group_size: usize,
+    /// Whole file contents were hashed (`HashMode::Full`), not just samples
+    #[serde(default)]
+    pub verified: bool,
+    /// Path of the copy to keep, see `duplicates::suggest_keeper`
+    #[serde(default)]
+    pub suggested_keeper: Option<String>,
+    /// Ids of the other copies, selected for deletion up front
+    #[serde(default)]
+    pub preselected: Vec<String>,
 }
 
-/// Scan for storage recovery opportunities (duplicates, large files, old downloads)
-/// Returns results even if some scans fail (partial success)
-pub fn scan_storage_recovery() -> AnyhowResult<StorageRecoveryResults> {
+/// Files a duplicate scan looks at below one root, unless told otherwise
+pub const DUPLICATE_FILE_LIMIT: usize = 10000;
+
+/// A directory a targeted duplicate scan walks
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct DuplicateRoot {
+    /// `~` expands to the home directory
+    pub path: String,
+    /// Files to look at below it; `DUPLICATE_FILE_LIMIT` when unset
+    pub max_files: Option<usize>,
+}
+
+/// How duplicate candidates are compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum HashMode {
+    /// Size plus the first, middle and last 64KB; fast, but files that differ
+    /// elsewhere are reported as duplicates
+    #[default]
+    Sampled,
+    /// BLAKE3 of the entire file, so duplicates are identical byte for byte
+    Full,
+}
+
+impl HashMode {
+    /// Tag for hashes stored in the file index
+    pub(crate) fn index_kind(self) -> &'static str {
+        match self {
+            HashMode::Sampled => "sampled",
+            HashMode::Full => "blake3",
+        }
+    }
+}
+
+/// Scan for storage recovery opportunities (duplicates, large files, old downloads,
+/// disabled snap revisions, stale filesystem snapshots)
+/// Returns results even if some scans fail (partial success), or what was found
+/// so far once `cancel` is set. Files unchanged since they were indexed aren't rehashed;
+/// the ones that are hashed draw on `read_budget`.
+pub fn scan_storage_recovery(
+    cancel: &CancelFlag,
+    index: &SharedIndex,
+    hash_mode: HashMode,
+    filter: &PathFilter,
+    similar_images: bool,
+    read_budget: &ReadBudget,
+) -> AnyhowResult<StorageRecoveryResults> {
     let start_time = Instant::now();
 
-    tracing::info!("Starting storage recovery scan");
+    tracing::info!("Starting storage recovery scan ({:?} duplicate hashing)", hash_mode);
+
+    let home = dirs::home_dir()
+        .context("Cannot determine home directory")?;
 
     // Run all scans - each can fail independently
-    let duplicates = scan_duplicate_files()
+    let duplicates = scan_duplicate_files(&[(home.clone(), DUPLICATE_FILE_LIMIT)], cancel, index, hash_mode, filter, read_budget)
         .context("Failed to scan for duplicate files")?;
 
-    let large_files = scan_large_files_storage_recovery(1024 * 1024 * 1024) // 1GB threshold
+    let large_files = scan_large_files_storage_recovery(&home, 1024 * 1024 * 1024, cancel, filter) // 1GB threshold
         .context("Failed to scan for large files")?;
 
-    let old_downloads = scan_old_downloads(90) // 90 days
+    let old_downloads = scan_old_downloads(&home, 90, cancel, filter) // 90 days
         .context("Failed to scan for old downloads")?;
 
+    let old_snap_revisions = if is_cancelled(cancel) { Vec::new() } else { crate::snaps::revision_items() };
+
+    let similar_images = if similar_images && !is_cancelled(cancel) {
+        similar::scan_similar_images(cancel, index, filter, &duplicates, read_budget)
+    } else {
+        Vec::new()
+    };
+
     let total_duplicate_size: u64 = duplicates.iter().map(|g| g.total_size).sum();
     let total_large_files_size: u64 = large_files.iter().map(|i| i.size).sum();
     let total_old_downloads_size: u64 = old_downloads.iter().map(|i| i.size).sum();
-    let total_recoverable_size = total_duplicate_size + total_large_files_size + total_old_downloads_size;
+    let total_snap_revisions_size: u64 = old_snap_revisions.iter().map(|i| i.size).sum();
+
+    let snapshots = if is_cancelled(cancel) { Vec::new() } else { crate::snapshots::existing::list_snapshots() };
+    let stale_snapshots = crate::snapshots::existing::stale_items(&snapshots, chrono::Utc::now().timestamp());
+    let total_stale_snapshots_size: u64 = stale_snapshots.iter().map(|i| i.size).sum();
+    // One copy of each duplicate stays
+    let deletable = duplicates.iter().flat_map(|g| g.files.iter().skip(1)).chain(&large_files).chain(&old_downloads);
+    let snapshot_held_size = crate::snapshots::existing::held_size(deletable, &snapshots);
+    let total_recoverable_size = (total_duplicate_size
+        + total_large_files_size
+        + total_old_downloads_size
+        + total_snap_revisions_size
+        + total_stale_snapshots_size)
+        .saturating_sub(snapshot_held_size);
 
     let scan_time = start_time.elapsed().as_millis() as u64;
-    tracing::info!("Storage recovery scan completed in {}ms: {} duplicates, {} large files, {} old downloads, {} bytes recoverable",
-                   scan_time, duplicates.len(), large_files.len(), old_downloads.len(), total_recoverable_size);
+    tracing::info!("Storage recovery scan completed in {}ms: {} duplicates, {} large files, {} old downloads, {} snap revisions, {} bytes recoverable",
+                   scan_time, duplicates.len(), large_files.len(), old_downloads.len(), old_snap_revisions.len(), total_recoverable_size);
 
     Ok(StorageRecoveryResults {
         duplicates,
@@ -1031,13 +1007,20 @@ pub fn scan_storage_recovery() -> AnyhowResult<StorageRecoveryResults> {
         total_duplicate_size,
         total_large_files_size,
         total_old_downloads_size,
+        old_snap_revisions,
+        total_snap_revisions_size,
+        similar_images,
+        stale_snapshots,
+        total_stale_snapshots_size,
+        snapshot_held_size,
         total_recoverable_size,
+        cancelled: is_cancelled(cancel),
     })
 }
 
 /// Compute a content hash of a file using chunked reading (doesn't load entire file)
 /// Samples: first 64KB, middle 64KB, and last 64KB
-fn compute_file_hash_chunked(path: &Path) -> AnyhowResult<String> {
+pub(crate) fn compute_file_hash_chunked(path: &Path) -> AnyhowResult<String> {
     let mut file = File::open(path)
         .with_context(|| format!("Failed to open file for hashing: {}", path.display()))?;
 
@@ -1088,66 +1071,137 @@ fn compute_file_hash_chunked(path: &Path) -> AnyhowResult<String> {
     Ok(format!("{:x}", hasher.finish()))
 }
 
-/// Scan for duplicate files using chunked hashing (memory-efficient)
-/// Limits scan to prevent excessive processing time
-fn scan_duplicate_files() -> AnyhowResult<Vec<DuplicateGroup>> {
-    let mut duplicates = Vec::new();
+/// Hash the whole file with BLAKE3, reading it in 1MB blocks
+pub(crate) fn compute_file_hash_full(path: &Path, cancel: &CancelFlag) -> AnyhowResult<String> {
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open file for hashing: {}", path.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
 
-    let home = dirs::home_dir()
-        .context("Cannot determine home directory")?;
+    loop {
+        if is_cancelled(cancel) {
+            anyhow::bail!("Cancelled while hashing {}", path.display());
+        }
+        let read = file.read(&mut buffer)
+            .with_context(|| format!("Failed to read: {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
 
-    // Use a hash map to group files by size first, then by content hash
-    let mut size_groups: std::collections::HashMap<u64, Vec<std::path::PathBuf>> = std::collections::HashMap::new();
+    Ok(hasher.finalize().to_hex().to_string())
+}
 
-    const MAX_FILES_TO_SCAN: usize = 10000; // Limit to prevent excessive scanning
-    let mut files_scanned = 0;
+/// Duplicates among the files below `roots` only, each walked up to its own
+/// file limit; see `scan_duplicate_files`
+pub fn scan_duplicates_in(
+    roots: &[DuplicateRoot],
+    home: &Path,
+    cancel: &CancelFlag,
+    index: &SharedIndex,
+    hash_mode: HashMode,
+    filter: &PathFilter,
+    read_budget: &ReadBudget,
+) -> AnyhowResult<Vec<DuplicateGroup>> {
+    let roots: Vec<(PathBuf, usize)> = roots
+        .iter()
+        .map(|root| (glob::expand_home(&root.path, home), root.max_files.unwrap_or(DUPLICATE_FILE_LIMIT)))
+        .collect();
+    scan_duplicate_files(&roots, cancel, index, hash_mode, filter, read_budget)
+}
+
+/// Scan for duplicate files using chunked hashing (memory-efficient) or, with
+/// `HashMode::Full`, whole-file hashing; hashes of unchanged files come from the file index.
+/// Each root is walked up to its file limit, to prevent excessive processing time;
+/// files reached through more than one root count once.
+fn scan_duplicate_files(
+    roots: &[(PathBuf, usize)],
+    cancel: &CancelFlag,
+    index: &SharedIndex,
+    hash_mode: HashMode,
+    filter: &PathFilter,
+    read_budget: &ReadBudget,
+) -> AnyhowResult<Vec<DuplicateGroup>> {
+    let mut duplicates = Vec::new();
+
+    // Use a hash map to group files by size first, then by content hash
+    let mut size_groups: std::collections::HashMap<u64, Vec<(std::path::PathBuf, std::fs::Metadata)>> = std::collections::HashMap::new();
+    let mut links = allocation::InodeSet::new();
 
     // First pass: group by size
-    let walker = WalkDir::new(&home)
-        .follow_links(false)
-        .into_iter()
-        .filter_map(|e| {
-            match e {
-                Ok(entry) => Some(entry),
-                Err(e) => {
-                    tracing::debug!("WalkDir error (skipping): {}", e);
-                    None
+    for (root, max_files) in roots {
+        let mut files_scanned = 0;
+        let walker = WalkDir::new(root)
+            .follow_links(false)
+            .same_file_system(filter.one_file_system())
+            .into_iter()
+            .filter_entry(|e| !e.file_type().is_dir() || filter.enters(e.path()))
+            .filter_map(|e| {
+                match e {
+                    Ok(entry) => Some(entry),
+                    Err(e) => {
+                        tracing::debug!("WalkDir error (skipping): {}", e);
+                        None
+                    }
                 }
-            }
-        })
-        .filter(|e| e.file_type().is_file())
-        .take(MAX_FILES_TO_SCAN);
+            })
+            .filter(|e| e.file_type().is_file() && filter.allows(e.path()))
+            .take(*max_files);
 
-    for entry in walker {
-        files_scanned += 1;
-        let path = entry.path();
+        for entry in walker {
+            if is_cancelled(cancel) {
+                break;
+            }
+            files_scanned += 1;
+            let path = entry.path();
 
-        match std::fs::metadata(path) {
-            Ok(metadata) => {
-                let size = metadata.len();
-                // Only consider files larger than 1KB to avoid too many small duplicates
-                if size > 1024 {
-                    size_groups.entry(size).or_default().push(path.to_path_buf());
+            match std::fs::metadata(path) {
+                // Further hard links of a file, or a file seen from another root, are the same data, not a copy
+                Ok(metadata) if !links.first(&metadata) => {}
+                Ok(metadata) => {
+                    let size = metadata.len();
+                    // Only consider files larger than 1KB to avoid too many small duplicates
+                    if size > 1024 {
+                        size_groups.entry(size).or_default().push((path.to_path_buf(), metadata));
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!("Failed to get metadata for {}: {}", path.display(), e);
+                    // Continue with other files
                 }
-            }
-            Err(e) => {
-                tracing::debug!("Failed to get metadata for {}: {}", path.display(), e);
-                // Continue with other files
             }
         }
-    }
 
-    if files_scanned >= MAX_FILES_TO_SCAN {
-        tracing::warn!("Duplicate scan limited to {} files to prevent timeout", MAX_FILES_TO_SCAN);
+        if files_scanned >= *max_files {
+            tracing::warn!("Duplicate scan of {} limited to {} files to prevent timeout", root.display(), max_files);
+        }
     }
 
-    // Second pass: check actual duplicates within same-size groups using chunked hashing
+    // Second pass: check actual duplicates within same-size groups by content hash
     for (size, paths) in size_groups {
+        if is_cancelled(cancel) {
+            break;
+        }
         if paths.len() > 1 {
             let mut hash_groups: std::collections::HashMap<String, Vec<std::path::PathBuf>> = std::collections::HashMap::new();
 
-            for path in paths {
-                match compute_file_hash_chunked(&path) {
+            for (path, metadata) in paths {
+                let hashed = index
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .file_hash(&path, &metadata, hash_mode.index_kind(), || match hash_mode {
+                        HashMode::Sampled => {
+                            // First, middle and last 64KB at most
+                            read_budget.take(size.min(3 * 64 * 1024));
+                            compute_file_hash_chunked(&path)
+                        }
+                        HashMode::Full => {
+                            read_budget.take(size);
+                            compute_file_hash_full(&path, cancel)
+                        }
+                    });
+                match hashed {
                     Ok(hash) => {
                         hash_groups.entry(hash).or_default().push(path);
                     }
@@ -1174,6 +1228,7 @@ fn scan_duplicate_files() -> AnyhowResult<Vec<DuplicateGroup>> {
                             name,
                             path: path_str,
                             size,
+                            apparent_size: None,
                             item_type: "file".to_string(),
                             category: "duplicate".to_string(),
                             risk_level: 2, // Medium risk - review recommended
@@ -1190,6 +1245,9 @@ fn scan_duplicate_files() -> AnyhowResult<Vec<DuplicateGroup>> {
                         files,
                         total_size: size * group_size as u64,
                         group_size,
+                        verified: hash_mode == HashMode::Full,
+                        suggested_keeper: None,
+                        preselected: Vec::new(),
                     });
                 }
             }
@@ -1202,18 +1260,17 @@ fn scan_duplicate_files() -> AnyhowResult<Vec<DuplicateGroup>> {
 
 /// Scan for large files (above specified threshold)
 /// Limits scan to prevent excessive processing time
-fn scan_large_files_storage_recovery(min_size: u64) -> AnyhowResult<Vec<ScanItem>> {
+fn scan_large_files_storage_recovery(home: &Path, min_size: u64, cancel: &CancelFlag, filter: &PathFilter) -> AnyhowResult<Vec<ScanItem>> {
     let mut large_files = Vec::new();
 
-    let home = dirs::home_dir()
-        .context("Cannot determine home directory")?;
-
     const MAX_FILES_TO_SCAN: usize = 5000; // Limit to prevent timeout
     let mut files_scanned = 0;
 
-    let walker = WalkDir::new(&home)
+    let walker = WalkDir::new(home)
         .follow_links(false)
+        .same_file_system(filter.one_file_system())
         .into_iter()
+        .filter_entry(|e| !e.file_type().is_dir() || filter.enters(e.path()))
         .filter_map(|e| {
             match e {
                 Ok(entry) => Some(entry),
@@ -1223,16 +1280,19 @@ fn scan_large_files_storage_recovery(min_size: u64) -> AnyhowResult<Vec<ScanItem
                 }
             }
         })
-        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_type().is_file() && filter.allows(e.path()))
         .take(MAX_FILES_TO_SCAN);
 
     for entry in walker {
+        if is_cancelled(cancel) {
+            break;
+        }
         files_scanned += 1;
         let path = entry.path();
 
         match std::fs::metadata(path) {
             Ok(metadata) => {
-                let size = metadata.len();
+                let size = allocation::allocated(&metadata);
                 if size >= min_size {
                     let path_str = path.to_string_lossy().to_string();
                     let name = path.file_name()
@@ -1245,6 +1305,7 @@ fn scan_large_files_storage_recovery(min_size: u64) -> AnyhowResult<Vec<ScanItem
                         name,
                         path: path_str,
                         size,
+                        apparent_size: allocation::apparent_if_different(&metadata),
                         item_type: "file".to_string(),
                         category: "large_file".to_string(),
                         risk_level: 3, // High risk - careful review required
@@ -1266,20 +1327,17 @@ fn scan_large_files_storage_recovery(min_size: u64) -> AnyhowResult<Vec<ScanItem
         tracing::warn!("Large files scan limited to {} files to prevent timeout", MAX_FILES_TO_SCAN);
     }
 
-    // Sort by size descending
-    large_files.sort_by(|a, b| b.size.cmp(&a.size));
+    // Files inside an app's library become one item per library, sorted by size descending
+    let large_files = crate::libraries::group_scan_items(large_files, &mut crate::libraries::LibraryDetector::new(home));
 
     tracing::info!("Found {} large files", large_files.len());
     Ok(large_files)
 }
 
 /// Scan for old downloads (files in Downloads directory older than threshold)
-fn scan_old_downloads(days_threshold: u64) -> AnyhowResult<Vec<ScanItem>> {
+fn scan_old_downloads(home: &Path, days_threshold: u64, cancel: &CancelFlag, filter: &PathFilter) -> AnyhowResult<Vec<ScanItem>> {
     let mut old_downloads = Vec::new();
 
-    let home = dirs::home_dir()
-        .context("Cannot determine home directory")?;
-
     let downloads_dir = home.join("Downloads");
     let threshold_seconds = days_threshold * 24 * 3600;
 
@@ -1290,8 +1348,10 @@ fn scan_old_downloads(days_threshold: u64) -> AnyhowResult<Vec<ScanItem>> {
 
     let walker = WalkDir::new(&downloads_dir)
         .follow_links(false)
+        .same_file_system(filter.one_file_system())
         .max_depth(2) // Don't go too deep
         .into_iter()
+        .filter_entry(|e| !e.file_type().is_dir() || filter.enters(e.path()))
         .filter_map(|e| {
             match e {
                 Ok(entry) => Some(entry),
@@ -1301,9 +1361,12 @@ fn scan_old_downloads(days_threshold: u64) -> AnyhowResult<Vec<ScanItem>> {
                 }
             }
         })
-        .filter(|e| e.file_type().is_file());
+        .filter(|e| e.file_type().is_file() && filter.allows(e.path()));
 
     for entry in walker {
+        if is_cancelled(cancel) {
+            break;
+        }
         let path = entry.path();
 
         match std::fs::metadata(path) {
@@ -1320,7 +1383,7 @@ fn scan_old_downloads(days_threshold: u64) -> AnyhowResult<Vec<ScanItem>> {
 
                         if age_seconds > threshold_seconds {
                             let path_str = path.to_string_lossy().to_string();
-                            let size = metadata.len();
+                            let size = allocation::allocated(&metadata);
                             let name = path.file_name()
                                 .and_then(|n| n.to_str())
                                 .map(|s| s.to_string())
@@ -1331,6 +1394,7 @@ fn scan_old_downloads(days_threshold: u64) -> AnyhowResult<Vec<ScanItem>> {
                                 name,
                                 path: path_str,
                                 size,
+                                apparent_size: allocation::apparent_if_different(&metadata),
                                 item_type: "file".to_string(),
                                 category: "old_download".to_string(),
                                 risk_level: 1, // Low risk - downloads can usually be removed
@@ -1373,3 +1437,208 @@ pub fn format_bytes(bytes: u64) -> String {
 
     format!("{:.2} {}", size, UNITS[unit_index])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::testfs::{cancel_flag, empty_index, SimFs};
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_full_hash_tells_apart_sampled_collisions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Same size, same first/middle/last 64KB, different bytes in between
+        let mut a = vec![0u8; 512 * 1024];
+        let mut b = a.clone();
+        a[100_000] = 1;
+        b[100_000] = 2;
+        let (path_a, path_b) = (dir.path().join("a.bin"), dir.path().join("b.bin"));
+        std::fs::write(&path_a, &a).unwrap();
+        std::fs::write(&path_b, &b).unwrap();
+
+        assert_eq!(compute_file_hash_chunked(&path_a).unwrap(), compute_file_hash_chunked(&path_b).unwrap());
+        let cancel: CancelFlag = Arc::new(AtomicBool::new(false));
+        assert_ne!(compute_file_hash_full(&path_a, &cancel).unwrap(), compute_file_hash_full(&path_b, &cancel).unwrap());
+
+        cancel.store(true, Ordering::Relaxed);
+        assert!(compute_file_hash_full(&path_a, &cancel).is_err());
+    }
+
+    #[test]
+    fn test_hash_mode_serde() {
+        assert_eq!(HashMode::default(), HashMode::Sampled);
+        assert_eq!(serde_json::to_string(&HashMode::Full).unwrap(), "\"full\"");
+    }
+
+    #[test]
+    fn test_cancel_active_scans() {
+        let first = begin_scan("test_first");
+        let second = begin_scan("test_second");
+        assert_eq!(cancel_scans(Some("test_first")), 1);
+        assert!(is_cancelled(first.flag()));
+        assert!(!is_cancelled(second.flag()));
+
+        drop(first);
+        assert_eq!(cancel_scans(Some("test_first")), 0);
+
+        // A replaced scan must not unregister its successor
+        let replaced = begin_scan("test_second");
+        drop(second);
+        assert_eq!(cancel_scans(Some("test_second")), 1);
+        assert!(is_cancelled(replaced.flag()));
+    }
+
+    #[test]
+    fn test_abandon_long_running_scan() {
+        let stuck = begin_scan("test_stuck");
+        let long_running = |threshold| {
+            scans_running_longer_than(threshold).into_iter().filter(|s| s.kind == "test_stuck").collect::<Vec<_>>()
+        };
+        assert!(long_running(Duration::from_secs(3600)).is_empty());
+        assert!(!long_running(Duration::ZERO)[0].cancelled);
+
+        cancel_scans(Some("test_stuck"));
+        assert!(long_running(Duration::ZERO)[0].cancelled);
+        assert!(abandon_scan("test_stuck"));
+        assert!(long_running(Duration::ZERO).is_empty());
+        assert!(!abandon_scan("test_stuck"));
+        drop(stuck);
+    }
+    #[test]
+    fn test_health_scan_on_simulated_tree() {
+        let fs = SimFs::new();
+        fs.dir("empty/nested")
+            .file("notes.txt", 10)
+            .symlink("dangling", "missing.txt")
+            .symlink("working", "notes.txt")
+            .file("draft.swp", 100)
+            .aged("draft.swp", 40)
+            .file("fresh.tmp", 100)
+            .file("tmp/old-report.pdf", 100)
+            .aged("tmp/old-report.pdf", 31)
+            .file("a/b/c/deep.bak", 100)
+            .aged("a/b/c/deep.bak", 40);
+        let cancel = cancel_flag(false);
+
+        assert_eq!(fs.relative(&scan_empty_directories(fs.root(), &cancel, true)), ["empty/nested"]);
+        let broken = scan_broken_symlinks(fs.root(), &cancel, true);
+        assert_eq!(fs.relative(&broken), ["dangling"]);
+        assert_eq!(SimFs::categories(&broken), HashSet::from(["broken_symlink".to_string()]));
+        // Too deep, too new or neither in a temp dir nor named like a temp file
+        let temp = scan_orphaned_temp_files(fs.root(), &cancel, true);
+        assert_eq!(fs.relative(&temp), ["draft.swp", "tmp/old-report.pdf"]);
+        assert!(temp.iter().all(|i| i.category == "orphaned_temp" && i.size > 0));
+
+        assert!(scan_empty_directories(fs.root(), &cancel_flag(true), true).is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_entries_are_skipped() {
+        let fs = SimFs::new();
+        fs.file("open/a.bin", 4096).copy("open/b.bin", "open/a.bin").copy("locked/c.bin", "open/a.bin");
+        fs.dir("locked/inner");
+        if !fs.unreadable("locked") {
+            return; // running as root
+        }
+        let cancel = cancel_flag(false);
+        assert!(scan_empty_directories(fs.root(), &cancel, true).is_empty());
+        let roots = [(fs.root().to_path_buf(), DUPLICATE_FILE_LIMIT)];
+        let groups = scan_duplicate_files(&roots, &cancel, &empty_index(), HashMode::Sampled, &fs.filter(&[]), &ReadBudget::unlimited())
+            .unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(fs.relative(&groups[0].files), ["open/a.bin", "open/b.bin"]);
+    }
+
+    #[test]
+    fn test_duplicates_on_simulated_tree() {
+        let fs = SimFs::new();
+        fs.file("photos/a.jpg", 300 * 1024)
+            .copy("backup/a.jpg", "photos/a.jpg")
+            .copy("backup/old/a copy.jpg", "photos/a.jpg")
+            .file("photos/b.jpg", 300 * 1024) // same size, other content
+            .hard_link("photos/a-link.jpg", "photos/a.jpg")
+            .symlink("photos/a-symlink.jpg", "a.jpg")
+            .file("small/x.txt", 512)
+            .copy("small/y.txt", "small/x.txt")
+            .copy("node_modules/a.jpg", "photos/a.jpg");
+        let cancel = cancel_flag(false);
+        let filter = fs.filter(&["node_modules"]);
+        let roots = [(fs.root().to_path_buf(), DUPLICATE_FILE_LIMIT)];
+
+        for mode in [HashMode::Sampled, HashMode::Full] {
+            let groups = scan_duplicate_files(&roots, &cancel, &empty_index(), mode, &filter, &ReadBudget::unlimited()).unwrap();
+            assert_eq!(groups.len(), 1, "{:?}", mode);
+            let group = &groups[0];
+            // One of the two hard links, never the symlink or the excluded copy
+            let paths = fs.relative(&group.files);
+            assert_eq!(paths.len(), 3);
+            assert!(paths.contains(&"backup/a.jpg".to_string()) && paths.contains(&"backup/old/a copy.jpg".to_string()));
+            assert!(paths.iter().all(|p| p.starts_with("backup/") || p == "photos/a.jpg" || p == "photos/a-link.jpg"));
+            assert_eq!(group.total_size, 3 * 300 * 1024);
+            assert_eq!(group.verified, mode == HashMode::Full);
+            assert_eq!(SimFs::categories(&group.files), HashSet::from(["duplicate".to_string()]));
+        }
+
+        // Every hashed candidate is recorded in the index
+        let index = empty_index();
+        scan_duplicate_files(&roots, &cancel, &index, HashMode::Full, &filter, &ReadBudget::unlimited()).unwrap();
+        assert!(index.lock().unwrap().entry_count() >= 3);
+    }
+
+    #[test]
+    fn test_duplicates_in_chosen_roots() {
+        let fs = SimFs::new();
+        fs.file("Pictures/a.jpg", 64 * 1024)
+            .copy("Pictures/2023/a.jpg", "Pictures/a.jpg")
+            .copy("Documents/a.jpg", "Pictures/a.jpg")
+            .file("Documents/report.pdf", 64 * 1024)
+            .copy("Documents/report (1).pdf", "Documents/report.pdf");
+        let (cancel, index, filter) = (cancel_flag(false), empty_index(), fs.filter(&[]));
+        let scan = |roots: &[DuplicateRoot]| {
+            scan_duplicates_in(roots, fs.root(), &cancel, &index, HashMode::Sampled, &filter, &ReadBudget::unlimited()).unwrap()
+        };
+        let root = |path: &str, max_files: Option<usize>| DuplicateRoot { path: fs.path(path).to_string_lossy().to_string(), max_files };
+
+        let groups = scan(&[root("Pictures", None)]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(fs.relative(&groups[0].files), ["Pictures/2023/a.jpg", "Pictures/a.jpg"]);
+
+        // Nested roots don't turn one file into two copies
+        let groups = scan(&[root("Pictures", None), root("Pictures/2023", None)]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].group_size, 2);
+
+        // A root's limit only applies to that root
+        let groups = scan(&[root("Pictures", Some(1)), root("Documents", None)]);
+        let mut sizes: Vec<usize> = groups.iter().map(|g| g.group_size).collect();
+        sizes.sort();
+        assert_eq!(sizes, [2, 2]);
+        assert!(groups.iter().any(|g| fs.relative(&g.files) == ["Documents/report (1).pdf", "Documents/report.pdf"]));
+    }
+
+    #[test]
+    fn test_large_files_and_old_downloads_on_simulated_tree() {
+        let fs = SimFs::new();
+        fs.file("videos/big.mkv", 2 * 1024 * 1024)
+            .file("videos/small.mkv", 64 * 1024)
+            .symlink("videos/big-link.mkv", "big.mkv")
+            .file("Downloads/setup.iso", 4096)
+            .aged("Downloads/setup.iso", 120)
+            .file("Downloads/recent.pdf", 4096)
+            .aged("Downloads/recent.pdf", 10)
+            .file("Downloads/a/b/buried.zip", 4096)
+            .aged("Downloads/a/b/buried.zip", 120)
+            .file("Downloads/keep/kept.zip", 4096)
+            .aged("Downloads/keep/kept.zip", 120);
+        let cancel = cancel_flag(false);
+        let filter = fs.filter(&["~/Downloads/keep"]);
+
+        let large = scan_large_files_storage_recovery(fs.root(), 1024 * 1024, &cancel, &filter).unwrap();
+        assert_eq!(fs.relative(&large), ["videos/big.mkv"]);
+        assert!(large[0].size >= 2 * 1024 * 1024);
+
+        let old = scan_old_downloads(fs.root(), 90, &cancel, &filter).unwrap();
+        assert_eq!(fs.relative(&old), ["Downloads/setup.iso"]);
+        assert_eq!(old[0].category, "old_download");
+    }
+}
@@ -0,0 +1,377 @@
+//! Sandboxed Lua analyzer plugins (built with the `lua-plugins` feature).
+//!
+//! Every `*.lua` file in ~/.config/pulito/plugins is loaded into its own Lua state with
+//! only the table/string/math/utf8 libraries (no io, os or require), a memory cap and a
+//! per-call time budget. A plugin defines:
+//!
+//! ```lua
+//! roots = { "~/Projects" }            -- optional, defaults to the home directory
+//!
+//! function analyze(file)              -- file.path, name, extension, size, modified, is_dir
+//!   if file.name:match("^tmp_") and file.modified < os_time - 90 * 86400 then
+//!     return { category = "Retention", insight = "Older than 90-day policy", risk_level = 1 }
+//!   end
+//! end
+//! ```
+//!
+//! `os_time` is provided as a global (current unix time) since `os` is unavailable.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+
+use super::registry::{ScanContext, Scanner};
+use super::{ScanItem, ScanOptions, ScannerError};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct AnalyzerPluginInfo {
+    pub name: String,
+    pub path: String,
+    pub loaded: bool,
+    pub error: Option<String>,
+}
+
+/// File metadata handed to plugins
+#[derive(Debug, Clone)]
+pub struct FileMeta {
+    pub path: String,
+    pub name: String,
+    pub extension: String,
+    pub size: u64,
+    pub modified: i64,
+    pub is_dir: bool,
+}
+
+/// What a plugin returns for a file it wants to flag
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginTag {
+    pub category: String,
+    pub insight: String,
+    pub risk_level: u8,
+}
+
+pub fn plugins_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("pulito").join("plugins"))
+}
+
+fn plugin_files() -> Vec<PathBuf> {
+    let Some(dir) = plugins_dir() else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|ext| ext == "lua").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[cfg(feature = "lua-plugins")]
+mod lua_runtime {
+    use mlua::{HookTriggers, Lua, LuaOptions, StdLib, Table, Value, VmState};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::{FileMeta, PluginTag};
+
+    const MEMORY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
+    const CALL_TIME_BUDGET: Duration = Duration::from_millis(50);
+    const LOAD_TIME_BUDGET: Duration = Duration::from_secs(1);
+
+    pub struct LuaPlugin {
+        pub name: String,
+        pub roots: Vec<String>,
+        lua: Lua,
+        deadline: Arc<Mutex<Instant>>,
+    }
+
+    impl LuaPlugin {
+        pub fn load(path: &Path) -> Result<Self, String> {
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+            let lua = Lua::new_with(StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8, LuaOptions::default())
+                .map_err(|e| format!("Failed to create Lua state: {}", e))?;
+            lua.set_memory_limit(MEMORY_LIMIT_BYTES).map_err(|e| e.to_string())?;
+            // The base library always loads; these read and run files from disk
+            for loader in ["dofile", "loadfile", "load"] {
+                lua.globals().set(loader, Value::Nil).map_err(|e| e.to_string())?;
+            }
+
+            // Abort any call that runs past its deadline (infinite loops, heavy work)
+            let deadline = Arc::new(Mutex::new(Instant::now() + LOAD_TIME_BUDGET));
+            let hook_deadline = Arc::clone(&deadline);
+            lua.set_hook(HookTriggers::new().every_nth_instruction(10_000), move |_lua, _debug| {
+                let expired = hook_deadline.lock().map(|d| Instant::now() > *d).unwrap_or(true);
+                if expired {
+                    Err(mlua::Error::RuntimeError("plugin exceeded its time budget".to_string()))
+                } else {
+                    Ok(VmState::Continue)
+                }
+            });
+
+            lua.globals().set("os_time", chrono::Utc::now().timestamp()).map_err(|e| e.to_string())?;
+            lua.load(&source).set_name(name.as_str()).exec().map_err(|e| format!("{}: {}", name, e))?;
+
+            let analyze: Value = lua.globals().get("analyze").map_err(|e| e.to_string())?;
+            if !matches!(analyze, Value::Function(_)) {
+                return Err(format!("{}: missing analyze(file) function", name));
+            }
+
+            let roots = match lua.globals().get::<Value>("roots").map_err(|e| e.to_string())? {
+                Value::Table(table) => table.sequence_values::<String>().filter_map(|r| r.ok()).collect(),
+                _ => Vec::new(),
+            };
+
+            Ok(Self { name, roots, lua, deadline })
+        }
+
+        pub fn analyze(&self, file: &FileMeta) -> Result<Option<PluginTag>, String> {
+            if let Ok(mut deadline) = self.deadline.lock() {
+                *deadline = Instant::now() + CALL_TIME_BUDGET;
+            }
+
+            let table = self.lua.create_table().map_err(|e| e.to_string())?;
+            table.set("path", file.path.as_str()).map_err(|e| e.to_string())?;
+            table.set("name", file.name.as_str()).map_err(|e| e.to_string())?;
+            table.set("extension", file.extension.as_str()).map_err(|e| e.to_string())?;
+            table.set("size", file.size).map_err(|e| e.to_string())?;
+            table.set("modified", file.modified).map_err(|e| e.to_string())?;
+            table.set("is_dir", file.is_dir).map_err(|e| e.to_string())?;
+
+            let analyze: mlua::Function = self.lua.globals().get("analyze").map_err(|e| e.to_string())?;
+            match analyze.call::<Value>(table).map_err(|e| format!("{}: {}", self.name, e))? {
+                Value::Table(result) => Ok(Some(Self::parse_tag(&self.name, &result))),
+                _ => Ok(None),
+            }
+        }
+
+        fn parse_tag(plugin: &str, result: &Table) -> PluginTag {
+            PluginTag {
+                category: result.get::<String>("category").unwrap_or_else(|_| plugin.to_string()),
+                insight: result.get::<String>("insight").unwrap_or_default(),
+                risk_level: result.get::<u8>("risk_level").unwrap_or(1).min(3),
+            }
+        }
+    }
+}
+
+/// Runs analyzer plugins over file metadata and reports the files they tag
+pub struct PluginAnalyzerScanner;
+
+impl Scanner for PluginAnalyzerScanner {
+    fn id(&self) -> &'static str {
+        "plugins"
+    }
+
+    fn name(&self) -> &'static str {
+        "Analyzer Plugins"
+    }
+
+    fn description(&self) -> &'static str {
+        "Files tagged by Lua plugins in ~/.config/pulito/plugins"
+    }
+
+    fn enabled_by_default(&self, _options: &ScanOptions) -> bool {
+        cfg!(feature = "lua-plugins") && !plugin_files().is_empty()
+    }
+
+    #[cfg(not(feature = "lua-plugins"))]
+    fn run(&self, _ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        Err(ScannerError::PathValidationError("Pulito was built without plugin support".to_string()))
+    }
+
+    #[cfg(feature = "lua-plugins")]
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        use super::builtin::{file_name_or_path, home_dir, new_item};
+        use super::glob::expand_home;
+        use std::collections::HashSet;
+        use walkdir::WalkDir;
+
+        let home = home_dir()?;
+        let mut plugins = Vec::new();
+        for path in plugin_files() {
+            match lua_runtime::LuaPlugin::load(&path) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => tracing::warn!("Skipping analyzer plugin: {}", e),
+            }
+        }
+
+        // Walk the union of all plugin roots once, only inside the home directory
+        let mut roots: Vec<PathBuf> = plugins
+            .iter()
+            .flat_map(|p| if p.roots.is_empty() { vec!["~".to_string()] } else { p.roots.clone() })
+            .map(|r| expand_home(&r, &home))
+            .filter(|r| r.starts_with(&home) && !r.components().any(|c| c == std::path::Component::ParentDir))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        roots.sort();
+        // Drop roots nested inside another root
+        let roots: Vec<PathBuf> = roots.iter().filter(|r| !roots.iter().any(|o| o != *r && r.starts_with(o))).cloned().collect();
+
+        let mut disabled: HashSet<String> = HashSet::new();
+        let mut items = Vec::new();
+        let mut visited = 0usize;
+
+        for root in &roots {
+            for entry in WalkDir::new(root).max_depth(ctx.limits.max_depth).follow_links(false).same_file_system(ctx.one_file_system()).into_iter().filter_map(|e| e.ok()) {
+                ctx.check_cancelled()?;
+                visited += 1;
+                if visited > ctx.limits.max_files {
+                    break;
+                }
+                if visited % 1000 == 0 {
+                    ctx.progress(((visited * 100) / ctx.limits.max_files.max(1)) as u8, &format!("Analyzed {} files...", visited));
+                }
+
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let path = entry.path();
+                let file = FileMeta {
+                    path: path.to_string_lossy().to_string(),
+                    name: file_name_or_path(path),
+                    extension: path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default(),
+                    size: metadata.len(),
+                    modified: metadata
+                        .modified()
+                        .ok()
+                        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0),
+                    is_dir: metadata.is_dir(),
+                };
+
+                for plugin in &plugins {
+                    if disabled.contains(&plugin.name) {
+                        continue;
+                    }
+                    let in_roots = plugin.roots.is_empty()
+                        || plugin.roots.iter().any(|r| path.starts_with(expand_home(r, &home)));
+                    if !in_roots {
+                        continue;
+                    }
+
+                    match plugin.analyze(&file) {
+                        Ok(Some(tag)) => {
+                            let size = if file.is_dir { ctx.dir_size(path) } else { file.size };
+                            let description = if tag.insight.is_empty() {
+                                format!("Flagged by plugin {}", plugin.name)
+                            } else {
+                                format!("{} ({})", tag.insight, plugin.name)
+                            };
+                            let item_type = if file.is_dir { "directory" } else { "file" };
+                            items.push(new_item(file.name.clone(), path, size, item_type, &tag.category, tag.risk_level, &description));
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            // One faulty plugin shouldn't spam the log or slow the whole walk
+                            tracing::warn!("Disabling analyzer plugin for this scan: {}", e);
+                            disabled.insert(plugin.name.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(items)
+    }
+}
+
+/// List installed analyzer plugins and whether they load
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_analyzer_plugins() -> Result<Vec<AnalyzerPluginInfo>, String> {
+    let infos = tokio::task::spawn_blocking(|| {
+        plugin_files()
+            .into_iter()
+            .map(|path| {
+                #[cfg(feature = "lua-plugins")]
+                let error = lua_runtime::LuaPlugin::load(&path).err();
+                #[cfg(not(feature = "lua-plugins"))]
+                let error = Some("Pulito was built without plugin support".to_string());
+
+                AnalyzerPluginInfo {
+                    name: path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                    path: path.to_string_lossy().to_string(),
+                    loaded: error.is_none(),
+                    error,
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+
+    Ok(infos)
+}
+
+#[cfg(all(test, feature = "lua-plugins"))]
+mod tests {
+    use super::*;
+
+    fn meta(name: &str, size: u64) -> FileMeta {
+        FileMeta {
+            path: format!("/home/u/Projects/{}", name),
+            name: name.to_string(),
+            extension: "bin".to_string(),
+            size,
+            modified: 0,
+            is_dir: false,
+        }
+    }
+
+    #[test]
+    fn test_plugin_tags_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("retention.lua");
+        std::fs::write(
+            &path,
+            r#"
+            roots = { "~/Projects" }
+            function analyze(file)
+              if file.name:match("^tmp_") then
+                return { category = "Retention", insight = "Temporary build output", risk_level = 0 }
+              end
+            end
+            "#,
+        )
+        .unwrap();
+
+        let plugin = lua_runtime::LuaPlugin::load(&path).unwrap();
+        assert_eq!(plugin.roots, vec!["~/Projects".to_string()]);
+        assert_eq!(
+            plugin.analyze(&meta("tmp_output.bin", 10)).unwrap(),
+            Some(PluginTag { category: "Retention".into(), insight: "Temporary build output".into(), risk_level: 0 })
+        );
+        assert_eq!(plugin.analyze(&meta("keep.bin", 10)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_plugin_sandbox() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let io_plugin = dir.path().join("io.lua");
+        std::fs::write(&io_plugin, "io.open('/etc/passwd')\nfunction analyze(f) end").unwrap();
+        assert!(lua_runtime::LuaPlugin::load(&io_plugin).is_err());
+
+        let loader_plugin = dir.path().join("loader.lua");
+        std::fs::write(&loader_plugin, "loadfile('/etc/passwd')\nfunction analyze(f) end").unwrap();
+        assert!(lua_runtime::LuaPlugin::load(&loader_plugin).is_err());
+        std::fs::write(&loader_plugin, "dofile('/etc/passwd')\nfunction analyze(f) end").unwrap();
+        assert!(lua_runtime::LuaPlugin::load(&loader_plugin).is_err());
+
+        let looping = dir.path().join("loop.lua");
+        std::fs::write(&looping, "function analyze(f) while true do end end").unwrap();
+        let plugin = lua_runtime::LuaPlugin::load(&looping).unwrap();
+        assert!(plugin.analyze(&meta("x", 1)).is_err());
+    }
+}
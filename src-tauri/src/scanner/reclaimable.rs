@@ -0,0 +1,129 @@
+//! One figure for the space a cleanup could free. The cache folder, the last
+//! filesystem health check and the last storage recovery scan each report a
+//! total, but they look at overlapping parts of the disk: a big wheel in
+//! `~/.cache/pip` is in the cache total and among the large files. Sources are
+//! added in order and every path is counted once, by the first source that
+//! covers it; what later sources would have added again is their overlap.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{FilesystemHealthResults, ScanItem, StorageRecoveryResults};
+
+/// What one source adds to the reclaimable total
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ReclaimableSource {
+    pub source: String,
+    /// Bytes counted for this source
+    pub size: u64,
+    /// Bytes this source found that an earlier one already counted
+    pub overlap: u64,
+}
+
+/// Running total over the sources added so far
+#[derive(Debug, Default)]
+pub struct Reclaimable {
+    /// Paths counted so far, with all the bytes they hold
+    claimed: BTreeMap<PathBuf, u64>,
+    sources: Vec<ReclaimableSource>,
+}
+
+impl Reclaimable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn source(&mut self, source: &str) -> usize {
+        match self.sources.iter().position(|s| s.source == source) {
+            Some(index) => index,
+            None => {
+                self.sources.push(ReclaimableSource { source: source.to_string(), size: 0, overlap: 0 });
+                self.sources.len() - 1
+            }
+        }
+    }
+
+    /// Count `path` for `source` unless it is, or is inside, a path already
+    /// counted. Paths counted earlier inside `path` move to the overlap.
+    pub fn add(&mut self, source: &str, path: &Path, size: u64) {
+        let index = self.source(source);
+        if path.ancestors().any(|p| self.claimed.contains_key(p)) {
+            self.sources[index].overlap += size;
+            return;
+        }
+        // Path ordering is by component, so everything inside `path` follows it directly
+        let inside: Vec<PathBuf> = self.claimed.range(path.to_path_buf()..).map(|(p, _)| p).take_while(|p| p.starts_with(path)).cloned().collect();
+        let mut counted = size;
+        for p in inside {
+            if let Some(bytes) = self.claimed.remove(&p) {
+                counted = counted.saturating_sub(bytes);
+                self.sources[index].overlap += bytes;
+            }
+        }
+        self.sources[index].size += counted;
+        self.claimed.insert(path.to_path_buf(), size);
+    }
+
+    /// Space with no path of its own, such as orphaned packages, which nothing else counts
+    pub fn add_unpathed(&mut self, source: &str, size: u64) {
+        let index = self.source(source);
+        self.sources[index].size += size;
+    }
+
+    pub fn add_items<'a>(&mut self, source: &str, items: impl IntoIterator<Item = &'a ScanItem>) {
+        for item in items {
+            self.add(source, Path::new(&item.path), item.size);
+        }
+    }
+
+    pub fn add_filesystem_health(&mut self, results: &FilesystemHealthResults) {
+        self.add_items("Filesystem health", results.empty_directories.iter().chain(&results.broken_symlinks).chain(&results.orphaned_temp_files));
+    }
+
+    pub fn add_storage_recovery(&mut self, results: &StorageRecoveryResults) {
+        // One copy of each duplicate stays
+        self.add_items("Duplicates", results.duplicates.iter().flat_map(|g| g.files.iter().skip(1)));
+        self.add_items("Large files", &results.large_files);
+        self.add_items("Old downloads", &results.old_downloads);
+        self.add_items("Snap revisions", &results.old_snap_revisions);
+        self.add_items("Filesystem snapshots", &results.stale_snapshots);
+    }
+
+    pub fn total(&self) -> u64 {
+        self.sources.iter().map(|s| s.size).sum()
+    }
+
+    /// Sources in the order they were added, empty ones left out
+    pub fn breakdown(self) -> Vec<ReclaimableSource> {
+        self.sources.into_iter().filter(|s| s.size > 0 || s.overlap > 0).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_counted_once() {
+        let mut space = Reclaimable::new();
+        space.add("Large files", Path::new("/home/u/.cache/pip/wheels/torch.whl"), 800);
+        space.add("Cache", Path::new("/home/u/.cache"), 1000);
+        space.add("Old downloads", Path::new("/home/u/.cache/pip/http/x"), 50);
+        space.add("Old downloads", Path::new("/home/u/.cachefile"), 10);
+        space.add_unpathed("Orphaned packages", 300);
+
+        assert_eq!(space.total(), 800 + 200 + 10 + 300);
+        assert_eq!(
+            space.breakdown(),
+            vec![
+                ReclaimableSource { source: "Large files".to_string(), size: 800, overlap: 0 },
+                ReclaimableSource { source: "Cache".to_string(), size: 200, overlap: 800 },
+                ReclaimableSource { source: "Old downloads".to_string(), size: 10, overlap: 50 },
+                ReclaimableSource { source: "Orphaned packages".to_string(), size: 300, overlap: 0 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Pluggable scanner architecture.
+//!
+//! Each scan category implements [`Scanner`] and is registered in a central registry
+//! that `scan_system_async` iterates. Scanners run synchronously on a blocking thread
+//! and report progress and observe cancellation through [`ScanContext`].
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use super::glob::PathFilter;
+use super::index::SharedIndex;
+use super::{ScanItem, ScanLimits, ScanOptions, ScannerError};
+
+/// Shared flag a running scan polls to stop early
+pub type CancelFlag = Arc<AtomicBool>;
+
+/// Progress callback: (percent of this scanner's work 0-100, message)
+pub type ProgressFn = dyn Fn(u8, &str) + Send + Sync;
+
+/// Describes one configurable option a scanner accepts via `ScanOptions::scanner_options`
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ScannerOptionSchema {
+    pub key: String,
+    pub label: String,
+    pub value_type: String, // "number", "bool", "string"
+    pub default_value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ScannerInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub options: Vec<ScannerOptionSchema>,
+}
+
+/// Everything a scanner needs while running
+pub struct ScanContext<'a> {
+    pub limits: &'a ScanLimits,
+    pub options: HashMap<String, String>,
+    progress: &'a ProgressFn,
+    cancel: &'a CancelFlag,
+    index: Option<&'a SharedIndex>,
+    mount_point: Option<&'a str>,
+    filter: Option<&'a PathFilter>,
+    operations: AtomicU64, // counted by `pace`
+}
+
+impl<'a> ScanContext<'a> {
+    pub fn new(limits: &'a ScanLimits, options: HashMap<String, String>, progress: &'a ProgressFn, cancel: &'a CancelFlag) -> Self {
+        Self { limits, options, progress, cancel, index: None, mount_point: None, filter: None, operations: AtomicU64::new(0) }
+    }
+
+    /// Use the persistent file index so unchanged directories aren't re-examined
+    pub fn with_index(mut self, index: &'a SharedIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Restrict the scan to one mounted volume; items elsewhere are dropped afterwards
+    pub fn with_mount(mut self, mount_point: &'a str) -> Self {
+        self.mount_point = Some(mount_point);
+        self
+    }
+
+    /// The volume the scan is limited to, if any
+    pub fn mount_point(&self) -> Option<&str> {
+        self.mount_point
+    }
+
+    /// Apply the scan's include/exclude globs
+    pub fn with_filter(mut self, filter: &'a PathFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Whether an item at `path` passes the include/exclude globs; others are dropped after the scanner returns
+    pub fn allows(&self, path: &Path) -> bool {
+        self.filter.map_or(true, |f| f.allows(path))
+    }
+
+    /// Whether a directory walk should descend into `dir`
+    pub fn enters(&self, dir: &Path) -> bool {
+        self.filter.map_or(true, |f| f.enters(dir))
+    }
+
+    /// Whether directory walks stay on the filesystem they start on
+    pub fn one_file_system(&self) -> bool {
+        self.filter.map_or(true, |f| f.one_file_system())
+    }
+
+    /// Total size of a directory tree, through the file index when the scan has one
+    pub fn dir_size(&self, path: &Path) -> u64 {
+        self.pace();
+        match self.index {
+            Some(index) => index.lock().unwrap_or_else(|e| e.into_inner()).dir_size(path),
+            None => crate::trash::get_dir_size(path),
+        }
+    }
+
+    /// Sleep for `limits.pause` every `limits.pause_every` calls, as the scan's
+    /// throughput profile asks; call once per file or directory in walks
+    pub fn pace(&self) {
+        if self.limits.pause_every == 0 {
+            return;
+        }
+        let done = self.operations.fetch_add(1, Ordering::Relaxed) + 1;
+        if done % self.limits.pause_every == 0 {
+            std::thread::sleep(self.limits.pause);
+        }
+    }
+
+    pub fn progress(&self, percent: u8, message: &str) {
+        (self.progress)(percent.min(100), message);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Return `Err(Cancelled)` once the scan has been cancelled; call this inside loops
+    pub fn check_cancelled(&self) -> Result<(), ScannerError> {
+        if self.is_cancelled() {
+            Err(ScannerError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Parse a scanner option, falling back to `default` when unset or invalid
+    pub fn option<T: std::str::FromStr>(&self, key: &str, default: T) -> T {
+        self.options.get(key).and_then(|v| v.parse().ok()).unwrap_or(default)
+    }
+}
+
+pub trait Scanner: Send + Sync {
+    /// Stable identifier used in ScanOptions, progress events and failed_categories
+    fn id(&self) -> &'static str;
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        Vec::new()
+    }
+
+    /// Whether this scanner runs when the caller didn't pick categories explicitly
+    fn enabled_by_default(&self, _options: &ScanOptions) -> bool {
+        false
+    }
+
+    /// Upper bound for a single run; the scan records a failed category when exceeded
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError>;
+
+    fn info(&self) -> ScannerInfo {
+        ScannerInfo {
+            id: self.id().to_string(),
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            options: self.options_schema(),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: RwLock<Vec<Arc<dyn Scanner>>> = RwLock::new(super::builtin::builtin_scanners());
+}
+
+/// Add a scanner to the registry. A scanner with the same id replaces the existing one.
+#[allow(dead_code)] // Extension point for additional/plugin scanners
+pub fn register(scanner: Arc<dyn Scanner>) {
+    if let Ok(mut registry) = REGISTRY.write() {
+        registry.retain(|s| s.id() != scanner.id());
+        registry.push(scanner);
+    }
+}
+
+/// All registered scanners in registration order
+pub fn scanners() -> Vec<Arc<dyn Scanner>> {
+    REGISTRY.read().map(|r| r.clone()).unwrap_or_default()
+}
+
+/// Scanners selected by `options`: the explicit `categories` list if given,
+/// otherwise every scanner enabled by default
+pub fn selected_scanners(options: &ScanOptions) -> Vec<Arc<dyn Scanner>> {
+    scanners()
+        .into_iter()
+        .filter(|s| match &options.categories {
+            Some(categories) => categories.iter().any(|c| c == s.id()),
+            None => s.enabled_by_default(options),
+        })
+        .collect()
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_scanners() -> Result<Vec<ScannerInfo>, String> {
+    Ok(scanners().iter().map(|s| s.info()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{IoPriority, ReadBudget};
+
+    #[test]
+    fn test_selected_scanners_follow_options() {
+        // Scanners for external tools and project folders are only on by default where those exist
+        let options = ScanOptions::default();
+        let conditional = [
+            "docker", "flatpak", "snap", "journal", "old_kernels", "node_modules", "cargo_target", "python_venv", "pycache", "conda_envs",
+            "stale_thumbnails", "browser_profiles", "old_installers", "wine_prefixes", "electron_caches", "system_trash",
+        ];
+        let ids: Vec<_> = selected_scanners(&options).iter().map(|s| s.id()).filter(|id| !conditional.contains(id)).collect();
+        assert_eq!(ids, vec!["caches", "packages", "logs", "large_files", "coredumps"]);
+
+        let options = ScanOptions { include_logs: false, include_packages: false, ..ScanOptions::default() };
+        let ids: Vec<_> = selected_scanners(&options).iter().map(|s| s.id()).filter(|id| !conditional.contains(id)).collect();
+        assert_eq!(ids, vec!["caches", "large_files"]);
+
+        let options = ScanOptions { categories: Some(vec!["logs".to_string()]), ..ScanOptions::default() };
+        let ids: Vec<_> = selected_scanners(&options).iter().map(|s| s.id()).collect();
+        assert_eq!(ids, vec!["logs"]);
+    }
+
+    #[test]
+    fn test_context_cancellation_and_options() {
+        let limits = ScanLimits {
+            max_files: 10,
+            max_depth: 2,
+            max_memory_mb: 100,
+            timeout_seconds: 10,
+            threads: 1,
+            pause_every: 0,
+            pause: Duration::ZERO,
+            io_priority: IoPriority::Normal,
+            read_budget: Arc::new(ReadBudget::unlimited()),
+        };
+        let cancel: CancelFlag = Arc::new(AtomicBool::new(false));
+        let progress = |_: u8, _: &str| {};
+        let mut options = HashMap::new();
+        options.insert("min_size_mb".to_string(), "250".to_string());
+        let ctx = ScanContext::new(&limits, options, &progress, &cancel);
+
+        assert_eq!(ctx.option("min_size_mb", 100u64), 250);
+        assert_eq!(ctx.option("missing", 7u64), 7);
+        assert!(ctx.check_cancelled().is_ok());
+        cancel.store(true, Ordering::Relaxed);
+        assert!(matches!(ctx.check_cancelled(), Err(ScannerError::Cancelled)));
+    }
+}
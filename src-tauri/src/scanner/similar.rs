@@ -0,0 +1,222 @@
+//! Visually near-identical photos: the same shot saved at another size or
+//! quality, a burst of almost the same frame, an edited copy next to the
+//! original. Content hashes only find byte-identical files, so each image gets
+//! a difference hash (dHash) from a 9x8 grayscale thumbnail instead, and
+//! images whose hashes differ in a few bits are grouped. Decoding every photo
+//! is CPU-heavy, so this only runs when a scan asks for it.
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::builtin::new_item;
+use super::{is_cancelled, CancelFlag, DuplicateGroup, PathFilter, ReadBudget, ScanItem, SharedIndex};
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+/// Icons and thumbnails are below this
+const MIN_IMAGE_SIZE: u64 = 20 * 1024;
+const MAX_IMAGES: usize = 5000;
+/// Hashes differing in at most this many of their 64 bits show the same picture
+pub const MAX_DISTANCE: u32 = 10;
+/// File index kind the hashes are cached under
+const INDEX_KIND: &str = "dhash";
+
+/// Photos that look the same; the first file is the largest and kept by default
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SimilarImageGroup {
+    pub id: String,
+    pub files: Vec<ScanItem>,
+    /// Lowest similarity between two files of the group, 0 to 1
+    pub similarity: f32,
+    /// Size of all files but the first
+    pub recoverable_size: u64,
+}
+
+/// Difference hash: one bit per neighbouring pixel pair of a 9x8 grayscale
+/// thumbnail, set where brightness rises to the right
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image.thumbnail_exact(9, 8).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y).0[0] < small.get_pixel(x + 1, y).0[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+pub fn similarity(a: u64, b: u64) -> f32 {
+    1.0 - (a ^ b).count_ones() as f32 / 64.0
+}
+
+/// Groups of indices into `hashes` that are within `MAX_DISTANCE` of another
+/// member, largest group first
+pub fn group_hashes(hashes: &[u64]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..hashes.len() {
+        for j in i + 1..hashes.len() {
+            if (hashes[i] ^ hashes[j]).count_ones() <= MAX_DISTANCE {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of = std::collections::HashMap::new();
+    for i in 0..hashes.len() {
+        let r = root(&mut parent, i);
+        let slot = *group_of.entry(r).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[slot].push(i);
+    }
+    groups.retain(|g| g.len() > 1);
+    groups.sort_by(|a, b| b.len().cmp(&a.len()));
+    groups
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Group similar photos in the home directory. Extra copies of exact
+/// duplicates are left out; they are reported as duplicates already.
+pub fn scan_similar_images(
+    cancel: &CancelFlag,
+    index: &SharedIndex,
+    filter: &PathFilter,
+    duplicates: &[DuplicateGroup],
+    read_budget: &ReadBudget,
+) -> Vec<SimilarImageGroup> {
+    let Some(home) = dirs::home_dir() else { return Vec::new() };
+    let extra_copies: HashSet<PathBuf> =
+        duplicates.iter().flat_map(|g| g.files.iter().skip(1)).map(|f| PathBuf::from(&f.path)).collect();
+
+    let mut images: Vec<(PathBuf, u64, u64)> = Vec::new();
+    let walker = WalkDir::new(&home)
+        .follow_links(false)
+        .same_file_system(filter.one_file_system())
+        .into_iter()
+        // Hidden folders hold caches and thumbnails, not photos
+        .filter_entry(|e| e.depth() == 0 || !e.file_type().is_dir() || (!e.file_name().to_string_lossy().starts_with('.') && filter.enters(e.path())))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_image(e.path()) && filter.allows(e.path()) && !extra_copies.contains(e.path()));
+    for entry in walker {
+        if is_cancelled(cancel) || images.len() >= MAX_IMAGES {
+            break;
+        }
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.len() < MIN_IMAGE_SIZE {
+            continue;
+        }
+        let hashed = index.lock().unwrap_or_else(|e| e.into_inner()).file_hash(entry.path(), &metadata, INDEX_KIND, || {
+            read_budget.take(metadata.len());
+            image::open(entry.path()).map(|image| format!("{:016x}", dhash(&image)))
+        });
+        match hashed.map(|h| u64::from_str_radix(&h, 16)) {
+            Ok(Ok(hash)) => images.push((entry.path().to_path_buf(), metadata.len(), hash)),
+            Ok(Err(_)) => {}
+            Err(e) => tracing::debug!("Cannot decode {}: {}", entry.path().display(), e),
+        }
+    }
+    if images.len() >= MAX_IMAGES {
+        tracing::warn!("Similar image scan limited to {} images", MAX_IMAGES);
+    }
+
+    let hashes: Vec<u64> = images.iter().map(|(_, _, hash)| *hash).collect();
+    group_hashes(&hashes)
+        .into_iter()
+        .enumerate()
+        .map(|(group_index, mut members)| {
+            // The largest file is usually the best quality
+            members.sort_by(|a, b| images[*b].1.cmp(&images[*a].1));
+            let keep = members[0];
+            let mut lowest: f32 = 1.0;
+            for (n, a) in members.iter().enumerate() {
+                for b in &members[n + 1..] {
+                    lowest = lowest.min(similarity(images[*a].2, images[*b].2));
+                }
+            }
+            let keep_name = images[keep].0.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let files: Vec<ScanItem> = members
+                .iter()
+                .enumerate()
+                .map(|(n, &i)| {
+                    let (path, size, hash) = &images[i];
+                    let description = if n == 0 {
+                        "Largest of the similar images".to_string()
+                    } else {
+                        format!("{:.0}% similar to {}", similarity(*hash, images[keep].2) * 100.0, keep_name)
+                    };
+                    let mut item = new_item(
+                        path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+                        path,
+                        *size,
+                        "file",
+                        "similar_image",
+                        2, // Not identical, so always for review
+                        &description,
+                    );
+                    item.id = format!("similar_{}_{}", group_index, n);
+                    item
+                })
+                .collect();
+            SimilarImageGroup {
+                id: format!("similar_group_{}", group_index),
+                recoverable_size: files.iter().skip(1).map(|f| f.size).sum(),
+                files,
+                similarity: lowest,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    /// Smooth waves, brightness `offset` higher all over
+    fn photo(width: u32, height: u32, offset: u8) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+            let fx = x as f32 / width as f32 * std::f32::consts::TAU * 1.5;
+            let fy = y as f32 / height as f32 * std::f32::consts::PI;
+            Luma([(110.0 + 60.0 * fx.sin() + 40.0 * fy.cos()) as u8 + offset])
+        }))
+    }
+
+    #[test]
+    fn test_dhash_survives_resize_and_brightness() {
+        let original = dhash(&photo(400, 300, 0));
+        let smaller = dhash(&photo(400, 300, 0).resize_exact(200, 150, image::imageops::FilterType::Triangle));
+        let brighter = dhash(&photo(400, 300, 20));
+        let other = dhash(&DynamicImage::ImageLuma8(GrayImage::from_fn(400, 300, |x, _| Luma([255 - (x / 2) as u8]))));
+
+        assert!((original ^ smaller).count_ones() <= MAX_DISTANCE);
+        assert!((original ^ brighter).count_ones() <= MAX_DISTANCE);
+        assert!((original ^ other).count_ones() > MAX_DISTANCE);
+    }
+
+    #[test]
+    fn test_group_hashes() {
+        let base = 0xF0F0_F0F0_F0F0_F0F0u64;
+        let hashes = [base, !base, base ^ 0b111, base ^ 0b111_0000_0000, !base ^ 1, 0x1234_5678_9ABC_DEF0];
+        assert_eq!(group_hashes(&hashes), vec![vec![0, 2, 3], vec![1, 4]]);
+        assert!((similarity(base, base ^ 0b111) - 61.0 / 64.0).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,75 @@
+//! Directory sizes for dashboard stats. Each stat is measured under a key; a new
+//! request for the same key cancels the walk still running for the previous one,
+//! and a walk that runs out of time is cancelled instead of left behind on a
+//! blocking thread.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::CancelFlag;
+
+lazy_static::lazy_static! {
+    /// Cancel flag of the latest measurement per stat key
+    static ref MEASUREMENTS: Mutex<HashMap<&'static str, CancelFlag>> = Mutex::new(HashMap::new());
+}
+
+/// Register a measurement of `key`, cancelling the one it supersedes
+fn begin(key: &'static str) -> CancelFlag {
+    let flag: CancelFlag = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = MEASUREMENTS.lock().unwrap_or_else(|e| e.into_inner()).insert(key, Arc::clone(&flag)) {
+        previous.store(true, Ordering::Relaxed);
+    }
+    flag
+}
+
+fn finish(key: &'static str, flag: &CancelFlag) {
+    let mut measurements = MEASUREMENTS.lock().unwrap_or_else(|e| e.into_inner());
+    if measurements.get(key).is_some_and(|f| Arc::ptr_eq(f, flag)) {
+        measurements.remove(key);
+    }
+}
+
+/// Size of the directory tree at `path`, or `None` when it took longer than
+/// `limit` or a newer measurement of `key` superseded it
+pub async fn measure(key: &'static str, path: PathBuf, limit: Duration) -> Option<u64> {
+    let flag = begin(key);
+    let walk_flag = Arc::clone(&flag);
+    let walk = tokio::task::spawn_blocking(move || crate::trash::get_dir_size_cancellable(&path, &walk_flag));
+    let size = match tokio::time::timeout(limit, walk).await {
+        Ok(Ok(size)) => size,
+        Ok(Err(e)) => {
+            tracing::warn!("Measuring {} failed: {}", key, e);
+            None
+        }
+        Err(_) => {
+            tracing::warn!("Measuring {} timed out after {} seconds", key, limit.as_secs());
+            flag.store(true, Ordering::Relaxed);
+            None
+        }
+    };
+    finish(key, &flag);
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_newer_measurement_supersedes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a"), vec![0u8; 100]).unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/b"), vec![0u8; 50]).unwrap();
+
+        let older = begin("test_stat");
+        let expected = crate::trash::get_dir_size(dir.path());
+        assert_eq!(measure("test_stat", dir.path().to_path_buf(), Duration::from_secs(10)).await, Some(expected));
+        assert!(older.load(Ordering::Relaxed));
+        assert_eq!(crate::trash::get_dir_size_cancellable(dir.path(), &older), None);
+        assert!(!MEASUREMENTS.lock().unwrap().contains_key("test_stat"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Synthetic directory trees for scanner tests. A `SimFs` is a temporary
+//! directory that tests fill with files of given sizes and ages, copies,
+//! hard and symbolic links and unreadable entries, then walk as if it were
+//! home. Paths are relative to its root throughout.
+
+use std::collections::HashSet;
+use std::fs::{self, File, FileTimes};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use super::{CancelFlag, FileIndex, PathFilter, ScanItem, SharedIndex};
+
+const DAY: Duration = Duration::from_secs(24 * 3600);
+
+pub struct SimFs {
+    dir: tempfile::TempDir,
+    locked: Mutex<Vec<PathBuf>>,
+}
+
+impl SimFs {
+    pub fn new() -> Self {
+        Self { dir: tempfile::TempDir::new().unwrap(), locked: Mutex::new(Vec::new()) }
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn path(&self, rel: &str) -> PathBuf {
+        self.root().join(rel)
+    }
+
+    fn parent_of(&self, rel: &str) -> PathBuf {
+        let path = self.path(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        path
+    }
+
+    /// A file of `size` bytes whose content depends on `rel`, so files of the
+    /// same size only match when copied with `copy`
+    pub fn file(&self, rel: &str, size: usize) -> &Self {
+        let seed = rel.as_bytes();
+        let content: Vec<u8> = (0..size).map(|i| seed[i % seed.len()].wrapping_add((i / seed.len()) as u8)).collect();
+        self.content(rel, &content)
+    }
+
+    pub fn content(&self, rel: &str, content: &[u8]) -> &Self {
+        fs::write(self.parent_of(rel), content).unwrap();
+        self
+    }
+
+    /// A byte-for-byte copy of `of`, as a separate file
+    pub fn copy(&self, rel: &str, of: &str) -> &Self {
+        fs::copy(self.path(of), self.parent_of(rel)).unwrap();
+        self
+    }
+
+    pub fn hard_link(&self, rel: &str, of: &str) -> &Self {
+        fs::hard_link(self.path(of), self.parent_of(rel)).unwrap();
+        self
+    }
+
+    /// `target` is taken as is, so relative targets resolve from the link's directory
+    pub fn symlink(&self, rel: &str, target: &str) -> &Self {
+        std::os::unix::fs::symlink(target, self.parent_of(rel)).unwrap();
+        self
+    }
+
+    pub fn dir(&self, rel: &str) -> &Self {
+        fs::create_dir_all(self.path(rel)).unwrap();
+        self
+    }
+
+    /// Set the modification time of an existing file or directory `days` back
+    pub fn aged(&self, rel: &str, days: u64) -> &Self {
+        let modified = SystemTime::now() - DAY * days as u32;
+        File::open(self.path(rel)).unwrap().set_times(FileTimes::new().set_modified(modified)).unwrap();
+        self
+    }
+
+    /// Take away all permissions from `rel`. False when they aren't enforced
+    /// (tests running as root), in which case the entry is still readable.
+    pub fn unreadable(&self, rel: &str) -> bool {
+        let path = self.path(rel);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).unwrap();
+        self.locked.lock().unwrap().push(path.clone());
+        if path.is_dir() {
+            fs::read_dir(&path).is_err()
+        } else {
+            File::open(&path).is_err()
+        }
+    }
+
+    /// Paths of `items` relative to the root, sorted
+    pub fn relative<'a>(&self, items: impl IntoIterator<Item = &'a ScanItem>) -> Vec<String> {
+        let mut paths: Vec<String> = items
+            .into_iter()
+            .map(|item| Path::new(&item.path).strip_prefix(self.root()).unwrap().to_string_lossy().to_string())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Categories found among `items`, without repeats
+    pub fn categories<'a>(items: impl IntoIterator<Item = &'a ScanItem>) -> HashSet<String> {
+        items.into_iter().map(|item| item.category.clone()).collect()
+    }
+
+    /// A filter with only `exclude` patterns, `~` being the root
+    pub fn filter(&self, exclude: &[&str]) -> PathFilter {
+        let exclude: Vec<String> = exclude.iter().map(|p| p.to_string()).collect();
+        PathFilter::new(&[], &exclude, self.root())
+    }
+}
+
+impl Drop for SimFs {
+    fn drop(&mut self) {
+        // TempDir can't remove what it can't enter
+        for path in self.locked.lock().unwrap().iter() {
+            let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o755));
+        }
+    }
+}
+
+pub fn cancel_flag(cancelled: bool) -> CancelFlag {
+    Arc::new(AtomicBool::new(cancelled))
+}
+
+pub fn empty_index() -> SharedIndex {
+    FileIndex::in_memory().shared()
+}
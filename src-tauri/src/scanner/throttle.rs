@@ -0,0 +1,264 @@
+//! Throughput profiles. A profile sets how many scanners run at once, how often a
+//! scanner pauses between filesystem operations, how much memory the scan may
+//! use, and the IO priority and read rate of its threads. The profile follows
+//! the power source (see [`ThrottleSettings`]) unless a scan asks for one
+//! through `ScanOptions::throughput_profile`.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::power::{self, PowerSource};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum ThroughputProfile {
+    /// Several scanners in parallel, no pauses
+    Performance,
+    #[default]
+    Balanced,
+    /// One scanner at a time with frequent pauses, so the disk and CPU can idle
+    BatterySaver,
+    /// Low impact while the computer is in use: idle IO priority, so the disk
+    /// serves everything else first, and file contents read at a capped rate
+    Background,
+}
+
+/// Kernel IO scheduling class for scan threads, as set by `ionice`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IoPriority {
+    #[default]
+    Normal,
+    /// Best-effort class at its lowest level (`ionice -c2 -n7`)
+    Low,
+    /// Only served when no other process wants the disk (`ionice -c3`)
+    Idle,
+}
+
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+const IOPRIO_CLASS_BE: i32 = 2;
+const IOPRIO_CLASS_IDLE: i32 = 3;
+/// `who` is a thread id; 0 is the calling thread
+const IOPRIO_WHO_PROCESS: i32 = 1;
+
+impl IoPriority {
+    /// Value for `ioprio_set`, None to leave the thread as it is
+    fn ioprio(self) -> Option<i32> {
+        match self {
+            Self::Normal => None,
+            Self::Low => Some((IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | 7),
+            Self::Idle => Some(IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT),
+        }
+    }
+
+    /// Apply to the calling thread until the guard is dropped. Blocking tasks
+    /// run on pooled threads, so the previous priority is put back afterwards.
+    pub fn enter(self) -> IoPriorityGuard {
+        let Some(ioprio) = self.ioprio() else { return IoPriorityGuard { previous: None } };
+        // SAFETY: ioprio_get/ioprio_set only read their integer arguments
+        let previous = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, 0) } as i32;
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) } != 0 {
+            tracing::debug!("Cannot lower IO priority: {}", std::io::Error::last_os_error());
+            return IoPriorityGuard { previous: None };
+        }
+        IoPriorityGuard { previous: (previous >= 0).then_some(previous) }
+    }
+}
+
+/// Restores the thread's IO priority when dropped
+pub struct IoPriorityGuard {
+    previous: Option<i32>,
+}
+
+impl Drop for IoPriorityGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous {
+            // SAFETY: as in `IoPriority::enter`
+            unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, previous) };
+        }
+    }
+}
+
+/// Token bucket on bytes read from files, shared by every scanner of a scan.
+/// Up to one second of reads may happen at once; after that readers sleep.
+#[derive(Debug)]
+pub struct ReadBudget {
+    bytes_per_sec: u64, // 0 is unlimited
+    state: Mutex<(f64, Instant)>, // tokens available, last refill
+}
+
+impl ReadBudget {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self { bytes_per_sec, state: Mutex::new((bytes_per_sec as f64, Instant::now())) }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::new(0)
+    }
+
+    /// How long a read of `bytes` at `now` has to wait, taking its tokens
+    fn reserve(&self, bytes: u64, now: Instant) -> Duration {
+        if self.bytes_per_sec == 0 {
+            return Duration::ZERO;
+        }
+        let rate = self.bytes_per_sec as f64;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last) = &mut *state;
+        *tokens = (*tokens + now.saturating_duration_since(*last).as_secs_f64() * rate).min(rate);
+        *last = now;
+        *tokens -= bytes as f64;
+        if *tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*tokens / rate)
+        }
+    }
+
+    /// Account for `bytes` read, sleeping while the scan is over its rate
+    pub fn take(&self, bytes: u64) {
+        let wait = self.reserve(bytes, Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// What a profile allows a scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileLimits {
+    pub threads: usize, // scanners running at once
+    pub pause_every: u64, // filesystem operations between pauses, 0 never pauses
+    pub pause: Duration,
+    pub max_memory_mb: usize,
+    pub io_priority: IoPriority,
+    pub read_bytes_per_sec: u64, // 0 is unlimited
+}
+
+impl ThroughputProfile {
+    pub fn limits(self) -> ProfileLimits {
+        let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        match self {
+            Self::Performance => ProfileLimits {
+                threads: cpus.clamp(1, 4),
+                pause_every: 0,
+                pause: Duration::ZERO,
+                max_memory_mb: 1000,
+                io_priority: IoPriority::Normal,
+                read_bytes_per_sec: 0,
+            },
+            Self::Balanced => ProfileLimits {
+                threads: (cpus / 2).clamp(1, 2),
+                pause_every: 256,
+                pause: Duration::from_millis(1),
+                max_memory_mb: 500,
+                io_priority: IoPriority::Normal,
+                read_bytes_per_sec: 0,
+            },
+            Self::BatterySaver => ProfileLimits {
+                threads: 1,
+                pause_every: 32,
+                pause: Duration::from_millis(10),
+                max_memory_mb: 250,
+                io_priority: IoPriority::Low,
+                read_bytes_per_sec: 0,
+            },
+            Self::Background => ProfileLimits {
+                threads: 1,
+                pause_every: 64,
+                pause: Duration::from_millis(5),
+                max_memory_mb: 250,
+                io_priority: IoPriority::Idle,
+                read_bytes_per_sec: 16 * 1024 * 1024,
+            },
+        }
+    }
+}
+
+/// Profile used on each power source when a scan doesn't pick one
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ThrottleSettings {
+    pub on_ac: ThroughputProfile,
+    pub on_battery: ThroughputProfile,
+}
+
+impl Default for ThrottleSettings {
+    fn default() -> Self {
+        Self { on_ac: ThroughputProfile::Balanced, on_battery: ThroughputProfile::BatterySaver }
+    }
+}
+
+impl ThrottleSettings {
+    pub fn for_source(&self, source: PowerSource) -> ThroughputProfile {
+        match source {
+            PowerSource::Ac => self.on_ac,
+            PowerSource::Battery => self.on_battery,
+        }
+    }
+}
+
+/// The profile a scan runs with and why
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ThroughputStatus {
+    pub power_source: PowerSource,
+    pub profile: ThroughputProfile,
+}
+
+/// `requested` if the scan picked a profile, otherwise the one configured for the current power source
+pub fn resolve(requested: Option<ThroughputProfile>, settings: &ThrottleSettings) -> ThroughputStatus {
+    let power_source = power::power_source();
+    ThroughputStatus { power_source, profile: requested.unwrap_or_else(|| settings.for_source(power_source)) }
+}
+
+/// The profile the next scan without an explicit profile would use
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_throughput_status(app_handle: tauri::AppHandle) -> Result<ThroughputStatus, String> {
+    let settings = crate::commands::get_settings(app_handle).await.unwrap_or_default().scan.throttle;
+    tokio::task::spawn_blocking(move || resolve(None, &settings))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_scale_down() {
+        let (fast, balanced, saver) = (
+            ThroughputProfile::Performance.limits(),
+            ThroughputProfile::Balanced.limits(),
+            ThroughputProfile::BatterySaver.limits(),
+        );
+        assert!(fast.threads >= balanced.threads && balanced.threads >= saver.threads);
+        assert_eq!(saver.threads, 1);
+        assert!(fast.max_memory_mb > balanced.max_memory_mb && balanced.max_memory_mb > saver.max_memory_mb);
+        assert_eq!(fast.pause_every, 0);
+        assert!(saver.pause_every < balanced.pause_every && saver.pause > balanced.pause);
+
+        let settings = ThrottleSettings::default();
+        assert_eq!(settings.for_source(PowerSource::Ac), ThroughputProfile::Balanced);
+        assert_eq!(settings.for_source(PowerSource::Battery), ThroughputProfile::BatterySaver);
+        assert_eq!(resolve(Some(ThroughputProfile::Performance), &settings).profile, ThroughputProfile::Performance);
+
+        let background = ThroughputProfile::Background.limits();
+        assert_eq!((background.threads, background.io_priority), (1, IoPriority::Idle));
+        assert!(background.read_bytes_per_sec > 0 && fast.read_bytes_per_sec == 0);
+    }
+
+    #[test]
+    fn test_read_budget() {
+        let budget = ReadBudget::new(1000);
+        let start = Instant::now();
+        // A second's worth is available up front
+        assert_eq!(budget.reserve(1000, start), Duration::ZERO);
+        assert_eq!(budget.reserve(500, start), Duration::from_millis(500));
+        // Half a second later the bucket has refilled what the last read borrowed
+        assert_eq!(budget.reserve(0, start + Duration::from_millis(500)), Duration::ZERO);
+        assert_eq!(ReadBudget::unlimited().reserve(u64::MAX, start), Duration::ZERO);
+    }
+}
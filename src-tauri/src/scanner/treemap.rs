@@ -0,0 +1,139 @@
+//! Pre-aggregated directory sizes for treemap and sunburst views. The tree is
+//! walked once, bottom-up: every directory's size is the sum of what its walk
+//! returned, so no subtree is measured twice the way a `get_dir_size` call per
+//! node would. Sizes are bytes on disk with hard links counted once. Below
+//! `depth` only sizes are kept, and small siblings are folded into one "other"
+//! node so the payload stays small on large trees.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::allocation::{self, InodeSet};
+
+pub const MAX_DEPTH: usize = 8;
+/// Children kept per directory; the rest become one aggregate node
+pub const MAX_CHILDREN: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct UsageNode {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub file_count: u64,
+    pub is_directory: bool,
+    /// Stands for several small siblings, named "N more items"
+    pub aggregate: bool,
+    pub children: Vec<UsageNode>, // largest first; empty below the requested depth
+}
+
+/// Walk `root` once and keep `depth` levels of children, at most `max_children`
+/// each. `None` once `cancel` is set.
+pub fn build(root: &Path, depth: usize, max_children: usize, cancel: &AtomicBool) -> Option<UsageNode> {
+    let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| root.to_string_lossy().to_string());
+    walk(root, name, depth, max_children, cancel, &mut InodeSet::new())
+}
+
+fn walk(dir: &Path, name: String, depth: usize, max_children: usize, cancel: &AtomicBool, seen: &mut InodeSet) -> Option<UsageNode> {
+    let mut node = UsageNode {
+        name,
+        path: dir.to_string_lossy().to_string(),
+        size: 0,
+        file_count: 0,
+        is_directory: true,
+        aggregate: false,
+        children: Vec::new(),
+    };
+    // Unreadable directories count as empty rather than failing the whole tree
+    for entry in std::fs::read_dir(dir).into_iter().flatten().filter_map(|e| e.ok()) {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let Ok(file_type) = entry.file_type() else { continue };
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        // Symlinks are not followed, so nothing is counted twice or outside the root
+        let child = if file_type.is_dir() {
+            walk(&entry.path(), entry_name, depth.saturating_sub(1), max_children, cancel, seen)?
+        } else if file_type.is_file() {
+            let Ok(metadata) = entry.metadata() else { continue };
+            UsageNode {
+                name: entry_name,
+                path: entry.path().to_string_lossy().to_string(),
+                size: seen.count(&metadata),
+                file_count: 1,
+                is_directory: false,
+                aggregate: false,
+                children: Vec::new(),
+            }
+        } else {
+            continue;
+        };
+        node.size += child.size;
+        node.file_count += child.file_count;
+        if depth > 0 {
+            node.children.push(child);
+        }
+    }
+    fold_small_children(&mut node, max_children);
+    Some(node)
+}
+
+/// Keep the `max_children` largest children and merge the rest into one node
+fn fold_small_children(node: &mut UsageNode, max_children: usize) {
+    node.children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    if node.children.len() <= max_children {
+        return;
+    }
+    let rest = node.children.split_off(max_children.saturating_sub(1));
+    node.children.push(UsageNode {
+        name: format!("{} more items", rest.len()),
+        path: node.path.clone(),
+        size: rest.iter().map(|c| c.size).sum(),
+        file_count: rest.iter().map(|c| c.file_count).sum(),
+        is_directory: false,
+        aggregate: true,
+        children: Vec::new(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_build_aggregates_in_one_pass() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("a/deep/er")).unwrap();
+        fs::write(root.join("a/deep/er/x"), vec![7u8; 300_000]).unwrap();
+        fs::write(root.join("a/y"), vec![7u8; 100]).unwrap();
+        for i in 0..4 {
+            fs::write(root.join(format!("f{}", i)), vec![7u8; 10]).unwrap();
+        }
+        // Counted once, under the first of its names the walk meets
+        fs::hard_link(root.join("f0"), root.join("f0-link")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("link")).unwrap();
+        let disk = |p: &str| allocation::allocated(&fs::metadata(root.join(p)).unwrap());
+
+        let tree = build(root, 2, 3, &AtomicBool::new(false)).unwrap();
+        let small = disk("f0") + disk("f1") + disk("f2") + disk("f3");
+        assert_eq!(tree.size, disk("a/deep/er/x") + disk("a/y") + small);
+        assert_eq!(tree.file_count, 7);
+        assert_eq!(tree.children.len(), 3);
+        assert_eq!(tree.children[0].name, "a");
+        let other = &tree.children[2];
+        assert_eq!((other.name.as_str(), other.aggregate), ("4 more items", true));
+        assert_eq!(tree.children.iter().map(|c| c.size).sum::<u64>(), tree.size);
+
+        let a = &tree.children[0];
+        let deep = a.children.iter().find(|c| c.name == "deep").unwrap();
+        // Depth 2 ends here: sized, but without children
+        assert_eq!((deep.size, deep.children.len()), (disk("a/deep/er/x"), 0));
+
+        let cancelled = AtomicBool::new(true);
+        assert_eq!(build(root, 2, 3, &cancelled), None);
+    }
+}
@@ -0,0 +1,211 @@
+//! Global keyboard shortcuts (Quick Clean, show/hide window) registered from
+//! settings, with conflicts reported per shortcut instead of failing the save.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::commands::{self, ShortcutSettings};
+use crate::detached;
+use crate::metrics;
+use crate::operations::{guard, OperationPlan};
+
+lazy_static::lazy_static! {
+    /// Registered shortcut id -> action
+    static ref BINDINGS: Mutex<HashMap<u32, ShortcutAction>> = Mutex::new(HashMap::new());
+    static ref STATUS: Mutex<Vec<ShortcutStatus>> = Mutex::new(Vec::new());
+}
+
+/// Set while a Quick Clean started by the shortcut runs, so holding or mashing
+/// the keys doesn't start several at once
+static QUICK_CLEAN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum ShortcutAction {
+    QuickClean,
+    ToggleWindow,
+}
+
+impl ShortcutAction {
+    fn name(self) -> &'static str {
+        match self {
+            ShortcutAction::QuickClean => "Quick Clean",
+            ShortcutAction::ToggleWindow => "Show/Hide Window",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ShortcutStatus {
+    pub action: ShortcutAction,
+    pub accelerator: String,
+    pub registered: bool,
+    pub conflict: Option<String>, // why it could not be registered
+}
+
+fn configured(settings: &ShortcutSettings) -> Vec<(ShortcutAction, String)> {
+    [
+        (ShortcutAction::QuickClean, &settings.quick_clean),
+        (ShortcutAction::ToggleWindow, &settings.toggle_window),
+    ]
+    .into_iter()
+    .filter_map(|(action, accelerator)| {
+        let accelerator = accelerator.as_deref()?.trim();
+        (!accelerator.is_empty()).then(|| (action, accelerator.to_string()))
+    })
+    .collect()
+}
+
+/// Parse each binding and catch the ones that collide with each other; clashes
+/// with other applications only show up when registering
+pub fn plan(bindings: &[(ShortcutAction, String)]) -> Vec<(ShortcutStatus, Option<Shortcut>)> {
+    let mut planned: Vec<(ShortcutStatus, Option<Shortcut>)> = Vec::new();
+    for (action, accelerator) in bindings {
+        let mut status = ShortcutStatus { action: *action, accelerator: accelerator.clone(), registered: false, conflict: None };
+        let shortcut = match accelerator.parse::<Shortcut>() {
+            Ok(shortcut) => Some(shortcut),
+            Err(e) => {
+                status.conflict = Some(format!("Invalid shortcut: {}", e));
+                None
+            }
+        };
+        let taken_by = shortcut
+            .and_then(|s| planned.iter().find(|(_, other)| *other == Some(s)))
+            .map(|(other, _)| other.action);
+        if let Some(other) = taken_by {
+            status.conflict = Some(format!("Already used for {}", other.name()));
+            planned.push((status, None));
+        } else {
+            planned.push((status, shortcut));
+        }
+    }
+    planned
+}
+
+/// Replace all registered shortcuts with the ones in `settings`
+pub fn apply(app_handle: &tauri::AppHandle, settings: &ShortcutSettings) -> Vec<ShortcutStatus> {
+    let global_shortcut = app_handle.global_shortcut();
+    if let Err(e) = global_shortcut.unregister_all() {
+        tracing::warn!("Failed to unregister global shortcuts: {}", e);
+    }
+    let mut bindings = BINDINGS.lock().unwrap_or_else(|e| e.into_inner());
+    bindings.clear();
+
+    let statuses: Vec<ShortcutStatus> = if settings.enabled {
+        plan(&configured(settings))
+            .into_iter()
+            .map(|(mut status, shortcut)| {
+                if let Some(shortcut) = shortcut {
+                    match global_shortcut.register(shortcut) {
+                        Ok(()) => {
+                            status.registered = true;
+                            bindings.insert(shortcut.id(), status.action);
+                        }
+                        Err(e) => {
+                            tracing::warn!("Global shortcut {} unavailable: {}", status.accelerator, e);
+                            status.conflict = Some(format!("In use by another application: {}", e));
+                        }
+                    }
+                }
+                status
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    *STATUS.lock().unwrap_or_else(|e| e.into_inner()) = statuses.clone();
+    statuses
+}
+
+/// Global shortcut plugin handler
+pub fn handle(app_handle: &tauri::AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let action = BINDINGS.lock().unwrap_or_else(|e| e.into_inner()).get(&shortcut.id()).copied();
+    match action {
+        Some(ShortcutAction::ToggleWindow) => detached::toggle_main(app_handle),
+        Some(ShortcutAction::QuickClean) => {
+            if QUICK_CLEAN_RUNNING.swap(true, Ordering::SeqCst) {
+                tracing::info!("Quick Clean shortcut ignored, the previous run hasn't finished");
+                return;
+            }
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                tracing::info!("Quick Clean triggered by global shortcut");
+                // Confirmed when the shortcut was set up, but still rate limited and audited
+                let result = match guard::authorize_internal(&app_handle, &OperationPlan::QuickCleanSafe) {
+                    Ok(()) => metrics::timed("quick_clean_safe", commands::quick_clean_safe_inner(app_handle.clone())).await,
+                    Err(e) => Err(e),
+                };
+                QUICK_CLEAN_RUNNING.store(false, Ordering::SeqCst);
+                if let Err(e) = &result {
+                    tracing::error!("Shortcut quick clean failed: {}", e);
+                }
+                if let Err(e) = detached::emit(&app_handle, "shortcut-quick-clean", result) {
+                    tracing::warn!("Failed to emit quick clean result: {}", e);
+                }
+            });
+        }
+        None => {}
+    }
+}
+
+/// Register the saved shortcuts at startup
+pub async fn restore(app_handle: tauri::AppHandle) {
+    if let Ok(settings) = commands::get_settings(app_handle.clone()).await {
+        apply(&app_handle, &settings.shortcuts);
+    }
+}
+
+/// Outcome of the last registration, one entry per configured shortcut
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_shortcut_status() -> Result<Vec<ShortcutStatus>, String> {
+    Ok(STATUS.lock().map_err(|e| format!("Lock failed: {}", e))?.clone())
+}
+
+/// Register shortcuts without saving the rest of the settings
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn apply_shortcuts(app_handle: tauri::AppHandle, settings: ShortcutSettings) -> Result<Vec<ShortcutStatus>, String> {
+    Ok(apply(&app_handle, &settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_skips_empty() {
+        let settings = ShortcutSettings {
+            enabled: true,
+            quick_clean: Some("Super+Shift+C".to_string()),
+            toggle_window: Some("  ".to_string()),
+        };
+        assert_eq!(configured(&settings), vec![(ShortcutAction::QuickClean, "Super+Shift+C".to_string())]);
+    }
+
+    #[test]
+    fn test_plan_detects_conflicts() {
+        let planned = plan(&[
+            (ShortcutAction::QuickClean, "Super+Shift+C".to_string()),
+            (ShortcutAction::ToggleWindow, "shift+super+c".to_string()),
+        ]);
+        assert!(planned[0].1.is_some());
+        assert_eq!(planned[0].0.conflict, None);
+        assert!(planned[1].1.is_none());
+        assert_eq!(planned[1].0.conflict.as_deref(), Some("Already used for Quick Clean"));
+
+        let planned = plan(&[(ShortcutAction::ToggleWindow, "Super+Banana".to_string())]);
+        assert!(planned[0].1.is_none());
+        assert!(planned[0].0.conflict.as_deref().unwrap().starts_with("Invalid shortcut"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Orderly exit. Quitting used to drop the runtime with scans, DiskPulse and
+//! half-written saves still running. Now the first exit request is held back
+//! while [`run`] cancels scans, stops the monitors and the job worker, waits
+//! for background writes to land and closes the database; then the app exits
+//! with the code it was asked to. Every wait has a deadline, so a stuck task
+//! delays quitting by seconds at most: an interrupted job is queued again on
+//! the next start, and scans resume from their checkpoints. SIGTERM and SIGINT
+//! go through the same path, which is how the headless agent is stopped.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::db::{AppState, DbAccess};
+use crate::jobs;
+use crate::monitoring::MonitoringHandle;
+use crate::scanner;
+
+/// How long cancelled scans get to stop and save their checkpoints
+const SCAN_GRACE: Duration = Duration::from_secs(3);
+/// How long a running job or save gets to finish writing
+const WRITE_GRACE: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static COMPLETE: AtomicBool = AtomicBool::new(false);
+static PENDING_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+/// A background write shutdown waits for; done when dropped
+pub struct PendingWrite(());
+
+impl Drop for PendingWrite {
+    fn drop(&mut self) {
+        PENDING_WRITES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Register a write about to start outside of any command, such as saving the
+/// file index after a scan. Hold the guard until it's done.
+pub fn pending_write() -> PendingWrite {
+    PENDING_WRITES.fetch_add(1, Ordering::SeqCst);
+    PendingWrite(())
+}
+
+/// Whether an exit request may go ahead: only once `run` has finished.
+/// The first request starts it.
+pub fn allow_exit(app_handle: &tauri::AppHandle, code: Option<i32>) -> bool {
+    if COMPLETE.load(Ordering::SeqCst) {
+        return true;
+    }
+    if !STARTED.swap(true, Ordering::SeqCst) {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            run(&app_handle).await;
+            COMPLETE.store(true, Ordering::SeqCst);
+            app_handle.exit(code.unwrap_or(0));
+        });
+    }
+    false
+}
+
+/// Shut down in order on SIGTERM or SIGINT rather than being killed mid-write.
+/// A second signal while that's under way exits at once.
+pub fn handle_signals(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let (mut terminate, mut interrupt) = match (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) {
+            (Ok(terminate), Ok(interrupt)) => (terminate, interrupt),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::warn!("Failed to install signal handlers: {}", e);
+                return;
+            }
+        };
+        loop {
+            let name = tokio::select! {
+                _ = terminate.recv() => "SIGTERM",
+                _ = interrupt.recv() => "SIGINT",
+            };
+            if STARTED.load(Ordering::SeqCst) {
+                tracing::warn!("{} during shutdown, exiting now", name);
+                std::process::exit(1);
+            }
+            tracing::info!("{} received", name);
+            allow_exit(&app_handle, Some(0));
+        }
+    });
+}
+
+/// Poll `done` until it holds or `grace` runs out; false on timeout
+async fn settle(grace: Duration, done: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + grace;
+    while !done() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    true
+}
+
+/// Stop background work and close the database
+pub async fn run(app_handle: &tauri::AppHandle) {
+    let started = Instant::now();
+    tracing::info!("Shutting down");
+
+    let cancelled = scanner::cancel_scans(None);
+    if cancelled > 0 && !settle(SCAN_GRACE, || scanner::active_scans() == 0).await {
+        tracing::warn!("{} scans still running at exit", scanner::active_scans());
+    }
+
+    let monitoring = MonitoringHandle::of(app_handle);
+    if monitoring.stop_diskpulse().await {
+        tracing::info!("Stopped DiskPulse monitoring");
+    }
+    if monitoring.stop_scheduler().await {
+        tracing::info!("Stopped the scheduler");
+    }
+
+    jobs::stop_worker();
+    if !settle(WRITE_GRACE, || PENDING_WRITES.load(Ordering::SeqCst) == 0).await {
+        tracing::warn!("{} background writes unfinished at exit", PENDING_WRITES.load(Ordering::SeqCst));
+    }
+
+    if let Err(e) = app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO monitoring_state (key, value, updated_at) VALUES ('last_shutdown', ?1, ?1)",
+            [chrono::Utc::now().timestamp()],
+        )?;
+        conn.execute_batch("PRAGMA optimize;")
+    }) {
+        tracing::warn!("Failed to record shutdown: {}", e);
+    }
+    close_database(app_handle);
+
+    tracing::info!("Shutdown finished in {}ms", started.elapsed().as_millis());
+}
+
+/// Later `db` calls fail instead of writing to a closing connection
+fn close_database(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let conn = state.db.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(Err((_, e))) = conn.map(|c| c.close()) {
+        tracing::warn!("Failed to close the database: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_settle() {
+        assert!(settle(Duration::ZERO, || true).await);
+        assert!(!settle(Duration::from_millis(120), || false).await);
+
+        let polls = AtomicUsize::new(0);
+        assert!(settle(Duration::from_secs(1), || polls.fetch_add(1, Ordering::SeqCst) >= 2).await);
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+    }
+}
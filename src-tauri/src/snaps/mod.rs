@@ -0,0 +1,239 @@
+//! Disabled snap revisions. snapd keeps the previous revisions of every snap
+//! (two by default, three for some cores) so a refresh can be reverted; they
+//! show up as `disabled` in `snap list --all`. Removing them goes through
+//! `snap remove --revision` as root, so snapd's own bookkeeping stays intact.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::privileged::{self, PrivilegedOp, PrivilegedOutput};
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Snap";
+
+/// Where snapd keeps the squashfs image of every installed revision
+const SNAPS_DIR: &str = "/var/lib/snapd/snaps";
+
+/// Removes every disabled revision; run as root through the privileged helper or pkexec
+pub const REMOVE_DISABLED_SCRIPT: &str = "LANG=C snap list --all \
+    | awk '$NF ~ /(^|,)disabled(,|$)/ {print $1, $3}' \
+    | { status=0; while read -r name rev; do snap remove \"$name\" --revision=\"$rev\" || status=1; done; exit $status; }";
+
+/// A revision snapd keeps around after a refresh
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisabledRevision {
+    pub name: String,
+    pub version: String,
+    pub revision: String,
+    pub path: PathBuf, // the revision's .snap image
+}
+
+/// (name, version, revision) of the disabled rows in `snap list --all`:
+///
+/// ```text
+/// Name    Version   Rev    Tracking       Publisher   Notes
+/// core20  20230801  2015   latest/stable  canonical✓  base,disabled
+/// ```
+pub fn parse_snap_list(output: &str) -> Vec<(String, String, String)> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 4 || !fields[fields.len() - 1].split(',').any(|note| note == "disabled") {
+                return None;
+            }
+            Some((fields[0].to_string(), fields[1].to_string(), fields[2].to_string()))
+        })
+        .collect()
+}
+
+fn image_path(root: &Path, name: &str, revision: &str) -> PathBuf {
+    root.join(format!("{}_{}.snap", name, revision))
+}
+
+pub fn snap_available() -> bool {
+    crate::snapshots::tool_available("snap")
+}
+
+/// Disabled revisions of installed snaps
+pub fn disabled_revisions() -> Result<Vec<DisabledRevision>, String> {
+    let output = Command::new("snap")
+        .args(["list", "--all"])
+        .env("LANG", "C")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run snap: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("snap list failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(parse_snap_list(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .map(|(name, version, revision)| DisabledRevision {
+            path: image_path(Path::new(SNAPS_DIR), &name, &revision),
+            name,
+            version,
+            revision,
+        })
+        .collect())
+}
+
+/// Bytes a revision's image takes on disk
+pub fn revision_size(revision: &DisabledRevision) -> u64 {
+    std::fs::metadata(&revision.path).map(|m| m.len()).unwrap_or(0)
+}
+
+pub fn revision_item(revision: &DisabledRevision) -> ScanItem {
+    new_item(
+        format!("{} {} (rev {})", revision.name, revision.version, revision.revision),
+        &revision.path,
+        revision_size(revision),
+        "snap_revision",
+        CATEGORY,
+        0,
+        &format!("Disabled revision of {} kept by snapd after a refresh", revision.name),
+    )
+}
+
+/// Disabled revisions as scan items, largest first; empty where snap isn't installed
+pub fn revision_items() -> Vec<ScanItem> {
+    if !snap_available() {
+        return Vec::new();
+    }
+    match disabled_revisions() {
+        Ok(revisions) => {
+            let mut items: Vec<ScanItem> = revisions.iter().map(revision_item).collect();
+            items.sort_by(|a, b| b.size.cmp(&a.size));
+            items
+        }
+        Err(e) => {
+            tracing::warn!("Failed to list snap revisions: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+pub struct SnapScanner;
+
+impl Scanner for SnapScanner {
+    fn id(&self) -> &'static str {
+        "snap"
+    }
+
+    fn name(&self) -> &'static str {
+        "Snap"
+    }
+
+    fn description(&self) -> &'static str {
+        "Disabled snap revisions kept after refreshes"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_packages && snap_available()
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        if !snap_available() {
+            return Ok(Vec::new());
+        }
+        ctx.progress(0, "Looking for disabled snap revisions...");
+        let revisions = disabled_revisions().map_err(|e| ScannerError::IoError(std::io::Error::other(e)))?;
+        ctx.check_cancelled()?;
+        let mut items: Vec<ScanItem> = revisions.iter().map(revision_item).collect();
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        Ok(items)
+    }
+}
+
+fn run_removal(app_handle: &tauri::AppHandle) -> Result<PrivilegedOutput, String> {
+    if let Some(output) = privileged::run_in_session(app_handle, PrivilegedOp::SnapRemoveDisabled)? {
+        return Ok(output);
+    }
+    let output = Command::new("pkexec")
+        .args(["sh", "-c", REMOVE_DISABLED_SCRIPT])
+        .output()
+        .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+    // pkexec exits with 126 when the user dismisses the authentication dialog
+    if output.status.code() == Some(126) {
+        return Err("Authentication was cancelled".to_string());
+    }
+    Ok(PrivilegedOutput::from(output))
+}
+
+/// Remove every disabled snap revision. Revisions still listed afterwards are
+/// counted as failed.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn remove_disabled_snaps(app_handle: tauri::AppHandle, confirmation_token: String) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::RemoveDisabledSnaps, &confirmation_token)?;
+
+    let handle = app_handle.clone();
+    let (before, after) = tokio::task::spawn_blocking(move || {
+        // Sized up front, the images are gone afterwards
+        let sizes: Vec<(DisabledRevision, u64)> = disabled_revisions()?
+            .into_iter()
+            .map(|r| {
+                let size = revision_size(&r);
+                (r, size)
+            })
+            .collect();
+        if sizes.is_empty() {
+            return Ok((sizes, Vec::new()));
+        }
+        let output = run_removal(&handle)?;
+        if !output.success {
+            tracing::warn!("Removing disabled snap revisions reported errors: {}", output.stderr.trim());
+        }
+        Ok::<_, String>((sizes, disabled_revisions()?))
+    })
+    .await
+    .map_err(|e| format!("Snap task failed: {}", e))??;
+
+    let removed: Vec<&(DisabledRevision, u64)> = before.iter().filter(|(r, _)| !after.contains(r)).collect();
+    let cleaned = removed.len();
+    let failed = before.len() - cleaned;
+    let total_size: u64 = removed.iter().map(|(_, size)| size).sum();
+    tracing::info!("Removed {} disabled snap revisions ({} bytes), {} failed", cleaned, total_size, failed);
+
+    if total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snap_list() {
+        let output = "Name      Version          Rev    Tracking         Publisher   Notes\n\
+                      core20    20230801         2015   latest/stable    canonical✓  base,disabled\n\
+                      core20    20230901         2105   latest/stable    canonical✓  base\n\
+                      firefox   118.0.1-1        3206   latest/stable/…  mozilla✓    disabled\n\
+                      firefox   118.0.2-1        3252   latest/stable/…  mozilla✓    -\n\
+                      disabled  1.0              7      latest/stable    someone     -\n";
+        assert_eq!(
+            parse_snap_list(output),
+            vec![
+                ("core20".to_string(), "20230801".to_string(), "2015".to_string()),
+                ("firefox".to_string(), "118.0.1-1".to_string(), "3206".to_string()),
+            ]
+        );
+        assert!(parse_snap_list("No snaps are installed yet.\n").is_empty());
+        assert_eq!(image_path(Path::new(SNAPS_DIR), "core20", "2015"), PathBuf::from("/var/lib/snapd/snaps/core20_2015.snap"));
+    }
+}
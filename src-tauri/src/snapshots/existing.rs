@@ -0,0 +1,348 @@
+//! Snapshots already on btrfs and ZFS filesystems. A snapshot keeps every
+//! block it shares with the live filesystem, so deleting a file that hasn't
+//! changed since the newest snapshot of its filesystem frees nothing until that
+//! snapshot goes. Snapshots come from snapper, from `zfs list`, and from the
+//! plain btrfs snapshots Pulito takes itself (`SNAPSHOT_DIR_NAME`).
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use super::{parse_snapper_configs, tool_available, SNAPSHOT_DESCRIPTION, SNAPSHOT_DIR_NAME};
+use crate::cache_redirect;
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::privileged::{self, PrivilegedOp};
+use crate::scanner::builtin::new_item;
+use crate::scanner::ScanItem;
+
+pub const CATEGORY: &str = "Filesystem Snapshots";
+/// Snapshots older than this are offered for removal
+pub const STALE_AFTER_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct ExistingSnapshot {
+    pub id: String, // "snapper:<config>:<number>", "zfs:<dataset>@<name>" or "btrfs:<path>"
+    pub tool: String,
+    pub filesystem: String,
+    pub mount_point: String, // the subvolume or dataset the snapshot is of
+    pub path: String,        // where its files can be browsed
+    pub created_at: i64,
+    /// Bytes only this snapshot holds; unknown for btrfs without quotas
+    pub exclusive_bytes: Option<u64>,
+    pub description: String,
+    pub created_by_pulito: bool,
+}
+
+/// Whether files on `fs_type` can be held by snapshots
+pub fn snapshot_capable(fs_type: &str) -> bool {
+    fs_type == "btrfs" || fs_type == "zfs"
+}
+
+/// Parse `zfs list -Hp -t snapshot -o name,creation,used`; `mounts` maps
+/// datasets to their mount points and snapshots of unmounted datasets are left out
+pub fn parse_zfs_snapshots(output: &str, mounts: &HashMap<String, String>) -> Vec<ExistingSnapshot> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (name, creation, used) = (fields.next()?, fields.next()?, fields.next()?);
+            let (dataset, snapshot) = name.split_once('@')?;
+            let mount_point = mounts.get(dataset)?;
+            Some(ExistingSnapshot {
+                id: format!("zfs:{}", name),
+                tool: "zfs".to_string(),
+                filesystem: "zfs".to_string(),
+                mount_point: mount_point.clone(),
+                path: Path::new(mount_point).join(".zfs/snapshot").join(snapshot).to_string_lossy().to_string(),
+                created_at: creation.trim().parse().ok()?,
+                exclusive_bytes: used.trim().parse().ok(),
+                description: snapshot.to_string(),
+                created_by_pulito: false,
+            })
+        })
+        .collect()
+}
+
+/// Parse `snapper --csvout --iso -c <config> list --columns number,date,description`
+/// for the config of `subvolume`. Snapshot 0 is the live filesystem.
+pub fn parse_snapper_snapshots(output: &str, config: &str, subvolume: &str) -> Vec<ExistingSnapshot> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ',');
+            let (number, date) = (fields.next()?.trim(), fields.next()?.trim());
+            let description = fields.next().unwrap_or("").trim().trim_matches('"').to_string();
+            let number: u64 = number.parse().ok().filter(|n| *n > 0)?;
+            let created_at = chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+                .ok()?
+                .and_local_timezone(chrono::Local)
+                .earliest()?
+                .timestamp();
+            Some(ExistingSnapshot {
+                id: format!("snapper:{}:{}", config, number),
+                tool: "snapper".to_string(),
+                filesystem: "btrfs".to_string(),
+                mount_point: subvolume.to_string(),
+                path: Path::new(subvolume).join(".snapshots").join(number.to_string()).join("snapshot").to_string_lossy().to_string(),
+                created_at,
+                exclusive_bytes: None,
+                created_by_pulito: description == SNAPSHOT_DESCRIPTION,
+                description,
+            })
+        })
+        .collect()
+}
+
+fn zfs_snapshots(mounts: &[cache_redirect::MountEntry]) -> Vec<ExistingSnapshot> {
+    let datasets: HashMap<String, String> =
+        mounts.iter().filter(|m| m.fs_type == "zfs").map(|m| (m.source.clone(), m.mount_point.clone())).collect();
+    if datasets.is_empty() || !tool_available("zfs") {
+        return Vec::new();
+    }
+    match Command::new("zfs").args(["list", "-Hp", "-t", "snapshot", "-o", "name,creation,used"]).output() {
+        Ok(output) => parse_zfs_snapshots(&String::from_utf8_lossy(&output.stdout), &datasets),
+        Err(e) => {
+            tracing::warn!("Failed to list ZFS snapshots: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn snapper_snapshots() -> Vec<ExistingSnapshot> {
+    if !tool_available("snapper") {
+        return Vec::new();
+    }
+    let Ok(output) = Command::new("snapper").arg("list-configs").output() else { return Vec::new() };
+    let mut snapshots = Vec::new();
+    for (config, subvolume) in parse_snapper_configs(&String::from_utf8_lossy(&output.stdout)) {
+        let listed = Command::new("snapper")
+            .args(["--csvout", "--iso", "-c", &config, "list", "--columns", "number,date,description"])
+            .output();
+        match listed {
+            Ok(output) => snapshots.extend(parse_snapper_snapshots(&String::from_utf8_lossy(&output.stdout), &config, &subvolume)),
+            Err(e) => tracing::warn!("Failed to list snapper snapshots of {}: {}", config, e),
+        }
+    }
+    snapshots
+}
+
+/// Plain snapshots Pulito took under each btrfs mount
+fn pulito_btrfs_snapshots(mounts: &[cache_redirect::MountEntry]) -> Vec<ExistingSnapshot> {
+    let mut snapshots = Vec::new();
+    for mount in mounts.iter().filter(|m| m.fs_type == "btrfs") {
+        let dir = Path::new(&mount.mount_point).join(SNAPSHOT_DIR_NAME);
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let named_at = name
+                .strip_prefix("pre-cleanup-")
+                .and_then(|stamp| chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S").ok())
+                .and_then(|at| at.and_local_timezone(chrono::Local).earliest())
+                .map(|at| at.timestamp());
+            let Some(created_at) = named_at.or_else(|| modified_at(&entry.path())) else { continue };
+            let path = entry.path().to_string_lossy().to_string();
+            if snapshots.iter().any(|s: &ExistingSnapshot| s.path == path) {
+                continue; // a subvolume mounted at several places
+            }
+            snapshots.push(ExistingSnapshot {
+                id: format!("btrfs:{}", path),
+                tool: "btrfs".to_string(),
+                filesystem: "btrfs".to_string(),
+                mount_point: mount.mount_point.clone(),
+                path,
+                created_at,
+                exclusive_bytes: None,
+                description: SNAPSHOT_DESCRIPTION.to_string(),
+                created_by_pulito: true,
+            });
+        }
+    }
+    snapshots
+}
+
+fn modified_at(path: &Path) -> Option<i64> {
+    let modified = std::fs::symlink_metadata(path).ok()?.modified().ok()?;
+    Some(chrono::DateTime::<chrono::Utc>::from(modified).timestamp())
+}
+
+fn read_mounts() -> Vec<cache_redirect::MountEntry> {
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    cache_redirect::parse_mounts(&mounts)
+}
+
+/// Every snapshot on the mounted btrfs and ZFS filesystems, newest first
+pub fn list_snapshots() -> Vec<ExistingSnapshot> {
+    let mounts = read_mounts();
+    if !mounts.iter().any(|m| snapshot_capable(&m.fs_type)) {
+        return Vec::new();
+    }
+    let mut snapshots = zfs_snapshots(&mounts);
+    snapshots.extend(snapper_snapshots());
+    snapshots.extend(pulito_btrfs_snapshots(&mounts));
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    snapshots
+}
+
+/// Whether a file on `mount_point` last modified at `modified` is still held by
+/// one of `snapshots`: snapshots of a subvolume don't reach into the ones
+/// mounted below it, so only snapshots of exactly that mount count
+pub fn is_held(mount_point: &str, modified: i64, snapshots: &[ExistingSnapshot]) -> bool {
+    snapshots.iter().any(|s| s.mount_point == mount_point && s.created_at >= modified)
+}
+
+/// Bytes of `items` that deleting would not free because snapshots still hold them
+pub fn held_size<'a>(items: impl IntoIterator<Item = &'a ScanItem>, snapshots: &[ExistingSnapshot]) -> u64 {
+    if snapshots.is_empty() {
+        return 0;
+    }
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let mut held = 0;
+    for item in items {
+        let path = Path::new(&item.path);
+        let Some(mount) = cache_redirect::mount_entry(&mounts, path) else { continue };
+        if !snapshot_capable(&mount.fs_type) {
+            continue;
+        }
+        if modified_at(path).is_some_and(|modified| is_held(&mount.mount_point, modified, snapshots)) {
+            held += item.size;
+        }
+    }
+    held
+}
+
+/// Snapshots older than `STALE_AFTER_DAYS` as scan items, largest first. Ones
+/// Pulito took before a cleanup are low risk; others belong to a backup policy.
+pub fn stale_items(snapshots: &[ExistingSnapshot], now: i64) -> Vec<ScanItem> {
+    let cutoff = now - STALE_AFTER_DAYS * 86400;
+    let mut items: Vec<ScanItem> = snapshots
+        .iter()
+        .filter(|s| s.created_at < cutoff)
+        .map(|s| {
+            let age_days = (now - s.created_at) / 86400;
+            let mut item = new_item(
+                Path::new(&s.path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| s.id.clone()),
+                Path::new(&s.path),
+                s.exclusive_bytes.unwrap_or(0),
+                "filesystem_snapshot",
+                CATEGORY,
+                if s.created_by_pulito { 1 } else { 3 },
+                &format!("{} snapshot of {} from {} days ago", s.tool, s.mount_point, age_days),
+            );
+            item.id = s.id.clone();
+            item
+        })
+        .collect();
+    items.sort_by(|a, b| b.size.cmp(&a.size));
+    items
+}
+
+/// The command deleting the snapshot listed as `id`, for the privileged helper
+/// to run; an id it doesn't list itself is refused
+pub fn deletion_command(id: &str) -> Result<(&'static str, Vec<String>), String> {
+    let snapshot = list_snapshots().into_iter().find(|s| s.id == id).ok_or_else(|| format!("No snapshot {}", id))?;
+    Ok(match snapshot.tool.as_str() {
+        "snapper" => {
+            let mut parts = snapshot.id.splitn(3, ':').skip(1);
+            let (config, number) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+            ("snapper", vec!["-c".to_string(), config.to_string(), "delete".to_string(), number.to_string()])
+        }
+        "zfs" => ("zfs", vec!["destroy".to_string(), snapshot.id.trim_start_matches("zfs:").to_string()]),
+        _ => ("btrfs", vec!["subvolume".to_string(), "delete".to_string(), snapshot.path]),
+    })
+}
+
+/// Snapshots on the mounted btrfs and ZFS filesystems, newest first
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_filesystem_snapshots() -> Result<Vec<ExistingSnapshot>, String> {
+    tokio::task::spawn_blocking(list_snapshots)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Delete the snapshots with these ids. Only snapshots still listed are
+/// touched; unknown ids count as failed.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn delete_filesystem_snapshots(
+    app_handle: tauri::AppHandle,
+    ids: Vec<String>,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    guard::authorize(&app_handle, &OperationPlan::DeleteFilesystemSnapshots { ids: ids.clone() }, &confirmation_token)?;
+
+    let handle = app_handle.clone();
+    let (cleaned, failed, total_size) = tokio::task::spawn_blocking(move || -> Result<_, String> {
+        let listed = list_snapshots();
+        let targets: Vec<&ExistingSnapshot> = ids.iter().filter_map(|id| listed.iter().find(|s| &s.id == id)).collect();
+        let ops = targets.iter().map(|s| PrivilegedOp::DeleteSnapshot { id: s.id.clone() }).collect();
+        let outputs = privileged::run_all(&handle, ops)?;
+
+        let (mut cleaned, mut failed, mut total_size) = (0, ids.len() - targets.len(), 0);
+        for (snapshot, output) in targets.iter().zip(outputs) {
+            if output.success {
+                cleaned += 1;
+                total_size += snapshot.exclusive_bytes.unwrap_or(0);
+            } else {
+                tracing::warn!("Failed to delete snapshot {}: {}", snapshot.id, output.stderr.trim());
+                failed += 1;
+            }
+        }
+        Ok((cleaned, failed, total_size))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+    tracing::info!("Deleted {} filesystem snapshots ({} bytes), {} failed", cleaned, total_size, failed);
+
+    if total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+
+    Ok(CleanResult {
+        cleaned,
+        failed,
+        total_size,
+        pending_release: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zfs_snapshots() {
+        let mounts = HashMap::from([("tank/home".to_string(), "/home".to_string())]);
+        let output = "tank/home@daily-1\t1760000000\t1048576\ntank/var@daily-1\t1760000000\t4096\nbroken line\n";
+        let snapshots = parse_zfs_snapshots(output, &mounts);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].id, "zfs:tank/home@daily-1");
+        assert_eq!(snapshots[0].path, "/home/.zfs/snapshot/daily-1");
+        assert_eq!((snapshots[0].created_at, snapshots[0].exclusive_bytes), (1760000000, Some(1048576)));
+    }
+
+    #[test]
+    fn test_parse_snapper_snapshots_and_staleness() {
+        let output = "number,date,description\n0,,current\n41,2026-08-01 10:00:00,timeline\n42,2026-10-15 09:30:00,Pulito: before cleanup\n";
+        let snapshots = parse_snapper_snapshots(output, "home", "/home");
+        assert_eq!(snapshots.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["snapper:home:41", "snapper:home:42"]);
+        assert_eq!(snapshots[1].path, "/home/.snapshots/42/snapshot");
+        assert!(!snapshots[0].created_by_pulito && snapshots[1].created_by_pulito);
+
+        let newest = snapshots[1].created_at;
+        assert!(is_held("/home", newest - 60, &snapshots));
+        assert!(!is_held("/home", newest + 60, &snapshots));
+        assert!(!is_held("/", newest - 60, &snapshots));
+
+        let stale = stale_items(&snapshots, newest + 86400);
+        assert_eq!(stale.len(), 1);
+        assert_eq!((stale[0].id.as_str(), stale[0].risk_level), ("snapper:home:41", 3));
+    }
+}
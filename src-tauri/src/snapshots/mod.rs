@@ -0,0 +1,370 @@
+//! Pre-cleanup filesystem snapshots on btrfs.
+//!
+//! Before a high-risk cleanup plan runs, the UI can ask for a snapshot of the
+//! filesystem the plan touches. snapper is used when a config covers the path,
+//! timeshift for the root filesystem (through the privileged session when one is
+//! active), and a plain read-only `btrfs subvolume snapshot` otherwise. The snapshot
+//! is recorded in `cleanup_snapshots` and linked to the cleanup's audit entry once
+//! the destructive command is authorized, together with a rollback pointer.
+//! Snapshots already on the system, and what they keep from being freed, are in
+//! `existing`.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cache_redirect;
+use crate::db::DbAccess;
+use crate::operations::guard::TOKEN_TTL;
+use crate::operations::OperationPlan;
+use crate::privileged::{self, PrivilegedOp, PrivilegedOutput};
+use crate::read_only;
+
+pub mod existing;
+
+/// Directory (relative to the btrfs mount point) holding plain btrfs snapshots
+const SNAPSHOT_DIR_NAME: &str = ".pulito-snapshots";
+
+const SNAPSHOT_DESCRIPTION: &str = "Pulito: before cleanup";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SnapshotSupport {
+    pub target_path: String,
+    pub mount_point: Option<String>,
+    pub filesystem: Option<String>,
+    pub is_btrfs: bool,
+    /// btrfs or ZFS: files deleted there stay on disk while a snapshot holds them
+    #[serde(default)]
+    pub snapshot_capable: bool,
+    pub tools: Vec<String>,   // usable tools in preference order: "snapper", "timeshift", "btrfs"
+    pub snapper_config: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct CleanupSnapshot {
+    pub id: i64,
+    pub created_at: i64,
+    pub tool: String,
+    pub snapshot_ref: String, // snapper number, timeshift snapshot name or snapshot path
+    pub mount_point: String,
+    pub operation: String,
+    pub audit_id: Option<i64>, // operation_audit entry of the cleanup that followed
+    pub rollback_hint: String,
+}
+
+pub(crate) fn tool_available(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(name).is_file()))
+        .unwrap_or(false)
+}
+
+/// Parse `snapper list-configs` output (table or csv) into (config, subvolume) pairs
+pub fn parse_snapper_configs(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(['|', '│', ',']).map(str::trim);
+            let (config, subvolume) = (fields.next()?, fields.next()?);
+            if config.is_empty() || config == "Config" || config == "config" || !subvolume.starts_with('/') {
+                return None;
+            }
+            Some((config.to_string(), subvolume.to_string()))
+        })
+        .collect()
+}
+
+/// The snapper config whose subvolume most specifically contains `path`
+pub fn snapper_config_for(configs: &[(String, String)], path: &Path) -> Option<String> {
+    configs
+        .iter()
+        .filter(|(_, subvolume)| path.starts_with(subvolume))
+        .max_by_key(|(_, subvolume)| subvolume.len())
+        .map(|(config, _)| config.clone())
+}
+
+/// Snapshot name from `timeshift --create` output
+pub fn parse_timeshift_snapshot(output: &str) -> Option<String> {
+    for line in output.lines() {
+        if let Some(rest) = line.split("Tagged snapshot '").nth(1) {
+            return rest.split('\'').next().map(|s| s.to_string());
+        }
+    }
+    output
+        .lines()
+        .filter_map(|line| line.split("/snapshots/").nth(1))
+        .map(|rest| rest.trim().trim_end_matches('/').to_string())
+        .find(|name| !name.is_empty() && !name.contains('/'))
+}
+
+pub fn rollback_hint(tool: &str, snapshot_ref: &str, snapper_config: Option<&str>) -> String {
+    match tool {
+        "snapper" => format!(
+            "Restore changed files with: snapper -c {} undochange {}..0",
+            snapper_config.unwrap_or("root"),
+            snapshot_ref
+        ),
+        "timeshift" => format!("Restore with: sudo timeshift --restore --snapshot '{}'", snapshot_ref),
+        _ => format!("Read-only snapshot at {}; copy files back from it to restore them", snapshot_ref),
+    }
+}
+
+/// Path a plan's cleanup happens under: package cleanups touch the root filesystem,
+/// everything else the home directory
+fn target_path(plan: &OperationPlan) -> Result<PathBuf, String> {
+    match plan {
+        OperationPlan::CleanPackages
+        | OperationPlan::RemoveDisabledSnaps
+        | OperationPlan::VacuumJournal { .. }
+        | OperationPlan::RemoveCoredumps { .. }
+        | OperationPlan::RemoveOldKernels { .. } => Ok(PathBuf::from("/")),
+        // Images and volumes live in the daemon's storage, not in a snapshotted subvolume
+        OperationPlan::CleanDockerArtifacts { .. } => Err("Docker data is managed by the Docker daemon".to_string()),
+        OperationPlan::TerminateHolder { .. } => Err("Stopping a process removes nothing".to_string()),
+        OperationPlan::DeleteFilesystemSnapshots { .. } => Err("Removing snapshots is not undone with another one".to_string()),
+        _ => dirs::home_dir().ok_or_else(|| "Cannot determine home directory".to_string()),
+    }
+}
+
+fn detect_support(target: &Path) -> SnapshotSupport {
+    let mount = std::fs::read_to_string("/proc/mounts")
+        .ok()
+        .and_then(|mounts| cache_redirect::mount_entry(&mounts, target));
+    let is_btrfs = mount.as_ref().map(|m| m.fs_type == "btrfs").unwrap_or(false);
+    let snapshot_capable = mount.as_ref().is_some_and(|m| existing::snapshot_capable(&m.fs_type));
+
+    let mut tools = Vec::new();
+    let mut snapper_config = None;
+    if is_btrfs {
+        if tool_available("snapper") {
+            if let Ok(output) = Command::new("snapper").arg("list-configs").output() {
+                let configs = parse_snapper_configs(&String::from_utf8_lossy(&output.stdout));
+                snapper_config = snapper_config_for(&configs, target);
+            }
+            if snapper_config.is_some() {
+                tools.push("snapper".to_string());
+            }
+        }
+        // timeshift only snapshots the root filesystem
+        if tool_available("timeshift") && mount.as_ref().map(|m| m.mount_point == "/").unwrap_or(false) {
+            tools.push("timeshift".to_string());
+        }
+        if tool_available("btrfs") {
+            tools.push("btrfs".to_string());
+        }
+    }
+
+    SnapshotSupport {
+        target_path: target.to_string_lossy().to_string(),
+        mount_point: mount.as_ref().map(|m| m.mount_point.clone()),
+        filesystem: mount.map(|m| m.fs_type),
+        is_btrfs,
+        snapshot_capable,
+        tools,
+        snapper_config,
+    }
+}
+
+/// Snapshot support for the filesystem a plan touches
+pub fn support_for(plan: &OperationPlan) -> Option<SnapshotSupport> {
+    target_path(plan).ok().map(|target| detect_support(&target))
+}
+
+fn run_snapper(config: &str) -> Result<String, String> {
+    let output = Command::new("snapper")
+        .args(["-c", config, "create", "--type", "single", "--cleanup-algorithm", "number", "--print-number"])
+        .args(["--description", SNAPSHOT_DESCRIPTION])
+        .output()
+        .map_err(|e| format!("Failed to run snapper: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("snapper failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let number = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if number.is_empty() {
+        return Err("snapper did not report a snapshot number".to_string());
+    }
+    Ok(number)
+}
+
+fn run_timeshift(app_handle: &tauri::AppHandle) -> Result<String, String> {
+    let output = match privileged::run_in_session(app_handle, PrivilegedOp::TimeshiftCreate)? {
+        Some(output) => output,
+        None => {
+            let output = Command::new("pkexec")
+                .args(["timeshift", "--create", "--scripted", "--comments", SNAPSHOT_DESCRIPTION])
+                .output()
+                .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+            // pkexec exits with 126 when the user dismisses the authentication dialog
+            if output.status.code() == Some(126) {
+                return Err("Authentication was cancelled".to_string());
+            }
+            PrivilegedOutput::from(output)
+        }
+    };
+    if !output.success {
+        return Err(format!("timeshift failed: {}", output.stderr.trim()));
+    }
+    parse_timeshift_snapshot(&output.stdout).ok_or_else(|| "timeshift did not report a snapshot name".to_string())
+}
+
+fn run_btrfs(mount_point: &str) -> Result<String, String> {
+    let dir = Path::new(mount_point).join(SNAPSHOT_DIR_NAME);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let destination = dir.join(format!("pre-cleanup-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+
+    let output = Command::new("btrfs")
+        .args(["subvolume", "snapshot", "-r"])
+        .arg(mount_point)
+        .arg(&destination)
+        .output()
+        .map_err(|e| format!("Failed to run btrfs: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("btrfs snapshot failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(destination.to_string_lossy().to_string())
+}
+
+/// Mark snapshots taken for `fingerprint` as belonging to the cleanup authorized
+/// under audit entry `audit_id`. Called by the operation guard.
+pub fn link_to_audit(app_handle: &tauri::AppHandle, fingerprint: &str, audit_id: i64) {
+    let since = chrono::Utc::now().timestamp() - TOKEN_TTL.as_secs() as i64;
+    let result = app_handle.db(|conn| {
+        conn.execute(
+            "UPDATE cleanup_snapshots SET audit_id = ?1 WHERE plan_fingerprint = ?2 AND audit_id IS NULL AND created_at >= ?3",
+            rusqlite::params![audit_id, fingerprint, since],
+        )
+    });
+    if let Err(e) = result {
+        tracing::warn!("Failed to link snapshot to cleanup: {}", e);
+    }
+}
+
+fn row_to_snapshot(row: &rusqlite::Row) -> rusqlite::Result<CleanupSnapshot> {
+    Ok(CleanupSnapshot {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        tool: row.get(2)?,
+        snapshot_ref: row.get(3)?,
+        mount_point: row.get(4)?,
+        operation: row.get(5)?,
+        audit_id: row.get(6)?,
+        rollback_hint: row.get(7)?,
+    })
+}
+
+const SNAPSHOT_COLUMNS: &str = "id, created_at, tool, snapshot_ref, mount_point, operation, audit_id, rollback_hint";
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_snapshot_support(plan: OperationPlan) -> Result<SnapshotSupport, String> {
+    let target = target_path(&plan)?;
+    tokio::task::spawn_blocking(move || detect_support(&target))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Snapshot the filesystem `plan` touches. Call after `describe_operation` and
+/// before running the cleanup; the snapshot is linked to it when it is authorized.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn create_cleanup_snapshot(app_handle: tauri::AppHandle, plan: OperationPlan) -> Result<CleanupSnapshot, String> {
+    read_only::check("Creating snapshots")?;
+    let target = target_path(&plan)?;
+    let handle = app_handle.clone();
+
+    let (tool, snapshot_ref, mount_point, snapper_config) = tokio::task::spawn_blocking(move || {
+        let support = detect_support(&target);
+        let mount_point = support.mount_point.clone().ok_or("Cannot determine mount point")?;
+        if !support.is_btrfs {
+            return Err(format!("{} is not on btrfs", support.target_path));
+        }
+
+        let mut errors = Vec::new();
+        for tool in &support.tools {
+            let result = match tool.as_str() {
+                "snapper" => run_snapper(support.snapper_config.as_deref().unwrap_or("root")),
+                "timeshift" => run_timeshift(&handle),
+                _ => run_btrfs(&mount_point),
+            };
+            match result {
+                Ok(snapshot_ref) => return Ok((tool.clone(), snapshot_ref, mount_point, support.snapper_config.clone())),
+                Err(e) => {
+                    tracing::warn!("Snapshot with {} failed: {}", tool, e);
+                    errors.push(e);
+                }
+            }
+        }
+        if errors.is_empty() {
+            Err("No snapshot tool available (install snapper, timeshift or btrfs-progs)".to_string())
+        } else {
+            Err(errors.join("; "))
+        }
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))??;
+
+    let created_at = chrono::Utc::now().timestamp();
+    let hint = rollback_hint(&tool, &snapshot_ref, snapper_config.as_deref());
+    let fingerprint = plan.fingerprint();
+    let operation = plan.name();
+    tracing::info!("Created {} snapshot {} before {}", tool, snapshot_ref, operation);
+
+    app_handle.db(|conn| {
+        conn.execute(
+            "INSERT INTO cleanup_snapshots (created_at, tool, snapshot_ref, mount_point, operation, plan_fingerprint, rollback_hint)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![created_at, tool, snapshot_ref, mount_point, operation, fingerprint, hint],
+        )?;
+        conn.query_row(
+            &format!("SELECT {} FROM cleanup_snapshots WHERE id = ?1", SNAPSHOT_COLUMNS),
+            [conn.last_insert_rowid()],
+            row_to_snapshot,
+        )
+    }).map_err(|e| format!("Failed to record snapshot: {}", e))
+}
+
+/// Snapshots taken before cleanups, newest first, with their rollback pointers
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn list_cleanup_snapshots(app_handle: tauri::AppHandle) -> Result<Vec<CleanupSnapshot>, String> {
+    app_handle.db(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM cleanup_snapshots ORDER BY created_at DESC LIMIT 100",
+            SNAPSHOT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_snapshot)?;
+        rows.collect::<Result<Vec<_>, _>>()
+    }).map_err(|e| format!("Failed to list snapshots: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snapper_configs() {
+        let table = "Config | Subvolume\n-------+----------\nhome   | /home\nroot   | /\n";
+        let configs = parse_snapper_configs(table);
+        assert_eq!(configs, vec![("home".to_string(), "/home".to_string()), ("root".to_string(), "/".to_string())]);
+        assert_eq!(snapper_config_for(&configs, Path::new("/home/u")).as_deref(), Some("home"));
+        assert_eq!(snapper_config_for(&configs, Path::new("/var/cache")).as_deref(), Some("root"));
+
+        let unicode = "Config │ Subvolume\n───────┼──────────\nroot   │ /\n";
+        assert_eq!(parse_snapper_configs(unicode), vec![("root".to_string(), "/".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_timeshift_snapshot() {
+        let output = "Creating new backup...(BTRFS)\nSaving to device: /dev/sda2, mounted at path: /run/timeshift/backup\nCreated directory: /run/timeshift/backup/timeshift-btrfs/snapshots/2026-10-16_10-00-01\nTagged snapshot '2026-10-16_10-00-01': ondemand\n";
+        assert_eq!(parse_timeshift_snapshot(output).as_deref(), Some("2026-10-16_10-00-01"));
+        assert_eq!(parse_timeshift_snapshot("nothing here"), None);
+    }
+
+    #[test]
+    fn test_rollback_hint() {
+        assert!(rollback_hint("snapper", "42", Some("home")).contains("snapper -c home undochange 42..0"));
+        assert!(rollback_hint("timeshift", "2026-10-16_10-00-01", None).contains("--snapshot '2026-10-16_10-00-01'"));
+    }
+}
@@ -5,8 +5,14 @@ use std::fs;
 use std::io::Write;
 use dirs;
 use tokio::time::{timeout, Duration};
-use crate::commands::validate_path_comprehensive;
-use crate::commands::SecurityContext;
+use crate::path_validation::{validate_path_comprehensive, SecurityContext};
+use crate::boot;
+use crate::read_only;
+
+pub mod persistence;
+pub mod units;
+
+use persistence::SuspiciousProcess;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[specta(export)]
@@ -17,8 +23,10 @@ pub struct StartupProgram {
     pub enabled: bool,
     pub location: String, // "xdg_autostart", "systemd_user", "systemd_system"
     pub file_path: String,
-    pub impact: String, // "low", "medium", "high"
+    pub impact: String, // "low", "medium", "high", or "suspicious" when flagged
     pub exec_command: Option<String>,
+    #[serde(default)]
+    pub suspicion_reasons: Vec<String>, // see persistence::command_reasons
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -145,6 +153,8 @@ fn parse_desktop_file(path: PathBuf) -> Result<StartupProgram, String> {
         .replace(".", "_")
         .replace("-", "_"));
 
+    let suspicion_reasons = suspicion_reasons(exec.as_deref());
+
     Ok(StartupProgram {
         id,
         name,
@@ -152,8 +162,9 @@ fn parse_desktop_file(path: PathBuf) -> Result<StartupProgram, String> {
         enabled,
         location: "xdg_autostart".to_string(),
         file_path: path.to_string_lossy().to_string(),
-        impact: "medium".to_string(), // Default, could be enhanced
+        impact: impact_for(&suspicion_reasons),
         exec_command: exec,
+        suspicion_reasons,
     })
 }
 
@@ -198,6 +209,8 @@ async fn parse_service_file(path: PathBuf) -> Result<StartupProgram, String> {
 
     let id = format!("systemd_{}", name.replace(".", "_").replace("-", "_"));
 
+    let suspicion_reasons = suspicion_reasons(exec_start.as_deref());
+
     Ok(StartupProgram {
         id,
         name,
@@ -205,16 +218,55 @@ async fn parse_service_file(path: PathBuf) -> Result<StartupProgram, String> {
         enabled,
         location: "systemd_user".to_string(),
         file_path: path.to_string_lossy().to_string(),
-        impact: "medium".to_string(),
+        impact: impact_for(&suspicion_reasons),
         exec_command: exec_start,
+        suspicion_reasons,
     })
 }
 
+fn suspicion_reasons(exec: Option<&str>) -> Vec<String> {
+    match (exec, dirs::home_dir()) {
+        (Some(exec), Some(home)) => persistence::command_reasons(exec, &home),
+        _ => Vec::new(),
+    }
+}
+
+fn impact_for(suspicion_reasons: &[String]) -> String {
+    if suspicion_reasons.is_empty() {
+        "medium".to_string()
+    } else {
+        persistence::SUSPICIOUS_IMPACT.to_string()
+    }
+}
+
+/// Running processes that look like miners or dropped daemons, linked to the
+/// startup entry that launches them when one matches
+#[tauri::command]
+pub async fn get_suspicious_processes() -> Result<Vec<SuspiciousProcess>, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let startup_execs: Vec<(String, String)> = get_startup_programs()
+        .await
+        .map(|list| list.programs)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|p| {
+            let exe = persistence::executable_of(p.exec_command.as_deref()?)?.to_string();
+            Some((p.id, exe))
+        })
+        .collect();
+
+    tokio::task::spawn_blocking(move || persistence::find_suspicious_processes(&home, &startup_execs))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
 #[tauri::command]
 pub async fn toggle_startup_program(
+    app_handle: tauri::AppHandle,
     id: String,
     enabled: bool,
 ) -> Result<(), String> {
+    read_only::check("Changing startup programs")?;
     let timeout_duration = Duration::from_secs(5);
 
     timeout(timeout_duration, async {
@@ -229,17 +281,22 @@ pub async fn toggle_startup_program(
         validate_path_comprehensive(&program.file_path, SecurityContext::StartupManagement)
             .map_err(|e| format!("Security validation failed: {}", e))?;
 
-        match program.location.as_str() {
+        let unit = match program.location.as_str() {
             "xdg_autostart" => {
                 toggle_xdg_autostart(&program.file_path, enabled)?;
+                None
             }
             "systemd_user" => {
                 toggle_systemd_service(&program.name, enabled)?;
+                Some(program.name.as_str())
             }
             _ => {
                 return Err("Unsupported startup location".to_string());
             }
-        }
+        };
+
+        // Compared against later boots by boot::impact
+        boot::impact::log_change(&app_handle, &program.id, &program.name, unit, enabled);
 
         Ok(())
     })
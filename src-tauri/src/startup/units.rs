@@ -0,0 +1,212 @@
+//! Health of systemd user units: failed and masked units, their journal
+//! footprint, and the actions to restart, disable, unmask or reset them.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::process::{Command, Stdio};
+use tokio::time::{timeout, Duration};
+
+use crate::read_only;
+
+/// Unit types a user can have failed or masked
+const UNIT_SUFFIXES: &[&str] = &[".service", ".socket", ".timer", ".path", ".mount", ".target", ".scope", ".slice"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct UserUnitHealth {
+    pub unit: String,
+    pub state: String, // "failed" or "masked"
+    pub description: String,
+    pub result: Option<String>, // systemd Result= of the last run, e.g. "exit-code"
+    pub restarts: Option<u32>,
+    pub journal_bytes: u64, // size of the unit's journal entries in export format
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[specta(export)]
+pub enum UnitAction {
+    Restart,
+    Disable,
+    ResetFailed,
+    Unmask,
+}
+
+impl UnitAction {
+    fn args(&self) -> &'static [&'static str] {
+        match self {
+            UnitAction::Restart => &["restart"],
+            UnitAction::Disable => &["disable", "--now"],
+            UnitAction::ResetFailed => &["reset-failed"],
+            UnitAction::Unmask => &["unmask"],
+        }
+    }
+}
+
+/// Unit names as systemd accepts them; rejects anything that could be an option or a path
+pub fn is_valid_unit_name(unit: &str) -> bool {
+    !unit.starts_with('-')
+        && UNIT_SUFFIXES.iter().any(|s| unit.ends_with(s) && unit.len() > s.len())
+        && unit.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '.' | '@' | '-' | '\\'))
+}
+
+/// `systemctl --user list-units --failed --plain --no-legend`: unit load active sub description...
+pub fn parse_failed_units(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            // Some versions prefix failed units with a bullet
+            let line = line.trim_start_matches(['●', '*', ' ']);
+            let mut fields = line.split_whitespace();
+            let unit = fields.next()?.to_string();
+            let description = fields.skip(3).collect::<Vec<_>>().join(" ");
+            is_valid_unit_name(&unit).then_some((unit, description))
+        })
+        .collect()
+}
+
+/// `systemctl --user list-unit-files --state=masked --plain --no-legend`: unit state [preset]
+pub fn parse_masked_units(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let unit = fields.next()?;
+            (fields.next() == Some("masked") && is_valid_unit_name(unit)).then(|| unit.to_string())
+        })
+        .collect()
+}
+
+/// `systemctl show -p Result -p NRestarts`: KEY=value lines
+pub fn parse_show(output: &str) -> (Option<String>, Option<u32>) {
+    let mut result = None;
+    let mut restarts = None;
+    for line in output.lines() {
+        match line.split_once('=') {
+            Some(("Result", v)) if !v.is_empty() => result = Some(v.to_string()),
+            Some(("NRestarts", v)) => restarts = v.parse().ok(),
+            _ => {}
+        }
+    }
+    (result, restarts)
+}
+
+fn systemctl_user(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to execute systemctl: {}", e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn journal_bytes(unit: &str) -> u64 {
+    Command::new("journalctl")
+        .args(["--user", "--unit", unit, "--output", "export", "--no-pager"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map(|o| o.stdout.len() as u64)
+        .unwrap_or(0)
+}
+
+fn collect_unit_health() -> Result<Vec<UserUnitHealth>, String> {
+    let failed = parse_failed_units(&systemctl_user(&["list-units", "--failed", "--all", "--plain", "--no-legend"])?);
+    let masked = parse_masked_units(&systemctl_user(&["list-unit-files", "--state=masked", "--plain", "--no-legend"])?);
+
+    let mut units = Vec::new();
+    for (unit, description) in failed {
+        let (result, restarts) = parse_show(&systemctl_user(&["show", "-p", "Result", "-p", "NRestarts", "--", &unit])?);
+        units.push(UserUnitHealth {
+            journal_bytes: journal_bytes(&unit),
+            unit,
+            state: "failed".to_string(),
+            description,
+            result,
+            restarts,
+        });
+    }
+    for unit in masked {
+        units.push(UserUnitHealth {
+            journal_bytes: journal_bytes(&unit),
+            unit,
+            state: "masked".to_string(),
+            description: String::new(),
+            result: None,
+            restarts: None,
+        });
+    }
+    units.sort_by(|a, b| b.journal_bytes.cmp(&a.journal_bytes));
+    Ok(units)
+}
+
+/// Failed and masked systemd user units with their journal footprint
+#[tauri::command]
+pub async fn get_user_unit_health() -> Result<Vec<UserUnitHealth>, String> {
+    let task = tokio::task::spawn_blocking(collect_unit_health);
+    timeout(Duration::from_secs(30), task)
+        .await
+        .map_err(|_| "Timeout checking systemd user units".to_string())?
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn manage_user_unit(unit: String, action: UnitAction) -> Result<(), String> {
+    read_only::check("Changing user services")?;
+    if !is_valid_unit_name(&unit) {
+        return Err(format!("Invalid unit name: {}", unit));
+    }
+
+    let task = tokio::task::spawn_blocking(move || {
+        let output = Command::new("systemctl")
+            .arg("--user")
+            .args(action.args())
+            .arg("--")
+            .arg(&unit)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to execute systemctl: {}", e))?;
+        if output.status.success() {
+            tracing::info!("systemctl --user {:?} {}", action, unit);
+            Ok(())
+        } else {
+            Err(format!("systemctl failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+        }
+    });
+    timeout(Duration::from_secs(30), task)
+        .await
+        .map_err(|_| "Timeout managing systemd user unit".to_string())?
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_name_validation() {
+        assert!(is_valid_unit_name("syncthing.service"));
+        assert!(is_valid_unit_name("app-org.gnome.Evince@12.service"));
+        assert!(!is_valid_unit_name("--now"));
+        assert!(!is_valid_unit_name(".service"));
+        assert!(!is_valid_unit_name("../evil.service"));
+        assert!(!is_valid_unit_name("foo; rm -rf ~"));
+    }
+
+    #[test]
+    fn test_parse_failed_units() {
+        let output = "● pipewire-media.service loaded failed failed PipeWire Media Session\n\
+                      tracker-miner.service loaded failed failed Tracker file system data miner\n";
+        let units = parse_failed_units(output);
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0], ("pipewire-media.service".to_string(), "PipeWire Media Session".to_string()));
+    }
+
+    #[test]
+    fn test_parse_masked_and_show() {
+        let masked = parse_masked_units("gvfs-daemon.service masked enabled\nfoo.service enabled enabled\n");
+        assert_eq!(masked, vec!["gvfs-daemon.service".to_string()]);
+        assert_eq!(parse_show("Result=exit-code\nNRestarts=5\n"), (Some("exit-code".to_string()), Some(5)));
+    }
+}
@@ -0,0 +1,429 @@
+//! Support bundles: a zip to attach to a bug report, with recent log lines,
+//! diagnostics, settings, database statistics and the last scan summaries.
+//! `preview_support_bundle` gathers and anonymizes everything and returns it
+//! for review; `create_support_bundle` then writes exactly the reviewed files,
+//! minus any the user left out. Home paths and the user name are replaced and
+//! values under secret-looking keys are dropped before anything is shown.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use specta::Type;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::db::DbAccess;
+
+pub mod zip;
+
+/// Log lines kept for bundles; the oldest are dropped first
+const LOG_CAPACITY: usize = 1_000;
+
+/// Hours of kernel log searched for disk errors
+const DISK_ERROR_HOURS: u32 = 7 * 24;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Keys whose values never leave the machine
+const SECRET_KEYS: &[&str] = &["token", "password", "secret", "credential", "api_key"];
+
+lazy_static::lazy_static! {
+    static ref RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(LOG_CAPACITY));
+    static ref PREVIEW: Mutex<Option<SupportBundlePreview>> = Mutex::new(None);
+}
+
+/// Tracing writer that keeps the latest lines in memory for bundles
+pub struct RecentLogWriter;
+
+impl Write for RecentLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        let mut lines = RECENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            if lines.len() == LOG_CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `MakeWriter` for the log layer that feeds support bundles
+pub fn recent_log_writer() -> RecentLogWriter {
+    RecentLogWriter
+}
+
+/// One file of a bundle, as it will be written
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SupportBundleFile {
+    pub name: String, // path inside the zip
+    pub description: String,
+    pub size: u64,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SupportBundlePreview {
+    pub id: String, // pass to create_support_bundle to write these files
+    pub files: Vec<SupportBundleFile>,
+    pub redactions: Vec<String>, // what was replaced or removed, in words
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct SupportBundle {
+    pub path: String,
+    pub files: Vec<String>,
+    pub size: u64,
+}
+
+/// Replaces what identifies the user: the home directory, the user name and secrets
+struct Anonymizer {
+    home: String,
+    user: String,
+}
+
+impl Anonymizer {
+    fn current() -> Anonymizer {
+        let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+        let user = std::env::var("USER")
+            .ok()
+            .or_else(|| Path::new(&home).file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        Anonymizer { home, user }
+    }
+
+    fn text(&self, text: &str) -> String {
+        let mut text = if self.home.len() > 1 { text.replace(&self.home, "~") } else { text.to_string() };
+        // Short names would match inside ordinary words
+        if self.user.len() >= 3 {
+            text = replace_word(&text, &self.user, "<user>");
+        }
+        text
+    }
+
+    fn value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.text(s),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.value(v)),
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    let key = key.to_lowercase();
+                    if SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+                        *v = Value::String("<redacted>".to_string());
+                    } else {
+                        self.value(v);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn redactions(&self) -> Vec<String> {
+        let mut redactions = Vec::new();
+        if self.home.len() > 1 {
+            redactions.push(format!("Home directory {} shown as ~", self.home));
+        }
+        if self.user.len() >= 3 {
+            redactions.push(format!("User name {} shown as <user>", self.user));
+        }
+        redactions.push("Values of token, password and other secret settings removed".to_string());
+        redactions
+    }
+}
+
+/// Replace whole-word occurrences of `word`, leaving it alone inside longer words
+fn replace_word(text: &str, word: &str, replacement: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find(word) {
+        let before = rest[..at].chars().next_back();
+        let after = rest[at + word.len()..].chars().next();
+        out.push_str(&rest[..at]);
+        if before.map_or(true, |c| !is_word(c)) && after.map_or(true, |c| !is_word(c)) {
+            out.push_str(replacement);
+        } else {
+            out.push_str(word);
+        }
+        rest = &rest[at + word.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn os_release() -> Option<String> {
+    let content = std::fs::read_to_string("/etc/os-release").ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|name| name.trim_matches('"').to_string())
+}
+
+fn database_stats(conn: &Connection, db_path: Option<&Path>) -> rusqlite::Result<Value> {
+    let tables: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut rows = serde_json::Map::new();
+    for table in tables {
+        let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| row.get(0))?;
+        rows.insert(table, json!(count));
+    }
+    let pragma = |name: &str| conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0));
+    Ok(json!({
+        "file_size": db_path.and_then(|p| p.metadata().ok()).map(|m| m.len()),
+        "page_size": pragma("page_size")?,
+        "page_count": pragma("page_count")?,
+        "freelist_count": pragma("freelist_count")?,
+        "rows": rows,
+    }))
+}
+
+/// What each kind of scan last found; the item lists themselves stay out
+fn last_scans(conn: &Connection) -> rusqlite::Result<Value> {
+    let scans: Vec<Value> = conn
+        .prepare("SELECT scan_type, total_size, total_items, timestamp FROM last_scan_results ORDER BY timestamp DESC")?
+        .query_map([], |row| {
+            Ok(json!({
+                "scan_type": row.get::<_, String>(0)?,
+                "total_size": row.get::<_, i64>(1)?,
+                "total_items": row.get::<_, i64>(2)?,
+                "timestamp": row.get::<_, i64>(3)?,
+            }))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(Value::Array(scans))
+}
+
+fn diagnostics(performance: crate::metrics::PerformanceMetrics) -> Value {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    json!({
+        "pulito_version": env!("CARGO_PKG_VERSION"),
+        "os": os_release(),
+        "kernel": std::fs::read_to_string("/proc/sys/kernel/osrelease").ok().map(|r| r.trim().to_string()),
+        "desktop": std::env::var("XDG_CURRENT_DESKTOP").ok(),
+        "session_type": std::env::var("XDG_SESSION_TYPE").ok(),
+        "cpu_cores": std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        "total_memory": system.total_memory(),
+        "time": crate::timestamps::time_context(),
+        "read_only": crate::read_only::is_enabled(),
+        "safe_mode": crate::safe_mode::is_enabled(),
+        "mounts": crate::mounts::list_mounts(),
+        "recent_disk_errors": crate::disk_health::recent_disk_errors(DISK_ERROR_HOURS),
+        "performance": performance,
+        "backend_health": crate::watchdog::status(),
+    })
+}
+
+fn json_file(name: &str, description: &str, mut value: Value, anonymizer: &Anonymizer) -> SupportBundleFile {
+    anonymizer.value(&mut value);
+    let content = serde_json::to_string_pretty(&value).unwrap_or_default();
+    SupportBundleFile { name: name.to_string(), description: description.to_string(), size: content.len() as u64, content }
+}
+
+fn gather(app_handle: &tauri::AppHandle, performance: crate::metrics::PerformanceMetrics) -> Vec<SupportBundleFile> {
+    let anonymizer = Anonymizer::current();
+    let failed = |what: &str, e: &dyn std::fmt::Display| json!({ "error": format!("Failed to read {}: {}", what, e) });
+
+    let logs: Vec<String> = RECENT_LOGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|line| anonymizer.text(line))
+        .collect();
+    let mut log_content = logs.join("\n");
+    log_content.push('\n');
+
+    // Reading these would wait behind a stuck query, which diagnostics.json reports instead
+    let (settings, database, scans) = if crate::watchdog::database_blocked() {
+        let skipped = json!({ "error": "Skipped: the database is held by a stuck query" });
+        (skipped.clone(), skipped.clone(), skipped)
+    } else {
+        let settings = serde_json::to_value(crate::commands::load_settings(app_handle)).unwrap_or(Value::Null);
+        let db_path = crate::db::get_db_path(app_handle).ok();
+        let database = app_handle
+            .db(|conn| database_stats(conn, db_path.as_deref()))
+            .unwrap_or_else(|e| failed("database statistics", &e));
+        let scans = app_handle.db(last_scans).unwrap_or_else(|e| failed("scan summaries", &e));
+        (settings, database, scans)
+    };
+
+    vec![
+        SupportBundleFile {
+            name: "logs/pulito.log".to_string(),
+            description: format!("The last {} log lines of this session", logs.len()),
+            size: log_content.len() as u64,
+            content: log_content,
+        },
+        json_file(
+            "diagnostics.json",
+            "Versions, memory, mounted volumes, recent disk errors, command timings and backend health",
+            diagnostics(performance),
+            &anonymizer,
+        ),
+        json_file("settings.json", "Your settings", settings, &anonymizer),
+        json_file("database.json", "Database size and row counts per table, no contents", database, &anonymizer),
+        json_file("last_scans.json", "Totals of the most recent scans, without the files found", scans, &anonymizer),
+    ]
+}
+
+/// The manifest listing a bundle's files, written into it last
+fn manifest(files: &[&SupportBundleFile], created_at: i64) -> Vec<u8> {
+    let listing: Vec<Value> = files
+        .iter()
+        .map(|f| json!({ "name": f.name, "description": f.description, "size": f.size }))
+        .collect();
+    serde_json::to_vec_pretty(&json!({
+        "pulito_version": env!("CARGO_PKG_VERSION"),
+        "created_at": created_at,
+        "files": listing,
+    }))
+    .unwrap_or_default()
+}
+
+fn default_bundle_path(now: chrono::DateTime<chrono::Local>) -> PathBuf {
+    let dir = dirs::download_dir().or_else(dirs::home_dir).unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("pulito-support-{}.zip", now.format("%Y%m%d-%H%M%S")))
+}
+
+/// Write the reviewed `files` (except `exclude`) as a zip at `path`, via a partial file
+fn write_bundle(path: &Path, files: &[SupportBundleFile], exclude: &[String]) -> Result<SupportBundle, String> {
+    let now = chrono::Local::now();
+    let included: Vec<&SupportBundleFile> = files.iter().filter(|f| !exclude.contains(&f.name)).collect();
+    let mut entries: Vec<(String, Vec<u8>)> =
+        included.iter().map(|f| (f.name.clone(), f.content.clone().into_bytes())).collect();
+    entries.push((MANIFEST.to_string(), manifest(&included, now.timestamp())));
+
+    let mut archive = Vec::new();
+    zip::write_zip(&mut archive, &entries, now.naive_local()).map_err(|e| format!("Failed to build bundle: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let partial = path.with_file_name(format!(".{}.partial", name));
+    std::fs::write(&partial, &archive)
+        .and_then(|_| std::fs::rename(&partial, path))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&partial);
+            format!("Failed to write {}: {}", path.display(), e)
+        })?;
+
+    Ok(SupportBundle {
+        path: path.to_string_lossy().to_string(),
+        files: entries.into_iter().map(|(name, _)| name).collect(),
+        size: archive.len() as u64,
+    })
+}
+
+/// Gather and anonymize a bundle's contents for review; nothing is written yet
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn preview_support_bundle(app_handle: tauri::AppHandle) -> Result<SupportBundlePreview, String> {
+    let performance = crate::metrics::get_performance_metrics().await?;
+    let preview = tokio::task::spawn_blocking(move || SupportBundlePreview {
+        id: uuid::Uuid::new_v4().to_string(),
+        files: gather(&app_handle, performance),
+        redactions: Anonymizer::current().redactions(),
+    })
+    .await
+    .map_err(|e| format!("Failed to gather support bundle: {}", e))?;
+
+    *PREVIEW.lock().unwrap_or_else(|e| e.into_inner()) = Some(preview.clone());
+    Ok(preview)
+}
+
+/// Write the files of the reviewed preview `preview_id` to a zip, leaving out
+/// those named in `exclude`. Without `output_path` it goes to the Downloads folder.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn create_support_bundle(
+    preview_id: String,
+    exclude: Option<Vec<String>>,
+    output_path: Option<String>,
+) -> Result<SupportBundle, String> {
+    let files = match PREVIEW.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(preview) if preview.id == preview_id => preview.files.clone(),
+        _ => return Err("This support bundle preview has expired; review it again before saving".to_string()),
+    };
+    let path = match output_path {
+        Some(path) => crate::scanner::glob::expand_home(path.trim(), &dirs::home_dir().unwrap_or_default()),
+        None => default_bundle_path(chrono::Local::now()),
+    };
+
+    let bundle = tokio::task::spawn_blocking(move || write_bundle(&path, &files, &exclude.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Failed to write support bundle: {}", e))??;
+    tracing::info!("Support bundle with {} files written to {}", bundle.files.len(), bundle.path);
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anonymizer() -> Anonymizer {
+        Anonymizer { home: "/home/dana".to_string(), user: "dana".to_string() }
+    }
+
+    #[test]
+    fn test_anonymize_text() {
+        let text = "Scanning /home/dana/.cache for dana (owner dana_x, in /srv/dana/data, mundane)";
+        assert_eq!(anonymizer().text(text), "Scanning ~/.cache for <user> (owner dana_x, in /srv/<user>/data, mundane)");
+    }
+
+    #[test]
+    fn test_anonymize_value() {
+        let mut value = json!({
+            "projects": { "roots": ["/home/dana/code"] },
+            "remote": { "api_token": "abc123", "Password": "hunter2" },
+            "theme": "dark",
+            "retention_days": 7,
+        });
+        anonymizer().value(&mut value);
+        assert_eq!(value["projects"]["roots"][0], "~/code");
+        assert_eq!(value["remote"]["api_token"], "<redacted>");
+        assert_eq!(value["remote"]["Password"], "<redacted>");
+        assert_eq!(value["theme"], "dark");
+        assert_eq!(value["retention_days"], 7);
+    }
+
+    #[test]
+    fn test_write_bundle_excludes_and_adds_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = |name: &str| SupportBundleFile {
+            name: name.to_string(),
+            description: String::new(),
+            size: 2,
+            content: "{}".to_string(),
+        };
+        let files = vec![file("settings.json"), file("diagnostics.json")];
+        let path = dir.path().join("bundle.zip");
+
+        let bundle = write_bundle(&path, &files, &["settings.json".to_string()]).unwrap();
+        assert_eq!(bundle.files, vec!["diagnostics.json".to_string(), MANIFEST.to_string()]);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), bundle.size);
+        assert!(!dir.path().join(".bundle.zip.partial").exists());
+    }
+
+    #[test]
+    fn test_database_stats() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE notes (id INTEGER); INSERT INTO notes VALUES (1), (2);").unwrap();
+        let stats = database_stats(&conn, None).unwrap();
+        assert_eq!(stats["rows"]["notes"], 2);
+        assert!(stats["file_size"].is_null());
+    }
+}
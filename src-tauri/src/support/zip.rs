@@ -0,0 +1,132 @@
+//! Just enough of the ZIP format to write a support bundle: stored (not
+//! compressed) entries with UTF-8 names, which every unzip tool reads.
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use std::io::{self, Write};
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+const VERSION: u16 = 20;
+const UTF8_NAMES: u16 = 1 << 11;
+const UNIX_FILE_MODE: u32 = 0o100644 << 16;
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// MS-DOS date and time, the only timestamps the base format has
+fn dos_datetime(at: NaiveDateTime) -> (u16, u16) {
+    let time = ((at.hour() << 11) | (at.minute() << 5) | (at.second() / 2)) as u16;
+    let date = (((at.year().clamp(1980, 2107) - 1980) as u32) << 9) | (at.month() << 5) | at.day();
+    (date as u16, time)
+}
+
+fn too_large(what: &str) -> io::Error {
+    io::Error::other(format!("{} is too large for a zip file", what))
+}
+
+/// Write `entries` (name, content) as a zip archive, every entry dated `modified`
+pub fn write_zip(out: &mut impl Write, entries: &[(String, Vec<u8>)], modified: NaiveDateTime) -> io::Result<()> {
+    let (date, time) = dos_datetime(modified);
+    let count = u16::try_from(entries.len()).map_err(|_| too_large("Entry count"))?;
+    let mut central = Vec::new();
+    let mut offset: u32 = 0;
+
+    for (name, data) in entries {
+        let size = u32::try_from(data.len()).map_err(|_| too_large(name))?;
+        let name_len = u16::try_from(name.len()).map_err(|_| too_large(name))?;
+        let crc = crc32(data);
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        for field in [VERSION, UTF8_NAMES, 0, time, date] {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        header.extend_from_slice(&name_len.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        out.write_all(&header)?;
+        out.write_all(data)?;
+
+        central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        for field in [VERSION, VERSION, UTF8_NAMES, 0, time, date] {
+            central.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            central.extend_from_slice(&field.to_le_bytes());
+        }
+        // name length, extra and comment lengths, disk number, internal attributes
+        for field in [name_len, 0, 0, 0, 0] {
+            central.extend_from_slice(&field.to_le_bytes());
+        }
+        central.extend_from_slice(&UNIX_FILE_MODE.to_le_bytes());
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+
+        offset = u32::try_from(header.len() + data.len())
+            .ok()
+            .and_then(|written| offset.checked_add(written))
+            .ok_or_else(|| too_large("Bundle"))?;
+    }
+
+    let central_size = u32::try_from(central.len()).map_err(|_| too_large("Directory"))?;
+    out.write_all(&central)?;
+    let mut end = Vec::with_capacity(22);
+    end.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+    for field in [0, 0, count, count] {
+        end.extend_from_slice(&field.to_le_bytes());
+    }
+    end.extend_from_slice(&central_size.to_le_bytes());
+    end.extend_from_slice(&offset.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes());
+    out.write_all(&end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_write_zip_layout() {
+        let at = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(12, 30, 10).unwrap();
+        let entries = vec![("a.txt".to_string(), b"hello".to_vec()), ("logs/b.log".to_string(), Vec::new())];
+        let mut zip = Vec::new();
+        write_zip(&mut zip, &entries, at).unwrap();
+
+        let u16_at = |i: usize| u16::from_le_bytes([zip[i], zip[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(zip[i..i + 4].try_into().unwrap());
+        assert_eq!(u32_at(0), LOCAL_HEADER);
+        assert_eq!(u32_at(14), crc32(b"hello"));
+        assert_eq!(&zip[30..35], b"a.txt");
+        assert_eq!(&zip[35..40], b"hello");
+        assert_eq!(u32_at(40), LOCAL_HEADER);
+
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(end), END_OF_DIRECTORY);
+        assert_eq!(u16_at(end + 10), 2);
+        let directory = u32_at(end + 16) as usize;
+        assert_eq!(u32_at(directory), CENTRAL_HEADER);
+        assert_eq!(directory + u32_at(end + 12) as usize, end);
+        // The second entry's local header is where the directory says
+        let second = directory + 46 + "a.txt".len();
+        assert_eq!(u32_at(second + 42), 40);
+        assert_eq!(dos_datetime(at), ((44 << 9) | (3 << 5) | 1, (12 << 11) | (30 << 5) | 5));
+    }
+}
@@ -4,12 +4,60 @@ use specta::{TypeCollection, Type};
 use specta_typescript::Typescript;
 
 // Include the actual modules
+mod agent;
+mod archive;
+mod batch;
+mod boot;
+mod browsers;
 mod cache;
+mod cache_redirect;
+mod coalesce;
 mod commands;
+mod coredumps;
+mod detached;
 mod db;
+mod digest;
+mod disk_health;
+mod docker;
+mod electron;
+mod flatpak;
+mod footprint;
+mod ignores;
+mod installers;
+mod jobs;
+mod journal;
+mod leaderboard;
+mod leftovers;
+mod libraries;
+mod maintenance;
+mod metrics;
+mod monitoring;
+mod mounts;
+mod notifications;
+mod operations;
+mod output;
 mod packages;
+mod path_validation;
+mod power;
+mod privileged;
+mod profiles;
+mod projects;
+mod read_only;
+mod recovery;
+mod reclaim;
+mod safe_mode;
 mod scanner;
+mod shortcuts;
+mod shutdown;
+mod snaps;
+mod snapshots;
+mod support;
+mod thumbnails;
+mod timestamps;
 mod trash;
+mod watchdog;
+mod wine;
+mod xdg_trash;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[specta(export)]
@@ -21,6 +69,7 @@ pub struct TestStruct {
 fn main() {
     let mut collection = TypeCollection::default();
     collection.register::<commands::SystemStats>();
+    collection.register::<mounts::MountStats>();
     collection.register::<commands::AppSettings>();
     collection.register::<scanner::ScanItem>();
     let types = collection;
@@ -0,0 +1,257 @@
+//! Thumbnails whose original is gone. Desktop thumbnailers follow the
+//! freedesktop.org thumbnail spec: every PNG in `~/.cache/thumbnails` carries the
+//! original's URI in a `Thumb::URI` text chunk. Only thumbnails whose original no
+//! longer exists are listed, so the previews of files still around survive and
+//! don't have to be regenerated.
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Thumbnails";
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Text chunks come before the image data; give up on anything that claims more
+const MAX_TEXT_CHUNK: u32 = 64 * 1024;
+/// Where removable drives are mounted; their files vanish while unplugged
+const REMOVABLE_MOUNT_DIRS: &[&str] = &["/media", "/run/media", "/mnt"];
+
+/// Thumbnail folders: the XDG location and the one used before it
+pub fn thumbnail_dirs(home: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![dirs::cache_dir().unwrap_or_else(|| home.join(".cache")).join("thumbnails"), home.join(".thumbnails")];
+    dirs.dedup();
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// The `Thumb::URI` of a thumbnail, read from the PNG text chunks in front of the image data
+pub fn thumbnail_uri(reader: &mut (impl Read + Seek)) -> Option<String> {
+    let mut signature = [0u8; 8];
+    reader.read_exact(&mut signature).ok()?;
+    if signature != PNG_SIGNATURE {
+        return None;
+    }
+    loop {
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).ok()?;
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        match &header[4..] {
+            b"IDAT" | b"IEND" => return None,
+            b"tEXt" if length <= MAX_TEXT_CHUNK => {
+                let mut data = vec![0u8; length as usize];
+                reader.read_exact(&mut data).ok()?;
+                if let Some(uri) = data.strip_prefix(b"Thumb::URI\0") {
+                    return Some(String::from_utf8_lossy(uri).to_string());
+                }
+                reader.seek(SeekFrom::Current(4)).ok()?; // CRC
+            }
+            _ => {
+                reader.seek(SeekFrom::Current(length as i64 + 4)).ok()?;
+            }
+        }
+    }
+}
+
+/// Local path of a `file://` URI; other schemes (trash, network shares) have none
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !rest.starts_with('/') {
+        return None;
+    }
+    let bytes = rest.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(PathBuf::from(OsString::from_vec(decoded)))
+}
+
+/// Whether a thumbnail's original is known to be gone. Originals on removable
+/// drives whose folder is missing may just be unplugged, so they count as present.
+pub fn is_orphaned(original: &Path) -> bool {
+    if original.symlink_metadata().is_ok() {
+        return false;
+    }
+    let removable = REMOVABLE_MOUNT_DIRS.iter().any(|dir| original.starts_with(dir));
+    !(removable && original.parent().is_some_and(|parent| !parent.exists()))
+}
+
+/// Thumbnail of a missing original
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrphanedThumbnail {
+    pub path: PathBuf,
+    pub original: PathBuf,
+    pub size: u64,
+}
+
+/// Orphaned thumbnails below `dir` by the size folder they are in (`normal`,
+/// `large`, `fail/gnome-thumbnail-factory`, ...)
+pub fn find_orphans(dir: &Path, ctx: Option<&ScanContext>) -> Result<Vec<(PathBuf, Vec<OrphanedThumbnail>)>, ScannerError> {
+    let mut folders: Vec<(PathBuf, Vec<OrphanedThumbnail>)> = Vec::new();
+    for entry in WalkDir::new(dir).min_depth(2).max_depth(3).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() || !entry.path().extension().is_some_and(|ext| ext == "png") {
+            continue;
+        }
+        if let Some(ctx) = ctx {
+            ctx.check_cancelled()?;
+            ctx.pace();
+        }
+        let Ok(file) = File::open(entry.path()) else { continue };
+        let Some(original) = thumbnail_uri(&mut BufReader::new(file)).as_deref().and_then(uri_to_path) else {
+            continue;
+        };
+        if !is_orphaned(&original) {
+            continue;
+        }
+        let folder = entry.path().parent().unwrap_or(dir).to_path_buf();
+        let thumbnail = OrphanedThumbnail {
+            path: entry.path().to_path_buf(),
+            original,
+            size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+        };
+        match folders.iter_mut().find(|(f, _)| *f == folder) {
+            Some((_, orphans)) => orphans.push(thumbnail),
+            None => folders.push((folder, vec![thumbnail])),
+        }
+    }
+    Ok(folders)
+}
+
+pub struct StaleThumbnailScanner;
+
+impl Scanner for StaleThumbnailScanner {
+    fn id(&self) -> &'static str {
+        "stale_thumbnails"
+    }
+
+    fn name(&self) -> &'static str {
+        "Stale thumbnails"
+    }
+
+    fn description(&self) -> &'static str {
+        "Thumbnails of files that no longer exist"
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_caches && dirs::home_dir().is_some_and(|home| !thumbnail_dirs(&home).is_empty())
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+
+        let mut items = Vec::new();
+        for dir in thumbnail_dirs(&home) {
+            if !ctx.enters(&dir) {
+                continue;
+            }
+            ctx.progress(0, &format!("Matching thumbnails in {} to their originals...", dir.display()));
+            for (folder, orphans) in find_orphans(&dir, Some(ctx))? {
+                let children: Vec<ScanItem> = orphans
+                    .iter()
+                    .filter(|orphan| ctx.allows(&orphan.path))
+                    .map(|orphan| new_item(
+                        orphan.original.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                        &orphan.path,
+                        orphan.size,
+                        "file",
+                        CATEGORY,
+                        0,
+                        &format!("Thumbnail of {}, which no longer exists", orphan.original.display()),
+                    ))
+                    .collect();
+                if children.is_empty() {
+                    continue;
+                }
+                let label = folder.strip_prefix(&dir).unwrap_or(&folder).to_string_lossy().to_string();
+                // The folder itself also holds thumbnails of existing files; only the children are removed
+                let mut item = new_item(
+                    format!("Orphaned thumbnails ({})", label),
+                    &folder,
+                    children.iter().map(|c| c.size).sum(),
+                    "thumbnails",
+                    CATEGORY,
+                    0,
+                    &format!("{} thumbnails of deleted or moved files", children.len()),
+                );
+                item.children = Some(children);
+                items.push(item);
+            }
+        }
+        items.sort_by(|a, b| b.size.cmp(&a.size));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Cursor;
+
+    fn chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&[0; 4]);
+        chunk
+    }
+
+    fn thumbnail(uri: &str) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(chunk(b"IHDR", &[0; 13]));
+        png.extend(chunk(b"tEXt", b"Software\0GNOME::ThumbnailFactory"));
+        png.extend(chunk(b"tEXt", format!("Thumb::URI\0{}", uri).as_bytes()));
+        png.extend(chunk(b"IDAT", &[0; 8]));
+        png
+    }
+
+    #[test]
+    fn test_thumbnail_uri() {
+        let uri = "file:///home/u/Pictures/My%20Trip/a.jpg";
+        assert_eq!(thumbnail_uri(&mut Cursor::new(thumbnail(uri))).as_deref(), Some(uri));
+        assert_eq!(uri_to_path(uri), Some(PathBuf::from("/home/u/Pictures/My Trip/a.jpg")));
+        assert_eq!(uri_to_path("trash:///a.jpg"), None);
+        assert_eq!(thumbnail_uri(&mut Cursor::new(b"GIF89a".to_vec())), None);
+
+        let mut without_uri = PNG_SIGNATURE.to_vec();
+        without_uri.extend(chunk(b"IDAT", &[0; 8]));
+        without_uri.extend(chunk(b"tEXt", b"Thumb::URI\0file:///late.jpg"));
+        assert_eq!(thumbnail_uri(&mut Cursor::new(without_uri)), None);
+    }
+
+    #[test]
+    fn test_only_orphans_are_found() {
+        let root = tempfile::TempDir::new().unwrap();
+        let photo = root.path().join("photo.jpg");
+        fs::write(&photo, "jpeg").unwrap();
+        let cache = root.path().join("thumbnails");
+        fs::create_dir_all(cache.join("large")).unwrap();
+        fs::create_dir_all(cache.join("fail/gnome-thumbnail-factory")).unwrap();
+        let uri = |path: &Path| format!("file://{}", path.display());
+        fs::write(cache.join("large/a.png"), thumbnail(&uri(&photo))).unwrap();
+        fs::write(cache.join("large/b.png"), thumbnail(&uri(&root.path().join("deleted.jpg")))).unwrap();
+        fs::write(cache.join("fail/gnome-thumbnail-factory/c.png"), thumbnail(&uri(&root.path().join("broken.pdf")))).unwrap();
+        fs::write(cache.join("large/d.png"), thumbnail("file:///media/u/USB/gone/e.jpg")).unwrap();
+
+        let found = find_orphans(&cache, None).unwrap();
+        let paths: Vec<Vec<PathBuf>> = found.iter().map(|(_, orphans)| orphans.iter().map(|o| o.path.clone()).collect()).collect();
+        assert_eq!(paths, vec![vec![cache.join("fail/gnome-thumbnail-factory/c.png")], vec![cache.join("large/b.png")]]);
+        assert_eq!(found[1].0, cache.join("large"));
+    }
+}
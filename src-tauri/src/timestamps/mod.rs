@@ -0,0 +1,231 @@
+//! Points in time as stored by Pulito: UTC unix seconds, everywhere it keeps
+//! them (SQLite columns, trash metadata). Older releases also wrote RFC 3339
+//! strings and unix milliseconds; `deserialize` and `parse_legacy` read all
+//! three so existing data keeps loading. Conversion to the user's time zone
+//! and locale happens only on the way out, in `localize_timestamps`.
+
+use chrono::{DateTime, FixedOffset, Local, Offset, TimeZone, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use specta::Type;
+
+/// Values this large can only be milliseconds: as seconds they'd be past the year 5000
+pub const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Current time in UTC unix seconds
+pub fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+/// Unix seconds for a stored number that may be in milliseconds
+pub fn normalize(value: i64) -> i64 {
+    if value.abs() >= MILLIS_THRESHOLD {
+        value / 1000
+    } else {
+        value
+    }
+}
+
+/// Unix seconds for a stored string: RFC 3339 or a plain number of seconds or
+/// milliseconds. None for anything else, including the empty string.
+pub fn parse_legacy(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<i64>() {
+        return Some(normalize(number));
+    }
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.timestamp())
+}
+
+pub fn to_datetime(timestamp: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(timestamp, 0).single().unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    Seconds(i64),
+    Fractional(f64),
+    Text(String),
+}
+
+/// `deserialize_with` for unix-seconds fields that older files stored in another
+/// format. Unreadable strings become 0, the epoch, which counts as long past.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Seconds(value) => normalize(value),
+        Stored::Fractional(value) => normalize(value as i64),
+        Stored::Text(value) => parse_legacy(&value).unwrap_or(0),
+    })
+}
+
+/// Time zone and locale that timestamps are shown in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TimeContext {
+    pub timezone: String, // IANA name when known, otherwise the UTC offset
+    pub utc_offset_seconds: i32,
+    pub locale: String,
+}
+
+/// One timestamp converted for display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct LocalizedTimestamp {
+    pub timestamp: i64,
+    pub utc: String,     // RFC 3339 in UTC
+    pub local: String,   // RFC 3339 with the display offset
+    pub display: String, // date and time in the locale's usual order
+}
+
+/// The system time zone's IANA name, from `TZ` or the `/etc/localtime` link
+fn system_timezone_name() -> Option<String> {
+    if let Ok(tz) = std::env::var("TZ") {
+        let tz = tz.trim().trim_start_matches(':');
+        if !tz.is_empty() && !tz.starts_with('/') {
+            return Some(tz.to_string());
+        }
+    }
+    if let Ok(target) = std::fs::read_link("/etc/localtime") {
+        let target = target.to_string_lossy().to_string();
+        if let Some((_, name)) = target.split_once("zoneinfo/") {
+            return Some(name.to_string());
+        }
+    }
+    std::fs::read_to_string("/etc/timezone").ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// The locale for dates, following the usual `LC_ALL` > `LC_TIME` > `LANG` precedence
+fn system_locale() -> String {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|value| normalize_locale(&value))
+        .find(|locale| !locale.is_empty())
+        .unwrap_or_else(|| "en-US".to_string())
+}
+
+/// `de_DE.UTF-8@euro` to `de-DE`; `C` and `POSIX` carry no preference
+fn normalize_locale(value: &str) -> String {
+    let base = value.split(['.', '@']).next().unwrap_or_default().trim();
+    if base.is_empty() || base == "C" || base == "POSIX" {
+        return String::new();
+    }
+    base.replace('_', "-")
+}
+
+pub fn time_context() -> TimeContext {
+    let utc_offset_seconds = Local::now().offset().fix().local_minus_utc();
+    TimeContext {
+        timezone: system_timezone_name().unwrap_or_else(|| format_offset(utc_offset_seconds)),
+        utc_offset_seconds,
+        locale: system_locale(),
+    }
+}
+
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.unsigned_abs() / 60;
+    format!("UTC{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// strftime pattern for a locale's customary short date and time
+fn display_pattern(locale: &str) -> &'static str {
+    let mut parts = locale.split('-');
+    let language = parts.next().unwrap_or_default().to_lowercase();
+    let region = parts.next().unwrap_or_default().to_uppercase();
+    match (language.as_str(), region.as_str()) {
+        ("en", "US") | ("en", "PH") => "%m/%d/%Y %I:%M %p",
+        ("en", "CA") | ("sv", _) | ("lt", _) | ("fr", "CA") => "%Y-%m-%d %H:%M",
+        ("ja", _) | ("zh", _) => "%Y/%m/%d %H:%M",
+        ("ko", _) | ("hu", _) => "%Y. %m. %d. %H:%M",
+        ("de", _) | ("ru", _) | ("pl", _) | ("cs", _) | ("fi", _) | ("nb", _) | ("tr", _) | ("uk", _) => {
+            "%d.%m.%Y %H:%M"
+        }
+        ("nl", _) => "%d-%m-%Y %H:%M",
+        _ => "%d/%m/%Y %H:%M",
+    }
+}
+
+pub fn localize(timestamp: i64, offset: FixedOffset, locale: &str) -> LocalizedTimestamp {
+    let utc = to_datetime(timestamp);
+    let local = utc.with_timezone(&offset);
+    LocalizedTimestamp {
+        timestamp,
+        utc: utc.to_rfc3339(),
+        local: local.to_rfc3339(),
+        display: local.format(display_pattern(locale)).to_string(),
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_time_context() -> Result<TimeContext, String> {
+    Ok(time_context())
+}
+
+/// Convert stored timestamps for display. Without `utc_offset_minutes` or
+/// `locale`, the system's are used; the offset applies to every timestamp, so
+/// pass the one in effect when they were taken for dates across a DST change.
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn localize_timestamps(
+    timestamps: Vec<i64>,
+    utc_offset_minutes: Option<i32>,
+    locale: Option<String>,
+) -> Result<Vec<LocalizedTimestamp>, String> {
+    let context = time_context();
+    let offset_seconds = utc_offset_minutes.map_or(context.utc_offset_seconds, |m| m * 60);
+    let offset = FixedOffset::east_opt(offset_seconds)
+        .ok_or_else(|| format!("UTC offset out of range: {} minutes", offset_seconds / 60))?;
+    let locale = locale.map(|l| normalize_locale(&l)).filter(|l| !l.is_empty()).unwrap_or(context.locale);
+    Ok(timestamps.into_iter().map(|t| localize(normalize(t), offset, &locale)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy_formats() {
+        assert_eq!(parse_legacy("2024-03-01T12:00:00+00:00"), Some(1_709_294_400));
+        assert_eq!(parse_legacy("2024-03-01T13:00:00.123456789+01:00"), Some(1_709_294_400));
+        assert_eq!(parse_legacy("1709294400"), Some(1_709_294_400));
+        assert_eq!(parse_legacy("1709294400123"), Some(1_709_294_400));
+        assert_eq!(parse_legacy(""), None);
+        assert_eq!(parse_legacy("yesterday"), None);
+    }
+
+    #[test]
+    fn test_deserialize_accepts_every_stored_format() {
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(deserialize_with = "deserialize")]
+            at: i64,
+        }
+        let at = |json: &str| serde_json::from_str::<Row>(json).unwrap().at;
+        assert_eq!(at(r#"{"at":1709294400}"#), 1_709_294_400);
+        assert_eq!(at(r#"{"at":1709294400123}"#), 1_709_294_400);
+        assert_eq!(at(r#"{"at":"2024-03-01T12:00:00Z"}"#), 1_709_294_400);
+        assert_eq!(at(r#"{"at":""}"#), 0);
+    }
+
+    #[test]
+    fn test_localize() {
+        let cet = FixedOffset::east_opt(3600).unwrap();
+        let t = localize(1_709_294_400, cet, "de-DE");
+        assert_eq!(t.utc, "2024-03-01T12:00:00+00:00");
+        assert_eq!(t.local, "2024-03-01T13:00:00+01:00");
+        assert_eq!(t.display, "01.03.2024 13:00");
+
+        let eastern = FixedOffset::west_opt(5 * 3600).unwrap();
+        assert_eq!(localize(1_709_294_400, eastern, "en-US").display, "03/01/2024 07:00 AM");
+        assert_eq!(localize(1_709_294_400, Utc.fix(), "en-GB").display, "01/03/2024 12:00");
+    }
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("de_DE.UTF-8"), "de-DE");
+        assert_eq!(normalize_locale("sr_RS@latin"), "sr-RS");
+        assert_eq!(normalize_locale("C.UTF-8"), "");
+        assert_eq!(format_offset(-9000), "UTC-02:30");
+    }
+}
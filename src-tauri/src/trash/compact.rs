@@ -0,0 +1,515 @@
+//! Idle-time compaction of the trash, so the safety net stays cheap enough for
+//! long retention periods. Old file payloads are compressed with zstd, identical
+//! files across payloads are hard-linked to one copy, checksums recorded on
+//! earlier runs are verified, and payloads no metadata entry refers to are pruned.
+//!
+//! What compaction did to an item is kept in its [`TrashPayload`], which
+//! `restore_from_trash` uses to decompress the file or give it back its own copy.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::{TrashItem, TrashPayload};
+use crate::db::DbAccess;
+use crate::read_only;
+
+/// File payloads are compressed once they have been in the trash this long
+const COMPRESS_AFTER_DAYS: i64 = 7;
+const MIN_COMPRESS_SIZE: u64 = 64 * 1024;
+/// The compressed copy is only kept when it saves at least this share
+const MIN_SAVING_PERCENT: u64 = 10;
+const ZSTD_LEVEL: i32 = 9;
+const MIN_DEDUP_SIZE: u64 = 4 * 1024;
+/// Untracked payloads younger than this may belong to a `move_to_trash` in progress
+const ORPHAN_GRACE_SECS: i64 = 24 * 3600;
+/// Formats that are already compressed
+const INCOMPRESSIBLE_EXTENSIONS: &[&str] = &[
+    "7z", "avi", "bz2", "deb", "flac", "gif", "gz", "heic", "jpeg", "jpg", "mkv", "mov", "mp3", "mp4", "ogg", "png",
+    "rar", "rpm", "webm", "webp", "xz", "zip", "zst",
+];
+
+/// Compaction runs at most this often, and only while the system is idle
+const COMPACTION_INTERVAL_SECS: i64 = 24 * 3600;
+/// 1-minute load average per CPU below which the system counts as idle
+const IDLE_LOAD_PER_CPU: f64 = 0.3;
+/// monitoring_state key holding the last report
+const LAST_RUN_KEY: &str = "trash_compaction_last_run";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashCompactionReport {
+    pub ran_at: i64,
+    pub compressed: usize,
+    pub deduplicated: usize, // files replaced by a hard link to identical content
+    pub bytes_saved: u64,
+    pub verified: usize,
+    pub corrupted: Vec<String>, // ids of items whose payload no longer matches its checksum
+    pub orphans_pruned: usize,
+    pub orphan_bytes: u64,
+    pub duration_ms: u64,
+}
+
+fn hash_reader(mut reader: impl Read) -> io::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// BLAKE3 of a file payload's original content
+pub fn payload_checksum(path: &Path, compressed: bool) -> io::Result<String> {
+    let file = File::open(path)?;
+    if compressed {
+        hash_reader(zstd::Decoder::new(file)?)
+    } else {
+        hash_reader(file)
+    }
+}
+
+/// Write the original content of a compressed payload to `target`, checking it
+/// against `checksum`. A mismatching copy is removed again.
+pub fn decompress(path: &Path, target: &Path, checksum: Option<&str>) -> Result<(), String> {
+    let result = (|| -> io::Result<String> {
+        let mut decoder = zstd::Decoder::new(File::open(path)?)?;
+        let mut output = File::create(target)?;
+        let mut hasher = blake3::Hasher::new();
+        let mut buffer = vec![0; 256 * 1024];
+        loop {
+            let n = decoder.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+            output.write_all(&buffer[..n])?;
+        }
+        output.sync_all()?;
+        Ok(hasher.finalize().to_hex().to_string())
+    })();
+    match result {
+        Ok(hash) if checksum.map_or(true, |c| c == hash) => Ok(()),
+        Ok(_) => {
+            let _ = fs::remove_file(target);
+            Err(format!("Trashed copy of {} is corrupted", target.display()))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(target);
+            Err(format!("Failed to decompress {}: {}", path.display(), e))
+        }
+    }
+}
+
+fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".zst");
+    PathBuf::from(name)
+}
+
+/// Compress a file payload next to itself. Returns the checksum of the content
+/// and, when the compressed copy was worth keeping, its size; the original is
+/// only removed once the compressed copy has been read back.
+fn compress(path: &Path) -> io::Result<(String, Option<u64>)> {
+    let target = compressed_path(path);
+    let original_size = fs::metadata(path)?.len();
+    let written = (|| -> io::Result<String> {
+        let mut input = File::open(path)?;
+        let mut encoder = zstd::Encoder::new(File::create(&target)?, ZSTD_LEVEL)?;
+        let mut hasher = blake3::Hasher::new();
+        let mut buffer = vec![0; 256 * 1024];
+        loop {
+            let n = input.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+            encoder.write_all(&buffer[..n])?;
+        }
+        encoder.finish()?.sync_all()?;
+        Ok(hasher.finalize().to_hex().to_string())
+    })();
+    let checksum = match written {
+        Ok(checksum) => checksum,
+        Err(e) => {
+            let _ = fs::remove_file(&target);
+            return Err(e);
+        }
+    };
+
+    let stored_size = fs::metadata(&target)?.len();
+    if stored_size * 100 > original_size * (100 - MIN_SAVING_PERCENT) {
+        fs::remove_file(&target)?;
+        return Ok((checksum, None));
+    }
+    if payload_checksum(&target, true)? != checksum {
+        let _ = fs::remove_file(&target);
+        return Err(io::Error::other(format!("Compressed copy of {} does not read back", path.display())));
+    }
+    fs::remove_file(path)?;
+    Ok((checksum, Some(stored_size)))
+}
+
+fn compressible(item: &TrashItem, now: DateTime<Utc>) -> bool {
+    let old_enough = now - crate::timestamps::to_datetime(item.deleted_at) >= Duration::days(COMPRESS_AFTER_DAYS);
+    let extension = Path::new(&item.original_path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    item.item_type == "file"
+        && item.payload.is_none()
+        && item.size >= MIN_COMPRESS_SIZE
+        && old_enough
+        && !INCOMPRESSIBLE_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// Replace `duplicate` with a hard link to `original`, atomically
+fn link_to(original: &Path, duplicate: &Path) -> io::Result<()> {
+    let mut temporary = duplicate.as_os_str().to_os_string();
+    temporary.push(".pulito-link");
+    let temporary = PathBuf::from(temporary);
+    fs::hard_link(original, &temporary)?;
+    fs::rename(&temporary, duplicate).inspect_err(|_| {
+        let _ = fs::remove_file(&temporary);
+    })
+}
+
+struct DedupCandidate {
+    item: usize,
+    path: PathBuf,
+    relative: String,
+    size: u64,
+    mode: u32,
+    inode: (u64, u64),
+}
+
+/// Hard-link identical files across uncompressed payloads. Files that already
+/// have other links are left alone, so only links compaction made itself are
+/// recorded and undone on restore.
+fn deduplicate(items: &mut [TrashItem], report: &mut TrashCompactionReport) {
+    let mut by_size: HashMap<(u64, u32), Vec<DedupCandidate>> = HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        if item.payload.as_ref().is_some_and(|p| p.compressed) {
+            continue;
+        }
+        let root = Path::new(&item.trash_path);
+        let linked: HashSet<&str> =
+            item.payload.iter().flat_map(|p| p.linked_files.iter().map(String::as_str)).collect();
+        for entry in WalkDir::new(root).follow_links(false).into_iter().filter_map(|e| e.ok()) {
+            let Ok(metadata) = entry.metadata() else { continue };
+            if !metadata.is_file() || metadata.len() < MIN_DEDUP_SIZE {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path()).to_string_lossy().to_string();
+            if metadata.nlink() > 1 && !linked.contains(relative.as_str()) {
+                continue;
+            }
+            by_size.entry((metadata.len(), metadata.mode())).or_default().push(DedupCandidate {
+                item: index,
+                path: entry.into_path(),
+                relative,
+                size: metadata.len(),
+                mode: metadata.mode(),
+                inode: (metadata.dev(), metadata.ino()),
+            });
+        }
+    }
+
+    for candidates in by_size.into_values().filter(|c| c.len() > 1) {
+        let mut by_content: HashMap<String, Vec<DedupCandidate>> = HashMap::new();
+        for candidate in candidates {
+            if let Ok(hash) = payload_checksum(&candidate.path, false) {
+                by_content.entry(hash).or_default().push(candidate);
+            }
+        }
+        for group in by_content.into_values().filter(|g| g.len() > 1) {
+            let keep = &group[0];
+            for duplicate in &group[1..] {
+                if duplicate.inode == keep.inode || duplicate.mode != keep.mode {
+                    continue;
+                }
+                if let Err(e) = link_to(&keep.path, &duplicate.path) {
+                    tracing::warn!("Failed to deduplicate {}: {}", duplicate.path.display(), e);
+                    continue;
+                }
+                for (index, relative) in [(keep.item, &keep.relative), (duplicate.item, &duplicate.relative)] {
+                    let item = &mut items[index];
+                    let payload = item.payload.get_or_insert_with(|| TrashPayload {
+                        checksum: None,
+                        compressed: false,
+                        stored_size: item.size,
+                        linked_files: Vec::new(),
+                    });
+                    if !payload.linked_files.contains(relative) {
+                        payload.linked_files.push(relative.clone());
+                    }
+                }
+                let payload = items[duplicate.item].payload.as_mut().expect("payload set above");
+                payload.stored_size = payload.stored_size.saturating_sub(duplicate.size);
+                report.deduplicated += 1;
+                report.bytes_saved += duplicate.size;
+            }
+        }
+    }
+}
+
+/// Remove entries of the trash directory no item refers to, once they are older than the grace period
+fn prune_orphans(trash_dir: &Path, items: &[TrashItem], report: &mut TrashCompactionReport) {
+    let tracked: HashSet<&Path> = items.iter().map(|i| Path::new(&i.trash_path)).collect();
+    let Ok(entries) = fs::read_dir(trash_dir) else { return };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.file_name().is_some_and(|n| n == "metadata.json") || tracked.contains(path.as_path()) {
+            continue;
+        }
+        // ctime changes when a file is renamed into the trash, mtime doesn't
+        let now = Utc::now().timestamp();
+        if entry.metadata().map_or(true, |m| now - m.ctime() < ORPHAN_GRACE_SECS) {
+            continue;
+        }
+        let size = if path.is_dir() { super::get_dir_size(&path) } else { entry.metadata().map(|m| m.len()).unwrap_or(0) };
+        match super::remove_path(&path) {
+            Ok(()) => {
+                report.orphans_pruned += 1;
+                report.orphan_bytes += size;
+            }
+            Err(e) => tracing::warn!("Failed to prune orphaned trash payload {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Verify, compress and deduplicate the payloads of `items`, updating their
+/// `trash_path` and `payload`
+pub fn compact(items: &mut [TrashItem], now: DateTime<Utc>) -> TrashCompactionReport {
+    let mut report = TrashCompactionReport { ran_at: now.timestamp(), ..Default::default() };
+
+    for item in items.iter() {
+        let Some(checksum) = item.payload.as_ref().and_then(|p| p.checksum.as_deref().map(|c| (c, p.compressed))) else {
+            continue;
+        };
+        match payload_checksum(Path::new(&item.trash_path), checksum.1) {
+            Ok(actual) if actual == checksum.0 => report.verified += 1,
+            Ok(_) => report.corrupted.push(item.id.clone()),
+            Err(e) => {
+                tracing::warn!("Failed to verify trashed copy of {}: {}", item.original_path, e);
+                report.corrupted.push(item.id.clone());
+            }
+        }
+    }
+
+    for item in items.iter_mut().filter(|i| compressible(i, now)) {
+        let path = PathBuf::from(&item.trash_path);
+        // Hard links to the file elsewhere would keep the uncompressed content around
+        if fs::metadata(&path).map_or(true, |m| m.nlink() > 1) {
+            continue;
+        }
+        match compress(&path) {
+            Ok((checksum, Some(stored_size))) => {
+                report.compressed += 1;
+                report.bytes_saved += item.size.saturating_sub(stored_size);
+                item.trash_path = compressed_path(&path).to_string_lossy().to_string();
+                item.payload = Some(TrashPayload { checksum: Some(checksum), compressed: true, stored_size, linked_files: Vec::new() });
+            }
+            Ok((checksum, None)) => {
+                item.payload =
+                    Some(TrashPayload { checksum: Some(checksum), compressed: false, stored_size: item.size, linked_files: Vec::new() });
+            }
+            Err(e) => tracing::warn!("Failed to compress trashed copy of {}: {}", item.original_path, e),
+        }
+    }
+
+    deduplicate(items, &mut report);
+    report
+}
+
+/// Compact the trash, merging the result into metadata saved meanwhile so items
+/// trashed during the run aren't lost, then prune orphans against that metadata
+pub fn compact_trash() -> Result<TrashCompactionReport, String> {
+    let start = std::time::Instant::now();
+    let mut items = super::load_trash_metadata();
+    let mut report = compact(&mut items, Utc::now());
+
+    let compacted: HashMap<String, TrashItem> = items.into_iter().map(|i| (i.id.clone(), i)).collect();
+    let mut latest = super::load_trash_metadata();
+    for item in latest.iter_mut() {
+        if let Some(done) = compacted.get(&item.id) {
+            item.trash_path = done.trash_path.clone();
+            item.payload = done.payload.clone();
+        }
+    }
+    super::save_trash_metadata(&latest).map_err(|e| format!("Failed to update metadata: {}", e))?;
+    prune_orphans(&super::get_trash_dir(), &latest, &mut report);
+
+    report.duration_ms = start.elapsed().as_millis() as u64;
+    Ok(report)
+}
+
+/// Give files compaction hard-linked their own copy again, under a restored `root`
+pub fn unshare_links(root: &Path, linked_files: &[String]) -> io::Result<()> {
+    for relative in linked_files {
+        let path = if relative.is_empty() { root.to_path_buf() } else { root.join(relative) };
+        if !fs::symlink_metadata(&path).is_ok_and(|m| m.is_file() && m.nlink() > 1) {
+            continue;
+        }
+        let mut temporary = path.as_os_str().to_os_string();
+        temporary.push(".pulito-copy");
+        let temporary = PathBuf::from(temporary);
+        fs::copy(&path, &temporary)?;
+        fs::rename(&temporary, &path)?;
+    }
+    Ok(())
+}
+
+/// Whether the machine is quiet enough for background I/O
+pub fn system_idle() -> bool {
+    let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+    sysinfo::System::load_average().one < cpus * IDLE_LOAD_PER_CPU
+}
+
+fn last_run_at(app_handle: &tauri::AppHandle) -> Option<i64> {
+    app_handle
+        .db(|conn| {
+            conn.query_row("SELECT updated_at FROM monitoring_state WHERE key = ?1", [LAST_RUN_KEY], |row| row.get(0))
+                .optional()
+        })
+        .ok()
+        .flatten()
+}
+
+fn save_report(app_handle: &tauri::AppHandle, report: &TrashCompactionReport) {
+    let Ok(json) = serde_json::to_string(report) else { return };
+    if let Err(e) = app_handle.db(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO monitoring_state (key, value, updated_at) VALUES (?1, ?2, ?3)",
+            params![LAST_RUN_KEY, json, report.ran_at],
+        )?;
+        Ok(())
+    }) {
+        tracing::warn!("Failed to store trash compaction report: {}", e);
+    }
+}
+
+async fn run(app_handle: &tauri::AppHandle) -> Result<TrashCompactionReport, String> {
+    let report = tokio::task::spawn_blocking(compact_trash)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))??;
+    tracing::info!(
+        "Trash compaction: {} compressed, {} deduplicated, {} bytes saved, {} orphans pruned, {} corrupted",
+        report.compressed,
+        report.deduplicated,
+        report.bytes_saved,
+        report.orphans_pruned,
+        report.corrupted.len()
+    );
+    save_report(app_handle, &report);
+    Ok(report)
+}
+
+/// Compact the trash if the last run is a day old and nothing else keeps the machine busy
+pub async fn run_if_due(app_handle: &tauri::AppHandle) {
+    let now = Utc::now().timestamp();
+    if last_run_at(app_handle).is_some_and(|last| now - last < COMPACTION_INTERVAL_SECS) || !system_idle() {
+        return;
+    }
+    if let Err(e) = run(app_handle).await {
+        tracing::warn!("Trash compaction failed: {}", e);
+    }
+}
+
+/// Compress, deduplicate and verify the trash now
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn compact_trash_now(app_handle: tauri::AppHandle) -> Result<TrashCompactionReport, String> {
+    read_only::check("Compacting the trash")?;
+    run(&app_handle).await
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_last_trash_compaction(app_handle: tauri::AppHandle) -> Result<Option<TrashCompactionReport>, String> {
+    let json: Option<String> = app_handle
+        .db(|conn| {
+            conn.query_row("SELECT value FROM monitoring_state WHERE key = ?1", [LAST_RUN_KEY], |row| row.get(0))
+                .optional()
+        })
+        .map_err(|e| format!("Failed to read trash compaction report: {}", e))?;
+    Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trashed(dir: &Path, id: &str, name: &str, content: &[u8], days_ago: i64) -> TrashItem {
+        let trash_path = dir.join(id);
+        fs::write(&trash_path, content).unwrap();
+        let deleted = Utc::now() - Duration::days(days_ago);
+        TrashItem {
+            id: id.to_string(),
+            original_path: format!("/home/u/{}", name),
+            trash_path: trash_path.to_string_lossy().to_string(),
+            deleted_at: deleted.timestamp(),
+            expires_at: (deleted + Duration::days(30)).timestamp(),
+            size: content.len() as u64,
+            item_type: "file".to_string(),
+            metadata: None,
+            pinned: false,
+            payload: None,
+        }
+    }
+
+    #[test]
+    fn test_compress_dedup_and_verify() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = b"2024-01-01 service started\n".repeat(10_000);
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+        let mut items = vec![
+            trashed(dir.path(), "old-log", "app.log", &log, 10),
+            trashed(dir.path(), "new-log", "app.log.1", &log, 1),
+            trashed(dir.path(), "copy-a", "a.bin", &data, 1),
+            trashed(dir.path(), "copy-b", "b.bin", &data, 1),
+        ];
+
+        let report = compact(&mut items, Utc::now());
+        // Only the week-old log is compressed; the two fresh binaries share one copy
+        assert_eq!((report.compressed, report.deduplicated), (1, 1));
+        assert!(items[0].trash_path.ends_with(".zst"));
+        assert!(items[0].payload.as_ref().unwrap().compressed);
+        assert!(items[1].payload.is_none());
+        let inode = |i: &TrashItem| fs::metadata(&i.trash_path).unwrap().ino();
+        assert_eq!(inode(&items[2]), inode(&items[3]));
+        assert_eq!(items[3].payload.as_ref().unwrap().linked_files, vec![String::new()]);
+
+        let restored = dir.path().join("restored.log");
+        let checksum = items[0].payload.as_ref().unwrap().checksum.clone();
+        decompress(Path::new(&items[0].trash_path), &restored, checksum.as_deref()).unwrap();
+        assert_eq!(fs::read(&restored).unwrap(), log);
+        fs::remove_file(&restored).unwrap();
+
+        // A second run verifies the recorded checksum and finds the corruption
+        let report = compact(&mut items, Utc::now());
+        assert_eq!((report.verified, report.corrupted.len()), (1, 0));
+        fs::write(&items[0].trash_path, b"garbage").unwrap();
+        assert_eq!(compact(&mut items, Utc::now()).corrupted, vec!["old-log".to_string()]);
+
+        let copy = dir.path().join("copy-b");
+        unshare_links(&copy, &[String::new()]).unwrap();
+        assert_ne!(fs::metadata(&copy).unwrap().ino(), inode(&items[2]));
+        assert_eq!(fs::read(&copy).unwrap(), data);
+    }
+
+    #[test]
+    fn test_young_orphans_are_kept() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("metadata.json"), b"[]").unwrap();
+        fs::write(dir.path().join("in-flight"), b"x").unwrap();
+        let mut report = TrashCompactionReport::default();
+        prune_orphans(dir.path(), &[], &mut report);
+        assert_eq!(report.orphans_pruned, 0);
+        assert!(dir.path().join("in-flight").exists());
+    }
+}
@@ -1,8 +1,26 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+use crate::scanner::allocation::{self, InodeSet};
+use crate::timestamps;
+
+pub mod compact;
+
+/// Retention no item is moved to trash with less of; raised by safe mode
+static MIN_RETENTION_DAYS: AtomicI64 = AtomicI64::new(0);
+
+pub fn set_min_retention_days(days: i64) {
+    MIN_RETENTION_DAYS.store(days, Ordering::Relaxed);
+}
+
+pub fn min_retention_days() -> i64 {
+    MIN_RETENTION_DAYS.load(Ordering::Relaxed)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[specta(export)]
@@ -10,11 +28,32 @@ pub struct TrashItem {
     pub id: String,
     pub original_path: String,
     pub trash_path: String,
-    pub deleted_at: String,
-    pub expires_at: String,
+    /// UTC unix seconds; older metadata files have RFC 3339 strings here
+    #[serde(deserialize_with = "crate::timestamps::deserialize")]
+    pub deleted_at: i64,
+    #[serde(deserialize_with = "crate::timestamps::deserialize")]
+    pub expires_at: i64,
     pub size: u64,
     pub item_type: String,
     pub metadata: Option<TrashMetadata>,
+    /// Kept indefinitely: never expired or evicted to stay under the size limit
+    #[serde(default)]
+    pub pinned: bool,
+    /// How compaction stored the content; None until it has been compacted
+    #[serde(default)]
+    pub payload: Option<TrashPayload>,
+}
+
+/// What `compact::compact` did to a trashed item's content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashPayload {
+    pub checksum: Option<String>, // BLAKE3 of the original content, file items only
+    pub compressed: bool, // trash_path is a zstd stream of the file
+    pub stored_size: u64, // bytes the payload occupies on its own
+    /// Files hard-linked to identical content in other payloads, relative to trash_path
+    #[serde(default)]
+    pub linked_files: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -31,6 +70,47 @@ pub struct TrashData {
     pub items: Vec<TrashItem>,
     pub total_size: u64,
     pub total_items: usize,
+    pub by_category: Vec<TrashCategoryStats>,
+    pub by_risk_level: Vec<TrashRiskStats>,
+}
+
+/// Items trashed from one scan category, largest categories first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashCategoryStats {
+    pub category: String,
+    pub count: usize,
+    pub total_size: u64,
+    pub nearest_expiry: Option<i64>, // unix seconds; None when every item is pinned
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashRiskStats {
+    pub risk_level: u8,
+    pub count: usize,
+    pub total_size: u64,
+    pub nearest_expiry: Option<i64>,
+}
+
+/// Category for items trashed without scan metadata
+const UNCATEGORIZED: &str = "Uncategorized";
+
+#[derive(Default)]
+struct Tally {
+    count: usize,
+    total_size: u64,
+    nearest_expiry: Option<i64>,
+}
+
+impl Tally {
+    fn add(&mut self, item: &TrashItem) {
+        self.count += 1;
+        self.total_size += item.size;
+        if !item.pinned && self.nearest_expiry.map_or(true, |e| item.expires_at < e) {
+            self.nearest_expiry = Some(item.expires_at);
+        }
+    }
 }
 
 pub fn get_trash_dir() -> PathBuf {
@@ -94,7 +174,7 @@ pub fn move_to_trash(
     let size = if source_path.is_dir() {
         get_dir_size(&source_path)
     } else {
-        source_path.metadata().map(|m| m.len()).unwrap_or(0)
+        source_path.metadata().map(|m| allocation::allocated(&m)).unwrap_or(0)
     };
 
     let item_type = if source_path.is_dir() { "directory" } else { "file" }.to_string();
@@ -102,17 +182,19 @@ pub fn move_to_trash(
     fs::rename(&source_path, &trash_path).map_err(|e| format!("Failed to move to trash: {}", e))?;
 
     let now = Utc::now();
-    let expires = now + Duration::days(retention_days);
+    let expires = now + Duration::days(retention_days.max(min_retention_days()));
 
     let item = TrashItem {
         id,
         original_path: path.to_string(),
         trash_path: trash_path.to_string_lossy().to_string(),
-        deleted_at: now.to_rfc3339(),
-        expires_at: expires.to_rfc3339(),
+        deleted_at: now.timestamp(),
+        expires_at: expires.timestamp(),
         size,
         item_type,
         metadata,
+        pinned: false,
+        payload: None,
     };
 
     let mut items = load_trash_metadata();
@@ -146,7 +228,21 @@ pub fn restore_from_trash(id: &str) -> Result<(), String> {
         return Err(format!("Cannot restore: path already exists: {}", item.original_path));
     }
 
-    fs::rename(&trash_path, &original_path).map_err(|e| format!("Failed to restore: {}", e))?;
+    match &item.payload {
+        Some(payload) if payload.compressed => {
+            compact::decompress(&trash_path, &original_path, payload.checksum.as_deref())?;
+            fs::remove_file(&trash_path).map_err(|e| format!("Failed to remove trashed copy: {}", e))?;
+        }
+        payload => {
+            fs::rename(&trash_path, &original_path).map_err(|e| format!("Failed to restore: {}", e))?;
+            // Content shared with other trashed items must not change along with the restored copy
+            if let Some(payload) = payload {
+                if let Err(e) = compact::unshare_links(&original_path, &payload.linked_files) {
+                    tracing::warn!("Restored {} still shares content with trashed items: {}", item.original_path, e);
+                }
+            }
+        }
+    }
 
     items.remove(item_idx);
     save_trash_metadata(&items).map_err(|e| format!("Failed to update metadata: {}", e))?;
@@ -193,20 +289,179 @@ pub fn get_trash_items() -> TrashData {
     let items = load_trash_metadata();
     let total_size: u64 = items.iter().map(|i| i.size).sum();
     let total_items = items.len();
+    let (by_category, by_risk_level) = aggregate(&items);
 
-    TrashData { items, total_size, total_items }
+    TrashData { items, total_size, total_items, by_category, by_risk_level }
+}
+
+/// Per-category and per-risk-level counts, sizes and soonest expiry. Items
+/// without scan metadata count as uncategorized and are left out of the risk levels.
+fn aggregate(items: &[TrashItem]) -> (Vec<TrashCategoryStats>, Vec<TrashRiskStats>) {
+    let mut categories: HashMap<&str, Tally> = HashMap::new();
+    let mut risk_levels: BTreeMap<u8, Tally> = BTreeMap::new();
+    for item in items {
+        let category = item.metadata.as_ref().map_or(UNCATEGORIZED, |m| m.category.as_str());
+        categories.entry(category).or_default().add(item);
+        if let Some(metadata) = &item.metadata {
+            risk_levels.entry(metadata.risk_level).or_default().add(item);
+        }
+    }
+
+    let mut by_category: Vec<TrashCategoryStats> = categories
+        .into_iter()
+        .map(|(category, t)| TrashCategoryStats {
+            category: category.to_string(),
+            count: t.count,
+            total_size: t.total_size,
+            nearest_expiry: t.nearest_expiry,
+        })
+        .collect();
+    by_category.sort_by(|a, b| b.total_size.cmp(&a.total_size).then_with(|| a.category.cmp(&b.category)));
+
+    let by_risk_level = risk_levels
+        .into_iter()
+        .map(|(risk_level, t)| TrashRiskStats {
+            risk_level,
+            count: t.count,
+            total_size: t.total_size,
+            nearest_expiry: t.nearest_expiry,
+        })
+        .collect();
+
+    (by_category, by_risk_level)
+}
+
+/// Items that will be removed by `cleanup_expired` within `window`, soonest first
+pub fn expiring_within(window: Duration) -> Vec<TrashItem> {
+    let cutoff = (Utc::now() + window).timestamp();
+    let mut items: Vec<TrashItem> = load_trash_metadata()
+        .into_iter()
+        .filter(|item| !item.pinned && item.expires_at <= cutoff)
+        .collect();
+    items.sort_by_key(|item| item.expires_at);
+    items
+}
+
+/// What the next expiry run will purge
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashExpiryDigest {
+    pub items: Vec<TrashItem>,
+    pub total_size: u64,
+    pub window_hours: u32,
+}
+
+pub fn expiry_digest(window_hours: u32) -> TrashExpiryDigest {
+    let items = expiring_within(Duration::hours(window_hours as i64));
+    let total_size = items.iter().map(|i| i.size).sum();
+    TrashExpiryDigest { items, total_size, window_hours }
+}
+
+/// One-line summary for a reminder, naming the largest items
+pub fn digest_summary(digest: &TrashExpiryDigest) -> String {
+    const NAMED: usize = 3;
+    let mut largest: Vec<&TrashItem> = digest.items.iter().collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size));
+    let names: Vec<&str> = largest
+        .iter()
+        .take(NAMED)
+        .map(|i| Path::new(&i.original_path).file_name().and_then(|n| n.to_str()).unwrap_or(&i.original_path))
+        .collect();
+    let more = digest.items.len().saturating_sub(NAMED);
+
+    format!(
+        "{} item{} ({}) will be permanently deleted within {} hours: {}{}",
+        digest.items.len(),
+        if digest.items.len() == 1 { "" } else { "s" },
+        crate::scanner::format_bytes(digest.total_size),
+        digest.window_hours,
+        names.join(", "),
+        if more > 0 { format!(" and {} more", more) } else { String::new() },
+    )
+}
+
+/// Push the expiry of the matching items `extra_days` past the later of now and
+/// their current expiry; returns how many were extended
+fn extend_items(items: &mut [TrashItem], ids: &[String], extra_days: i64, now: DateTime<Utc>) -> usize {
+    let mut extended = 0;
+    for item in items.iter_mut().filter(|i| ids.contains(&i.id)) {
+        let current = timestamps::to_datetime(item.expires_at).max(now);
+        item.expires_at = (current + Duration::days(extra_days)).timestamp();
+        extended += 1;
+    }
+    extended
+}
+
+pub fn extend_retention(ids: &[String], extra_days: i64) -> Result<usize, String> {
+    if extra_days <= 0 {
+        return Err("Retention can only be extended by a positive number of days".to_string());
+    }
+    let mut items = load_trash_metadata();
+    let extended = extend_items(&mut items, ids, extra_days, Utc::now());
+    if extended > 0 {
+        save_trash_metadata(&items).map_err(|e| format!("Failed to update metadata: {}", e))?;
+    }
+    Ok(extended)
+}
+
+pub fn set_pinned(id: &str, pinned: bool) -> Result<TrashItem, String> {
+    let mut items = load_trash_metadata();
+    let item = items.iter_mut().find(|i| i.id == id).ok_or_else(|| format!("Item not found in trash: {}", id))?;
+    item.pinned = pinned;
+    let item = item.clone();
+    save_trash_metadata(&items).map_err(|e| format!("Failed to update metadata: {}", e))?;
+    Ok(item)
+}
+
+/// Ids to delete so the trash fits in `max_bytes`, soonest-expiring first.
+/// Pinned items count towards the total but are never chosen.
+fn eviction_candidates(items: &[TrashItem], max_bytes: u64) -> Vec<String> {
+    let mut total: u64 = items.iter().map(|i| i.size).sum();
+    let mut unpinned: Vec<&TrashItem> = items.iter().filter(|i| !i.pinned).collect();
+    unpinned.sort_by_key(|item| item.expires_at);
+
+    let mut evict = Vec::new();
+    for item in unpinned {
+        if total <= max_bytes {
+            break;
+        }
+        total = total.saturating_sub(item.size);
+        evict.push(item.id.clone());
+    }
+    evict
+}
+
+/// Permanently delete unpinned items until the trash is within `max_bytes`
+pub fn enforce_size_limit(max_bytes: u64) -> Result<usize, String> {
+    let items = load_trash_metadata();
+    let evict = eviction_candidates(&items, max_bytes);
+    if evict.is_empty() {
+        return Ok(0);
+    }
+
+    let mut remaining = Vec::with_capacity(items.len());
+    for item in items {
+        if evict.contains(&item.id) {
+            let trash_path = PathBuf::from(&item.trash_path);
+            if trash_path.exists() {
+                remove_path(&trash_path).ok();
+            }
+        } else {
+            remaining.push(item);
+        }
+    }
+    save_trash_metadata(&remaining).map_err(|e| format!("Failed to update metadata: {}", e))?;
+    Ok(evict.len())
 }
 
 pub fn cleanup_expired() -> Result<usize, String> {
     let items = load_trash_metadata();
-    let now = Utc::now();
+    let now = timestamps::now();
     let mut removed = 0;
     let mut remaining = Vec::new();
 
     for item in items {
-    let expires: DateTime<Utc> = item.expires_at.parse().unwrap_or(now);
-
-        if expires <= now {
+        if !item.pinned && item.expires_at <= now {
             let trash_path = PathBuf::from(&item.trash_path);
             if trash_path.exists() {
                 remove_path(&trash_path).ok();
@@ -222,21 +477,208 @@ pub fn cleanup_expired() -> Result<usize, String> {
     Ok(removed)
 }
 
+/// Outcome of reconciling trash metadata with the trash directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct TrashConsistency {
+    pub removed_entries: usize,       // metadata entries whose content was gone
+    pub untracked_files: Vec<String>, // files in the trash dir without metadata
+    pub metadata_unreadable: bool,
+}
+
+/// Drop metadata entries whose trashed content no longer exists and report
+/// files in the trash directory that no entry refers to. Untracked files are
+/// left alone since their original location is unknown. An unreadable
+/// metadata file is reported but never overwritten.
+pub fn repair_metadata() -> Result<TrashConsistency, String> {
+    let trash_dir = get_trash_dir();
+    let metadata_path = get_metadata_path();
+    let mut report = TrashConsistency::default();
+
+    let items = if metadata_path.exists() {
+        let content = fs::read_to_string(&metadata_path).map_err(|e| format!("Failed to read trash metadata: {}", e))?;
+        match serde_json::from_str::<Vec<TrashItem>>(&content) {
+            Ok(items) => items,
+            Err(_) => {
+                report.metadata_unreadable = true;
+                return Ok(report);
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let before = items.len();
+    let remaining: Vec<TrashItem> = items.into_iter().filter(|i| Path::new(&i.trash_path).exists()).collect();
+    report.removed_entries = before - remaining.len();
+    if report.removed_entries > 0 {
+        save_trash_metadata(&remaining).map_err(|e| format!("Failed to update metadata: {}", e))?;
+    }
+
+    if let Ok(entries) = fs::read_dir(&trash_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path == metadata_path {
+                continue;
+            }
+            let path_str = path.to_string_lossy().to_string();
+            if !remaining.iter().any(|i| i.trash_path == path_str) {
+                report.untracked_files.push(path_str);
+            }
+        }
+    }
+    report.untracked_files.sort();
+
+    Ok(report)
+}
+
 pub fn get_dir_size(path: &Path) -> u64 {
+    get_dir_size_cancellable(path, &AtomicBool::new(false)).unwrap_or(0)
+}
+
+/// `get_dir_size` that gives up with `None` once `cancel` is set, checked per entry
+pub fn get_dir_size_cancellable(path: &Path, cancel: &AtomicBool) -> Option<u64> {
+    dir_size_counted(path, cancel, &mut InodeSet::new())
+}
+
+/// Bytes on disk below `path`, each hard-linked file counted once
+fn dir_size_counted(path: &Path, cancel: &AtomicBool, seen: &mut InodeSet) -> Option<u64> {
     let mut size: u64 = 0;
 
     if let Ok(entries) = fs::read_dir(path) {
         for entry in entries.filter_map(|e| e.ok()) {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
             let entry_path = entry.path();
             if entry_path.is_file() {
                 if let Ok(metadata) = entry_path.metadata() {
-                    size += metadata.len();
+                    size += seen.count(&metadata);
                 }
             } else if entry_path.is_dir() {
-                size += get_dir_size(&entry_path);
+                size += dir_size_counted(&entry_path, cancel, seen)?;
             }
         }
     }
 
-    size
+    Some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, path: &str, size: u64, expires_at: DateTime<Utc>) -> TrashItem {
+        TrashItem {
+            id: id.to_string(),
+            original_path: path.to_string(),
+            trash_path: format!("/trash/{}", id),
+            deleted_at: expires_at.timestamp(),
+            expires_at: expires_at.timestamp(),
+            size,
+            item_type: "file".to_string(),
+            metadata: None,
+            pinned: false,
+            payload: None,
+        }
+    }
+
+    #[test]
+    fn test_digest_summary() {
+        let now = Utc::now();
+        let items = vec![
+            item("a", "/home/u/small.txt", 10, now),
+            item("b", "/home/u/big.iso", 4096, now),
+            item("c", "/home/u/mid.log", 1024, now),
+            item("d", "/home/u/tiny", 1, now),
+        ];
+        let digest = TrashExpiryDigest { total_size: 5131, items, window_hours: 24 };
+        assert_eq!(
+            digest_summary(&digest),
+            format!(
+                "4 items ({}) will be permanently deleted within 24 hours: big.iso, mid.log, small.txt and 1 more",
+                crate::scanner::format_bytes(5131)
+            )
+        );
+    }
+
+    #[test]
+    fn test_extend_items() {
+        let now = Utc::now();
+        let mut items = vec![
+            item("soon", "/a", 1, now + Duration::hours(2)),
+            item("overdue", "/b", 1, now - Duration::days(1)),
+            item("other", "/c", 1, now + Duration::hours(2)),
+        ];
+        let ids = vec!["soon".to_string(), "overdue".to_string()];
+        assert_eq!(extend_items(&mut items, &ids, 7, now), 2);
+
+        assert_eq!(items[0].expires_at, (now + Duration::hours(2) + Duration::days(7)).timestamp());
+        // Overdue items count from now, not from their past expiry
+        assert_eq!(items[1].expires_at, (now + Duration::days(7)).timestamp());
+        assert_eq!(items[2].expires_at, (now + Duration::hours(2)).timestamp());
+    }
+
+    #[test]
+    fn test_eviction_skips_pinned() {
+        let now = Utc::now();
+        let mut pinned = item("pinned", "/p", 500, now);
+        pinned.pinned = true;
+        let items = vec![
+            pinned,
+            item("later", "/l", 300, now + Duration::days(2)),
+            item("sooner", "/s", 300, now + Duration::days(1)),
+        ];
+
+        assert!(eviction_candidates(&items, 1100).is_empty());
+        assert_eq!(eviction_candidates(&items, 900), vec!["sooner".to_string()]);
+        // The pinned item alone exceeds the limit; everything else goes, it stays
+        assert_eq!(eviction_candidates(&items, 100), vec!["sooner".to_string(), "later".to_string()]);
+    }
+
+    #[test]
+    fn test_aggregate_by_category_and_risk() {
+        let now = Utc::now();
+        let with_meta = |id: &str, size: u64, days: i64, category: &str, risk_level: u8| TrashItem {
+            metadata: Some(TrashMetadata { category: category.to_string(), risk_level, reason: String::new() }),
+            ..item(id, &format!("/{}", id), size, now + Duration::days(days))
+        };
+        let mut pinned = with_meta("p", 500, 1, "Cache", 0);
+        pinned.pinned = true;
+        let items = vec![
+            with_meta("a", 100, 5, "Cache", 0),
+            with_meta("b", 200, 3, "Cache", 0),
+            with_meta("c", 50, 2, "Logs", 1),
+            item("d", "/d", 10, now + Duration::days(4)),
+            pinned,
+        ];
+
+        let (by_category, by_risk_level) = aggregate(&items);
+        let categories: Vec<(&str, usize, u64)> =
+            by_category.iter().map(|c| (c.category.as_str(), c.count, c.total_size)).collect();
+        assert_eq!(categories, vec![("Cache", 3, 800), ("Logs", 1, 50), (UNCATEGORIZED, 1, 10)]);
+        // The pinned item never expires, so "b" is the nearest
+        assert_eq!(by_category[0].nearest_expiry, Some((now + Duration::days(3)).timestamp()));
+
+        let levels: Vec<(u8, usize, u64)> = by_risk_level.iter().map(|r| (r.risk_level, r.count, r.total_size)).collect();
+        assert_eq!(levels, vec![(0, 3, 800), (1, 1, 50)]);
+    }
+
+    #[test]
+    fn test_pinned_flag_defaults_off() {
+        let json = r#"{"id":"a","original_path":"/a","trash_path":"/t/a","deleted_at":"","expires_at":"","size":1,"item_type":"file","metadata":null}"#;
+        let item: TrashItem = serde_json::from_str(json).unwrap();
+        assert!(!item.pinned);
+    }
+
+    #[test]
+    fn test_legacy_rfc3339_times_load() {
+        let json = r#"{"id":"a","original_path":"/a","trash_path":"/t/a","deleted_at":"2024-03-01T12:00:00.5+00:00",
+            "expires_at":"2024-03-31T14:00:00+02:00","size":1,"item_type":"file","metadata":null}"#;
+        let item: TrashItem = serde_json::from_str(json).unwrap();
+        assert_eq!(item.deleted_at, 1_709_294_400);
+        assert_eq!(item.expires_at, 1_709_294_400 + 30 * 86_400);
+        // Saved back as unix seconds
+        assert!(serde_json::to_string(&item).unwrap().contains(r#""expires_at":1711886400"#));
+    }
 }
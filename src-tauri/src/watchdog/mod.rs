@@ -0,0 +1,215 @@
+//! Watchdog for a hung backend. The database connection sits behind a single
+//! mutex and scans run on blocking threads; either can wedge and leave every
+//! command waiting behind it. [`status`] reports what's stuck without taking
+//! the database lock, so `ping` still answers then. A background check logs
+//! when the backend turns degraded and resets what's stuck: a query holding the
+//! database too long is interrupted, and a scan running past every command
+//! timeout is cancelled, then unregistered if it ignores that.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::scanner::{self, LongRunningScan};
+
+/// The database lock held this long means a query is stuck; the busy timeout is 5s
+const DB_STALL: Duration = Duration::from_secs(30);
+/// Longer than any scan command's timeout, so the command gave up on it already
+const SCAN_STALL: Duration = Duration::from_secs(20 * 60);
+/// How long a stalled scan gets to notice its cancel flag before it's abandoned
+const SCAN_CANCEL_GRACE: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+lazy_static::lazy_static! {
+    static ref EPOCH: Instant = Instant::now();
+    static ref DB_INTERRUPT: Mutex<Option<rusqlite::InterruptHandle>> = Mutex::new(None);
+    static ref RECOVERIES: Mutex<Recoveries> = Mutex::new(Recoveries::default());
+}
+
+/// Milliseconds after EPOCH the database lock was taken, plus one; 0 while it's free
+static DB_HELD_SINCE: AtomicU64 = AtomicU64::new(0);
+/// The DB_HELD_SINCE value last interrupted, so one stuck hold is interrupted once
+static DB_INTERRUPTED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct Recoveries {
+    count: u32,
+    last: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct HealthIssue {
+    pub subsystem: String, // "database" or "scan:<kind>"
+    pub detail: String,
+    pub blocked_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct BackendHealth {
+    pub degraded: bool,
+    pub uptime_secs: u64,
+    pub issues: Vec<HealthIssue>,
+    pub recoveries: u32, // subsystems reset since start
+    pub last_recovery: Option<String>,
+}
+
+/// Marks the database lock as taken until dropped; see `DbAccess::db`
+pub struct DatabaseHold(());
+
+impl Drop for DatabaseHold {
+    fn drop(&mut self) {
+        DB_HELD_SINCE.store(0, Ordering::SeqCst);
+    }
+}
+
+pub fn hold_database() -> DatabaseHold {
+    DB_HELD_SINCE.store(EPOCH.elapsed().as_millis() as u64 + 1, Ordering::SeqCst);
+    DatabaseHold(())
+}
+
+/// Keep a handle to interrupt `conn`'s queries from the watchdog
+pub fn register_database(conn: &rusqlite::Connection) {
+    *DB_INTERRUPT.lock().unwrap_or_else(|e| e.into_inner()) = Some(conn.get_interrupt_handle());
+}
+
+fn held_for(since: u64) -> Option<Duration> {
+    match since {
+        0 => None,
+        since => Some(EPOCH.elapsed().saturating_sub(Duration::from_millis(since - 1))),
+    }
+}
+
+/// Whether a database call now would wait behind a stuck one
+pub fn database_blocked() -> bool {
+    held_for(DB_HELD_SINCE.load(Ordering::SeqCst)).is_some_and(|held| held >= DB_STALL)
+}
+
+fn issues(db_held: Option<Duration>, scans: &[LongRunningScan]) -> Vec<HealthIssue> {
+    let mut issues = Vec::new();
+    if let Some(held) = db_held.filter(|held| *held >= DB_STALL) {
+        issues.push(HealthIssue {
+            subsystem: "database".to_string(),
+            detail: "A query has been holding the database".to_string(),
+            blocked_secs: held.as_secs(),
+        });
+    }
+    for scan in scans {
+        let detail = if scan.cancelled { "Scan still running after being cancelled" } else { "Scan running past its timeout" };
+        issues.push(HealthIssue {
+            subsystem: format!("scan:{}", scan.kind),
+            detail: detail.to_string(),
+            blocked_secs: scan.running_for.as_secs(),
+        });
+    }
+    issues
+}
+
+/// What's stuck right now
+pub fn status() -> BackendHealth {
+    let issues = issues(held_for(DB_HELD_SINCE.load(Ordering::SeqCst)), &scanner::scans_running_longer_than(SCAN_STALL));
+    let recoveries = RECOVERIES.lock().unwrap_or_else(|e| e.into_inner());
+    BackendHealth {
+        degraded: !issues.is_empty(),
+        uptime_secs: EPOCH.elapsed().as_secs(),
+        issues,
+        recoveries: recoveries.count,
+        last_recovery: recoveries.last.clone(),
+    }
+}
+
+fn record_recovery(action: String) {
+    tracing::warn!("Watchdog: {}", action);
+    let mut recoveries = RECOVERIES.lock().unwrap_or_else(|e| e.into_inner());
+    recoveries.count += 1;
+    recoveries.last = Some(action);
+}
+
+/// Interrupt a stuck query, cancel stalled scans and abandon those that ignore it
+fn recover() {
+    let since = DB_HELD_SINCE.load(Ordering::SeqCst);
+    if let Some(held) = held_for(since).filter(|held| *held >= DB_STALL) {
+        // Interrupting only ends SQLite work; a hold stuck elsewhere is left to report
+        if DB_INTERRUPTED.swap(since, Ordering::SeqCst) != since {
+            if let Some(handle) = DB_INTERRUPT.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+                handle.interrupt();
+                record_recovery(format!("Interrupted a database query held for {}s", held.as_secs()));
+            }
+        }
+    }
+
+    for scan in scanner::scans_running_longer_than(SCAN_STALL) {
+        if !scan.cancelled {
+            scanner::cancel_scans(Some(scan.kind));
+            record_recovery(format!("Cancelled the {} scan after {} minutes", scan.kind, scan.running_for.as_secs() / 60));
+        } else if scan.running_for >= SCAN_STALL + SCAN_CANCEL_GRACE && scanner::abandon_scan(scan.kind) {
+            record_recovery(format!("Abandoned the {} scan, which didn't stop when cancelled", scan.kind));
+        }
+    }
+}
+
+/// Check on the backend every few seconds for the rest of the process
+pub fn start() {
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    STARTED.call_once(|| {
+        lazy_static::initialize(&EPOCH);
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            let mut was_degraded = false;
+            loop {
+                interval.tick().await;
+                let health = status();
+                if health.degraded && !was_degraded {
+                    let blocked: Vec<&str> = health.issues.iter().map(|i| i.subsystem.as_str()).collect();
+                    tracing::warn!("Backend degraded, blocked: {}", blocked.join(", "));
+                } else if !health.degraded && was_degraded {
+                    tracing::info!("Backend recovered");
+                }
+                was_degraded = health.degraded;
+                if health.degraded {
+                    recover();
+                }
+            }
+        });
+    });
+}
+
+/// Answers as long as the runtime does, even with the database stuck; the tray
+/// and agent clients call it to tell a busy backend from a hung one
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn ping() -> Result<BackendHealth, String> {
+    Ok(status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_hold() {
+        let hold = hold_database();
+        assert!(held_for(DB_HELD_SINCE.load(Ordering::SeqCst)).is_some_and(|held| held < DB_STALL));
+        assert!(!database_blocked());
+        drop(hold);
+        assert_eq!(held_for(DB_HELD_SINCE.load(Ordering::SeqCst)), None);
+    }
+
+    #[test]
+    fn test_issues() {
+        assert!(issues(Some(Duration::from_secs(5)), &[]).is_empty());
+
+        let scans = [
+            LongRunningScan { kind: "system", running_for: Duration::from_secs(1500), cancelled: false },
+            LongRunningScan { kind: "duplicates", running_for: Duration::from_secs(1300), cancelled: true },
+        ];
+        let found = issues(Some(Duration::from_secs(45)), &scans);
+        let subsystems: Vec<&str> = found.iter().map(|i| i.subsystem.as_str()).collect();
+        assert_eq!(subsystems, ["database", "scan:system", "scan:duplicates"]);
+        assert_eq!(found[0].blocked_secs, 45);
+        assert_eq!(found[2].detail, "Scan still running after being cancelled");
+    }
+}
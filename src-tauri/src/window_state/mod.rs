@@ -0,0 +1,163 @@
+//! Main window geometry and last active view, kept in the settings table and
+//! restored at startup so the app reopens where it was left.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::Mutex;
+use tauri::{Manager, PhysicalPosition, PhysicalSize};
+
+use crate::db::DbAccess;
+use crate::detached::MAIN_LABEL;
+
+const SETTINGS_KEY: &str = "window_state";
+
+lazy_static::lazy_static! {
+    static ref CURRENT: Mutex<WindowState> = Mutex::new(WindowState::default());
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[specta(export)]
+pub struct WindowState {
+    pub x: Option<i32>, // physical pixels, outer position
+    pub y: Option<i32>,
+    pub width: Option<u32>, // physical pixels, inner size while not maximized
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub maximized: bool,
+    pub last_view: Option<String>,
+}
+
+/// A monitor's area in physical pixels: x, y, width, height
+pub type MonitorRect = (i32, i32, u32, u32);
+
+pub fn load(conn: &Connection) -> rusqlite::Result<Option<WindowState>> {
+    let json: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [SETTINGS_KEY], |row| row.get(0))
+        .optional()?;
+    Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+}
+
+pub fn save(conn: &Connection, state: &WindowState) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(state).map_err(|_| rusqlite::Error::InvalidQuery)?;
+    conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)", [SETTINGS_KEY, &json])?;
+    Ok(())
+}
+
+/// Whether the window's title bar area would land on a connected monitor;
+/// a saved position from an unplugged display is dropped
+pub fn position_visible(x: i32, y: i32, width: u32, monitors: &[MonitorRect]) -> bool {
+    // Sample the middle of the top edge, where the title bar is
+    let (px, py) = (x + (width / 2) as i32, y + 10);
+    monitors
+        .iter()
+        .any(|&(mx, my, mw, mh)| px >= mx && px < mx + mw as i32 && py >= my && py < my + mh as i32)
+}
+
+fn persist(app_handle: &tauri::AppHandle) {
+    let state = CURRENT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Err(e) = app_handle.db(|conn| save(conn, &state)) {
+        tracing::warn!("Failed to save window state: {}", e);
+    }
+}
+
+/// Apply the saved geometry to the main window; called from setup
+pub fn restore(app_handle: &tauri::AppHandle) {
+    let Ok(Some(state)) = app_handle.db(load) else { return };
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = state.clone();
+    let Some(window) = app_handle.get_webview_window(MAIN_LABEL) else { return };
+
+    if let (Some(width), Some(height)) = (state.width, state.height) {
+        let _ = window.set_size(PhysicalSize::new(width, height));
+    }
+    if let (Some(x), Some(y)) = (state.x, state.y) {
+        let monitors: Vec<MonitorRect> = window
+            .available_monitors()
+            .unwrap_or_default()
+            .iter()
+            .map(|m| (m.position().x, m.position().y, m.size().width, m.size().height))
+            .collect();
+        if position_visible(x, y, state.width.unwrap_or(0), &monitors) {
+            let _ = window.set_position(PhysicalPosition::new(x, y));
+        }
+    }
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Track main window geometry; written to the database when it loses focus or closes
+pub fn handle_window_event(window: &tauri::Window, event: &tauri::WindowEvent) {
+    if window.label() != MAIN_LABEL {
+        return;
+    }
+    match event {
+        tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+            let maximized = window.is_maximized().unwrap_or(false);
+            let mut state = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+            state.maximized = maximized;
+            // Keep the restored geometry so un-maximizing after restart goes back to it
+            if !maximized && !window.is_minimized().unwrap_or(false) {
+                if let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) {
+                    state.x = Some(position.x);
+                    state.y = Some(position.y);
+                    state.width = Some(size.width);
+                    state.height = Some(size.height);
+                }
+            }
+        }
+        tauri::WindowEvent::Focused(false) | tauri::WindowEvent::CloseRequested { .. } => {
+            persist(window.app_handle());
+        }
+        _ => {}
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn get_window_state() -> Result<WindowState, String> {
+    Ok(CURRENT.lock().map_err(|e| format!("Lock failed: {}", e))?.clone())
+}
+
+/// Remember the active view for the next launch
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn set_last_view(app_handle: tauri::AppHandle, view: String) -> Result<(), String> {
+    CURRENT.lock().map_err(|e| format!("Lock failed: {}", e))?.last_view = Some(view);
+    persist(&app_handle);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);").unwrap();
+        assert_eq!(load(&conn).unwrap(), None);
+
+        let state = WindowState {
+            x: Some(-1920),
+            y: Some(40),
+            width: Some(1400),
+            height: Some(900),
+            maximized: true,
+            last_view: Some("disk-pulse".to_string()),
+        };
+        save(&conn, &state).unwrap();
+        save(&conn, &state).unwrap();
+        assert_eq!(load(&conn).unwrap(), Some(state));
+    }
+
+    #[test]
+    fn test_position_visible() {
+        let monitors = [(0, 0, 2560, 1440), (-1920, 0, 1920, 1080)];
+        assert!(position_visible(100, 100, 1200, &monitors));
+        assert!(position_visible(-1800, 50, 1200, &monitors));
+        // Secondary monitor above the primary was unplugged
+        assert!(!position_visible(100, -1200, 1200, &monitors));
+        assert!(!position_visible(3000, 100, 1200, &monitors));
+    }
+}
@@ -0,0 +1,353 @@
+//! Wine prefixes: `~/.wine` and winetricks prefixes, the prefixes Lutris
+//! creates per game and the `compatdata` prefixes Steam keeps per Proton game.
+//! A prefix is a whole Windows install, often several GB, and nothing removes
+//! it when the game goes: Steam leaves `compatdata` behind on uninstall and
+//! Lutris keeps `~/Games/<game>` unless asked. Prefixes also hold save games,
+//! so they are listed with their last run and never as safe to remove.
+
+use std::collections::HashSet;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::scanner::builtin::new_item;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "Wine Prefixes";
+/// Days without a run after which a prefix counts as abandoned
+pub const DEFAULT_STALE_DAYS: u32 = 180;
+
+/// Where Steam keeps its data, native and Flatpak
+const STEAM_ROOTS: &[&str] = &[".local/share/Steam", ".steam/steam", ".var/app/com.valvesoftware.Steam/.local/share/Steam"];
+/// Lutris game configs, older and newer releases
+const LUTRIS_CONFIG_DIRS: &[&str] = &[".config/lutris/games", ".local/share/lutris/games"];
+/// Steam gives shortcuts to non-Steam games app ids from here up; they have no manifest
+const SHORTCUT_APP_IDS: u64 = 1 << 31;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixKind {
+    Wine,
+    Lutris,
+    Proton,
+}
+
+impl PrefixKind {
+    fn label(self) -> &'static str {
+        match self {
+            PrefixKind::Wine => "Wine",
+            PrefixKind::Lutris => "Lutris",
+            PrefixKind::Proton => "Proton",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WinePrefix {
+    /// Folder removed with the prefix; for Proton the app's `compatdata` folder
+    pub path: PathBuf,
+    pub kind: PrefixKind,
+    pub name: String,
+    /// Last time Wine ran in it, 0 if unknown
+    pub last_run: i64,
+    /// False when the game it belongs to is no longer installed
+    pub installed: bool,
+}
+
+fn is_prefix(path: &Path) -> bool {
+    path.join("drive_c").is_dir() && path.join("system.reg").is_file()
+}
+
+/// Wine rewrites the registry files of a prefix every time it starts in it
+fn last_run(prefix: &Path) -> i64 {
+    ["user.reg", "system.reg", "userdef.reg"]
+        .iter()
+        .filter_map(|file| std::fs::metadata(prefix.join(file)).ok())
+        .map(|m| m.mtime())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Values of `key` in a Steam VDF file, e.g. the `"path"` of every library
+/// in `libraryfolders.vdf`
+pub fn vdf_values(text: &str, key: &str) -> Vec<String> {
+    let quoted = format!("\"{}\"", key);
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix(&quoted))
+        .filter_map(|rest| {
+            let rest = rest.trim();
+            rest.strip_prefix('"')?.strip_suffix('"').map(|v| v.replace("\\\\", "\\"))
+        })
+        .collect()
+}
+
+fn expand_home(path: &str, home: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+fn wine_prefixes(home: &Path) -> Vec<WinePrefix> {
+    // ~/.wine, ~/.wine32 and the like, plus the ones winetricks manages
+    let dotted = std::fs::read_dir(home)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(".wine"))
+        .map(|e| e.path());
+    let winetricks = std::fs::read_dir(home.join(".local/share/wineprefixes")).into_iter().flatten().filter_map(|e| e.ok()).map(|e| e.path());
+    dotted
+        .chain(winetricks)
+        .filter(|path| is_prefix(path))
+        .map(|path| WinePrefix {
+            name: path.strip_prefix(home).map(|p| format!("~/{}", p.display())).unwrap_or_else(|_| path.display().to_string()),
+            last_run: last_run(&path),
+            kind: PrefixKind::Wine,
+            installed: true,
+            path,
+        })
+        .collect()
+}
+
+fn lutris_prefixes(home: &Path) -> Vec<WinePrefix> {
+    let mut prefixes = Vec::new();
+    for dir in LUTRIS_CONFIG_DIRS {
+        for entry in std::fs::read_dir(home.join(dir)).into_iter().flatten().filter_map(|e| e.ok()) {
+            let Ok(config) = std::fs::read_to_string(entry.path()) else { continue };
+            // Configs are named `<slug>-<timestamp>.yml`
+            let stem = entry.path().file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let slug = stem.rsplit_once('-').filter(|(_, ts)| ts.chars().all(|c| c.is_ascii_digit())).map_or(stem.as_str(), |(slug, _)| slug);
+            for line in config.lines() {
+                let Some(value) = line.trim().strip_prefix("prefix:") else { continue };
+                let path = expand_home(value.trim().trim_matches(['"', '\'']), home);
+                if is_prefix(&path) {
+                    prefixes.push(WinePrefix { name: slug.to_string(), last_run: last_run(&path), kind: PrefixKind::Lutris, installed: true, path });
+                }
+            }
+        }
+    }
+    // Lutris installs to ~/Games by default; prefixes there without a config are left from removed games
+    let known: HashSet<PathBuf> = prefixes.iter().map(|p| p.path.clone()).collect();
+    for entry in std::fs::read_dir(home.join("Games")).into_iter().flatten().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        for prefix in [path.clone(), path.join("prefix")] {
+            if is_prefix(&prefix) && !known.contains(&prefix) {
+                prefixes.push(WinePrefix {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    last_run: last_run(&prefix),
+                    kind: PrefixKind::Lutris,
+                    installed: false,
+                    path: prefix,
+                });
+            }
+        }
+    }
+    prefixes
+}
+
+/// Steam library folders, each once however many roots point at it
+fn steam_libraries(home: &Path) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    let mut libraries = Vec::new();
+    for root in STEAM_ROOTS.iter().map(|r| home.join(r)) {
+        let listed = std::fs::read_to_string(root.join("steamapps/libraryfolders.vdf"))
+            .map(|text| vdf_values(&text, "path"))
+            .unwrap_or_default();
+        for library in std::iter::once(root).chain(listed.into_iter().map(PathBuf::from)) {
+            let Ok(canonical) = library.canonicalize() else { continue };
+            if canonical.join("steamapps").is_dir() && seen.insert(canonical.clone()) {
+                libraries.push(canonical);
+            }
+        }
+    }
+    libraries
+}
+
+fn proton_prefixes(home: &Path) -> Vec<WinePrefix> {
+    let libraries = steam_libraries(home);
+    // A game's manifest may be in another library than its compatdata
+    let game_name = |app_id: &str| {
+        libraries.iter().find_map(|library| {
+            let manifest = std::fs::read_to_string(library.join(format!("steamapps/appmanifest_{}.acf", app_id))).ok()?;
+            vdf_values(&manifest, "name").into_iter().next()
+        })
+    };
+
+    let mut prefixes = Vec::new();
+    for library in &libraries {
+        for entry in std::fs::read_dir(library.join("steamapps/compatdata")).into_iter().flatten().filter_map(|e| e.ok()) {
+            let app_id = entry.file_name().to_string_lossy().to_string();
+            let Ok(id) = app_id.parse::<u64>() else { continue };
+            let path = entry.path();
+            if !is_prefix(&path.join("pfx")) {
+                continue;
+            }
+            let (name, installed) = match game_name(&app_id) {
+                Some(name) => (name, true),
+                None if id >= SHORTCUT_APP_IDS => (format!("Non-Steam game {}", app_id), true),
+                None => (format!("Steam app {}", app_id), false),
+            };
+            prefixes.push(WinePrefix { last_run: last_run(&path.join("pfx")), path, kind: PrefixKind::Proton, name, installed });
+        }
+    }
+    prefixes
+}
+
+/// Wine, Lutris and Proton prefixes in `home` and the Steam libraries it knows of
+pub fn find_prefixes(home: &Path) -> Vec<WinePrefix> {
+    let mut seen = HashSet::new();
+    let mut prefixes: Vec<WinePrefix> = proton_prefixes(home)
+        .into_iter()
+        .chain(lutris_prefixes(home))
+        .chain(wine_prefixes(home))
+        // A Lutris game may well run in ~/.wine
+        .filter(|p| seen.insert(p.path.canonicalize().unwrap_or_else(|_| p.path.clone())))
+        .collect();
+    prefixes.sort_by(|a, b| a.path.cmp(&b.path));
+    prefixes
+}
+
+pub struct WinePrefixScanner;
+
+impl Scanner for WinePrefixScanner {
+    fn id(&self) -> &'static str {
+        "wine_prefixes"
+    }
+
+    fn name(&self) -> &'static str {
+        "Wine and Proton prefixes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Windows prefixes of Wine, Lutris games and Steam Proton games"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        vec![ScannerOptionSchema {
+            key: "stale_days".to_string(),
+            label: "Not run for (days)".to_string(),
+            value_type: "number".to_string(),
+            default_value: DEFAULT_STALE_DAYS.to_string(),
+        }]
+    }
+
+    /// On when large files are included and there is a prefix
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_large_files && dirs::home_dir().is_some_and(|home| !find_prefixes(&home).is_empty())
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let stale_days = ctx.option("stale_days", DEFAULT_STALE_DAYS) as i64;
+        let now = chrono::Utc::now().timestamp();
+
+        ctx.progress(0, "Looking for Wine and Proton prefixes...");
+        let prefixes = find_prefixes(&home);
+        let mut items = Vec::new();
+        for (index, prefix) in prefixes.iter().enumerate() {
+            ctx.check_cancelled()?;
+            if !ctx.allows(&prefix.path) {
+                continue;
+            }
+            ctx.progress((index * 100 / prefixes.len()) as u8, &format!("Measuring {}...", prefix.name));
+            let last_run = if prefix.last_run > 0 {
+                format!("last run {} days ago", (now - prefix.last_run).max(0) / (24 * 3600))
+            } else {
+                "never run".to_string()
+            };
+            let stale = prefix.last_run == 0 || now - prefix.last_run >= stale_days * 24 * 3600;
+            // Save games often live only in the prefix
+            let (risk_level, description) = match (prefix.installed, prefix.kind) {
+                (false, PrefixKind::Proton) => (1, format!("Game no longer installed, {}; saves not in Steam Cloud are lost with it", last_run)),
+                (false, _) => (1, format!("No Lutris game uses it, {}; check for save games first", last_run)),
+                (true, _) if stale => (2, format!("Abandoned, {}; save games in it are lost with it", last_run)),
+                (true, _) => (3, format!("In use, {}", last_run)),
+            };
+            items.push(new_item(
+                format!("{} prefix ({})", prefix.kind.label(), prefix.name),
+                &prefix.path,
+                ctx.dir_size(&prefix.path),
+                "directory",
+                CATEGORY,
+                risk_level,
+                &description,
+            ));
+        }
+        items.sort_by(|a, b| a.risk_level.cmp(&b.risk_level).then(b.size.cmp(&a.size)));
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn make_prefix(path: &Path) {
+        fs::create_dir_all(path.join("drive_c/windows")).unwrap();
+        fs::write(path.join("system.reg"), "WINE REGISTRY Version 2").unwrap();
+        fs::write(path.join("user.reg"), "WINE REGISTRY Version 2").unwrap();
+    }
+
+    #[test]
+    fn test_vdf_values() {
+        let text = r#""libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/u/.local/share/Steam"
+		"apps" { "620" "12000" }
+	}
+	"1"
+	{
+		"path"		"/mnt/games\\Steam"
+	}
+}"#;
+        assert_eq!(vdf_values(text, "path"), vec!["/home/u/.local/share/Steam", "/mnt/games\\Steam"]);
+        assert!(vdf_values(text, "name").is_empty());
+    }
+
+    #[test]
+    fn test_find_prefixes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Steam libraries are listed canonicalized
+        let home = dir.path().canonicalize().unwrap();
+        make_prefix(&home.join(".wine"));
+        fs::create_dir_all(home.join(".wine-notaprefix")).unwrap();
+
+        let other_library = home.join("ssd/SteamLibrary");
+        let steam = home.join(".local/share/Steam");
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        fs::create_dir_all(other_library.join("steamapps")).unwrap();
+        fs::write(
+            steam.join("steamapps/libraryfolders.vdf"),
+            format!("\"libraryfolders\"\n{{\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n", other_library.display()),
+        )
+        .unwrap();
+        fs::write(other_library.join("steamapps/appmanifest_620.acf"), "\"AppState\"\n{\n\t\"name\"\t\t\"Portal 2\"\n}\n").unwrap();
+        make_prefix(&steam.join("steamapps/compatdata/620/pfx"));
+        make_prefix(&other_library.join("steamapps/compatdata/8930/pfx"));
+
+        make_prefix(&home.join("Games/witcher"));
+        make_prefix(&home.join("Games/old-game"));
+        fs::create_dir_all(home.join(".config/lutris/games")).unwrap();
+        fs::write(
+            home.join(".config/lutris/games/the-witcher-1700000000.yml"),
+            "game:\n  exe: drive_c/witcher.exe\n  prefix: ~/Games/witcher\nwine:\n  version: lutris-7\n",
+        )
+        .unwrap();
+
+        let found: Vec<(String, PrefixKind, bool)> = find_prefixes(&home).into_iter().map(|p| (p.name, p.kind, p.installed)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Portal 2".to_string(), PrefixKind::Proton, true),
+                ("~/.wine".to_string(), PrefixKind::Wine, true),
+                ("old-game".to_string(), PrefixKind::Lutris, false),
+                ("the-witcher".to_string(), PrefixKind::Lutris, true),
+                ("Steam app 8930".to_string(), PrefixKind::Proton, false),
+            ]
+        );
+    }
+}
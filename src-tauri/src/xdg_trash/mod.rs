@@ -0,0 +1,469 @@
+//! The desktop's own trash, as file managers fill it following the XDG trash
+//! spec: `~/.local/share/Trash`, the `.Trash/$uid` and `.Trash-$uid` folders at
+//! the top of other mounted drives, and the trash of sandboxed Flatpak apps
+//! under `~/.var/app`. Each trashed file sits in `files/` with a `.trashinfo`
+//! in `info/` recording where it came from and when it was deleted. Unlike
+//! Pulito's own trash (see `trash`), emptying it deletes for good.
+
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+
+use crate::cache_redirect;
+use crate::commands::CleanResult;
+use crate::db::DbAccess;
+use crate::digest;
+use crate::operations::{guard, OperationPlan};
+use crate::scanner::builtin::new_item;
+use crate::scanner::registry::ScannerOptionSchema;
+use crate::scanner::{ScanContext, ScanItem, ScanOptions, Scanner, ScannerError};
+
+pub const CATEGORY: &str = "System Trash";
+
+/// Entries listed under each trash folder in scan results, largest first
+const MAX_LISTED_ENTRIES: usize = 100;
+
+/// Filesystems that never hold a trash folder
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs", "debugfs", "tracefs", "pstore",
+    "bpf", "mqueue", "hugetlbfs", "configfs", "fusectl", "autofs", "binfmt_misc", "efivarfs", "squashfs", "overlay",
+    "nsfs",
+];
+
+/// A trash folder and what it holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashDir {
+    pub path: PathBuf,
+    pub label: String, // "Home", the drive's mount point or the Flatpak app id
+    pub entries: Vec<TrashEntry>,
+}
+
+/// One trashed file or folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    pub file_path: PathBuf,            // inside files/
+    pub original_path: Option<String>, // from the .trashinfo; missing for orphans
+    pub deleted_at: Option<i64>,
+    pub size: u64,
+}
+
+impl TrashEntry {
+    fn info_path(&self) -> Option<PathBuf> {
+        let name = self.file_path.file_name()?;
+        let trash = self.file_path.parent()?.parent()?;
+        Some(trash.join("info").join(format!("{}.trashinfo", name.to_string_lossy())))
+    }
+
+    fn age_days(&self, now: i64) -> Option<i64> {
+        self.deleted_at.map(|deleted| (now - deleted).max(0) / (24 * 3600))
+    }
+}
+
+/// Decode the %XX escapes the spec uses in `Path=`
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Original path and deletion time (local time, as the spec has it) from a
+/// `.trashinfo` file. Relative paths are relative to the drive the trash is on.
+pub fn parse_trashinfo(contents: &str) -> Option<(String, Option<i64>)> {
+    let mut lines = contents.lines().map(str::trim).skip_while(|l| l.is_empty());
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+    let mut path = None;
+    let mut deleted_at = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Path=") {
+            path = Some(percent_decode(value));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deleted_at = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+                .ok()
+                .and_then(|date| Local.from_local_datetime(&date).earliest())
+                .map(|date| date.timestamp());
+        } else if line.starts_with('[') {
+            break;
+        }
+    }
+    Some((path?, deleted_at))
+}
+
+fn entry_size(path: &Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => crate::trash::get_dir_size(path),
+        Ok(m) => m.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Everything in one trash folder; `root` resolves relative `Path=` values
+pub fn list_entries(trash: &Path, root: &Path) -> Vec<TrashEntry> {
+    let Ok(files) = std::fs::read_dir(trash.join("files")) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<TrashEntry> = files
+        .filter_map(|e| e.ok())
+        .map(|file| {
+            let name = file.file_name().to_string_lossy().to_string();
+            let info = std::fs::read_to_string(trash.join("info").join(format!("{}.trashinfo", name)))
+                .ok()
+                .and_then(|contents| parse_trashinfo(&contents));
+            let (original_path, deleted_at) = match info {
+                Some((path, deleted_at)) if Path::new(&path).is_absolute() => (Some(path), deleted_at),
+                Some((path, deleted_at)) => (Some(root.join(path).to_string_lossy().to_string()), deleted_at),
+                None => (None, None),
+            };
+            TrashEntry { size: entry_size(&file.path()), file_path: file.path(), original_path, deleted_at }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.size.cmp(&a.size));
+    entries
+}
+
+/// `path` itself as a directory, not a symlink to one
+fn real_dir(path: &Path) -> Option<std::fs::Metadata> {
+    std::fs::symlink_metadata(path).ok().filter(|m| m.file_type().is_dir())
+}
+
+/// The user's trash folders at the top of a drive, checked as the spec asks since
+/// anyone who can write there can plant them: `.Trash` must be a sticky directory,
+/// and `.Trash/$uid` and `.Trash-$uid` the user's own, none of them symlinks
+fn topdir_trashes(top: &Path, uid: u32) -> Vec<PathBuf> {
+    let owned = |path: &Path| real_dir(path).is_some_and(|m| m.uid() == uid);
+    let mut trashes = Vec::new();
+    let shared = top.join(".Trash");
+    if real_dir(&shared).is_some_and(|m| m.mode() & libc::S_ISVTX != 0) && owned(&shared.join(uid.to_string())) {
+        trashes.push(shared.join(uid.to_string()));
+    }
+    let own = top.join(format!(".Trash-{}", uid));
+    if owned(&own) {
+        trashes.push(own);
+    }
+    trashes
+}
+
+/// Trash folders that exist: the home trash, those of other drives and of Flatpak apps
+pub fn trash_locations(home: &Path) -> Vec<(PathBuf, PathBuf, String)> {
+    let uid = unsafe { libc::getuid() };
+    let data_home = dirs::data_dir().unwrap_or_else(|| home.join(".local/share"));
+    let mut locations = vec![(data_home.join("Trash"), PathBuf::from("/"), "Home".to_string())];
+
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    for mount in cache_redirect::parse_mounts(&mounts) {
+        if PSEUDO_FILESYSTEMS.contains(&mount.fs_type.as_str()) {
+            continue;
+        }
+        let top = PathBuf::from(&mount.mount_point);
+        for trash in topdir_trashes(&top, uid) {
+            locations.push((trash, top.clone(), mount.mount_point.clone()));
+        }
+    }
+
+    if let Ok(apps) = std::fs::read_dir(home.join(".var/app")) {
+        for app in apps.filter_map(|e| e.ok()) {
+            let app_id = app.file_name().to_string_lossy().to_string();
+            locations.push((app.path().join("data/Trash"), PathBuf::from("/"), app_id));
+        }
+    }
+
+    // Symlinks aren't followed: emptying one would delete wherever it points.
+    // A bind-mounted drive shows the same folder twice, told apart by inode.
+    let mut seen = Vec::new();
+    locations.retain(|(trash, _, _)| {
+        let identity = real_dir(trash).filter(|_| real_dir(&trash.join("files")).is_some()).map(|m| (m.dev(), m.ino()));
+        match identity {
+            Some(identity) if !seen.contains(&identity) => {
+                seen.push(identity);
+                true
+            }
+            _ => false,
+        }
+    });
+    locations
+}
+
+/// Every trash folder with what it holds
+pub fn list_trash_dirs(home: &Path) -> Vec<TrashDir> {
+    trash_locations(home)
+        .into_iter()
+        .map(|(path, root, label)| TrashDir { entries: list_entries(&path, &root), path, label })
+        .collect()
+}
+
+/// Entries of `dir` deleted at least `older_than_days` ago at `now`; entries
+/// without a deletion date are orphans left by a crashed file manager and always count
+pub fn expired(dir: &TrashDir, older_than_days: u32, now: i64) -> Vec<&TrashEntry> {
+    dir.entries
+        .iter()
+        .filter(|entry| entry.age_days(now).map_or(true, |age| age >= older_than_days as i64))
+        .collect()
+}
+
+fn entry_item(entry: &TrashEntry, now: i64) -> ScanItem {
+    let name = entry.file_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let description = match (&entry.original_path, entry.age_days(now)) {
+        (Some(original), Some(age)) => format!("Deleted {} days ago from {}", age, original),
+        (Some(original), None) => format!("Deleted from {}", original),
+        _ => "Left in the trash without a record of where it came from".to_string(),
+    };
+    let name = entry
+        .original_path
+        .as_deref()
+        .and_then(|p| Path::new(p).file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(name);
+    new_item(name, &entry.file_path, entry.size, "xdg_trash_entry", CATEGORY, 1, &description)
+}
+
+pub struct XdgTrashScanner;
+
+impl Scanner for XdgTrashScanner {
+    fn id(&self) -> &'static str {
+        "system_trash"
+    }
+
+    fn name(&self) -> &'static str {
+        "System Trash"
+    }
+
+    fn description(&self) -> &'static str {
+        "Files deleted to the desktop trash, on every drive and in Flatpak apps"
+    }
+
+    fn options_schema(&self) -> Vec<ScannerOptionSchema> {
+        vec![ScannerOptionSchema {
+            key: "min_age_days".to_string(),
+            label: "Only items deleted more than (days) ago".to_string(),
+            value_type: "number".to_string(),
+            default_value: "0".to_string(),
+        }]
+    }
+
+    fn enabled_by_default(&self, options: &ScanOptions) -> bool {
+        options.include_caches && dirs::home_dir().is_some_and(|home| !trash_locations(&home).is_empty())
+    }
+
+    fn run(&self, ctx: &ScanContext) -> Result<Vec<ScanItem>, ScannerError> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| ScannerError::PathValidationError("Cannot determine home directory".to_string()))?;
+        let min_age_days = ctx.option("min_age_days", 0u32);
+        let now = chrono::Utc::now().timestamp();
+        let mut items = Vec::new();
+
+        ctx.progress(0, "Looking for trash folders...");
+        for dir in list_trash_dirs(&home) {
+            ctx.check_cancelled()?;
+            if !ctx.allows(&dir.path) {
+                continue;
+            }
+            let entries = expired(&dir, min_age_days, now);
+            let size: u64 = entries.iter().map(|e| e.size).sum();
+            if entries.is_empty() || size == 0 {
+                continue;
+            }
+            let oldest = entries.iter().filter_map(|e| e.age_days(now)).max();
+            let description = match oldest {
+                Some(days) => format!("{} items in the {} trash, the oldest deleted {} days ago", entries.len(), dir.label, days),
+                None => format!("{} items in the {} trash", entries.len(), dir.label),
+            };
+            let mut item = new_item(format!("{} trash", dir.label), &dir.path, size, "xdg_trash", CATEGORY, 1, &description);
+            item.children = Some(entries.iter().take(MAX_LISTED_ENTRIES).map(|e| entry_item(e, now)).collect());
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+/// Check every path names a trash folder or one trashed entry, and resolve them
+pub fn resolve_targets<'a>(
+    found: &'a [TrashDir],
+    trash_dirs: &[String],
+    entries: &[String],
+) -> Result<(Vec<&'a TrashDir>, Vec<&'a TrashEntry>), String> {
+    let selected_dirs = trash_dirs
+        .iter()
+        .map(|path| found.iter().find(|d| d.path == Path::new(path)).ok_or_else(|| format!("{} is not a trash folder", path)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let selected_entries = entries
+        .iter()
+        .map(|path| {
+            found
+                .iter()
+                .flat_map(|d| d.entries.iter())
+                .find(|e| e.file_path == Path::new(path))
+                .ok_or_else(|| format!("{} is not in the trash", path))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((selected_dirs, selected_entries))
+}
+
+fn remove_entry(entry: &TrashEntry) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(&entry.file_path)?;
+    if metadata.is_dir() {
+        std::fs::remove_dir_all(&entry.file_path)?;
+    } else {
+        std::fs::remove_file(&entry.file_path)?;
+    }
+    if let Some(info) = entry.info_path() {
+        let _ = std::fs::remove_file(info);
+    }
+    Ok(())
+}
+
+/// Delete entries from the desktop trash for good: everything in `trash_dirs`
+/// deleted at least `older_than_days` ago, plus each of `entries`
+#[allow(dead_code)]
+#[tauri::command]
+pub async fn empty_system_trash(
+    app_handle: tauri::AppHandle,
+    trash_dirs: Vec<String>,
+    entries: Vec<String>,
+    older_than_days: u32,
+    confirmation_token: String,
+) -> Result<CleanResult, String> {
+    let plan = OperationPlan::EmptySystemTrash { trash_dirs: trash_dirs.clone(), entries: entries.clone(), older_than_days };
+    guard::authorize(&app_handle, &plan, &confirmation_token)?;
+
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    let result = tokio::task::spawn_blocking(move || {
+        let found = list_trash_dirs(&home);
+        let (selected_dirs, selected_entries) = resolve_targets(&found, &trash_dirs, &entries)?;
+        let now = chrono::Utc::now().timestamp();
+        let mut targets: Vec<&TrashEntry> = selected_dirs.iter().flat_map(|d| expired(d, older_than_days, now)).collect();
+        targets.extend(selected_entries);
+        targets.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        targets.dedup_by(|a, b| a.file_path == b.file_path);
+
+        let mut result = CleanResult { cleaned: 0, failed: 0, total_size: 0, pending_release: Vec::new() };
+        let mut touched = Vec::new();
+        for entry in targets {
+            match remove_entry(entry) {
+                Ok(()) => {
+                    result.cleaned += 1;
+                    result.total_size += entry.size;
+                    if let Some(trash) = entry.file_path.parent().and_then(Path::parent) {
+                        if !touched.contains(&trash) {
+                            touched.push(trash);
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to remove {} from the trash: {}", entry.file_path.display(), e);
+                    result.failed += 1;
+                }
+            }
+        }
+        // File managers rebuild this size cache; stale lines would misreport the trash
+        for trash in touched {
+            let _ = std::fs::remove_file(trash.join("directorysizes"));
+        }
+        Ok::<_, String>(result)
+    })
+    .await
+    .map_err(|e| format!("Trash task failed: {}", e))??;
+
+    tracing::info!("Emptied {} items ({} bytes) from the system trash, {} failed", result.cleaned, result.total_size, result.failed);
+    if result.total_size > 0 {
+        if let Err(e) = app_handle.db(|conn| digest::record_cleanup(conn, CATEGORY, result.total_size)) {
+            tracing::warn!("Failed to record cleanup history: {}", e);
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trashinfo() {
+        let info = "[Trash Info]\nPath=/home/u/My%20Notes/plan%25.txt\nDeletionDate=2024-03-01T10:20:30\n";
+        let (path, deleted_at) = parse_trashinfo(info).unwrap();
+        assert_eq!(path, "/home/u/My Notes/plan%.txt");
+        let expected = Local.with_ymd_and_hms(2024, 3, 1, 10, 20, 30).unwrap().timestamp();
+        assert_eq!(deleted_at, Some(expected));
+
+        assert_eq!(parse_trashinfo("[Trash Info]\nPath=photos/a.jpg\n").unwrap(), ("photos/a.jpg".to_string(), None));
+        assert!(parse_trashinfo("[Desktop Entry]\nPath=/x\n").is_none());
+        assert!(parse_trashinfo("[Trash Info]\nDeletionDate=2024-03-01T10:20:30\n").is_none());
+    }
+
+    #[test]
+    fn test_list_and_remove_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let trash = dir.path().join("Trash");
+        std::fs::create_dir_all(trash.join("files/album")).unwrap();
+        std::fs::create_dir_all(trash.join("info")).unwrap();
+        std::fs::write(trash.join("files/album/a.jpg"), vec![0u8; 300]).unwrap();
+        std::fs::write(trash.join("files/report.pdf"), vec![0u8; 100]).unwrap();
+        std::fs::write(trash.join("files/orphan"), b"x").unwrap();
+        std::fs::write(trash.join("info/album.trashinfo"), "[Trash Info]\nPath=/home/u/album\nDeletionDate=2020-01-01T00:00:00\n").unwrap();
+        let recent = Local::now().format("%Y-%m-%dT%H:%M:%S");
+        std::fs::write(trash.join("info/report.pdf.trashinfo"), format!("[Trash Info]\nPath=Documents/report.pdf\nDeletionDate={}\n", recent)).unwrap();
+
+        let entries = list_entries(&trash, Path::new("/media/usb"));
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].original_path.as_deref(), Some("/home/u/album"));
+        assert_eq!(entries[0].size, 300);
+        assert_eq!(entries[1].original_path.as_deref(), Some("/media/usb/Documents/report.pdf"));
+        assert_eq!(entries[2].original_path, None);
+
+        let trash_dir = TrashDir { path: trash.clone(), label: "Home".to_string(), entries };
+        let now = chrono::Utc::now().timestamp();
+        let old: Vec<&Path> = expired(&trash_dir, 30, now).iter().map(|e| e.file_path.as_path()).collect();
+        assert_eq!(old, vec![trash.join("files/album").as_path(), trash.join("files/orphan").as_path()]);
+        assert_eq!(expired(&trash_dir, 0, now).len(), 3);
+
+        let dirs = vec![trash_dir.clone()];
+        let report = trash.join("files/report.pdf").to_string_lossy().to_string();
+        let (_, picked) = resolve_targets(&dirs, &[], &[report]).unwrap();
+        remove_entry(picked[0]).unwrap();
+        assert!(!trash.join("files/report.pdf").exists());
+        assert!(!trash.join("info/report.pdf.trashinfo").exists());
+        assert!(resolve_targets(&dirs, &["/tmp".to_string()], &[]).is_err());
+        assert!(resolve_targets(&dirs, &[], &["/etc/passwd".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_topdir_trashes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let drive = tempfile::TempDir::new().unwrap();
+        let top = drive.path();
+        let uid = unsafe { libc::getuid() };
+        std::fs::create_dir_all(top.join(".Trash").join(uid.to_string())).unwrap();
+        std::fs::create_dir(top.join(format!(".Trash-{}", uid))).unwrap();
+        assert_eq!(topdir_trashes(top, uid), vec![top.join(format!(".Trash-{}", uid))]); // .Trash not sticky
+
+        std::fs::set_permissions(top.join(".Trash"), std::fs::Permissions::from_mode(0o1777)).unwrap();
+        assert_eq!(topdir_trashes(top, uid), vec![top.join(".Trash").join(uid.to_string()), top.join(format!(".Trash-{}", uid))]);
+        // Folders named for another user but created by this one
+        std::fs::create_dir(top.join(".Trash").join((uid + 1).to_string())).unwrap();
+        std::fs::create_dir(top.join(format!(".Trash-{}", uid + 1))).unwrap();
+        assert!(topdir_trashes(top, uid + 1).is_empty());
+
+        let elsewhere = tempfile::TempDir::new().unwrap();
+        std::fs::remove_dir_all(top.join(".Trash")).unwrap();
+        std::fs::remove_dir(top.join(format!(".Trash-{}", uid))).unwrap();
+        std::os::unix::fs::symlink(elsewhere.path(), top.join(".Trash")).unwrap();
+        std::os::unix::fs::symlink(elsewhere.path(), top.join(format!(".Trash-{}", uid))).unwrap();
+        assert!(topdir_trashes(top, uid).is_empty());
+    }
+}
@@ -1,9 +1,23 @@
 // This file has been generated by Specta. DO NOT EDIT.
 
-export type AppSettings = { trash: TrashSettings; monitoring: MonitoringSettings; notifications: NotificationSettings; scan: ScanSettings; theme: string; scheduling?: SchedulingSettings | undefined }
+export type AntivirusReport = { available: boolean; scanned: number; detections: MalwareDetection[]; errors: string[] }
+
+export type AppNotification = { id: number; kind: NotificationKind; title: string; body: string; read: boolean; created_at: number }
+
+export type AppSettings = { trash: TrashSettings; monitoring: MonitoringSettings; notifications: NotificationSettings; scan: ScanSettings; theme: string; scheduling?: SchedulingSettings | undefined; shortcuts: ShortcutSettings; journal: JournalSettings; projects: ProjectSettings; memory: FootprintSettings; safe_mode: SafeModeSettings; read_only: boolean }
+
+export type ArchivedFile = { id: number; name: string; hash: string; size: number; original_path: string; location: string; archive: string; archived_at: number }
+
+export type BackendHealth = { degraded: boolean; uptime_secs: number; issues: HealthIssue[]; recoveries: number; last_recovery: string | undefined }
+
+export type BatchReport = { path: string; name: string | undefined; dry_run: boolean; started_at: number; duration_ms: number; steps: BatchStepReport[]; total_found: number; total_freed: number; total_would_free: number; failed_steps: number; stopped_early: boolean; confirmation_token?: string | undefined }
+
+export type BatchStepReport = { index: number; action: string; ok: boolean; dry_run: boolean; item_count: number; total_size: number; message: string; warnings: string[] }
 
 export type BatteryInfo = { percentage: number; is_charging: boolean; time_to_full: number | undefined; time_to_empty: number | undefined; power_consumption: number | undefined }
 
+export type BootWindow = { boots: number; avg_total_ms: number | undefined; avg_userspace_ms: number | undefined }
+
 export type CacheAnalytics = { total_cache_size: number; cache_breakdown: CacheContributor[]; growth_trend: CacheGrowthPoint[]; recommended_limits: Partial<{ [key in string]: number }> }
 
 export type CacheContributor = { source: string; size: number; growth_rate: number; last_activity: number; recommended_limit: number | undefined }
@@ -12,71 +26,186 @@ export type CacheEvent = { id: number; path: string; size_change: number; event_
 
 export type CacheGrowthPoint = { timestamp: number; total_size: number; sources: Partial<{ [key in string]: number }> }
 
-export type CacheItem = { name: string; size: number; category: string; can_clear: boolean }
+export type CacheItem = { name: string; size: number; category: string; can_clear: boolean; data_size?: number | undefined }
 
-export type CleanResult = { cleaned: number; failed: number; total_size: number }
+export type CleanResult = { cleaned: number; failed: number; total_size: number; pending_release: PendingRelease[] }
 
 export type CleanupPreview = { cache_items: PreviewItem[]; log_items: PreviewItem[]; filesystem_items: PreviewItem[]; storage_items: PreviewItem[]; total_size: number; total_items: number }
 
+export type CommandMetrics = { command: string; calls: number; failures: number; avg_ms: number; p95_ms: number; max_ms: number; avg_request_bytes: number; avg_response_bytes: number; last_called_at: number }
+
+
 export type DiskPulseHealth = { disk_usage_percent: number; projected_days_until_full: number | undefined; status_color: string; status_message: string }
 
-export type DuplicateGroup = { id: string; files: ScanItem[]; total_size: number; group_size: number }
+export type DockerArtifact = "dangling_images" | "stopped_containers" | "unused_volumes" | "build_cache"
+
+export type DuplicateGroup = { id: string; files: ScanItem[]; total_size: number; group_size: number; verified: boolean; suggested_keeper: string | undefined; preselected: string[] }
+
+export type DuplicateRoot = { path: string; max_files: number | undefined }
+
+export type ExistingSnapshot = { id: string; tool: string; filesystem: string; mount_point: string; path: string; created_at: number; exclusive_bytes: number | undefined; description: string; created_by_pulito: boolean }
 
 export type FailedCategory = { category: string; error: string }
 
-export type FilesystemHealthResults = { empty_directories: ScanItem[]; broken_symlinks: ScanItem[]; orphaned_temp_files: ScanItem[]; total_size: number; total_items: number }
+export type FilesystemHealthResults = { empty_directories: ScanItem[]; broken_symlinks: ScanItem[]; orphaned_temp_files: ScanItem[]; broken_launchers: ScanItem[]; junk_files: ScanItem[]; total_size: number; total_items: number; cancelled: boolean }
+
+export type FootprintSettings = { limit_mb: number }
 
 export type GpuInfo = { name: string; usage: number; memory_used: number; memory_total: number; temperature: number | undefined }
 
+export type HashMode = "sampled" | "full"
+
+export type HealthIssue = { subsystem: string; detail: string; blocked_secs: number }
+
+export type HeldFile = { path: string; size: number }
+
+export type IgnoreRule = { id: number; pattern: string; category: string | undefined; created_at: number; expires_at: number | undefined; note: string | undefined }
+
+export type ItemDecision = "ignored" | "selected"
+
+export type ItemDecisionRecord = { item_id: string; path: string; decision: ItemDecision; decided_at: number }
+
+export type JobRecord = { id: number; kind: string; state: string; priority: number; attempts: number; last_error: string | undefined; created_at: number; finished_at: number | undefined }
+
+export type JournalSettings = { max_size_mb: number; max_age_days: number }
+
+export type JsonExport = { kind: ResultKind; schema_version: number; content: string; written_to: string | undefined }
+
+export type KeepStrategy = "newest" | "oldest" | "shortest_path" | "first_listed"
+
+export type LibraryFile = { path: string; size: number }
+
 export type LoadAverage = { one_minute: number; five_minutes: number; fifteen_minutes: number }
 
+export type LocalizedTimestamp = { timestamp: number; utc: string; local: string; display: string }
+
+export type MalwareDetection = { path: string; signature: string }
+
+export type ManagedLibrary = { app: string; root: string; file_count: number; total_size: number; largest_files: LibraryFile[] }
+
+export type MemoryFootprint = { rss_bytes: number; peak_rss_bytes: number; limit_bytes: number; under_pressure: boolean; last_relief_at: number | undefined; history: MemorySample[] }
+
+export type MemorySample = { timestamp: number; rss_bytes: number }
+
 export type MonitoringSettings = { enabled: boolean; interval_hours: number }
 
+export type MountStats = { mount_point: string; device: string; file_system: string; total_space: number; used_space: number; available_space: number; is_removable: boolean }
+
 export type NetworkConnection = { local_address: string; remote_address: string; local_port: number; remote_port: number; state: string; process_name: string | undefined; process_pid: number | undefined }
 
 export type NetworkInterfaceInfo = { name: string; received: number; transmitted: number; packets_received: number; packets_transmitted: number; errors_received: number; errors_transmitted: number }
 
-export type NotificationSettings = { system: boolean; tray: boolean; in_app: boolean }
+export type NotificationKind = "low_disk" | "scan_complete" | "trash_expiring" | "cleanup_complete"
+
+export type NotificationSettings = { system: boolean; tray: boolean; in_app: boolean; suppress_during_dnd: boolean }
+
+export type OldFilesSummary = { total_files: number; total_size: number; cutoff_days: number; libraries: ManagedLibrary[] }
+
+export type PendingRelease = { pid: number; process_name: string; bytes: number; files: HeldFile[] }
 
-export type OldFilesSummary = { total_files: number; total_size: number; cutoff_days: number }
+export type PerformanceMetrics = { commands: CommandMetrics[]; sample_count: number; oldest_sample_at: number | undefined }
+
+export type PowerSource = "ac" | "battery"
 
 export type PreviewItem = { id: string; name: string; path: string; size: number; category: string; risk_level: number; description: string }
 
 export type ProcessInfo = { pid: number; name: string; cpu_usage: number; memory_usage: number; status: string; user_id: number | undefined }
 
+export type ProjectSettings = { roots: string[]; stale_days: number }
+
 export type QuickCleanResult = { cleaned: number; failed: number; total_size: number; categories: string[]; duration_ms: number }
 
-export type ScanItem = { id: string; name: string; path: string; size: number; type: string; category: string; risk_level: number; description: string; children?: ScanItem[] | undefined; dependencies?: string[] | undefined; dependents?: string[] | undefined }
+export type ReadOnlyStatus = { enabled: boolean; locked: boolean }
+
+export type ReclaimRecord = { timestamp: number; category: string; estimated_bytes: number; measured_bytes: number | undefined }
+
+export type ReclaimableSource = { source: string; size: number; overlap: number }
+
+export type ReclaimedSpace = { estimated_bytes: number; measured_bytes: number; measured_estimate_bytes: number; measured_cleanups: number; history: ReclaimRecord[] }
+
+export type ResultKind = "scan" | "storage_recovery" | "filesystem_health" | "system_stats" | "disk_health" | "cache_analytics" | "batch"
+
+export type SafeModeSettings = { enabled: boolean; min_retention_days: number }
+
+export type ScanItem = { id: string; name: string; path: string; size: number; apparent_size?: number | undefined; type: string; category: string; risk_level: number; description: string; children?: ScanItem[] | undefined; dependencies?: string[] | undefined; dependents?: string[] | undefined }
 
-export type ScanOptions = { include_caches: boolean; include_packages: boolean; include_large_files: boolean; include_logs: boolean; max_files?: number | undefined; max_depth?: number | undefined; max_memory_mb?: number | undefined }
+export type ScanOptions = { include_caches: boolean; include_packages: boolean; include_large_files: boolean; include_logs: boolean; max_files?: number | undefined; max_depth?: number | undefined; max_memory_mb?: number | undefined; categories?: string[] | undefined; scanner_options?: { [key in string]: { [key in string]: string } } | undefined; mount_point?: string | undefined; include_globs?: string[] | undefined; exclude_globs?: string[] | undefined; throughput_profile?: ThroughputProfile | undefined; similar_images?: boolean | undefined; one_file_system?: boolean | undefined; resume?: boolean | undefined }
 
-export type ScanResults = { items: ScanItem[]; total_size: number; total_items: number; scan_time_ms: number; timestamp: string; failed_categories?: FailedCategory[] }
+export type ScanResults = { items: ScanItem[]; total_size: number; total_items: number; scan_time_ms: number; timestamp: number; failed_categories?: FailedCategory[]; cancelled: boolean; shared_items: SharedItem[]; preselected: string[]; resumed_categories: string[] }
 
-export type ScanSettings = { include_hidden: boolean; large_file_threshold_mb: number }
+export type ScanSettings = { include_hidden: boolean; large_file_threshold_mb: number; antivirus_scan_downloads: boolean; duplicate_hash_mode: HashMode; duplicate_keep_strategy: KeepStrategy; duplicate_keep_dirs: string[]; exclude_globs: string[]; throttle: ThrottleSettings; one_file_system: boolean }
 
 export type ScheduleStatus = { enabled: boolean; next_run: number | undefined; last_run: number | undefined; status: string }
 
 export type SchedulingSettings = { enabled: boolean; frequency: string; time: string | undefined; day_of_week: number | undefined; last_run: number | undefined; next_run: number | undefined }
 
-export type StartupProgram = { id: string; name: string; description: string; enabled: boolean; location: string; file_path: string; impact: string; exec_command: string | undefined }
+export type SettingsProfile = { name: string; updated_at: number; active: boolean }
+
+export type SharedItem = { item_id: string; path: string; category: string; also_in: string[] }
+
+export type ShortcutAction = "quick_clean" | "toggle_window"
+
+export type ShortcutSettings = { enabled: boolean; quick_clean: string | undefined; toggle_window: string | undefined }
+
+export type ShortcutStatus = { action: ShortcutAction; accelerator: string; registered: boolean; conflict: string | undefined }
+
+export type SimilarImageGroup = { id: string; files: ScanItem[]; similarity: number; recoverable_size: number }
+
+export type StartupImpact = { change_id: number; program_id: string; program_name: string; unit: string | undefined; enabled: boolean; changed_at: number; before: BootWindow; after: BootWindow; units: UnitImpact[] }
+
+export type StartupProgram = { id: string; name: string; description: string; enabled: boolean; location: string; file_path: string; impact: string; exec_command: string | undefined; suspicion_reasons: string[] }
 
 export type StartupProgramsList = { programs: StartupProgram[]; total_count: number; enabled_count: number }
 
-export type StorageRecoveryResults = { duplicates: DuplicateGroup[]; large_files: ScanItem[]; old_downloads: ScanItem[]; total_duplicate_size: number; total_large_files_size: number; total_old_downloads_size: number; total_recoverable_size: number }
+export type StorageRecoveryResults = { duplicates: DuplicateGroup[]; large_files: ScanItem[]; old_downloads: ScanItem[]; total_duplicate_size: number; total_large_files_size: number; total_old_downloads_size: number; old_snap_revisions: ScanItem[]; total_snap_revisions_size: number; similar_images: SimilarImageGroup[]; stale_snapshots: ScanItem[]; total_stale_snapshots_size: number; snapshot_held_size: number; total_recoverable_size: number; cancelled: boolean }
+
+export type SupportBundle = { path: string; files: string[]; size: number }
+
+export type SupportBundleFile = { name: string; description: string; size: number; content: string }
+
+export type SupportBundlePreview = { id: string; files: SupportBundleFile[]; redactions: string[] }
+
+export type SuspiciousProcess = { pid: number; name: string; exe: string | undefined; cmdline: string; cpu_usage: number; run_time_secs: number; reasons: string[]; startup_entry: string | undefined }
 
 export type SystemHealthData = { cpu_usage: number; cpu_cores: number; cpu_frequency: number; core_usages: number[]; total_memory: number; used_memory: number; available_memory: number; gpu_info: GpuInfo | undefined; network_up: number; network_down: number; network_interfaces: NetworkInterfaceInfo[]; active_connections: NetworkConnection[]; temperatures: Temperatures; disk_read_bytes: number; disk_write_bytes: number; disk_read_ops: number; disk_write_ops: number; battery_info: BatteryInfo | undefined; top_processes: ProcessInfo[]; load_average: LoadAverage | undefined; swap_total: number; swap_used: number; timestamp: number }
 
-export type SystemStats = { total_disk_space: number; used_disk_space: number; cleanable_space: number; last_scan: string | undefined; packages_installed: number; orphan_packages: number; cache_size: number; filesystem_health_savings: number | undefined; storage_recovery_savings: number | undefined; orphan_packages_size: number | undefined }
+export type SystemStats = { total_disk_space: number; used_disk_space: number; cleanable_space: number; last_scan: number | undefined; packages_installed: number; orphan_packages: number; cache_size: number; filesystem_health_savings: number | undefined; storage_recovery_savings: number | undefined; orphan_packages_size: number | undefined; mounts: MountStats[]; reclaimable: ReclaimableSource[] }
 
 export type Temperatures = { cpu: number; cpu_sensors: number; system: number; gpu: number | undefined }
 
-export type TrashData = { items: TrashItem[]; total_size: number; total_items: number }
+export type ThrottleSettings = { on_ac: ThroughputProfile; on_battery: ThroughputProfile }
+
+export type ThroughputProfile = "performance" | "balanced" | "battery_saver" | "background"
+
+export type ThroughputStatus = { power_source: PowerSource; profile: ThroughputProfile }
+
+export type TimeContext = { timezone: string; utc_offset_seconds: number; locale: string }
+
+export type TrashCategoryStats = { category: string; count: number; total_size: number; nearest_expiry: number | undefined }
+
+export type TrashCompactionReport = { ran_at: number; compressed: number; deduplicated: number; bytes_saved: number; verified: number; corrupted: string[]; orphans_pruned: number; orphan_bytes: number; duration_ms: number }
 
-export type TrashItem = { id: string; original_path: string; trash_path: string; deleted_at: string; expires_at: string; size: number; item_type: string; metadata: TrashMetadata | undefined }
+export type TrashData = { items: TrashItem[]; total_size: number; total_items: number; by_category: TrashCategoryStats[]; by_risk_level: TrashRiskStats[] }
+
+export type TrashExpiryDigest = { items: TrashItem[]; total_size: number; window_hours: number }
+
+export type TrashItem = { id: string; original_path: string; trash_path: string; deleted_at: number; expires_at: number; size: number; item_type: string; metadata: TrashMetadata | undefined; pinned: boolean; payload: TrashPayload | undefined }
 
 export type TrashMetadata = { category: string; risk_level: number; reason: string }
 
+export type TrashPayload = { checksum: string | undefined; compressed: boolean; stored_size: number; linked_files: string[] }
+
+export type TrashRiskStats = { risk_level: number; count: number; total_size: number; nearest_expiry: number | undefined }
+
 export type TrashSettings = { retention_days: number; max_size_mb: number }
 
+export type TreeOverlap = { larger: string; smaller: string; larger_size: number; smaller_size: number; overlap_size: number; overlap_percent: number; matched_files: number; name_date_matches: number }
+
 export type TreeNode = { id: string; name: string; path: string; size: number; isDirectory: boolean; lastModified: number; lastAccessed: number; children?: TreeNode[] | undefined; expanded: boolean; selected: boolean; riskLevel: string; usagePattern?: string | undefined }
 
+export type UnitImpact = { unit: string; before_ms: number | undefined; after_ms: number | undefined }
+
+export type UsageNode = { name: string; path: string; size: number; file_count: number; is_directory: boolean; aggregate: boolean; children: UsageNode[] }
+
+export type WindowState = { x: number | undefined; y: number | undefined; width: number | undefined; height: number | undefined; maximized: boolean; last_view: string | undefined }
+
@@ -2,6 +2,12 @@
 
 export type View = 'dashboard' | 'cleanup' | 'treeview' | 'filesystem-health' | 'trash' | 'settings' | 'startup' | 'system-health' | 'disk-pulse' | 'storage-recovery';
 
+const views: readonly string[] = ['dashboard', 'cleanup', 'treeview', 'filesystem-health', 'trash', 'settings', 'startup', 'system-health', 'disk-pulse', 'storage-recovery'];
+
+export function isView(value: string | undefined): value is View {
+	return value !== undefined && views.includes(value);
+}
+
 let currentView = $state<View>('dashboard');
 
 // Use $derived to ensure reactivity
@@ -2,9 +2,12 @@
 
 // Import only types used within this store
 import type {
+	ItemDecision,
 	ScanItem,
 	ScanResults,
 } from '$lib/generated/types';
+import { invoke } from '$lib/utils/tauri';
+import { logger } from '$lib/utils/logger';
 
 // State
 let scanResults = $state<ScanResults | null>(null);
@@ -12,6 +15,22 @@ let isScanning = $state(false);
 let scanProgress = $state(0);
 let selectedItemIds = $state<Set<string>>(new Set());
 
+// Item ids are stable across scans, so decisions are remembered by the backend
+function saveDecision(item: ScanItem, decision: ItemDecision | null) {
+	invoke('set_item_decision', { itemId: item.id, path: item.path, decision }, 5000).catch((e) => {
+		logger.error('Failed to save item decision', { component: 'scanner' }, e);
+	});
+}
+
+function findItem(items: ScanItem[], id: string): ScanItem | undefined {
+	for (const item of items) {
+		if (item.id === id) return item;
+		const child = item.children ? findItem(item.children, id) : undefined;
+		if (child) return child;
+	}
+	return undefined;
+}
+
 // Computed values
 const selectedItems = $derived(() => {
 	if (!scanResults) return [];
@@ -77,6 +96,11 @@ export const scanner = {
 
 	setResults(results: ScanResults | null) {
 		scanResults = results;
+		// Items selected last time they were listed start out selected
+		const ids = (results?.preselected ?? []).filter((id) => !selectedItemIds.has(id));
+		if (ids.length > 0) {
+			selectedItemIds = new Set([...selectedItemIds, ...ids]);
+		}
 	},
 
 	setScanning(value: boolean) {
@@ -89,12 +113,37 @@ export const scanner = {
 
 	toggleSelection(id: string) {
 		const newSet = new Set(selectedItemIds);
-		if (newSet.has(id)) {
-			newSet.delete(id);
-		} else {
+		const selected = !newSet.has(id);
+		if (selected) {
 			newSet.add(id);
+		} else {
+			newSet.delete(id);
 		}
 		selectedItemIds = newSet;
+		const item = scanResults ? findItem(scanResults.items, id) : undefined;
+		if (item) {
+			saveDecision(item, selected ? 'selected' : null);
+		}
+	},
+
+	// Leave an item out of this and later scans
+	ignore(item: ScanItem) {
+		saveDecision(item, 'ignored');
+		if (!scanResults) return;
+		const without = (items: ScanItem[]): ScanItem[] =>
+			items
+				.filter((i) => i.id !== item.id)
+				.map((i) => (i.children ? { ...i, children: without(i.children) } : i));
+		const items = without(scanResults.items);
+		scanResults = {
+			...scanResults,
+			items,
+			total_size: items.reduce((sum, i) => sum + i.size, 0),
+			total_items: items.reduce((sum, i) => sum + 1 + (i.children?.length ?? 0), 0)
+		};
+		const newSet = new Set(selectedItemIds);
+		newSet.delete(item.id);
+		selectedItemIds = newSet;
 	},
 
 	selectAll() {
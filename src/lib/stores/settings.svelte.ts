@@ -7,6 +7,9 @@ import type {
 	MonitoringSettings,
 	NotificationSettings,
 	ScanSettings,
+	ShortcutSettings,
+	JournalSettings,
+	ProjectSettings,
 } from '$lib/generated/types';
 
 // Re-export types for convenience
@@ -16,6 +19,9 @@ export type {
 	MonitoringSettings,
 	NotificationSettings,
 	ScanSettings,
+	ShortcutSettings,
+	JournalSettings,
+	ProjectSettings,
 };
 
 const defaultSettings: AppSettings = {
@@ -30,13 +36,45 @@ const defaultSettings: AppSettings = {
 	notifications: {
 		system: true,
 		tray: true,
-		in_app: true
+		in_app: true,
+		suppress_during_dnd: false
 	},
 	scan: {
 		include_hidden: false,
-		large_file_threshold_mb: 100
+		large_file_threshold_mb: 100,
+		antivirus_scan_downloads: false,
+		duplicate_hash_mode: 'sampled',
+		duplicate_keep_strategy: 'newest',
+		duplicate_keep_dirs: [],
+		exclude_globs: [],
+		throttle: {
+			on_ac: 'balanced',
+			on_battery: 'battery_saver'
+		},
+		one_file_system: true
 	},
-	theme: 'system'
+	theme: 'system',
+	shortcuts: {
+		enabled: false,
+		quick_clean: 'Super+Shift+C',
+		toggle_window: 'Super+Shift+P'
+	},
+	journal: {
+		max_size_mb: 500,
+		max_age_days: 30
+	},
+	projects: {
+		roots: ['~/Projects', '~/projects', '~/src', '~/code', '~/dev', '~/workspace'],
+		stale_days: 90
+	},
+	memory: {
+		limit_mb: 400
+	},
+	safe_mode: {
+		enabled: false,
+		min_retention_days: 14
+	},
+	read_only: false
 };
 
 let currentSettings = $state<AppSettings>({ ...defaultSettings });
@@ -78,6 +116,41 @@ export const settings = {
 		};
 	},
 
+	updateShortcuts(partial: Partial<AppSettings['shortcuts']>) {
+		currentSettings = {
+			...currentSettings,
+			shortcuts: { ...currentSettings.shortcuts, ...partial }
+		};
+	},
+
+	updateJournal(partial: Partial<AppSettings['journal']>) {
+		currentSettings = {
+			...currentSettings,
+			journal: { ...currentSettings.journal, ...partial }
+		};
+	},
+
+	updateProjects(partial: Partial<AppSettings['projects']>) {
+		currentSettings = {
+			...currentSettings,
+			projects: { ...currentSettings.projects, ...partial }
+		};
+	},
+
+	updateMemory(partial: Partial<AppSettings['memory']>) {
+		currentSettings = {
+			...currentSettings,
+			memory: { ...currentSettings.memory, ...partial }
+		};
+	},
+
+	updateSafeMode(partial: Partial<AppSettings['safe_mode']>) {
+		currentSettings = {
+			...currentSettings,
+			safe_mode: { ...currentSettings.safe_mode, ...partial }
+		};
+	},
+
 	updateScheduling(partial: Partial<AppSettings['scheduling']>) {
 		// Load current scheduling or create new with all required fields
 		const current = currentSettings.scheduling ?? {
@@ -1,5 +1,7 @@
 import { confirmation } from '../stores/confirmation.svelte';
 import { logger } from './logger';
+import { invokeConfirmed, formatBytes } from './tauri';
+import type { PendingRelease } from '$lib/generated/types';
 
 interface ConfirmationOptions {
 	title: string;
@@ -43,3 +45,24 @@ export async function confirmAction(options: ConfirmationOptions): Promise<boole
 		return false;
 	}
 }
+
+// Deleted files a process still holds open free their space only when it closes them;
+// offer to stop each holder after a cleanup that deleted files directly
+export async function offerToStopHolders(holders: PendingRelease[]): Promise<void> {
+	for (const holder of holders) {
+		const files = holder.files.map(f => f.path).slice(0, 3).join(', ');
+		const stop = await confirmAction({
+			title: 'Space Pending Release',
+			message: `${formatBytes(holder.bytes)} is still held by ${holder.process_name} (${holder.pid}), which has ${files}${holder.files.length > 3 ? ' and more' : ''} open. Stop it to release the space?`,
+			confirmText: `Stop ${holder.process_name}`,
+			cancelText: 'Leave Running',
+			type: 'warning'
+		});
+		if (!stop) continue;
+		try {
+			await invokeConfirmed('terminate_holder', { pid: holder.pid });
+		} catch (error) {
+			logger.error('Failed to stop process holding deleted files', { operation: 'terminate_holder', pid: holder.pid }, error);
+		}
+	}
+}
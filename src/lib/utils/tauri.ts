@@ -1,6 +1,7 @@
 // Tauri utility functions
 
 import { invoke as tauriInvoke } from '@tauri-apps/api/core';
+import { confirmation } from '$lib/stores/confirmation.svelte';
 
 /**
  * Enhanced IPC Manager with queuing, batching, and retry mechanisms
@@ -431,6 +432,69 @@ export function isTauri(): boolean {
 	}
 }
 
+/**
+ * Structured confirmation data returned by the `describe_operation` command
+ */
+export interface OperationDescription {
+	operation: string;
+	title: string;
+	message: string;
+	dialog_type: 'info' | 'warning' | 'danger';
+	item_count: number;
+	total_size: number;
+	highest_risk_level: number;
+	irreversible: boolean;
+	requires_privileges: boolean;
+	affected_apps: string[];
+	warnings: string[];
+	confirmation_token: string;
+	acknowledgement_required: string[];
+}
+
+/**
+ * Invoke a destructive command. The backend only runs these with a fresh
+ * confirmation token from `describe_operation` issued for the same arguments.
+ * Not retried: the token is single-use.
+ */
+export async function invokeConfirmed<T = unknown>(
+	cmd: string,
+	args: Record<string, unknown> = {},
+	timeout?: number
+): Promise<T> {
+	// The plan mirrors the command arguments in snake_case; item IDs aren't part of it
+	const plan: Record<string, unknown> = { kind: cmd };
+	for (const [key, value] of Object.entries(args)) {
+		if (key === 'itemIds' || key === 'item_ids') continue;
+		plan[key.replace(/[A-Z]/g, c => `_${c.toLowerCase()}`)] = value;
+	}
+
+	const description = await invoke<OperationDescription>('describe_operation', { plan }, 60000);
+
+	// Safe mode: every risky item is acknowledged on its own
+	const acknowledgements: string[] = [];
+	for (const path of description.acknowledgement_required ?? []) {
+		const acknowledged = await confirmation.show({
+			title: 'Remove This Item?',
+			message: `${path} may hold personal files or app data. It will be moved to trash.`,
+			confirmText: 'Move to Trash',
+			cancelText: 'Keep Everything',
+			type: 'warning'
+		});
+		if (!acknowledged) throw new Error('Cleanup cancelled');
+		acknowledgements.push(await invoke<string>('acknowledge_risky_item', { path }));
+	}
+
+	return invoke<T>(
+		cmd,
+		{
+			...args,
+			confirmationToken: description.confirmation_token,
+			...(acknowledgements.length > 0 ? { acknowledgements } : {})
+		},
+		{ timeout, retry: false }
+	);
+}
+
 /**
  * Direct access to IPC manager for advanced usage
  */